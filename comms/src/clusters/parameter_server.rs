@@ -1,15 +1,33 @@
-use std::io;
+use std::{
+    io,
+    time::{Duration, Instant},
+};
 
 use futures::future;
 
 use crate::{ParamServerHandle, TransportLayer};
 
 /// A helper struct to manage a cluster of parameter servers.
+///
+/// The requests to every server are issued concurrently, in the order set with
+/// `set_issue_order` or the order the servers were added in by default. The results
+/// are always returned in the order the servers were added in.
 pub struct ParamServerCluster<T>
 where
     T: TransportLayer,
 {
-    server_handles: Vec<ParamServerHandle<T>>,
+    servers: Vec<ClusterServer<T>>,
+    ranks: Vec<usize>,
+}
+
+/// A server of the cluster along with the timing of it's latest exchange.
+struct ClusterServer<T>
+where
+    T: TransportLayer,
+{
+    handle: ParamServerHandle<T>,
+    issued_at: Option<Instant>,
+    rtt: Option<Duration>,
 }
 
 impl<T> Default for ParamServerCluster<T>
@@ -18,7 +36,8 @@ where
 {
     fn default() -> Self {
         Self {
-            server_handles: Default::default(),
+            servers: Default::default(),
+            ranks: Default::default(),
         }
     }
 }
@@ -40,7 +59,49 @@ where
     /// # Args
     /// * `server_handle` - The handler for communicating with a new server.
     pub fn spawn(&mut self, server_handle: ParamServerHandle<T>) {
-        self.server_handles.push(server_handle);
+        self.servers.push(ClusterServer {
+            handle: server_handle,
+            issued_at: None,
+            rtt: None,
+        });
+    }
+
+    /// Sets the order the requests to the servers are issued in.
+    ///
+    /// # Args
+    /// * `order` - The indices of the servers, the first one is issued first.
+    pub fn set_issue_order(&mut self, order: &[usize]) {
+        self.ranks = vec![0; self.servers.len()];
+
+        for (rank, &i) in order.iter().enumerate() {
+            if let Some(r) = self.ranks.get_mut(i) {
+                *r = rank;
+            }
+        }
+    }
+
+    /// The round trip time of the latest exchange with every server, from pushing the
+    /// gradient to receiving the parameters updated with it.
+    ///
+    /// # Returns
+    /// The round trip time of every server, `None` if it wasn't measured yet.
+    pub fn rtts(&self) -> Vec<Option<Duration>> {
+        self.servers.iter().map(|server| server.rtt).collect()
+    }
+
+    /// The amount of bytes exchanged with all the servers.
+    ///
+    /// # Returns
+    /// The total amount of sent and received bytes.
+    pub fn bytes_transferred(&self) -> (u64, u64) {
+        self.servers
+            .iter()
+            .fold((0, 0), |(sent, received), server| {
+                (
+                    sent + server.handle.bytes_sent(),
+                    received + server.handle.bytes_received(),
+                )
+            })
     }
 
     /// Pulls the new parameters from all the servers.
@@ -48,12 +109,17 @@ where
     /// # Returns
     /// The parameters from all the servers or io errors if occurred.
     pub async fn pull_params(&mut self) -> Vec<io::Result<&mut [f32]>> {
-        let futs = self
-            .server_handles
-            .iter_mut()
-            .map(async |server_handle| server_handle.pull_params().await);
+        let futs = issue_order(&mut self.servers, &self.ranks).map(async |(i, server)| {
+            let res = server.handle.pull_params().await;
+
+            if let Some(issued_at) = server.issued_at.take() {
+                server.rtt = Some(issued_at.elapsed());
+            }
+
+            (i, res)
+        });
 
-        future::join_all(futs).await
+        server_order(future::join_all(futs).await)
     }
 
     /// Pushes the latest gradients to the servers.
@@ -61,13 +127,12 @@ where
     /// # Returns
     /// The thresholds for cleaning the residual vecs or io errors if occurred.
     pub async fn push_grads(&mut self, residuals: &[Vec<f32>]) -> Vec<io::Result<Option<f32>>> {
-        let futs = self
-            .server_handles
-            .iter_mut()
-            .zip(residuals)
-            .map(async |(server_handle, residual)| server_handle.push_grad(residual).await);
+        let futs = issue_order(&mut self.servers, &self.ranks).map(async |(i, server)| {
+            server.issued_at = Some(Instant::now());
+            (i, server.handle.push_grad(&residuals[i]).await)
+        });
 
-        future::join_all(futs).await
+        server_order(future::join_all(futs).await)
     }
 
     /// Waits till receiving a message and discards it.
@@ -76,9 +141,9 @@ where
     /// An io error if occurred.
     pub async fn discard_one(&mut self) -> io::Result<()> {
         let futs = self
-            .server_handles
+            .servers
             .iter_mut()
-            .map(async |server_handle| server_handle.discard_one().await);
+            .map(async |server| server.handle.discard_one().await);
 
         future::join_all(futs).await;
         Ok(())
@@ -90,11 +155,47 @@ where
     /// An io error if occurred.
     pub async fn disconnect(&mut self) -> io::Result<()> {
         let futs = self
-            .server_handles
+            .servers
             .iter_mut()
-            .map(async |server_handle| server_handle.disconnect().await);
+            .map(async |server| server.handle.disconnect().await);
 
         future::try_join_all(futs).await?;
         Ok(())
     }
 }
+
+/// Sorts the servers in the order their requests are issued in.
+///
+/// # Args
+/// * `servers` - The servers of the cluster.
+/// * `ranks` - The position of every server in the issue order, empty to keep theirs.
+///
+/// # Returns
+/// An iterator over the servers in issue order along with their index.
+fn issue_order<'a, T>(
+    servers: &'a mut [ClusterServer<T>],
+    ranks: &[usize],
+) -> impl Iterator<Item = (usize, &'a mut ClusterServer<T>)>
+where
+    T: TransportLayer,
+{
+    let mut servers: Vec<_> = servers.iter_mut().enumerate().collect();
+
+    if !ranks.is_empty() {
+        servers.sort_by_key(|&(i, _)| ranks.get(i).copied().unwrap_or(i));
+    }
+
+    servers.into_iter()
+}
+
+/// Sorts the results of the servers back into the order they were added in.
+///
+/// # Args
+/// * `results` - The results in issue order along with the index of their server.
+///
+/// # Returns
+/// The results in server order.
+fn server_order<R>(mut results: Vec<(usize, R)>) -> Vec<R> {
+    results.sort_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, res)| res).collect()
+}
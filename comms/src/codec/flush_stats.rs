@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+/// The time a peer took to drain the frames sent to it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlushStats {
+    /// The amount of timed sends.
+    pub sends: usize,
+    /// The amount of sends that took longer than the threshold.
+    pub slow_sends: usize,
+    /// The amount of consecutive slow sends up to the last one.
+    pub slow_streak: usize,
+    /// The duration of the last send.
+    pub last: Duration,
+    /// The duration of the slowest send.
+    pub max: Duration,
+    /// The accumulated duration of every send.
+    pub total: Duration,
+}
+
+impl FlushStats {
+    /// Records the duration of a send.
+    ///
+    /// # Args
+    /// * `elapsed` - The time it took to write and flush the frame.
+    /// * `threshold` - The duration over which a send is considered slow.
+    pub(super) fn record(&mut self, elapsed: Duration, threshold: Duration) {
+        self.sends += 1;
+        self.last = elapsed;
+        self.max = self.max.max(elapsed);
+        self.total += elapsed;
+
+        if elapsed > threshold {
+            self.slow_sends += 1;
+            self.slow_streak += 1;
+        } else {
+            self.slow_streak = 0;
+        }
+    }
+
+    /// The mean duration of the sends.
+    ///
+    /// # Returns
+    /// The mean send duration or zero if nothing was sent.
+    pub fn mean(&self) -> Duration {
+        match self.sends {
+            0 => Duration::ZERO,
+            n => self.total / n as u32,
+        }
+    }
+}
@@ -1,6 +1,8 @@
+mod flush_stats;
 mod sink;
 mod source;
 
+pub use flush_stats::FlushStats;
 pub use sink::Sink;
 pub use source::Source;
 
@@ -1,15 +1,27 @@
-use std::io::{self, IoSlice};
+use std::{
+    io::{self, IoSlice},
+    num::NonZeroUsize,
+    time::{Duration, Instant},
+};
 
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 
-use super::{LEN_TYPE_SIZE, LenType};
-use crate::{protocol::Msg, utils};
+use super::{FlushStats, LEN_TYPE_SIZE, LenType};
+use crate::{
+    protocol::{CHUNK_FLAG, HEADER_SIZE, Header, LAST_CHUNK_FLAG, Msg},
+    utils,
+};
 
 /// The sending end handle of the communication.
 #[derive(Debug, Clone)]
 pub struct Sink<W: AsyncWrite + Unpin> {
     writer: W,
     buf: Vec<u8>,
+    max_frame_size: Option<usize>,
+    chunk_size: Option<NonZeroUsize>,
+    slow_send_threshold: Option<Duration>,
+    flush_stats: FlushStats,
+    bytes_sent: u64,
 }
 
 impl<W: AsyncWrite + Unpin> Sink<W> {
@@ -24,24 +36,110 @@ impl<W: AsyncWrite + Unpin> Sink<W> {
         Self {
             writer,
             buf: Vec::new(),
+            max_frame_size: None,
+            chunk_size: None,
+            slow_send_threshold: None,
+            flush_stats: FlushStats::default(),
+            bytes_sent: 0,
         }
     }
 
+    /// Bounds the size of the sent frames.
+    ///
+    /// # Args
+    /// * `max_frame_size` - The maximum size in bytes of a frame, `None` to lift the bound.
+    pub fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.max_frame_size = max_frame_size;
+    }
+
+    /// Splits the payloads that don't fit in a single frame of `chunk_size` bytes
+    /// into several chunked frames.
+    ///
+    /// # Args
+    /// * `chunk_size` - The maximum size in bytes of a frame, `None` to never split payloads.
+    pub fn set_chunk_size(&mut self, chunk_size: Option<NonZeroUsize>) {
+        self.chunk_size = chunk_size;
+    }
+
+    /// Times every send, counting the ones that take longer than `threshold` as slow.
+    ///
+    /// # Args
+    /// * `threshold` - The duration over which a send is slow, `None` to stop timing the sends.
+    pub fn set_slow_send_threshold(&mut self, threshold: Option<Duration>) {
+        self.slow_send_threshold = threshold;
+        self.flush_stats = FlushStats::default();
+    }
+
+    /// The time the peer took to drain the timed sends.
+    ///
+    /// # Returns
+    /// The statistics of the sends since the threshold was set.
+    pub fn flush_stats(&self) -> FlushStats {
+        self.flush_stats
+    }
+
+    /// The amount of bytes written so far, counting the length prefixes.
+    ///
+    /// # Returns
+    /// The total amount of sent bytes.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
     /// Writes the msg prefixed by the payload's length.
     ///
+    /// If a slow send threshold is set, the time until the frame is flushed is recorded.
+    ///
     /// # Args
     /// * `msg` - The message to serialize and send.
     ///
     /// # Returns
     /// An io error if occurred.
     pub async fn send<'a>(&mut self, msg: &Msg<'a>) -> io::Result<()> {
-        let Self { writer, buf } = self;
+        let Some(threshold) = self.slow_send_threshold else {
+            self.bytes_sent += self.write(msg).await? as u64;
+            return Ok(());
+        };
+
+        let start = Instant::now();
+        self.bytes_sent += self.write(msg).await? as u64;
+        self.flush_stats.record(start.elapsed(), threshold);
+        Ok(())
+    }
+
+    /// Serializes and writes the msg, flushing the writer afterwards.
+    ///
+    /// # Args
+    /// * `msg` - The message to serialize and send.
+    ///
+    /// # Returns
+    /// The amount of written bytes or an io error if occurred.
+    async fn write<'a>(&mut self, msg: &Msg<'a>) -> io::Result<usize> {
+        let Self {
+            writer,
+            buf,
+            max_frame_size,
+            chunk_size,
+            ..
+        } = self;
 
         buf.clear();
         buf.resize(LEN_TYPE_SIZE, 0);
 
         let zero_copy_data = msg.serialize(buf);
         let len = buf.len() - LEN_TYPE_SIZE + zero_copy_data.map(<[_]>::len).unwrap_or_default();
+
+        if let Some(data) = zero_copy_data
+            && !data.is_empty()
+            && let Some(chunk_size) = *chunk_size
+            && len > chunk_size.get()
+        {
+            let kind_buf = buf[LEN_TYPE_SIZE..].try_into().unwrap();
+            let kind = Header::from_be_bytes(kind_buf);
+            return send_chunked(writer, kind, data, chunk_size.get(), *max_frame_size).await;
+        }
+
+        check_frame_size(len, *max_frame_size)?;
         let header = (len as LenType).to_be_bytes();
 
         buf[..header.len()].copy_from_slice(&header);
@@ -54,6 +152,76 @@ impl<W: AsyncWrite + Unpin> Sink<W> {
             None => writer.write_all(buf).await?,
         }
 
-        writer.flush().await
+        writer.flush().await?;
+        Ok(LEN_TYPE_SIZE + len)
     }
 }
+
+/// Checks that a frame of `len` bytes fits in the size bound.
+///
+/// # Args
+/// * `len` - The size in bytes of the frame.
+/// * `max_frame_size` - The maximum size in bytes of a frame, if any.
+///
+/// # Errors
+/// An io error with `ErrorKind::InvalidInput` if the frame is too big.
+fn check_frame_size(len: usize, max_frame_size: Option<usize>) -> io::Result<()> {
+    if let Some(max) = max_frame_size
+        && len > max
+    {
+        let text = format!(
+            "Tried to send a frame of {len} bytes, over the maximum of {max} bytes, raise the max_frame_size to send it"
+        );
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, text));
+    }
+
+    Ok(())
+}
+
+/// Writes the payload as a sequence of frames of at most `chunk_size` bytes each.
+///
+/// Every frame carries the payload's kind header with the `CHUNK_FLAG` set, the
+/// last one also has the `LAST_CHUNK_FLAG` set.
+///
+/// # Args
+/// * `writer` - The underlying writer.
+/// * `kind` - The header of the payload.
+/// * `data` - The serialized payload.
+/// * `chunk_size` - The maximum size in bytes of each frame.
+/// * `max_frame_size` - The maximum size in bytes of a frame, if any.
+///
+/// # Returns
+/// The amount of written bytes or an io error if occurred.
+async fn send_chunked<W>(
+    writer: &mut W,
+    kind: Header,
+    data: &[u8],
+    chunk_size: usize,
+    max_frame_size: Option<usize>,
+) -> io::Result<usize>
+where
+    W: AsyncWrite + Unpin,
+{
+    let data_size = chunk_size.saturating_sub(HEADER_SIZE).max(1);
+    let nchunks = data.len().div_ceil(data_size);
+
+    for (i, chunk) in data.chunks(data_size).enumerate() {
+        let len = HEADER_SIZE + chunk.len();
+        check_frame_size(len, max_frame_size)?;
+
+        let mut header = kind | CHUNK_FLAG;
+        if i + 1 == nchunks {
+            header |= LAST_CHUNK_FLAG;
+        }
+
+        let mut prefix = [0; LEN_TYPE_SIZE + HEADER_SIZE];
+        prefix[..LEN_TYPE_SIZE].copy_from_slice(&(len as LenType).to_be_bytes());
+        prefix[LEN_TYPE_SIZE..].copy_from_slice(&header.to_be_bytes());
+
+        let bufs = [IoSlice::new(&prefix), IoSlice::new(chunk)];
+        utils::write_all_vectored(writer, bufs).await?;
+    }
+
+    writer.flush().await?;
+    Ok(nchunks * (LEN_TYPE_SIZE + HEADER_SIZE) + data.len())
+}
@@ -3,13 +3,15 @@ use std::io;
 use tokio::io::{AsyncRead, AsyncReadExt};
 
 use super::{LEN_TYPE_SIZE, LenType};
-use crate::protocol::Msg;
+use crate::protocol::{CHUNK_FLAG, HEADER_SIZE, Header, LAST_CHUNK_FLAG, Msg};
 
 /// The receiving end handle of the communication.
 #[derive(Debug, Clone)]
 pub struct Source<R: AsyncRead + Unpin> {
     reader: R,
     buf: Vec<u32>,
+    max_frame_size: Option<usize>,
+    bytes_received: u64,
 }
 
 impl<R: AsyncRead + Unpin> Source<R> {
@@ -24,35 +26,167 @@ impl<R: AsyncRead + Unpin> Source<R> {
         Self {
             reader,
             buf: Vec::new(),
+            max_frame_size: None,
+            bytes_received: 0,
         }
     }
 
+    /// Bounds the size of the received frames.
+    ///
+    /// # Args
+    /// * `max_frame_size` - The maximum size in bytes of a frame, `None` to lift the bound.
+    pub fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.max_frame_size = max_frame_size;
+    }
+
+    /// The amount of bytes read so far, counting the length prefixes.
+    ///
+    /// # Returns
+    /// The total amount of received bytes.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
     /// Waits to receive a new message from the inner reader.
     ///
+    /// Chunked payloads are reassembled into a single message before being returned.
+    ///
     /// # Returns
     /// A result object that returns `T` on success or `io::Error` on failure.
     pub async fn recv<'a>(&'a mut self) -> io::Result<Msg<'a>> {
-        let Self { reader, buf } = self;
+        let Self {
+            reader,
+            buf,
+            max_frame_size,
+            bytes_received,
+        } = self;
 
-        let mut size_buf = [0; LEN_TYPE_SIZE];
-        reader.read_exact(&mut size_buf).await?;
-        let len = LenType::from_be_bytes(size_buf) as usize;
+        let mut len = read_len(reader, *max_frame_size).await?;
+        let slice = resize(buf, len);
+        reader.read_exact(slice).await?;
+        *bytes_received += (LEN_TYPE_SIZE + len) as u64;
 
-        let b_size = size_of::<u32>();
-        let needed_amount = len.div_ceil(b_size);
+        if len >= HEADER_SIZE {
+            let header = Header::from_be_bytes(slice[..HEADER_SIZE].try_into().unwrap());
 
-        if buf.capacity() < needed_amount {
-            buf.reserve(needed_amount - buf.len());
+            if header & CHUNK_FLAG != 0 {
+                len = reassemble(reader, buf, header, len, *max_frame_size, bytes_received).await?;
+            }
         }
 
-        // SAFETY: The buffer has capacity for at least the amount of items. These
-        //         will be immediatelly overwritten in the read_exact call.
-        unsafe { buf.set_len(needed_amount) };
-
         let view = bytemuck::cast_slice_mut(buf);
-        let slice = &mut view[..len];
-        reader.read_exact(slice).await?;
+        Msg::deserialize(&mut view[..len])
+    }
+}
+
+/// Reads the length prefix of the next frame.
+///
+/// The length is checked against the bound before the frame's body is read, so an
+/// oversized frame fails right away instead of being buffered and then misread.
+///
+/// # Args
+/// * `reader` - The underlying reader.
+/// * `max_frame_size` - The maximum size in bytes of a frame, if any.
+///
+/// # Returns
+/// The size in bytes of the frame or an io error if occurred.
+async fn read_len<R>(reader: &mut R, max_frame_size: Option<usize>) -> io::Result<usize>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut size_buf = [0; LEN_TYPE_SIZE];
+    reader.read_exact(&mut size_buf).await?;
+    let len = LenType::from_be_bytes(size_buf) as usize;
+
+    if let Some(max) = max_frame_size
+        && len > max
+    {
+        let text = format!(
+            "Received a frame of {len} bytes, over the maximum of {max} bytes, raise the max_frame_size to accept it"
+        );
+        return Err(io::Error::new(io::ErrorKind::InvalidData, text));
+    }
+
+    Ok(len)
+}
 
-        Msg::deserialize(slice)
+/// Resizes the buffer to hold `len` bytes, keeping it's current contents.
+///
+/// # Args
+/// * `buf` - The buffer to resize.
+/// * `len` - The amount of bytes the buffer should hold.
+///
+/// # Returns
+/// The first `len` bytes of the buffer.
+fn resize(buf: &mut Vec<u32>, len: usize) -> &mut [u8] {
+    let b_size = size_of::<u32>();
+    let needed_amount = len.div_ceil(b_size);
+
+    if buf.capacity() < needed_amount {
+        buf.reserve(needed_amount - buf.len());
     }
+
+    // SAFETY: The buffer has capacity for at least the amount of items. The new
+    //         items will be immediatelly overwritten in the read_exact calls.
+    unsafe { buf.set_len(needed_amount) };
+
+    let view = bytemuck::cast_slice_mut(buf);
+    &mut view[..len]
+}
+
+/// Reads the remaining chunks of a payload, appending them to the first one.
+///
+/// The flags are cleared from the first chunk's header, leaving a regular
+/// message in the buffer.
+///
+/// # Args
+/// * `reader` - The underlying reader.
+/// * `buf` - The buffer holding the first chunk.
+/// * `header` - The header of the first chunk.
+/// * `len` - The size in bytes of the first chunk.
+/// * `max_frame_size` - The maximum size in bytes of a frame, if any.
+/// * `bytes_received` - The counter of received bytes.
+///
+/// # Returns
+/// The size in bytes of the reassembled message or an io error if occurred.
+async fn reassemble<R>(
+    reader: &mut R,
+    buf: &mut Vec<u32>,
+    mut header: Header,
+    mut len: usize,
+    max_frame_size: Option<usize>,
+    bytes_received: &mut u64,
+) -> io::Result<usize>
+where
+    R: AsyncRead + Unpin,
+{
+    let flags = CHUNK_FLAG | LAST_CHUNK_FLAG;
+    let kind = header & !flags;
+    resize(buf, len)[..HEADER_SIZE].copy_from_slice(&kind.to_be_bytes());
+
+    while header & LAST_CHUNK_FLAG == 0 {
+        let frame_len = read_len(reader, max_frame_size).await?;
+
+        if frame_len < HEADER_SIZE {
+            let text = format!("Received a chunk of {frame_len} bytes, it has no header");
+            return Err(io::Error::new(io::ErrorKind::InvalidData, text));
+        }
+
+        let mut header_buf = [0; HEADER_SIZE];
+        reader.read_exact(&mut header_buf).await?;
+        header = Header::from_be_bytes(header_buf);
+
+        if header & CHUNK_FLAG == 0 || header & !flags != kind {
+            let text = format!("Expected a chunk of kind {kind}, got the header {header:#x}");
+            return Err(io::Error::new(io::ErrorKind::InvalidData, text));
+        }
+
+        let chunk_len = frame_len - HEADER_SIZE;
+        let view = resize(buf, len + chunk_len);
+        reader.read_exact(&mut view[len..]).await?;
+        *bytes_received += (LEN_TYPE_SIZE + frame_len) as u64;
+        len += chunk_len;
+    }
+
+    Ok(len)
 }
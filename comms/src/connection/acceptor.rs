@@ -2,10 +2,10 @@ use std::{io, marker::PhantomData};
 
 use uuid::Uuid;
 
-use super::Connection;
+use super::{Connection, auth};
 use crate::{
     NodeHandle, OrchHandle, ParamServerHandle, WorkerHandle,
-    protocol::{Command, Entity, Msg},
+    protocol::{Capabilities, Command, Entity, Msg},
     transport::TransportLayer,
 };
 
@@ -16,6 +16,9 @@ where
     F: AsyncFn() -> io::Result<T>,
 {
     id: Uuid,
+    port: Option<u16>,
+    auth_token: Option<Vec<u8>>,
+    capabilities: Capabilities,
     transport_factory: F,
     _phantom: PhantomData<T>,
 }
@@ -36,11 +39,52 @@ where
     pub fn new(id: Uuid, transport_factory: F) -> Self {
         Self {
             id,
+            port: None,
+            auth_token: None,
+            capabilities: Capabilities::SUPPORTED,
             transport_factory,
             _phantom: Default::default(),
         }
     }
 
+    /// Sets the port this node is listening on, reported to the peers on every accepted
+    /// connection so they can address this node even if it was bound to an ephemeral port.
+    ///
+    /// # Args
+    /// * `port` - The listening port.
+    ///
+    /// # Returns
+    /// The modified `Acceptor`.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Sets the pre-shared token every incoming connection must prove to know before
+    /// being accepted.
+    ///
+    /// # Args
+    /// * `token` - The pre-shared token.
+    ///
+    /// # Returns
+    /// The modified `Acceptor`.
+    pub fn with_auth_token(mut self, token: impl Into<Vec<u8>>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Sets the gradient compression modes advertised to the peers, every supported one by default.
+    ///
+    /// # Args
+    /// * `capabilities` - The advertised compression modes.
+    ///
+    /// # Returns
+    /// The modified `Acceptor`.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
     /// Blocks the current thread until a new connection arrives.
     ///
     /// # Args
@@ -48,28 +92,78 @@ where
     ///
     /// # Returns
     /// A new connection or an io error if occurred while waiting for incoming connections
-    /// or receiving the type of entity from the peer.
+    /// or receiving the type of entity from the peer. If the peer failed to authenticate
+    /// the error is of kind `PermissionDenied` and the connection is closed.
     pub async fn accept(&mut self, src: Entity) -> io::Result<Connection<T>> {
         let mut transport_layer = (self.transport_factory)().await?;
 
         let msg = transport_layer.recv().await?;
-        let Msg::Control(Command::Connect { id, src: dst }) = msg else {
+        let Msg::Control(Command::Connect {
+            id,
+            src: dst,
+            capabilities,
+            samples,
+        }) = msg
+        else {
             let text = format!("Expected Connect message, got: {msg:?}");
             return Err(io::Error::other(text));
         };
 
-        let msg = Msg::Control(Command::Accept { id: self.id, src });
+        if let Some(token) = &self.auth_token {
+            Self::authenticate(&mut transport_layer, token, id).await?;
+        }
+
+        let msg = Msg::Control(Command::Accept {
+            id: self.id,
+            src,
+            port: self.port,
+            capabilities: self.capabilities,
+        });
         transport_layer.send(&msg).await?;
 
+        let capabilities = self.capabilities.intersection(capabilities);
+
         let conn = match dst {
-            Entity::Worker => Connection::Worker(WorkerHandle::new(id, transport_layer)),
-            Entity::ParamServer => {
-                Connection::ParamServer(ParamServerHandle::new(id, transport_layer))
-            }
+            Entity::Worker => Connection::Worker(
+                WorkerHandle::new(id, transport_layer)
+                    .with_capabilities(capabilities)
+                    .with_samples(samples),
+            ),
+            Entity::ParamServer => Connection::ParamServer(
+                ParamServerHandle::new(id, transport_layer).with_capabilities(capabilities),
+            ),
             Entity::Orchestrator => Connection::Orch(OrchHandle::new(id, transport_layer)),
             Entity::Node => Connection::Node(NodeHandle::new(id, transport_layer)),
         };
 
         Ok(conn)
     }
+
+    /// Challenges the peer to prove it knows the pre-shared token.
+    ///
+    /// # Args
+    /// * `transport_layer` - The transport layer of the communication.
+    /// * `token` - The pre-shared token.
+    /// * `id` - The peer's id.
+    ///
+    /// # Returns
+    /// An io error of kind `PermissionDenied` if the peer failed to authenticate.
+    async fn authenticate(transport_layer: &mut T, token: &[u8], id: Uuid) -> io::Result<()> {
+        let nonce = auth::nonce();
+        let msg = Msg::Control(Command::AuthChallenge {
+            nonce: nonce.clone(),
+        });
+
+        transport_layer.send(&msg).await?;
+
+        let text = match transport_layer.recv().await? {
+            Msg::Control(Command::Auth { mac }) if auth::verify(token, &nonce, &mac) => {
+                return Ok(());
+            }
+            Msg::Control(Command::Auth { .. }) => format!("Peer {id} sent an invalid auth token"),
+            msg => format!("Expected Auth message from peer {id}, got: {msg:?}"),
+        };
+
+        Err(io::Error::new(io::ErrorKind::PermissionDenied, text))
+    }
 }
@@ -0,0 +1,46 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The size in bytes of the challenge nonce.
+pub const NONCE_SIZE: usize = 32;
+
+/// Generates a new random challenge nonce.
+///
+/// # Returns
+/// The nonce bytes.
+pub fn nonce() -> Vec<u8> {
+    rand::random::<[u8; NONCE_SIZE]>().to_vec()
+}
+
+/// Signs a challenge nonce with the shared token so the token itself never travels in clear.
+///
+/// # Args
+/// * `token` - The pre-shared token.
+/// * `nonce` - The challenge nonce sent by the accepting side.
+///
+/// # Returns
+/// The HMAC-SHA256 of the nonce keyed by the token.
+pub fn sign(token: &[u8], nonce: &[u8]) -> Vec<u8> {
+    // SAFETY: HMAC accepts keys of any length.
+    let mut mac = HmacSha256::new_from_slice(token).unwrap();
+    mac.update(nonce);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Verifies in constant time that `tag` is the signature of `nonce` with the shared token.
+///
+/// # Args
+/// * `token` - The pre-shared token.
+/// * `nonce` - The challenge nonce sent to the connecting side.
+/// * `tag` - The signature received from the connecting side.
+///
+/// # Returns
+/// `true` if the signature matches.
+pub fn verify(token: &[u8], nonce: &[u8], tag: &[u8]) -> bool {
+    // SAFETY: HMAC accepts keys of any length.
+    let mut mac = HmacSha256::new_from_slice(token).unwrap();
+    mac.update(nonce);
+    mac.verify_slice(tag).is_ok()
+}
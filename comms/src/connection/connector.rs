@@ -3,10 +3,11 @@ use std::{io, marker::PhantomData};
 use tokio::io::{AsyncRead, AsyncWrite};
 use uuid::Uuid;
 
+use super::auth;
 use crate::{
     Connection, WorkerHandle,
     handles::{NodeHandle, OrchHandle, ParamServerHandle},
-    protocol::{Command, Entity, Msg},
+    protocol::{Capabilities, Command, Entity, Msg},
     transport::TransportLayer,
 };
 
@@ -20,6 +21,9 @@ where
     F: Fn(R, W) -> T,
 {
     id: Uuid,
+    auth_token: Option<Vec<u8>>,
+    capabilities: Capabilities,
+    samples: Option<u64>,
     transport_factory: F,
     _phantom: PhantomData<(R, W, T)>,
 }
@@ -34,6 +38,9 @@ where
     fn clone(&self) -> Self {
         Self {
             id: self.id,
+            auth_token: self.auth_token.clone(),
+            capabilities: self.capabilities,
+            samples: self.samples,
             transport_factory: self.transport_factory.clone(),
             _phantom: self._phantom,
         }
@@ -58,11 +65,47 @@ where
     pub fn new(id: Uuid, transport_factory: F) -> Self {
         Self {
             id,
+            auth_token: None,
+            capabilities: Capabilities::SUPPORTED,
+            samples: None,
             transport_factory,
             _phantom: Default::default(),
         }
     }
 
+    /// Sets the pre-shared token used to answer the peers' authentication challenges.
+    ///
+    /// # Args
+    /// * `token` - The pre-shared token.
+    ///
+    /// # Returns
+    /// The modified `Connector`.
+    pub fn with_auth_token(mut self, token: impl Into<Vec<u8>>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Sets the gradient compression modes advertised to the peers, every supported one by default.
+    ///
+    /// # Args
+    /// * `capabilities` - The advertised compression modes.
+    ///
+    /// # Returns
+    /// The modified `Connector`.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Sets the amount of samples reported to the peers, so the servers can weigh this
+    /// worker's gradients by them. Nothing is reported by default.
+    ///
+    /// # Args
+    /// * `samples` - The amount of samples this node trains on.
+    pub fn set_samples(&mut self, samples: Option<u64>) {
+        self.samples = samples;
+    }
+
     /// Connects to an uninitialised node and returns a handle to bootstrap it.
     ///
     /// The caller assigns the node's role by calling `NodeHandle::create_server` or
@@ -174,22 +217,51 @@ where
     /// A new `TransportLayer` or an io error if occurred.
     async fn connect(&self, rx: R, tx: W, src: Entity) -> io::Result<Connection<T>> {
         let mut transport_layer = (self.transport_factory)(rx, tx);
-        let msg = Msg::Control(Command::Connect { id: self.id, src });
+        let msg = Msg::Control(Command::Connect {
+            id: self.id,
+            src,
+            capabilities: self.capabilities,
+            samples: self.samples,
+        });
         transport_layer.send(&msg).await?;
 
-        let msg = transport_layer.recv().await?;
-        let Msg::Control(Command::Accept { id, src: dst }) = msg else {
+        let mut msg = transport_layer.recv().await?;
+
+        if let Msg::Control(Command::AuthChallenge { nonce }) = msg {
+            let Some(token) = &self.auth_token else {
+                let text = "The peer requires authentication but no auth token was set";
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, text));
+            };
+
+            let mac = auth::sign(token, &nonce);
+            transport_layer
+                .send(&Msg::Control(Command::Auth { mac }))
+                .await?;
+            msg = transport_layer.recv().await?;
+        }
+
+        let Msg::Control(Command::Accept {
+            id,
+            src: dst,
+            port,
+            capabilities,
+        }) = msg
+        else {
             let details = format!("Invalid connection message, expected Accept, got {msg:?}");
             return Err(io::Error::other(details));
         };
 
+        let capabilities = self.capabilities.intersection(capabilities);
+
         let conn = match dst {
-            Entity::Node => Connection::Node(NodeHandle::new(id, transport_layer)),
+            Entity::Node => Connection::Node(NodeHandle::new(id, transport_layer).with_port(port)),
             Entity::Orchestrator => Connection::Orch(OrchHandle::new(id, transport_layer)),
-            Entity::ParamServer => {
-                Connection::ParamServer(ParamServerHandle::new(id, transport_layer))
-            }
-            Entity::Worker => Connection::Worker(WorkerHandle::new(id, transport_layer)),
+            Entity::ParamServer => Connection::ParamServer(
+                ParamServerHandle::new(id, transport_layer).with_capabilities(capabilities),
+            ),
+            Entity::Worker => Connection::Worker(
+                WorkerHandle::new(id, transport_layer).with_capabilities(capabilities),
+            ),
         };
 
         Ok(conn)
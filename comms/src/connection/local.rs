@@ -0,0 +1,141 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use tokio::{
+    io::DuplexStream,
+    sync::{
+        Mutex as AsyncMutex,
+        mpsc::{self, UnboundedReceiver, UnboundedSender},
+    },
+};
+
+/// The prefix of the addresses of the listeners living inside this process.
+const LOCAL_PREFIX: &str = "local:";
+
+/// The amount of bytes buffered in each direction of an in-process connection.
+const LOCAL_BUF_SIZE: usize = 1 << 20;
+
+/// The next id to assign to a local listener.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The bound local listeners, keyed by their id.
+static LISTENERS: Mutex<BTreeMap<u64, UnboundedSender<DuplexStream>>> = Mutex::new(BTreeMap::new());
+
+/// A listener living inside this process, reachable only from it.
+///
+/// The peers connect to it through `connect` with it's address, getting one end of an
+/// in-memory duplex stream instead of a tcp stream while the listener gets the other.
+#[derive(Debug)]
+pub struct LocalListener {
+    id: u64,
+    stream_rx: AsyncMutex<UnboundedReceiver<DuplexStream>>,
+}
+
+impl LocalListener {
+    /// Binds a new local listener to an unique address.
+    ///
+    /// # Returns
+    /// A new `LocalListener` instance.
+    pub fn bind() -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let (stream_tx, stream_rx) = mpsc::unbounded_channel();
+
+        LISTENERS.lock().unwrap().insert(id, stream_tx);
+
+        Self {
+            id,
+            stream_rx: AsyncMutex::new(stream_rx),
+        }
+    }
+
+    /// The address peers connect to this listener with.
+    ///
+    /// # Returns
+    /// The listener's address.
+    pub fn local_addr(&self) -> String {
+        format!("{LOCAL_PREFIX}{}", self.id)
+    }
+
+    /// Waits for a new incoming connection.
+    ///
+    /// # Returns
+    /// The listener's end of the connection or an io error if the listener was unbound.
+    pub async fn accept(&self) -> io::Result<DuplexStream> {
+        self.stream_rx.lock().await.recv().await.ok_or_else(|| {
+            let text = format!("the local listener {} was unbound", self.local_addr());
+            io::Error::new(io::ErrorKind::ConnectionAborted, text)
+        })
+    }
+
+    /// Tells whether a local listener is bound to the given address.
+    ///
+    /// # Args
+    /// * `addr` - The address of the listener.
+    ///
+    /// # Returns
+    /// Whether the address is a bound local listener's.
+    pub fn is_bound(addr: &str) -> bool {
+        let Some(Ok(id)) = addr.strip_prefix(LOCAL_PREFIX).map(str::parse) else {
+            return false;
+        };
+
+        LISTENERS.lock().unwrap().contains_key(&id)
+    }
+
+    /// Unbinds the local listener at the given address, the connections it already queued
+    /// are still accepted and then accepting fails, the ones after are refused.
+    ///
+    /// # Args
+    /// * `addr` - The address of the listener.
+    pub fn unbind(addr: &str) {
+        if let Some(Ok(id)) = addr.strip_prefix(LOCAL_PREFIX).map(str::parse) {
+            LISTENERS.lock().unwrap().remove(&id);
+        }
+    }
+}
+
+impl Drop for LocalListener {
+    fn drop(&mut self) {
+        LISTENERS.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Connects to a local listener if the address is a local one.
+///
+/// # Args
+/// * `addr` - The address to connect to.
+///
+/// # Returns
+/// `None` if the address isn't a local one, otherwise the connected stream or an io error
+/// if no listener is bound to it.
+pub(super) fn connect_local(addr: &str) -> Option<io::Result<DuplexStream>> {
+    let id = addr.strip_prefix(LOCAL_PREFIX)?;
+
+    let refused = || {
+        let text = format!("no local listener is bound to {addr}");
+        io::Error::new(io::ErrorKind::ConnectionRefused, text)
+    };
+
+    let Ok(id) = id.parse() else {
+        return Some(Err(refused()));
+    };
+
+    let listeners = LISTENERS.lock().unwrap();
+    let Some(stream_tx) = listeners.get(&id) else {
+        return Some(Err(refused()));
+    };
+
+    let (stream, peer_stream) = tokio::io::duplex(LOCAL_BUF_SIZE);
+    Some(
+        stream_tx
+            .send(peer_stream)
+            .map(|_| stream)
+            .map_err(|_| refused()),
+    )
+}
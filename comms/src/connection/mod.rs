@@ -1,7 +1,20 @@
 mod acceptor;
+mod auth;
 mod connector;
+mod local;
+mod stream;
+mod tests;
 
-use std::fmt::{self, Display, Formatter};
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+    time::Duration,
+};
+
+use tokio::{
+    net::{self, TcpStream},
+    time,
+};
 
 use crate::{
     handles::{NodeHandle, OrchHandle, ParamServerHandle, WorkerHandle},
@@ -10,6 +23,8 @@ use crate::{
 
 pub use acceptor::Acceptor;
 pub use connector::Connector;
+pub use local::LocalListener;
+pub use stream::{NetListener, NetReadHalf, NetStream, NetWriteHalf};
 
 /// The different connection types.
 #[allow(clippy::large_enum_variant)]
@@ -38,3 +53,54 @@ where
         f.write_str(&s)
     }
 }
+
+/// Opens a stream to the given address.
+///
+/// The addresses of the local listeners connect to them in-process, any other address
+/// is dialed through the network as `connect_tcp` does.
+///
+/// # Args
+/// * `addr` - The address to connect to.
+/// * `attempt_timeout` - The maximum amount of time to wait for each resolved address.
+///
+/// # Returns
+/// The connected stream or an io error if it couldn't connect.
+pub async fn connect(addr: &str, attempt_timeout: Duration) -> io::Result<NetStream> {
+    if let Some(stream) = local::connect_local(addr) {
+        return stream.map(NetStream::Local);
+    }
+
+    connect_tcp(addr, attempt_timeout).await.map(NetStream::Tcp)
+}
+
+/// Opens a tcp stream to the given network address.
+///
+/// The address may be an ip literal, including IPv6 ones such as `[::1]:40000`, or a
+/// hostname. Every address the hostname resolves to is tried in order, each attempt
+/// bounded by `attempt_timeout`, until one of them connects.
+///
+/// # Args
+/// * `addr` - The network address to connect to.
+/// * `attempt_timeout` - The maximum amount of time to wait for each resolved address.
+///
+/// # Returns
+/// The connected stream or the last connection error if none of the addresses connected.
+pub async fn connect_tcp(addr: &str, attempt_timeout: Duration) -> io::Result<TcpStream> {
+    let mut last_err = None;
+
+    for sock_addr in net::lookup_host(addr).await? {
+        match time::timeout(attempt_timeout, TcpStream::connect(sock_addr)).await {
+            Ok(Ok(stream)) => return Ok(stream),
+            Ok(Err(e)) => last_err = Some(e),
+            Err(_) => {
+                let text = format!("connection attempt to {sock_addr} timed out");
+                last_err = Some(io::Error::new(io::ErrorKind::TimedOut, text));
+            }
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        let text = format!("{addr} didn't resolve to any address");
+        io::Error::new(io::ErrorKind::NotFound, text)
+    }))
+}
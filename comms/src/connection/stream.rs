@@ -0,0 +1,168 @@
+use std::{
+    io::{self, IoSlice},
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf, ReadHalf, WriteHalf},
+    net::{
+        TcpListener, TcpStream,
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+    },
+};
+
+use super::LocalListener;
+
+/// A connection to a peer, either through the network or to a listener in this process.
+#[derive(Debug)]
+pub enum NetStream {
+    Tcp(TcpStream),
+    Local(DuplexStream),
+}
+
+/// The reading end of a `NetStream`.
+#[derive(Debug)]
+pub enum NetReadHalf {
+    Tcp(OwnedReadHalf),
+    Local(ReadHalf<DuplexStream>),
+}
+
+/// The writing end of a `NetStream`.
+#[derive(Debug)]
+pub enum NetWriteHalf {
+    Tcp(OwnedWriteHalf),
+    Local(WriteHalf<DuplexStream>),
+}
+
+/// A listener accepting connections either from the network or from this process.
+#[derive(Debug)]
+pub enum NetListener {
+    Tcp(TcpListener),
+    Local(LocalListener),
+}
+
+impl NetStream {
+    /// The network address of the peer.
+    ///
+    /// # Returns
+    /// The peer's address, `None` if it lives in this process, or an io error if occurred.
+    pub fn peer_addr(&self) -> io::Result<Option<SocketAddr>> {
+        match self {
+            Self::Tcp(stream) => stream.peer_addr().map(Some),
+            Self::Local(_) => Ok(None),
+        }
+    }
+
+    /// Splits the stream into it's reading and writing ends.
+    ///
+    /// # Returns
+    /// The reading and writing ends of the stream.
+    pub fn into_split(self) -> (NetReadHalf, NetWriteHalf) {
+        match self {
+            Self::Tcp(stream) => {
+                let (rx, tx) = stream.into_split();
+                (NetReadHalf::Tcp(rx), NetWriteHalf::Tcp(tx))
+            }
+            Self::Local(stream) => {
+                let (rx, tx) = tokio::io::split(stream);
+                (NetReadHalf::Local(rx), NetWriteHalf::Local(tx))
+            }
+        }
+    }
+}
+
+impl NetListener {
+    /// The port the listener is bound to.
+    ///
+    /// # Returns
+    /// The port, `None` if it's a local listener, or an io error if occurred.
+    pub fn port(&self) -> io::Result<Option<u16>> {
+        match self {
+            Self::Tcp(listener) => listener.local_addr().map(|addr| Some(addr.port())),
+            Self::Local(_) => Ok(None),
+        }
+    }
+
+    /// Waits for a new incoming connection.
+    ///
+    /// # Returns
+    /// The accepted stream or an io error if occurred.
+    pub async fn accept(&self) -> io::Result<NetStream> {
+        match self {
+            Self::Tcp(listener) => listener.accept().await.map(|(s, _)| NetStream::Tcp(s)),
+            Self::Local(listener) => listener.accept().await.map(NetStream::Local),
+        }
+    }
+}
+
+impl From<TcpListener> for NetListener {
+    fn from(listener: TcpListener) -> Self {
+        Self::Tcp(listener)
+    }
+}
+
+impl From<LocalListener> for NetListener {
+    fn from(listener: LocalListener) -> Self {
+        Self::Local(listener)
+    }
+}
+
+impl AsyncRead for NetReadHalf {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(rx) => Pin::new(rx).poll_read(cx, buf),
+            Self::Local(rx) => Pin::new(rx).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for NetWriteHalf {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(tx) => Pin::new(tx).poll_write(cx, buf),
+            Self::Local(tx) => Pin::new(tx).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(tx) => Pin::new(tx).poll_write_vectored(cx, bufs),
+            Self::Local(tx) => Pin::new(tx).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Self::Tcp(tx) => tx.is_write_vectored(),
+            Self::Local(tx) => tx.is_write_vectored(),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(tx) => Pin::new(tx).poll_flush(cx),
+            Self::Local(tx) => Pin::new(tx).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(tx) => Pin::new(tx).poll_shutdown(cx),
+            Self::Local(tx) => Pin::new(tx).poll_shutdown(cx),
+        }
+    }
+}
@@ -0,0 +1,243 @@
+#![cfg(test)]
+
+use std::{sync::Mutex, time::Duration};
+
+use tokio::{
+    io::{self, AsyncReadExt, AsyncWriteExt, DuplexStream, ReadHalf, WriteHalf},
+    net::TcpListener,
+};
+use uuid::Uuid;
+
+use super::{Acceptor, Connection, Connector, LocalListener, NetListener, connect, connect_tcp};
+use crate::{
+    ParamServerHandle,
+    protocol::{Capabilities, Compression, Entity},
+    transport::{self, Stp},
+};
+
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(1);
+
+type DuplexStp = Stp<ReadHalf<DuplexStream>, WriteHalf<DuplexStream>>;
+
+/// Runs a handshake between a connector and an acceptor with the given auth tokens.
+async fn auth_handshake(
+    acceptor_token: Option<&str>,
+    connector_token: Option<&str>,
+) -> (
+    io::Result<Connection<DuplexStp>>,
+    io::Result<ParamServerHandle<DuplexStp>>,
+) {
+    let (acceptor_stream, connector_stream) = io::duplex(4096);
+    let acceptor_stream = Mutex::new(Some(acceptor_stream));
+
+    let transport_factory = async || {
+        let stream = acceptor_stream.lock().unwrap().take().unwrap();
+        let (rx, tx) = io::split(stream);
+        Ok(transport::build_simple_transport(rx, tx))
+    };
+
+    let mut acceptor = Acceptor::new(Uuid::new_v4(), transport_factory);
+    if let Some(token) = acceptor_token {
+        acceptor = acceptor.with_auth_token(token);
+    }
+
+    let mut connector = Connector::new(Uuid::new_v4(), transport::build_simple_transport);
+    if let Some(token) = connector_token {
+        connector = connector.with_auth_token(token);
+    }
+
+    let (rx, tx) = io::split(connector_stream);
+
+    tokio::join!(
+        acceptor.accept(Entity::ParamServer),
+        connector.connect_parameter_server(rx, tx, Entity::Worker)
+    )
+}
+
+#[tokio::test]
+async fn test_auth_accepts_matching_token() {
+    let (accepted, connected) = auth_handshake(Some("secret"), Some("secret")).await;
+
+    assert!(matches!(accepted, Ok(Connection::Worker(_))));
+    assert!(connected.is_ok());
+}
+
+#[tokio::test]
+async fn test_auth_rejects_mismatched_token() {
+    let (accepted, connected) = auth_handshake(Some("secret"), Some("guess")).await;
+
+    let Err(e) = accepted else {
+        panic!("the acceptor accepted an invalid token");
+    };
+
+    assert_eq!(e.kind(), io::ErrorKind::PermissionDenied);
+    assert!(connected.is_err());
+}
+
+#[tokio::test]
+async fn test_auth_rejects_missing_token() {
+    let (accepted, connected) = auth_handshake(Some("secret"), None).await;
+
+    let Err(e) = connected else {
+        panic!("the connector answered a challenge without a token");
+    };
+
+    assert_eq!(e.kind(), io::ErrorKind::PermissionDenied);
+    assert!(accepted.is_err());
+}
+
+/// Runs a handshake between a connector and an acceptor advertising the given capabilities.
+async fn capabilities_handshake(
+    acceptor_capabilities: Capabilities,
+    connector_capabilities: Capabilities,
+) -> (Capabilities, Capabilities) {
+    let (acceptor_stream, connector_stream) = io::duplex(4096);
+    let acceptor_stream = Mutex::new(Some(acceptor_stream));
+
+    let transport_factory = async || {
+        let stream = acceptor_stream.lock().unwrap().take().unwrap();
+        let (rx, tx) = io::split(stream);
+        Ok(transport::build_simple_transport(rx, tx))
+    };
+
+    let mut acceptor =
+        Acceptor::new(Uuid::new_v4(), transport_factory).with_capabilities(acceptor_capabilities);
+    let connector = Connector::new(Uuid::new_v4(), transport::build_simple_transport)
+        .with_capabilities(connector_capabilities);
+
+    let (rx, tx) = io::split(connector_stream);
+
+    let (accepted, connected) = tokio::join!(
+        acceptor.accept(Entity::ParamServer),
+        connector.connect_parameter_server(rx, tx, Entity::Worker)
+    );
+
+    let Ok(Connection::Worker(worker_handle)) = accepted else {
+        panic!("the acceptor didn't accept the worker");
+    };
+
+    (
+        worker_handle.capabilities(),
+        connected.unwrap().capabilities(),
+    )
+}
+
+#[tokio::test]
+async fn test_handshake_negotiates_the_common_compression() {
+    let supported = Capabilities::SUPPORTED;
+    let (accepted, connected) = capabilities_handshake(supported, supported).await;
+
+    assert_eq!(accepted, supported);
+    assert_eq!(connected, supported);
+
+    for compression in [Compression::Dense, Compression::Sparse, Compression::TopK] {
+        assert_eq!(connected.negotiate(compression, true).unwrap(), compression);
+    }
+}
+
+#[tokio::test]
+async fn test_handshake_falls_back_to_dense_with_an_older_peer() {
+    let (accepted, connected) =
+        capabilities_handshake(Capabilities::DENSE_ONLY, Capabilities::SUPPORTED).await;
+
+    assert_eq!(accepted, Capabilities::DENSE_ONLY);
+    assert_eq!(connected, Capabilities::DENSE_ONLY);
+
+    let negotiated = connected.negotiate(Compression::Sparse, false).unwrap();
+    assert_eq!(negotiated, Compression::Dense);
+
+    let Err(e) = connected.negotiate(Compression::TopK, true) else {
+        panic!("a required compression was silently downgraded");
+    };
+
+    assert_eq!(e.kind(), io::ErrorKind::Unsupported);
+}
+
+#[test]
+fn test_capabilities_ignore_unknown_modes() {
+    let capabilities: Capabilities = serde_json::from_str("4294967295").unwrap();
+    assert_eq!(
+        capabilities.intersection(Capabilities::SUPPORTED),
+        Capabilities::SUPPORTED
+    );
+}
+
+#[tokio::test]
+async fn test_connect_tcp_hostname_falls_back_between_families() -> io::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+
+    // `localhost` may resolve to `::1` before `127.0.0.1`, where nothing is listening.
+    let addr = format!("localhost:{port}");
+    let (stream, accepted) = tokio::join!(connect_tcp(&addr, ATTEMPT_TIMEOUT), listener.accept());
+
+    assert!(stream?.peer_addr()?.is_ipv4());
+    accepted?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_connect_tcp_ipv6_literal() -> io::Result<()> {
+    let Ok(listener) = TcpListener::bind("[::1]:0").await else {
+        // IPv6 isn't available in this environment.
+        return Ok(());
+    };
+
+    let port = listener.local_addr()?.port();
+
+    let addr = format!("[::1]:{port}");
+    let (stream, accepted) = tokio::join!(connect_tcp(&addr, ATTEMPT_TIMEOUT), listener.accept());
+
+    assert!(stream?.peer_addr()?.is_ipv6());
+    accepted?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_connect_tcp_unresolvable_address() {
+    assert!(
+        connect_tcp("not-a-host.invalid:40000", ATTEMPT_TIMEOUT)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_connect_reaches_a_local_listener_in_process() -> io::Result<()> {
+    let listener = NetListener::from(LocalListener::bind());
+    let NetListener::Local(ref local) = listener else {
+        unreachable!();
+    };
+
+    let addr = local.local_addr();
+    let (stream, accepted) = tokio::join!(connect(&addr, ATTEMPT_TIMEOUT), listener.accept());
+    let (stream, accepted) = (stream?, accepted?);
+
+    assert!(stream.peer_addr()?.is_none());
+
+    let (_, mut tx) = stream.into_split();
+    let (mut rx, _) = accepted.into_split();
+    tx.write_all(b"ping").await?;
+
+    let mut buf = [0; 4];
+    rx.read_exact(&mut buf).await?;
+    assert_eq!(&buf, b"ping");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_connect_to_an_unbound_local_listener_is_refused() {
+    let listener = LocalListener::bind();
+    let addr = listener.local_addr();
+    assert!(LocalListener::is_bound(&addr));
+
+    LocalListener::unbind(&addr);
+    assert!(!LocalListener::is_bound(&addr));
+
+    let Err(e) = connect(&addr, ATTEMPT_TIMEOUT).await else {
+        panic!("connecting to an unbound local listener should fail");
+    };
+
+    assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+    assert!(listener.accept().await.is_err());
+}
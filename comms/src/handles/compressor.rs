@@ -26,11 +26,20 @@ struct SparseCapability<R>
 where
     R: Rng,
 {
-    r: Float01,
-    rng: R,
+    selection: Selection<R>,
     ser_buf: Vec<u8>,
 }
 
+/// The strategy to select which values of the gradient are sent.
+#[derive(Debug)]
+enum Selection<R>
+where
+    R: Rng,
+{
+    Sampled { r: Float01, rng: R },
+    TopK { density: Float01 },
+}
+
 impl<R> Compressor<R>
 where
     R: Rng,
@@ -53,8 +62,20 @@ where
     /// * `rng` - A random number generator.
     pub fn enable_sparse_compression(&mut self, r: Float01, rng: R) {
         let sparse_capability = SparseCapability {
-            r,
-            rng,
+            selection: Selection::Sampled { r, rng },
+            ser_buf: Vec::new(),
+        };
+
+        self.sparse_capability = Some(sparse_capability);
+    }
+
+    /// Enables the top-k sparse gradient capability for this compressor.
+    ///
+    /// # Args
+    /// * `density` - The fraction of the largest-magnitude gradient values to send.
+    pub fn enable_top_k_compression(&mut self, density: Float01) {
+        let sparse_capability = SparseCapability {
+            selection: Selection::TopK { density },
             ser_buf: Vec::new(),
         };
 
@@ -73,7 +94,13 @@ where
             Some(cap) => {
                 cap.ser_buf.clear();
 
-                let threshold = sparse::calculate_threshold(residual, cap.r, &mut cap.rng);
+                let threshold = match &mut cap.selection {
+                    Selection::Sampled { r, rng } => sparse::calculate_threshold(residual, *r, rng),
+                    Selection::TopK { density } => {
+                        sparse::calculate_top_k_threshold(residual, *density)
+                    }
+                };
+
                 sparse::grad_drop_into(&mut cap.ser_buf, residual, threshold);
 
                 if cap.ser_buf.len() <= residual.len() * size_of::<f16>() {
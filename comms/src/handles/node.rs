@@ -15,9 +15,11 @@ use crate::{
 /// A handle to a node that has not yet been bootstrapped.
 ///
 /// Obtained from [`crate::Connector::connect_node`]. Consumed when the node is assigned
-/// its role via [`NodeHandle::create_server`] or [`NodeHandle::create_worker`].
+/// its role via [`NodeHandle::create_server`] or [`NodeHandle::create_worker`], the role's
+/// handle yields it back once the session is over.
 pub struct NodeHandle<T: TransportLayer> {
     id: Uuid,
+    port: Option<u16>,
     transport: T,
 }
 
@@ -38,7 +40,23 @@ impl<T: TransportLayer> NodeHandle<T> {
     /// # Returns
     /// A new `NodeHandle` instance.
     pub(crate) fn new(id: Uuid, transport: T) -> Self {
-        Self { id, transport }
+        Self {
+            id,
+            port: None,
+            transport,
+        }
+    }
+
+    /// Sets the port the node reported to be listening on.
+    ///
+    /// # Args
+    /// * `port` - The reported listening port, if any.
+    ///
+    /// # Returns
+    /// The modified `NodeHandle`.
+    pub(crate) fn with_port(mut self, port: Option<u16>) -> Self {
+        self.port = port;
+        self
     }
 
     /// The node's id.
@@ -49,6 +67,14 @@ impl<T: TransportLayer> NodeHandle<T> {
         self.id
     }
 
+    /// The port the node reported to be listening on during the connection handshake.
+    ///
+    /// # Returns
+    /// The node's listening port or `None` if it didn't report one.
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
     /// Bootstraps the node as a parameter server.
     ///
     /// # Args
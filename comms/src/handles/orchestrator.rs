@@ -4,12 +4,14 @@ use uuid::Uuid;
 
 use super::DatasetSrc;
 use crate::{
+    floats::FloatPositive,
     protocol::{Command, Msg, Payload},
     share_dataset,
     specs::{
         machine_learning::TrainerSpec,
         node::{NodeSpec, StatRequest, StatResponse},
-        server::ServerSpec,
+        server::{ServerSpec, StalenessStats},
+        worker::{MetricsReport, TimeBreakdown},
     },
     transport::TransportLayer,
 };
@@ -27,7 +29,17 @@ pub enum OrchEvent {
         spec: NodeSpec,
     },
     Disconnect,
+    Ping,
+    RequestEmaParams,
     RequestParams,
+    RequestSnapshot,
+    RequestStaleness,
+    SetEpoch {
+        epoch: usize,
+    },
+    SetLearningRate {
+        lr: FloatPositive,
+    },
     ShareDataset,
     StatsRequest {
         reqs: Vec<StatRequest>,
@@ -85,17 +97,31 @@ where
     ///
     /// # Args
     /// * `losses` - An array of loss values.
+    /// * `timing` - How the worker spent the round that produced them.
     ///
     /// # Returns
     /// An io error if occurred.
-    pub async fn push_losses(&mut self, losses: &[f64]) -> io::Result<()> {
+    pub async fn push_losses(&mut self, losses: &[f64], timing: TimeBreakdown) -> io::Result<()> {
         let msg = Msg::Control(Command::ReportLoss {
             losses: Cow::Borrowed(losses),
+            timing,
         });
 
         self.transport.send(&msg).await
     }
 
+    /// Pushes the metrics of the latest round to the orchestrator.
+    ///
+    /// # Args
+    /// * `report` - The round's metrics.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    pub async fn push_metrics(&mut self, report: MetricsReport) -> io::Result<()> {
+        let msg = Msg::Data(Payload::Metrics(report));
+        self.transport.send(&msg).await
+    }
+
     /// Pushes the given statistics onto the orchestrator.
     ///
     /// # Args
@@ -108,6 +134,18 @@ where
         self.transport.send(&msg).await
     }
 
+    /// Pushes the staleness of the gradients applied by this server onto the orchestrator.
+    ///
+    /// # Args
+    /// * `stats` - The staleness statistics.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    pub async fn push_staleness(&mut self, stats: StalenessStats) -> io::Result<()> {
+        let msg = Msg::Control(Command::StalenessResponse { stats });
+        self.transport.send(&msg).await
+    }
+
     /// Blocks until receiving an event from an orchestrator.
     ///
     /// # Returns
@@ -115,7 +153,13 @@ where
     pub async fn recv_event(&mut self) -> io::Result<OrchEvent> {
         let event = match self.transport.recv().await? {
             Msg::Control(Command::Disconnect) => OrchEvent::Disconnect,
+            Msg::Control(Command::Ping) => OrchEvent::Ping,
             Msg::Control(Command::RequestParams) => OrchEvent::RequestParams,
+            Msg::Control(Command::RequestEmaParams) => OrchEvent::RequestEmaParams,
+            Msg::Control(Command::RequestSnapshot) => OrchEvent::RequestSnapshot,
+            Msg::Control(Command::RequestStaleness) => OrchEvent::RequestStaleness,
+            Msg::Control(Command::SetEpoch { epoch }) => OrchEvent::SetEpoch { epoch },
+            Msg::Control(Command::SetLearningRate { lr }) => OrchEvent::SetLearningRate { lr },
             Msg::Control(Command::StopAfterEpoch) => OrchEvent::Stop,
             Msg::Control(Command::CreateNode { spec }) => OrchEvent::Create { spec },
             Msg::Control(Command::Upgrade { spec, ranges }) => OrchEvent::Upgrade { spec, ranges },
@@ -141,6 +185,15 @@ where
         Ok(event)
     }
 
+    /// Responds to a ping request by sending a pong response.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    pub async fn pong(&mut self) -> io::Result<()> {
+        let msg = Msg::Control(Command::Pong);
+        self.transport.send(&msg).await
+    }
+
     /// Tells the orchestrator that this worker has upgraded into a server succesfuly..
     ///
     /// # Returns
@@ -160,6 +213,21 @@ where
         self.transport.send(&msg).await
     }
 
+    /// Tells the orchestrator why this entity failed, sent right before disconnecting.
+    ///
+    /// # Args
+    /// * `message` - The reason of the failure.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    pub async fn report_error(&mut self, message: &str) -> io::Result<()> {
+        let msg = Msg::Control(Command::Error {
+            message: message.to_string(),
+        });
+
+        self.transport.send(&msg).await
+    }
+
     /// Disconnects the orchestrator.
     ///
     /// # Returns
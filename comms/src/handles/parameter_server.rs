@@ -5,10 +5,11 @@ use uuid::Uuid;
 
 use super::{CompressedGrad, Compressor};
 use crate::{
-    floats::Float01,
-    handles::DatasetSrc,
-    protocol::{Command, Msg, Payload},
-    share_dataset,
+    floats::{Float01, FloatPositive},
+    handles::{DatasetSrc, NodeHandle},
+    protocol::{Capabilities, Command, Msg, Payload},
+    quantize, share_dataset,
+    specs::server::StalenessStats,
     transport::TransportLayer,
 };
 
@@ -18,6 +19,9 @@ pub struct ParamServerHandle<T> {
     id: Uuid,
     transport: T,
     compressor: Compressor<StdRng>,
+    capabilities: Capabilities,
+    params: Vec<f32>,
+    nparams: Option<usize>,
 }
 
 impl<T> ParamServerHandle<T>
@@ -37,6 +41,9 @@ where
             id,
             transport,
             compressor: Compressor::new(),
+            capabilities: Capabilities::SUPPORTED,
+            params: Vec::new(),
+            nparams: None,
         }
     }
 
@@ -48,6 +55,26 @@ where
         self.id
     }
 
+    /// Sets the gradient compression modes both ends of the connection support.
+    ///
+    /// # Args
+    /// * `capabilities` - The negotiated compression modes.
+    ///
+    /// # Returns
+    /// The modified `ParamServerHandle`.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// The gradient compression modes both ends of the connection support.
+    ///
+    /// # Returns
+    /// The negotiated compression modes, every supported one if there was no handshake.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
     /// Enables the sparse gradient capability for this handle.
     ///
     /// # Args
@@ -65,18 +92,70 @@ where
         self.compressor.enable_sparse_compression(r, rng);
     }
 
+    /// Enables the top-k sparse gradient capability for this handle.
+    ///
+    /// # Args
+    /// * `density` - The fraction of the largest-magnitude gradient values to send.
+    pub fn enable_top_k_capability(&mut self, density: Float01) {
+        self.compressor.enable_top_k_compression(density);
+    }
+
+    /// Bounds the size of the frames exchanged with the server.
+    ///
+    /// # Args
+    /// * `max_frame_size` - The maximum size in bytes of a frame, `None` to lift the bound.
+    pub fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.transport.set_max_frame_size(max_frame_size);
+    }
+
+    /// The amount of bytes sent to the server.
+    ///
+    /// # Returns
+    /// The total amount of sent bytes.
+    pub fn bytes_sent(&self) -> u64 {
+        self.transport.bytes_sent()
+    }
+
+    /// The amount of bytes received from the server.
+    ///
+    /// # Returns
+    /// The total amount of received bytes.
+    pub fn bytes_received(&self) -> u64 {
+        self.transport.bytes_received()
+    }
+
+    /// Sets the amount of parameters expected in every pull.
+    ///
+    /// # Args
+    /// * `nparams` - The amount of parameters the server holds, `None` to accept any amount.
+    pub fn set_expected_params(&mut self, nparams: Option<usize>) {
+        self.nparams = nparams;
+    }
+
     /// Pulls the latest parameters from the server.
     ///
+    /// Quantized parameters are dequantized before being returned.
+    ///
     /// # Returns
     /// The parameters as a mutable slice or an io error if occurred.
     pub async fn pull_params(&mut self) -> io::Result<&mut [f32]> {
-        let msg = self.transport.recv().await?;
-        let Msg::Data(Payload::Params(params)) = msg else {
-            let text = format!("Expected params from server {}, got: {msg:?}", self.id);
-            return Err(io::Error::other(text));
-        };
+        match self.transport.recv().await? {
+            Msg::Data(Payload::Params(params)) => {
+                check_params_len(self.id, self.nparams, params.len())?;
+                Ok(params)
+            }
+            Msg::Data(Payload::QuantizedParams(quantized)) => {
+                quantize::params_dequantize_into(&mut self.params, quantized)
+                    .map_err(io::Error::other)?;
 
-        Ok(params)
+                check_params_len(self.id, self.nparams, self.params.len())?;
+                Ok(&mut self.params)
+            }
+            msg => {
+                let text = format!("Expected params from server {}, got: {msg:?}", self.id);
+                Err(io::Error::other(text))
+            }
+        }
     }
 
     /// Pushes the gradient to the server.
@@ -112,6 +191,81 @@ where
         self.transport.send(&msg).await
     }
 
+    /// Sends a request for a snapshot of the stored params in a server.
+    ///
+    /// Unlike `req_params`, the server answers it while the workers are still training,
+    /// so the parameters are the ones of that moment.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    pub async fn req_snapshot(&mut self) -> io::Result<()> {
+        let msg = Msg::Control(Command::RequestSnapshot);
+        self.transport.send(&msg).await
+    }
+
+    /// Sends a request for the moving average of the stored params in a server.
+    ///
+    /// Only servers keeping the average can answer it, once the training stage ended.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    pub async fn req_ema_params(&mut self) -> io::Result<()> {
+        let msg = Msg::Control(Command::RequestEmaParams);
+        self.transport.send(&msg).await
+    }
+
+    /// Sends a request for the staleness of the gradients applied by a server.
+    ///
+    /// Only servers tracking the staleness can answer it, once the training stage ended.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    pub async fn req_staleness(&mut self) -> io::Result<()> {
+        let msg = Msg::Control(Command::RequestStaleness);
+        self.transport.send(&msg).await
+    }
+
+    /// Waits for the staleness statistics of a server.
+    ///
+    /// # Returns
+    /// The staleness of the gradients the server applied or an io error if occurred.
+    pub async fn pull_staleness(&mut self) -> io::Result<StalenessStats> {
+        match self.transport.recv().await? {
+            Msg::Control(Command::StalenessResponse { stats }) => Ok(stats),
+            msg => {
+                let text = format!("Expected staleness from server {}, got: {msg:?}", self.id);
+                Err(io::Error::other(text))
+            }
+        }
+    }
+
+    /// Sends a new learning rate for the optimizers of a server.
+    ///
+    /// The server takes it between two updates of each of it's shards, while the training is
+    /// still running.
+    ///
+    /// # Args
+    /// * `lr` - The new learning rate.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    pub async fn set_learning_rate(&mut self, lr: FloatPositive) -> io::Result<()> {
+        let msg = Msg::Control(Command::SetLearningRate { lr });
+        self.transport.send(&msg).await
+    }
+
+    /// Sends the epoch the training is at, for the server to follow it's learning rate schedule.
+    ///
+    /// # Args
+    /// * `epoch` - The amount of epochs the workers ran so far.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    pub async fn set_epoch(&mut self, epoch: usize) -> io::Result<()> {
+        let msg = Msg::Control(Command::SetEpoch { epoch });
+        self.transport.send(&msg).await
+    }
+
     /// Waits for a message and discards it.
     ///
     /// # Returns
@@ -128,6 +282,15 @@ where
         let msg = Msg::Control(Command::Disconnect);
         self.transport.send(&msg).await
     }
+
+    /// Yields the handle of the node the server ran on once it's session is over, the
+    /// node is idle on the same transport layer waiting to be bootstrapped again.
+    ///
+    /// # Returns
+    /// A new `NodeHandle` instance.
+    pub fn into_node_handle(self) -> NodeHandle<T> {
+        NodeHandle::new(self.id, self.transport)
+    }
 }
 
 impl<T> DatasetSrc for ParamServerHandle<T>
@@ -147,3 +310,23 @@ where
         share_dataset::recv_dataset(xs, ys, &mut self.transport).await
     }
 }
+
+/// Checks that the server sent the expected amount of parameters.
+///
+/// # Args
+/// * `id` - The id of the server.
+/// * `expected` - The expected amount of parameters, if any.
+/// * `len` - The amount of parameters received.
+///
+/// # Errors
+/// An io error with `ErrorKind::InvalidData` if the amounts differ.
+fn check_params_len(id: Uuid, expected: Option<usize>, len: usize) -> io::Result<()> {
+    if let Some(expected) = expected
+        && len != expected
+    {
+        let text = format!("Expected {expected} params from server {id}, got {len}");
+        return Err(io::Error::new(io::ErrorKind::InvalidData, text));
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use std::io;
+use std::{io, num::NonZeroUsize, time::Duration};
 
 use rand::{SeedableRng, rngs::StdRng};
 use tokio::io::AsyncRead;
@@ -6,11 +6,15 @@ use uuid::Uuid;
 
 use super::{Compressor, compressor::CompressedGrad};
 use crate::{
-    ParamServerHandle,
+    FlushStats, NodeHandle, ParamServerHandle,
     floats::Float01,
-    protocol::{Command, Msg, Payload},
-    share_dataset, sparse,
-    specs::{machine_learning::TrainerSpec, server::ServerSpec},
+    protocol::{Capabilities, Command, Msg, Payload},
+    quantize, share_dataset, sparse,
+    specs::{
+        machine_learning::TrainerSpec,
+        server::ServerSpec,
+        worker::{MetricsReport, TimeBreakdown},
+    },
     transport::TransportLayer,
 };
 
@@ -20,16 +24,27 @@ pub struct WorkerHandle<T> {
     transport: T,
     grad: Vec<f32>,
     compressor: Compressor<StdRng>,
+    capabilities: Capabilities,
+    samples: Option<u64>,
+    quantization: Option<Quantization>,
+}
+
+/// The necessary metadata for enabling quantized parameter broadcasting.
+struct Quantization {
+    shard_size: NonZeroUsize,
+    ser_buf: Vec<u8>,
 }
 
 /// A notified worker event.
 #[derive(Debug)]
 pub enum WorkerEvent<'a> {
     Grad(&'a [f32]),
-    Loss(Vec<f64>),
+    Loss(Vec<f64>, TimeBreakdown),
+    Metrics(MetricsReport),
     RequestParams,
     Disconnect,
     Done,
+    Error(String),
     Upgraded,
 }
 
@@ -48,6 +63,9 @@ impl<T: TransportLayer> WorkerHandle<T> {
             transport,
             grad: Vec::new(),
             compressor: Compressor::new(),
+            capabilities: Capabilities::SUPPORTED,
+            samples: None,
+            quantization: None,
         }
     }
 
@@ -59,6 +77,46 @@ impl<T: TransportLayer> WorkerHandle<T> {
         self.id
     }
 
+    /// Sets the gradient compression modes both ends of the connection support.
+    ///
+    /// # Args
+    /// * `capabilities` - The negotiated compression modes.
+    ///
+    /// # Returns
+    /// The modified `WorkerHandle`.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// The gradient compression modes both ends of the connection support.
+    ///
+    /// # Returns
+    /// The negotiated compression modes, every supported one if there was no handshake.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Sets the amount of samples the worker trains on, as it reported them.
+    ///
+    /// # Args
+    /// * `samples` - The worker's amount of samples, `None` if it didn't report them.
+    ///
+    /// # Returns
+    /// The modified `WorkerHandle`.
+    pub fn with_samples(mut self, samples: Option<u64>) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// The amount of samples the worker trains on.
+    ///
+    /// # Returns
+    /// The worker's amount of samples, `None` if it didn't report them during the handshake.
+    pub fn samples(&self) -> Option<u64> {
+        self.samples
+    }
+
     /// Enables the sparse gradient capability for this handle.
     ///
     /// # Args
@@ -76,6 +134,78 @@ impl<T: TransportLayer> WorkerHandle<T> {
         self.compressor.enable_sparse_compression(r, rng);
     }
 
+    /// Enables the top-k sparse gradient capability for this handle.
+    ///
+    /// # Args
+    /// * `density` - The fraction of the largest-magnitude gradient values to send.
+    pub fn enable_top_k_capability(&mut self, density: Float01) {
+        self.compressor.enable_top_k_compression(density);
+    }
+
+    /// Enables the int8 parameter quantization for this handle.
+    ///
+    /// Every parameter pushed to the worker will be quantized per shard of `shard_size`
+    /// parameters, each with it's own scale and zero point.
+    ///
+    /// # Args
+    /// * `shard_size` - The amount of parameters that share the same scale and zero point.
+    pub fn enable_params_quantization(&mut self, shard_size: NonZeroUsize) {
+        let quantization = Quantization {
+            shard_size,
+            ser_buf: Vec::new(),
+        };
+
+        self.quantization = Some(quantization);
+    }
+
+    /// Bounds the size of the frames exchanged with the worker.
+    ///
+    /// # Args
+    /// * `max_frame_size` - The maximum size in bytes of a frame, `None` to lift the bound.
+    pub fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.transport.set_max_frame_size(max_frame_size);
+    }
+
+    /// Splits the parameters pushed to the worker into chunked frames.
+    ///
+    /// # Args
+    /// * `chunk_size` - The maximum size in bytes of a chunk, `None` to never split the parameters.
+    pub fn set_chunk_size(&mut self, chunk_size: Option<NonZeroUsize>) {
+        self.transport.set_chunk_size(chunk_size);
+    }
+
+    /// Times the sends to the worker, counting the ones over `threshold` as slow.
+    ///
+    /// # Args
+    /// * `threshold` - The duration over which a send is slow, `None` to stop timing the sends.
+    pub fn set_slow_send_threshold(&mut self, threshold: Option<Duration>) {
+        self.transport.set_slow_send_threshold(threshold);
+    }
+
+    /// The time the worker took to drain the sends.
+    ///
+    /// # Returns
+    /// The statistics of the timed sends.
+    pub fn flush_stats(&self) -> FlushStats {
+        self.transport.flush_stats()
+    }
+
+    /// The amount of bytes sent to the worker.
+    ///
+    /// # Returns
+    /// The total amount of sent bytes.
+    pub fn bytes_sent(&self) -> u64 {
+        self.transport.bytes_sent()
+    }
+
+    /// The amount of bytes received from the worker.
+    ///
+    /// # Returns
+    /// The total amount of received bytes.
+    pub fn bytes_received(&self) -> u64 {
+        self.transport.bytes_received()
+    }
+
     /// Blocks until receiving an event from a worker.
     ///
     /// # Returns
@@ -102,18 +232,20 @@ impl<T: TransportLayer> WorkerHandle<T> {
                 WorkerEvent::Grad(&self.grad)
             }
             Msg::Control(Command::Upgraded) => WorkerEvent::Upgraded,
-            Msg::Control(Command::ReportLoss { losses }) => {
+            Msg::Control(Command::ReportLoss { losses, timing }) => {
                 // TODO: Ver donde atajamos esto, capaz aca no es el mejor lugar.
                 //       De momento esta aca si me olvido de pensar donde dejarlo.
                 if losses.iter().any(|l| !l.is_finite()) {
                     return Err(io::Error::other("loss diverged: NaN or Inf detected"));
                 }
 
-                WorkerEvent::Loss(losses.into_owned())
+                WorkerEvent::Loss(losses.into_owned(), timing)
             }
+            Msg::Data(Payload::Metrics(report)) => WorkerEvent::Metrics(report),
             Msg::Control(Command::RequestParams) => WorkerEvent::RequestParams,
             Msg::Control(Command::Disconnect) => WorkerEvent::Disconnect,
             Msg::Control(Command::Done) => WorkerEvent::Done,
+            Msg::Control(Command::Error { message }) => WorkerEvent::Error(message),
             msg => {
                 let text = format!("Unexpected message from worker {}, got: {msg:?}", self.id);
                 return Err(io::Error::other(text));
@@ -163,10 +295,27 @@ impl<T: TransportLayer> WorkerHandle<T> {
 
     /// Pushes the latest state of the parameters to the worker.
     ///
+    /// If the params quantization is enabled, the parameters are sent quantized.
+    ///
+    /// # Args
+    /// * `params` - The parameters to send.
+    ///
     /// # Returns
     /// An io error if occurred.
     pub async fn push_params(&mut self, params: &mut [f32]) -> io::Result<()> {
-        let msg = Msg::Data(Payload::Params(params));
+        let payload = match self.quantization.as_mut() {
+            Some(Quantization {
+                shard_size,
+                ser_buf,
+            }) => {
+                ser_buf.clear();
+                quantize::params_quantize_into(ser_buf, params, shard_size.get());
+                Payload::QuantizedParams(ser_buf)
+            }
+            None => Payload::Params(params),
+        };
+
+        let msg = Msg::Data(payload);
         self.transport.send(&msg).await
     }
 
@@ -254,7 +403,16 @@ impl<T: TransportLayer> WorkerHandle<T> {
     /// # Returns
     /// A new `ParamServerHandle` instance.
     pub fn upgrade_handle(self) -> ParamServerHandle<T> {
-        ParamServerHandle::new(self.id, self.transport)
+        ParamServerHandle::new(self.id, self.transport).with_capabilities(self.capabilities)
+    }
+
+    /// Yields the handle of the node the worker ran on once it's session is over, the
+    /// node is idle on the same transport layer waiting to be bootstrapped again.
+    ///
+    /// # Returns
+    /// A new `NodeHandle` instance.
+    pub fn into_node_handle(self) -> NodeHandle<T> {
+        NodeHandle::new(self.id, self.transport)
     }
 
     /// Tells the worker to stop it's execution.
@@ -4,13 +4,20 @@ mod connection;
 pub mod floats;
 mod handles;
 pub mod protocol;
+mod quantize;
 pub mod share_dataset;
 mod sparse;
+#[cfg(feature = "tls")]
+pub mod tls;
 mod transport;
 mod utils;
 
 pub use clusters::ParamServerCluster;
-pub use connection::{Acceptor, Connection, Connector};
+pub use codec::FlushStats;
+pub use connection::{
+    Acceptor, Connection, Connector, LocalListener, NetListener, NetReadHalf, NetStream,
+    NetWriteHalf, connect, connect_tcp,
+};
 pub use handles::{
     DatasetSrc, NodeEvent, NodeHandle, OrchEvent, OrchHandle, ParamServerHandle, WorkerEvent,
     WorkerHandle,
@@ -18,4 +25,5 @@ pub use handles::{
 pub use protocol::specs;
 pub use transport::{
     NetRtp, Rtp, Stp, TransportLayer, build_reliable_transport, build_simple_transport,
+    max_frame_size,
 };
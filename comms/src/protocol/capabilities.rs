@@ -0,0 +1,104 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A gradient compression mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Dense,
+    Sparse,
+    TopK,
+}
+
+impl Compression {
+    /// The bit of the mode within a `Capabilities` set.
+    ///
+    /// # Returns
+    /// The mode's bit, dense gradients are always supported so theirs is empty.
+    const fn bit(self) -> u32 {
+        match self {
+            Compression::Dense => 0,
+            Compression::Sparse => 1 << 0,
+            Compression::TopK => 1 << 1,
+        }
+    }
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Compression::Dense => "dense",
+            Compression::Sparse => "sparse",
+            Compression::TopK => "top-k",
+        };
+
+        f.write_str(s)
+    }
+}
+
+/// The set of gradient compression modes a node can exchange.
+///
+/// Travels as a bit set during the connection handshake so the modes added by newer
+/// versions are ignored by older ones instead of failing to deserialize, a peer that
+/// doesn't send it at all is assumed to only exchange dense gradients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    /// Only dense gradients.
+    pub const DENSE_ONLY: Self = Self(0);
+
+    /// Every mode this version supports.
+    pub const SUPPORTED: Self = Self(Compression::Sparse.bit() | Compression::TopK.bit());
+
+    /// Whether the set contains a mode.
+    ///
+    /// # Args
+    /// * `compression` - The compression mode.
+    ///
+    /// # Returns
+    /// `true` if the mode is in the set.
+    pub fn supports(self, compression: Compression) -> bool {
+        self.0 & compression.bit() == compression.bit()
+    }
+
+    /// The modes in both sets.
+    ///
+    /// # Args
+    /// * `other` - The other set.
+    ///
+    /// # Returns
+    /// A new set with the modes supported by both.
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Picks the mode to exchange gradients with given the requested one.
+    ///
+    /// # Args
+    /// * `requested` - The mode the user configured.
+    /// * `required` - Whether to fail instead of falling back to dense gradients.
+    ///
+    /// # Returns
+    /// The requested mode if it's in the set, dense gradients otherwise.
+    ///
+    /// # Errors
+    /// An io error of kind `Unsupported` if the mode isn't in the set and it's required.
+    pub fn negotiate(self, requested: Compression, required: bool) -> io::Result<Compression> {
+        if self.supports(requested) {
+            return Ok(requested);
+        }
+
+        if required {
+            let text =
+                format!("{requested} gradient compression is required but the peer lacks it");
+            return Err(io::Error::new(io::ErrorKind::Unsupported, text));
+        }
+
+        Ok(Compression::Dense)
+    }
+}
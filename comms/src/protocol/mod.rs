@@ -1,4 +1,7 @@
+mod capabilities;
 mod msg;
 pub mod specs;
 
+pub use capabilities::{Capabilities, Compression};
 pub use msg::{Command, Entity, Msg, Payload};
+pub(crate) use msg::{CHUNK_FLAG, HEADER_SIZE, Header, LAST_CHUNK_FLAG};
@@ -11,15 +11,26 @@ use serde::{
 };
 use uuid::Uuid;
 
-use super::specs::{
-    machine_learning::TrainerSpec,
-    node::{NodeSpec, StatRequest, StatResponse},
-    server::ServerSpec,
+use super::{
+    Capabilities,
+    specs::{
+        machine_learning::TrainerSpec,
+        node::{NodeSpec, StatRequest, StatResponse},
+        server::{ServerSpec, StalenessStats},
+        worker::{MetricsReport, TimeBreakdown},
+    },
 };
+use crate::floats::FloatPositive;
 
 pub type Header = u32;
 pub const HEADER_SIZE: usize = size_of::<Header>();
 
+/// Marks a frame as a chunk of a payload split across several frames.
+pub const CHUNK_FLAG: Header = 1 << 31;
+
+/// Marks a frame as the final chunk of a payload split across several frames.
+pub const LAST_CHUNK_FLAG: Header = 1 << 30;
+
 /// The payload data for the `Data` variant of the `Msg` enum.
 #[derive(Debug)]
 pub enum Payload<'a> {
@@ -27,6 +38,8 @@ pub enum Payload<'a> {
     SparseGrad(&'a [u8]),
     Params(&'a mut [f32]),
     Datachunk(&'a [f32]),
+    QuantizedParams(&'a [u8]),
+    Metrics(MetricsReport),
 }
 
 /// An enum of the different types of entities in the system.
@@ -45,10 +58,24 @@ pub enum Command<'a> {
     Connect {
         id: Uuid,
         src: Entity,
+        #[serde(default)]
+        capabilities: Capabilities,
+        #[serde(default)]
+        samples: Option<u64>,
     },
     Accept {
         id: Uuid,
         src: Entity,
+        #[serde(default)]
+        port: Option<u16>,
+        #[serde(default)]
+        capabilities: Capabilities,
+    },
+    Auth {
+        mac: Vec<u8>,
+    },
+    AuthChallenge {
+        nonce: Vec<u8>,
     },
     CreateNode {
         spec: NodeSpec,
@@ -56,13 +83,27 @@ pub enum Command<'a> {
     Disconnect,
     Done,
     Eof,
+    Error {
+        message: String,
+    },
     Ping,
     Pong,
     ReportLoss {
         #[serde(deserialize_with = "deserialize_null_as_nan")]
         losses: Cow<'a, [f64]>,
+        #[serde(default)]
+        timing: TimeBreakdown,
     },
+    RequestEmaParams,
     RequestParams,
+    RequestSnapshot,
+    RequestStaleness,
+    SetEpoch {
+        epoch: usize,
+    },
+    SetLearningRate {
+        lr: FloatPositive,
+    },
     ShareDataset,
     ShareDatasetSize {
         size: usize,
@@ -70,6 +111,9 @@ pub enum Command<'a> {
     StatsRequest {
         reqs: Vec<StatRequest>,
     },
+    StalenessResponse {
+        stats: StalenessStats,
+    },
     StatsResponse {
         stats: Vec<StatResponse>,
     },
@@ -89,6 +133,7 @@ pub enum Command<'a> {
 
 /// The application layer message for the entire system.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Msg<'a> {
     Control(Command<'a>),
     Data(Payload<'a>),
@@ -119,6 +164,15 @@ impl<'a> Msg<'a> {
     /// An optional zero copy slice.
     pub fn serialize(&'a self, out: &mut Vec<u8>) -> Option<&'a [u8]> {
         match self {
+            Msg::Data(Payload::Metrics(report)) => {
+                let header = (6 as Header).to_be_bytes();
+                out.extend_from_slice(&header);
+
+                // SAFETY: Serialize impl for `MetricsReport` is derived and not implemented
+                //         by hand. Nor has a non string-key map inside.
+                serde_json::to_writer(out, report).unwrap();
+                None
+            }
             Msg::Control(cmd) => {
                 let header = (0 as Header).to_be_bytes();
                 out.extend_from_slice(&header);
@@ -134,6 +188,8 @@ impl<'a> Msg<'a> {
                     Payload::SparseGrad(sparse) => (2, sparse),
                     Payload::Params(params) => (3, bytemuck::cast_slice(params)),
                     Payload::Datachunk(chunk) => (4, bytemuck::cast_slice(chunk)),
+                    Payload::QuantizedParams(quantized) => (5, quantized),
+                    Payload::Metrics(..) => unreachable!(),
                 };
 
                 let header = kind.to_be_bytes();
@@ -162,32 +218,35 @@ impl<'a> Msg<'a> {
 
         match kind {
             0 => Ok(Msg::Control(serde_json::from_slice(rest)?)),
-            1..5 => {
+            1..6 => {
                 let payload = match kind {
                     1 => Payload::DenseGrad(bytemuck::cast_slice(rest)),
                     2 => Payload::SparseGrad(rest),
                     3 => Payload::Params(bytemuck::cast_slice_mut(rest)),
                     4 => Payload::Datachunk(bytemuck::cast_slice(rest)),
+                    5 => Payload::QuantizedParams(rest),
                     _ => unreachable!(),
                 };
 
                 Ok(Msg::Data(payload))
             }
+            6 => Ok(Msg::Data(Payload::Metrics(serde_json::from_slice(rest)?))),
             byte => Msg::invalid_kind_byte(byte),
         }
     }
 }
 
-/// Deserializes the `ReportLoss` variant of the `Command` variant of messages.
+/// Deserializes the losses of the `ReportLoss` command and the `Metrics` payload.
 ///
 /// # Args
 /// * `deserializer` - The deserializar that serde will use to deserialize the loss report.
 ///
 /// # Returns
 /// Will always return the loss report, this deserialization can't fail.
-fn deserialize_null_as_nan<'de, 'a, D>(deserializer: D) -> Result<Cow<'a, [f64]>, D::Error>
+pub(crate) fn deserialize_null_as_nan<'de, D, L>(deserializer: D) -> Result<L, D::Error>
 where
     D: Deserializer<'de>,
+    L: From<Vec<f64>>,
 {
     struct LossVisitor;
 
@@ -212,5 +271,21 @@ where
         }
     }
 
-    deserializer.deserialize_seq(LossVisitor).map(Cow::Owned)
+    deserializer.deserialize_seq(LossVisitor).map(L::from)
+}
+
+/// Deserializes a float of the `Metrics` payload that may have been sent as `NaN`, which
+/// JSON encodes as `null`.
+///
+/// # Args
+/// * `deserializer` - The deserializer that serde will use to deserialize the float.
+///
+/// # Returns
+/// The float, `NaN` if it was `null`.
+pub(crate) fn deserialize_null_as_nan_float<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<f64>::deserialize(deserializer)?;
+    Ok(value.unwrap_or(f64::NAN))
 }
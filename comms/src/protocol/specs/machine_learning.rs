@@ -1,4 +1,4 @@
-use std::num::NonZeroUsize;
+use std::{num::NonZeroUsize, ops::Range, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
@@ -29,15 +29,23 @@ pub enum ParamGenSpec {
     Inline {
         params: Vec<f32>,
     },
+    /// Parameters sampled from `distribution`, scaled by `gain`.
     Rand {
         distribution: DistributionSpec,
         limit: usize,
+        #[serde(default = "default_gain")]
+        gain: f32,
     },
     Chained {
         specs: Vec<ParamGenSpec>,
     },
 }
 
+/// The gain of a random parameter generator, leaving the sampled values as they are.
+fn default_gain() -> f32 {
+    1.0
+}
+
 impl ParamGenSpec {
     pub fn size(&self) -> usize {
         match self {
@@ -53,19 +61,37 @@ impl ParamGenSpec {
 #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ActFnSpec {
-    Sigmoid { amp: f32 },
-    Tanh { amp: f32 },
-    ReLU { slope: Float01 },
+    Sigmoid {
+        amp: f32,
+        #[serde(default)]
+        clamp: Option<FloatPositive>,
+    },
+    Tanh {
+        amp: f32,
+        #[serde(default)]
+        clamp: Option<FloatPositive>,
+    },
+    ReLU {
+        slope: Float01,
+    },
     Softmax,
 }
 
 /// The specification for the `Layer` enum.
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LayerSpec {
+    /// A dense layer, with `tie` set it's weights are the transpose of the ones of the
+    /// shared layer at that index, which must be a dense layer of the transposed `dim`.
+    ///
+    /// The parameters of a `frozen` layer are never updated, so the workers skip their gradient.
     Dense {
         dim: (usize, usize),
         act_fn: Option<ActFnSpec>,
+        #[serde(default)]
+        tie: Option<usize>,
+        #[serde(default)]
+        frozen: bool,
     },
     Conv {
         input_dim: (usize, usize, usize),
@@ -73,6 +99,8 @@ pub enum LayerSpec {
         stride: usize,
         padding: usize,
         act_fn: Option<ActFnSpec>,
+        #[serde(default)]
+        frozen: bool,
     },
     MaxPooling {
         input_dim: (usize, usize, usize),
@@ -81,6 +109,13 @@ pub enum LayerSpec {
         padding: usize,
         act_fn: Option<ActFnSpec>,
     },
+    /// A layer defined outside of the crate, built by the constructor registered under
+    /// `name` in the layer registry of the node out of `params`.
+    Custom {
+        name: String,
+        #[serde(default)]
+        params: serde_json::Value,
+    },
 }
 
 /// The specification for the `Optimizer` trait.
@@ -102,12 +137,71 @@ pub enum OptimizerSpec {
     },
 }
 
+impl OptimizerSpec {
+    /// The learning rate the optimizer is configured with.
+    ///
+    /// # Returns
+    /// The base learning rate.
+    pub fn learning_rate(&self) -> FloatPositive {
+        match *self {
+            OptimizerSpec::Adam { learning_rate, .. } => learning_rate,
+            OptimizerSpec::GradientDescent { learning_rate } => learning_rate,
+            OptimizerSpec::GradientDescentWithMomentum { learning_rate, .. } => learning_rate,
+        }
+    }
+
+    /// Replaces the learning rate the optimizer is configured with.
+    ///
+    /// # Args
+    /// * `lr` - The new learning rate.
+    ///
+    /// # Returns
+    /// The same specification with the new learning rate.
+    pub fn with_learning_rate(mut self, lr: FloatPositive) -> Self {
+        match &mut self {
+            OptimizerSpec::Adam { learning_rate, .. }
+            | OptimizerSpec::GradientDescent { learning_rate }
+            | OptimizerSpec::GradientDescentWithMomentum { learning_rate, .. } => {
+                *learning_rate = lr;
+            }
+        }
+
+        self
+    }
+}
+
+/// The specification for the `LrSchedule` trait.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LrScheduleSpec {
+    WarmRestarts {
+        t_0: NonZeroUsize,
+        t_mult: NonZeroUsize,
+        min_lr: FloatPositive,
+    },
+}
+
 /// The specification for the `Dataset`.
 #[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct DatasetSpec {
     pub x_size: NonZeroUsize,
     pub y_size: NonZeroUsize,
+    /// Whether the samples are sent as the packed rows of a sparse matrix.
+    #[serde(default)]
+    pub sparse: bool,
+}
+
+/// The specification for a worker's shard of a synthetic dataset.
+///
+/// The worker generates the rows itself instead of receiving them from the orchestrator,
+/// every row is drawn from it's own seeded stream so the shards concatenate to the whole
+/// dataset.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct SyntheticSpec {
+    pub seed: u64,
+    pub rows: Range<u64>,
+    pub noise_std: f32,
 }
 
 /// The specification for the `LossFn` enum.
@@ -118,6 +212,105 @@ pub enum LossFnSpec {
     CrossEntropy,
 }
 
+/// When a worker stops training.
+///
+/// A step is a single optimization step over a mini batch, an epoch is a whole
+/// pass over the worker's dataset partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopCondition {
+    MaxEpochs(NonZeroUsize),
+    MaxSteps(NonZeroUsize),
+    MaxWallTime(Duration),
+}
+
+/// What a worker does with the last epoch of a training stopped after a number of steps,
+/// when the stop falls in the middle of it.
+///
+/// `ApplyPartial` trains on the epoch's batches up to the last step, sending their
+/// gradient to be applied, and `DiscardPartial` skips the whole epoch instead. Either way
+/// the worker still sends a gradient, a zero one when discarding, so the barrier stays
+/// balanced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinalStepPolicy {
+    #[default]
+    ApplyPartial,
+    DiscardPartial,
+}
+
+/// How a worker orders the rows of it's dataset partition on every epoch.
+///
+/// `WeightedByClass` draws as many rows as the partition holds, with replacement, weighting
+/// each by the inverse of the frequency of it's class so the batches come out roughly class
+/// balanced. The class of a row is the value of it's label's `label_col` column, a whole
+/// number below `num_classes`. The frequencies are counted over the worker's own partition,
+/// not the whole dataset.
+///
+/// `GlobalOrder` leaves the order to the worker, which holds the whole dataset and trains on
+/// it's share of every batch of an order shared by all the `nworkers`, `worker` being it's
+/// position among them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplerSpec {
+    Sequential,
+    #[default]
+    Shuffle,
+    WeightedByClass {
+        label_col: usize,
+        num_classes: NonZeroUsize,
+    },
+    GlobalOrder {
+        worker: usize,
+        nworkers: NonZeroUsize,
+    },
+}
+
+/// How far a worker's training went, the part of a checkpoint that belongs to the worker.
+///
+/// The shuffles are fully determined by the run's seed and the amount of finished epochs,
+/// so a worker resumed from it replays them and keeps the batch order of an uninterrupted run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrainerProgress {
+    pub epoch: usize,
+    pub step: usize,
+}
+
+/// A range of the model's parameters whose gradient is clipped to a maximum norm.
+///
+/// The parameters are numbered following the order of the model's layers.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ClipGroupSpec {
+    pub start: usize,
+    pub end: usize,
+    pub max_norm: FloatPositive,
+}
+
+/// A head of a multi task model, branching off the shared layers to predict some of the targets.
+///
+/// The heads predict consecutive columns of the labels, in the order they're given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeadSpec {
+    pub layers: Vec<LayerSpec>,
+    pub loss_fn: LossFnSpec,
+    pub y_size: NonZeroUsize,
+    #[serde(default)]
+    pub weight: Option<FloatPositive>,
+}
+
+/// How a worker fits the model to it's dataset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategySpec {
+    /// Mini batch gradient descent through backpropagation.
+    #[default]
+    Backprop,
+    /// The exact least squares solution of a linear regression, a ground truth to compare
+    /// the iterative training against. Only fits a single dense layer without an
+    /// activation function trained with the mean squared error.
+    ClosedFormLinReg,
+}
+
 /// The specification for the `Trainer` struct.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrainerSpec {
@@ -126,7 +319,25 @@ pub struct TrainerSpec {
     pub dataset: DatasetSpec,
     pub loss_fn: LossFnSpec,
     pub offline_epochs: usize,
-    pub max_epochs: NonZeroUsize,
+    pub stop_condition: StopCondition,
+    #[serde(default)]
+    pub final_step: FinalStepPolicy,
     pub batch_size: NonZeroUsize,
+    #[serde(default)]
+    pub sampler: SamplerSpec,
     pub seed: Option<u64>,
+    /// The seed of the worker's augmentation streams, unlike `seed` it's different on
+    /// every worker.
+    #[serde(default)]
+    pub augmentation_seed: Option<u64>,
+    #[serde(default)]
+    pub clip_groups: Vec<ClipGroupSpec>,
+    #[serde(default)]
+    pub heads: Vec<HeadSpec>,
+    #[serde(default)]
+    pub checkpoint_every: Option<NonZeroUsize>,
+    #[serde(default)]
+    pub resume: Option<TrainerProgress>,
+    #[serde(default)]
+    pub strategy: StrategySpec,
 }
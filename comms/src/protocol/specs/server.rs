@@ -1,17 +1,58 @@
-use std::num::NonZeroUsize;
+use std::{num::NonZeroUsize, ops::Range, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
-use super::machine_learning::{OptimizerSpec, ParamGenSpec};
+use super::machine_learning::{LrScheduleSpec, OptimizerSpec, ParamGenSpec};
+use crate::{floats::Float01, transport};
 
 /// The specification for the `Synchronizer` trait.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SynchronizerSpec {
-    Barrier { barrier_size: NonZeroUsize },
+    Barrier {
+        barrier_size: NonZeroUsize,
+        #[serde(default)]
+        reduction: Reduction,
+        #[serde(default)]
+        timeout: Option<BarrierTimeout>,
+    },
     NonBlocking,
 }
 
+/// How long a barrier waits for it's workers before giving up and what it does then.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BarrierTimeout {
+    pub after: Duration,
+    pub policy: TimeoutPolicy,
+}
+
+/// What a barrier does once it's workers take longer than it's timeout to arrive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutPolicy {
+    /// Fails the step of the workers that arrived.
+    #[default]
+    Fail,
+    /// Waits once more for the given duration before failing.
+    RetryOnce(Duration),
+    /// Takes the step without the workers yet to arrive, which are dropped from the training.
+    DropLaggard,
+}
+
+/// How the gradients of the workers are combined on each barrier step.
+///
+/// With `Sum` the size of the update grows with the amount of workers, so the
+/// learning rate has to be scaled down accordingly. `Mean` keeps the update
+/// magnitude of a single worker regardless of how many are training, weighing
+/// every gradient by the samples it's worker reported to train on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reduction {
+    Mean,
+    #[default]
+    Sum,
+}
+
 /// The specification for the `Store` trait.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -20,6 +61,41 @@ pub enum StoreSpec {
     Wild,
 }
 
+/// When the server sends a worker the parameters, relative to applying it's gradient.
+///
+/// With `UpdateThenSend` the worker gets the parameters updated with it's own gradient,
+/// so it's next gradient is computed over them. `SendThenUpdate` replies with the
+/// parameters as they were before the step, sparing the worker the wait for the update
+/// at the cost of every gradient being computed one update behind, which slows down
+/// the convergence and may need a smaller learning rate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateRule {
+    #[default]
+    UpdateThenSend,
+    SendThenUpdate,
+}
+
+/// The encoding of the parameters the server broadcasts to it's workers.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuantizationSpec {
+    #[default]
+    None,
+    Int8,
+}
+
+/// How stale the gradients applied by a server were, in parameter updates.
+///
+/// A gradient's staleness is the amount of updates the server applied between the
+/// worker pulling the parameters and the gradient computed over them arriving.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StalenessStats {
+    pub updates: usize,
+    pub mean: f64,
+    pub max: usize,
+}
+
 /// The specification for the `Server` trait.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerSpec {
@@ -28,5 +104,83 @@ pub struct ServerSpec {
     pub optimizer: OptimizerSpec,
     pub synchronizer: SynchronizerSpec,
     pub store: StoreSpec,
+    #[serde(default)]
+    pub quantization: QuantizationSpec,
+    #[serde(default)]
+    pub allow_worker_loss: bool,
+    #[serde(default)]
+    pub min_workers: Option<usize>,
+    #[serde(default)]
+    pub max_workers: Option<usize>,
+    #[serde(default)]
+    pub max_frame_size: Option<usize>,
+    #[serde(default)]
+    pub chunk_size: Option<NonZeroUsize>,
+    #[serde(default)]
+    pub slow_send_ms: Option<u64>,
+    #[serde(default)]
+    pub lr_schedule: Option<LrScheduleSpec>,
+    #[serde(default)]
+    pub ema_decay: Option<Float01>,
+    /// How much of the moving average of the gradients is kept on every step, if the
+    /// optimizer steps over it instead of the raw gradients.
+    #[serde(default)]
+    pub server_momentum: Option<Float01>,
+    #[serde(default)]
+    pub track_staleness: bool,
+    /// The amount of steps of a worker in between two waits for the rest of them on a
+    /// `NonBlocking` server, so they all get the same parameters to report losses over.
+    #[serde(default)]
+    pub metric_barrier_every: Option<NonZeroUsize>,
+    /// Whether to add the gradients up in `f64` before applying them.
+    #[serde(default)]
+    pub accum_f64: bool,
+    /// The ranges of the server's parameters whose gradients are added up in `f64`, the
+    /// rest are added up in `f32`. Ignored when `accum_f64` is set.
+    #[serde(default)]
+    pub accum_f64_ranges: Vec<Range<usize>>,
+    /// The ranges of the server's parameters left out of the optimizer's updates.
+    #[serde(default)]
+    pub frozen: Vec<Range<usize>>,
+    #[serde(default)]
+    pub update_rule: UpdateRule,
     pub seed: Option<u64>,
 }
+
+impl ServerSpec {
+    /// The amount of workers the server waits for before starting the training.
+    ///
+    /// # Returns
+    /// `min_workers` if set, `nworkers` otherwise.
+    pub fn initial_workers(&self) -> usize {
+        self.min_workers
+            .unwrap_or(self.nworkers)
+            .clamp(1, self.nworkers.max(1))
+    }
+
+    /// The amount of workers that can join after the training started.
+    ///
+    /// # Returns
+    /// The free slots up to `max_workers`, which defaults to `nworkers`.
+    pub fn late_workers(&self) -> usize {
+        let max_workers = self.max_workers.unwrap_or(self.nworkers);
+        max_workers.saturating_sub(self.initial_workers())
+    }
+
+    /// The maximum size of the frames exchanged with the workers.
+    ///
+    /// # Returns
+    /// `max_frame_size` if set, otherwise the size fitting this server's parameters.
+    pub fn frame_size(&self) -> usize {
+        self.max_frame_size
+            .unwrap_or_else(|| transport::max_frame_size(self.param_gen.size()))
+    }
+
+    /// The duration over which a send to a worker is considered slow.
+    ///
+    /// # Returns
+    /// `slow_send_ms` as a duration, `None` if the sends aren't timed.
+    pub fn slow_send_threshold(&self) -> Option<Duration> {
+        self.slow_send_ms.map(Duration::from_millis)
+    }
+}
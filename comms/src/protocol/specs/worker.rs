@@ -1,7 +1,9 @@
+use std::{num::NonZeroUsize, time::Duration};
+
 use serde::{Deserialize, Serialize};
 
-use super::machine_learning::{ParamGenSpec, TrainerSpec};
-use crate::floats::Float01;
+use super::machine_learning::{ParamGenSpec, SyntheticSpec, TrainerSpec};
+use crate::{floats::Float01, protocol::Compression};
 
 /// Distributed training algorithm selection.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +20,12 @@ pub enum AlgorithmSpec {
         param_gen: ParamGenSpec,
         amount_of_layers: usize,
     },
+    /// Computes the loss of the given parameters over the worker's partition, forward
+    /// only, without ever training on it or exchanging gradients.
+    Evaluate {
+        param_gen: ParamGenSpec,
+        amount_of_layers: usize,
+    },
 }
 
 /// Message serializer for gradient sparse compression.
@@ -26,6 +34,21 @@ pub enum AlgorithmSpec {
 pub enum SerializerSpec {
     Base,
     SparseCapable { r: Float01 },
+    TopK { density: Float01 },
+}
+
+impl SerializerSpec {
+    /// The gradient compression mode of the serializer.
+    ///
+    /// # Returns
+    /// The mode to negotiate with the peers.
+    pub fn compression(self) -> Compression {
+        match self {
+            SerializerSpec::Base => Compression::Dense,
+            SerializerSpec::SparseCapable { .. } => Compression::Sparse,
+            SerializerSpec::TopK { .. } => Compression::TopK,
+        }
+    }
 }
 
 /// The specification for the `Worker`.
@@ -35,4 +58,178 @@ pub struct WorkerSpec {
     pub algorithm: AlgorithmSpec,
     pub serializer: SerializerSpec,
     pub seed: Option<u64>,
+    #[serde(default)]
+    pub max_frame_size: Option<usize>,
+    #[serde(default)]
+    pub require_compression: bool,
+    #[serde(default)]
+    pub latency_warmup_rounds: Option<NonZeroUsize>,
+    #[serde(default)]
+    pub synthetic: Option<SyntheticSpec>,
+}
+
+/// How a worker spent the wall time of a training round.
+///
+/// The sections are timed one after the other and never overlap, a stretch of time
+/// is counted in a single one of them so their sum never exceeds the round's length.
+/// If computing ever overlaps with communication, the overlapped time is counted as
+/// compute and only the time the worker stays blocked afterwards is communication.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeBreakdown {
+    /// The forward and backward passes along with the optimizer steps.
+    pub compute: Duration,
+    /// Sending and receiving the gradients and the losses.
+    pub comm: Duration,
+    /// Blocked until the peers or the servers were ready to answer.
+    pub wait: Duration,
+}
+
+/// The metrics a worker reports to the orchestrator after every training round.
+///
+/// New metrics are added as optional fields, so it can't be built by it's fields outside
+/// of this crate, use `MetricsReport::new` instead.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct MetricsReport {
+    /// The amount of epochs the worker finished so far.
+    pub epoch: usize,
+    /// The losses of the epochs run in the round, one per epoch.
+    #[serde(deserialize_with = "crate::protocol::msg::deserialize_null_as_nan")]
+    pub losses: Vec<f64>,
+    /// The samples per second the worker trained on during the round.
+    pub throughput: f64,
+    /// How the worker spent the round.
+    pub timing: TimeBreakdown,
+    /// The amount of samples the worker trained on during the round.
+    #[serde(default)]
+    pub samples: u64,
+    /// The amount of bytes the worker sent to it's peers during the round's step.
+    #[serde(default)]
+    pub bytes_sent: u64,
+    /// The amount of bytes the worker received from it's peers during the round's step.
+    #[serde(default)]
+    pub bytes_received: u64,
+    /// The classifications of the final model over the worker's partition, only set on the
+    /// report of the validation pass that follows the last round.
+    #[serde(default)]
+    pub confusion: Option<ConfusionCounts>,
+    /// The sums behind the regression metrics of the final model over the worker's
+    /// partition, only set on the report of the validation pass that follows the last round.
+    #[serde(default)]
+    pub regression: Option<RegressionSums>,
+}
+
+/// The sums behind a worker's regression metrics as they're sent over the wire.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RegressionSums {
+    /// The amount of predicted values.
+    pub n: u64,
+    /// The mean of the targets.
+    #[serde(deserialize_with = "crate::protocol::msg::deserialize_null_as_nan_float")]
+    pub target_mean: f64,
+    /// The sum of the squared deviations of the targets from their mean.
+    #[serde(deserialize_with = "crate::protocol::msg::deserialize_null_as_nan_float")]
+    pub target_m2: f64,
+    /// The residual sum of squares.
+    #[serde(deserialize_with = "crate::protocol::msg::deserialize_null_as_nan_float")]
+    pub rss: f64,
+    /// The sum of the squared logarithmic errors, `NaN` if a value wasn't greater than -1.
+    #[serde(deserialize_with = "crate::protocol::msg::deserialize_null_as_nan_float")]
+    pub squared_log_error: f64,
+}
+
+/// The counts of a confusion matrix as they're sent over the wire.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfusionCounts {
+    /// The amount of classes.
+    pub nclasses: usize,
+    /// The counts in row major order, a row per actual class.
+    pub counts: Vec<u64>,
+}
+
+impl MetricsReport {
+    /// Creates a new `MetricsReport`.
+    ///
+    /// # Args
+    /// * `epoch` - The amount of epochs the worker finished so far.
+    /// * `losses` - The losses of the epochs run in the round.
+    /// * `throughput` - The samples per second the worker trained on.
+    /// * `timing` - How the worker spent the round.
+    ///
+    /// # Returns
+    /// A new `MetricsReport` instance.
+    pub fn new(epoch: usize, losses: Vec<f64>, throughput: f64, timing: TimeBreakdown) -> Self {
+        Self {
+            epoch,
+            losses,
+            throughput,
+            timing,
+            samples: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            confusion: None,
+            regression: None,
+        }
+    }
+
+    /// Sets the amount of samples the worker trained on during the round.
+    ///
+    /// # Args
+    /// * `samples` - The amount of samples.
+    ///
+    /// # Returns
+    /// The modified `MetricsReport`.
+    pub fn with_samples(mut self, samples: u64) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Sets the amount of bytes the worker exchanged with it's peers during the round's step.
+    ///
+    /// # Args
+    /// * `bytes_sent` - The amount of bytes sent.
+    /// * `bytes_received` - The amount of bytes received.
+    ///
+    /// # Returns
+    /// The modified `MetricsReport`.
+    pub fn with_bytes(mut self, bytes_sent: u64, bytes_received: u64) -> Self {
+        self.bytes_sent = bytes_sent;
+        self.bytes_received = bytes_received;
+        self
+    }
+
+    /// Sets the classifications of the final model over the worker's partition.
+    ///
+    /// # Args
+    /// * `confusion` - The counts of the confusion matrix.
+    ///
+    /// # Returns
+    /// The modified `MetricsReport`.
+    pub fn with_confusion(mut self, confusion: ConfusionCounts) -> Self {
+        self.confusion = Some(confusion);
+        self
+    }
+
+    /// Sets the sums behind the regression metrics of the final model over the worker's
+    /// partition.
+    ///
+    /// # Args
+    /// * `regression` - The regression sums.
+    ///
+    /// # Returns
+    /// The modified `MetricsReport`.
+    pub fn with_regression(mut self, regression: RegressionSums) -> Self {
+        self.regression = Some(regression);
+        self
+    }
+}
+
+impl TimeBreakdown {
+    /// The time of every section together.
+    ///
+    /// # Returns
+    /// The sum of the compute, communication and wait times.
+    pub fn total(&self) -> Duration {
+        self.compute + self.comm + self.wait
+    }
 }
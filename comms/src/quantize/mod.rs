@@ -0,0 +1,4 @@
+mod protocol;
+mod tests;
+
+pub use protocol::{params_dequantize_into, params_quantize_into, params_quantize_max_len};
@@ -0,0 +1,201 @@
+/// The type for the total length of the dequantized parameter buffer.
+type TotalLen = u64;
+/// The size in bytes of the used type for the total length.
+const TOTAL_LEN_SIZE: usize = size_of::<TotalLen>();
+
+/// The type for the amount of parameters per shard.
+type ShardLen = u64;
+/// The size in bytes of the used type for the shard length.
+const SHARD_LEN_SIZE: usize = size_of::<ShardLen>();
+
+/// The type for the scale of a shard.
+type Scale = f32;
+/// The size in bytes of the used type for the scale.
+const SCALE_SIZE: usize = size_of::<Scale>();
+
+/// The type for the zero point of a shard.
+type ZeroPoint = i8;
+/// The size in bytes of the used type for the zero point.
+const ZERO_POINT_SIZE: usize = size_of::<ZeroPoint>();
+
+const QMIN: f32 = i8::MIN as f32;
+const QMAX: f32 = i8::MAX as f32;
+
+/// Serializes the given parameters into `buf` quantizing each shard of `shard_size`
+/// parameters to `i8` with it's own scale and zero point.
+///
+/// # Args
+/// * `buf` - The buffer to use to serialize the quantized parameters.
+/// * `params` - The parameters to quantize.
+/// * `shard_size` - The amount of parameters that share the same scale and zero point.
+pub fn params_quantize_into(buf: &mut Vec<u8>, params: &[f32], shard_size: usize) {
+    let shard_size = shard_size.max(1);
+    let nshards = params.len().div_ceil(shard_size);
+
+    buf.reserve(
+        TOTAL_LEN_SIZE + SHARD_LEN_SIZE + nshards * (SCALE_SIZE + ZERO_POINT_SIZE) + params.len(),
+    );
+
+    buf.extend_from_slice(&(params.len() as TotalLen).to_le_bytes());
+    buf.extend_from_slice(&(shard_size as ShardLen).to_le_bytes());
+
+    for shard in params.chunks(shard_size) {
+        let (min, max) = shard
+            .iter()
+            .fold((0.0f32, 0.0f32), |(min, max), &p| (min.min(p), max.max(p)));
+
+        let range = max - min;
+        let scale = if range > 0.0 && range.is_finite() {
+            range / (QMAX - QMIN)
+        } else {
+            1.0
+        };
+
+        let zero_point = (QMIN - min / scale).round().clamp(QMIN, QMAX) as ZeroPoint;
+
+        buf.extend_from_slice(&scale.to_le_bytes());
+        buf.extend_from_slice(&zero_point.to_le_bytes());
+
+        for &p in shard {
+            let q = (p / scale).round() + zero_point as f32;
+            buf.push(q.clamp(QMIN, QMAX) as i8 as u8);
+        }
+    }
+}
+
+/// The largest amount of bytes `params_quantize_into` can produce for `len` parameters,
+/// reached when every parameter lies in it's own shard.
+///
+/// # Args
+/// * `len` - The amount of parameters.
+///
+/// # Returns
+/// The upper bound for the size of the quantized parameters.
+pub fn params_quantize_max_len(len: usize) -> usize {
+    TOTAL_LEN_SIZE + SHARD_LEN_SIZE + len * (SCALE_SIZE + ZERO_POINT_SIZE + 1)
+}
+
+/// Deserializes quantized parameters into `params`.
+///
+/// # Args
+/// * `params` - The buffer where to write the dequantized parameters.
+/// * `buf` - The buffer containing the serialized quantized parameters.
+///
+/// # Returns
+/// An error if there are missing or invalid values in the input buffer.
+pub fn params_dequantize_into(params: &mut Vec<f32>, buf: &[u8]) -> Result<(), &'static str> {
+    let Some((total_len_bytes, buf)) = buf.split_at_checked(TOTAL_LEN_SIZE) else {
+        return Err("The given quantized buffer is smaller than TOTAL_LEN_SIZE");
+    };
+
+    let Some((shard_len_bytes, mut buf)) = buf.split_at_checked(SHARD_LEN_SIZE) else {
+        return Err("The given quantized buffer is smaller than SHARD_LEN_SIZE");
+    };
+
+    // SAFETY: Both slices have exactly `TOTAL_LEN_SIZE` and `SHARD_LEN_SIZE` bytes in size.
+    let total_len = TotalLen::from_le_bytes(total_len_bytes.try_into().unwrap()) as usize;
+    let shard_len = ShardLen::from_le_bytes(shard_len_bytes.try_into().unwrap()) as usize;
+
+    if shard_len == 0 {
+        return Err("The shard length of the quantized buffer is zero");
+    }
+
+    params.clear();
+    params.reserve(total_len);
+
+    while params.len() < total_len {
+        let Some((scale_bytes, rest)) = buf.split_at_checked(SCALE_SIZE) else {
+            return Err("Missing scale bytes at params dequantization");
+        };
+
+        let Some((zero_point_bytes, rest)) = rest.split_at_checked(ZERO_POINT_SIZE) else {
+            return Err("Missing zero point bytes at params dequantization");
+        };
+
+        let len = shard_len.min(total_len - params.len());
+        let Some((data, rest)) = rest.split_at_checked(len) else {
+            return Err("Truncated quantized data");
+        };
+
+        // SAFETY: Both slices have exactly `SCALE_SIZE` and `ZERO_POINT_SIZE` bytes in size.
+        let scale = Scale::from_le_bytes(scale_bytes.try_into().unwrap());
+        let zero_point = ZeroPoint::from_le_bytes(zero_point_bytes.try_into().unwrap()) as f32;
+
+        params.extend(data.iter().map(|&q| (q as i8 as f32 - zero_point) * scale));
+        buf = rest;
+    }
+
+    if !buf.is_empty() {
+        return Err("Trailing bytes after the quantized parameters");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_quantize_layout() {
+        let params = [0.0, 2.55, 0.0];
+        let mut buf = Vec::new();
+
+        params_quantize_into(&mut buf, &params, 2);
+
+        let nshards = 2;
+        let expected_len = TOTAL_LEN_SIZE
+            + SHARD_LEN_SIZE
+            + nshards * (SCALE_SIZE + ZERO_POINT_SIZE)
+            + params.len();
+
+        assert_eq!(buf.len(), expected_len);
+        assert_eq!(buf[..TOTAL_LEN_SIZE], [3, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            buf[TOTAL_LEN_SIZE..][..SHARD_LEN_SIZE],
+            [2, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_quantize_and_dequantize_consistency() {
+        let params: Vec<_> = (0..100).map(|i| (i as f32 - 50.0) / 10.0).collect();
+        let mut buf = Vec::new();
+
+        params_quantize_into(&mut buf, &params, 32);
+
+        let mut got = Vec::new();
+        params_dequantize_into(&mut got, &buf).unwrap();
+
+        assert_eq!(got.len(), params.len());
+
+        for (p, q) in params.iter().zip(&got) {
+            assert!((p - q).abs() <= 10.0 / 255.0, "{p} != {q}");
+        }
+    }
+
+    #[test]
+    fn test_quantize_constant_shard() {
+        let params = [0.0; 8];
+        let mut buf = Vec::new();
+
+        params_quantize_into(&mut buf, &params, 4);
+
+        let mut got = Vec::new();
+        params_dequantize_into(&mut got, &buf).unwrap();
+
+        assert_eq!(got, params);
+    }
+
+    #[test]
+    fn test_dequantize_truncated_buffer() {
+        let params = [1.0, -1.0, 0.5, 0.25];
+        let mut buf = Vec::new();
+
+        params_quantize_into(&mut buf, &params, 2);
+        buf.pop();
+
+        let mut got = Vec::new();
+        assert!(params_dequantize_into(&mut got, &buf).is_err());
+    }
+}
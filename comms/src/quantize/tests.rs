@@ -0,0 +1,45 @@
+#![cfg(test)]
+
+use tokio::io;
+
+use crate::{
+    protocol::{Msg, Payload},
+    quantize,
+    transport::{Framer, TransportLayer},
+};
+
+#[tokio::test]
+async fn test_quantized_params() -> io::Result<()> {
+    const PARAMS_SIZE: usize = 16;
+    const SHARD_SIZE: usize = 4;
+
+    let (sv_stream, wk_stream) = io::duplex(1024);
+
+    let (rx, tx) = io::split(sv_stream);
+    let mut sv_transport = Framer::new(rx, tx);
+
+    let (rx, tx) = io::split(wk_stream);
+    let mut wk_transport = Framer::new(rx, tx);
+
+    let params: Vec<_> = (0..PARAMS_SIZE).map(|i| i as f32 / 4.0).collect();
+    let mut ser_buf = Vec::new();
+    quantize::params_quantize_into(&mut ser_buf, &params, SHARD_SIZE);
+
+    let msg = Msg::Data(Payload::QuantizedParams(&ser_buf));
+    sv_transport.send(&msg).await?;
+
+    let Msg::Data(Payload::QuantizedParams(quantized)) = wk_transport.recv().await? else {
+        panic!("Didn't receive quantized parameters");
+    };
+
+    let mut got = Vec::new();
+    quantize::params_dequantize_into(&mut got, quantized).map_err(io::Error::other)?;
+
+    assert_eq!(got.len(), PARAMS_SIZE);
+
+    for (p, q) in params.iter().zip(&got) {
+        assert!((p - q).abs() <= 4.0 / 255.0, "{p} != {q}");
+    }
+
+    Ok(())
+}
@@ -1,4 +1,7 @@
 mod protocol;
 mod tests;
 
-pub use protocol::{calculate_threshold, grad_drop_into, grad_lift_into};
+pub use protocol::{
+    calculate_threshold, calculate_top_k_threshold, grad_drop_into, grad_drop_max_len,
+    grad_lift_into,
+};
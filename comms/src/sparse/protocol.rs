@@ -48,6 +48,29 @@ pub fn calculate_threshold<R: Rng>(residual: &[f32], r: Float01, rng: &mut R) ->
     sample[k].max(MIN_POSITIVE_F16)
 }
 
+/// Calculates the exact threshold that keeps the top-k largest-magnitude values of the gradient.
+///
+/// # Args
+/// * `residual` - The residual gradient to use to calculate the threshold.
+/// * `density` - The fraction of the gradient's values to keep, `k = ceil(len * density)`.
+///
+/// # Returns
+/// The threshold to use with `grad_drop`.
+pub fn calculate_top_k_threshold(residual: &[f32], density: Float01) -> f32 {
+    if residual.is_empty() {
+        return 0.0;
+    }
+
+    let mut magnitudes: Vec<_> = residual.iter().map(|x| x.abs()).collect();
+
+    let k = (residual.len() as f32 * *density).ceil() as usize;
+    let k = k.clamp(1, residual.len());
+    let nth = residual.len() - k;
+    magnitudes.select_nth_unstable_by(nth, |a, b| a.total_cmp(b));
+
+    magnitudes[nth].max(MIN_POSITIVE_F16)
+}
+
 /// Serializes the given gradient into `buf` dropping any values with a magnitude lower than `threshold`.
 ///
 /// # Args
@@ -85,6 +108,18 @@ pub fn grad_drop_into(buf: &mut Vec<u8>, residual: &[f32], threshold: f32) {
     }
 }
 
+/// The largest amount of bytes `grad_drop_into` can produce for a gradient of `len` values,
+/// reached when the kept values alternate with the dropped ones.
+///
+/// # Args
+/// * `len` - The amount of values of the gradient.
+///
+/// # Returns
+/// The upper bound for the size of the sparse gradient.
+pub fn grad_drop_max_len(len: usize) -> usize {
+    TOTAL_LEN_SIZE + len.div_ceil(2) * (OFFSET_SIZE + CHUNK_LEN_SIZE) + len * size_of::<f16>()
+}
+
 /// Deserializes a sparse gradient into `grad`.
 ///
 /// # Args
@@ -169,6 +204,27 @@ mod tests {
         assert_eq!(buf, expected);
     }
 
+    #[test]
+    fn test_top_k_threshold() {
+        let residual = [0.5, -4.0, 1.0, 3.0, -2.0];
+        let density = Float01::new(0.4).unwrap();
+
+        let threshold = calculate_top_k_threshold(&residual, density);
+        assert_eq!(threshold, 3.0);
+
+        let kept = residual.iter().filter(|g| g.abs() >= threshold).count();
+        assert_eq!(kept, 2);
+    }
+
+    #[test]
+    fn test_top_k_threshold_keeps_at_least_one() {
+        let residual = [0.5, -4.0, 1.0];
+        let density = Float01::new(0.0).unwrap();
+
+        let threshold = calculate_top_k_threshold(&residual, density);
+        assert_eq!(threshold, 4.0);
+    }
+
     #[test]
     fn test_grad_lift() {
         let buf = vec![
@@ -54,3 +54,48 @@ async fn test_sparse_gradient() -> io::Result<()> {
     assert_eq!(grad, expected);
     Ok(())
 }
+
+#[test]
+fn test_top_k_error_feedback_converges_like_dense() {
+    const SIZE: usize = 64;
+    const STEPS: usize = 2000;
+    const LR: f32 = 0.1;
+
+    let target: Vec<_> = (0..SIZE).map(|i| (i as f32 - 32.0) / 8.0).collect();
+    let density = Float01::new(0.1).unwrap();
+
+    let mut dense = vec![0.0; SIZE];
+    let mut sparse_params = vec![0.0; SIZE];
+    let mut residual = vec![0.0; SIZE];
+    let mut grad = vec![0.0; SIZE];
+    let mut ser_buf = Vec::new();
+
+    for _ in 0..STEPS {
+        for (p, t) in dense.iter_mut().zip(&target) {
+            *p -= LR * (*p - t);
+        }
+
+        for ((r, p), t) in residual.iter_mut().zip(&sparse_params).zip(&target) {
+            *r += p - t;
+        }
+
+        ser_buf.clear();
+        let threshold = sparse::calculate_top_k_threshold(&residual, density);
+        sparse::grad_drop_into(&mut ser_buf, &residual, threshold);
+        sparse::grad_lift_into(&mut grad, &ser_buf).unwrap();
+
+        for (p, g) in sparse_params.iter_mut().zip(&grad) {
+            *p -= LR * g;
+        }
+
+        residual
+            .iter_mut()
+            .filter(|r| r.abs() >= threshold)
+            .for_each(|r| *r = 0.0);
+    }
+
+    for ((d, s), t) in dense.iter().zip(&sparse_params).zip(&target) {
+        assert!((d - t).abs() < 1e-3, "dense {d} didn't converge to {t}");
+        assert!((s - t).abs() < 1e-2, "top-k {s} didn't converge to {t}");
+    }
+}
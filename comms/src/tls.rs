@@ -0,0 +1,117 @@
+use std::{io, path::Path, sync::Arc};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadHalf, WriteHalf};
+use tokio_rustls::{
+    TlsAcceptor, TlsConnector, client, rustls,
+    rustls::{
+        ClientConfig, RootCertStore, ServerConfig,
+        pki_types::{CertificateDer, PrivateKeyDer, ServerName, pem::PemObject},
+    },
+    server,
+};
+
+/// The reading and writing ends of a TLS channel.
+pub type TlsChannel<S> = (ReadHalf<S>, WriteHalf<S>);
+
+/// Builds a TLS acceptor for the listening side of the communication.
+///
+/// # Args
+/// * `cert_path` - The path to the PEM encoded certificate chain.
+/// * `key_path` - The path to the PEM encoded private key of the certificate.
+///
+/// # Returns
+/// A new `TlsAcceptor` or an io error if the certificate or key are invalid.
+pub fn acceptor(cert_path: &Path, key_path: &Path) -> io::Result<TlsAcceptor> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .map_err(io::Error::other)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io::Error::other)?;
+
+    let key = PrivateKeyDer::from_pem_file(key_path).map_err(io::Error::other)?;
+
+    let config = ServerConfig::builder_with_provider(Arc::new(provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(io::Error::other)?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(io::Error::other)?;
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Builds a TLS connector for the connecting side of the communication.
+///
+/// # Args
+/// * `ca_path` - The path to the PEM encoded certificate authorities to trust.
+///
+/// # Returns
+/// A new `TlsConnector` or an io error if the certificate authorities are invalid.
+pub fn connector(ca_path: &Path) -> io::Result<TlsConnector> {
+    let mut roots = RootCertStore::empty();
+
+    for cert in CertificateDer::pem_file_iter(ca_path).map_err(io::Error::other)? {
+        let cert = cert.map_err(io::Error::other)?;
+        roots.add(cert).map_err(io::Error::other)?;
+    }
+
+    let config = ClientConfig::builder_with_provider(Arc::new(provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(io::Error::other)?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+/// Runs the listening side of the TLS handshake over `stream` and splits it into
+/// a channel. The message framing on top of it is the same as on a plain channel.
+///
+/// # Args
+/// * `stream` - The underlying stream.
+/// * `acceptor` - The TLS acceptor.
+///
+/// # Returns
+/// The encrypted reading and writing ends or an io error if the handshake failed.
+pub async fn accept_channel<S>(
+    stream: S,
+    acceptor: &TlsAcceptor,
+) -> io::Result<TlsChannel<server::TlsStream<S>>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let stream = acceptor.accept(stream).await?;
+    Ok(tokio::io::split(stream))
+}
+
+/// Runs the connecting side of the TLS handshake over `stream` and splits it into
+/// a channel. The message framing on top of it is the same as on a plain channel.
+///
+/// # Args
+/// * `stream` - The underlying stream.
+/// * `connector` - The TLS connector.
+/// * `server_name` - The name to verify the peer's certificate against.
+///
+/// # Returns
+/// The encrypted reading and writing ends or an io error if the handshake failed.
+pub async fn connect_channel<S>(
+    stream: S,
+    connector: &TlsConnector,
+    server_name: &str,
+) -> io::Result<TlsChannel<client::TlsStream<S>>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let server_name = ServerName::try_from(server_name.to_string())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let stream = connector.connect(server_name, stream).await?;
+    Ok(tokio::io::split(stream))
+}
+
+/// The cryptographic provider for every TLS configuration.
+///
+/// # Returns
+/// The `ring` crypto provider.
+fn provider() -> rustls::crypto::CryptoProvider {
+    rustls::crypto::ring::default_provider()
+}
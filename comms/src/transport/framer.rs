@@ -1,10 +1,10 @@
-use std::io;
+use std::{io, num::NonZeroUsize, time::Duration};
 
 use tokio::io::{AsyncRead, AsyncWrite};
 
 use super::TransportLayer;
 use crate::{
-    codec::{Sink, Source},
+    codec::{FlushStats, Sink, Source},
     protocol::Msg,
 };
 
@@ -65,4 +65,53 @@ where
     async fn send<'a>(&mut self, msg: &Msg<'a>) -> io::Result<()> {
         self.tx.send(msg).await
     }
+
+    /// Bounds the size of the frames on both ends of the communication.
+    ///
+    /// # Args
+    /// * `max_frame_size` - The maximum size in bytes of a frame, `None` to lift the bound.
+    fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.rx.set_max_frame_size(max_frame_size);
+        self.tx.set_max_frame_size(max_frame_size);
+    }
+
+    /// Splits the payloads sent through the inner sender into chunked frames.
+    ///
+    /// # Args
+    /// * `chunk_size` - The maximum size in bytes of a chunk, `None` to never split payloads.
+    fn set_chunk_size(&mut self, chunk_size: Option<NonZeroUsize>) {
+        self.tx.set_chunk_size(chunk_size);
+    }
+
+    /// Times the sends through the inner sender.
+    ///
+    /// # Args
+    /// * `threshold` - The duration over which a send is slow, `None` to stop timing the sends.
+    fn set_slow_send_threshold(&mut self, threshold: Option<Duration>) {
+        self.tx.set_slow_send_threshold(threshold);
+    }
+
+    /// The time the peer took to drain the sends through the inner sender.
+    ///
+    /// # Returns
+    /// The statistics of the timed sends.
+    fn flush_stats(&self) -> FlushStats {
+        self.tx.flush_stats()
+    }
+
+    /// The amount of bytes sent through the inner sender.
+    ///
+    /// # Returns
+    /// The total amount of sent bytes.
+    fn bytes_sent(&self) -> u64 {
+        self.tx.bytes_sent()
+    }
+
+    /// The amount of bytes received through the inner receiver.
+    ///
+    /// # Returns
+    /// The total amount of received bytes.
+    fn bytes_received(&self) -> u64 {
+        self.rx.bytes_received()
+    }
 }
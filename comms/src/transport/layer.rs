@@ -1,6 +1,6 @@
-use std::io;
+use std::{io, num::NonZeroUsize, time::Duration};
 
-use crate::protocol::Msg;
+use crate::{codec::FlushStats, protocol::Msg};
 
 /// The trait that the different transport layers should implement
 /// following a decorator pattern to easily add capabilities to the
@@ -22,4 +22,40 @@ pub trait TransportLayerTemplate {
     /// # Returns
     /// An io error if occurred.
     async fn send<'a>(&mut self, msg: &Msg<'a>) -> io::Result<()>;
+
+    /// Bounds the size of the frames sent and received through the layer.
+    ///
+    /// # Args
+    /// * `max_frame_size` - The maximum size in bytes of a frame, `None` to lift the bound.
+    fn set_max_frame_size(&mut self, max_frame_size: Option<usize>);
+
+    /// Splits the sent payloads into chunked frames of at most `chunk_size` bytes.
+    ///
+    /// # Args
+    /// * `chunk_size` - The maximum size in bytes of a chunk, `None` to never split payloads.
+    fn set_chunk_size(&mut self, chunk_size: Option<NonZeroUsize>);
+
+    /// Times the sends through the layer, counting the ones over `threshold` as slow.
+    ///
+    /// # Args
+    /// * `threshold` - The duration over which a send is slow, `None` to stop timing the sends.
+    fn set_slow_send_threshold(&mut self, threshold: Option<Duration>);
+
+    /// The time the peer took to drain the sends through the layer.
+    ///
+    /// # Returns
+    /// The statistics of the timed sends.
+    fn flush_stats(&self) -> FlushStats;
+
+    /// The amount of bytes sent through the layer.
+    ///
+    /// # Returns
+    /// The total amount of sent bytes.
+    fn bytes_sent(&self) -> u64;
+
+    /// The amount of bytes received through the layer.
+    ///
+    /// # Returns
+    /// The total amount of received bytes.
+    fn bytes_received(&self) -> u64;
 }
@@ -1,6 +1,7 @@
 mod framer;
 mod layer;
 mod retryer;
+mod tests;
 mod timeouter;
 
 use std::time::Duration;
@@ -9,10 +10,12 @@ pub use framer::Framer;
 pub use layer::TransportLayer;
 pub use retryer::Retryer;
 pub use timeouter::TimeOuter;
-use tokio::{
-    io::{AsyncRead, AsyncWrite},
-    net::tcp::{OwnedReadHalf, OwnedWriteHalf},
-};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::{NetReadHalf, NetWriteHalf, quantize, sparse};
+
+/// The room left in a frame for the control messages and the payloads' headers.
+const FRAME_OVERHEAD: usize = 1 << 16;
 
 /// The reliable transport.
 pub type Rtp<R, W> = Retryer<TimeOuter<Framer<R, W>>>;
@@ -20,8 +23,8 @@ pub type Rtp<R, W> = Retryer<TimeOuter<Framer<R, W>>>;
 /// The simple transport;
 pub type Stp<R, W> = Framer<R, W>;
 
-/// The network TCP reliable transport layer.
-pub type NetRtp = Rtp<OwnedReadHalf, OwnedWriteHalf>;
+/// The network reliable transport layer.
+pub type NetRtp = Rtp<NetReadHalf, NetWriteHalf>;
 
 /// Builds an uninitialized reliable transport.
 ///
@@ -29,6 +32,7 @@ pub type NetRtp = Rtp<OwnedReadHalf, OwnedWriteHalf>;
 /// * `reader` - The reading end of the communication.
 /// * `writer` - The writing end of the communication.
 /// * `timeout` - The timeout duration to wait for the receival of a message.
+/// * `send_timeout` - The timeout duration to wait for the peer to take a sent message.
 /// * `base_retry_dur` - The base duration for the exponential backoff retryer.
 /// * `retry_coef` - The coefficient to which to multiply the current wait duration.
 /// * `retries` - The amount of retries till declaring a dead node.
@@ -39,6 +43,7 @@ pub fn build_reliable_transport<R, W>(
     reader: R,
     writer: W,
     timeout: Duration,
+    send_timeout: Duration,
     base_retry_dur: Duration,
     retry_coef: u32,
     retries: usize,
@@ -48,7 +53,7 @@ where
     W: AsyncWrite + Unpin + Send,
 {
     let framer = Framer::new(reader, writer);
-    let timeouter = TimeOuter::new(timeout, framer);
+    let timeouter = TimeOuter::new(timeout, send_timeout, framer);
     Retryer::new(base_retry_dur, retry_coef, retries, timeouter)
 }
 
@@ -67,3 +72,19 @@ where
 {
     Framer::new(reader, writer)
 }
+
+/// Calculates the maximum size of the frames exchanged while training a model.
+///
+/// Sized after the largest encoding of the model's parameters, be it dense, sparse or quantized.
+///
+/// # Args
+/// * `nparams` - The amount of parameters of the model.
+///
+/// # Returns
+/// The maximum size in bytes of a frame.
+pub fn max_frame_size(nparams: usize) -> usize {
+    let dense = nparams * size_of::<f32>();
+    let sparse = sparse::grad_drop_max_len(nparams);
+    let quantized = quantize::params_quantize_max_len(nparams);
+    dense.max(sparse).max(quantized) + FRAME_OVERHEAD
+}
@@ -1,9 +1,9 @@
-use std::{io, mem, time::Duration};
+use std::{io, mem, num::NonZeroUsize, time::Duration};
 
 use tokio::time;
 
 use super::TransportLayer;
-use crate::protocol::Msg;
+use crate::{codec::FlushStats, protocol::Msg};
 
 /// The `Retryer` retries sending and receiving messages using exponential backoff.
 #[derive(Debug)]
@@ -101,6 +101,9 @@ impl<L: TransportLayer> TransportLayer for Retryer<L> {
 
         for _ in 0..self.retries {
             match self.inner.send(msg).await {
+                // The timed out send may have left part of the message in the stream,
+                // sending it again would interleave it with a partial copy.
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => return Err(e),
                 Err(e) if Self::is_retriable(&e) => {
                     time::sleep(sleep_dur).await;
                     sleep_dur = self.next_backoff(sleep_dur);
@@ -111,4 +114,52 @@ impl<L: TransportLayer> TransportLayer for Retryer<L> {
 
         self.inner.send(msg).await
     }
+
+    /// Bounds the size of the frames of the inner transport layer.
+    ///
+    /// # Args
+    /// * `max_frame_size` - The maximum size in bytes of a frame, `None` to lift the bound.
+    fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.inner.set_max_frame_size(max_frame_size);
+    }
+
+    /// Splits the payloads of the inner transport layer into chunked frames.
+    ///
+    /// # Args
+    /// * `chunk_size` - The maximum size in bytes of a chunk, `None` to never split payloads.
+    fn set_chunk_size(&mut self, chunk_size: Option<NonZeroUsize>) {
+        self.inner.set_chunk_size(chunk_size);
+    }
+
+    /// Times the sends of the inner transport layer.
+    ///
+    /// # Args
+    /// * `threshold` - The duration over which a send is slow, `None` to stop timing the sends.
+    fn set_slow_send_threshold(&mut self, threshold: Option<Duration>) {
+        self.inner.set_slow_send_threshold(threshold);
+    }
+
+    /// The time the peer took to drain the sends of the inner transport layer.
+    ///
+    /// # Returns
+    /// The statistics of the timed sends.
+    fn flush_stats(&self) -> FlushStats {
+        self.inner.flush_stats()
+    }
+
+    /// The amount of bytes sent through the inner transport layer.
+    ///
+    /// # Returns
+    /// The total amount of sent bytes.
+    fn bytes_sent(&self) -> u64 {
+        self.inner.bytes_sent()
+    }
+
+    /// The amount of bytes received through the inner transport layer.
+    ///
+    /// # Returns
+    /// The total amount of received bytes.
+    fn bytes_received(&self) -> u64 {
+        self.inner.bytes_received()
+    }
 }
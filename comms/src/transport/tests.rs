@@ -0,0 +1,307 @@
+#![cfg(test)]
+
+use std::{num::NonZeroUsize, time::Duration};
+
+use tokio::{
+    io::{self, AsyncWriteExt},
+    time,
+};
+use uuid::Uuid;
+
+use super::{Framer, TimeOuter, TransportLayer};
+use crate::{
+    ParamServerHandle, WorkerHandle,
+    protocol::{Msg, Payload},
+    specs::worker::{ConfusionCounts, MetricsReport, RegressionSums, TimeBreakdown},
+};
+
+const NPARAMS: usize = 1024;
+
+/// The size of a frame without any payload.
+fn empty_frame_size() -> usize {
+    let mut buf = Vec::new();
+    Msg::Data(Payload::QuantizedParams(&[])).serialize(&mut buf);
+    buf.len()
+}
+
+#[tokio::test]
+async fn test_frame_at_max_size_round_trips() -> io::Result<()> {
+    let max_frame_size = super::max_frame_size(NPARAMS);
+    let (sv_stream, wk_stream) = io::duplex(1 << 16);
+
+    let (rx, tx) = io::split(sv_stream);
+    let mut sv_transport = Framer::new(rx, tx);
+    sv_transport.set_max_frame_size(Some(max_frame_size));
+
+    let (rx, tx) = io::split(wk_stream);
+    let mut wk_transport = Framer::new(rx, tx);
+    wk_transport.set_max_frame_size(Some(max_frame_size));
+
+    let payload = vec![7; max_frame_size - empty_frame_size()];
+    let msg = Msg::Data(Payload::QuantizedParams(&payload));
+
+    let (sent, received) = tokio::join!(sv_transport.send(&msg), wk_transport.recv());
+    sent?;
+
+    let Msg::Data(Payload::QuantizedParams(got)) = received? else {
+        panic!("Didn't receive the payload");
+    };
+
+    assert_eq!(got, payload);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_frame_over_max_size_errors() -> io::Result<()> {
+    let max_frame_size = super::max_frame_size(NPARAMS);
+    let (sv_stream, wk_stream) = io::duplex(1 << 20);
+
+    let (rx, tx) = io::split(sv_stream);
+    let mut sv_transport = Framer::new(rx, tx);
+
+    let (rx, tx) = io::split(wk_stream);
+    let mut wk_transport = Framer::new(rx, tx);
+    wk_transport.set_max_frame_size(Some(max_frame_size));
+
+    let payload = vec![7; max_frame_size - empty_frame_size() + 1];
+    let msg = Msg::Data(Payload::QuantizedParams(&payload));
+
+    let e = wk_transport.send(&msg).await.unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    assert!(e.to_string().contains(&max_frame_size.to_string()));
+
+    sv_transport.send(&msg).await?;
+
+    let e = wk_transport.recv().await.unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    assert!(e.to_string().contains(&(max_frame_size + 1).to_string()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_oversized_length_prefix_errors_before_the_body_arrives() -> io::Result<()> {
+    let max_frame_size = super::max_frame_size(NPARAMS);
+    let (mut sv_stream, wk_stream) = io::duplex(1 << 16);
+
+    let (rx, tx) = io::split(wk_stream);
+    let mut wk_transport = Framer::new(rx, tx);
+    wk_transport.set_max_frame_size(Some(max_frame_size));
+
+    // Only the length prefix is written, waiting for the body would hang the test.
+    let len = 2 * max_frame_size as u64;
+    sv_stream.write_all(&len.to_be_bytes()).await?;
+
+    let e = time::timeout(Duration::from_secs(1), wk_transport.recv())
+        .await
+        .expect("the receiver waited for the body of an oversized frame")
+        .unwrap_err();
+
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    assert!(e.to_string().contains(&len.to_string()));
+    assert!(e.to_string().contains(&max_frame_size.to_string()));
+    assert!(e.to_string().contains("max_frame_size"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_times_out_when_the_peer_never_reads() -> io::Result<()> {
+    let send_timeout = Duration::from_millis(50);
+    let (_sv_stream, wk_stream) = io::duplex(1 << 10);
+
+    let (rx, tx) = io::split(wk_stream);
+    let mut wk_transport =
+        TimeOuter::new(Duration::from_secs(5), send_timeout, Framer::new(rx, tx));
+
+    // The payload doesn't fit in the peer's buffer and the peer never drains it.
+    let payload = vec![7; 1 << 16];
+    let msg = Msg::Data(Payload::QuantizedParams(&payload));
+
+    let e = time::timeout(Duration::from_secs(1), wk_transport.send(&msg))
+        .await
+        .expect("the sender hanged on a peer that never reads")
+        .unwrap_err();
+
+    assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+    Ok(())
+}
+
+#[test]
+fn test_max_frame_size_fits_every_encoding() {
+    let max_frame_size = super::max_frame_size(NPARAMS);
+
+    assert!(max_frame_size > NPARAMS * size_of::<f32>());
+    assert!(max_frame_size > crate::sparse::grad_drop_max_len(NPARAMS));
+    assert!(max_frame_size > crate::quantize::params_quantize_max_len(NPARAMS));
+}
+
+#[tokio::test]
+async fn test_chunked_params_are_reassembled() -> io::Result<()> {
+    let chunk_size = NonZeroUsize::new(1000).unwrap();
+    let (sv_stream, wk_stream) = io::duplex(1 << 16);
+
+    let (rx, tx) = io::split(sv_stream);
+    let mut sv_transport = Framer::new(rx, tx);
+    sv_transport.set_chunk_size(Some(chunk_size));
+
+    let (rx, tx) = io::split(wk_stream);
+    let mut wk_transport = Framer::new(rx, tx);
+    wk_transport.set_max_frame_size(Some(chunk_size.get()));
+
+    let mut params: Vec<_> = (0..NPARAMS).map(|i| i as f32).collect();
+    let expected = params.clone();
+    let msg = Msg::Data(Payload::Params(&mut params));
+
+    let (sent, received) = tokio::join!(sv_transport.send(&msg), wk_transport.recv());
+    sent?;
+
+    let Msg::Data(Payload::Params(got)) = received? else {
+        panic!("Didn't receive the params");
+    };
+
+    assert_eq!(got, expected);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chunked_params_with_wrong_len_error() -> io::Result<()> {
+    let (sv_stream, wk_stream) = io::duplex(1 << 16);
+
+    let (rx, tx) = io::split(sv_stream);
+    let mut worker_handle = WorkerHandle::new(Uuid::new_v4(), Framer::new(rx, tx));
+    worker_handle.set_chunk_size(NonZeroUsize::new(1000));
+
+    let (rx, tx) = io::split(wk_stream);
+    let mut server_handle = ParamServerHandle::new(Uuid::new_v4(), Framer::new(rx, tx));
+    server_handle.set_expected_params(Some(NPARAMS - 1));
+
+    let mut params = vec![1.; NPARAMS];
+    let (sent, received) = tokio::join!(
+        worker_handle.push_params(&mut params),
+        server_handle.pull_params()
+    );
+    sent?;
+
+    let e = received.unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    assert!(e.to_string().contains(&NPARAMS.to_string()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_slow_peer_sends_are_recorded() -> io::Result<()> {
+    let threshold = Duration::from_millis(20);
+    let (sv_stream, wk_stream) = io::duplex(64);
+
+    let (rx, tx) = io::split(sv_stream);
+    let mut sv_transport = Framer::new(rx, tx);
+    sv_transport.set_slow_send_threshold(Some(threshold));
+
+    let (rx, tx) = io::split(wk_stream);
+    let mut wk_transport = Framer::new(rx, tx);
+
+    let mut params = vec![1.; NPARAMS];
+    let msg = Msg::Data(Payload::Params(&mut params));
+
+    let slow_recv = async {
+        time::sleep(threshold * 3).await;
+        wk_transport.recv().await.map(|_| ())
+    };
+
+    let (sent, received) = tokio::join!(sv_transport.send(&msg), slow_recv);
+    sent?;
+    received?;
+
+    let stats = sv_transport.flush_stats();
+    assert_eq!(stats.sends, 1);
+    assert_eq!(stats.slow_sends, 1);
+    assert_eq!(stats.slow_streak, 1);
+    assert!(stats.last > threshold);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bytes_transferred_are_counted_on_both_ends() -> io::Result<()> {
+    let (sv_stream, wk_stream) = io::duplex(1 << 16);
+
+    let (rx, tx) = io::split(sv_stream);
+    let mut sv_transport = Framer::new(rx, tx);
+
+    let (rx, tx) = io::split(wk_stream);
+    let mut wk_transport = Framer::new(rx, tx);
+
+    let mut params = vec![1.; NPARAMS];
+    let msg = Msg::Data(Payload::Params(&mut params));
+
+    let (sent, received) = tokio::join!(sv_transport.send(&msg), wk_transport.recv());
+    sent?;
+    received?;
+
+    let frame_size = size_of::<u64>() + empty_frame_size() + NPARAMS * size_of::<f32>();
+    assert_eq!(sv_transport.bytes_sent(), frame_size as u64);
+    assert_eq!(wk_transport.bytes_received(), frame_size as u64);
+
+    sv_transport.set_chunk_size(NonZeroUsize::new(1000));
+    let (sent, received) = tokio::join!(sv_transport.send(&msg), wk_transport.recv());
+    sent?;
+    received?;
+
+    assert!(sv_transport.bytes_sent() > 2 * frame_size as u64);
+    assert_eq!(sv_transport.bytes_sent(), wk_transport.bytes_received());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_metrics_report_round_trips_with_a_diverged_loss() -> io::Result<()> {
+    let (sv_stream, wk_stream) = io::duplex(4096);
+
+    let (rx, tx) = io::split(sv_stream);
+    let mut sv_transport = Framer::new(rx, tx);
+
+    let (rx, tx) = io::split(wk_stream);
+    let mut wk_transport = Framer::new(rx, tx);
+
+    let timing = TimeBreakdown {
+        compute: Duration::from_millis(20),
+        ..Default::default()
+    };
+
+    let confusion = ConfusionCounts {
+        nclasses: 2,
+        counts: vec![3, 1, 0, 4],
+    };
+
+    let regression = RegressionSums {
+        n: 4,
+        target_mean: 2.5,
+        target_m2: 5.0,
+        rss: 0.5,
+        squared_log_error: f64::NAN,
+    };
+
+    let report = MetricsReport::new(3, vec![0.5, f64::NAN], 200.0, timing)
+        .with_bytes(1024, 2048)
+        .with_confusion(confusion.clone())
+        .with_regression(regression);
+    let msg = Msg::Data(Payload::Metrics(report));
+
+    let (sent, received) = tokio::join!(wk_transport.send(&msg), sv_transport.recv());
+    sent?;
+
+    let Msg::Data(Payload::Metrics(got)) = received? else {
+        panic!("Didn't receive the metrics");
+    };
+
+    assert_eq!(got.epoch, 3);
+    assert_eq!(got.losses[0], 0.5);
+    assert!(got.losses[1].is_nan());
+    assert_eq!(got.throughput, 200.0);
+    assert_eq!(got.timing, timing);
+    assert_eq!((got.bytes_sent, got.bytes_received), (1024, 2048));
+    assert_eq!(got.confusion, Some(confusion));
+
+    // SAFETY: The regression sums were just sent.
+    let got = got.regression.unwrap();
+    assert_eq!((got.n, got.target_mean, got.rss), (4, 2.5, 0.5));
+    assert!(got.squared_log_error.is_nan());
+    Ok(())
+}
@@ -1,15 +1,16 @@
-use std::{io, time::Duration};
+use std::{io, num::NonZeroUsize, time::Duration};
 
 use tokio::time;
 
 use super::TransportLayer;
-use crate::protocol::Msg;
+use crate::{codec::FlushStats, protocol::Msg};
 
-/// The `TimeOuter` tries receiving messages inside a time window.
+/// The `TimeOuter` tries receiving and sending messages inside a time window.
 /// If it fails it returns an error with `ErrorKind::TimedOut`.
 #[derive(Debug)]
 pub struct TimeOuter<L: TransportLayer> {
     timeout: Duration,
+    send_timeout: Duration,
     inner: L,
 }
 
@@ -18,12 +19,17 @@ impl<L: TransportLayer> TimeOuter<L> {
     ///
     /// # Args
     /// * `timeout` - The duration to wait until receiving a message and declaring an error.
+    /// * `send_timeout` - The duration to wait until the peer takes a message and declaring an error.
     /// * `inner` - The inner transport layer stack.
     ///
     /// # Returns
     /// A new `TimeOuter` transport layer instance.
-    pub fn new(timeout: Duration, inner: L) -> Self {
-        Self { timeout, inner }
+    pub fn new(timeout: Duration, send_timeout: Duration, inner: L) -> Self {
+        Self {
+            timeout,
+            send_timeout,
+            inner,
+        }
     }
 }
 
@@ -43,7 +49,12 @@ impl<L: TransportLayer> TransportLayer for TimeOuter<L> {
             })?
     }
 
-    /// Sends the given messaege as is.
+    /// Calls send on the inner transport layer setting it's timeout.
+    /// Returning an io error with `ErrorKind::TimedOut` if the peer stops
+    /// taking the message, such as when it's wedged with a full socket buffer.
+    ///
+    /// A send that timed out may have written part of the message, so the
+    /// stream can't be trusted to be sent through afterwards.
     ///
     /// # Args
     /// * `msg` - The message to send.
@@ -51,6 +62,59 @@ impl<L: TransportLayer> TransportLayer for TimeOuter<L> {
     /// # Returns
     /// An io error if occurred.
     async fn send<'a>(&mut self, msg: &Msg<'a>) -> io::Result<()> {
-        self.inner.send(msg).await
+        time::timeout(self.send_timeout, self.inner.send(msg))
+            .await
+            .map_err(|e| {
+                let text = format!("Peer took too long to take the message: {e}");
+                io::Error::new(io::ErrorKind::TimedOut, text)
+            })?
+    }
+
+    /// Bounds the size of the frames of the inner transport layer.
+    ///
+    /// # Args
+    /// * `max_frame_size` - The maximum size in bytes of a frame, `None` to lift the bound.
+    fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.inner.set_max_frame_size(max_frame_size);
+    }
+
+    /// Splits the payloads of the inner transport layer into chunked frames.
+    ///
+    /// # Args
+    /// * `chunk_size` - The maximum size in bytes of a chunk, `None` to never split payloads.
+    fn set_chunk_size(&mut self, chunk_size: Option<NonZeroUsize>) {
+        self.inner.set_chunk_size(chunk_size);
+    }
+
+    /// Times the sends of the inner transport layer.
+    ///
+    /// # Args
+    /// * `threshold` - The duration over which a send is slow, `None` to stop timing the sends.
+    fn set_slow_send_threshold(&mut self, threshold: Option<Duration>) {
+        self.inner.set_slow_send_threshold(threshold);
+    }
+
+    /// The time the peer took to drain the sends of the inner transport layer.
+    ///
+    /// # Returns
+    /// The statistics of the timed sends.
+    fn flush_stats(&self) -> FlushStats {
+        self.inner.flush_stats()
+    }
+
+    /// The amount of bytes sent through the inner transport layer.
+    ///
+    /// # Returns
+    /// The total amount of sent bytes.
+    fn bytes_sent(&self) -> u64 {
+        self.inner.bytes_sent()
+    }
+
+    /// The amount of bytes received through the inner transport layer.
+    ///
+    /// # Returns
+    /// The total amount of received bytes.
+    fn bytes_received(&self) -> u64 {
+        self.inner.bytes_received()
     }
 }
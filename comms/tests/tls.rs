@@ -0,0 +1,103 @@
+use std::{env, fs, io, path::PathBuf};
+
+use comms::{
+    TransportLayer,
+    protocol::{Command, Msg, Payload},
+    tls,
+};
+use tokio::net::{TcpListener, TcpStream};
+
+const SERVER_NAME: &str = "localhost";
+
+/// Writes a new self signed certificate and it's key to the temporary directory.
+fn self_signed_cert(name: &str) -> io::Result<(PathBuf, PathBuf)> {
+    let cert = rcgen::generate_simple_self_signed(vec![SERVER_NAME.to_string()])
+        .map_err(io::Error::other)?;
+
+    let dir = env::temp_dir();
+    let cert_path = dir.join(format!("{name}-{}.pem", std::process::id()));
+    let key_path = dir.join(format!("{name}-{}.key", std::process::id()));
+
+    fs::write(&cert_path, cert.cert.pem())?;
+    fs::write(&key_path, cert.key_pair.serialize_pem())?;
+    Ok((cert_path, key_path))
+}
+
+#[tokio::test]
+async fn test_tls_channel_keeps_framing() -> io::Result<()> {
+    let (cert_path, key_path) = self_signed_cert("ono-tls-framing")?;
+    let acceptor = tls::acceptor(&cert_path, &key_path)?;
+    let connector = tls::connector(&cert_path)?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    let server = async {
+        let (stream, _) = listener.accept().await?;
+        let (rx, tx) = tls::accept_channel(stream, &acceptor).await?;
+        let mut transport = comms::build_simple_transport(rx, tx);
+
+        let Msg::Data(Payload::Params(params)) = transport.recv().await? else {
+            panic!("Didn't receive params over the tls channel");
+        };
+
+        let params = params.to_vec();
+        transport.send(&Msg::Control(Command::Disconnect)).await?;
+        Ok::<_, io::Error>(params)
+    };
+
+    let client = async {
+        let stream = TcpStream::connect(addr).await?;
+        let (rx, tx) = tls::connect_channel(stream, &connector, SERVER_NAME).await?;
+        let mut transport = comms::build_simple_transport(rx, tx);
+
+        let mut params = [1.0, -2.5, 3.25];
+        transport
+            .send(&Msg::Data(Payload::Params(&mut params)))
+            .await?;
+
+        let msg = transport.recv().await?;
+        assert!(matches!(msg, Msg::Control(Command::Disconnect)));
+        Ok::<_, io::Error>(())
+    };
+
+    let (params, res) = tokio::join!(server, client);
+    res?;
+    assert_eq!(params?, [1.0, -2.5, 3.25]);
+
+    fs::remove_file(cert_path)?;
+    fs::remove_file(key_path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tls_channel_rejects_untrusted_cert() -> io::Result<()> {
+    let (cert_path, key_path) = self_signed_cert("ono-tls-server")?;
+    let (ca_path, ca_key_path) = self_signed_cert("ono-tls-other")?;
+    let acceptor = tls::acceptor(&cert_path, &key_path)?;
+    let connector = tls::connector(&ca_path)?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    let server = async {
+        let (stream, _) = listener.accept().await?;
+        tls::accept_channel(stream, &acceptor).await.map(|_| ())
+    };
+
+    let client = async {
+        let stream = TcpStream::connect(addr).await?;
+        tls::connect_channel(stream, &connector, SERVER_NAME)
+            .await
+            .map(|_| ())
+    };
+
+    let (_, res) = tokio::join!(server, client);
+    assert!(res.is_err());
+
+    for path in [cert_path, key_path, ca_path, ca_key_path] {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
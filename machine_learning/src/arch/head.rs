@@ -0,0 +1,61 @@
+use ndarray::{ArrayView, ArrayViewMut, Dimension};
+
+use super::{
+    layers::Layer,
+    loss::{CrossEntropy, LossFn, Mse},
+};
+
+/// The loss function of a `Head`, picked at runtime since every head can measure it's
+/// targets differently.
+#[derive(Clone)]
+pub enum HeadLoss {
+    Mse(Mse),
+    CrossEntropy(CrossEntropy),
+}
+
+impl LossFn for HeadLoss {
+    fn loss_prime<D>(
+        &mut self,
+        y_pred: ArrayView<f32, D>,
+        y: ArrayView<f32, D>,
+    ) -> (f64, ArrayViewMut<'_, f32, D>)
+    where
+        D: Dimension,
+    {
+        match self {
+            HeadLoss::Mse(loss_fn) => loss_fn.loss_prime(y_pred, y),
+            HeadLoss::CrossEntropy(loss_fn) => loss_fn.loss_prime(y_pred, y),
+        }
+    }
+}
+
+/// A branch of a multi task model, taking the output of the shared layers to predict
+/// some of the targets with it's own layers and loss.
+#[derive(Clone)]
+pub struct Head {
+    pub(super) layers: Vec<Layer>,
+    pub(super) loss_fn: HeadLoss,
+    pub(super) y_size: usize,
+    pub(super) weight: f32,
+}
+
+impl Head {
+    /// Creates a new `Head`.
+    ///
+    /// # Args
+    /// * `layers` - The head's own layers.
+    /// * `loss_fn` - The loss measuring the head's predictions.
+    /// * `y_size` - The amount of targets the head predicts.
+    /// * `weight` - How much the head's loss weighs in the model's.
+    ///
+    /// # Returns
+    /// A new `Head` instance.
+    pub fn new(layers: Vec<Layer>, loss_fn: HeadLoss, y_size: usize, weight: f32) -> Self {
+        Self {
+            layers,
+            loss_fn,
+            y_size,
+            weight,
+        }
+    }
+}
@@ -19,6 +19,7 @@ pub struct Conv2d {
     /// kernel_size)`
     kernels_dim: (usize, usize, usize, usize),
     size: usize,
+    frozen: bool,
 
     // Forward metadata
     real_input_dim: (usize, usize),
@@ -55,6 +56,7 @@ impl Conv2d {
             kernels_size,
             kernels_dim,
             size,
+            frozen: false,
             real_input_dim,
             effective_input: zeros4.clone(),
             output: zeros4.clone(),
@@ -67,6 +69,30 @@ impl Conv2d {
         self.size
     }
 
+    /// Stops the layer from computing the gradient of it's parameters, the backward
+    /// pass only propagates the delta and leaves the gradient untouched.
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    /// Whether the layer's parameters are frozen.
+    ///
+    /// # Returns
+    /// `true` if the backward pass skips the gradient of the parameters.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Frees the buffers kept between the forward and the backward passes.
+    pub fn release(&mut self) {
+        let zeros4 = Array4::zeros((1, 1, 1, 1));
+
+        self.effective_input = zeros4.clone();
+        self.output = zeros4.clone();
+        self.delta_out = zeros4.clone();
+        self.dilated = zeros4;
+    }
+
     pub fn forward(&mut self, params: &[f32], x: ArrayView4<f32>) -> Result<ArrayView4<'_, f32>> {
         let (k, b) = self.view_params(params)?;
 
@@ -148,6 +174,7 @@ impl Conv2d {
             filters,
             in_channels,
             padding,
+            frozen,
             real_input_dim,
             ref effective_input,
             ref mut delta_out,
@@ -157,7 +184,10 @@ impl Conv2d {
 
         let batch_size = effective_input.dim().0;
 
-        dk.fill(0.);
+        if !frozen {
+            dk.fill(0.);
+        }
+
         delta_out.reshape_inplace((
             effective_input.dim().0,
             effective_input.dim().1,
@@ -172,16 +202,18 @@ impl Conv2d {
 
                 for c_idx in 0..in_channels {
                     // kernel
-                    let effective_input_bc = effective_input.slice(s![b_idx, c_idx, .., ..]);
+                    if !frozen {
+                        let effective_input_bc = effective_input.slice(s![b_idx, c_idx, .., ..]);
 
-                    let dk_step = effective_input_bc.conv(
-                        dilated_bf.no_reverse(),
-                        ConvMode::Valid,
-                        PaddingMode::Zeros,
-                    )?;
+                        let dk_step = effective_input_bc.conv(
+                            dilated_bf.no_reverse(),
+                            ConvMode::Valid,
+                            PaddingMode::Zeros,
+                        )?;
 
-                    let mut dk_view = dk.slice_mut(s![f_idx, c_idx, .., ..]);
-                    dk_view += &dk_step;
+                        let mut dk_view = dk.slice_mut(s![f_idx, c_idx, .., ..]);
+                        dk_view += &dk_step;
+                    }
 
                     // delta
                     let k_fc = k.slice(s![f_idx, c_idx, .., ..]);
@@ -203,8 +235,10 @@ impl Conv2d {
             }
         }
 
-        let db_sum = d_in.sum_axis(Axis(0)).sum_axis(Axis(1)).sum_axis(Axis(1));
-        db.assign(&db_sum);
+        if !frozen {
+            let db_sum = d_in.sum_axis(Axis(0)).sum_axis(Axis(1)).sum_axis(Axis(1));
+            db.assign(&db_sum);
+        }
 
         Ok(delta_out.view_mut())
     }
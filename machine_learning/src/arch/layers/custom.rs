@@ -0,0 +1,126 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, OnceLock, PoisonError, RwLock},
+};
+
+use ndarray::{ArrayViewD, ArrayViewMutD};
+use serde_json::Value;
+
+use crate::{MlErr, Result};
+
+/// The constructor of a custom layer out of the parameters given in the model's specification.
+pub type LayerCtor = dyn Fn(&Value) -> Result<Box<dyn CustomLayer>> + Send + Sync;
+
+/// The custom layers registered in this process, keyed by their name.
+static REGISTRY: OnceLock<RwLock<HashMap<String, Arc<LayerCtor>>>> = OnceLock::new();
+
+/// A layer defined outside of this crate, built by name out of the layer registry.
+///
+/// The model calls it through a vtable once per pass, never per value, so it's dynamic
+/// dispatch costs about as much on the hot path as the match over the built-in layers.
+/// Implementing it takes no `unsafe`, it's `Send + Sync` bounds let the model be moved
+/// to the compute threads as any other. A layer without parameters acts as a custom
+/// activation function.
+pub trait CustomLayer: Debug + Send + Sync {
+    /// The amount of parameters the layer holds, the model lays out it's slice of the
+    /// parameters and the gradient by it.
+    ///
+    /// # Returns
+    /// The layer's parameter count.
+    fn size(&self) -> usize;
+
+    /// Performs a forward pass of the layer and returns a view of it's activation.
+    ///
+    /// # Args
+    /// * `params` - The layer's slice of the model's parameters.
+    /// * `x` - The input of the layer.
+    ///
+    /// # Returns
+    /// The layer's output or an error if occurred.
+    fn forward<'a>(
+        &'a mut self,
+        params: &[f32],
+        x: ArrayViewD<'a, f32>,
+    ) -> Result<ArrayViewD<'a, f32>>;
+
+    /// Performs a backward pass, writing the gradient of the layer's parameters and
+    /// returning the delta of it's input.
+    ///
+    /// # Args
+    /// * `params` - The layer's slice of the model's parameters.
+    /// * `grad` - The layer's slice of the model's gradient.
+    /// * `d` - The delta of the next layer.
+    ///
+    /// # Returns
+    /// The delta of this layer or an error if occurred.
+    fn backward<'a>(
+        &'a mut self,
+        params: &[f32],
+        grad: &mut [f32],
+        d: ArrayViewMutD<'a, f32>,
+    ) -> Result<ArrayViewMutD<'a, f32>>;
+
+    /// Frees the buffers the layer keeps between the forward and the backward passes.
+    fn release(&mut self) {}
+
+    /// Clones the layer behind a new box.
+    ///
+    /// # Returns
+    /// A boxed copy of the layer.
+    fn box_clone(&self) -> Box<dyn CustomLayer>;
+}
+
+impl Clone for Box<dyn CustomLayer> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Gives access to the registry, creating it empty the first time.
+///
+/// # Returns
+/// The process' layer registry.
+fn registry() -> &'static RwLock<HashMap<String, Arc<LayerCtor>>> {
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Registers a custom layer under a name, replacing the one registered under it before.
+///
+/// Every process building or laying out a model with the layer must register it, the
+/// nodes and the orchestrator alike.
+///
+/// # Args
+/// * `name` - The name the layer is specified by.
+/// * `ctor` - The constructor of the layer out of it's parameters.
+pub fn register_layer<F>(name: impl Into<String>, ctor: F)
+where
+    F: Fn(&Value) -> Result<Box<dyn CustomLayer>> + Send + Sync + 'static,
+{
+    registry()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(name.into(), Arc::new(ctor));
+}
+
+/// Builds a custom layer out of the registry.
+///
+/// # Args
+/// * `name` - The name the layer was registered under.
+/// * `params` - The parameters of the layer given in the model's specification.
+///
+/// # Returns
+/// The new layer, an `MlErr::UnknownLayer` if there's no layer registered under the name
+/// or the constructor's error.
+pub fn build_custom_layer(name: &str, params: &Value) -> Result<Box<dyn CustomLayer>> {
+    let ctor = registry()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(name)
+        .cloned()
+        .ok_or_else(|| MlErr::UnknownLayer {
+            name: name.to_string(),
+        })?;
+
+    ctor(params)
+}
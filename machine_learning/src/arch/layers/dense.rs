@@ -1,15 +1,26 @@
 use ndarray::{linalg, prelude::*};
 
 use super::InplaceReshape;
-use crate::{MlErr, Result};
+use crate::{MlErr, Result, datasets::CsrBatch};
+
+/// The weights a tied layer shares with an earlier one, see `Dense::tied`.
+#[derive(Clone, Debug)]
+struct Tied {
+    source: usize,
+    w: Array2<f32>,
+    dw: Array2<f32>,
+}
 
 #[derive(Clone, Debug)]
 pub struct Dense {
     dim: (usize, usize),
     size: usize,
+    tied: Option<Box<Tied>>,
+    frozen: bool,
 
     // Forward metadata
     input: Array2<f32>,
+    sparse_input: Option<CsrBatch>,
     w_sums: Array2<f32>,
 
     // Backward metadata
@@ -23,21 +34,126 @@ impl Dense {
         Self {
             dim,
             size: (dim.0 + 1) * dim.1,
+            tied: None,
+            frozen: false,
             input: zeros.clone(),
+            sparse_input: None,
             w_sums: zeros.clone(),
             delta: zeros,
         }
     }
 
+    /// Creates a new `Dense` whose weights are the transpose of an earlier layer's.
+    ///
+    /// The layer only holds it's biases, the weights are copied from the source's
+    /// parameters with `tie_weights` before every pass and their gradient is handed
+    /// back to the source's with `add_tied_grad`.
+    ///
+    /// # Args
+    /// * `dim` - The dimension of the layer, the transpose of the source's.
+    /// * `source` - The index of the source layer in the model.
+    ///
+    /// # Returns
+    /// A new `Dense` instance.
+    pub fn tied(dim: (usize, usize), source: usize) -> Self {
+        Self {
+            size: dim.1,
+            tied: Some(Box::new(Tied {
+                source,
+                w: Array2::zeros(dim),
+                dw: Array2::zeros(dim),
+            })),
+            ..Self::new(dim)
+        }
+    }
+
     pub fn size(&self) -> usize {
         self.size
     }
 
+    /// Stops the layer from computing the gradient of it's parameters, the backward
+    /// pass only propagates the delta and leaves the gradient untouched.
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    /// Whether the layer's parameters are frozen.
+    ///
+    /// # Returns
+    /// `true` if the backward pass skips the gradient of the parameters.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// The dimension of the layer.
+    ///
+    /// # Returns
+    /// The input and output dimensions.
+    pub fn dim(&self) -> (usize, usize) {
+        self.dim
+    }
+
+    /// The layer this one's weights are tied to.
+    ///
+    /// # Returns
+    /// The index of the source layer in the model, if the layer is tied.
+    pub fn tied_to(&self) -> Option<usize> {
+        self.tied.as_ref().map(|tied| tied.source)
+    }
+
+    /// Copies the weights of the source layer into this tied layer, transposed.
+    ///
+    /// # Args
+    /// * `source` - The parameters of the source layer.
+    ///
+    /// # Returns
+    /// An error if the source's parameters don't match the layer's transposed dimension.
+    pub fn tie_weights(&mut self, source: &[f32]) -> Result<()> {
+        let Some(tied) = &mut self.tied else {
+            return Ok(());
+        };
+
+        let w = Self::source_weights(self.dim, source)?;
+        tied.w.assign(&w.t());
+        Ok(())
+    }
+
+    /// Adds the weights' gradient of this tied layer onto the source layer's, transposed.
+    ///
+    /// # Args
+    /// * `source` - The gradient of the source layer.
+    ///
+    /// # Returns
+    /// An error if the source's gradient doesn't match the layer's transposed dimension.
+    pub fn add_tied_grad(&self, source: &mut [f32]) -> Result<()> {
+        let Some(tied) = &self.tied else {
+            return Ok(());
+        };
+
+        let mut dw = Self::source_weights_mut(self.dim, source)?;
+        dw += &tied.dw.t();
+        Ok(())
+    }
+
+    /// Frees the buffers kept between the forward and the backward passes.
+    pub fn release(&mut self) {
+        let zeros = Array2::zeros((1, 1));
+
+        self.input = zeros.clone();
+        self.sparse_input = None;
+        self.w_sums = zeros.clone();
+        self.delta = zeros;
+    }
+
     pub fn forward(&mut self, params: &[f32], x: ArrayView2<f32>) -> Result<ArrayView2<'_, f32>> {
         self.input.reshape_inplace(x.raw_dim());
         self.input.assign(&x);
+        self.sparse_input = None;
 
-        let (w, b) = self.view_params(params)?;
+        let (w, b) = match &self.tied {
+            Some(tied) => (tied.w.view(), self.view_biases(params)?),
+            None => self.view_params(params)?,
+        };
         let outer_shape = (x.nrows(), self.dim.1);
 
         self.w_sums.reshape_inplace(outer_shape);
@@ -47,17 +163,57 @@ impl Dense {
         Ok(self.w_sums.view())
     }
 
+    /// Performs a forward pass over a sparse input, skipping it's zero entries.
+    ///
+    /// The input is kept for the next `backward` to compute the weights' gradient
+    /// the same way, it still gets written into the dense gradient buffer. Keeping it
+    /// only clones the batch's handle to the matrix, not it's entries.
+    ///
+    /// # Args
+    /// * `params` - The parameters of the layer.
+    /// * `x` - The sparse input.
+    ///
+    /// # Returns
+    /// The weighted sums of the input or an error if occurred.
+    pub fn forward_csr(&mut self, params: &[f32], x: &CsrBatch) -> Result<ArrayView2<'_, f32>> {
+        let (w, b) = match &self.tied {
+            Some(tied) => (tied.w.view(), self.view_biases(params)?),
+            None => self.view_params(params)?,
+        };
+        let outer_shape = (x.nrows(), self.dim.1);
+
+        self.w_sums.reshape_inplace(outer_shape);
+        x.matmul_into(w, self.w_sums.view_mut())?;
+        self.w_sums += &b;
+        self.sparse_input = Some(x.clone());
+
+        Ok(self.w_sums.view())
+    }
+
     pub fn backward(
         &mut self,
         params: &[f32],
         grad: &mut [f32],
         d: ArrayViewMut2<f32>,
     ) -> Result<ArrayViewMut2<'_, f32>> {
-        let (mut dw, mut db) = self.view_grad(grad)?;
-        linalg::general_mat_mul(1.0, &self.input.t(), &d, 0.0, &mut dw);
-        db.assign(&d.sum_axis(Axis(0)));
+        if !self.frozen {
+            let (mut dw, mut db) = match &mut self.tied {
+                Some(tied) => (tied.dw.view_mut(), Self::view_biases_mut(self.dim, grad)?),
+                None => self.view_grad(grad)?,
+            };
+
+            match &self.sparse_input {
+                Some(x) => x.t_matmul_into(d.view(), dw)?,
+                None => linalg::general_mat_mul(1.0, &self.input.t(), &d, 0.0, &mut dw),
+            }
+
+            db.assign(&d.sum_axis(Axis(0)));
+        }
 
-        let (w, _) = self.view_params(params)?;
+        let w = match &self.tied {
+            Some(tied) => tied.w.view(),
+            None => self.view_params(params)?.0,
+        };
         self.delta.reshape_inplace((d.nrows(), w.nrows()));
         linalg::general_mat_mul(1.0, &d, &w.t(), 0.0, &mut self.delta);
 
@@ -116,11 +272,88 @@ impl Dense {
 
         Ok((dw, db))
     }
+
+    /// Gives a view of the raw parameter slice of a tied layer as it's biases.
+    ///
+    /// # Args
+    /// * `params` - A slice of parameters.
+    ///
+    /// # Returns
+    /// The biases or an error if there's a mismatch between the size of the
+    /// parameters and the size of the layer.
+    fn view_biases<'a>(&self, params: &'a [f32]) -> Result<ArrayView1<'a, f32>> {
+        if params.len() != self.size {
+            return Err(MlErr::size_mismatch("params", params.len(), self.size));
+        }
+
+        Ok(ArrayView1::from(params))
+    }
+
+    /// Gives a view of the raw gradient slice of a tied layer as it's delta biases.
+    ///
+    /// # Args
+    /// * `dim` - The dimension of the layer.
+    /// * `grad` - A gradient slice.
+    ///
+    /// # Returns
+    /// The delta biases or an error if there's a mismatch between the size of the
+    /// gradient and the size of the layer.
+    fn view_biases_mut(dim: (usize, usize), grad: &mut [f32]) -> Result<ArrayViewMut1<'_, f32>> {
+        if grad.len() != dim.1 {
+            return Err(MlErr::size_mismatch("grad", grad.len(), dim.1));
+        }
+
+        Ok(ArrayViewMut1::from(grad))
+    }
+
+    /// Gives a view of the parameters of a tied layer's source as it's weights.
+    ///
+    /// # Args
+    /// * `dim` - The dimension of the tied layer.
+    /// * `source` - The parameters of the source layer.
+    ///
+    /// # Returns
+    /// The source's weights or an error if the size of the source doesn't match
+    /// the tied layer's transposed dimension.
+    fn source_weights(dim: (usize, usize), source: &[f32]) -> Result<ArrayView2<'_, f32>> {
+        let size = (dim.1 + 1) * dim.0;
+        if source.len() != size {
+            return Err(MlErr::size_mismatch("tied params", source.len(), size));
+        }
+
+        // SAFETY: The if condition above checks that the size of the
+        //         source is exactly the size of the transposed layer.
+        Ok(ArrayView2::from_shape((dim.1, dim.0), &source[..dim.1 * dim.0]).unwrap())
+    }
+
+    /// Gives a view of the gradient of a tied layer's source as it's delta weights.
+    ///
+    /// # Args
+    /// * `dim` - The dimension of the tied layer.
+    /// * `source` - The gradient of the source layer.
+    ///
+    /// # Returns
+    /// The source's delta weights or an error if the size of the source doesn't
+    /// match the tied layer's transposed dimension.
+    fn source_weights_mut(
+        dim: (usize, usize),
+        source: &mut [f32],
+    ) -> Result<ArrayViewMut2<'_, f32>> {
+        let size = (dim.1 + 1) * dim.0;
+        if source.len() != size {
+            return Err(MlErr::size_mismatch("tied grad", source.len(), size));
+        }
+
+        // SAFETY: The if condition above checks that the size of the
+        //         source is exactly the size of the transposed layer.
+        Ok(ArrayViewMut2::from_shape((dim.1, dim.0), &mut source[..dim.1 * dim.0]).unwrap())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::datasets::CsrMatrix;
 
     #[test]
     fn test_dense_3by2_forward() {
@@ -195,4 +428,31 @@ mod tests {
         assert_eq!(db, expected_db);
         assert_eq!(dw, expected_dw);
     }
+
+    #[test]
+    fn test_dense_csr_matches_the_dense_path() {
+        let mut dense = Dense::new((4, 3));
+        let params: Vec<_> = (0..15).map(|i| i as f32 * 0.1 - 0.7).collect();
+
+        let x = [0.0, 0.9, 0.0, 0.0, 0.3, 0.0, 0.0, -0.6, 0.0, 0.0, 0.0, 0.0];
+        let csr = CsrBatch::from(CsrMatrix::from_dense(&x, 4).unwrap());
+        let x = ArrayView2::from_shape((3, 4), &x).unwrap();
+
+        let expected = dense.forward(&params, x).unwrap().to_owned();
+        let mut d = expected.mapv(|y| y - 0.5);
+        let mut expected_grad = vec![0.0; params.len()];
+        let expected_bp = dense
+            .backward(&params, &mut expected_grad, d.view_mut())
+            .unwrap()
+            .to_owned();
+
+        let y_pred = dense.forward_csr(&params, &csr).unwrap();
+        assert_eq!(y_pred, expected);
+
+        let mut grad = vec![0.0; params.len()];
+        let bp = dense.backward(&params, &mut grad, d.view_mut()).unwrap();
+
+        assert_eq!(bp, expected_bp);
+        assert_eq!(grad, expected_grad);
+    }
 }
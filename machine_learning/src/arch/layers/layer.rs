@@ -1,8 +1,8 @@
 use comms::floats::Float01;
 use ndarray::{Data, RawData, prelude::*};
 
-use super::{Conv2d, Dense, MaxPooling, ReLU, Sigmoid, Softmax, Tanh};
-use crate::{MlErr, Result, arch::layers::Reshape};
+use super::{Conv2d, CustomLayer, Dense, MaxPooling, ReLU, Sigmoid, Softmax, Tanh};
+use crate::{MlErr, Result, arch::layers::Reshape, datasets::CsrBatch};
 
 /// An indirection layer to prevent leaking the
 /// inner enum representation to the upper mods.
@@ -16,6 +16,7 @@ pub enum Inner {
     ReLU(ReLU),
     Softmax(Softmax),
     Reshape(Reshape),
+    Custom(Box<dyn CustomLayer>),
 }
 use Inner::*;
 
@@ -35,6 +36,21 @@ impl Layer {
         Self(Inner::Dense(Dense::new(dim)))
     }
 
+    /// Creates a new `Layer::Dense` layer whose weights are tied to an earlier dense layer.
+    ///
+    /// The layer uses the transpose of the source's weights and only holds it's own biases,
+    /// the model keeps both in sync, see `Sequential::forward`.
+    ///
+    /// # Args
+    /// * `dim` - The dimension of the layer, the transpose of the source's.
+    /// * `source` - The index of the source layer in the model.
+    ///
+    /// # Returns
+    /// A new `Layer` instance.
+    pub fn tied_dense(dim: (usize, usize), source: usize) -> Self {
+        Self(Inner::Dense(Dense::tied(dim, source)))
+    }
+
     /// Creates a new `Layer::Sigmoid` layer.
     ///
     /// # Args
@@ -46,6 +62,18 @@ impl Layer {
         Self(Inner::Sigmoid(Sigmoid::new(amp)))
     }
 
+    /// Creates a new `Layer::Sigmoid` layer clamping it's inputs.
+    ///
+    /// # Args
+    /// * `amp` - The amlitude of the sigmoid.
+    /// * `clamp` - The greatest magnitude of the inputs.
+    ///
+    /// # Returns
+    /// A new `Layer` instance.
+    pub fn clamped_sigmoid(amp: f32, clamp: f32) -> Self {
+        Self(Inner::Sigmoid(Sigmoid::new(amp).with_clamp(clamp)))
+    }
+
     /// Creates a new `Layer::Conv2d` layer.
     ///
     /// # Arguments
@@ -106,6 +134,18 @@ impl Layer {
         Self(Inner::Tanh(Tanh::new(amp)))
     }
 
+    /// Creates a new `Layer::Tanh` layer clamping it's inputs.
+    ///
+    /// # Args
+    /// * `amp` - The amplitude of the tanh.
+    /// * `clamp` - The greatest magnitude of the inputs.
+    ///
+    /// # Returns
+    /// A new `Layer` instance.
+    pub fn clamped_tanh(amp: f32, clamp: f32) -> Self {
+        Self(Inner::Tanh(Tanh::new(amp).with_clamp(clamp)))
+    }
+
     /// Creates a new `Layer::ReLU` layer.
     ///
     /// # Args
@@ -145,6 +185,17 @@ impl Layer {
         )))
     }
 
+    /// Creates a new `Layer::Custom` layer out of a layer defined outside of this crate.
+    ///
+    /// # Args
+    /// * `layer` - The custom layer, usually built out of the layer registry.
+    ///
+    /// # Returns
+    /// A new `Layer` instance.
+    pub fn custom(layer: Box<dyn CustomLayer>) -> Self {
+        Self(Inner::Custom(layer))
+    }
+
     /// The size of the layer.
     ///
     /// # Returns
@@ -159,6 +210,104 @@ impl Layer {
             MaxPooling(layer) => layer.size(),
             Softmax(layer) => layer.size(),
             Reshape(layer) => layer.size(),
+            Custom(layer) => layer.size(),
+        }
+    }
+
+    /// Freezes the layer's parameters, the backward pass stops computing their gradient.
+    ///
+    /// Layers without parameters have nothing to freeze.
+    pub fn freeze(&mut self) {
+        match &mut self.0 {
+            Dense(layer) => layer.freeze(),
+            Conv2d(layer) => layer.freeze(),
+            _ => {}
+        }
+    }
+
+    /// Whether the backward pass computes the gradient of the layer's parameters.
+    ///
+    /// # Returns
+    /// `true` if the layer has parameters and they aren't frozen.
+    pub fn is_trainable(&self) -> bool {
+        match &self.0 {
+            Dense(layer) => !layer.is_frozen(),
+            Conv2d(layer) => !layer.is_frozen(),
+            Custom(layer) => layer.size() > 0,
+            _ => false,
+        }
+    }
+
+    /// The layer this one's weights are tied to.
+    ///
+    /// # Returns
+    /// The index of the source layer in the model, if the layer is tied.
+    pub fn tied_to(&self) -> Option<usize> {
+        match &self.0 {
+            Dense(layer) => layer.tied_to(),
+            _ => None,
+        }
+    }
+
+    /// Whether this tied layer can share the weights of another layer.
+    ///
+    /// # Args
+    /// * `source` - The layer to take the weights of.
+    ///
+    /// # Returns
+    /// `true` if the source is an untied dense layer of the transposed dimension.
+    pub fn can_tie_to(&self, source: &Layer) -> bool {
+        match (&self.0, &source.0) {
+            (Dense(tied), Dense(source)) => {
+                let (rows, cols) = source.dim();
+                source.tied_to().is_none() && tied.dim() == (cols, rows)
+            }
+            _ => false,
+        }
+    }
+
+    /// Copies the weights of the source layer into this tied layer, untied layers are left untouched.
+    ///
+    /// # Args
+    /// * `source` - The parameters of the source layer.
+    ///
+    /// # Returns
+    /// An error if occurred.
+    pub fn tie_weights(&mut self, source: &[f32]) -> Result<()> {
+        match &mut self.0 {
+            Dense(layer) => layer.tie_weights(source),
+            _ => Ok(()),
+        }
+    }
+
+    /// Adds the weights' gradient of this tied layer onto the source layer's, untied layers add nothing.
+    ///
+    /// # Args
+    /// * `source` - The gradient of the source layer.
+    ///
+    /// # Returns
+    /// An error if occurred.
+    pub fn add_tied_grad(&self, source: &mut [f32]) -> Result<()> {
+        match &self.0 {
+            Dense(layer) => layer.add_tied_grad(source),
+            _ => Ok(()),
+        }
+    }
+
+    /// Frees the buffers the layer keeps between the forward and the backward passes.
+    ///
+    /// The next forward pass allocates them again.
+    pub fn release(&mut self) {
+        match &mut self.0 {
+            Dense(layer) => layer.release(),
+            Sigmoid(layer) => layer.release(),
+            Tanh(layer) => layer.release(),
+            ReLU(layer) => layer.release(),
+            Conv2d(layer) => layer.release(),
+            MaxPooling(layer) => layer.release(),
+            Softmax(layer) => layer.release(),
+            Reshape(_) => {}
+            Custom(layer) => layer.release(),
         }
     }
 
@@ -184,11 +333,40 @@ impl Layer {
             MaxPooling(layer) => layer.forward(try_cast_dim(x)?)?.into_dyn(),
             Softmax(layer) => layer.forward(try_cast_dim(x)?)?.into_dyn(),
             Reshape(layer) => layer.forward(x)?,
+            Custom(layer) => layer.forward(params, x)?,
         };
 
         Ok(y)
     }
 
+    /// Performs a forward pass of the layer over a sparse input.
+    ///
+    /// # Args
+    /// * `params` - The parameters to use for the forward pass.
+    /// * `x` - The sparse input that is to be *forwarded*.
+    ///
+    /// # Returns
+    /// The prediction for the given input `x` or an `MlErr::SparseInput` if the
+    /// layer isn't a dense one.
+    pub fn forward_csr(&mut self, params: &[f32], x: &CsrBatch) -> Result<ArrayViewD<'_, f32>> {
+        let layer = match &self.0 {
+            Dense(_) => "dense",
+            Sigmoid(_) => "sigmoid",
+            Tanh(_) => "tanh",
+            ReLU(_) => "relu",
+            Conv2d(_) => "conv2d",
+            MaxPooling(_) => "max pooling",
+            Softmax(_) => "softmax",
+            Reshape(_) => "reshape",
+            Custom(_) => "custom",
+        };
+
+        match &mut self.0 {
+            Dense(dense) => Ok(dense.forward_csr(params, x)?.into_dyn()),
+            _ => Err(MlErr::SparseInput { layer }),
+        }
+    }
+
     /// Performs a backward pass, writes the gradient of the delta with respect to the layer's portion of
     /// parameters and returns a view of its delta.
     ///
@@ -214,6 +392,7 @@ impl Layer {
             ReLU(layer) => layer.backward(try_cast_dim(d)?)?.into_dyn(),
             Softmax(layer) => layer.backward(try_cast_dim(d)?)?.into_dyn(),
             Reshape(layer) => layer.backward(try_cast_dim(d)?)?,
+            Custom(layer) => layer.backward(params, grad, d)?,
         };
 
         Ok(q)
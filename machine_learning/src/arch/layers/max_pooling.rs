@@ -45,6 +45,16 @@ impl MaxPooling {
         0
     }
 
+    /// Frees the buffers kept between the forward and the backward passes.
+    pub fn release(&mut self) {
+        let zeros4 = Array4::zeros((1, 1, 1, 1));
+
+        self.effective_input = zeros4.clone();
+        self.output = zeros4.clone();
+        self.delta_out = zeros4;
+        self.max_indices = Array4::from_elem((1, 1, 1, 1), (0, 0));
+    }
+
     pub fn forward(&mut self, x: ArrayView4<f32>) -> Result<ArrayView4<'_, f32>> {
         let Self {
             filter_size,
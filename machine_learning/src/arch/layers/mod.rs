@@ -1,4 +1,5 @@
 mod conv2d;
+mod custom;
 mod dense;
 mod layer;
 mod max_pooling;
@@ -11,6 +12,7 @@ mod tanh;
 use std::mem;
 
 pub(super) use conv2d::Conv2d;
+pub use custom::{CustomLayer, LayerCtor, build_custom_layer, register_layer};
 pub(super) use dense::Dense;
 pub use layer::{Inner, Layer};
 pub(super) use max_pooling::MaxPooling;
@@ -24,6 +24,11 @@ impl ReLU {
         0
     }
 
+    /// Frees the activations kept for the backward pass.
+    pub fn release(&mut self) {
+        self.activations = Array2::zeros((1, 1));
+    }
+
     pub fn forward(&mut self, x: ArrayView2<f32>) -> Result<ArrayView2<'_, f32>> {
         self.activations.reshape_inplace(x.raw_dim());
 
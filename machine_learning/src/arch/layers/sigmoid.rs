@@ -3,9 +3,14 @@ use std::f32;
 
 use crate::{Result, arch::layers::InplaceReshape};
 
+/// The sigmoid activation scaled by an amplitude, `amp * sigmoid(x)`.
+///
+/// The amplitude stretches the output range to `(0, amp)` while keeping the slope of the
+/// input, so the derivative is `amp * sigmoid(x) * (1 - sigmoid(x))`.
 #[derive(Clone, Debug, Default)]
 pub struct Sigmoid {
     amp: f32,
+    clamp: Option<f32>,
 
     // Forward metadata
     activations: Array2<f32>,
@@ -17,19 +22,44 @@ impl Sigmoid {
 
         Self {
             amp,
+            clamp: None,
             activations: zeros,
         }
     }
 
+    /// Clamps the inputs to `[-clamp, clamp]` before activating them, so large inputs
+    /// can't overflow into `NaN`s.
+    ///
+    /// The gradient is zeroed wherever the input reached the clamp, the derivative of the
+    /// saturated activation being close to zero there anyway.
+    ///
+    /// # Args
+    /// * `clamp` - The greatest magnitude of the inputs.
+    ///
+    /// # Returns
+    /// The modified `Sigmoid`.
+    pub fn with_clamp(mut self, clamp: f32) -> Self {
+        self.clamp = Some(clamp);
+        self
+    }
+
     pub fn size(&self) -> usize {
         0
     }
 
+    /// Frees the activations kept for the backward pass.
+    pub fn release(&mut self) {
+        self.activations = Array2::zeros((1, 1));
+    }
+
     pub fn forward(&mut self, x: ArrayView2<f32>) -> Result<ArrayView2<'_, f32>> {
         self.activations.reshape_inplace(x.raw_dim());
 
+        let amp = self.amp;
+        let clamp = self.clamp.unwrap_or(f32::INFINITY);
+
         azip!((a in &mut self.activations, &x_in in &x) {
-            *a = self.amp / (1.0 + (-x_in).exp());
+            *a = Self::activate(amp, x_in.clamp(-clamp, clamp));
         });
 
         Ok(self.activations.view())
@@ -40,11 +70,67 @@ impl Sigmoid {
         mut d: ArrayViewMut2<'a, f32>,
     ) -> Result<ArrayViewMut2<'a, f32>> {
         let one_over_amp = 1.0 / self.amp;
+        let (lo, hi) = self.saturation();
 
+        // With a = amp * sigmoid(x), the derivative is a * (1 - a / amp).
         azip!((d_in in &mut d, &a in &self.activations) {
-            *d_in *= a * (1.0 - a * one_over_amp);
+            if a <= lo || a >= hi {
+                *d_in = 0.0;
+            } else {
+                *d_in *= a * (1.0 - a * one_over_amp);
+            }
         });
 
         Ok(d)
     }
+
+    /// The scaled sigmoid of a single input.
+    fn activate(amp: f32, x: f32) -> f32 {
+        amp / (1.0 + (-x).exp())
+    }
+
+    /// The activations of the clamped inputs, unbounded if the inputs aren't clamped.
+    fn saturation(&self) -> (f32, f32) {
+        let Some(clamp) = self.clamp else {
+            return (f32::NEG_INFINITY, f32::INFINITY);
+        };
+
+        let low = Self::activate(self.amp, -clamp);
+        let high = Self::activate(self.amp, clamp);
+        (low.min(high), low.max(high))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+
+    #[test]
+    fn test_amplitude_scales_the_output_and_the_gradient() {
+        let x = array![[-1.0, 0.0, 2.0]];
+        let d = array![[1.0, 1.0, 1.0]];
+
+        let mut unit = Sigmoid::new(1.0);
+        let unit_out = unit.forward(x.view()).unwrap().to_owned();
+        let mut unit_d = d.clone();
+        let unit_grad = unit.backward(unit_d.view_mut()).unwrap().to_owned();
+
+        let mut scaled = Sigmoid::new(2.5);
+        let scaled_out = scaled.forward(x.view()).unwrap().to_owned();
+        let mut scaled_d = d.clone();
+        let scaled_grad = scaled.backward(scaled_d.view_mut()).unwrap().to_owned();
+
+        assert_eq!(scaled_out[[0, 1]], 1.25);
+
+        for ((&u, &s), (&du, &ds)) in unit_out
+            .iter()
+            .zip(&scaled_out)
+            .zip(unit_grad.iter().zip(&scaled_grad))
+        {
+            assert!((s - 2.5 * u).abs() < 1e-6);
+            assert!((ds - 2.5 * du).abs() < 1e-6);
+        }
+    }
 }
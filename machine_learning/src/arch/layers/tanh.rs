@@ -6,6 +6,7 @@ use crate::{Result, arch::layers::InplaceReshape};
 #[derive(Clone, Debug, Default)]
 pub struct Tanh {
     amp: f32,
+    clamp: Option<f32>,
 
     // Forward metadata
     activations: Array2<f32>,
@@ -17,21 +18,44 @@ impl Tanh {
 
         Self {
             amp,
+            clamp: None,
             activations: zeros,
         }
     }
 
+    /// Clamps the inputs to `[-clamp, clamp]` before activating them, so large inputs
+    /// can't overflow into `NaN`s.
+    ///
+    /// The gradient is zeroed wherever the input reached the clamp, the derivative of the
+    /// saturated activation being close to zero there anyway.
+    ///
+    /// # Args
+    /// * `clamp` - The greatest magnitude of the inputs.
+    ///
+    /// # Returns
+    /// The modified `Tanh`.
+    pub fn with_clamp(mut self, clamp: f32) -> Self {
+        self.clamp = Some(clamp);
+        self
+    }
+
     pub fn size(&self) -> usize {
         0
     }
 
+    /// Frees the activations kept for the backward pass.
+    pub fn release(&mut self) {
+        self.activations = Array2::zeros((1, 1));
+    }
+
     pub fn forward(&mut self, x: ArrayView2<f32>) -> Result<ArrayView2<'_, f32>> {
         self.activations.reshape_inplace(x.raw_dim());
 
+        let amp = self.amp;
+        let clamp = self.clamp.unwrap_or(f32::INFINITY);
+
         azip!((a in &mut self.activations, &x_in in &x) {
-            let exp = x_in.exp();
-            let neg_exp = (-x_in).exp();
-            *a = self.amp * (exp - neg_exp) / (exp + neg_exp);
+            *a = Self::activate(amp, x_in.clamp(-clamp, clamp));
         });
 
         Ok(self.activations.view())
@@ -42,11 +66,61 @@ impl Tanh {
         mut d: ArrayViewMut2<'a, f32>,
     ) -> Result<ArrayViewMut2<'a, f32>> {
         let one_over_amp = 1.0 / self.amp;
+        let (lo, hi) = self.saturation();
 
         azip!((d_in in &mut d, &a in &self.activations) {
-            *d_in *= self.amp - (a.powi(2) * one_over_amp);
+            if a <= lo || a >= hi {
+                *d_in = 0.0;
+            } else {
+                *d_in *= self.amp - (a.powi(2) * one_over_amp);
+            }
         });
 
         Ok(d)
     }
+
+    /// The scaled tanh of a single input.
+    fn activate(amp: f32, x: f32) -> f32 {
+        let exp = x.exp();
+        let neg_exp = (-x).exp();
+        amp * (exp - neg_exp) / (exp + neg_exp)
+    }
+
+    /// The activations of the clamped inputs, unbounded if the inputs aren't clamped.
+    fn saturation(&self) -> (f32, f32) {
+        let Some(clamp) = self.clamp else {
+            return (f32::NEG_INFINITY, f32::INFINITY);
+        };
+
+        let low = Self::activate(self.amp, -clamp);
+        let high = Self::activate(self.amp, clamp);
+        (low.min(high), low.max(high))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+
+    #[test]
+    fn test_clamping_keeps_large_inputs_from_overflowing() {
+        let x = array![[-1000.0, 0.5, 1000.0]];
+
+        let mut unclamped = Tanh::new(1.0);
+        let out = unclamped.forward(x.view()).unwrap();
+        assert!(out[[0, 0]].is_nan() && out[[0, 2]].is_nan());
+
+        let mut clamped = Tanh::new(2.0).with_clamp(20.0);
+        let out = clamped.forward(x.view()).unwrap().to_owned();
+        assert_eq!((out[[0, 0]], out[[0, 2]]), (-2.0, 2.0));
+        assert!((out[[0, 1]] - 2.0 * 0.5f32.tanh()).abs() < 1e-6);
+
+        let mut d = array![[1.0, 1.0, 1.0]];
+        let grad = clamped.backward(d.view_mut()).unwrap();
+        assert_eq!(grad[[0, 0]], 0.0);
+        assert_eq!(grad[[0, 2]], 0.0);
+        assert!((grad[[0, 1]] - 2.0 * (1.0 - 0.5f32.tanh().powi(2))).abs() < 1e-6);
+    }
 }
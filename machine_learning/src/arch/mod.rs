@@ -1,6 +1,10 @@
+mod head;
 pub mod layers;
 pub mod loss;
+mod model;
 mod sequential;
 
+pub use head::{Head, HeadLoss};
 pub use layers::InplaceReshape;
+pub use model::{BUNDLE_MODEL_FILE, BUNDLE_WEIGHTS_FILE, Model};
 pub use sequential::Sequential;
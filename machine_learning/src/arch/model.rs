@@ -0,0 +1,141 @@
+use std::{fs, path::Path};
+
+use comms::specs::machine_learning::LayerSpec;
+use ndarray::{ArrayD, ArrayViewD};
+use serde::Deserialize;
+
+use super::Sequential;
+use crate::{MlErr, Result, param_manager::ParamManager, training::TrainerBuilder};
+
+/// The name of the file describing the model's architecture inside a bundle.
+pub const BUNDLE_MODEL_FILE: &str = "model.json";
+
+/// The name of the file holding the model's parameters inside a bundle.
+pub const BUNDLE_WEIGHTS_FILE: &str = "weights.bin";
+
+/// The part of a bundle's `model.json` needed to rebuild the model, the rest is left unread.
+#[derive(Deserialize)]
+struct Manifest {
+    layers: Vec<LayerSpec>,
+}
+
+/// A trained model along with it's parameters, ready to make predictions.
+pub struct Model {
+    arch: Sequential,
+    params: Vec<f32>,
+    grad: Vec<f32>,
+    residual: Vec<f32>,
+    nlayers: usize,
+}
+
+impl Model {
+    /// Creates a new `Model`.
+    ///
+    /// # Args
+    /// * `arch` - The model's architecture.
+    /// * `params` - The model's parameters.
+    ///
+    /// # Returns
+    /// A new `Model` instance or a size mismatch if the architecture doesn't hold as many
+    /// parameters as given.
+    pub fn new(arch: Sequential, params: Vec<f32>) -> Result<Self> {
+        if params.len() != arch.size() {
+            return Err(MlErr::size_mismatch(
+                "model parameters",
+                params.len(),
+                arch.size(),
+            ));
+        }
+
+        let n = params.len();
+        let nlayers = arch.layer_sizes().len();
+
+        Ok(Self {
+            arch,
+            params,
+            grad: vec![0.; n],
+            residual: vec![0.; n],
+            nlayers,
+        })
+    }
+
+    /// Loads a model saved as a bundle, a directory holding a `model.json` with the
+    /// specification of it's layers and a `weights.bin` with it's parameters as raw
+    /// little endian `f32`s.
+    ///
+    /// # Args
+    /// * `dir` - The bundle's directory.
+    ///
+    /// # Returns
+    /// The loaded `Model`, an `MlErr::InvalidBundle` if the files can't be read or a size
+    /// mismatch if the weights don't fit the architecture.
+    pub fn load_bundle(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let read = |file: &str| {
+            let path = dir.join(file);
+            fs::read(&path).map_err(|e| MlErr::InvalidBundle {
+                reason: format!("can't read {}: {e}", path.display()),
+            })
+        };
+
+        let manifest: Manifest =
+            serde_json::from_slice(&read(BUNDLE_MODEL_FILE)?).map_err(|e| {
+                MlErr::InvalidBundle {
+                    reason: format!("malformed {BUNDLE_MODEL_FILE}: {e}"),
+                }
+            })?;
+
+        let bytes = read(BUNDLE_WEIGHTS_FILE)?;
+        if bytes.len() % size_of::<f32>() != 0 {
+            return Err(MlErr::InvalidBundle {
+                reason: format!(
+                    "{BUNDLE_WEIGHTS_FILE} holds {} bytes, not a whole amount of f32s",
+                    bytes.len()
+                ),
+            });
+        }
+
+        let params = bytes
+            .chunks_exact(size_of::<f32>())
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+
+        let arch = TrainerBuilder::new().build_model(&manifest.layers)?;
+        Self::new(arch, params)
+    }
+
+    /// Makes a prediction for the given input.
+    ///
+    /// # Args
+    /// * `x` - The input data.
+    ///
+    /// # Returns
+    /// The prediction or an error if the input doesn't fit the model.
+    pub fn predict(&mut self, x: ArrayViewD<f32>) -> Result<ArrayD<f32>> {
+        let mut param_manager = ParamManager::for_all_reduce(
+            &mut self.params,
+            &mut self.grad,
+            &mut self.residual,
+            self.nlayers,
+        );
+
+        let y_pred = self.arch.forward(&mut param_manager, x)?;
+        Ok(y_pred.to_owned())
+    }
+
+    /// The model's architecture.
+    ///
+    /// # Returns
+    /// A reference to the model's `Sequential`.
+    pub fn arch(&self) -> &Sequential {
+        &self.arch
+    }
+
+    /// The model's parameters.
+    ///
+    /// # Returns
+    /// The parameters as a flat slice.
+    pub fn params(&self) -> &[f32] {
+        &self.params
+    }
+}
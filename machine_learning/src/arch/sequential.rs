@@ -1,12 +1,42 @@
-use ndarray::{ArrayView2, ArrayViewD, ArrayViewMutD};
+use std::mem;
 
-use super::{layers::Layer, loss::LossFn};
-use crate::{MlErr, Result, optimization::Optimizer, param_manager::ParamManager};
+use ndarray::{ArrayD, ArrayView2, ArrayViewD, ArrayViewMutD, IxDyn, s};
+
+use super::{InplaceReshape, head::Head, layers::Layer, loss::LossFn};
+use crate::{
+    MlErr, Phase, Result,
+    datasets::{CsrBatch, Samples},
+    optimization::Optimizer,
+    param_manager::{BackIter, FrontIter, ParamManager},
+};
+
+/// The inputs kept by the gradient checkpointing, see `Sequential::with_checkpoints`.
+#[derive(Clone)]
+struct Checkpoints {
+    /// The bounds of the segments of layers recomputed together.
+    bounds: Vec<usize>,
+    /// The input of every segment.
+    inputs: Vec<ArrayD<f32>>,
+    delta: ArrayD<f32>,
+    spare: ArrayD<f32>,
+}
 
 /// A trainable model, this model's architecture is a sequence of trainable layers.
+///
+/// The sequence can branch into heads after it's layers, each predicting some of the
+/// targets with it's own loss. Their parameters follow the shared ones, in order.
+///
+/// A dense layer can tie it's weights to the transpose of another's, the source's
+/// parameter range then holds the weights of both and receives both their gradients.
 #[derive(Clone)]
 pub struct Sequential {
     layers: Vec<Layer>,
+    heads: Vec<Head>,
+    checkpoints: Option<Checkpoints>,
+    layer_sizes: Vec<usize>,
+    /// The tied layers along with their sources.
+    ties: Vec<(usize, usize)>,
+    delta: ArrayD<f32>,
 }
 
 impl Sequential {
@@ -18,7 +48,90 @@ impl Sequential {
     /// # Returns
     /// A new `Sequential` instance.
     pub fn new(layers: Vec<Layer>) -> Self {
-        Self { layers }
+        let layer_sizes = layers.iter().map(|layer| layer.size()).collect();
+
+        let mut model = Self {
+            layers,
+            heads: Vec::new(),
+            checkpoints: None,
+            layer_sizes,
+            ties: Vec::new(),
+            delta: ArrayD::zeros(IxDyn(&[1])),
+        };
+
+        model.ties = model.ties();
+        model
+    }
+
+    /// Checkpoints the gradient of the model's shared layers.
+    ///
+    /// Only the inputs of the checkpoint layers are kept through the forward pass, the
+    /// layers in between free their activations once they're done and go forward again
+    /// from the last checkpoint during the backward pass, trading compute for memory.
+    ///
+    /// # Args
+    /// * `checkpoints` - The indices of the checkpoint layers, the first one always is.
+    ///
+    /// # Returns
+    /// The modified `Sequential`.
+    pub fn with_checkpoints(mut self, checkpoints: impl IntoIterator<Item = usize>) -> Self {
+        let nlayers = self.layers.len();
+        let mut bounds: Vec<_> = checkpoints
+            .into_iter()
+            .filter(|&i| 0 < i && i < nlayers)
+            .collect();
+
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        self.checkpoints = (!bounds.is_empty()).then(|| {
+            bounds.insert(0, 0);
+            bounds.push(nlayers);
+
+            Checkpoints {
+                bounds,
+                inputs: Vec::new(),
+                delta: ArrayD::zeros(IxDyn(&[1])),
+                spare: ArrayD::zeros(IxDyn(&[1])),
+            }
+        });
+
+        self
+    }
+
+    /// Branches the model into heads after it's layers.
+    ///
+    /// The heads predict consecutive columns of the targets and the model's loss is the
+    /// weighted sum of theirs, the loss function given to the model is left unused.
+    ///
+    /// # Args
+    /// * `heads` - The heads of the model.
+    ///
+    /// # Returns
+    /// The modified `Sequential`.
+    pub fn with_heads(mut self, heads: Vec<Head>) -> Self {
+        self.heads = heads;
+        self.layer_sizes = self.layer_sizes();
+        self.ties = self.ties();
+        self
+    }
+
+    /// Checks that the source of every tied layer is an untied dense layer of the
+    /// transposed dimension.
+    ///
+    /// # Errors
+    /// An `MlErr::InvalidTie` for the first tied layer that can't share it's source's weights.
+    pub fn check_ties(&self) -> Result<()> {
+        for &(layer, source) in &self.ties {
+            let tied = self.all_layers().nth(layer);
+            let valid = tied.zip(self.all_layers().nth(source));
+
+            if !valid.is_some_and(|(tied, source)| tied.can_tie_to(source)) {
+                return Err(MlErr::InvalidTie { layer, source });
+            }
+        }
+
+        Ok(())
     }
 
     /// Calculates the amount of parameters in the model.
@@ -26,11 +139,21 @@ impl Sequential {
     /// # Returns
     /// The size of this model in the amount of parameters.
     pub fn size(&self) -> usize {
-        self.layers.iter().map(|layer| layer.size()).sum()
+        self.all_layers().map(|layer| layer.size()).sum()
+    }
+
+    /// Calculates the amount of parameters of each of the model's layers.
+    ///
+    /// # Returns
+    /// The size of every layer, in order.
+    pub fn layer_sizes(&self) -> Vec<usize> {
+        self.all_layers().map(|layer| layer.size()).collect()
     }
 
     /// Makes a forward pass through the network.
     ///
+    /// The heads, if any, are left out, the output is the one of the shared layers.
+    ///
     /// # Args
     /// * `param_manager` - The manager of parameters.
     /// * `x` - The input data.
@@ -43,24 +166,58 @@ impl Sequential {
     pub fn forward<'x, 'mw>(
         &'x mut self,
         param_manager: &mut ParamManager<'mw>,
-        mut x: ArrayViewD<'x, f32>,
+        x: ArrayViewD<'x, f32>,
     ) -> Result<ArrayViewD<'x, f32>> {
-        let mut front = param_manager.front();
-        let n = self.layers.len();
+        self.tie_weights(param_manager)?;
 
-        for (i, layer) in self.layers.iter_mut().enumerate() {
-            let params = front
-                .next(layer.size())
-                .ok_or(MlErr::size_mismatch("layers", i, n))?;
+        let n = self.nlayers();
+        let mut front = param_manager.front();
+        Self::forward_shared(
+            &mut self.layers,
+            self.checkpoints.as_mut(),
+            &mut front,
+            x,
+            n,
+        )
+    }
 
-            x = layer.forward(params, x)?;
-        }
+    /// Makes a forward pass through the network over a sparse input.
+    ///
+    /// Only the first layer sees the sparse input, so it must be a dense one. The following
+    /// `backward` computes it's weights' gradient skipping the zero entries as well.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of parameters.
+    /// * `x` - The sparse input data.
+    ///
+    /// # Errors
+    /// An error if there's a size mismatch between the layers' sizes and the parameter manager,
+    /// the first layer can't take a sparse input or the model keeps checkpoints.
+    ///
+    /// # Returns
+    /// The prediction for the given input or an error if occurred.
+    pub fn forward_csr<'x, 'mw>(
+        &'x mut self,
+        param_manager: &mut ParamManager<'mw>,
+        x: &CsrBatch,
+    ) -> Result<ArrayViewD<'x, f32>> {
+        self.tie_weights(param_manager)?;
 
-        Ok(x)
+        let n = self.nlayers();
+        let mut front = param_manager.front();
+        Self::forward_sparse(
+            &mut self.layers,
+            self.checkpoints.as_ref(),
+            &mut front,
+            x,
+            n,
+        )
     }
 
     /// Makes a backward pass through the network.
     ///
+    /// The heads, if any, are left out, the delta is the one of the shared layers' output.
+    ///
     /// # Args
     /// * `param_manager` - The manager of parameters.
     /// * `d` - The starting delta, the loss prime.
@@ -70,23 +227,102 @@ impl Sequential {
     ///
     /// # Returns
     /// An error if occurred.
-    pub fn backward<'d, 'mw>(
-        &'d mut self,
+    pub fn backward<'mw>(
+        &mut self,
         param_manager: &mut ParamManager<'mw>,
-        mut d: ArrayViewMutD<'d, f32>,
+        d: ArrayViewMutD<f32>,
     ) -> Result<()> {
-        let mut back = param_manager.back();
-        let n = self.layers.len();
+        let n = self.nlayers();
+        let Self {
+            layers,
+            checkpoints,
+            layer_sizes,
+            ..
+        } = self;
 
-        for (i, layer) in self.layers.iter_mut().rev().enumerate() {
-            let (params, grad) = back
-                .next(layer.size())
-                .ok_or(MlErr::size_mismatch("layers", i, n))?;
+        Self::backward_shared(
+            layers,
+            checkpoints.as_mut(),
+            layer_sizes,
+            param_manager,
+            d,
+            n,
+        )?;
+
+        self.add_tied_grads(param_manager)
+    }
+
+    /// Computes the model's loss over a batch without training on it.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of parameters.
+    /// * `loss_fn` - The loss function, unused if the model has heads.
+    /// * `x` - The batch's samples.
+    /// * `y` - The batch's expected outputs.
+    ///
+    /// # Errors
+    /// An error if there's a size mismatch between the layers' sizes and the parameter manager
+    ///
+    /// # Returns
+    /// The loss over the batch or an error if occurred.
+    pub fn evaluate<'mw, L>(
+        &mut self,
+        param_manager: &mut ParamManager<'mw>,
+        loss_fn: &mut L,
+        x: Samples<'_>,
+        y: ArrayView2<f32>,
+    ) -> Result<f64>
+    where
+        L: LossFn,
+    {
+        if !self.heads.is_empty() {
+            return self.heads_pass(param_manager, x, y, false);
+        }
 
-            d = layer.backward(params, grad, d)?;
+        let y_pred = self.forward_samples(param_manager, x)?;
+        Ok(loss_fn.loss(y_pred, y.into_dyn()))
+    }
+
+    /// Runs the model over some batches without training on them.
+    ///
+    /// Only the shared layers' output is a prediction of the targets, so a model with heads
+    /// isn't run.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of parameters.
+    /// * `batches` - The batches' samples along with their expected outputs.
+    /// * `f` - Called with the outputs of the model and the expected outputs of every batch,
+    ///   both in row major order.
+    ///
+    /// # Errors
+    /// An error if there's a size mismatch between the layers' sizes and the parameter manager
+    /// or `f` failed.
+    ///
+    /// # Returns
+    /// Whether the model was run, `false` if it has heads.
+    pub fn predict_batches<'d, I>(
+        &mut self,
+        param_manager: &mut ParamManager<'_>,
+        batches: I,
+        f: &mut dyn FnMut(&[f32], &[f32]) -> Result<()>,
+    ) -> Result<bool>
+    where
+        I: IntoIterator<Item = (Samples<'d>, ArrayView2<'d, f32>)>,
+    {
+        if !self.heads.is_empty() {
+            return Ok(false);
         }
 
-        Ok(())
+        for (x, y) in batches {
+            let y_pred = self.forward_samples(param_manager, x)?;
+            let y_pred = y_pred.as_standard_layout();
+            let y = y.as_standard_layout();
+
+            // SAFETY: Both arrays were just laid out in standard order.
+            f(y_pred.as_slice().unwrap(), y.as_slice().unwrap())?;
+        }
+
+        Ok(true)
     }
 
     /// Computes the gradient of the loss function with respect to the parameters of the model over
@@ -121,20 +357,24 @@ impl Sequential {
     where
         L: LossFn,
         O: Optimizer + Send,
-        I: Iterator<Item = (ArrayView2<'a, f32>, ArrayView2<'a, f32>)>,
+        I: Iterator<Item = (Samples<'a>, ArrayView2<'a, f32>)>,
     {
         let mut total_loss = 0.0;
         let mut num_batches: usize = 0;
 
         for (x, y) in batches {
-            let y_pred = self.forward(param_manager, x.into_dyn())?;
-            let (loss, mut d) = loss_fn.loss_prime(y_pred, y.into_dyn());
+            let loss = if self.heads.is_empty() {
+                let y_pred = self.forward_samples(param_manager, x)?;
+                let (loss, mut d) = loss_fn.loss_prime(y_pred, y.into_dyn());
+                self.backward(param_manager, d.view_mut())?;
+                loss
+            } else {
+                self.heads_pass(param_manager, x, y, true)?
+            };
 
             total_loss += loss;
             num_batches += 1;
 
-            self.backward(param_manager, d.view_mut())?;
-
             param_manager.optimize(optimizers)?;
             param_manager.acc_residual();
             param_manager.zero_grad();
@@ -146,4 +386,513 @@ impl Sequential {
 
         Ok(total_loss / num_batches as f64)
     }
+
+    /// Runs a batch through the shared layers and every head, computing the gradient of
+    /// the weighted sum of the heads' losses if asked to.
+    ///
+    /// Every head goes forward and backward before the next one, adding it's delta to the
+    /// one of the shared layers' output, which then goes backward once.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of parameters.
+    /// * `x` - The batch's samples.
+    /// * `y` - The batch's expected outputs, split between the heads.
+    /// * `backward` - Whether to compute the gradient.
+    ///
+    /// # Errors
+    /// An error if there's a size mismatch between the layers' sizes and the parameter manager
+    /// or between the heads' outputs and the expected ones.
+    ///
+    /// # Returns
+    /// The weighted sum of the heads' losses or an error if occurred.
+    fn heads_pass<'mw>(
+        &mut self,
+        param_manager: &mut ParamManager<'mw>,
+        x: Samples<'_>,
+        y: ArrayView2<f32>,
+        backward: bool,
+    ) -> Result<f64> {
+        let y_size: usize = self.heads.iter().map(|head| head.y_size).sum();
+        if y_size != y.ncols() {
+            return Err(MlErr::size_mismatch("head targets", y_size, y.ncols()));
+        }
+
+        self.tie_weights(param_manager)?;
+
+        let n = self.nlayers();
+        let Self {
+            layers,
+            heads,
+            checkpoints,
+            layer_sizes,
+            delta,
+            ..
+        } = self;
+
+        let mut first = layers.len();
+        let mut front = param_manager.front();
+        let stem = match &x {
+            Samples::Dense(x) => {
+                let x = x.view().reborrow().into_dyn();
+                Self::forward_shared(layers, checkpoints.as_mut(), &mut front, x, n)?
+            }
+            Samples::Sparse(x) => {
+                Self::forward_sparse(layers, checkpoints.as_ref(), &mut front, x, n)?
+            }
+        };
+        let mut total_loss = 0.0;
+        let mut col = 0;
+
+        for (k, head) in heads.iter_mut().enumerate() {
+            let Head {
+                layers: head_layers,
+                loss_fn,
+                y_size,
+                weight,
+            } = head;
+
+            let last = first + head_layers.len();
+            let mut front = param_manager.front();
+            Self::skip_front(&mut front, &layer_sizes[..first]);
+            let y_pred = Self::forward_layers(head_layers, &mut front, stem.view(), first, n)?;
+
+            let y_head = y.slice(s![.., col..col + *y_size]).into_dyn();
+            if y_pred.shape() != y_head.shape() {
+                let err = MlErr::size_mismatch("head outputs", y_pred.len(), y_head.len());
+                return Err(err.in_layer(last - 1, Phase::Forward));
+            }
+
+            let (loss, mut d) = loss_fn.loss_prime(y_pred, y_head);
+            total_loss += *weight as f64 * loss;
+
+            if backward {
+                d.mapv_inplace(|v| v * *weight);
+
+                let mut back = param_manager.back();
+                Self::skip_back(&mut back, &layer_sizes[last..]);
+                let d = Self::backward_layers(head_layers, &mut back, d, first, first, n)?;
+
+                if k == 0 {
+                    delta.reshape_inplace(d.raw_dim());
+                    delta.assign(&d);
+                } else {
+                    *delta += &d;
+                }
+            }
+
+            first = last;
+            col += *y_size;
+        }
+
+        if backward {
+            let d = delta.view_mut();
+            Self::backward_shared(
+                layers,
+                checkpoints.as_mut(),
+                layer_sizes,
+                param_manager,
+                d,
+                n,
+            )?;
+
+            self.add_tied_grads(param_manager)?;
+        }
+
+        Ok(total_loss)
+    }
+
+    /// Makes a forward pass through the network over a batch's samples, see `forward` and
+    /// `forward_csr`.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of parameters.
+    /// * `x` - The batch's samples.
+    ///
+    /// # Errors
+    /// An error if there's a size mismatch between the layers' sizes and the parameter manager
+    /// or the model can't take sparse samples.
+    ///
+    /// # Returns
+    /// The prediction for the given samples or an error if occurred.
+    fn forward_samples<'x, 's: 'x>(
+        &'x mut self,
+        param_manager: &mut ParamManager<'_>,
+        x: Samples<'s>,
+    ) -> Result<ArrayViewD<'x, f32>> {
+        match x {
+            Samples::Dense(x) => self.forward(param_manager, x.reborrow().into_dyn()),
+            Samples::Sparse(x) => self.forward_csr(param_manager, &x),
+        }
+    }
+
+    /// Makes a forward pass through the shared layers over a sparse input, the first
+    /// of them must be a dense one.
+    ///
+    /// The checkpoints only keep dense inputs, so a model with them can't take one.
+    ///
+    /// # Args
+    /// * `layers` - The shared layers.
+    /// * `checkpoints` - The gradient checkpointing's state, if enabled.
+    /// * `front` - The parameters' iterator.
+    /// * `x` - The sparse input data.
+    /// * `n` - The amount of layers in the model.
+    ///
+    /// # Errors
+    /// An error if there's a size mismatch between the layers' sizes and the parameter manager,
+    /// the first layer can't take a sparse input or there are checkpoints.
+    ///
+    /// # Returns
+    /// The output of the shared layers or an error if occurred.
+    fn forward_sparse<'x>(
+        layers: &'x mut [Layer],
+        checkpoints: Option<&Checkpoints>,
+        front: &mut FrontIter<'_, '_>,
+        x: &CsrBatch,
+        n: usize,
+    ) -> Result<ArrayViewD<'x, f32>> {
+        if checkpoints.is_some() {
+            let layer = "checkpointed";
+            return Err(MlErr::SparseInput { layer });
+        }
+
+        let (first, rest) = layers
+            .split_first_mut()
+            .ok_or(MlErr::size_mismatch("layers", 0, 1))?;
+
+        let params = front
+            .next(first.size())
+            .ok_or(MlErr::size_mismatch("layers", 0, n).in_layer(0, Phase::Forward))?;
+
+        let y = first
+            .forward_csr(params, x)
+            .map_err(|e| e.in_layer(0, Phase::Forward))?;
+
+        Self::forward_layers(rest, front, y, 1, n)
+    }
+
+    /// Makes a forward pass through the shared layers, keeping only the inputs of the
+    /// checkpoints if there are any.
+    ///
+    /// # Args
+    /// * `layers` - The shared layers.
+    /// * `checkpoints` - The gradient checkpointing's state, if enabled.
+    /// * `front` - The parameters' iterator.
+    /// * `x` - The input data.
+    /// * `n` - The amount of layers in the model.
+    ///
+    /// # Errors
+    /// An error if there's a size mismatch between the layers' sizes and the parameter manager
+    ///
+    /// # Returns
+    /// The output of the shared layers or an error if occurred.
+    fn forward_shared<'x>(
+        layers: &'x mut [Layer],
+        checkpoints: Option<&'x mut Checkpoints>,
+        front: &mut FrontIter<'_, '_>,
+        x: ArrayViewD<'x, f32>,
+        n: usize,
+    ) -> Result<ArrayViewD<'x, f32>> {
+        let Some(Checkpoints { bounds, inputs, .. }) = checkpoints else {
+            return Self::forward_layers(layers, front, x, 0, n);
+        };
+
+        let nsegments = bounds.len() - 1;
+        inputs.resize_with(nsegments, || ArrayD::zeros(IxDyn(&[1])));
+
+        let last_start = bounds[nsegments - 1];
+        let (segments, last) = layers.split_at_mut(last_start);
+        let mut inputs = inputs.iter_mut();
+
+        // SAFETY: There's an input for every segment.
+        let mut input = inputs.next().unwrap();
+        input.reshape_inplace(x.raw_dim());
+        input.assign(&x);
+
+        for (start, end) in bounds.iter().zip(&bounds[1..nsegments]) {
+            let segment = &mut segments[*start..*end];
+            let next = inputs.next().unwrap();
+            let y = Self::forward_layers(segment, front, input.view(), *start, n)?;
+
+            next.reshape_inplace(y.raw_dim());
+            next.assign(&y);
+            segment.iter_mut().for_each(Layer::release);
+            input = next;
+        }
+
+        let input: &'x ArrayD<f32> = input;
+        Self::forward_layers(last, front, input.view(), last_start, n)
+    }
+
+    /// Makes a backward pass through the shared layers, going forward again through
+    /// every segment between checkpoints before going backwards if there are any.
+    ///
+    /// # Args
+    /// * `layers` - The shared layers.
+    /// * `checkpoints` - The gradient checkpointing's state, if enabled.
+    /// * `layer_sizes` - The size of every layer of the model.
+    /// * `param_manager` - The manager of parameters.
+    /// * `d` - The delta of the shared layers' output.
+    /// * `n` - The amount of layers in the model.
+    ///
+    /// # Errors
+    /// An error if there's a size mismatch between the layers' sizes and the parameter manager
+    ///
+    /// # Returns
+    /// An error if occurred.
+    fn backward_shared<'d>(
+        layers: &'d mut [Layer],
+        checkpoints: Option<&'d mut Checkpoints>,
+        layer_sizes: &[usize],
+        param_manager: &mut ParamManager<'_>,
+        d: ArrayViewMutD<'d, f32>,
+        n: usize,
+    ) -> Result<()> {
+        let nlayers = layers.len();
+        let mut back = param_manager.back();
+        Self::skip_back(&mut back, &layer_sizes[nlayers..]);
+
+        // Nothing before the first trainable layer needs it's delta.
+        let stop = layers
+            .iter()
+            .position(Layer::is_trainable)
+            .unwrap_or(nlayers);
+
+        let Some(Checkpoints {
+            bounds,
+            inputs,
+            delta,
+            spare,
+        }) = checkpoints
+        else {
+            Self::backward_layers(layers, &mut back, d, 0, stop, n)?;
+            return Ok(());
+        };
+
+        // The last segment still holds it's activations.
+        let nsegments = bounds.len() - 1;
+        let last_start = bounds[nsegments - 1];
+        let (segments, last) = layers.split_at_mut(last_start);
+        let d = Self::backward_layers(last, &mut back, d, last_start, stop, n)?;
+
+        delta.reshape_inplace(d.raw_dim());
+        delta.assign(&d);
+
+        for (s, (&start, &end)) in bounds.iter().zip(&bounds[1..nsegments]).enumerate().rev() {
+            if end <= stop {
+                break;
+            }
+
+            let segment = &mut segments[start..end];
+
+            let mut front = param_manager.front();
+            Self::skip_front(&mut front, &layer_sizes[..start]);
+            Self::forward_layers(segment, &mut front, inputs[s].view(), start, n)?;
+
+            let mut back = param_manager.back();
+            Self::skip_back(&mut back, &layer_sizes[end..]);
+            let d = Self::backward_layers(segment, &mut back, delta.view_mut(), start, stop, n)?;
+
+            spare.reshape_inplace(d.raw_dim());
+            spare.assign(&d);
+            segment.iter_mut().for_each(Layer::release);
+            mem::swap(delta, spare);
+        }
+
+        Ok(())
+    }
+
+    /// Makes a forward pass through some of the model's layers.
+    ///
+    /// # Args
+    /// * `layers` - The layers to go through.
+    /// * `front` - The parameters' iterator, positioned at the first of the layers.
+    /// * `x` - The input data.
+    /// * `first` - The index of the first of the layers in the model.
+    /// * `n` - The amount of layers in the model.
+    ///
+    /// # Errors
+    /// An error if there's a size mismatch between the layers' sizes and the parameter manager
+    ///
+    /// # Returns
+    /// The output of the last layer or an error if occurred.
+    fn forward_layers<'x>(
+        layers: &'x mut [Layer],
+        front: &mut FrontIter<'_, '_>,
+        mut x: ArrayViewD<'x, f32>,
+        first: usize,
+        n: usize,
+    ) -> Result<ArrayViewD<'x, f32>> {
+        for (i, layer) in (first..).zip(layers) {
+            let params = front
+                .next(layer.size())
+                .ok_or(MlErr::size_mismatch("layers", i, n).in_layer(i, Phase::Forward))?;
+
+            x = layer
+                .forward(params, x)
+                .map_err(|e| e.in_layer(i, Phase::Forward))?;
+        }
+
+        Ok(x)
+    }
+
+    /// Makes a backward pass through some of the model's layers.
+    ///
+    /// # Args
+    /// * `layers` - The layers to go through.
+    /// * `back` - The parameters' iterator, positioned at the last of the layers.
+    /// * `d` - The delta of the last layer's output.
+    /// * `first` - The index of the first of the layers in the model.
+    /// * `stop` - The index of the first layer in the model going through the backward pass,
+    ///   the earlier ones have no gradient to compute.
+    /// * `n` - The amount of layers in the model.
+    ///
+    /// # Errors
+    /// An error if there's a size mismatch between the layers' sizes and the parameter manager
+    ///
+    /// # Returns
+    /// The delta of the last layer going through the backward pass or an error if occurred.
+    fn backward_layers<'d>(
+        layers: &'d mut [Layer],
+        back: &mut BackIter<'_, '_>,
+        mut d: ArrayViewMutD<'d, f32>,
+        first: usize,
+        stop: usize,
+        n: usize,
+    ) -> Result<ArrayViewMutD<'d, f32>> {
+        for (j, layer) in layers.iter_mut().enumerate().rev() {
+            let i = first + j;
+
+            if i < stop {
+                break;
+            }
+
+            let (params, grad) = back
+                .next(layer.size())
+                .ok_or(MlErr::size_mismatch("layers", n - 1 - i, n).in_layer(i, Phase::Backward))?;
+
+            d = layer
+                .backward(params, grad, d)
+                .map_err(|e| e.in_layer(i, Phase::Backward))?;
+        }
+
+        Ok(d)
+    }
+
+    /// Copies the weights of every tied layer's source into it.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of parameters.
+    ///
+    /// # Errors
+    /// An `MlErr::InvalidTie` if a tied layer's source isn't an untied dense layer of the
+    /// transposed dimension or a size mismatch with the parameter manager.
+    fn tie_weights(&mut self, param_manager: &mut ParamManager<'_>) -> Result<()> {
+        self.check_ties()?;
+        let n = self.nlayers();
+
+        for (layer, source) in self.ties.clone() {
+            let mut front = param_manager.front();
+            Self::skip_front(&mut front, &self.layer_sizes[..source]);
+            let params = front.next(self.layer_sizes[source]).ok_or(
+                MlErr::size_mismatch("layers", source, n).in_layer(source, Phase::Forward),
+            )?;
+
+            // SAFETY: The tied layers were checked above.
+            let tied = self.all_layers_mut().nth(layer).unwrap();
+            tied.tie_weights(params)
+                .map_err(|e| e.in_layer(layer, Phase::Forward))?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds the weights' gradient of every tied layer onto it's source's.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of parameters.
+    ///
+    /// # Errors
+    /// A size mismatch with the parameter manager.
+    fn add_tied_grads(&self, param_manager: &mut ParamManager<'_>) -> Result<()> {
+        let n = self.nlayers();
+
+        for &(layer, source) in &self.ties {
+            let mut back = param_manager.back();
+            Self::skip_back(&mut back, &self.layer_sizes[source + 1..]);
+            let (_, grad) = back.next(self.layer_sizes[source]).ok_or(
+                MlErr::size_mismatch("layers", n - 1 - source, n).in_layer(source, Phase::Backward),
+            )?;
+
+            // SAFETY: The tied layers are checked on every forward pass.
+            let tied = self.all_layers().nth(layer).unwrap();
+            tied.add_tied_grad(grad)
+                .map_err(|e| e.in_layer(layer, Phase::Backward))?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves the parameters' iterator past some layers from the front.
+    ///
+    /// # Args
+    /// * `front` - The parameters' iterator.
+    /// * `sizes` - The sizes of the layers to skip.
+    fn skip_front(front: &mut FrontIter<'_, '_>, sizes: &[usize]) {
+        for &size in sizes {
+            front.next(size);
+        }
+    }
+
+    /// Moves the parameters' iterator past some layers from the back.
+    ///
+    /// # Args
+    /// * `back` - The parameters' iterator.
+    /// * `sizes` - The sizes of the layers to skip, in the model's order.
+    fn skip_back(back: &mut BackIter<'_, '_>, sizes: &[usize]) {
+        for &size in sizes.iter().rev() {
+            back.next(size);
+        }
+    }
+
+    /// The amount of layers in the model, counting the heads'.
+    ///
+    /// # Returns
+    /// The model's layer count.
+    fn nlayers(&self) -> usize {
+        self.layers.len()
+            + self
+                .heads
+                .iter()
+                .map(|head| head.layers.len())
+                .sum::<usize>()
+    }
+
+    /// Iterates the model's layers, the heads' following the shared ones.
+    ///
+    /// # Returns
+    /// An iterator over every layer of the model.
+    fn all_layers(&self) -> impl Iterator<Item = &Layer> {
+        let heads = self.heads.iter().flat_map(|head| &head.layers);
+        self.layers.iter().chain(heads)
+    }
+
+    /// Iterates the model's layers mutably, the heads' following the shared ones.
+    ///
+    /// # Returns
+    /// A mutable iterator over every layer of the model.
+    fn all_layers_mut(&mut self) -> impl Iterator<Item = &mut Layer> {
+        let heads = self.heads.iter_mut().flat_map(|head| &mut head.layers);
+        self.layers.iter_mut().chain(heads)
+    }
+
+    /// Finds the model's tied layers.
+    ///
+    /// # Returns
+    /// The index of every tied layer along with the one of it's source.
+    fn ties(&self) -> Vec<(usize, usize)> {
+        self.all_layers()
+            .enumerate()
+            .filter_map(|(i, layer)| Some((i, layer.tied_to()?)))
+            .collect()
+    }
 }
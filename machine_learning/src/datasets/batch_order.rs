@@ -0,0 +1,70 @@
+use ndarray::ArrayView2;
+
+use super::{Dataset, Samples};
+
+/// Decides the rows of every batch of an epoch in place of a trainer's `Sampler`.
+///
+/// Lets the order come from outside of the trainer, like one shared by every worker of a
+/// distributed training where each only trains on it's own share of every batch.
+pub trait BatchOrder: Send {
+    /// The rows of every batch of an epoch.
+    ///
+    /// # Args
+    /// * `epoch` - The epoch the batches are for.
+    ///
+    /// # Returns
+    /// The indices of the dataset's rows of every batch, in the order they're trained on.
+    fn batches(&self, epoch: usize) -> Vec<Vec<usize>>;
+}
+
+/// The rows of a `Dataset` laid out in the batches of a `BatchOrder`.
+pub struct OrderedBatches {
+    dataset: Dataset,
+    sizes: Vec<usize>,
+}
+
+impl OrderedBatches {
+    /// Creates a new `OrderedBatches`.
+    ///
+    /// # Args
+    /// * `dataset` - The dataset to take the rows from, it must hold every row of the batches.
+    /// * `batches` - The indices of the rows of every batch, the empty ones are skipped.
+    ///
+    /// # Returns
+    /// A new `OrderedBatches` instance.
+    pub fn new(dataset: &Dataset, batches: &[Vec<usize>]) -> Self {
+        let sizes = batches
+            .iter()
+            .map(Vec::len)
+            .filter(|&size| size > 0)
+            .collect();
+
+        Self {
+            dataset: dataset.select(&batches.concat()),
+            sizes,
+        }
+    }
+
+    /// The amount of non empty batches.
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Whether there are no batches to train on.
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    /// Retrieves the batches in order.
+    ///
+    /// # Returns
+    /// An iterator over the batches in the form of tuples of their samples and an
+    /// `ArrayView2` of their labels.
+    pub fn iter(&self) -> impl Iterator<Item = (Samples<'_>, ArrayView2<'_, f32>)> + '_ {
+        self.sizes.iter().scan(0, |start, &size| {
+            let batch = self.dataset.view_batch(*start, size);
+            *start += size;
+            Some(batch)
+        })
+    }
+}
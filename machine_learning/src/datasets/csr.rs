@@ -0,0 +1,619 @@
+use std::{ops::Range, sync::Arc};
+
+use ndarray::prelude::*;
+
+use crate::{MlErr, Result};
+
+/// The density below which forwarding a `CsrMatrix` beats densifying it.
+///
+/// Measured on a release build against `ndarray`'s matrix multiplication, forwarding
+/// batches of 64 to 512 rows of 784 features into 32 to 128 outputs. Both paths took
+/// as long at around a 20% density, above it the dense kernel's vectorization makes up
+/// for the zeros it multiplies.
+pub const CSR_CROSSOVER_DENSITY: f32 = 0.15;
+
+/// A row major sparse matrix in compressed sparse row (CSR) format.
+///
+/// The non zero entries of the `i`-th row are `values[indptr[i]..indptr[i + 1]]`, and
+/// `indices` holds the column of each of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsrMatrix {
+    ncols: usize,
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    values: Vec<f32>,
+}
+
+impl CsrMatrix {
+    /// Creates a new `CsrMatrix`.
+    ///
+    /// # Args
+    /// * `ncols` - The amount of columns of the matrix.
+    /// * `indptr` - The offset of each row within `indices` and `values`, followed by their length.
+    /// * `indices` - The column of each of the non zero entries.
+    /// * `values` - The value of each of the non zero entries.
+    ///
+    /// # Returns
+    /// A new `CsrMatrix` instance.
+    ///
+    /// # Errors
+    /// An `MlErr::InvalidCsr` if the three buffers don't describe a valid matrix.
+    pub fn new(
+        ncols: usize,
+        indptr: Vec<usize>,
+        indices: Vec<usize>,
+        values: Vec<f32>,
+    ) -> Result<Self> {
+        if indices.len() != values.len() {
+            return Err(MlErr::size_mismatch(
+                "csr indices",
+                indices.len(),
+                values.len(),
+            ));
+        }
+
+        if indptr.first() != Some(&0) {
+            return Err(MlErr::invalid_csr("indptr must start with a 0"));
+        }
+
+        if indptr.windows(2).any(|w| w[0] > w[1]) {
+            return Err(MlErr::invalid_csr("indptr must be non decreasing"));
+        }
+
+        // SAFETY: The first check above ensures indptr isn't empty.
+        let nnz = *indptr.last().unwrap();
+        if nnz != values.len() {
+            return Err(MlErr::size_mismatch("csr values", values.len(), nnz));
+        }
+
+        if let Some(&col) = indices.iter().find(|&&col| col >= ncols) {
+            let reason = format!("column {col} is out of bounds for {ncols} columns");
+            return Err(MlErr::invalid_csr(reason));
+        }
+
+        Ok(Self {
+            ncols,
+            indptr,
+            indices,
+            values,
+        })
+    }
+
+    /// Compresses a row major dense matrix, dropping it's zero entries.
+    ///
+    /// # Args
+    /// * `data` - The entries of the matrix.
+    /// * `ncols` - The amount of columns of the matrix.
+    ///
+    /// # Returns
+    /// A new `CsrMatrix` instance.
+    ///
+    /// # Errors
+    /// An `MlErr::SizeMismatch` if `data` isn't made of whole rows.
+    pub fn from_dense(data: &[f32], ncols: usize) -> Result<Self> {
+        if ncols == 0 || !data.len().is_multiple_of(ncols) {
+            let expected = data.len().next_multiple_of(ncols.max(1));
+            return Err(MlErr::size_mismatch("csr dense data", data.len(), expected));
+        }
+
+        let mut indptr = vec![0];
+        let mut indices = Vec::new();
+        let mut values = Vec::new();
+
+        for row in data.chunks(ncols) {
+            for (col, &value) in row.iter().enumerate().filter(|(_, v)| **v != 0.0) {
+                indices.push(col);
+                values.push(value);
+            }
+
+            indptr.push(values.len());
+        }
+
+        Ok(Self {
+            ncols,
+            indptr,
+            indices,
+            values,
+        })
+    }
+
+    /// The amount of rows of the matrix.
+    pub fn nrows(&self) -> usize {
+        self.indptr.len() - 1
+    }
+
+    /// The amount of columns of the matrix.
+    pub fn ncols(&self) -> usize {
+        self.ncols
+    }
+
+    /// The amount of stored entries.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The fraction of the entries of the matrix that are stored.
+    ///
+    /// # Returns
+    /// A number between 0 and 1, compare it against `CSR_CROSSOVER_DENSITY` to pick a path.
+    pub fn density(&self) -> f32 {
+        let size = self.nrows() * self.ncols;
+
+        if size == 0 {
+            return 0.0;
+        }
+
+        self.nnz() as f32 / size as f32
+    }
+
+    /// The stored entries of a row.
+    ///
+    /// # Args
+    /// * `row` - The index of the row, must be less than `nrows`.
+    ///
+    /// # Returns
+    /// The columns and the values of the row's entries.
+    pub fn row(&self, row: usize) -> (&[usize], &[f32]) {
+        let range = self.indptr[row]..self.indptr[row + 1];
+        (&self.indices[range.clone()], &self.values[range])
+    }
+
+    /// Expands the matrix back into a dense one.
+    ///
+    /// # Returns
+    /// A `(nrows, ncols)` array.
+    pub fn to_dense(&self) -> Array2<f32> {
+        let mut dense = Array2::zeros((self.nrows(), self.ncols));
+
+        for (i, mut dense_row) in dense.rows_mut().into_iter().enumerate() {
+            let (cols, values) = self.row(i);
+
+            for (&col, &value) in cols.iter().zip(values) {
+                dense_row[col] = value;
+            }
+        }
+
+        dense
+    }
+
+    /// Copies some of the rows of the matrix into a new one, in the given order.
+    ///
+    /// # Args
+    /// * `rows` - The indices of the rows to copy, they can repeat.
+    ///
+    /// # Returns
+    /// A new `CsrMatrix` with the selected rows.
+    pub fn select(&self, rows: &[usize]) -> Self {
+        let mut indptr = Vec::with_capacity(rows.len() + 1);
+        let mut indices = Vec::new();
+        let mut values = Vec::new();
+        indptr.push(0);
+
+        for &row in rows {
+            let (cols, row_values) = self.row(row);
+            indices.extend_from_slice(cols);
+            values.extend_from_slice(row_values);
+            indptr.push(values.len());
+        }
+
+        Self {
+            ncols: self.ncols,
+            indptr,
+            indices,
+            values,
+        }
+    }
+
+    /// Appends the rows of another matrix with the same amount of columns after these ones.
+    ///
+    /// # Args
+    /// * `other` - The matrix whose rows to append.
+    pub fn append(&mut self, other: &Self) {
+        let offset = self.nnz();
+        let indptr = &other.indptr[1..];
+
+        self.indptr.extend(indptr.iter().map(|i| i + offset));
+        self.indices.extend_from_slice(&other.indices);
+        self.values.extend_from_slice(&other.values);
+    }
+
+    /// Consumes self and yields it's buffers.
+    ///
+    /// # Returns
+    /// The row offsets, the columns and the values of the stored entries.
+    pub fn into_parts(self) -> (Vec<usize>, Vec<usize>, Vec<f32>) {
+        (self.indptr, self.indices, self.values)
+    }
+
+    /// Packs some contiguous rows of a matrix into a buffer of `f32` words, to send them
+    /// through the same streams as a dense dataset's samples.
+    ///
+    /// The words hold the row offsets, rebased to start at 0, followed by the columns and
+    /// the values of the entries. The offsets and the columns are stored as the bits of
+    /// `u32`s, so they go through the wire untouched.
+    ///
+    /// # Args
+    /// * `indptr` - The offsets of the rows, followed by the end of the last one.
+    /// * `indices` - The columns of the rows' entries.
+    /// * `values` - The values of the rows' entries.
+    ///
+    /// # Returns
+    /// The packed rows.
+    ///
+    /// # Errors
+    /// An `MlErr::SizeMismatch` if the buffers don't hold the same amount of entries or an
+    /// `MlErr::InvalidCsr` if an offset or a column doesn't fit in a `u32`.
+    pub fn encode_rows(indptr: &[usize], indices: &[usize], values: &[f32]) -> Result<Vec<f32>> {
+        let (Some(&start), Some(&end)) = (indptr.first(), indptr.last()) else {
+            return Err(MlErr::invalid_csr("indptr must hold at least a 0"));
+        };
+
+        let nnz = end.saturating_sub(start);
+        if indices.len() != nnz || values.len() != nnz {
+            return Err(MlErr::size_mismatch("csr rows", values.len(), nnz));
+        }
+
+        let word = |n: usize| {
+            u32::try_from(n).map(f32::from_bits).map_err(|_| {
+                MlErr::invalid_csr(format!("{n} doesn't fit in the 32 bits of a word"))
+            })
+        };
+
+        let mut words = Vec::with_capacity(indptr.len() + 2 * nnz);
+
+        for &offset in indptr {
+            words.push(word(offset - start)?);
+        }
+
+        for &col in indices {
+            words.push(word(col)?);
+        }
+
+        words.extend_from_slice(values);
+        Ok(words)
+    }
+
+    /// Packs some contiguous rows of the matrix, see `CsrMatrix::encode_rows`.
+    ///
+    /// # Args
+    /// * `rows` - The range of the rows to pack, it must be within the matrix.
+    ///
+    /// # Returns
+    /// The packed rows.
+    ///
+    /// # Errors
+    /// An `MlErr::InvalidCsr` if an offset or a column doesn't fit in a `u32`.
+    pub fn to_words(&self, rows: Range<usize>) -> Result<Vec<f32>> {
+        let indptr = &self.indptr[rows.start..=rows.end];
+        let entries = indptr[0]..indptr[indptr.len() - 1];
+        Self::encode_rows(
+            indptr,
+            &self.indices[entries.clone()],
+            &self.values[entries],
+        )
+    }
+
+    /// Unpacks the rows packed by `CsrMatrix::encode_rows`.
+    ///
+    /// # Args
+    /// * `ncols` - The amount of columns of the matrix.
+    /// * `nrows` - The amount of packed rows.
+    /// * `words` - The packed rows.
+    ///
+    /// # Returns
+    /// A new `CsrMatrix` instance.
+    ///
+    /// # Errors
+    /// An `MlErr::SizeMismatch` if there are more or less words than the rows take or an
+    /// `MlErr::InvalidCsr` if they don't describe a valid matrix.
+    pub fn from_words(ncols: usize, nrows: usize, words: &[f32]) -> Result<Self> {
+        let Some(nnz) = words.get(nrows).map(|word| word.to_bits() as usize) else {
+            return Err(MlErr::size_mismatch("csr words", words.len(), nrows + 1));
+        };
+
+        let expected = nrows + 1 + 2 * nnz;
+        if words.len() != expected {
+            return Err(MlErr::size_mismatch("csr words", words.len(), expected));
+        }
+
+        let (indptr, rest) = words.split_at(nrows + 1);
+        let (indices, values) = rest.split_at(nnz);
+        let as_usize = |words: &[f32]| words.iter().map(|w| w.to_bits() as usize).collect();
+
+        Self::new(ncols, as_usize(indptr), as_usize(indices), values.to_vec())
+    }
+
+    /// Multiplies the matrix by a dense one, skipping the zero entries.
+    ///
+    /// # Args
+    /// * `w` - A `(ncols, k)` matrix.
+    /// * `out` - The `(nrows, k)` buffer to write `self * w` into.
+    ///
+    /// # Errors
+    /// An `MlErr::SizeMismatch` if the shapes don't agree.
+    pub fn matmul_into(&self, w: ArrayView2<f32>, out: ArrayViewMut2<f32>) -> Result<()> {
+        self.matmul_rows(0..self.nrows(), w, out)
+    }
+
+    /// Multiplies the transpose of the matrix by a dense one, skipping the zero entries.
+    ///
+    /// # Args
+    /// * `d` - A `(nrows, k)` matrix.
+    /// * `out` - The `(ncols, k)` buffer to write `self^T * d` into.
+    ///
+    /// # Errors
+    /// An `MlErr::SizeMismatch` if the shapes don't agree.
+    pub fn t_matmul_into(&self, d: ArrayView2<f32>, out: ArrayViewMut2<f32>) -> Result<()> {
+        self.t_matmul_rows(0..self.nrows(), d, out)
+    }
+
+    /// Multiplies some contiguous rows of the matrix by a dense one.
+    ///
+    /// # Args
+    /// * `rows` - The range of the rows to multiply, it must be within the matrix.
+    /// * `w` - A `(ncols, k)` matrix.
+    /// * `out` - The `(rows.len(), k)` buffer to write the product into.
+    ///
+    /// # Errors
+    /// An `MlErr::SizeMismatch` if the shapes don't agree.
+    fn matmul_rows(
+        &self,
+        rows: Range<usize>,
+        w: ArrayView2<f32>,
+        mut out: ArrayViewMut2<f32>,
+    ) -> Result<()> {
+        if w.nrows() != self.ncols {
+            return Err(MlErr::size_mismatch(
+                "csr matmul rhs",
+                w.nrows(),
+                self.ncols,
+            ));
+        }
+
+        if out.dim() != (rows.len(), w.ncols()) {
+            return Err(MlErr::size_mismatch(
+                "csr matmul out",
+                out.nrows(),
+                rows.len(),
+            ));
+        }
+
+        out.fill(0.0);
+
+        for (i, mut out_row) in rows.zip(out.rows_mut()) {
+            let (cols, values) = self.row(i);
+
+            for (&col, &value) in cols.iter().zip(values) {
+                out_row.scaled_add(value, &w.row(col));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Multiplies the transpose of some contiguous rows of the matrix by a dense one.
+    ///
+    /// # Args
+    /// * `rows` - The range of the rows to multiply, it must be within the matrix.
+    /// * `d` - A `(rows.len(), k)` matrix.
+    /// * `out` - The `(ncols, k)` buffer to write the product into.
+    ///
+    /// # Errors
+    /// An `MlErr::SizeMismatch` if the shapes don't agree.
+    fn t_matmul_rows(
+        &self,
+        rows: Range<usize>,
+        d: ArrayView2<f32>,
+        mut out: ArrayViewMut2<f32>,
+    ) -> Result<()> {
+        if d.nrows() != rows.len() {
+            return Err(MlErr::size_mismatch(
+                "csr t_matmul rhs",
+                d.nrows(),
+                rows.len(),
+            ));
+        }
+
+        if out.dim() != (self.ncols, d.ncols()) {
+            return Err(MlErr::size_mismatch(
+                "csr t_matmul out",
+                out.nrows(),
+                self.ncols,
+            ));
+        }
+
+        out.fill(0.0);
+
+        for (i, d_row) in rows.zip(d.rows()) {
+            let (cols, values) = self.row(i);
+
+            for (&col, &value) in cols.iter().zip(values) {
+                out.row_mut(col).scaled_add(value, &d_row);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A batch of contiguous rows of a shared `CsrMatrix`.
+///
+/// Cloning it only clones the handle to the matrix, so a layer can keep it's sparse input
+/// around for the backward pass without copying the entries.
+#[derive(Debug, Clone)]
+pub struct CsrBatch {
+    matrix: Arc<CsrMatrix>,
+    rows: Range<usize>,
+}
+
+impl CsrBatch {
+    /// Creates a new `CsrBatch`.
+    ///
+    /// # Args
+    /// * `matrix` - The matrix holding the rows.
+    /// * `rows` - The range of the batch's rows.
+    ///
+    /// # Returns
+    /// A new `CsrBatch` instance.
+    ///
+    /// # Errors
+    /// An `MlErr::SizeMismatch` if the range isn't within the matrix.
+    pub fn new(matrix: Arc<CsrMatrix>, rows: Range<usize>) -> Result<Self> {
+        if rows.start > rows.end || rows.end > matrix.nrows() {
+            return Err(MlErr::size_mismatch(
+                "csr batch rows",
+                rows.end,
+                matrix.nrows(),
+            ));
+        }
+
+        Ok(Self { matrix, rows })
+    }
+
+    /// The amount of rows of the batch.
+    pub fn nrows(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The amount of columns of the batch.
+    pub fn ncols(&self) -> usize {
+        self.matrix.ncols()
+    }
+
+    /// Expands the batch into a dense one.
+    ///
+    /// # Returns
+    /// A `(nrows, ncols)` array.
+    pub fn to_dense(&self) -> Array2<f32> {
+        let mut dense = Array2::zeros((self.nrows(), self.ncols()));
+
+        for (i, mut dense_row) in self.rows.clone().zip(dense.rows_mut()) {
+            let (cols, values) = self.matrix.row(i);
+
+            for (&col, &value) in cols.iter().zip(values) {
+                dense_row[col] = value;
+            }
+        }
+
+        dense
+    }
+
+    /// Multiplies the batch by a dense matrix, see `CsrMatrix::matmul_into`.
+    ///
+    /// # Args
+    /// * `w` - A `(ncols, k)` matrix.
+    /// * `out` - The `(nrows, k)` buffer to write `self * w` into.
+    ///
+    /// # Errors
+    /// An `MlErr::SizeMismatch` if the shapes don't agree.
+    pub fn matmul_into(&self, w: ArrayView2<f32>, out: ArrayViewMut2<f32>) -> Result<()> {
+        self.matrix.matmul_rows(self.rows.clone(), w, out)
+    }
+
+    /// Multiplies the transpose of the batch by a dense matrix, see `CsrMatrix::t_matmul_into`.
+    ///
+    /// # Args
+    /// * `d` - A `(nrows, k)` matrix.
+    /// * `out` - The `(ncols, k)` buffer to write `self^T * d` into.
+    ///
+    /// # Errors
+    /// An `MlErr::SizeMismatch` if the shapes don't agree.
+    pub fn t_matmul_into(&self, d: ArrayView2<f32>, out: ArrayViewMut2<f32>) -> Result<()> {
+        self.matrix.t_matmul_rows(self.rows.clone(), d, out)
+    }
+}
+
+impl From<CsrMatrix> for CsrBatch {
+    fn from(matrix: CsrMatrix) -> Self {
+        let rows = 0..matrix.nrows();
+
+        Self {
+            matrix: Arc::new(matrix),
+            rows,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csr_round_trips_through_dense() {
+        let dense = [0.0, 1.5, 0.0, 2.0, 0.0, 0.0, 0.0, -3.0, 4.0];
+        let csr = CsrMatrix::from_dense(&dense, 3).unwrap();
+
+        assert_eq!(csr.nrows(), 3);
+        assert_eq!(csr.nnz(), 4);
+        assert_eq!(csr.row(1), (&[0][..], &[2.0][..]));
+        assert_eq!(csr.to_dense().as_slice().unwrap(), dense);
+
+        let built = CsrMatrix::new(
+            3,
+            vec![0, 1, 2, 4],
+            vec![1, 0, 1, 2],
+            vec![1.5, 2.0, -3.0, 4.0],
+        );
+        assert_eq!(built.unwrap(), csr);
+    }
+
+    #[test]
+    fn test_csr_rejects_malformed_buffers() {
+        assert!(CsrMatrix::new(3, vec![], vec![], vec![]).is_err());
+        assert!(CsrMatrix::new(3, vec![0, 2, 1], vec![0, 1], vec![1.0, 1.0]).is_err());
+        assert!(CsrMatrix::new(3, vec![0, 1], vec![3], vec![1.0]).is_err());
+        assert!(CsrMatrix::new(3, vec![0, 2], vec![0, 1], vec![1.0]).is_err());
+        assert!(CsrMatrix::from_dense(&[1.0; 5], 3).is_err());
+    }
+
+    #[test]
+    fn test_csr_products_match_the_dense_ones() {
+        let x = [0.0, 2.0, 0.0, 0.0, -1.0, 0.0, 0.5, 0.0];
+        let csr = CsrMatrix::from_dense(&x, 4).unwrap();
+        let x = ArrayView2::from_shape((2, 4), &x).unwrap();
+
+        let w = Array2::from_shape_fn((4, 3), |(i, j)| (i * 3 + j) as f32 * 0.25 - 1.0);
+        let mut out = Array2::zeros((2, 3));
+        csr.matmul_into(w.view(), out.view_mut()).unwrap();
+        assert_eq!(out, x.dot(&w));
+
+        let d = Array2::from_shape_fn((2, 3), |(i, j)| (i + j) as f32 - 1.5);
+        let mut out = Array2::zeros((4, 3));
+        csr.t_matmul_into(d.view(), out.view_mut()).unwrap();
+        assert_eq!(out, x.t().dot(&d));
+    }
+
+    #[test]
+    fn test_csr_round_trips_through_words() {
+        let dense = [0.0, 1.5, 0.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -3.0, 4.0];
+        let csr = CsrMatrix::from_dense(&dense, 3).unwrap();
+
+        let words = csr.to_words(1..4).unwrap();
+        let rows = CsrMatrix::from_words(3, 3, &words).unwrap();
+        assert_eq!(rows.to_dense().as_slice().unwrap(), &dense[3..]);
+
+        assert!(CsrMatrix::from_words(3, 2, &words).is_err());
+        assert!(CsrMatrix::from_words(3, 3, &words[..words.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_csr_batch_products_match_the_selected_rows() {
+        let x = [0.0, 2.0, 0.0, 0.0, -1.0, 0.0, 0.5, 0.0, 0.0, 0.0, 3.0, 1.0];
+        let csr = Arc::new(CsrMatrix::from_dense(&x, 4).unwrap());
+        let batch = CsrBatch::new(Arc::clone(&csr), 1..3).unwrap();
+        let rows = csr.select(&[1, 2]).to_dense();
+        assert_eq!(batch.to_dense(), rows);
+
+        let w = Array2::from_shape_fn((4, 2), |(i, j)| (i * 2 + j) as f32 * 0.5 - 1.0);
+        let mut out = Array2::zeros((2, 2));
+        batch.matmul_into(w.view(), out.view_mut()).unwrap();
+        assert_eq!(out, rows.dot(&w));
+
+        let d = Array2::from_shape_fn((2, 2), |(i, j)| (i + j) as f32 - 0.5);
+        let mut out = Array2::zeros((4, 2));
+        batch.t_matmul_into(d.view(), out.view_mut()).unwrap();
+        assert_eq!(out, rows.t().dot(&d));
+
+        assert!(CsrBatch::new(csr, 2..4).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use std::{ops::Range, sync::Arc};
+
+use rand::Rng;
+
+use super::{CsrBatch, CsrMatrix, DataSrc};
+use crate::Result;
+
+/// A data source whose samples are the rows of a sparse matrix, the labels stay dense.
+#[derive(Debug)]
+pub struct CsrSrc {
+    samples: Arc<CsrMatrix>,
+    labels: Vec<f32>,
+}
+
+impl CsrSrc {
+    /// Creates a new `CsrSrc`.
+    ///
+    /// # Args
+    /// * `samples` - The samples, one per row of the matrix.
+    /// * `labels` - The labels data.
+    ///
+    /// # Returns
+    /// A new `CsrSrc` instance.
+    pub fn new(samples: CsrMatrix, labels: Vec<f32>) -> Self {
+        Self {
+            samples: Arc::new(samples),
+            labels,
+        }
+    }
+
+    /// Returns the amount of rows in the source.
+    pub fn rows(&self) -> usize {
+        self.samples.nrows()
+    }
+
+    /// Returns the labels data.
+    pub fn labels(&self) -> &[f32] {
+        &self.labels
+    }
+
+    /// Shuffles the rows with the same swaps an `InMemSrc` would make for the same rng.
+    ///
+    /// # Args
+    /// * `rows` - The amount of rows to shuffle.
+    /// * `y_size` - The size of a label.
+    /// * `rng` - A random number generator.
+    pub fn shuffle<R: Rng>(&mut self, rows: usize, y_size: usize, rng: &mut R) {
+        let mut order: Vec<_> = (0..rows).collect();
+
+        for i in 0..rows {
+            let j = rng.random_range(i..rows);
+            order.swap(i, j);
+        }
+
+        *self = self.select(&order, y_size);
+    }
+
+    /// Copies some of the rows into a new source, in the given order.
+    ///
+    /// # Args
+    /// * `rows` - The indices of the rows to copy, they can repeat.
+    /// * `y_size` - The size of a label.
+    ///
+    /// # Returns
+    /// A new `CsrSrc` with the selected rows.
+    pub fn select(&self, rows: &[usize], y_size: usize) -> Self {
+        let mut labels = Vec::with_capacity(rows.len() * y_size);
+
+        for &row in rows {
+            labels.extend_from_slice(self.rows_labels(row..row + 1, y_size));
+        }
+
+        Self::new(self.samples.select(rows), labels)
+    }
+
+    /// Appends the given data source to self, compressing it's samples if they're dense.
+    ///
+    /// # Args
+    /// * `src` - The data to append.
+    pub fn load(&mut self, src: DataSrc) {
+        let samples = Arc::make_mut(&mut self.samples);
+
+        match src {
+            DataSrc::InMem(src) => {
+                let (x, y) = src.into_parts();
+
+                // SAFETY: The rows of a dataset all have the same amount of columns.
+                let rows = CsrMatrix::from_dense(&x, samples.ncols()).unwrap();
+                samples.append(&rows);
+                self.labels.extend(y);
+            }
+            DataSrc::Csr(src) => {
+                samples.append(&src.samples);
+                self.labels.extend_from_slice(&src.labels);
+            }
+        }
+    }
+
+    /// Retrieves a batch of the data source.
+    ///
+    /// # Args
+    /// * `rows` - The range of the rows to be retrieved.
+    /// * `y_size` - The size of a label.
+    ///
+    /// # Returns
+    /// The batch's sparse samples and it's labels.
+    pub fn batch(&self, rows: Range<usize>, y_size: usize) -> Result<(CsrBatch, &[f32])> {
+        let labels = self.rows_labels(rows.clone(), y_size);
+        Ok((CsrBatch::new(Arc::clone(&self.samples), rows)?, labels))
+    }
+
+    /// Packs some of the rows to send them to another node, see `CsrMatrix::encode_rows`.
+    ///
+    /// # Args
+    /// * `rows` - The range of the rows to be packed.
+    /// * `y_size` - The size of a label.
+    ///
+    /// # Returns
+    /// The packed samples and the labels of the rows.
+    pub fn raw_batch(&self, rows: Range<usize>, y_size: usize) -> Result<(Vec<f32>, &[f32])> {
+        let labels = self.rows_labels(rows.clone(), y_size);
+        Ok((self.samples.to_words(rows)?, labels))
+    }
+
+    /// Expands the samples into a dense buffer.
+    ///
+    /// # Returns
+    /// The samples and the labels as raw row major data.
+    pub fn to_dense(&self) -> (Vec<f32>, &[f32]) {
+        let (samples, _) = self.samples.to_dense().into_raw_vec_and_offset();
+        (samples, &self.labels)
+    }
+
+    fn rows_labels(&self, rows: Range<usize>, y_size: usize) -> &[f32] {
+        &self.labels[rows.start * y_size..rows.end * y_size]
+    }
+}
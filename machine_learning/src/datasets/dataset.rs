@@ -1,9 +1,10 @@
-use std::num::NonZeroUsize;
+use std::{borrow::Cow, num::NonZeroUsize};
 
 use ndarray::ArrayView2;
 use rand::Rng;
 
-use super::{dataset_src::DataSrc, inmem_src::InMemSrc};
+use super::{Samples, dataset_src::DataSrc, inmem_src::InMemSrc};
+use crate::Result;
 
 /// A container for the *raw* dataset and its meta data. The raw data is expected to be structured
 /// as rows, each with an x and it's expected output y.
@@ -43,8 +44,7 @@ impl Dataset {
     /// A new `Dataset` instance.
     pub fn loaded(src: DataSrc, x_size: NonZeroUsize, y_size: NonZeroUsize) -> Self {
         Self {
-            // SAFETY: The divisor is always greater than 0.
-            rows: src.size() / (x_size.get() + y_size.get()),
+            rows: src.rows(x_size.get(), y_size.get()),
             src,
             x_size,
             y_size,
@@ -57,7 +57,7 @@ impl Dataset {
     /// * `src` - The data to append.
     pub fn load(&mut self, src: DataSrc) {
         let (x_size, y_size) = self.sizes();
-        self.rows += src.size() / (x_size.get() + y_size.get());
+        self.rows += src.rows(x_size.get(), y_size.get());
         self.src.load(src);
     }
 
@@ -81,11 +81,12 @@ impl Dataset {
     /// * `batch_size` - The maximum size of batches to yield.
     ///
     /// # Returns
-    /// An iterator over the batches of the dataset in the form of tuples of `ArrayView2`s.
+    /// An iterator over the batches of the dataset in the form of tuples of their samples
+    /// and an `ArrayView2` of their labels.
     pub fn batches<'a>(
         &'a self,
         batch_size: NonZeroUsize,
-    ) -> impl Iterator<Item = (ArrayView2<'a, f32>, ArrayView2<'a, f32>)> + 'a {
+    ) -> impl Iterator<Item = (Samples<'a>, ArrayView2<'a, f32>)> + 'a {
         (0..self.rows)
             .step_by(batch_size.get())
             .filter_map(move |start| {
@@ -94,14 +95,59 @@ impl Dataset {
             })
     }
 
+    /// Retrieves the label of a single row of the dataset.
+    ///
+    /// # Args
+    /// * `row` - The index of the row.
+    ///
+    /// # Returns
+    /// The row's label.
+    pub fn label(&self, row: usize) -> &[f32] {
+        let (_, y_size) = self.sizes();
+        &self.src.labels()[row * y_size.get()..(row + 1) * y_size.get()]
+    }
+
+    /// Copies some of the rows of the dataset into a new one, in the given order.
+    ///
+    /// # Args
+    /// * `rows` - The indices of the rows to copy, they can repeat.
+    ///
+    /// # Returns
+    /// A new `Dataset` with the selected rows.
+    pub fn select(&self, rows: &[usize]) -> Dataset {
+        let (x_size, y_size) = self.sizes();
+
+        let src = match &self.src {
+            DataSrc::InMem(src) => {
+                let mut samples = Vec::with_capacity(rows.len() * x_size.get());
+                let mut labels = Vec::with_capacity(rows.len() * y_size.get());
+
+                for &row in rows {
+                    let x_range = row * x_size.get()..(row + 1) * x_size.get();
+                    let y_range = row * y_size.get()..(row + 1) * y_size.get();
+                    let (x, y) = src.raw_batch(x_range, y_range);
+                    samples.extend_from_slice(x);
+                    labels.extend_from_slice(y);
+                }
+
+                DataSrc::inmem(samples, labels)
+            }
+            DataSrc::Csr(src) => DataSrc::Csr(src.select(rows, y_size.get())),
+        };
+
+        Dataset::loaded(src, x_size, y_size)
+    }
+
     /// Partitions the dataset into n parts minimizing the size between them all.
     ///
+    /// The samples of a sparse dataset are packed as `CsrMatrix::encode_rows` does.
+    ///
     /// # Args
     /// * `n` - The amount of partitions to make.
     ///
     /// # Returns
-    /// An iterator over the dataset's raw partitions.
-    pub fn partition(&self, n: usize) -> impl Iterator<Item = (&[f32], &[f32])> {
+    /// An iterator over the dataset's raw partitions or the error packing them.
+    pub fn partition(&self, n: usize) -> impl Iterator<Item = Result<(Cow<'_, [f32]>, &[f32])>> {
         let (x_size, y_size) = self.sizes();
 
         let batch_size = self.rows / n.max(1);
@@ -110,11 +156,21 @@ impl Dataset {
 
         (0..n).map(move |i| {
             let end = start + batch_size + (i < extra) as usize;
-            let samples_range = start * x_size.get()..end * x_size.get();
-            let labels_range = start * y_size.get()..end * y_size.get();
-
+            let rows = start..end;
             start = end;
-            self.src.raw_batch(samples_range, labels_range)
+
+            match &self.src {
+                DataSrc::InMem(src) => {
+                    let samples_range = rows.start * x_size.get()..rows.end * x_size.get();
+                    let labels_range = rows.start * y_size.get()..rows.end * y_size.get();
+                    let (samples, labels) = src.raw_batch(samples_range, labels_range);
+                    Ok((Cow::Borrowed(samples), labels))
+                }
+                DataSrc::Csr(src) => {
+                    let (samples, labels) = src.raw_batch(rows, y_size.get())?;
+                    Ok((Cow::Owned(samples), labels))
+                }
+            }
         })
     }
 
@@ -142,11 +198,11 @@ impl Dataset {
     ///
     /// # Returns
     /// A tuple of both samples and labels inside the selected batch.
-    fn view_batch<'a>(
+    pub(super) fn view_batch<'a>(
         &'a self,
         row: usize,
         n: usize,
-    ) -> (ArrayView2<'a, f32>, ArrayView2<'a, f32>) {
+    ) -> (Samples<'a>, ArrayView2<'a, f32>) {
         let &Self {
             x_size,
             y_size,
@@ -154,13 +210,24 @@ impl Dataset {
             ..
         } = self;
 
-        let x_offset = row * x_size.get();
-        let y_offset = row * y_size.get();
-        let x_range = x_offset..x_offset + x_size.get() * n;
-        let y_range = y_offset..y_offset + y_size.get() * n;
-        let (x_raw_batch, y_raw_batch) = src.raw_batch(x_range, y_range);
+        let (x_batch, y_raw_batch) = match src {
+            DataSrc::InMem(src) => {
+                let x_offset = row * x_size.get();
+                let y_offset = row * y_size.get();
+                let x_range = x_offset..x_offset + x_size.get() * n;
+                let y_range = y_offset..y_offset + y_size.get() * n;
+                let (x_raw_batch, y_raw_batch) = src.raw_batch(x_range, y_range);
+
+                let x_batch = ArrayView2::from_shape((n, x_size.get()), x_raw_batch).unwrap();
+                (Samples::Dense(x_batch), y_raw_batch)
+            }
+            DataSrc::Csr(src) => {
+                // SAFETY: The batch's rows are always within the dataset.
+                let (x_batch, y_raw_batch) = src.batch(row..row + n, y_size.get()).unwrap();
+                (Samples::Sparse(x_batch), y_raw_batch)
+            }
+        };
 
-        let x_batch = ArrayView2::from_shape((n, x_size.get()), x_raw_batch).unwrap();
         let y_batch = ArrayView2::from_shape((n, y_size.get()), y_raw_batch).unwrap();
 
         (x_batch, y_batch)
@@ -188,7 +255,7 @@ mod tests {
 
         let (x, y) = ds.view_batch(0, 2);
 
-        assert_eq!(x, expected_x);
+        assert_eq!(x.to_dense(), expected_x);
         assert_eq!(y, expected_y);
     }
 
@@ -1,13 +1,12 @@
-use std::ops::Range;
-
 use rand::Rng;
 
-use super::inmem_src::InMemSrc;
+use super::{CsrMatrix, csr_src::CsrSrc, inmem_src::InMemSrc};
 
 /// The source of a dataset.
 #[derive(Debug)]
 pub enum DataSrc {
     InMem(InMemSrc),
+    Csr(CsrSrc),
     // Stream(StreamSrc<R>),
 }
 
@@ -21,10 +20,32 @@ impl DataSrc {
         DataSrc::InMem(InMemSrc::new(samples, labels))
     }
 
-    /// Returns the amount of values in the dataset source.
-    pub fn size(&self) -> usize {
+    /// Returns a new `DataSrc::Csr` dataset source.
+    ///
+    /// # Args
+    /// * `samples` - The sparse matrix holding a sample per row.
+    /// * `labels` - The buffer containing the dataset labels raw data.
+    pub fn csr(samples: CsrMatrix, labels: Vec<f32>) -> Self {
+        DataSrc::Csr(CsrSrc::new(samples, labels))
+    }
+
+    /// Returns the amount of rows in the dataset source.
+    ///
+    /// # Args
+    /// * `x_size` - The size of a sample.
+    /// * `y_size` - The size of a label.
+    pub fn rows(&self, x_size: usize, y_size: usize) -> usize {
+        match self {
+            DataSrc::InMem(src) => src.size() / (x_size + y_size),
+            DataSrc::Csr(src) => src.rows(),
+        }
+    }
+
+    /// Returns the labels of every row in the dataset source.
+    pub fn labels(&self) -> &[f32] {
         match self {
-            DataSrc::InMem(src) => src.size(),
+            DataSrc::InMem(src) => src.labels(),
+            DataSrc::Csr(src) => src.labels(),
         }
     }
 
@@ -38,30 +59,22 @@ impl DataSrc {
     pub fn shuffle<Rn: Rng>(&mut self, rows: usize, x_size: usize, y_size: usize, rng: &mut Rn) {
         match self {
             DataSrc::InMem(src) => src.shuffle(rows, x_size, y_size, rng),
-        }
-    }
-
-    /// Retrieves a batch of the data source.
-    ///
-    /// # Args
-    /// * `x_range` - The range of the samples to be retrieved.
-    /// * `y_range` - The range of the labels to be retrieved.
-    ///
-    /// # Returns
-    /// A reference `&[f32]` to the raw data batch within the range.
-    pub fn raw_batch(&self, x_range: Range<usize>, y_range: Range<usize>) -> (&[f32], &[f32]) {
-        match self {
-            DataSrc::InMem(src) => src.raw_batch(x_range, y_range),
+            DataSrc::Csr(src) => src.shuffle(rows, y_size, rng),
         }
     }
 
     /// Appends the given data source to self.
     ///
+    /// An empty source takes the kind of the given one, otherwise the given rows are
+    /// converted to the kind of self.
+    ///
     /// # Args
     /// * `src` - The data to be appended.
     pub fn load(&mut self, src: Self) {
         match self {
+            DataSrc::InMem(dst) if dst.size() == 0 => *self = src,
             DataSrc::InMem(dst) => dst.load(src),
+            DataSrc::Csr(dst) => dst.load(src),
         }
     }
 }
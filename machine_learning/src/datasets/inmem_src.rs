@@ -28,6 +28,11 @@ impl InMemSrc {
         self.samples.len() + self.labels.len()
     }
 
+    /// Returns the labels data.
+    pub fn labels(&self) -> &[f32] {
+        &self.labels
+    }
+
     pub fn shuffle<R: Rng>(&mut self, rows: usize, x_size: usize, y_size: usize, rng: &mut R) {
         for i in 0..rows {
             let j = rng.random_range(i..rows);
@@ -52,9 +57,22 @@ impl InMemSrc {
                 self.samples.extend_from_slice(&src.samples);
                 self.labels.extend_from_slice(&src.labels)
             }
+            DataSrc::Csr(src) => {
+                let (samples, labels) = src.to_dense();
+                self.samples.extend(samples);
+                self.labels.extend_from_slice(labels);
+            }
         }
     }
 
+    /// Consumes self and yields it's buffers.
+    ///
+    /// # Returns
+    /// The samples and the labels data.
+    pub fn into_parts(self) -> (Vec<f32>, Vec<f32>) {
+        (self.samples, self.labels)
+    }
+
     fn shuffle_slice(slice: &mut [f32], min: usize, max: usize, size: usize) {
         let i_data = min * size;
         let j_data = max * size;
@@ -1,6 +1,17 @@
+mod batch_order;
+mod csr;
+mod csr_src;
 mod dataset;
 mod dataset_src;
 mod inmem_src;
+mod sampler;
+mod samples;
+mod synthetic;
 
+pub use batch_order::{BatchOrder, OrderedBatches};
+pub use csr::{CSR_CROSSOVER_DENSITY, CsrBatch, CsrMatrix};
 pub use dataset::Dataset;
 pub use dataset_src::DataSrc;
+pub use sampler::Sampler;
+pub use samples::Samples;
+pub use synthetic::SyntheticDataset;
@@ -0,0 +1,214 @@
+use comms::specs::machine_learning::SamplerSpec;
+use rand::{
+    Rng,
+    distr::{Distribution, weighted::WeightedIndex},
+};
+
+use super::Dataset;
+use crate::{MlErr, Result};
+
+/// Orders the rows of a `Dataset` on every epoch.
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    spec: SamplerSpec,
+    weights: Option<WeightedIndex<f32>>,
+}
+
+impl Sampler {
+    /// Creates a new `Sampler`.
+    ///
+    /// # Args
+    /// * `spec` - The specification of the sampler.
+    ///
+    /// # Returns
+    /// A new `Sampler` instance.
+    pub fn new(spec: SamplerSpec) -> Self {
+        Self {
+            spec,
+            weights: None,
+        }
+    }
+
+    /// Draws the order of the rows for the next epoch.
+    ///
+    /// `Shuffle` shuffles the dataset in place while `WeightedByClass` leaves it untouched
+    /// and draws the rows instead. The class weights are counted on the first draw, so the
+    /// dataset must be loaded by then. `GlobalOrder` leaves the dataset untouched, the rows
+    /// come from the trainer's `BatchOrder` instead.
+    ///
+    /// # Args
+    /// * `dataset` - The dataset to order.
+    /// * `rng` - A random number generator.
+    ///
+    /// # Returns
+    /// The drawn rows or `None` if the epoch runs over the dataset in it's own order.
+    ///
+    /// # Errors
+    /// An `MlErr::InvalidClass` if a row's class isn't one of the sampler's.
+    pub fn draw<R: Rng>(
+        &mut self,
+        dataset: &mut Dataset,
+        rng: &mut R,
+    ) -> Result<Option<Vec<usize>>> {
+        match self.spec {
+            SamplerSpec::Sequential | SamplerSpec::GlobalOrder { .. } => Ok(None),
+            SamplerSpec::Shuffle => {
+                dataset.shuffle(rng);
+                Ok(None)
+            }
+            SamplerSpec::WeightedByClass { .. } if dataset.rows() == 0 => Ok(None),
+            SamplerSpec::WeightedByClass {
+                label_col,
+                num_classes,
+            } => {
+                if self.weights.is_none() {
+                    let weights = Self::class_weights(dataset, label_col, num_classes.get())?;
+                    // SAFETY: There's at least a row and every weight is positive.
+                    self.weights = Some(WeightedIndex::new(weights).unwrap());
+                }
+
+                let weights = self.weights.as_ref().unwrap();
+                Ok(Some(
+                    weights.sample_iter(rng).take(dataset.rows()).collect(),
+                ))
+            }
+        }
+    }
+
+    /// Weights every row of the dataset by the inverse of the frequency of it's class.
+    ///
+    /// # Args
+    /// * `dataset` - The dataset to weight.
+    /// * `label_col` - The column of the labels holding the class.
+    /// * `num_classes` - The amount of classes.
+    ///
+    /// # Returns
+    /// The weight of every row.
+    ///
+    /// # Errors
+    /// An `MlErr::InvalidClass` if a row's class isn't a whole number below `num_classes`
+    /// or a size mismatch if the labels don't have the column.
+    fn class_weights(dataset: &Dataset, label_col: usize, num_classes: usize) -> Result<Vec<f32>> {
+        let (_, y_size) = dataset.sizes();
+
+        if label_col >= y_size.get() {
+            return Err(MlErr::size_mismatch(
+                "label columns",
+                y_size.get(),
+                label_col + 1,
+            ));
+        }
+
+        let classes = (0..dataset.rows())
+            .map(|row| {
+                let class = dataset.label(row)[label_col];
+
+                if class < 0.0 || class.fract() != 0.0 || class as usize >= num_classes {
+                    return Err(MlErr::InvalidClass {
+                        row,
+                        class,
+                        num_classes,
+                    });
+                }
+
+                Ok(class as usize)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut counts = vec![0usize; num_classes];
+        for &class in &classes {
+            counts[class] += 1;
+        }
+
+        Ok(classes
+            .into_iter()
+            .map(|class| 1.0 / counts[class] as f32)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::datasets::DataSrc;
+
+    /// Creates a dataset of 100 rows where only 10 are of the class 1.
+    fn imbalanced() -> Dataset {
+        let samples = (0..100).map(|i| i as f32).collect();
+        let labels = (0..100).map(|i| (i >= 90) as u8 as f32).collect();
+        let size = NonZeroUsize::new(1).unwrap();
+        Dataset::loaded(DataSrc::inmem(samples, labels), size, size)
+    }
+
+    fn weighted_by_class(num_classes: usize) -> Sampler {
+        Sampler::new(SamplerSpec::WeightedByClass {
+            label_col: 0,
+            num_classes: NonZeroUsize::new(num_classes).unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_weighted_by_class_balances_an_imbalanced_dataset() {
+        let mut dataset = imbalanced();
+        let mut sampler = weighted_by_class(2);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let mut ones = 0;
+        for _ in 0..50 {
+            let rows = sampler.draw(&mut dataset, &mut rng).unwrap().unwrap();
+            assert_eq!(rows.len(), 100);
+            ones += rows
+                .iter()
+                .filter(|&&row| dataset.label(row)[0] == 1.0)
+                .count();
+        }
+
+        let ratio = ones as f32 / 5000.0;
+        assert!((ratio - 0.5).abs() < 0.05, "class 1 ratio {ratio}");
+    }
+
+    #[test]
+    fn test_weighted_by_class_is_reproducible() {
+        let mut dataset = imbalanced();
+
+        let mut draw = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            weighted_by_class(2).draw(&mut dataset, &mut rng).unwrap()
+        };
+
+        assert_eq!(draw(7), draw(7));
+        assert_ne!(draw(7), draw(8));
+    }
+
+    #[test]
+    fn test_a_class_out_of_range_is_rejected() {
+        let mut dataset = imbalanced();
+        let mut rng = StdRng::seed_from_u64(42);
+        let err = weighted_by_class(1).draw(&mut dataset, &mut rng);
+
+        assert!(matches!(err, Err(MlErr::InvalidClass { row: 90, .. })));
+    }
+
+    #[test]
+    fn test_sequential_keeps_the_dataset_order() {
+        let mut dataset = imbalanced();
+        let mut sampler = Sampler::new(SamplerSpec::Sequential);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        assert_eq!(sampler.draw(&mut dataset, &mut rng).unwrap(), None);
+        let (x, _) = dataset
+            .batches(NonZeroUsize::new(100).unwrap())
+            .next()
+            .unwrap();
+        assert!(
+            x.to_dense()
+                .iter()
+                .enumerate()
+                .all(|(row, &x)| x == row as f32)
+        );
+    }
+}
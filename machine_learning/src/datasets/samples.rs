@@ -0,0 +1,43 @@
+use ndarray::{ArrayView2, CowArray, Ix2};
+
+use super::CsrBatch;
+
+/// The samples of a batch, dense or some rows of a sparse matrix.
+#[derive(Debug, Clone)]
+pub enum Samples<'a> {
+    Dense(ArrayView2<'a, f32>),
+    Sparse(CsrBatch),
+}
+
+impl Samples<'_> {
+    /// The amount of samples in the batch.
+    pub fn nrows(&self) -> usize {
+        match self {
+            Samples::Dense(x) => x.nrows(),
+            Samples::Sparse(x) => x.nrows(),
+        }
+    }
+
+    /// Gives the samples as a dense matrix, expanding them if they're sparse.
+    ///
+    /// # Returns
+    /// A `(nrows, x_size)` array, borrowing the samples if they're already dense.
+    pub fn to_dense(&self) -> CowArray<'_, f32, Ix2> {
+        match self {
+            Samples::Dense(x) => x.view().into(),
+            Samples::Sparse(x) => x.to_dense().into(),
+        }
+    }
+}
+
+impl<'a> From<ArrayView2<'a, f32>> for Samples<'a> {
+    fn from(x: ArrayView2<'a, f32>) -> Self {
+        Samples::Dense(x)
+    }
+}
+
+impl From<CsrBatch> for Samples<'_> {
+    fn from(x: CsrBatch) -> Self {
+        Samples::Sparse(x)
+    }
+}
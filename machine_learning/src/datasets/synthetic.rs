@@ -0,0 +1,202 @@
+use std::ops::Range;
+
+use rand::{SeedableRng, rngs::StdRng};
+use rand_distr::{Distribution, Normal, Uniform};
+
+/// The stream the map's parameters are drawn from, the rows are drawn from the stream
+/// of their index.
+const MAP_STREAM: u64 = u64::MAX;
+
+/// A dataset whose labels are a known linear map of it's samples plus gaussian noise.
+///
+/// The map's `weights` are laid out row major, `x_size` rows of `y_size` columns, the
+/// same as the parameters of a dense layer, so a trained model can be checked against it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticDataset {
+    pub weights: Vec<f32>,
+    pub biases: Vec<f32>,
+    pub samples: Vec<f32>,
+    pub labels: Vec<f32>,
+}
+
+impl SyntheticDataset {
+    /// Generates a new `SyntheticDataset`.
+    ///
+    /// # Args
+    /// * `nsamples` - The amount of rows to generate.
+    /// * `x_size` - The size of every sample.
+    /// * `y_size` - The size of every label.
+    /// * `noise_std` - The standard deviation of the noise added to every label.
+    /// * `seed` - The seed of the generator.
+    ///
+    /// # Returns
+    /// A new `SyntheticDataset` instance or `None` if `noise_std` isn't a finite
+    /// non negative number.
+    pub fn generate(
+        nsamples: usize,
+        x_size: usize,
+        y_size: usize,
+        noise_std: f32,
+        seed: u64,
+    ) -> Option<Self> {
+        Self::generate_rows(0..nsamples, x_size, y_size, noise_std, seed)
+    }
+
+    /// Generates a shard of the rows of a `SyntheticDataset`.
+    ///
+    /// The map's parameters and the samples are drawn uniformly from `[-1, 1)`. Every row
+    /// is drawn from it's own seeded stream, so a shard holds the same rows as the same
+    /// range of the whole dataset and the shards of a partition concatenate to it.
+    ///
+    /// # Args
+    /// * `rows` - The range of the rows to generate.
+    /// * `x_size` - The size of every sample.
+    /// * `y_size` - The size of every label.
+    /// * `noise_std` - The standard deviation of the noise added to every label.
+    /// * `seed` - The seed of the generator.
+    ///
+    /// # Returns
+    /// A new `SyntheticDataset` instance or `None` if `noise_std` isn't a finite
+    /// non negative number.
+    pub fn generate_rows(
+        rows: Range<usize>,
+        x_size: usize,
+        y_size: usize,
+        noise_std: f32,
+        seed: u64,
+    ) -> Option<Self> {
+        (noise_std.is_finite() && noise_std >= 0.0).then_some(())?;
+        let noise = Normal::new(0.0, noise_std).ok()?;
+        // SAFETY: The range is finite and not empty.
+        let uniform = Uniform::new(-1.0, 1.0).unwrap();
+
+        let draw = |rng: &mut StdRng, n| uniform.sample_iter(rng).take(n).collect::<Vec<_>>();
+        let mut rng = stream_rng(seed, MAP_STREAM);
+        let weights = draw(&mut rng, x_size * y_size);
+        let biases = draw(&mut rng, y_size);
+
+        let nrows = rows.len();
+        let mut dataset = Self {
+            weights,
+            biases,
+            samples: Vec::with_capacity(nrows * x_size),
+            labels: Vec::with_capacity(nrows * y_size),
+        };
+
+        for row in rows {
+            let mut rng = stream_rng(seed, row as u64);
+            let x = draw(&mut rng, x_size);
+            let y = dataset.truth(&x).into_iter();
+
+            dataset.labels.extend(y.map(|y| y + noise.sample(&mut rng)));
+            dataset.samples.extend(x);
+        }
+
+        Some(dataset)
+    }
+
+    /// The amount of rows in the dataset.
+    ///
+    /// # Returns
+    /// The amount of samples.
+    pub fn len(&self) -> usize {
+        self.labels.len() / self.biases.len().max(1)
+    }
+
+    /// Whether the dataset holds no rows.
+    ///
+    /// # Returns
+    /// `true` if there are no samples.
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Evaluates the dataset's linear map without the noise.
+    ///
+    /// # Args
+    /// * `x` - A single sample.
+    ///
+    /// # Returns
+    /// The sample's noiseless labels.
+    pub fn truth(&self, x: &[f32]) -> Vec<f32> {
+        let y_size = self.biases.len();
+
+        (0..y_size)
+            .map(|j| {
+                let dot: f32 = x
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| x * self.weights[i * y_size + j])
+                    .sum();
+
+                dot + self.biases[j]
+            })
+            .collect()
+    }
+}
+
+/// Creates the generator of one of the dataset's streams.
+///
+/// # Args
+/// * `seed` - The seed of the dataset.
+/// * `stream` - The stream to draw from.
+///
+/// # Returns
+/// A generator keyed by both the seed and the stream.
+fn stream_rng(seed: u64, stream: u64) -> StdRng {
+    let mut key = [0; 32];
+    key[..8].copy_from_slice(&seed.to_le_bytes());
+    key[8..16].copy_from_slice(&stream.to_le_bytes());
+    StdRng::from_seed(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_the_same_seed_generates_the_same_rows() {
+        let a = SyntheticDataset::generate(8, 3, 2, 0.1, 7).unwrap();
+        let b = SyntheticDataset::generate(8, 3, 2, 0.1, 7).unwrap();
+        let c = SyntheticDataset::generate(8, 3, 2, 0.1, 8).unwrap();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!((a.samples.len(), a.labels.len(), a.len()), (24, 16, 8));
+    }
+
+    #[test]
+    fn test_the_shards_concatenate_to_the_whole_dataset() {
+        let whole = SyntheticDataset::generate(10, 3, 2, 0.1, 7).unwrap();
+        let shards: Vec<_> = [0..3, 3..7, 7..10]
+            .into_iter()
+            .map(|rows| SyntheticDataset::generate_rows(rows, 3, 2, 0.1, 7).unwrap())
+            .collect();
+
+        for shard in &shards {
+            assert_eq!(
+                (&shard.weights, &shard.biases),
+                (&whole.weights, &whole.biases)
+            );
+        }
+
+        let samples: Vec<_> = shards.iter().flat_map(|s| s.samples.clone()).collect();
+        let labels: Vec<_> = shards.iter().flat_map(|s| s.labels.clone()).collect();
+        assert_eq!((samples, labels), (whole.samples, whole.labels));
+    }
+
+    #[test]
+    fn test_noiseless_labels_are_the_linear_map_of_the_samples() {
+        let dataset = SyntheticDataset::generate(5, 3, 2, 0.0, 42).unwrap();
+
+        for (x, y) in dataset.samples.chunks(3).zip(dataset.labels.chunks(2)) {
+            assert_eq!(dataset.truth(x), y);
+        }
+    }
+
+    #[test]
+    fn test_a_negative_noise_is_rejected() {
+        assert!(SyntheticDataset::generate(5, 3, 2, -1.0, 42).is_none());
+        assert!(SyntheticDataset::generate(5, 3, 2, f32::NAN, 42).is_none());
+    }
+}
@@ -9,13 +9,37 @@ use ndarray::ShapeError;
 /// The result type for the `machine_learning` module.
 pub type Result<T> = std::result::Result<T, MlErr>;
 
+/// The step of the training a size mismatch happened at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Forward,
+    Backward,
+    GradientPush,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Phase::Forward => "the forward pass",
+            Phase::Backward => "the backward pass",
+            Phase::GradientPush => "the gradient push",
+        };
+
+        write!(f, "{s}")
+    }
+}
+
 /// The error type for the `machine_learning` module.
 #[derive(Debug)]
 pub enum MlErr {
+    /// A buffer of a different size than expected, `layer` and `phase` pinpoint where
+    /// in the model it happened when known.
     SizeMismatch {
         what: &'static str,
         got: usize,
         expected: usize,
+        layer: Option<usize>,
+        phase: Option<Phase>,
         location: &'static Location<'static>,
     },
     MatrixError {
@@ -30,7 +54,42 @@ pub enum MlErr {
         source: ndarray_conv::Error<2>,
         location: &'static Location<'static>,
     },
+    InvalidCsr {
+        reason: String,
+        location: &'static Location<'static>,
+    },
+    SparseInput {
+        layer: &'static str,
+    },
+    /// A tied layer whose source isn't an untied dense layer of the transposed dimension.
+    InvalidTie {
+        layer: usize,
+        source: usize,
+    },
     EmptyEpoch,
+    /// A label whose class isn't one of the sampler's classes.
+    InvalidClass {
+        row: usize,
+        class: f32,
+        num_classes: usize,
+    },
+    /// A model the training's strategy can't fit.
+    InvalidStrategy {
+        reason: &'static str,
+    },
+    /// A saved model that can't be read back.
+    InvalidBundle {
+        reason: String,
+    },
+    /// A custom layer that isn't in the layer registry.
+    UnknownLayer {
+        name: String,
+    },
+    /// A custom layer whose parameters it's constructor can't take.
+    InvalidLayer {
+        name: String,
+        reason: String,
+    },
 }
 
 impl MlErr {
@@ -40,10 +99,69 @@ impl MlErr {
             what,
             got,
             expected,
+            layer: None,
+            phase: None,
             location: Location::caller(),
         }
     }
 
+    /// Places a size mismatch in a layer of the model, other errors are left untouched.
+    ///
+    /// # Args
+    /// * `layer` - The index of the layer the mismatch happened at.
+    /// * `phase` - The step of the training the mismatch happened at.
+    ///
+    /// # Returns
+    /// The same error with the added context.
+    pub fn in_layer(self, layer: usize, phase: Phase) -> MlErr {
+        match self {
+            MlErr::SizeMismatch {
+                what,
+                got,
+                expected,
+                layer: None,
+                location,
+                ..
+            } => MlErr::SizeMismatch {
+                what,
+                got,
+                expected,
+                layer: Some(layer),
+                phase: Some(phase),
+                location,
+            },
+            err => err,
+        }
+    }
+
+    /// Places a size mismatch in a step of the training, other errors are left untouched.
+    ///
+    /// # Args
+    /// * `phase` - The step of the training the mismatch happened at.
+    ///
+    /// # Returns
+    /// The same error with the added context.
+    pub fn during(self, phase: Phase) -> MlErr {
+        match self {
+            MlErr::SizeMismatch {
+                what,
+                got,
+                expected,
+                layer,
+                phase: None,
+                location,
+            } => MlErr::SizeMismatch {
+                what,
+                got,
+                expected,
+                layer,
+                phase: Some(phase),
+                location,
+            },
+            err => err,
+        }
+    }
+
     #[track_caller]
     pub fn matrix_error(source: ShapeError) -> MlErr {
         MlErr::MatrixError {
@@ -51,6 +169,14 @@ impl MlErr {
             location: Location::caller(),
         }
     }
+
+    #[track_caller]
+    pub fn invalid_csr(reason: impl Into<String>) -> MlErr {
+        MlErr::InvalidCsr {
+            reason: reason.into(),
+            location: Location::caller(),
+        }
+    }
 }
 
 impl Display for MlErr {
@@ -60,8 +186,22 @@ impl Display for MlErr {
                 what,
                 got,
                 expected,
+                layer,
+                phase,
                 location,
-            } => format!("size mismatch for {what}: got {got}, expected {expected} at {location}"),
+            } => {
+                let mut s = format!("size mismatch for {what}");
+
+                if let Some(layer) = layer {
+                    s.push_str(&format!(" in layer {layer}"));
+                }
+
+                if let Some(phase) = phase {
+                    s.push_str(&format!(" during {phase}"));
+                }
+
+                format!("{s}: got {got}, expected {expected} at {location}")
+            }
             MlErr::MatrixError { source, location } => {
                 format!("matrix operation failed: {source} at {location}")
             }
@@ -71,7 +211,33 @@ impl Display for MlErr {
             MlErr::Conv2dError { source, location } => {
                 format!("convolution operation failed: {source} at {location}")
             }
+            MlErr::InvalidCsr { reason, location } => {
+                format!("invalid csr matrix: {reason} at {location}")
+            }
+            MlErr::SparseInput { layer } => {
+                format!("a {layer} layer can't take a sparse input")
+            }
+            MlErr::InvalidTie { layer, source } => {
+                format!("layer {layer} can't tie it's weights to layer {source}")
+            }
             MlErr::EmptyEpoch => "this epoch has no batches".to_string(),
+            MlErr::InvalidClass {
+                row,
+                class,
+                num_classes,
+            } => {
+                format!("row {row} has the class {class}, not one of the {num_classes} classes")
+            }
+            MlErr::InvalidStrategy { reason } => {
+                format!("the strategy can't fit the model: {reason}")
+            }
+            MlErr::InvalidBundle { reason } => format!("invalid model bundle: {reason}"),
+            MlErr::UnknownLayer { name } => {
+                format!("there's no custom layer registered as {name}")
+            }
+            MlErr::InvalidLayer { name, reason } => {
+                format!("invalid parameters for the custom layer {name}: {reason}")
+            }
         };
 
         write!(f, "{s}")
@@ -106,3 +272,25 @@ impl From<ndarray_conv::Error<2>> for MlErr {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_mismatch_display_pinpoints_the_layer_and_phase() {
+        let err = MlErr::size_mismatch("grad", 3, 4);
+        let plain = err.to_string();
+        assert!(plain.starts_with("size mismatch for grad: got 3, expected 4 at "));
+
+        let err = err.in_layer(2, Phase::Backward);
+        let located = err.to_string();
+        assert!(located.starts_with(
+            "size mismatch for grad in layer 2 during the backward pass: got 3, expected 4 at "
+        ));
+
+        // The innermost context is kept.
+        let err = err.in_layer(0, Phase::Forward).during(Phase::GradientPush);
+        assert_eq!(err.to_string(), located);
+    }
+}
@@ -1,9 +1,10 @@
 use std::{cell::RefCell, rc::Rc};
 
 use comms::specs::machine_learning::{DistributionSpec, ParamGenSpec};
-use rand::{Rng, SeedableRng, rngs::StdRng};
+use rand::{Rng, rngs::StdRng};
 
 use super::{ChainedParamGen, ConstParamGen, InlineParamGen, ParamGen, RandParamGen, Result};
+use crate::rng::{self, RngPurpose};
 
 /// Makes `callback`'s return type generic, when trying to resolve a concrete `RandParamGen` it avoids boxing all
 /// parameter generators variants.
@@ -20,39 +21,39 @@ use super::{ChainedParamGen, ConstParamGen, InlineParamGen, ParamGen, RandParamG
 /// * `limit` - The limit of parameters that the `RandParamGen` can generate.
 /// * `callback` - The closure to call passing in the created weigth gen.
 macro_rules! with_distribution {
-    ($rng:expr, $dist_spec:expr, $limit:expr, $callback:expr) => {
+    ($rng:expr, $dist_spec:expr, $limit:expr, $gain:expr, $callback:expr) => {
         match $dist_spec {
             DistributionSpec::Uniform { low, high } => {
                 let param_gen = RandParamGen::uniform($rng, $limit, low, high)?;
-                ($callback)(param_gen)
+                ($callback)(param_gen.with_gain($gain))
             }
             DistributionSpec::UniformInclusive { low, high } => {
                 let param_gen = RandParamGen::uniform_inclusive($rng, $limit, low, high)?;
-                ($callback)(param_gen)
+                ($callback)(param_gen.with_gain($gain))
             }
             DistributionSpec::XavierUniform { fan_in, fan_out } => {
                 let param_gen = RandParamGen::xavier_uniform($rng, $limit, fan_in, fan_out)?;
-                ($callback)(param_gen)
+                ($callback)(param_gen.with_gain($gain))
             }
             DistributionSpec::LecunUniform { fan_in } => {
                 let param_gen = RandParamGen::lecun_uniform($rng, $limit, fan_in)?;
-                ($callback)(param_gen)
+                ($callback)(param_gen.with_gain($gain))
             }
             DistributionSpec::Normal { mean, std_dev } => {
                 let param_gen = RandParamGen::normal($rng, $limit, mean, std_dev)?;
-                ($callback)(param_gen)
+                ($callback)(param_gen.with_gain($gain))
             }
             DistributionSpec::Kaiming { fan_in } => {
                 let param_gen = RandParamGen::kaiming($rng, $limit, fan_in)?;
-                ($callback)(param_gen)
+                ($callback)(param_gen.with_gain($gain))
             }
             DistributionSpec::Xavier { fan_in, fan_out } => {
                 let param_gen = RandParamGen::xavier($rng, $limit, fan_in, fan_out)?;
-                ($callback)(param_gen)
+                ($callback)(param_gen.with_gain($gain))
             }
             DistributionSpec::Lecun { fan_in } => {
                 let param_gen = RandParamGen::lecun($rng, $limit, fan_in)?;
-                ($callback)(param_gen)
+                ($callback)(param_gen.with_gain($gain))
             }
         }
     };
@@ -95,9 +96,10 @@ impl ParamGenBuilder {
             ParamGenSpec::Rand {
                 distribution,
                 limit,
+                gain,
             } => {
                 let rng = self.generate_rng(seed.into());
-                with_distribution!(rng, distribution, limit, |param_gen| {
+                with_distribution!(rng, distribution, limit, gain, |param_gen| {
                     Ok(Box::new(param_gen) as Box<dyn ParamGen>)
                 })
             }
@@ -140,8 +142,9 @@ impl ParamGenBuilder {
                 ParamGenSpec::Rand {
                     distribution,
                     limit,
+                    gain,
                 } => {
-                    with_distribution!(rng.clone(), *distribution, *limit, |param_gen| {
+                    with_distribution!(rng.clone(), *distribution, *limit, *gain, |param_gen| {
                         param_gens.push(Box::new(param_gen))
                     });
                 }
@@ -163,11 +166,7 @@ impl ParamGenBuilder {
     /// # Returns
     /// An clonable random number generator with interior mutability.
     fn generate_rng(&self, seed: Option<u64>) -> Rc<RefCell<StdRng>> {
-        let rng = match seed {
-            Some(seed) => StdRng::seed_from_u64(seed),
-            None => StdRng::from_os_rng(),
-        };
-
+        let rng = rng::rng_for(seed, RngPurpose::WeightInit);
         Rc::new(RefCell::new(rng))
     }
 }
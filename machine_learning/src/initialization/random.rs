@@ -10,6 +10,7 @@ pub struct RandParamGen<R: Rng, D: Distribution<f32>> {
     rng: Rc<RefCell<R>>,
     distribution: D,
     remaining: usize,
+    gain: f32,
 }
 
 impl<R: Rng, D: Distribution<f32>> RandParamGen<R, D> {
@@ -24,8 +25,21 @@ impl<R: Rng, D: Distribution<f32>> RandParamGen<R, D> {
             rng,
             distribution,
             remaining: limit,
+            gain: 1.0,
         }
     }
+
+    /// Scales every sampled value by a gain.
+    ///
+    /// # Args
+    /// * `gain` - The factor to scale the samples by.
+    ///
+    /// # Returns
+    /// The same `RandParamGen`, now scaling it's samples.
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
 }
 
 impl<R: Rng> RandParamGen<R, Uniform<f32>> {
@@ -174,7 +188,9 @@ impl<R: Rng, D: Distribution<f32>> ParamGen for RandParamGen<R, D> {
         self.remaining -= n;
 
         let mut rng = self.rng.borrow_mut();
-        let sample = (0..n).map(|_| self.distribution.sample(&mut rng)).collect();
+        let sample = (0..n)
+            .map(|_| self.gain * self.distribution.sample(&mut rng))
+            .collect();
         Some(sample)
     }
 
@@ -226,4 +242,25 @@ mod tests {
 
         assert!(param_gen.sample(1).is_none());
     }
+
+    #[test]
+    fn doubling_the_gain_doubles_the_std_dev() {
+        const SIZE: usize = 1000;
+
+        let std_dev = |gain: f32| {
+            let param_gen = RandParamGen::kaiming(seeded_rng(), SIZE, 8).unwrap();
+            let sample = param_gen.with_gain(gain).sample_remaining().unwrap();
+
+            let mean = sample.iter().sum::<f32>() / SIZE as f32;
+            let var = sample.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / SIZE as f32;
+            var.sqrt()
+        };
+
+        let (single, double) = (std_dev(1.), std_dev(2.));
+        assert!((single - 0.5).abs() < 0.05, "std dev {single}");
+        assert!(
+            (double - 2. * single).abs() < 1e-4,
+            "{double} != 2 * {single}"
+        );
+    }
 }
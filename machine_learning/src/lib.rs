@@ -5,8 +5,9 @@ pub mod initialization;
 pub mod models;
 pub mod optimization;
 pub mod param_manager;
+pub mod rng;
 #[cfg(test)]
 mod test;
 pub mod training;
 
-pub use error::{MlErr, Result};
+pub use error::{MlErr, Phase, Result};
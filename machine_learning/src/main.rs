@@ -1,6 +1,6 @@
 use std::{env, fs::File, io::Read, num::NonZeroUsize, path::PathBuf};
 
-use comms::floats::FloatPositive;
+use comms::{floats::FloatPositive, specs::machine_learning::StopCondition};
 use machine_learning::{
     arch::loss::CrossEntropy,
     datasets::{DataSrc, Dataset},
@@ -46,7 +46,7 @@ fn main() {
         train_dataset,
         loss_fn,
         0,
-        epochs,
+        StopCondition::MaxEpochs(epochs),
         batch_size,
         rng,
     );
@@ -64,7 +64,9 @@ fn main() {
     let mut epoch = 0;
     let epochs_until_log = 1;
     loop {
-        let TrainResult { losses, was_last } = trainer.train(&mut param_manager).unwrap();
+        let TrainResult {
+            losses, was_last, ..
+        } = trainer.train(&mut param_manager).unwrap();
         let loss = losses.last().unwrap();
 
         if epoch % epochs_until_log == 0 {
@@ -83,8 +85,9 @@ fn main() {
 
     let mut got_right = 0;
     for (x, y) in test_batches {
+        let x = x.to_dense();
         let mut pred = model
-            .forward(&mut param_manager, x.into_dyn())
+            .forward(&mut param_manager, x.view().into_dyn())
             .unwrap()
             .to_owned();
 
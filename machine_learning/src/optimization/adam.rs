@@ -96,4 +96,46 @@ impl Optimizer for Adam {
     fn learning_rate(&self) -> FloatPositive {
         self.learning_rate
     }
+
+    fn set_learning_rate(&mut self, learning_rate: FloatPositive) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn name(&self) -> &'static str {
+        "adam"
+    }
+
+    /// The state is laid out as the decayed betas followed by the first and second moments.
+    fn state(&self) -> Vec<f32> {
+        let mut state = Vec::with_capacity(2 + self.v.len() + self.s.len());
+        state.extend([self.beta1_t, self.beta2_t]);
+        state.extend_from_slice(&self.v);
+        state.extend_from_slice(&self.s);
+        state
+    }
+
+    fn load_state(&mut self, state: &[f32]) -> Result<()> {
+        let expected = 2 + self.v.len() + self.s.len();
+
+        if state.len() != expected {
+            return Err(MlErr::size_mismatch(
+                "optimizer state",
+                state.len(),
+                expected,
+            ));
+        }
+
+        let (betas, moments) = state.split_at(2);
+        let (v, s) = moments.split_at(self.v.len());
+        [self.beta1_t, self.beta2_t] = [betas[0], betas[1]];
+        self.v.copy_from_slice(v);
+        self.s.copy_from_slice(s);
+        Ok(())
+    }
+
+    fn reset_state(&mut self) {
+        [self.beta1_t, self.beta2_t] = [1., 1.];
+        self.v.fill(0.);
+        self.s.fill(0.);
+    }
 }
@@ -52,4 +52,12 @@ impl Optimizer for GradientDescent {
     fn learning_rate(&self) -> FloatPositive {
         self.learning_rate
     }
+
+    fn set_learning_rate(&mut self, learning_rate: FloatPositive) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn name(&self) -> &'static str {
+        "gradient_descent"
+    }
 }
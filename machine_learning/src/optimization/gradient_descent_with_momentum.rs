@@ -67,4 +67,33 @@ impl Optimizer for GradientDescentWithMomentum {
     fn learning_rate(&self) -> FloatPositive {
         self.learning_rate
     }
+
+    fn set_learning_rate(&mut self, learning_rate: FloatPositive) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn name(&self) -> &'static str {
+        "gradient_descent_with_momentum"
+    }
+
+    fn state(&self) -> Vec<f32> {
+        self.velocity.to_vec()
+    }
+
+    fn load_state(&mut self, state: &[f32]) -> Result<()> {
+        if state.len() != self.velocity.len() {
+            return Err(MlErr::size_mismatch(
+                "optimizer state",
+                state.len(),
+                self.velocity.len(),
+            ));
+        }
+
+        self.velocity.copy_from_slice(state);
+        Ok(())
+    }
+
+    fn reset_state(&mut self) {
+        self.velocity.fill(0.);
+    }
 }
@@ -0,0 +1,128 @@
+use std::{f32::consts::PI, num::NonZeroUsize};
+
+/// Defines how the learning rate of an optimizer changes as the training goes on.
+pub trait LrSchedule {
+    /// The learning rate to use during an epoch.
+    ///
+    /// # Args
+    /// * `base_lr` - The learning rate the optimizer was configured with.
+    /// * `epoch` - The epoch, starting from 0.
+    ///
+    /// # Returns
+    /// The scheduled learning rate.
+    fn learning_rate(&self, base_lr: f32, epoch: usize) -> f32;
+
+    /// Whether the schedule restarts at the given epoch.
+    ///
+    /// # Args
+    /// * `epoch` - The epoch, starting from 0.
+    ///
+    /// # Returns
+    /// `true` if a new cycle starts at `epoch`, the first epoch isn't a restart.
+    fn is_restart(&self, epoch: usize) -> bool;
+}
+
+/// Cosine annealing with warm restarts (SGDR).
+///
+/// The learning rate is annealed from `base_lr` down to `min_lr` following half a cosine
+/// wave over a cycle of `t_0` epochs, then jumps back to `base_lr`. Every new cycle is
+/// `t_mult` times longer than the previous one.
+#[derive(Debug, Clone, Copy)]
+pub struct WarmRestarts {
+    t_0: NonZeroUsize,
+    t_mult: NonZeroUsize,
+    min_lr: f32,
+}
+
+impl WarmRestarts {
+    /// Creates a new `WarmRestarts` schedule.
+    ///
+    /// # Args
+    /// * `t_0` - The length of the first cycle in epochs.
+    /// * `t_mult` - The factor by which each cycle is longer than the previous one.
+    /// * `min_lr` - The learning rate reached at the end of every cycle.
+    ///
+    /// # Returns
+    /// A new `WarmRestarts` instance.
+    pub fn new(t_0: NonZeroUsize, t_mult: NonZeroUsize, min_lr: f32) -> Self {
+        Self {
+            t_0,
+            t_mult,
+            min_lr,
+        }
+    }
+
+    /// Locates an epoch within it's cycle.
+    ///
+    /// # Args
+    /// * `epoch` - The epoch, starting from 0.
+    ///
+    /// # Returns
+    /// The index of the cycle, the epochs elapsed since it started and it's length.
+    pub fn cycle(&self, epoch: usize) -> (usize, usize, usize) {
+        let mut cycle = 0;
+        let mut start = 0;
+        let mut len = self.t_0.get();
+
+        while epoch >= start + len {
+            start += len;
+            len = len.saturating_mul(self.t_mult.get());
+            cycle += 1;
+        }
+
+        (cycle, epoch - start, len)
+    }
+}
+
+impl LrSchedule for WarmRestarts {
+    fn learning_rate(&self, base_lr: f32, epoch: usize) -> f32 {
+        let (_, t_cur, t_i) = self.cycle(epoch);
+        let progress = t_cur as f32 / t_i as f32;
+        self.min_lr + 0.5 * (base_lr - self.min_lr) * (1.0 + (PI * progress).cos())
+    }
+
+    fn is_restart(&self, epoch: usize) -> bool {
+        let (cycle, t_cur, _) = self.cycle(epoch);
+        cycle > 0 && t_cur == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_LR: f32 = 0.1;
+
+    fn warm_restarts(t_0: usize, t_mult: usize) -> WarmRestarts {
+        let t_0 = NonZeroUsize::new(t_0).unwrap();
+        let t_mult = NonZeroUsize::new(t_mult).unwrap();
+        WarmRestarts::new(t_0, t_mult, 0.001)
+    }
+
+    #[test]
+    fn test_lr_returns_to_base_at_each_restart() {
+        let schedule = warm_restarts(3, 2);
+        let restarts: Vec<_> = (0..25).filter(|&e| schedule.is_restart(e)).collect();
+
+        // Cycles of 3, 6 and 12 epochs.
+        assert_eq!(restarts, [3, 9, 21]);
+
+        assert!((schedule.learning_rate(BASE_LR, 0) - BASE_LR).abs() < f32::EPSILON);
+
+        for epoch in restarts {
+            assert!((schedule.learning_rate(BASE_LR, epoch) - BASE_LR).abs() < f32::EPSILON);
+            assert!(schedule.learning_rate(BASE_LR, epoch - 1) < BASE_LR);
+        }
+    }
+
+    #[test]
+    fn test_lr_is_annealed_within_a_cycle() {
+        let schedule = warm_restarts(5, 1);
+        let lrs: Vec<_> = (0..5).map(|e| schedule.learning_rate(BASE_LR, e)).collect();
+
+        assert!(lrs.windows(2).all(|w| w[1] < w[0]));
+        assert!(lrs.iter().all(|&lr| lr > 0.001));
+        assert!((schedule.learning_rate(BASE_LR, 5) - BASE_LR).abs() < f32::EPSILON);
+        assert_eq!(schedule.cycle(12), (2, 2, 5));
+    }
+}
@@ -1,9 +1,11 @@
 mod adam;
 mod gradient_descent;
 mod gradient_descent_with_momentum;
+mod lr_schedule;
 mod optimizer;
 
 pub use adam::Adam;
 pub use gradient_descent::GradientDescent;
 pub use gradient_descent_with_momentum::GradientDescentWithMomentum;
+pub use lr_schedule::{LrSchedule, WarmRestarts};
 pub use optimizer::Optimizer;
@@ -1,6 +1,6 @@
 use comms::floats::FloatPositive;
 
-use crate::Result;
+use crate::{MlErr, Result};
 
 /// Defines the strategy for updating model parameters based on calculated gradients.
 pub trait Optimizer {
@@ -19,4 +19,42 @@ pub trait Optimizer {
     /// # Returns
     /// It's learning rate.
     fn learning_rate(&self) -> FloatPositive;
+
+    /// Replaces the learning rate of the optimizer, keeping the rest of it's state.
+    ///
+    /// # Args
+    /// * `learning_rate` - The new learning rate.
+    fn set_learning_rate(&mut self, learning_rate: FloatPositive);
+
+    /// The name of the optimization algorithm, used to tag it's saved state.
+    ///
+    /// # Returns
+    /// The optimizer's name.
+    fn name(&self) -> &'static str;
+
+    /// Exports the inner state of the optimizer, such as it's moments or velocity.
+    ///
+    /// # Returns
+    /// The optimizer's state as a flat vector, empty for stateless optimizers.
+    fn state(&self) -> Vec<f32> {
+        Vec::new()
+    }
+
+    /// Restores a state previously exported with `Optimizer::state`.
+    ///
+    /// # Args
+    /// * `state` - The optimizer's state.
+    ///
+    /// # Returns
+    /// A size mismatch error if the length of `state` doesn't match this optimizer's.
+    fn load_state(&mut self, state: &[f32]) -> Result<()> {
+        if !state.is_empty() {
+            return Err(MlErr::size_mismatch("optimizer state", state.len(), 0));
+        }
+
+        Ok(())
+    }
+
+    /// Zeroes the inner state of the optimizer, as if it never took a step.
+    fn reset_state(&mut self) {}
 }
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use rayon::prelude::*;
 
 use crate::{MlErr, Result, optimization::Optimizer};
@@ -177,6 +179,83 @@ impl<'mw> ParamManager<'mw> {
             }
         });
     }
+
+    /// Clips the accumulated gradient of a range of the model's parameters to a maximum norm.
+    ///
+    /// The range may span the buffers of several entities, it's norm is reduced over all
+    /// of them and only the parameters inside of it are scaled.
+    ///
+    /// # Args
+    /// * `layer_sizes` - The amount of parameters of each of the model's layers.
+    /// * `range` - The range of parameters, numbered following the order of the layers.
+    /// * `max_norm` - The maximum norm of the range's accumulated gradient.
+    ///
+    /// # Returns
+    /// The norm of the range's accumulated gradient before clipping.
+    pub fn clip_residual(
+        &mut self,
+        layer_sizes: &[usize],
+        range: Range<usize>,
+        max_norm: f32,
+    ) -> f32 {
+        let segments = self.segments(layer_sizes, range);
+
+        let norm = segments
+            .iter()
+            .flat_map(|&(id, start, end)| &self.metadatas[id].residual[start..end])
+            .map(|&g| (g as f64).powi(2))
+            .sum::<f64>()
+            .sqrt() as f32;
+
+        if norm > max_norm {
+            let scale = max_norm / norm;
+
+            for (id, start, end) in segments {
+                for g in &mut self.metadatas[id].residual[start..end] {
+                    *g *= scale;
+                }
+            }
+        }
+
+        norm
+    }
+
+    /// Locates a range of the model's parameters within the entities' buffers.
+    ///
+    /// # Args
+    /// * `layer_sizes` - The amount of parameters of each of the model's layers.
+    /// * `range` - The range of parameters, numbered following the order of the layers.
+    ///
+    /// # Returns
+    /// The entity id, start and end of every piece of the range.
+    fn segments(&self, layer_sizes: &[usize], range: Range<usize>) -> Vec<(usize, usize, usize)> {
+        let mut cursors = vec![0; self.metadatas.len()];
+        let mut segments = Vec::new();
+        let mut offset = 0;
+
+        // The layers without parameters take no place in the ordering.
+        let sizes = layer_sizes.iter().filter(|&&size| size > 0);
+
+        for (i, &size) in sizes.enumerate() {
+            let Some(id) = self.layer_ordering.nth(i) else {
+                break;
+            };
+
+            let cursor = cursors[id];
+            cursors[id] += size;
+
+            let start = range.start.max(offset);
+            let end = range.end.min(offset + size);
+
+            if start < end {
+                segments.push((id, cursor + start - offset, cursor + end - offset));
+            }
+
+            offset += size;
+        }
+
+        segments
+    }
 }
 
 /// A model's layer iterator.
@@ -386,4 +465,40 @@ mod tests {
         assert!(front.next(0).unwrap().is_empty());
         assert_eq!(front.next(1).unwrap(), &[1.0]);
     }
+
+    #[test]
+    fn clip_residual_only_scales_the_groups_over_their_max_norm() {
+        const SERVER_SIZES: [usize; 2] = [19, 20];
+        const LAYER_SIZES: [usize; 5] = [9, 15, 0, 5, 10];
+        const ORDERING: [usize; 4] = [0, 1, 1, 0];
+
+        let mut params_grads: Vec<_> = SERVER_SIZES
+            .iter()
+            .map(|&size| (vec![0.0; size], vec![0.0; size], vec![1.0; size]))
+            .collect();
+
+        let servers: Vec<_> = params_grads
+            .iter_mut()
+            .map(|(params, grad, residual)| ParamsMetadata::new(params, grad, residual))
+            .collect();
+
+        let mut manager = ParamManager::for_parameter_server(servers, &ORDERING);
+
+        // The first two layers span both servers, the last one lives in the first.
+        let clipped = manager.clip_residual(&LAYER_SIZES, 0..24, 1.0);
+        let unclipped = manager.clip_residual(&LAYER_SIZES, 29..39, 5.0);
+        drop(manager);
+
+        assert!((clipped - 24f32.sqrt()).abs() < 1e-5);
+        assert!((unclipped - 10f32.sqrt()).abs() < 1e-5);
+
+        let (_, _, first) = &params_grads[0];
+        let (_, _, second) = &params_grads[1];
+        let scale = 1.0 / 24f32.sqrt();
+
+        assert!(first[..9].iter().all(|&g| (g - scale).abs() < 1e-6));
+        assert!(second[..15].iter().all(|&g| (g - scale).abs() < 1e-6));
+        assert!(first[9..].iter().all(|&g| g == 1.0));
+        assert!(second[15..].iter().all(|&g| g == 1.0));
+    }
 }
@@ -0,0 +1,86 @@
+use rand::{SeedableRng, rngs::StdRng};
+
+/// The random sources of a training run.
+///
+/// Each purpose gets it's own deterministic substream of the run's seed, so enabling
+/// one source of randomness doesn't shift the values drawn by the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RngPurpose {
+    WeightInit,
+    Shuffle,
+    /// The rows of the dataset held out for testing.
+    TestSplit,
+    /// The stochastic transformations of a worker's batches, seeded per worker by `worker_seed`.
+    Augmentation,
+}
+
+impl RngPurpose {
+    /// The name of the purpose.
+    ///
+    /// # Returns
+    /// A snake case name, meant for logging.
+    pub fn name(self) -> &'static str {
+        match self {
+            RngPurpose::WeightInit => "weight_init",
+            RngPurpose::Shuffle => "shuffle",
+            RngPurpose::TestSplit => "test_split",
+            RngPurpose::Augmentation => "augmentation",
+        }
+    }
+
+    /// The salt mixed into the seed for this purpose.
+    ///
+    /// # Returns
+    /// A constant unique to the purpose.
+    fn salt(self) -> u64 {
+        match self {
+            RngPurpose::WeightInit => 0x5745_4947_4854_5321,
+            RngPurpose::Shuffle => 0x5348_5546_464c_4521,
+            RngPurpose::TestSplit => 0x5445_5354_5350_4c21,
+            RngPurpose::Augmentation => 0x4155_474d_454e_5421,
+        }
+    }
+}
+
+/// Derives the seed of a source of randomness from the seed of the run.
+///
+/// # Args
+/// * `seed` - The seed of the run.
+/// * `purpose` - What the seed will be used for.
+///
+/// # Returns
+/// The seed `rng_for` seeds the purpose's generator with.
+pub fn derive_seed(seed: u64, purpose: RngPurpose) -> u64 {
+    seed ^ purpose.salt()
+}
+
+/// Offsets the seed of the run by a worker's id.
+///
+/// The weights must start out the same on every worker, so their initialization always
+/// uses the run's seed as is. The augmentation streams use this one instead, each worker
+/// draws different ones while the run stays reproducible.
+///
+/// # Args
+/// * `seed` - The seed of the run.
+/// * `worker_id` - The position of the worker in the training.
+///
+/// # Returns
+/// The worker's seed, `seed + worker_id`.
+pub fn worker_seed(seed: u64, worker_id: usize) -> u64 {
+    seed.wrapping_add(worker_id as u64)
+}
+
+/// Creates the random number generator for a source of randomness of the run.
+///
+/// # Args
+/// * `seed` - The seed of the run, `None` to seed it from the os.
+/// * `purpose` - What the generator will be used for.
+///
+/// # Returns
+/// A new rng, deterministic given the seed and the purpose.
+pub fn rng_for(seed: Option<u64>, purpose: RngPurpose) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(derive_seed(seed, purpose)),
+        None => StdRng::from_os_rng(),
+    }
+}
@@ -1,5 +1,19 @@
+mod test_checkpointing;
+mod test_closed_form;
+mod test_custom_layer;
+mod test_evaluate;
+mod test_fit;
+mod test_frozen_layers;
+mod test_gradient_check;
+mod test_multi_head;
+mod test_reproducibility;
+mod test_resume;
 mod test_sequential_conv_dense;
 mod test_sequential_dense;
+mod test_sparse_input;
+mod test_stop_condition;
+mod test_trainer_builder;
+mod test_weight_tying;
 
 use rand::Rng;
 
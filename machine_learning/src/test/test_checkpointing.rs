@@ -0,0 +1,76 @@
+use comms::floats::Float01;
+use ndarray::{Array2, ArrayView2};
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    arch::{
+        Sequential,
+        layers::Layer,
+        loss::{LossFn, Mse},
+    },
+    param_manager::{ParamManager, ParamsMetadata},
+    test::gen_params_grads,
+};
+
+/// A model deep enough to be split in a few segments.
+fn deep_model() -> Sequential {
+    Sequential::new(vec![
+        Layer::dense((2, 8)),
+        Layer::sigmoid(1.0),
+        Layer::dense((8, 8)),
+        Layer::tanh(1.0),
+        Layer::dense((8, 8)),
+        Layer::relu(Float01::new(0.1).unwrap()),
+        Layer::dense((8, 1)),
+    ])
+}
+
+/// Computes the gradient of the model over a couple of batches, so the second one goes
+/// through the buffers freed by the first.
+///
+/// # Args
+/// * `model` - The model to differentiate.
+/// * `params` - The model's parameters.
+///
+/// # Returns
+/// The gradient over the last batch.
+fn gradient(mut model: Sequential, mut params: Vec<f32>) -> Vec<f32> {
+    let mut grad = vec![0.0; params.len()];
+    let mut residual = vec![0.0; params.len()];
+    let mut loss_fn = Mse::new();
+
+    let servers = vec![ParamsMetadata::new(&mut params, &mut grad, &mut residual)];
+    let mut param_manager = ParamManager::for_parameter_server(servers, &[0; 4]);
+
+    for batch in 0..2 {
+        let x = Array2::from_shape_fn((3, 2), |(i, j)| (batch + i + j) as f32 * 0.3 - 0.5);
+        let y = Array2::from_shape_fn((3, 1), |(i, _)| i as f32 - batch as f32);
+
+        let y_pred = model
+            .forward(&mut param_manager, x.view().into_dyn())
+            .unwrap();
+        let (_, mut d) = loss_fn.loss_prime(y_pred, ArrayView2::from(&y).into_dyn());
+        model.backward(&mut param_manager, d.view_mut()).unwrap();
+    }
+
+    drop(param_manager);
+    grad
+}
+
+#[test]
+fn test_checkpointed_gradient_matches_the_stored_one() {
+    let model = deep_model();
+    let mut rng = StdRng::seed_from_u64(42);
+    let (params, ..) = gen_params_grads(&[model.size()], &mut rng).swap_remove(0);
+
+    let stored = gradient(model.clone(), params.clone());
+    assert!(stored.iter().any(|&g| g != 0.0));
+
+    for checkpoints in [vec![2], vec![2, 4, 5], vec![1, 2, 3, 4, 5, 6]] {
+        let checkpointed = gradient(model.clone().with_checkpoints(checkpoints), params.clone());
+
+        for (s, c) in stored.iter().zip(&checkpointed) {
+            assert!((s - c).abs() < 1e-6, "{s} != {c}");
+        }
+    }
+}
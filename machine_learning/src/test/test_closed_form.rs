@@ -0,0 +1,111 @@
+use std::num::NonZeroUsize;
+
+use comms::{
+    floats::FloatPositive,
+    specs::machine_learning::{
+        DatasetSpec, FinalStepPolicy, LayerSpec, LossFnSpec, OptimizerSpec, SamplerSpec,
+        StopCondition, StrategySpec, TrainerSpec,
+    },
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    MlErr,
+    datasets::DataSrc,
+    param_manager::{ParamManager, ParamsMetadata},
+    test::gen_params_grads,
+    training::TrainerBuilder,
+};
+
+const LR: f32 = 0.1;
+
+/// A closed form linear regression spec for a model made of the given dense layers.
+///
+/// # Args
+/// * `dims` - The input and output size of every layer.
+///
+/// # Returns
+/// A new `TrainerSpec`.
+fn spec(dims: &[(usize, usize)]) -> TrainerSpec {
+    let layers = dims
+        .iter()
+        .map(|&dim| LayerSpec::Dense {
+            dim,
+            act_fn: None,
+            tie: None,
+            frozen: false,
+        })
+        .collect();
+
+    TrainerSpec {
+        layers,
+        optimizer: OptimizerSpec::GradientDescent {
+            learning_rate: FloatPositive::new(LR).unwrap(),
+        },
+        dataset: DatasetSpec {
+            x_size: NonZeroUsize::new(2).unwrap(),
+            y_size: NonZeroUsize::MIN,
+            sparse: false,
+        },
+        loss_fn: LossFnSpec::Mse,
+        offline_epochs: 0,
+        stop_condition: StopCondition::MaxEpochs(NonZeroUsize::new(100).unwrap()),
+        final_step: FinalStepPolicy::ApplyPartial,
+        batch_size: NonZeroUsize::MIN,
+        sampler: SamplerSpec::Shuffle,
+        seed: None,
+        augmentation_seed: None,
+        clip_groups: Vec::new(),
+        heads: Vec::new(),
+        checkpoint_every: None,
+        resume: None,
+        strategy: StrategySpec::ClosedFormLinReg,
+    }
+}
+
+#[test]
+fn test_a_single_update_lands_on_the_least_squares_solution() {
+    // y = 2a - b + 3
+    let x = vec![0., 0., 1., 0., 0., 1., 2., 3., -1., 4.];
+    let y: Vec<_> = x.chunks(2).map(|r| 2. * r[0] - r[1] + 3.).collect();
+
+    let mut trainer = TrainerBuilder::new().build(spec(&[(2, 1)]), &[3]).unwrap();
+    trainer.load_dataset(DataSrc::inmem(x, y));
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut params_grads = gen_params_grads(&[3], &mut rng);
+    let initial = params_grads[0].0.clone();
+
+    let ordering = [0];
+    let servers: Vec<_> = params_grads
+        .iter_mut()
+        .map(|(params, grad, residual)| ParamsMetadata::new(params, grad, residual))
+        .collect();
+
+    let mut param_manager = ParamManager::for_parameter_server(servers, &ordering);
+    let res = trainer.train(&mut param_manager).unwrap();
+
+    assert!(res.was_last);
+    assert!(res.losses[0] < 1e-8, "{:?}", res.losses);
+
+    let (params, _, residual) = &params_grads[0];
+    let expected = [2., -1., 3.];
+
+    // The worker's own parameters and a server stepping over the sent gradient both land
+    // on the solution.
+    for i in 0..expected.len() {
+        let stepped = initial[i] - LR * residual[i];
+        assert!((params[i] - expected[i]).abs() < 1e-4, "{params:?}");
+        assert!((stepped - expected[i]).abs() < 1e-4, "{stepped}");
+    }
+}
+
+#[test]
+fn test_a_model_that_isnt_linear_is_rejected() {
+    // (2 + 1) * 3 + (3 + 1) * 1 parameters.
+    let Err(err) = TrainerBuilder::new().build(spec(&[(2, 3), (3, 1)]), &[13]) else {
+        panic!("a two layer model was fit with a linear regression");
+    };
+
+    assert!(matches!(err, MlErr::InvalidStrategy { .. }));
+}
@@ -0,0 +1,124 @@
+use comms::specs::machine_learning::LayerSpec;
+use ndarray::{Array2, ArrayD, ArrayViewD, ArrayViewMutD, Axis};
+use serde_json::json;
+
+use crate::{
+    MlErr, Result,
+    arch::layers::{CustomLayer, register_layer},
+    param_manager::ParamManager,
+    training::TrainerBuilder,
+};
+
+/// A layer scaling every feature of it's input by a parameter of it's own.
+#[derive(Debug, Clone)]
+struct Scale {
+    size: usize,
+    x: ArrayD<f32>,
+    y: ArrayD<f32>,
+}
+
+impl CustomLayer for Scale {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn forward<'a>(
+        &'a mut self,
+        params: &[f32],
+        x: ArrayViewD<'a, f32>,
+    ) -> Result<ArrayViewD<'a, f32>> {
+        self.x = x.to_owned();
+        self.y = x.to_owned();
+
+        for mut row in self.y.axis_iter_mut(Axis(0)) {
+            row.iter_mut().zip(params).for_each(|(y, w)| *y *= w);
+        }
+
+        Ok(self.y.view())
+    }
+
+    fn backward<'a>(
+        &'a mut self,
+        params: &[f32],
+        grad: &mut [f32],
+        mut d: ArrayViewMutD<'a, f32>,
+    ) -> Result<ArrayViewMutD<'a, f32>> {
+        grad.fill(0.);
+
+        for (mut d_row, x_row) in d.axis_iter_mut(Axis(0)).zip(self.x.axis_iter(Axis(0))) {
+            for ((d, x), (g, w)) in d_row.iter_mut().zip(x_row).zip(grad.iter_mut().zip(params)) {
+                *g += *d * x;
+                *d *= w;
+            }
+        }
+
+        Ok(d)
+    }
+
+    fn box_clone(&self) -> Box<dyn CustomLayer> {
+        Box::new(self.clone())
+    }
+}
+
+/// Registers the `Scale` layer, sized by the `size` field of it's parameters.
+fn register_scale() {
+    register_layer("scale", |params| {
+        let size = params["size"].as_u64().ok_or_else(|| MlErr::InvalidLayer {
+            name: "scale".into(),
+            reason: "missing the size".into(),
+        })?;
+
+        let layer = Scale {
+            size: size as usize,
+            x: ArrayD::zeros(vec![0]),
+            y: ArrayD::zeros(vec![0]),
+        };
+
+        Ok(Box::new(layer) as Box<dyn CustomLayer>)
+    });
+}
+
+#[test]
+fn test_a_registered_layer_is_laid_out_and_backpropagated() {
+    register_scale();
+
+    let layers = [LayerSpec::Custom {
+        name: "scale".into(),
+        params: json!({ "size": 2 }),
+    }];
+
+    let mut model = TrainerBuilder::new().build_model(&layers).unwrap();
+    assert_eq!(model.layer_sizes(), [2]);
+
+    let mut params = vec![2., 3.];
+    let mut grad = vec![0.; 2];
+    let mut residual = vec![0.; 2];
+    let mut param_manager = ParamManager::for_all_reduce(&mut params, &mut grad, &mut residual, 1);
+
+    let x = Array2::from_shape_vec((2, 2), vec![1., 1., 2., 2.]).unwrap();
+    let y = model
+        .forward(&mut param_manager, x.view().into_dyn())
+        .unwrap();
+    assert_eq!(y.as_slice().unwrap(), [2., 3., 4., 6.]);
+
+    let mut d = Array2::<f32>::ones((2, 2)).into_dyn();
+    model.backward(&mut param_manager, d.view_mut()).unwrap();
+    drop(param_manager);
+
+    // The gradient of every scale is the sum of the feature it scales.
+    assert_eq!(grad, [3., 3.]);
+}
+
+#[test]
+fn test_an_unregistered_layer_is_rejected_by_name() {
+    let layers = [LayerSpec::Custom {
+        name: "missing".into(),
+        params: json!({}),
+    }];
+
+    let Err(err) = TrainerBuilder::new().build_model(&layers) else {
+        panic!("a model was built out of an unregistered layer");
+    };
+
+    assert!(matches!(err, MlErr::UnknownLayer { name } if name == "missing"));
+}
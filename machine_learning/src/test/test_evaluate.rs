@@ -0,0 +1,82 @@
+use std::num::NonZeroUsize;
+
+use comms::{floats::FloatPositive, specs::machine_learning::StopCondition};
+use ndarray::ArrayView2;
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    arch::{Sequential, layers::Layer, loss::Mse},
+    datasets::{DataSrc, Dataset},
+    optimization::GradientDescent,
+    param_manager::{ParamManager, ParamsMetadata},
+    test::gen_params_grads,
+    training::{BackpropTrainer, Trainer},
+};
+
+const X: [f32; 4] = [0., 1., 2., 3.];
+const Y: [f32; 4] = [1., 2., 3., 4.];
+
+/// Trains a linear model for some epochs, optionally evaluating it after every one of them.
+///
+/// # Args
+/// * `evaluate` - Whether to evaluate the model between the training calls.
+///
+/// # Returns
+/// The trained parameters along with the losses of the evaluations.
+fn train(evaluate: bool) -> (Vec<f32>, Vec<f64>) {
+    let model = Sequential::new(vec![Layer::dense((1, 1))]);
+    let nparams = model.size();
+
+    let size = NonZeroUsize::new(1).unwrap();
+    let dataset = Dataset::loaded(DataSrc::inmem(X.into(), Y.into()), size, size);
+    let optimizer = GradientDescent::new(FloatPositive::new(0.01).unwrap());
+    let max_epochs = NonZeroUsize::new(5).unwrap();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let mut trainer = BackpropTrainer::new(
+        model,
+        vec![optimizer],
+        dataset,
+        Mse::new(),
+        0,
+        StopCondition::MaxEpochs(max_epochs),
+        size,
+        rng.clone(),
+    );
+
+    let ordering = [0];
+    let mut params_grads = gen_params_grads(&[nparams], &mut rng);
+    let x = ArrayView2::from_shape((4, 1), &X).unwrap();
+    let y = ArrayView2::from_shape((4, 1), &Y).unwrap();
+    let mut losses = Vec::new();
+
+    loop {
+        let servers: Vec<_> = params_grads
+            .iter_mut()
+            .map(|(params, grad, residual)| ParamsMetadata::new(params, grad, residual))
+            .collect();
+
+        let mut param_manager = ParamManager::for_parameter_server(servers, &ordering);
+
+        if evaluate {
+            losses.push(trainer.evaluate(&mut param_manager, x.into(), y).unwrap());
+        }
+
+        if trainer.train(&mut param_manager).unwrap().was_last {
+            break;
+        }
+    }
+
+    let (params, ..) = params_grads.swap_remove(0);
+    (params, losses)
+}
+
+#[test]
+fn test_evaluate_doesnt_change_the_training() {
+    let (trained, _) = train(false);
+    let (evaluated, losses) = train(true);
+
+    assert_eq!(trained, evaluated);
+    assert_eq!(losses.len(), 5);
+    assert!(losses.windows(2).all(|w| w[1] < w[0]));
+}
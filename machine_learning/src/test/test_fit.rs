@@ -0,0 +1,65 @@
+use std::num::NonZeroUsize;
+
+use comms::{floats::FloatPositive, specs::machine_learning::StopCondition};
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    arch::{Sequential, layers::Layer, loss::Mse},
+    datasets::{DataSrc, Dataset},
+    optimization::GradientDescent,
+    param_manager::{ParamManager, ParamsMetadata},
+    test::gen_params_grads,
+    training::{BackpropTrainer, Trainer},
+};
+
+const X: [f32; 8] = [0., 0., 0., 1., 1., 0., 1., 1.];
+const Y: [f32; 4] = [0., 1., 1., 0.];
+
+#[test]
+fn test_fit_on_xor_reports_a_decreasing_train_loss() {
+    let model = Sequential::new(vec![
+        Layer::dense((2, 2)),
+        Layer::sigmoid(1.),
+        Layer::dense((2, 1)),
+        Layer::sigmoid(1.),
+    ]);
+    let nparams = model.size();
+
+    let x_size = NonZeroUsize::new(2).unwrap();
+    let y_size = NonZeroUsize::new(1).unwrap();
+    let train = Dataset::loaded(DataSrc::inmem(X.into(), Y.into()), x_size, y_size);
+    let val = Dataset::loaded(DataSrc::inmem(X.into(), Y.into()), x_size, y_size);
+    let optimizer = GradientDescent::new(FloatPositive::new(1.).unwrap());
+    let max_epochs = NonZeroUsize::new(1000).unwrap();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let mut trainer = BackpropTrainer::new(
+        model,
+        vec![optimizer],
+        train,
+        Mse::new(),
+        0,
+        StopCondition::MaxEpochs(max_epochs),
+        NonZeroUsize::new(4).unwrap(),
+        rng.clone(),
+    );
+
+    let ordering = [0, 0];
+    let mut params_grads = gen_params_grads(&[nparams], &mut rng);
+    let servers: Vec<_> = params_grads
+        .iter_mut()
+        .map(|(params, grad, residual)| ParamsMetadata::new(params, grad, residual))
+        .collect();
+
+    let mut param_manager = ParamManager::for_parameter_server(servers, &ordering);
+    let calls = NonZeroUsize::new(5000).unwrap();
+    let history = trainer.fit(&mut param_manager, &val, calls, None).unwrap();
+
+    // The trainer's own stop condition ends the fit before the calls run out.
+    assert_eq!(history.train_losses.len(), 1000);
+    assert_eq!(history.val_losses.len(), 1000);
+
+    let (first, last) = (history.train_losses[0], history.train_losses[999]);
+    assert!(last < first, "train loss went from {first} to {last}");
+    assert!(history.val_losses[999] < history.val_losses[0]);
+}
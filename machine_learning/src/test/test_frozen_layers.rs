@@ -0,0 +1,107 @@
+use std::num::NonZeroUsize;
+
+use comms::{
+    floats::{Float01, FloatPositive},
+    specs::machine_learning::{
+        ActFnSpec, DatasetSpec, FinalStepPolicy, LayerSpec, LossFnSpec, OptimizerSpec, SamplerSpec,
+        StopCondition, StrategySpec, TrainerSpec,
+    },
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    datasets::DataSrc,
+    param_manager::{ParamManager, ParamsMetadata},
+    test::gen_params_grads,
+    training::TrainerBuilder,
+};
+
+/// The two frozen layers, `(2 + 1) * 3 + (3 + 1) * 3`, followed by the head's `3 + 1`.
+const FROZEN: usize = 21;
+const SERVER_SIZES: [usize; 1] = [FROZEN + 4];
+const ORDERING: [usize; 3] = [0; 3];
+const X: [f32; 8] = [0.0, 1.0, 1.0, 0.0, 0.5, 0.5, 1.0, 1.0];
+const Y: [f32; 4] = [1.0, 1.0, 0.5, 0.0];
+
+/// A model whose first two layers are frozen, fine-tuning only the head.
+///
+/// # Returns
+/// A new `TrainerSpec`.
+fn spec() -> TrainerSpec {
+    let dense = |dim, frozen| LayerSpec::Dense {
+        dim,
+        act_fn: Some(ActFnSpec::Tanh {
+            amp: 1.0,
+            clamp: None,
+        }),
+        tie: None,
+        frozen,
+    };
+
+    TrainerSpec {
+        layers: vec![
+            dense((2, 3), true),
+            dense((3, 3), true),
+            dense((3, 1), false),
+        ],
+        optimizer: OptimizerSpec::Adam {
+            learning_rate: FloatPositive::new(0.05).unwrap(),
+            beta1: Float01::new(0.9).unwrap(),
+            beta2: Float01::new(0.999).unwrap(),
+            epsilon: FloatPositive::new(1e-8).unwrap(),
+        },
+        dataset: DatasetSpec {
+            x_size: NonZeroUsize::new(2).unwrap(),
+            y_size: NonZeroUsize::MIN,
+            sparse: false,
+        },
+        loss_fn: LossFnSpec::Mse,
+        offline_epochs: 0,
+        stop_condition: StopCondition::MaxEpochs(NonZeroUsize::new(20).unwrap()),
+        final_step: FinalStepPolicy::ApplyPartial,
+        batch_size: NonZeroUsize::new(2).unwrap(),
+        sampler: SamplerSpec::Shuffle,
+        seed: Some(42),
+        augmentation_seed: None,
+        clip_groups: Vec::new(),
+        heads: Vec::new(),
+        checkpoint_every: None,
+        resume: None,
+        strategy: StrategySpec::Backprop,
+    }
+}
+
+#[test]
+fn test_frozen_layers_keep_their_weights_while_the_head_trains() {
+    let mut trainer = TrainerBuilder::new().build(spec(), &SERVER_SIZES).unwrap();
+    trainer.load_dataset(DataSrc::inmem(X.into(), Y.into()));
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut params_grads = gen_params_grads(&SERVER_SIZES, &mut rng);
+    let initial = params_grads[0].0.clone();
+
+    for _ in 0..20 {
+        let (params, grad, residual) = &mut params_grads[0];
+        let servers = vec![ParamsMetadata::new(params, grad, residual)];
+        let mut param_manager = ParamManager::for_parameter_server(servers, &ORDERING);
+
+        trainer.train(&mut param_manager).unwrap();
+        drop(param_manager);
+
+        // The workers don't even compute the gradient of the frozen layers.
+        assert!(residual[..FROZEN].iter().all(|&g| g == 0.0));
+        assert!(residual[FROZEN..].iter().any(|&g| g != 0.0));
+
+        let servers = vec![ParamsMetadata::new(params, grad, residual)];
+        let mut param_manager = ParamManager::for_parameter_server(servers, &ORDERING);
+        trainer.optimize(&mut param_manager).unwrap();
+        drop(param_manager);
+        residual.fill(0.0);
+    }
+
+    let bits = |params: &[f32]| params.iter().map(|p| p.to_bits()).collect::<Vec<_>>();
+    let trained = &params_grads[0].0;
+
+    assert_eq!(bits(&trained[..FROZEN]), bits(&initial[..FROZEN]));
+    assert_ne!(trained[FROZEN..], initial[FROZEN..]);
+}
@@ -0,0 +1,73 @@
+use ndarray::{Array2, ArrayView2};
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    arch::{
+        Sequential,
+        layers::Layer,
+        loss::{LossFn, Mse},
+    },
+    param_manager::{ParamManager, ParamsMetadata},
+    test::gen_params_grads,
+};
+
+/// Runs the model over a fixed batch.
+///
+/// # Args
+/// * `model` - The model to run.
+/// * `params` - The model's parameters.
+///
+/// # Returns
+/// The loss over the batch and it's gradient with respect to the parameters.
+fn loss_and_gradient(model: &mut Sequential, params: &[f32]) -> (f64, Vec<f32>) {
+    let mut params = params.to_vec();
+    let mut grad = vec![0.0; params.len()];
+    let mut residual = vec![0.0; params.len()];
+    let mut loss_fn = Mse::new();
+
+    let servers = vec![ParamsMetadata::new(&mut params, &mut grad, &mut residual)];
+    let mut param_manager = ParamManager::for_parameter_server(servers, &[0; 3]);
+
+    let x = Array2::from_shape_fn((4, 3), |(i, j)| (i + 2 * j) as f32 * 0.25 - 0.75);
+    let y = Array2::from_shape_fn((4, 2), |(i, j)| (i * j) as f32 * 0.2);
+
+    let y_pred = model
+        .forward(&mut param_manager, x.view().into_dyn())
+        .unwrap();
+    let (loss, mut d) = loss_fn.loss_prime(y_pred, ArrayView2::from(&y).into_dyn());
+    model.backward(&mut param_manager, d.view_mut()).unwrap();
+
+    drop(param_manager);
+    (loss, grad)
+}
+
+#[test]
+fn test_the_dense_and_sigmoid_gradient_matches_finite_differences() {
+    const EPS: f32 = 1e-2;
+
+    let mut model = Sequential::new(vec![
+        Layer::dense((3, 4)),
+        Layer::sigmoid(1.0),
+        Layer::dense((4, 2)),
+    ]);
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let (params, ..) = gen_params_grads(&[model.size()], &mut rng).swap_remove(0);
+    let (_, grad) = loss_and_gradient(&mut model, &params);
+
+    for (i, &g) in grad.iter().enumerate() {
+        let mut plus = params.clone();
+        plus[i] += EPS;
+        let mut minus = params.clone();
+        minus[i] -= EPS;
+
+        let (loss_plus, _) = loss_and_gradient(&mut model, &plus);
+        let (loss_minus, _) = loss_and_gradient(&mut model, &minus);
+        let numeric = (loss_plus - loss_minus) / (2.0 * EPS as f64);
+
+        assert!(
+            (numeric - g as f64).abs() < 1e-3,
+            "param {i}: backprop {g} != finite differences {numeric}"
+        );
+    }
+}
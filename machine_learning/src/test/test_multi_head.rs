@@ -0,0 +1,131 @@
+use std::num::NonZeroUsize;
+
+use comms::{
+    floats::FloatPositive,
+    specs::machine_learning::{
+        DatasetSpec, FinalStepPolicy, HeadSpec, LayerSpec, LossFnSpec, OptimizerSpec, SamplerSpec,
+        StopCondition, StrategySpec, TrainerSpec,
+    },
+};
+use ndarray::{ArrayView2, s};
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    arch::{
+        Sequential,
+        layers::Layer,
+        loss::{LossFn, Mse},
+    },
+    datasets::DataSrc,
+    param_manager::{ParamManager, ParamsMetadata},
+    test::gen_params_grads,
+    training::TrainerBuilder,
+};
+
+/// Evaluates one of the heads as a model of it's own, the shared layers followed by it's layers.
+///
+/// # Args
+/// * `params` - The shared layers' parameters followed by the head's.
+/// * `x` - The samples.
+/// * `y` - The head's targets.
+///
+/// # Returns
+/// The head's loss.
+fn head_loss(mut params: Vec<f32>, x: ArrayView2<f32>, y: ArrayView2<f32>) -> f64 {
+    let mut model = Sequential::new(vec![Layer::dense((1, 4)), Layer::dense((4, 1))]);
+    let mut grad = vec![0.0; params.len()];
+    let mut residual = vec![0.0; params.len()];
+
+    let servers = vec![ParamsMetadata::new(&mut params, &mut grad, &mut residual)];
+    let mut param_manager = ParamManager::for_parameter_server(servers, &[0, 0]);
+
+    let y_pred = model.forward(&mut param_manager, x.into_dyn()).unwrap();
+    Mse::new().loss(y_pred, y.into_dyn())
+}
+
+#[test]
+fn test_two_heads_converge_on_their_own_targets() {
+    let xs = [-1.0, -0.5, 0.0, 0.5, 1.0];
+    let x: Vec<f32> = xs.to_vec();
+    let y: Vec<f32> = xs.iter().flat_map(|&x| [2.0 * x + 1.0, 0.5 - x]).collect();
+
+    let head = |weight| HeadSpec {
+        layers: vec![LayerSpec::Dense {
+            dim: (4, 1),
+            act_fn: None,
+            tie: None,
+            frozen: false,
+        }],
+        loss_fn: LossFnSpec::Mse,
+        y_size: NonZeroUsize::MIN,
+        weight,
+    };
+
+    let spec = TrainerSpec {
+        layers: vec![LayerSpec::Dense {
+            dim: (1, 4),
+            act_fn: None,
+            tie: None,
+            frozen: false,
+        }],
+        optimizer: OptimizerSpec::GradientDescent {
+            learning_rate: FloatPositive::new(0.02).unwrap(),
+        },
+        dataset: DatasetSpec {
+            x_size: NonZeroUsize::MIN,
+            y_size: NonZeroUsize::new(2).unwrap(),
+            sparse: false,
+        },
+        loss_fn: LossFnSpec::Mse,
+        offline_epochs: 0,
+        stop_condition: StopCondition::MaxEpochs(NonZeroUsize::new(500).unwrap()),
+        final_step: FinalStepPolicy::ApplyPartial,
+        batch_size: NonZeroUsize::new(5).unwrap(),
+        sampler: SamplerSpec::Shuffle,
+        seed: Some(42),
+        augmentation_seed: None,
+        clip_groups: Vec::new(),
+        heads: vec![head(None), head(Some(FloatPositive::new(2.0).unwrap()))],
+        checkpoint_every: None,
+        resume: None,
+        strategy: StrategySpec::Backprop,
+    };
+
+    // The shared layer's 8 parameters are followed by the 5 of every head.
+    let nparams = 8 + 5 + 5;
+    let mut trainer = TrainerBuilder::new().build(spec, &[nparams]).unwrap();
+    trainer.load_dataset(DataSrc::inmem(x.clone(), y.clone()));
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut params_grads = gen_params_grads(&[nparams], &mut rng);
+    let losses = loop {
+        let servers: Vec<_> = params_grads
+            .iter_mut()
+            .map(|(params, grad, residual)| ParamsMetadata::new(params, grad, residual))
+            .collect();
+
+        let mut param_manager = ParamManager::for_parameter_server(servers, &[0, 0, 0]);
+        let res = trainer.train(&mut param_manager).unwrap();
+
+        if res.was_last {
+            break res.losses.to_vec();
+        }
+    };
+
+    assert!(
+        losses[0] < 1e-4,
+        "the weighted loss didn't converge: {losses:?}"
+    );
+
+    let params = &params_grads[0].0;
+    let x = ArrayView2::from_shape((5, 1), &x).unwrap();
+    let targets = ArrayView2::from_shape((5, 2), &y).unwrap();
+
+    for (head, range) in [8..13, 13..18].into_iter().enumerate() {
+        let head_params = [&params[..8], &params[range]].concat();
+        let y = targets.slice(s![.., head..head + 1]);
+        let loss = head_loss(head_params, x, y);
+
+        assert!(loss < 1e-4, "head {head} didn't converge: {loss}");
+    }
+}
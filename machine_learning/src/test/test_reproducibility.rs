@@ -0,0 +1,95 @@
+use std::num::NonZeroUsize;
+
+use comms::{
+    floats::FloatPositive,
+    specs::machine_learning::{DistributionSpec, ParamGenSpec, StopCondition},
+};
+
+use crate::{
+    arch::{Sequential, layers::Layer, loss::Mse},
+    datasets::{DataSrc, Dataset},
+    initialization::ParamGenBuilder,
+    optimization::GradientDescent,
+    param_manager::{ParamManager, ParamsMetadata},
+    rng::{self, RngPurpose},
+    training::{BackpropTrainer, Trainer},
+};
+
+/// Initializes and trains a small model with shuffling on, returning it's final parameters.
+fn train_run(seed: u64) -> Vec<f32> {
+    let x = [
+        0., 0., 0., 1., 1., 0., 1., 1., 0., 0., 0., 1., 1., 0., 1., 1.,
+    ];
+    let y = [0., 1., 1., 0., 0., 1., 1., 0.];
+
+    let model = Sequential::new(vec![
+        Layer::dense((2, 3)),
+        Layer::sigmoid(1.),
+        Layer::dense((3, 1)),
+        Layer::sigmoid(1.),
+    ]);
+    let nparams = model.size();
+
+    let spec = ParamGenSpec::Rand {
+        distribution: DistributionSpec::XavierUniform {
+            fan_in: 2,
+            fan_out: 1,
+        },
+        limit: nparams,
+        gain: 1.0,
+    };
+    let mut param_gen = ParamGenBuilder::new().build(spec, seed).unwrap();
+    let mut params = param_gen.sample_remaining().unwrap();
+    let mut grad = vec![0.; nparams];
+    let mut residual = vec![0.; nparams];
+
+    let x_size = NonZeroUsize::new(2).unwrap();
+    let y_size = NonZeroUsize::new(1).unwrap();
+    let dataset = Dataset::loaded(DataSrc::inmem(x.into(), y.into()), x_size, y_size);
+
+    let mut trainer = BackpropTrainer::new(
+        model,
+        vec![GradientDescent::new(FloatPositive::new(0.5).unwrap())],
+        dataset,
+        Mse::new(),
+        0,
+        StopCondition::MaxEpochs(NonZeroUsize::new(20).unwrap()),
+        NonZeroUsize::new(2).unwrap(),
+        rng::rng_for(Some(seed), RngPurpose::Shuffle),
+    );
+
+    let ordering = [0, 0];
+    let servers = vec![ParamsMetadata::new(&mut params, &mut grad, &mut residual)];
+    let mut param_manager = ParamManager::for_parameter_server(servers, &ordering);
+
+    loop {
+        let was_last = trainer.train(&mut param_manager).unwrap().was_last;
+        trainer.optimize(&mut param_manager).unwrap();
+        param_manager.zero_grad();
+
+        if was_last {
+            break;
+        }
+    }
+
+    drop(param_manager);
+    params
+}
+
+#[test]
+fn test_same_seed_runs_end_with_identical_weights() {
+    let first = train_run(42);
+
+    assert_eq!(first, train_run(42));
+    assert_ne!(first, train_run(43));
+}
+
+#[test]
+fn test_purposes_get_independent_substreams() {
+    use rand::Rng;
+
+    let mut init = rng::rng_for(Some(42), RngPurpose::WeightInit);
+    let mut shuffle = rng::rng_for(Some(42), RngPurpose::Shuffle);
+
+    assert_ne!(init.random::<u64>(), shuffle.random::<u64>());
+}
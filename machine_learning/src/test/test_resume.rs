@@ -0,0 +1,121 @@
+use std::num::NonZeroUsize;
+
+use comms::{
+    floats::FloatPositive,
+    specs::machine_learning::{StopCondition, TrainerProgress},
+};
+use rand::rngs::StdRng;
+
+use crate::{
+    arch::{Sequential, layers::Layer, loss::Mse},
+    datasets::{DataSrc, Dataset},
+    optimization::GradientDescent,
+    param_manager::{ParamManager, ParamsMetadata},
+    rng::{self, RngPurpose},
+    training::{BackpropTrainer, Trainer},
+};
+
+const SEED: u64 = 42;
+const EPOCHS: usize = 6;
+const X: [f32; 8] = [0., 1., 2., 3., 4., 5., 6., 7.];
+const Y: [f32; 8] = [1., 3., 5., 7., 9., 11., 13., 15.];
+
+/// Builds a fresh trainer over an empty dataset, seeded the same way on every call.
+fn trainer() -> BackpropTrainer<GradientDescent, Mse, StdRng> {
+    let size = NonZeroUsize::new(1).unwrap();
+
+    BackpropTrainer::new(
+        Sequential::new(vec![Layer::dense((1, 1))]),
+        vec![GradientDescent::new(FloatPositive::new(0.01).unwrap())],
+        Dataset::new(size, size),
+        Mse::new(),
+        0,
+        StopCondition::MaxEpochs(NonZeroUsize::new(EPOCHS).unwrap()),
+        size,
+        rng::rng_for(Some(SEED), RngPurpose::Shuffle),
+    )
+}
+
+/// Trains for some rounds, recording the parameters after each of them.
+///
+/// Every sample is it's own batch, so the parameters depend on the order of the batches.
+///
+/// # Args
+/// * `trainer` - The trainer, it's dataset is loaded before training.
+/// * `params` - The parameters to train, updated in place.
+/// * `rounds` - The amount of rounds to train for.
+///
+/// # Returns
+/// The parameters after every round.
+fn train(trainer: &mut dyn Trainer, params: &mut [f32], rounds: usize) -> Vec<Vec<f32>> {
+    trainer.load_dataset(DataSrc::inmem(X.into(), Y.into()));
+
+    let mut grad = vec![0.; params.len()];
+    let mut residual = vec![0.; params.len()];
+    let ordering = [0];
+
+    (0..rounds)
+        .map(|_| {
+            let servers = vec![ParamsMetadata::new(params, &mut grad, &mut residual)];
+            let mut param_manager = ParamManager::for_parameter_server(servers, &ordering);
+            trainer.train(&mut param_manager).unwrap();
+            trainer.optimize(&mut param_manager).unwrap();
+            param_manager.zero_grad();
+            drop(param_manager);
+
+            params.to_vec()
+        })
+        .collect()
+}
+
+/// The order the dataset's samples ended up in.
+///
+/// # Args
+/// * `trainer` - The trainer holding the dataset.
+///
+/// # Returns
+/// The samples, in the order of the last epoch's batches.
+fn order(trainer: Box<dyn Trainer>) -> Vec<f32> {
+    let dataset = trainer.into_dataset();
+    let (samples, _) = dataset.partition(1).next().unwrap().unwrap();
+    samples.to_vec()
+}
+
+#[test]
+fn test_a_resumed_run_sees_the_batches_of_an_uninterrupted_one() {
+    let mut straight = trainer();
+    let mut straight_params = vec![0.1, -0.2];
+    let straight_rounds = train(&mut straight, &mut straight_params, EPOCHS);
+
+    let interrupted_at = EPOCHS / 2;
+    let mut interrupted = trainer();
+    let mut params = vec![0.1, -0.2];
+    let mut rounds = train(&mut interrupted, &mut params, interrupted_at);
+
+    let progress = interrupted.progress();
+    assert_eq!(
+        progress,
+        TrainerProgress {
+            epoch: interrupted_at,
+            step: interrupted_at * X.len(),
+        }
+    );
+
+    let mut resumed: Box<dyn Trainer> = Box::new(trainer().resumed_from(progress));
+    rounds.extend(train(
+        resumed.as_mut(),
+        &mut params,
+        EPOCHS - interrupted_at,
+    ));
+
+    assert_eq!(rounds, straight_rounds);
+    assert_eq!(resumed.progress(), straight.progress());
+
+    let straight_order = order(Box::new(straight));
+    assert_eq!(order(resumed), straight_order);
+
+    // Without replaying the shuffles the second half would start over the first permutations.
+    let mut restarted = trainer();
+    train(&mut restarted, &mut [0.1, -0.2], EPOCHS - interrupted_at);
+    assert_ne!(order(Box::new(restarted)), straight_order);
+}
@@ -1,6 +1,6 @@
 use std::num::NonZeroUsize;
 
-use comms::floats::FloatPositive;
+use comms::{floats::FloatPositive, specs::machine_learning::StopCondition};
 use ndarray::ArrayView2;
 use rand::{SeedableRng, rngs::StdRng};
 
@@ -55,7 +55,7 @@ fn test_conv_dense(
     let batch_size = NonZeroUsize::new(4).unwrap();
     let optimizer = GradientDescent::new(FloatPositive::new(1.).unwrap());
     let mut loss_fn = CrossEntropy::new();
-    let mut rng = StdRng::from_os_rng();
+    let mut rng = StdRng::seed_from_u64(42);
 
     let mut trainer = BackpropTrainer::new(
         model.clone(),
@@ -63,7 +63,7 @@ fn test_conv_dense(
         dataset,
         loss_fn.clone(),
         offline_epochs,
-        max_epochs,
+        StopCondition::MaxEpochs(max_epochs),
         batch_size,
         rng.clone(),
     );
@@ -347,7 +347,7 @@ fn test_machine_learning09_5by5by2_symbols_conv_max_pooling_dense_softmax() {
         dataset,
         loss_fn.clone(),
         offline_epochs,
-        max_epochs,
+        StopCondition::MaxEpochs(max_epochs),
         batch_size,
         rng.clone(),
     );
@@ -425,7 +425,7 @@ fn test_machine_learning_dimensionality_correctness() {
         dataset,
         loss_fn,
         offline_epochs,
-        max_epochs,
+        StopCondition::MaxEpochs(max_epochs),
         batch_size,
         rng.clone(),
     );
@@ -507,7 +507,7 @@ fn test_machine_learning_dimensionality_correctness_with_max_pooling() {
         dataset,
         loss_fn,
         offline_epochs,
-        max_epochs,
+        StopCondition::MaxEpochs(max_epochs),
         batch_size,
         rng.clone(),
     );
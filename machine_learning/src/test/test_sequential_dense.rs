@@ -1,6 +1,6 @@
 use std::num::NonZeroUsize;
 
-use comms::floats::FloatPositive;
+use comms::{floats::FloatPositive, specs::machine_learning::StopCondition};
 use ndarray::ArrayView2;
 use rand::{SeedableRng, rngs::StdRng};
 
@@ -43,7 +43,7 @@ fn test_machine_learning00_linear_convergence() {
         dataset,
         loss_fn.clone(),
         offline_epochs,
-        max_epochs,
+        StopCondition::MaxEpochs(max_epochs),
         batch_size,
         rng.clone(),
     );
@@ -100,7 +100,7 @@ fn test_machine_learning01_and2_gate_convergence() {
         dataset,
         loss_fn.clone(),
         offline_epochs,
-        max_epochs,
+        StopCondition::MaxEpochs(max_epochs),
         batch_size,
         rng.clone(),
     );
@@ -166,7 +166,7 @@ fn test_machine_learning02_and3_gate_convergence() {
         dataset,
         loss_fn.clone(),
         offline_epochs,
-        max_epochs,
+        StopCondition::MaxEpochs(max_epochs),
         batch_size,
         rng.clone(),
     );
@@ -228,7 +228,7 @@ fn test_machine_learning03_xor2_gate_convergence() {
         dataset,
         loss_fn.clone(),
         offline_epochs,
-        max_epochs,
+        StopCondition::MaxEpochs(max_epochs),
         batch_size,
         rng.clone(),
     );
@@ -307,7 +307,7 @@ fn test_machine_learning04_xor4_gate_convergence() {
         dataset,
         loss_fn.clone(),
         offline_epochs,
-        max_epochs,
+        StopCondition::MaxEpochs(max_epochs),
         batch_size,
         rng.clone(),
     );
@@ -0,0 +1,130 @@
+use std::num::NonZeroUsize;
+
+use comms::{
+    floats::FloatPositive,
+    specs::machine_learning::{
+        ActFnSpec, DatasetSpec, FinalStepPolicy, LayerSpec, LossFnSpec, OptimizerSpec, SamplerSpec,
+        StopCondition, StrategySpec, TrainerSpec,
+    },
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    datasets::{CsrMatrix, DataSrc},
+    param_manager::{ParamManager, ParamsMetadata},
+    test::gen_params_grads,
+    training::TrainerBuilder,
+};
+
+/// The dense layers, `(6 + 1) * 4 + (4 + 1) * 1`.
+const SERVER_SIZES: [usize; 1] = [33];
+const ORDERING: [usize; 2] = [0; 2];
+const EPOCHS: usize = 10;
+
+/// A two layer model trained on shuffled batches of 6 features.
+///
+/// # Returns
+/// A new `TrainerSpec`.
+fn spec() -> TrainerSpec {
+    let dense = |dim, act_fn| LayerSpec::Dense {
+        dim,
+        act_fn,
+        tie: None,
+        frozen: false,
+    };
+
+    TrainerSpec {
+        layers: vec![
+            dense(
+                (6, 4),
+                Some(ActFnSpec::Tanh {
+                    amp: 1.0,
+                    clamp: None,
+                }),
+            ),
+            dense((4, 1), None),
+        ],
+        optimizer: OptimizerSpec::GradientDescent {
+            learning_rate: FloatPositive::new(0.1).unwrap(),
+        },
+        dataset: DatasetSpec {
+            x_size: NonZeroUsize::new(6).unwrap(),
+            y_size: NonZeroUsize::MIN,
+            sparse: true,
+        },
+        loss_fn: LossFnSpec::Mse,
+        offline_epochs: 0,
+        stop_condition: StopCondition::MaxEpochs(NonZeroUsize::new(EPOCHS).unwrap()),
+        final_step: FinalStepPolicy::ApplyPartial,
+        batch_size: NonZeroUsize::new(3).unwrap(),
+        sampler: SamplerSpec::Shuffle,
+        seed: Some(42),
+        augmentation_seed: None,
+        clip_groups: Vec::new(),
+        heads: Vec::new(),
+        checkpoint_every: None,
+        resume: None,
+        strategy: StrategySpec::Backprop,
+    }
+}
+
+/// Trains a new trainer on the given rows until it's stop condition is met.
+///
+/// # Args
+/// * `src` - The rows to train on.
+///
+/// # Returns
+/// The trained parameters and the losses of every epoch.
+fn train(src: DataSrc) -> (Vec<f32>, Vec<f64>) {
+    let mut trainer = TrainerBuilder::new().build(spec(), &SERVER_SIZES).unwrap();
+    trainer.load_dataset(src);
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let (mut params, mut grad, mut residual) = gen_params_grads(&SERVER_SIZES, &mut rng).remove(0);
+    let mut losses = Vec::new();
+
+    loop {
+        let servers = vec![ParamsMetadata::new(&mut params, &mut grad, &mut residual)];
+        let mut param_manager = ParamManager::for_parameter_server(servers, &ORDERING);
+        let res = trainer.train(&mut param_manager).unwrap();
+        losses.extend_from_slice(res.losses);
+        let was_last = res.was_last;
+
+        trainer.optimize(&mut param_manager).unwrap();
+        drop(param_manager);
+        residual.fill(0.0);
+
+        if was_last {
+            break;
+        }
+    }
+
+    (params, losses)
+}
+
+#[test]
+fn test_training_on_sparse_samples_matches_training_on_dense_ones() {
+    let samples: Vec<_> = (0..60)
+        .map(|i| match i % 7 {
+            0 => i as f32 * 0.05,
+            3 => -0.5,
+            _ => 0.0,
+        })
+        .collect();
+    let labels: Vec<_> = (0..10).map(|i| (i % 3) as f32 * 0.5 - 0.5).collect();
+    let csr = CsrMatrix::from_dense(&samples, 6).unwrap();
+
+    let (dense_params, dense_losses) = train(DataSrc::inmem(samples, labels.clone()));
+    let (sparse_params, sparse_losses) = train(DataSrc::csr(csr, labels));
+
+    assert_eq!(dense_losses.len(), EPOCHS);
+    assert_eq!(sparse_losses.len(), dense_losses.len());
+
+    for (sparse, dense) in sparse_losses.iter().zip(&dense_losses) {
+        assert!((sparse - dense).abs() < 1e-6, "{sparse} != {dense}");
+    }
+
+    for (sparse, dense) in sparse_params.iter().zip(&dense_params) {
+        assert!((sparse - dense).abs() < 1e-5, "{sparse} != {dense}");
+    }
+}
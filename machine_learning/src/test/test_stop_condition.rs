@@ -0,0 +1,188 @@
+use std::{
+    num::NonZeroUsize,
+    time::{Duration, Instant},
+};
+
+use comms::{
+    floats::FloatPositive,
+    specs::machine_learning::{FinalStepPolicy, StopCondition},
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    arch::{Sequential, layers::Layer, loss::Mse},
+    datasets::{DataSrc, Dataset},
+    optimization::GradientDescent,
+    param_manager::{ParamManager, ParamsMetadata},
+    test::gen_params_grads,
+    training::{BackpropTrainer, Trainer},
+};
+
+/// Trains a linear model over 4 samples with batches of 1 until the stop condition is met.
+///
+/// # Args
+/// * `offline_epochs` - The amount of extra epochs to run per training call.
+/// * `stop_condition` - When the training should stop.
+///
+/// # Returns
+/// The amount of losses reported on every training call.
+fn train_until_done(offline_epochs: usize, stop_condition: StopCondition) -> Vec<usize> {
+    let final_step = FinalStepPolicy::ApplyPartial;
+    train_with_final_step(offline_epochs, stop_condition, final_step).0
+}
+
+/// Trains a linear model over 4 samples with batches of 1 until the stop condition is met.
+///
+/// # Args
+/// * `offline_epochs` - The amount of extra epochs to run per training call.
+/// * `stop_condition` - When the training should stop.
+/// * `final_step` - What to do with the epoch the stop condition falls in the middle of.
+///
+/// # Returns
+/// The amount of losses reported on every training call and the server's buffers.
+fn train_with_final_step(
+    offline_epochs: usize,
+    stop_condition: StopCondition,
+    final_step: FinalStepPolicy,
+) -> (Vec<usize>, (Vec<f32>, Vec<f32>, Vec<f32>)) {
+    let x = [0., 1., 2., 3.];
+    let y = [1., 2., 3., 4.];
+
+    let model = Sequential::new(vec![Layer::dense((1, 1))]);
+    let nparams = model.size();
+
+    let size = NonZeroUsize::new(1).unwrap();
+    let dataset = Dataset::loaded(DataSrc::inmem(x.into(), y.into()), size, size);
+    let optimizer = GradientDescent::new(FloatPositive::new(0.01).unwrap());
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let mut trainer = BackpropTrainer::new(
+        model,
+        vec![optimizer],
+        dataset,
+        Mse::new(),
+        offline_epochs,
+        stop_condition,
+        size,
+        rng.clone(),
+    )
+    .with_final_step(final_step);
+
+    let ordering = [0];
+    let mut params_grads = gen_params_grads(&[nparams], &mut rng);
+    let servers: Vec<_> = params_grads
+        .iter_mut()
+        .map(|(params, grad, residual)| ParamsMetadata::new(params, grad, residual))
+        .collect();
+
+    let mut param_manager = ParamManager::for_parameter_server(servers, &ordering);
+    let mut calls = Vec::new();
+
+    loop {
+        let res = trainer.train(&mut param_manager).unwrap();
+        calls.push(res.losses.len());
+
+        if res.was_last {
+            break;
+        }
+    }
+
+    drop(param_manager);
+    (calls, params_grads.swap_remove(0))
+}
+
+#[test]
+fn test_max_epochs_stops_after_the_last_epoch() {
+    let max_epochs = NonZeroUsize::new(5).unwrap();
+    let calls = train_until_done(0, StopCondition::MaxEpochs(max_epochs));
+    assert_eq!(calls, [1; 5]);
+
+    // The offline epochs count towards the limit.
+    let calls = train_until_done(1, StopCondition::MaxEpochs(max_epochs));
+    assert_eq!(calls, [2, 2, 1]);
+}
+
+#[test]
+fn test_max_steps_stops_mid_epoch() {
+    // 4 samples with batches of 1 make 4 steps per epoch.
+    let max_steps = NonZeroUsize::new(6).unwrap();
+    let calls = train_until_done(0, StopCondition::MaxSteps(max_steps));
+    assert_eq!(calls, [1, 1]);
+
+    let max_steps = NonZeroUsize::new(8).unwrap();
+    let calls = train_until_done(3, StopCondition::MaxSteps(max_steps));
+    assert_eq!(calls, [2]);
+}
+
+#[test]
+fn test_a_discarded_partial_epoch_leaves_the_buffers_of_the_last_whole_one() {
+    let max_steps = |n| StopCondition::MaxSteps(NonZeroUsize::new(n).unwrap());
+    let (_, whole_epoch) = train_with_final_step(0, max_steps(4), FinalStepPolicy::ApplyPartial);
+
+    // The 6th step falls in the middle of the second epoch.
+    let (calls, applied) = train_with_final_step(0, max_steps(6), FinalStepPolicy::ApplyPartial);
+    assert_eq!(calls, [1, 1]);
+    assert_ne!(applied, whole_epoch);
+
+    let (calls, discarded) =
+        train_with_final_step(0, max_steps(6), FinalStepPolicy::DiscardPartial);
+    assert_eq!(calls, [1, 0]);
+    assert_eq!(discarded, whole_epoch);
+}
+
+#[test]
+fn test_max_wall_time_stops_once_elapsed() {
+    let calls = train_until_done(3, StopCondition::MaxWallTime(Duration::ZERO));
+    assert_eq!(calls, [1]);
+
+    let max_wall_time = Duration::from_millis(20);
+    let start = Instant::now();
+    let calls = train_until_done(0, StopCondition::MaxWallTime(max_wall_time));
+
+    assert!(start.elapsed() >= max_wall_time);
+    assert!(calls.iter().all(|&n| n == 1));
+}
+
+#[test]
+fn test_an_empty_partition_sends_zero_gradients_until_the_stop_condition() {
+    let model = Sequential::new(vec![Layer::dense((1, 1))]);
+    let nparams = model.size();
+
+    let size = NonZeroUsize::new(1).unwrap();
+    let dataset = Dataset::new(size, size);
+    let optimizer = GradientDescent::new(FloatPositive::new(0.01).unwrap());
+    let max_epochs = NonZeroUsize::new(3).unwrap();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let mut trainer = BackpropTrainer::new(
+        model,
+        vec![optimizer],
+        dataset,
+        Mse::new(),
+        0,
+        StopCondition::MaxEpochs(max_epochs),
+        size,
+        rng.clone(),
+    );
+
+    let ordering = [0];
+    let mut params_grads = gen_params_grads(&[nparams], &mut rng);
+    let initial = params_grads[0].0.clone();
+
+    for call in 1..=max_epochs.get() {
+        let servers: Vec<_> = params_grads
+            .iter_mut()
+            .map(|(params, grad, residual)| ParamsMetadata::new(params, grad, residual))
+            .collect();
+
+        let mut param_manager = ParamManager::for_parameter_server(servers, &ordering);
+        let res = trainer.train(&mut param_manager).unwrap();
+
+        assert!(res.losses.is_empty());
+        assert_eq!(res.was_last, call == max_epochs.get());
+    }
+
+    let (params, grad, residual) = &params_grads[0];
+    assert_eq!(*params, initial);
+    assert!(grad.iter().chain(residual).all(|&g| g == 0.0));
+}
@@ -0,0 +1,154 @@
+use std::num::NonZeroUsize;
+
+use comms::{
+    floats::FloatPositive,
+    specs::machine_learning::{
+        ActFnSpec, DatasetSpec, FinalStepPolicy, LayerSpec, LossFnSpec, OptimizerSpec, SamplerSpec,
+        StopCondition, StrategySpec, TrainerSpec,
+    },
+};
+use ndarray::Array2;
+
+use crate::{
+    MlErr,
+    param_manager::{ParamManager, ParamsMetadata},
+    training::TrainerBuilder,
+};
+
+/// A trainer spec for a model made of the given dense layers.
+///
+/// # Args
+/// * `dims` - The input and output size of every layer.
+///
+/// # Returns
+/// A new `TrainerSpec`.
+fn spec(dims: &[(usize, usize)]) -> TrainerSpec {
+    let layers = dims
+        .iter()
+        .map(|&dim| LayerSpec::Dense {
+            dim,
+            act_fn: None,
+            tie: None,
+            frozen: false,
+        })
+        .collect();
+
+    TrainerSpec {
+        layers,
+        optimizer: OptimizerSpec::GradientDescent {
+            learning_rate: FloatPositive::new(0.1).unwrap(),
+        },
+        dataset: DatasetSpec {
+            x_size: NonZeroUsize::new(2).unwrap(),
+            y_size: NonZeroUsize::MIN,
+            sparse: false,
+        },
+        loss_fn: LossFnSpec::Mse,
+        offline_epochs: 0,
+        stop_condition: StopCondition::MaxEpochs(NonZeroUsize::MIN),
+        final_step: FinalStepPolicy::ApplyPartial,
+        batch_size: NonZeroUsize::MIN,
+        sampler: SamplerSpec::Shuffle,
+        seed: None,
+        augmentation_seed: None,
+        clip_groups: Vec::new(),
+        heads: Vec::new(),
+        checkpoint_every: None,
+        resume: None,
+        strategy: StrategySpec::Backprop,
+    }
+}
+
+#[test]
+fn test_a_model_matching_the_servers_builds() {
+    // (2 + 1) * 3 + (3 + 1) * 1 parameters, split across two servers.
+    let built = TrainerBuilder::new().build(spec(&[(2, 3), (3, 1)]), &[9, 4]);
+    assert!(built.is_ok());
+}
+
+#[test]
+fn test_a_wrong_layer_stack_is_rejected_with_both_sizes() {
+    // The second layer takes 4 inputs while the first only gives 3.
+    let Err(err) = TrainerBuilder::new().build(spec(&[(2, 3), (4, 1)]), &[9, 4]) else {
+        panic!("a model of 14 parameters was built over 13");
+    };
+
+    let MlErr::SizeMismatch { got, expected, .. } = err else {
+        panic!("expected a size mismatch, got: {err}");
+    };
+
+    assert_eq!((got, expected), (14, 13));
+}
+
+#[test]
+fn test_a_tie_to_a_layer_of_another_dimension_is_rejected() {
+    let tied = |dim| LayerSpec::Dense {
+        dim,
+        act_fn: None,
+        tie: Some(0),
+        frozen: false,
+    };
+
+    // The tied layer only holds it's biases.
+    let mut autoencoder = spec(&[(2, 3)]);
+    autoencoder.layers.push(tied((3, 2)));
+    assert!(TrainerBuilder::new().build(autoencoder, &[9, 2]).is_ok());
+
+    let mut mistied = spec(&[(2, 3)]);
+    mistied.layers.push(tied((3, 1)));
+    let Err(err) = TrainerBuilder::new().build(mistied, &[9, 1]) else {
+        panic!("a (3, 1) layer was tied to a (2, 3) one");
+    };
+
+    assert!(matches!(
+        err,
+        MlErr::InvalidTie {
+            layer: 1,
+            source: 0
+        }
+    ));
+}
+
+#[test]
+fn test_a_regressor_without_a_final_activation_ends_in_a_linear_map() {
+    let mut layers = spec(&[(2, 3), (3, 1)]).layers;
+    let LayerSpec::Dense { act_fn, .. } = &mut layers[0] else {
+        unreachable!();
+    };
+    *act_fn = Some(ActFnSpec::Sigmoid {
+        amp: 1.0,
+        clamp: None,
+    });
+
+    let mut model = TrainerBuilder::new().build_model(&layers).unwrap();
+    let x = Array2::from_shape_vec((2, 2), vec![0.5, -1.0, 2.0, 0.0]).unwrap();
+
+    let mut forward = |params: &mut Vec<f32>| {
+        let mut grad = vec![0.0; params.len()];
+        let mut residual = vec![0.0; params.len()];
+        let servers = vec![ParamsMetadata::new(params, &mut grad, &mut residual)];
+        let mut param_manager = ParamManager::for_parameter_server(servers, &[0; 2]);
+
+        model
+            .forward(&mut param_manager, x.view().into_dyn())
+            .unwrap()
+            .to_owned()
+    };
+
+    // The first layer's 9 parameters followed by the last one's 3 weights and bias.
+    let mut params: Vec<_> = (0..13).map(|i| i as f32 * 0.1 - 0.6).collect();
+    let y = forward(&mut params);
+
+    // Scaling the last layer's parameters scales the output as well, past the range of a sigmoid.
+    params[9..].iter_mut().for_each(|param| *param *= 10.0);
+    let scaled = forward(&mut params);
+
+    for (y, scaled) in y.iter().zip(scaled.iter()) {
+        assert!(
+            (y * 10.0 - scaled).abs() < 1e-4,
+            "{scaled} isn't 10 times {y}"
+        );
+    }
+
+    assert!(scaled.iter().any(|y| y.abs() > 1.0));
+}
@@ -0,0 +1,143 @@
+use comms::floats::FloatPositive;
+use ndarray::{Array2, ArrayView1, ArrayView2};
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    arch::{
+        Sequential,
+        layers::Layer,
+        loss::{LossFn, Mse},
+    },
+    optimization::GradientDescent,
+    param_manager::{ParamManager, ParamsMetadata},
+    test::gen_params_grads,
+};
+
+/// The encoder's parameters, `(3 + 1) * 2`, followed by the decoder's `3` biases.
+const SERVER_SIZES: [usize; 2] = [8, 3];
+const ORDERING: [usize; 2] = [0, 1];
+
+type ParamsGrads = Vec<(Vec<f32>, Vec<f32>, Vec<f32>)>;
+
+/// An autoencoder whose decoder uses the transpose of the encoder's weights.
+fn autoencoder() -> Sequential {
+    Sequential::new(vec![
+        Layer::dense((3, 2)),
+        Layer::tanh(1.0),
+        Layer::tied_dense((2, 3), 0),
+    ])
+}
+
+/// A batch the autoencoder learns to reconstruct.
+fn batch() -> Array2<f32> {
+    Array2::from_shape_fn((4, 3), |(i, j)| (i * 3 + j) as f32 * 0.1 - 0.5)
+}
+
+/// Lays the encoder and the decoder out in a server each.
+///
+/// # Args
+/// * `params_grads` - The parameters and buffers of every server.
+///
+/// # Returns
+/// A new `ParamManager` instance.
+fn manager(params_grads: &mut ParamsGrads) -> ParamManager<'_> {
+    let servers = params_grads
+        .iter_mut()
+        .map(|(params, grad, residual)| ParamsMetadata::new(params, grad, residual))
+        .collect();
+
+    ParamManager::for_parameter_server(servers, &ORDERING)
+}
+
+/// Computes the autoencoder's output by hand, decoding with the encoder's weights transposed.
+///
+/// # Args
+/// * `encoder` - The encoder's parameters.
+/// * `biases` - The decoder's biases.
+/// * `x` - The input data.
+///
+/// # Returns
+/// The reconstruction of the input.
+fn reconstruct(encoder: &[f32], biases: &[f32], x: ArrayView2<f32>) -> Array2<f32> {
+    let w = ArrayView2::from_shape((3, 2), &encoder[..6]).unwrap();
+    let b = ArrayView1::from(&encoder[6..]);
+    let h = (x.dot(&w) + b).mapv(f32::tanh);
+
+    h.dot(&w.t()) + ArrayView1::from(biases)
+}
+
+#[test]
+fn test_the_tied_layer_always_reads_the_transposed_weights() {
+    let mut model = autoencoder();
+    assert_eq!(model.size(), SERVER_SIZES.iter().sum::<usize>());
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut params_grads = gen_params_grads(&SERVER_SIZES, &mut rng);
+    let lr = FloatPositive::new(0.5).unwrap();
+    let mut optimizers = [GradientDescent::new(lr), GradientDescent::new(lr)];
+    let mut loss_fn = Mse::new();
+    let x = batch();
+
+    for _ in 0..5 {
+        let expected = reconstruct(&params_grads[0].0, &params_grads[1].0, x.view());
+        let mut param_manager = manager(&mut params_grads);
+
+        let y_pred = model
+            .forward(&mut param_manager, x.view().into_dyn())
+            .unwrap();
+
+        for (y, e) in y_pred.iter().zip(&expected) {
+            assert!((y - e).abs() < 1e-6, "{y} != {e}");
+        }
+
+        // Every step updates the encoder's weights, the next forward must decode with them.
+        let batches = [(x.view().into(), x.view())].into_iter();
+        model
+            .backprop(&mut param_manager, &mut optimizers, &mut loss_fn, batches)
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_the_shared_weights_receive_the_gradient_of_both_layers() {
+    let mut model = autoencoder();
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut params_grads = gen_params_grads(&SERVER_SIZES, &mut rng);
+    let mut loss_fn = Mse::new();
+    let x = batch();
+
+    let mut param_manager = manager(&mut params_grads);
+    let y_pred = model
+        .forward(&mut param_manager, x.view().into_dyn())
+        .unwrap();
+    let (_, mut d) = loss_fn.loss_prime(y_pred, x.view().into_dyn());
+    model.backward(&mut param_manager, d.view_mut()).unwrap();
+    drop(param_manager);
+
+    let eps = 1e-3;
+    let mut loss_at = |params_grads: &mut ParamsGrads| {
+        let mut param_manager = manager(params_grads);
+        model
+            .evaluate(&mut param_manager, &mut loss_fn, x.view().into(), x.view())
+            .unwrap()
+    };
+
+    for (server, size) in SERVER_SIZES.into_iter().enumerate() {
+        for i in 0..size {
+            let param = params_grads[server].0[i];
+
+            params_grads[server].0[i] = param + eps;
+            let above = loss_at(&mut params_grads);
+            params_grads[server].0[i] = param - eps;
+            let below = loss_at(&mut params_grads);
+            params_grads[server].0[i] = param;
+
+            let numeric = ((above - below) / (2.0 * eps as f64)) as f32;
+            let analytic = params_grads[server].1[i];
+            assert!(
+                (numeric - analytic).abs() < 1e-3,
+                "param {i} of server {server}: {numeric} != {analytic}"
+            );
+        }
+    }
+}
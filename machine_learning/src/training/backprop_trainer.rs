@@ -1,12 +1,16 @@
-use std::num::NonZeroUsize;
+use std::{mem, num::NonZeroUsize, time::Instant};
 
+use comms::specs::machine_learning::{
+    ClipGroupSpec, FinalStepPolicy, SamplerSpec, StopCondition, TrainerProgress,
+};
+use ndarray::ArrayView2;
 use rand::Rng;
 
 use super::{TrainResult, Trainer};
 use crate::{
     Result,
     arch::{Sequential, loss::LossFn},
-    datasets::{DataSrc, Dataset},
+    datasets::{BatchOrder, DataSrc, Dataset, OrderedBatches, Sampler, Samples},
     optimization::{GradientDescent, Optimizer},
     param_manager::ParamManager,
 };
@@ -26,10 +30,18 @@ where
     loss_fn: L,
 
     epoch: usize,
+    step: usize,
+    pending_shuffles: usize,
+    started: Option<Instant>,
     offline_epochs: usize,
-    max_epochs: NonZeroUsize,
+    stop_condition: StopCondition,
+    final_step: FinalStepPolicy,
     batch_size: NonZeroUsize,
+    sampler: Sampler,
+    batch_order: Option<Box<dyn BatchOrder>>,
     rng: R,
+    clip_groups: Vec<ClipGroupSpec>,
+    layer_sizes: Vec<usize>,
 
     losses: Vec<f64>,
 }
@@ -48,7 +60,7 @@ where
     /// * `dataset` - An empty dataset.
     /// * `loss_fn` - The loss function used to measure the difference between a model's output and the expected one.
     /// * `offline_epochs` - The amount of extra epochs to run per `train` call.
-    /// * `max_epochs` - The maximum amount of epochs to train.
+    /// * `stop_condition` - When to stop training.
     /// * `batch_size` - The size of the mini batch.
     /// * `rng` - A random number generator.
     ///
@@ -60,7 +72,7 @@ where
         dataset: Dataset,
         loss_fn: L,
         offline_epochs: usize,
-        max_epochs: NonZeroUsize,
+        stop_condition: StopCondition,
         batch_size: NonZeroUsize,
         rng: R,
     ) -> Self {
@@ -69,6 +81,8 @@ where
             .map(|optimizer| GradientDescent::new(optimizer.learning_rate()))
             .collect();
 
+        let layer_sizes = model.layer_sizes();
+
         Self {
             model,
             stateless_optimizers,
@@ -76,13 +90,101 @@ where
             dataset,
             loss_fn,
             epoch: 0,
+            step: 0,
+            pending_shuffles: 0,
+            started: None,
             offline_epochs,
-            max_epochs,
+            stop_condition,
+            final_step: FinalStepPolicy::default(),
             batch_size,
+            sampler: Sampler::new(SamplerSpec::default()),
+            batch_order: None,
             rng,
+            clip_groups: Vec::new(),
+            layer_sizes,
             losses: Vec::with_capacity(1 + offline_epochs),
         }
     }
+
+    /// Clips the gradient of some ranges of the parameters before it's sent.
+    ///
+    /// # Args
+    /// * `clip_groups` - The ranges of parameters along with their maximum norms.
+    ///
+    /// # Returns
+    /// The modified `BackpropTrainer`.
+    pub fn with_clip_groups(mut self, clip_groups: Vec<ClipGroupSpec>) -> Self {
+        self.clip_groups = clip_groups;
+        self
+    }
+
+    /// Sets what to do with the epoch a `MaxSteps` stop condition falls in the middle of.
+    ///
+    /// # Args
+    /// * `final_step` - The final step policy.
+    ///
+    /// # Returns
+    /// The modified `BackpropTrainer`.
+    pub fn with_final_step(mut self, final_step: FinalStepPolicy) -> Self {
+        self.final_step = final_step;
+        self
+    }
+
+    /// Sets how the rows of the dataset are ordered on every epoch.
+    ///
+    /// # Args
+    /// * `sampler` - The specification of the sampler.
+    ///
+    /// # Returns
+    /// The modified `BackpropTrainer`.
+    pub fn with_sampler(mut self, sampler: SamplerSpec) -> Self {
+        self.sampler = Sampler::new(sampler);
+        self
+    }
+
+    /// Resumes the training where a previous run left off.
+    ///
+    /// The sampler's draws of the finished epochs are replayed over the dataset before the
+    /// first training call, so as long as the trainer was built with the same seed and dataset
+    /// the batches come in the same order as in an uninterrupted run.
+    ///
+    /// # Args
+    /// * `progress` - How far the previous run went.
+    ///
+    /// # Returns
+    /// The modified `BackpropTrainer`.
+    pub fn resumed_from(mut self, progress: TrainerProgress) -> Self {
+        self.pending_shuffles = progress.epoch.saturating_sub(self.epoch);
+        self.epoch = progress.epoch;
+        self.step = progress.step;
+        self
+    }
+
+    /// Whether the stop condition was met.
+    ///
+    /// # Args
+    /// * `started` - When the training started.
+    ///
+    /// # Returns
+    /// `true` if the training should stop.
+    fn is_done(&self, started: Instant) -> bool {
+        match self.stop_condition {
+            StopCondition::MaxEpochs(max_epochs) => self.epoch >= max_epochs.get(),
+            StopCondition::MaxSteps(max_steps) => self.step >= max_steps.get(),
+            StopCondition::MaxWallTime(max_wall_time) => started.elapsed() >= max_wall_time,
+        }
+    }
+
+    /// The amount of steps left to take before meeting the stop condition.
+    ///
+    /// # Returns
+    /// The remaining steps, unbounded unless stopping after a number of steps.
+    fn remaining_steps(&self) -> usize {
+        match self.stop_condition {
+            StopCondition::MaxSteps(max_steps) => max_steps.get().saturating_sub(self.step),
+            _ => usize::MAX,
+        }
+    }
 }
 
 impl<O, L, R> Trainer for BackpropTrainer<O, L, R>
@@ -97,16 +199,91 @@ where
     /// * `param_manager` - The manager of parameters for this training.
     ///
     /// # Returns
-    /// A tuple with the param grads and the epoch loss.
+    /// A tuple with the param grads and the epoch loss, no losses if the dataset is empty.
     fn train<'mw>(&mut self, param_manager: &mut ParamManager<'mw>) -> Result<TrainResult<'_>> {
-        let remaining = self.max_epochs.get() - self.epoch;
-        let epochs = remaining.min(self.offline_epochs + 1);
-
+        let started = *self.started.get_or_insert_with(Instant::now);
         self.losses.clear();
 
-        for _ in 0..epochs {
-            self.dataset.shuffle(&mut self.rng);
-            let batches = self.dataset.batches(self.batch_size);
+        // An empty partition has nothing to train on, the call still counts as an epoch
+        // so the worker keeps sending it's zero gradient in step with the others.
+        if self.dataset.rows() == 0 {
+            self.step += 1;
+            self.epoch += 1;
+
+            let res = TrainResult {
+                losses: &self.losses,
+                was_last: self.is_done(started),
+                samples: 0,
+            };
+
+            return Ok(res);
+        }
+
+        // The dataset might've been loaded after resuming, so the draws are replayed here.
+        for _ in 0..mem::take(&mut self.pending_shuffles) {
+            self.sampler.draw(&mut self.dataset, &mut self.rng)?;
+        }
+
+        let mut samples = 0;
+
+        // Every call runs at least an epoch, unless it's a discarded partial one, the stop
+        // condition is only checked between the extra offline ones.
+        for i in 0..=self.offline_epochs {
+            if i > 0 && self.is_done(started) {
+                break;
+            }
+
+            let remaining_steps = self.remaining_steps();
+
+            // An order set from outside replaces the sampler, it's batches already hold
+            // only this trainer's share of the rows.
+            let ordered = self
+                .batch_order
+                .as_ref()
+                .map(|order| OrderedBatches::new(&self.dataset, &order.batches(self.epoch)));
+
+            let nbatches = match &ordered {
+                Some(ordered) => ordered.len(),
+                None => self.dataset.rows().div_ceil(self.batch_size.get()),
+            };
+
+            // None of the epoch's rows were dealt to this trainer, like with an empty
+            // dataset the epoch still counts so it keeps in step with the others.
+            if nbatches == 0 {
+                self.step += 1;
+                self.epoch += 1;
+                continue;
+            }
+
+            // The partial epoch is skipped whole, it's steps are still taken up so the
+            // stop condition is met.
+            if remaining_steps < nbatches && self.final_step == FinalStepPolicy::DiscardPartial {
+                self.step += remaining_steps;
+                break;
+            }
+
+            let mut steps = 0;
+
+            let sampled = match ordered {
+                Some(_) => None,
+                None => self
+                    .sampler
+                    .draw(&mut self.dataset, &mut self.rng)?
+                    .map(|rows| self.dataset.select(&rows)),
+            };
+
+            let batches: Box<dyn Iterator<Item = _>> = match &ordered {
+                Some(ordered) => Box::new(ordered.iter()),
+                None => {
+                    let dataset = sampled.as_ref().unwrap_or(&self.dataset);
+                    Box::new(dataset.batches(self.batch_size))
+                }
+            };
+
+            let batches = batches.take(remaining_steps).inspect(|(x, _)| {
+                steps += 1;
+                samples += x.nrows();
+            });
 
             let loss = self.model.backprop(
                 param_manager,
@@ -116,12 +293,19 @@ where
             )?;
 
             self.losses.push(loss);
+            self.step += steps;
+            self.epoch += 1;
+        }
+
+        for group in &self.clip_groups {
+            let range = group.start..group.end;
+            param_manager.clip_residual(&self.layer_sizes, range, *group.max_norm);
         }
 
-        self.epoch += epochs;
         let res = TrainResult {
             losses: &self.losses,
-            was_last: self.epoch == self.max_epochs.get(),
+            was_last: self.is_done(started),
+            samples,
         };
 
         Ok(res)
@@ -131,6 +315,44 @@ where
         param_manager.optimize(&mut self.optimizers)
     }
 
+    fn evaluate<'mw>(
+        &mut self,
+        param_manager: &mut ParamManager<'mw>,
+        x: Samples<'_>,
+        y: ArrayView2<f32>,
+    ) -> Result<f64> {
+        self.model.evaluate(param_manager, &mut self.loss_fn, x, y)
+    }
+
+    fn predict<'d>(
+        &mut self,
+        param_manager: &mut ParamManager<'_>,
+        batches: &mut dyn Iterator<Item = (Samples<'d>, ArrayView2<'d, f32>)>,
+        f: &mut dyn FnMut(&[f32], &[f32]) -> Result<()>,
+    ) -> Result<bool> {
+        self.model.predict_batches(param_manager, batches, f)
+    }
+
+    fn predict_dataset(
+        &mut self,
+        param_manager: &mut ParamManager<'_>,
+        f: &mut dyn FnMut(&[f32], &[f32]) -> Result<()>,
+    ) -> Result<bool> {
+        let batches = self.dataset.batches(self.batch_size);
+        self.model.predict_batches(param_manager, batches, f)
+    }
+
+    fn progress(&self) -> TrainerProgress {
+        TrainerProgress {
+            epoch: self.epoch,
+            step: self.step,
+        }
+    }
+
+    fn set_batch_order(&mut self, batch_order: Box<dyn BatchOrder>) {
+        self.batch_order = Some(batch_order);
+    }
+
     fn load_dataset(&mut self, src: DataSrc) {
         self.dataset.load(src);
     }
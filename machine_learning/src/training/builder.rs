@@ -1,17 +1,19 @@
 use comms::specs::machine_learning::{
-    ActFnSpec, DatasetSpec, LayerSpec, LossFnSpec, OptimizerSpec, TrainerSpec,
+    ActFnSpec, DatasetSpec, HeadSpec, LayerSpec, LossFnSpec, OptimizerSpec, StrategySpec,
+    TrainerSpec,
 };
-use rand::{SeedableRng, rngs::StdRng};
 
-use super::{BackpropTrainer, Trainer};
+use super::{BackpropTrainer, ClosedFormTrainer, Trainer};
 use crate::{
+    MlErr, Result,
     arch::{
-        Sequential,
-        layers::{Inner, Layer},
+        Head, HeadLoss, Sequential,
+        layers::{Inner, Layer, build_custom_layer},
         loss::{CrossEntropy, LossFn, Mse},
     },
     datasets::Dataset,
     optimization::{Adam, GradientDescent, GradientDescentWithMomentum, Optimizer},
+    rng::{self, RngPurpose},
 };
 
 /// Builds `Trainer`s given a specification.
@@ -31,11 +33,69 @@ impl TrainerBuilder {
     /// * `server_sizes` - The mount of parameters per server.
     ///
     /// # Returns
-    /// A new `Trainer`.
-    pub fn build(&self, spec: TrainerSpec, server_sizes: &[usize]) -> Box<dyn Trainer> {
+    /// A new `Trainer`, a size mismatch if the model's layers don't hold as many
+    /// parameters as the servers together or an `MlErr::InvalidStrategy` if the
+    /// strategy can't fit the model.
+    pub fn build(&self, spec: TrainerSpec, server_sizes: &[usize]) -> Result<Box<dyn Trainer>> {
+        self.check_strategy(&spec)?;
         self.resolve_optimizers(spec, server_sizes)
     }
 
+    /// Builds a bare `Sequential` model out of it's layers, to run it without training it.
+    ///
+    /// # Args
+    /// * `layers` - The specification of every layer of the model.
+    ///
+    /// # Returns
+    /// A new `Sequential` or an `MlErr::InvalidTie` if a layer is tied to a missing one.
+    pub fn build_model(&self, layers: &[LayerSpec]) -> Result<Sequential> {
+        let mut resolved = vec![];
+        let mut mains = vec![];
+
+        let mut last = None;
+        for spec in layers {
+            mains.push(self.resolve_layer_into(spec, last, &mains, &mut resolved)?);
+            last = Some(spec);
+        }
+
+        let model = Sequential::new(resolved);
+        model.check_ties()?;
+        Ok(model)
+    }
+
+    /// Checks that the trainer's strategy can fit it's model.
+    ///
+    /// # Args
+    /// * `spec` - The specification of the trainer.
+    ///
+    /// # Returns
+    /// An `MlErr::InvalidStrategy` if a closed form linear regression is asked for a model
+    /// that isn't a single untied and unfrozen dense layer without an activation function
+    /// trained with the mean squared error.
+    fn check_strategy(&self, spec: &TrainerSpec) -> Result<()> {
+        if spec.strategy != StrategySpec::ClosedFormLinReg {
+            return Ok(());
+        }
+
+        let linear = matches!(
+            spec.layers.as_slice(),
+            [LayerSpec::Dense {
+                act_fn: None,
+                tie: None,
+                frozen: false,
+                ..
+            }]
+        );
+
+        if !linear || !spec.heads.is_empty() || !matches!(spec.loss_fn, LossFnSpec::Mse) {
+            return Err(MlErr::InvalidStrategy {
+                reason: "a linear regression is a single dense layer without an activation function trained with the mse loss",
+            });
+        }
+
+        Ok(())
+    }
+
     /// Resolves the `Optimizer`s for this trainer.
     ///
     /// # Args
@@ -43,8 +103,14 @@ impl TrainerBuilder {
     /// * `server_sizes` - The amount of parameters per server.
     ///
     /// # Returns
-    /// A new `Trainer`.
-    fn resolve_optimizers(&self, spec: TrainerSpec, server_sizes: &[usize]) -> Box<dyn Trainer> {
+    /// A new `Trainer` or a size mismatch if the model doesn't fit the servers.
+    fn resolve_optimizers(
+        &self,
+        spec: TrainerSpec,
+        server_sizes: &[usize],
+    ) -> Result<Box<dyn Trainer>> {
+        let nparams = server_sizes.iter().sum();
+
         match spec.optimizer {
             OptimizerSpec::GradientDescent { learning_rate } => {
                 let optimizers: Vec<_> = server_sizes
@@ -52,7 +118,7 @@ impl TrainerBuilder {
                     .map(|_| GradientDescent::new(learning_rate))
                     .collect();
 
-                self.resolve_layers(spec, optimizers)
+                self.resolve_layers(spec, optimizers, nparams)
             }
             OptimizerSpec::Adam {
                 learning_rate,
@@ -65,7 +131,7 @@ impl TrainerBuilder {
                     .map(|&len| Adam::new(len, learning_rate, beta1, beta2, epsilon))
                     .collect();
 
-                self.resolve_layers(spec, optimizers)
+                self.resolve_layers(spec, optimizers, nparams)
             }
             OptimizerSpec::GradientDescentWithMomentum {
                 learning_rate,
@@ -76,54 +142,135 @@ impl TrainerBuilder {
                     .map(|&len| GradientDescentWithMomentum::new(len, learning_rate, momentum))
                     .collect();
 
-                self.resolve_layers(spec, optimizers)
+                self.resolve_layers(spec, optimizers, nparams)
             }
         }
     }
 
-    /// Resolves the the `Layer`s for a `Sequential` model.
+    /// Resolves the the `Layer`s and `Head`s for a `Sequential` model.
     ///
     /// # Args
     /// * `spec` - The specification of the trainer.
     /// * `optimizers` - A list of resolved optimizers.
+    /// * `nparams` - The amount of parameters the model is trained over.
     ///
     /// # Returns
-    /// A new `Trainer`.
-    fn resolve_layers<O>(&self, spec: TrainerSpec, optimizers: Vec<O>) -> Box<dyn Trainer>
+    /// A new `Trainer` or a size mismatch if the model's size isn't `nparams`.
+    fn resolve_layers<O>(
+        &self,
+        spec: TrainerSpec,
+        optimizers: Vec<O>,
+        nparams: usize,
+    ) -> Result<Box<dyn Trainer>>
     where
         O: Optimizer + Send + 'static,
     {
         let mut layers = vec![];
 
+        let mut starts = vec![];
+        let mut mains = vec![];
+
         let mut last = None;
         for spec in &spec.layers {
-            self.resolve_layer_into(*spec, last, &mut layers);
-            last = Some(*spec);
+            starts.push(layers.len());
+            mains.push(self.resolve_layer_into(spec, last, &mains, &mut layers)?);
+            last = Some(spec);
+        }
+
+        let heads = spec
+            .heads
+            .iter()
+            .map(|head| self.resolve_head(head, last, &mains))
+            .collect::<Result<_>>()?;
+
+        // A layer of the spec can resolve into many, the checkpoints land on the first of them.
+        let checkpoints = match spec.checkpoint_every {
+            Some(every) => starts.into_iter().step_by(every.get()).collect(),
+            None => vec![],
+        };
+
+        let model = Sequential::new(layers)
+            .with_heads(heads)
+            .with_checkpoints(checkpoints);
+
+        model.check_ties()?;
+
+        // A model of another size would read and write past the layers' parameters.
+        if model.size() != nparams {
+            return Err(MlErr::size_mismatch(
+                "model parameters",
+                model.size(),
+                nparams,
+            ));
         }
 
-        self.resolve_loss_fn(spec, optimizers, layers)
+        Ok(self.resolve_loss_fn(spec, optimizers, model))
+    }
+
+    /// Resolves a `Head` branching off a `Sequential` model.
+    ///
+    /// # Args
+    /// * `spec` - The specification of the head.
+    /// * `last` - The specification of the model's last shared layer.
+    /// * `sources` - The index of every shared layer of the spec once resolved.
+    ///
+    /// # Returns
+    /// A new `Head` or an `MlErr::InvalidTie` if a layer is tied to a missing one.
+    fn resolve_head<'a>(
+        &self,
+        spec: &'a HeadSpec,
+        mut last: Option<&'a LayerSpec>,
+        sources: &[usize],
+    ) -> Result<Head> {
+        let mut layers = vec![];
+
+        for layer in &spec.layers {
+            self.resolve_layer_into(layer, last, sources, &mut layers)?;
+            last = Some(layer);
+        }
+
+        let loss_fn = match spec.loss_fn {
+            LossFnSpec::Mse => HeadLoss::Mse(Mse::new()),
+            LossFnSpec::CrossEntropy => HeadLoss::CrossEntropy(CrossEntropy::new()),
+        };
+
+        let weight = spec.weight.map_or(1.0, |weight| *weight);
+        Ok(Head::new(layers, loss_fn, spec.y_size.get(), weight))
     }
 
     /// Resolves a `Layer` for a `Sequential` model.
     ///
     /// # Args
     /// * `spec` - The specification of a certain layer.
+    /// * `last` - The specification of the previous layer.
+    /// * `sources` - The index of every shared layer of the spec resolved so far.
+    /// * `layers` - The resolved layers, the new ones are pushed onto them.
     ///
     /// # Returns
-    /// A new `Layer`.
+    /// The index of the layer's dense, convolution or pooling layer once resolved or an
+    /// `MlErr::InvalidTie` if it's tied to a layer that wasn't resolved before it.
     fn resolve_layer_into(
         &self,
-        spec: LayerSpec,
-        last: Option<LayerSpec>,
+        spec: &LayerSpec,
+        last: Option<&LayerSpec>,
+        sources: &[usize],
         layers: &mut Vec<Layer>,
-    ) {
+    ) -> Result<usize> {
         use Inner::*;
 
-        let act_fn = match spec {
-            LayerSpec::Dense { dim, act_fn } => {
+        let frozen = matches!(
+            *spec,
+            LayerSpec::Dense { frozen: true, .. } | LayerSpec::Conv { frozen: true, .. }
+        );
+
+        let main;
+        let act_fn = match *spec {
+            LayerSpec::Dense {
+                dim, act_fn, tie, ..
+            } => {
                 if matches!(layers.last(), Some(Layer(Conv2d(_) | MaxPooling(_)))) {
                     let last = last.unwrap();
-                    let (out_h, out_w, out_c) = match last {
+                    let (out_h, out_w, out_c) = match *last {
                         LayerSpec::Conv {
                             input_dim,
                             kernel_dim,
@@ -151,7 +298,20 @@ impl TrainerBuilder {
                     layers.push(Layer::four_d_to2d(out_c, out_h, out_w))
                 }
 
-                layers.push(Layer::dense(dim));
+                main = layers.len();
+
+                match tie {
+                    Some(tie) => {
+                        let source = sources.get(tie).ok_or(MlErr::InvalidTie {
+                            layer: sources.len(),
+                            source: tie,
+                        })?;
+
+                        layers.push(Layer::tied_dense(dim, *source));
+                    }
+                    None => layers.push(Layer::dense(dim)),
+                }
+
                 act_fn
             }
             LayerSpec::Conv {
@@ -160,11 +320,13 @@ impl TrainerBuilder {
                 stride,
                 padding,
                 act_fn,
+                ..
             } => {
                 if !matches!(layers.last(), Some(Layer(Conv2d(_) | MaxPooling(_)))) {
                     layers.push(Layer::two_d_to4d(input_dim.0, input_dim.1, input_dim.2))
                 }
 
+                main = layers.len();
                 layers.push(Layer::conv2d(
                     kernel_dim.0,
                     kernel_dim.1,
@@ -192,6 +354,7 @@ impl TrainerBuilder {
                     layers.push(Layer::two_d_to4d(input_dim.0, input_dim.1, input_dim.2))
                 }
 
+                main = layers.len();
                 layers.push(Layer::max_pooling(filter_size, stride, padding));
 
                 if act_fn.is_some() {
@@ -202,12 +365,24 @@ impl TrainerBuilder {
 
                 act_fn
             }
+            LayerSpec::Custom {
+                ref name,
+                ref params,
+            } => {
+                main = layers.len();
+                layers.push(Layer::custom(build_custom_layer(name, params)?));
+                None
+            }
         };
 
+        if frozen {
+            layers[main].freeze();
+        }
+
         if let Some(spec) = act_fn {
             if matches!(layers.last(), Some(Layer(Conv2d(_) | MaxPooling(_)))) {
                 let last = last.unwrap();
-                let (out_h, out_w, out_c) = match last {
+                let (out_h, out_w, out_c) = match *last {
                     LayerSpec::Conv {
                         input_dim,
                         kernel_dim,
@@ -228,6 +403,8 @@ impl TrainerBuilder {
             }
             layers.push(self.resolve_act_fn(spec));
         };
+
+        Ok(main)
     }
 
     fn spatial_size(
@@ -257,9 +434,17 @@ impl TrainerBuilder {
     /// A new `Layer`.
     fn resolve_act_fn(&self, spec: ActFnSpec) -> Layer {
         match spec {
-            ActFnSpec::Sigmoid { amp } => Layer::sigmoid(amp),
+            ActFnSpec::Sigmoid { amp, clamp: None } => Layer::sigmoid(amp),
+            ActFnSpec::Sigmoid {
+                amp,
+                clamp: Some(clamp),
+            } => Layer::clamped_sigmoid(amp, *clamp),
             ActFnSpec::Softmax => Layer::softmax(),
-            ActFnSpec::Tanh { amp } => Layer::tanh(amp),
+            ActFnSpec::Tanh { amp, clamp: None } => Layer::tanh(amp),
+            ActFnSpec::Tanh {
+                amp,
+                clamp: Some(clamp),
+            } => Layer::clamped_tanh(amp, *clamp),
             ActFnSpec::ReLU { slope } => Layer::relu(slope),
         }
     }
@@ -269,7 +454,7 @@ impl TrainerBuilder {
     /// # Args
     /// * `spec` - The specification for this trainer.
     /// * `optimizers` - A list of resolved optimizers.
-    /// * `layers` - A list of resolved layers.
+    /// * `model` - The resolved model.
     ///
     /// # Returns
     /// A new `Trainer`.
@@ -277,7 +462,7 @@ impl TrainerBuilder {
         &self,
         spec: TrainerSpec,
         optimizers: Vec<O>,
-        layers: Vec<Layer>,
+        model: Sequential,
     ) -> Box<dyn Trainer>
     where
         O: Optimizer + Send + 'static,
@@ -285,11 +470,11 @@ impl TrainerBuilder {
         match spec.loss_fn {
             LossFnSpec::Mse => {
                 let loss_fn = Mse::new();
-                self.terminate_build(spec, optimizers, layers, loss_fn)
+                self.terminate_build(spec, optimizers, model, loss_fn)
             }
             LossFnSpec::CrossEntropy => {
                 let loss_fn = CrossEntropy::new();
-                self.terminate_build(spec, optimizers, layers, loss_fn)
+                self.terminate_build(spec, optimizers, model, loss_fn)
             }
         }
     }
@@ -299,7 +484,7 @@ impl TrainerBuilder {
     /// # Args
     /// * `spec` - The specification for this trainer.
     /// * `optimizers` - A list of optimizers, one per server.
-    /// * `layers` - A list of resolved layers.
+    /// * `model` - The resolved model.
     /// * `loss_fn` - A resolved loss function.
     ///
     /// # Returns
@@ -308,41 +493,41 @@ impl TrainerBuilder {
         &self,
         spec: TrainerSpec,
         optimizers: Vec<O>,
-        layers: Vec<Layer>,
+        model: Sequential,
         loss_fn: L,
     ) -> Box<dyn Trainer>
     where
         O: Optimizer + Send + 'static,
         L: LossFn + Send + 'static,
     {
-        let model = Sequential::new(layers);
-        let DatasetSpec { x_size, y_size } = spec.dataset;
+        let DatasetSpec { x_size, y_size, .. } = spec.dataset;
         let dataset = Dataset::new(x_size, y_size);
-        let trainer = BackpropTrainer::new(
+
+        if spec.strategy == StrategySpec::ClosedFormLinReg {
+            let learning_rate = spec.optimizer.learning_rate();
+            let trainer =
+                ClosedFormTrainer::new(model, optimizers, dataset, loss_fn, learning_rate);
+            return Box::new(trainer);
+        }
+
+        let mut trainer = BackpropTrainer::new(
             model,
             optimizers,
             dataset,
             loss_fn,
             spec.offline_epochs,
-            spec.max_epochs,
+            spec.stop_condition,
             spec.batch_size,
-            self.generate_rng(spec.seed),
-        );
+            rng::rng_for(spec.seed, RngPurpose::Shuffle),
+        )
+        .with_clip_groups(spec.clip_groups)
+        .with_final_step(spec.final_step)
+        .with_sampler(spec.sampler);
 
-        Box::new(trainer)
-    }
-
-    /// Generates a random number generator given (or not) a seed.
-    ///
-    /// # Args
-    /// * `seed` - An optional seed for the rng.
-    ///
-    /// # Returns
-    /// A new rng.
-    fn generate_rng(&self, seed: Option<u64>) -> StdRng {
-        match seed {
-            Some(seed) => StdRng::seed_from_u64(seed),
-            None => StdRng::from_os_rng(),
+        if let Some(progress) = spec.resume {
+            trainer = trainer.resumed_from(progress);
         }
+
+        Box::new(trainer)
     }
 }
@@ -0,0 +1,268 @@
+use std::num::NonZeroUsize;
+
+use comms::{floats::FloatPositive, specs::machine_learning::TrainerProgress};
+use ndarray::{Array2, ArrayView2, s};
+
+use super::{TrainResult, Trainer};
+use crate::{
+    MlErr, Result,
+    arch::{Sequential, loss::LossFn},
+    datasets::{BatchOrder, DataSrc, Dataset, Samples},
+    optimization::{GradientDescent, Optimizer},
+    param_manager::ParamManager,
+};
+
+/// A `Trainer` that fits a linear regression exactly instead of descending it's gradient,
+/// a ground truth to compare the iterative distributed training against.
+///
+/// The model must be a single dense layer without an activation function. The first and
+/// only training call solves the least squares problem over the whole dataset and sets
+/// the gradient to `(params - solution) / learning_rate`, so a single gradient descent
+/// step with the configured learning rate lands right on the solution. With a single
+/// worker and a gradient descent optimizer on the servers the model ends up at the exact
+/// optimum, other optimizers or many workers only move towards their shards' optimum.
+pub struct ClosedFormTrainer<O, L>
+where
+    O: Optimizer,
+    L: LossFn,
+{
+    model: Sequential,
+    optimizers: Vec<O>,
+    stateless_optimizers: Vec<GradientDescent>,
+    dataset: Dataset,
+    loss_fn: L,
+    learning_rate: FloatPositive,
+
+    epoch: usize,
+    step: usize,
+    losses: Vec<f64>,
+}
+
+impl<O, L> ClosedFormTrainer<O, L>
+where
+    O: Optimizer,
+    L: LossFn,
+{
+    /// Returns a new `ClosedFormTrainer` model trainer.
+    ///
+    /// # Args
+    /// * `model` - A model made of a single dense layer without an activation function.
+    /// * `optimizers` - A list of optimizers, one per server.
+    /// * `dataset` - An empty dataset.
+    /// * `loss_fn` - The loss function the solution's loss is reported with.
+    /// * `learning_rate` - The learning rate the gradient is scaled to.
+    ///
+    /// # Returns
+    /// A new `ClosedFormTrainer` instance.
+    pub fn new(
+        model: Sequential,
+        optimizers: Vec<O>,
+        dataset: Dataset,
+        loss_fn: L,
+        learning_rate: FloatPositive,
+    ) -> Self {
+        let stateless_optimizers = optimizers
+            .iter()
+            .map(|_| GradientDescent::new(learning_rate))
+            .collect();
+
+        Self {
+            model,
+            optimizers,
+            stateless_optimizers,
+            dataset,
+            loss_fn,
+            learning_rate,
+            epoch: 0,
+            step: 0,
+            losses: Vec::with_capacity(1),
+        }
+    }
+}
+
+impl<O, L> Trainer for ClosedFormTrainer<O, L>
+where
+    O: Optimizer + Send,
+    L: LossFn + Send,
+{
+    /// Solves the linear regression over the whole dataset.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of parameters for this training.
+    ///
+    /// # Returns
+    /// The loss of the solution, none if the dataset is empty, always the last training call.
+    fn train<'mw>(&mut self, param_manager: &mut ParamManager<'mw>) -> Result<TrainResult<'_>> {
+        self.losses.clear();
+        self.epoch += 1;
+        self.step += 1;
+
+        // An empty partition has nothing to fit, it's gradient is left at zero.
+        let Some(rows) = NonZeroUsize::new(self.dataset.rows()) else {
+            let res = TrainResult {
+                losses: &self.losses,
+                was_last: true,
+                samples: 0,
+            };
+
+            return Ok(res);
+        };
+
+        // SAFETY: A dataset with at least a row yields at least a batch.
+        let (x, y) = self.dataset.batches(rows).next().unwrap();
+        let x = x.to_dense();
+        let solution = least_squares(x.view(), y);
+
+        let size = self.model.size();
+        let mut back = param_manager.back();
+        let Some((params, grad)) = back.next(size) else {
+            return Err(MlErr::size_mismatch("model parameters", 0, size));
+        };
+
+        if params.len() != size {
+            return Err(MlErr::size_mismatch("model parameters", params.len(), size));
+        }
+
+        let lr = *self.learning_rate;
+        for ((g, p), p_star) in grad.iter_mut().zip(params.iter()).zip(solution) {
+            *g = (p - p_star) / lr;
+        }
+
+        param_manager.optimize(&mut self.stateless_optimizers)?;
+        param_manager.acc_residual();
+        param_manager.zero_grad();
+
+        let loss = self
+            .model
+            .evaluate(param_manager, &mut self.loss_fn, x.view().into(), y)?;
+
+        self.losses.push(loss);
+
+        let res = TrainResult {
+            losses: &self.losses,
+            was_last: true,
+            samples: rows.get(),
+        };
+
+        Ok(res)
+    }
+
+    fn optimize<'mw>(&mut self, param_manager: &mut ParamManager<'mw>) -> Result<()> {
+        param_manager.optimize(&mut self.optimizers)
+    }
+
+    fn evaluate<'mw>(
+        &mut self,
+        param_manager: &mut ParamManager<'mw>,
+        x: Samples<'_>,
+        y: ArrayView2<f32>,
+    ) -> Result<f64> {
+        self.model.evaluate(param_manager, &mut self.loss_fn, x, y)
+    }
+
+    fn predict<'d>(
+        &mut self,
+        param_manager: &mut ParamManager<'_>,
+        batches: &mut dyn Iterator<Item = (Samples<'d>, ArrayView2<'d, f32>)>,
+        f: &mut dyn FnMut(&[f32], &[f32]) -> Result<()>,
+    ) -> Result<bool> {
+        self.model.predict_batches(param_manager, batches, f)
+    }
+
+    fn predict_dataset(
+        &mut self,
+        param_manager: &mut ParamManager<'_>,
+        f: &mut dyn FnMut(&[f32], &[f32]) -> Result<()>,
+    ) -> Result<bool> {
+        // The whole dataset as a single batch, as it's trained on.
+        let batches = NonZeroUsize::new(self.dataset.rows())
+            .into_iter()
+            .flat_map(|rows| self.dataset.batches(rows));
+
+        self.model.predict_batches(param_manager, batches, f)
+    }
+
+    fn progress(&self) -> TrainerProgress {
+        TrainerProgress {
+            epoch: self.epoch,
+            step: self.step,
+        }
+    }
+
+    /// The solution doesn't depend on the order of the rows, so the order is ignored.
+    fn set_batch_order(&mut self, _batch_order: Box<dyn BatchOrder>) {}
+
+    fn load_dataset(&mut self, src: DataSrc) {
+        self.dataset.load(src);
+    }
+
+    fn into_dataset(self: Box<Self>) -> Dataset {
+        self.dataset
+    }
+}
+
+/// Solves the least squares problem `min ‖[x 1] θ - y‖` for the weights and biases of a
+/// dense layer.
+///
+/// Forming the normal equations `(AᵀA) θ = Aᵀy` squares the condition number of the data,
+/// so they're never built. Instead `A = [x 1]` is factored as `QR` with Householder
+/// reflections in `f64` and `R θ = Qᵀy` is solved by back substitution, which stays
+/// accurate for ill conditioned data. A column of `A` that's a combination of the previous
+/// ones leaves a negligible pivot, it's coefficient is left at zero.
+///
+/// # Args
+/// * `x` - The samples, one per row.
+/// * `y` - The labels, one per row.
+///
+/// # Returns
+/// The layer's parameters, the weights row by row followed by the biases.
+fn least_squares(x: ArrayView2<f32>, y: ArrayView2<f32>) -> Vec<f32> {
+    let (m, d) = x.dim();
+    let n = d + 1;
+
+    let mut a = Array2::ones((m, n));
+    a.slice_mut(s![.., ..d]).assign(&x.mapv(f64::from));
+    let mut b = y.mapv(f64::from);
+
+    let steps = n.min(m);
+
+    for j in 0..steps {
+        let mut v = a.slice(s![j.., j]).to_owned();
+        let norm = v.dot(&v).sqrt();
+        if norm == 0.0 {
+            continue;
+        }
+
+        // The reflection's sign is picked to avoid cancelling out it's first entry.
+        v[0] += v[0].signum() * norm;
+        let v_norm2 = v.dot(&v);
+
+        for mut col in a.slice_mut(s![j.., j..]).columns_mut() {
+            let f = 2.0 * v.dot(&col) / v_norm2;
+            col.scaled_add(-f, &v);
+        }
+
+        for mut col in b.slice_mut(s![j.., ..]).columns_mut() {
+            let f = 2.0 * v.dot(&col) / v_norm2;
+            col.scaled_add(-f, &v);
+        }
+    }
+
+    let max_pivot = (0..steps).map(|j| a[[j, j]].abs()).fold(0.0, f64::max);
+    let tol = max_pivot * m.max(n) as f64 * f64::EPSILON;
+    let mut theta = Array2::zeros((n, y.ncols()));
+
+    for j in (0..steps).rev() {
+        let pivot = a[[j, j]];
+        if pivot.abs() <= tol {
+            continue;
+        }
+
+        for k in 0..y.ncols() {
+            let known: f64 = (j + 1..steps).map(|i| a[[j, i]] * theta[[i, k]]).sum();
+            theta[[j, k]] = (b[[j, k]] - known) / pivot;
+        }
+    }
+
+    theta.iter().map(|&v| v as f32).collect()
+}
@@ -1,7 +1,9 @@
 mod backprop_trainer;
 mod builder;
+mod closed_form_trainer;
 mod trainer;
 
 pub use backprop_trainer::BackpropTrainer;
 pub use builder::TrainerBuilder;
-pub use trainer::{TrainResult, Trainer};
+pub use closed_form_trainer::ClosedFormTrainer;
+pub use trainer::{History, TrainResult, Trainer};
@@ -1,6 +1,11 @@
+use std::num::NonZeroUsize;
+
+use comms::specs::machine_learning::TrainerProgress;
+use ndarray::ArrayView2;
+
 use crate::{
-    Result,
-    datasets::{DataSrc, Dataset},
+    MlErr, Result,
+    datasets::{BatchOrder, DataSrc, Dataset, Samples},
     param_manager::ParamManager,
 };
 
@@ -10,6 +15,17 @@ use crate::{
 pub struct TrainResult<'trainer> {
     pub losses: &'trainer [f64],
     pub was_last: bool,
+    /// The amount of samples trained on during the call, one per row of every batch.
+    pub samples: usize,
+}
+
+/// The losses of a `fit`, one entry per training call.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    /// The loss of the last epoch of every call, `NaN` if the trainer's dataset is empty.
+    pub train_losses: Vec<f64>,
+    /// The loss over the validation dataset after every call.
+    pub val_losses: Vec<f64>,
 }
 
 /// This trait generalizes all the different concrete `ModelTrainer` variations between optimizers, loss functions, ...
@@ -34,6 +50,137 @@ pub trait Trainer: Send {
     /// An error if there's a mismatch in the sizes of the grad and param buffers.
     fn optimize<'mw>(&mut self, param_manager: &mut ParamManager<'mw>) -> Result<()>;
 
+    /// Computes the loss over a batch without training on it.
+    ///
+    /// Only runs the forward pass, the gradient and the parameters are left untouched. The
+    /// activations cached by the layers are overwritten, which is harmless since every
+    /// training step runs it's own forward pass before going backwards.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of the parameters to evaluate.
+    /// * `x` - The batch's samples.
+    /// * `y` - The batch's expected outputs.
+    ///
+    /// # Returns
+    /// The loss over the batch or an error if the model failed to run the forward pass.
+    fn evaluate<'mw>(
+        &mut self,
+        param_manager: &mut ParamManager<'mw>,
+        x: Samples<'_>,
+        y: ArrayView2<f32>,
+    ) -> Result<f64>;
+
+    /// Runs the model over some batches without training on them, like `evaluate` only the
+    /// forward pass runs.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of the parameters to run.
+    /// * `batches` - The batches' samples along with their expected outputs.
+    /// * `f` - Called with the outputs of the model and the expected outputs of every batch,
+    ///   both in row major order.
+    ///
+    /// # Returns
+    /// Whether the model was run, `false` if it has heads, or an error if the model failed
+    /// to run the forward pass or `f` failed.
+    fn predict<'d>(
+        &mut self,
+        param_manager: &mut ParamManager<'_>,
+        batches: &mut dyn Iterator<Item = (Samples<'d>, ArrayView2<'d, f32>)>,
+        f: &mut dyn FnMut(&[f32], &[f32]) -> Result<()>,
+    ) -> Result<bool>;
+
+    /// Runs the model over every row of the trainer's own dataset without training on it,
+    /// see `predict`.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of the parameters to run.
+    /// * `f` - Called with the outputs of the model and the expected outputs of every batch,
+    ///   both in row major order.
+    ///
+    /// # Returns
+    /// Whether the model was run, `false` if it has heads, or an error if the model failed
+    /// to run the forward pass or `f` failed.
+    fn predict_dataset(
+        &mut self,
+        param_manager: &mut ParamManager<'_>,
+        f: &mut dyn FnMut(&[f32], &[f32]) -> Result<()>,
+    ) -> Result<bool>;
+
+    /// Trains on the trainer's own dataset, evaluating the model on a validation dataset
+    /// after every training call.
+    ///
+    /// Lets the trainer be used on it's own, with the parameters living in the param manager
+    /// instead of in the servers. The training ends once the trainer's stop condition is met,
+    /// after `calls` calls or, with a `patience`, once the validation loss didn't improve for
+    /// that many calls in a row.
+    ///
+    /// # Args
+    /// * `param_manager` - The manager of the parameters to train.
+    /// * `val` - The validation dataset, evaluated as a single batch.
+    /// * `calls` - The maximum amount of training calls.
+    /// * `patience` - The calls without improvement to stop early after, if any.
+    ///
+    /// # Returns
+    /// The train and validation losses of every call.
+    ///
+    /// # Errors
+    /// An `MlErr::EmptyEpoch` if the validation dataset has no rows or any error
+    /// training or evaluating the model.
+    fn fit(
+        &mut self,
+        param_manager: &mut ParamManager<'_>,
+        val: &Dataset,
+        calls: NonZeroUsize,
+        patience: Option<NonZeroUsize>,
+    ) -> Result<History> {
+        let (x, y) = NonZeroUsize::new(val.rows())
+            .and_then(|rows| val.batches(rows).next())
+            .ok_or(MlErr::EmptyEpoch)?;
+
+        let mut history = History::default();
+        let mut best = f64::INFINITY;
+        let mut stale = 0;
+
+        for _ in 0..calls.get() {
+            let res = self.train(param_manager)?;
+            let was_last = res.was_last;
+            let train_loss = res.losses.last().copied().unwrap_or(f64::NAN);
+
+            let val_loss = self.evaluate(param_manager, x.clone(), y)?;
+            history.train_losses.push(train_loss);
+            history.val_losses.push(val_loss);
+
+            if was_last {
+                break;
+            }
+
+            if val_loss < best {
+                best = val_loss;
+                stale = 0;
+            } else {
+                stale += 1;
+            }
+
+            if patience.is_some_and(|patience| stale >= patience.get()) {
+                break;
+            }
+        }
+
+        Ok(history)
+    }
+
+    /// How far the training went.
+    ///
+    /// # Returns
+    /// The amount of finished epochs and steps.
+    fn progress(&self) -> TrainerProgress;
+
+    /// Takes the rows of every epoch's batches from an order instead of the sampler.
+    ///
+    /// # Args
+    /// * `batch_order` - The order of the batches of every epoch.
+    fn set_batch_order(&mut self, batch_order: Box<dyn BatchOrder>);
+
     /// Appends the given source to it's dataset.
     ///
     /// # Args
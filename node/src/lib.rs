@@ -0,0 +1,110 @@
+mod router;
+mod stat_service;
+
+use std::{io, num::NonZeroUsize, time::Duration};
+
+use comms::{Acceptor, Connector, NetListener};
+use log::info;
+use uuid::Uuid;
+use worker::compute::ComputePool;
+
+pub use router::NodeRouter;
+
+/// The timeout duration for the reliable transport.
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The timeout duration for a peer to take a message sent through the reliable transport.
+const NETWORK_SEND_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The starting sleep duration for exponential backoff.
+const NETWORK_EXP_BACKOFF_BASE: Duration = Duration::from_secs(2);
+
+/// The coeficient which to multiply the current sleep duration for exponential backoff.
+const NETWORK_EXP_BACKOFF_COEF: u32 = 2;
+
+/// The amount of retries to do until giving up the connection for exponential backoff.
+const NETWORK_EXP_BACKOFF_RETRIES: usize = 4;
+
+/// Serves a node on an already bound listener, either a tcp or a local one.
+///
+/// # Args
+/// * `listener` - The listener to accept the orchestrator's and the workers' connections from.
+/// * `auth_token` - The pre-shared token to authenticate the peers with, if any.
+/// * `bootstrap_timeout` - How long to wait for an orchestrator to bootstrap the node, if bounded.
+/// * `compute_threads` - The amount of threads to compute the training steps on, one per core if `None`.
+/// * `cpu_affinity` - The cpus to pin the compute threads to, unpinned if `None`.
+///
+/// # Returns
+/// An io error if there's an issue accepting new incoming connections or the
+/// bootstrap handshake is invalid.
+pub async fn serve(
+    listener: impl Into<NetListener>,
+    auth_token: Option<&[u8]>,
+    bootstrap_timeout: Option<Duration>,
+    compute_threads: Option<NonZeroUsize>,
+    cpu_affinity: Option<Vec<usize>>,
+) -> io::Result<()> {
+    let listener = listener.into();
+    let port = listener.port()?;
+
+    let rtp_factory = |rx, tx| {
+        comms::build_reliable_transport(
+            rx,
+            tx,
+            NETWORK_TIMEOUT,
+            NETWORK_SEND_TIMEOUT,
+            NETWORK_EXP_BACKOFF_BASE,
+            NETWORK_EXP_BACKOFF_COEF,
+            NETWORK_EXP_BACKOFF_RETRIES,
+        )
+    };
+
+    let transport_factory = async || {
+        let stream = listener.accept().await?;
+
+        match stream.peer_addr()? {
+            Some(peer_addr) => info!("new incoming connection from {peer_addr}"),
+            None => info!("new incoming connection from within the process"),
+        }
+
+        let (rx, tx) = stream.into_split();
+        Ok(rtp_factory(rx, tx))
+    };
+
+    let id = Uuid::new_v4();
+    info!("Assigned node id {id}");
+
+    let mut acceptor = Acceptor::new(id, transport_factory);
+    if let Some(port) = port {
+        acceptor = acceptor.with_port(port);
+    }
+
+    let mut connector = Connector::new(id, rtp_factory);
+
+    if let Some(token) = auth_token {
+        info!("authenticating peers with a pre-shared token");
+        acceptor = acceptor.with_auth_token(token);
+        connector = connector.with_auth_token(token);
+    }
+
+    let mut router = NodeRouter::new(acceptor, connector);
+
+    if let Some(timeout) = bootstrap_timeout {
+        info!("bootstrap timeout set to {timeout:?}");
+        router = router.with_bootstrap_timeout(timeout);
+    }
+
+    match (compute_threads, cpu_affinity) {
+        (nthreads, Some(cpus)) => {
+            info!("pinning the threads computing the training steps to the cpus {cpus:?}");
+            router = router.with_compute_pool(ComputePool::pinned(nthreads, cpus)?);
+        }
+        (Some(nthreads), None) => {
+            info!("computing the training steps on {nthreads} threads");
+            router = router.with_compute_pool(ComputePool::new(nthreads)?);
+        }
+        (None, None) => {}
+    }
+
+    router.run().await
+}
@@ -1,64 +1,120 @@
-mod router;
-mod stat_service;
+use std::{env, io, num::NonZeroUsize, time::Duration};
 
-use std::{env, io, time::Duration};
-
-use comms::{Acceptor, Connector};
-use log::info;
-use tokio::net::TcpListener;
-
-use router::NodeRouter;
-use uuid::Uuid;
+use log::{info, warn};
+use tokio::{
+    net::TcpListener,
+    runtime::{self, Runtime},
+};
 
 /// A default host address for the tcp listener in the acceptor.
 const DEFAULT_HOST: &str = "0.0.0.0";
 
-/// The timeout duration for the reliable transport.
-const NETWORK_TIMEOUT: Duration = Duration::from_secs(120);
+/// The environment variable holding the pre-shared authentication token.
+const AUTH_TOKEN_VAR: &str = "AUTH_TOKEN";
+
+/// The environment variable holding the bootstrap timeout in seconds.
+const BOOTSTRAP_TIMEOUT_VAR: &str = "BOOTSTRAP_TIMEOUT";
+
+/// The environment variable holding the amount of worker threads of the runtime.
+const RUNTIME_THREADS_VAR: &str = "ONO_RUNTIME_THREADS";
+
+/// The environment variable holding the flavor of the runtime.
+const RUNTIME_FLAVOR_VAR: &str = "ONO_RUNTIME_FLAVOR";
+
+/// The environment variable holding the amount of threads to compute the training steps on.
+const COMPUTE_THREADS_VAR: &str = "ONO_COMPUTE_THREADS";
+
+/// The environment variable holding the comma separated cpus to pin the compute threads to.
+const CPU_AFFINITY_VAR: &str = "ONO_CPU_AFFINITY";
+
+/// Builds the runtime the node runs on following the environment.
+///
+/// Defaults to a multi thread runtime with a worker thread per core.
+///
+/// # Returns
+/// A new `Runtime` or an io error if the environment holds invalid values.
+fn build_runtime() -> io::Result<Runtime> {
+    let flavor = env::var(RUNTIME_FLAVOR_VAR).unwrap_or_else(|_| "multi_thread".to_string());
+
+    let mut builder = match flavor.as_str() {
+        "multi_thread" => runtime::Builder::new_multi_thread(),
+        "current_thread" => runtime::Builder::new_current_thread(),
+        _ => {
+            let text = format!(
+                "invalid {RUNTIME_FLAVOR_VAR} {flavor:?}, expected multi_thread or current_thread"
+            );
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, text));
+        }
+    };
 
-/// The starting sleep duration for exponential backoff.
-const NETWORK_EXP_BACKOFF_BASE: Duration = Duration::from_secs(2);
+    if let Ok(threads) = env::var(RUNTIME_THREADS_VAR) {
+        let threads: NonZeroUsize = threads.parse().map_err(io::Error::other)?;
 
-/// The coeficient which to multiply the current sleep duration for exponential backoff.
-const NETWORK_EXP_BACKOFF_COEF: u32 = 2;
+        if flavor == "current_thread" {
+            warn!("ignoring {RUNTIME_THREADS_VAR}, the current_thread runtime has a single thread");
+        } else {
+            builder.worker_threads(threads.get());
+        }
+    }
 
-/// The amount of retries to do until giving up the connection for exponential backoff.
-const NETWORK_EXP_BACKOFF_RETRIES: usize = 4;
+    info!("running on a {flavor} runtime");
+    builder.enable_all().build()
+}
 
-#[tokio::main]
-async fn main() -> io::Result<()> {
+fn main() -> io::Result<()> {
     env_logger::init();
+    build_runtime()?.block_on(run())
+}
 
+/// Binds the listener and serves the node.
+///
+/// # Returns
+/// An io error if the environment holds invalid values or serving the node fails.
+async fn run() -> io::Result<()> {
     let host = env::var("HOST").unwrap_or_else(|_| DEFAULT_HOST.to_string());
-    let port = env::var("PORT").map_err(io::Error::other)?;
+    let port: u16 = env::var("PORT")
+        .map_err(io::Error::other)?
+        .parse()
+        .map_err(io::Error::other)?;
+
     let addr = format!("{host}:{port}");
 
     let listener = TcpListener::bind(&addr).await?;
-    info!("listening at {addr}");
-
-    let rtp_factory = |rx, tx| {
-        comms::build_reliable_transport(
-            rx,
-            tx,
-            NETWORK_TIMEOUT,
-            NETWORK_EXP_BACKOFF_BASE,
-            NETWORK_EXP_BACKOFF_COEF,
-            NETWORK_EXP_BACKOFF_RETRIES,
-        )
-    };
+    let local_addr = listener.local_addr()?;
+    info!("listening at {local_addr}");
+
+    if port == 0 {
+        // The port was assigned by the OS, print it so launchers can pick it up.
+        println!("{}", local_addr.port());
+    }
 
-    let transport_factory = async || {
-        let (stream, peer_addr) = listener.accept().await?;
-        info!("new incoming connection from {peer_addr}");
+    let auth_token = env::var(AUTH_TOKEN_VAR).ok();
 
-        let (rx, tx) = stream.into_split();
-        Ok(rtp_factory(rx, tx))
+    let bootstrap_timeout = match env::var(BOOTSTRAP_TIMEOUT_VAR) {
+        Ok(secs) => Some(Duration::from_secs(secs.parse().map_err(io::Error::other)?)),
+        Err(_) => None,
     };
 
-    let id = Uuid::new_v4();
-    info!("Assigned node id {id}");
+    let compute_threads = match env::var(COMPUTE_THREADS_VAR) {
+        Ok(threads) => Some(threads.parse().map_err(io::Error::other)?),
+        Err(_) => None,
+    };
+
+    let cpu_affinity = match env::var(CPU_AFFINITY_VAR) {
+        Ok(cpus) => Some(
+            cpus.split(',')
+                .map(|cpu| cpu.trim().parse().map_err(io::Error::other))
+                .collect::<io::Result<_>>()?,
+        ),
+        Err(_) => None,
+    };
 
-    let acceptor = Acceptor::new(id, transport_factory);
-    let connector = Connector::new(id, rtp_factory);
-    NodeRouter::new(acceptor, connector).run().await
+    node::serve(
+        listener,
+        auth_token.as_deref().map(str::as_bytes),
+        bootstrap_timeout,
+        compute_threads,
+        cpu_affinity,
+    )
+    .await
 }
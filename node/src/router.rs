@@ -1,7 +1,8 @@
-use std::io;
+use std::{io, time::Duration};
 
 use comms::{
-    Acceptor, Connection, Connector, OrchEvent, OrchHandle, TransportLayer,
+    Acceptor, Connection, Connector, NetReadHalf, NetWriteHalf, OrchEvent, OrchHandle,
+    TransportLayer,
     protocol::Entity,
     share_dataset,
     specs::{
@@ -16,10 +17,11 @@ use machine_learning::datasets::Dataset;
 use parameter_server::service::{Server, ServerBuilder};
 use tokio::{
     io::{AsyncRead, AsyncWrite},
-    net::tcp::{OwnedReadHalf, OwnedWriteHalf},
+    time,
 };
 use worker::{
     builder::WorkerBuilder,
+    compute::ComputePool,
     workers::{ParamServerWorker, Run, Worker},
 };
 
@@ -37,6 +39,8 @@ where
 {
     acceptor: Acceptor<T, F>,
     connector: Connector<R, W, T, G>,
+    bootstrap_timeout: Option<Duration>,
+    compute_pool: ComputePool,
 }
 
 impl<R, W, T, F, G> NodeRouter<R, W, T, F, G>
@@ -59,40 +63,111 @@ where
         Self {
             acceptor,
             connector,
+            bootstrap_timeout: None,
+            compute_pool: ComputePool::default(),
         }
     }
+
+    /// Sets the maximum amount of time to wait for the first orchestrator to bootstrap the node.
+    ///
+    /// Once elapsed without a complete handshake the node stops running. After the first
+    /// orchestrator connects, the node waits for the following ones indefinitely.
+    ///
+    /// # Args
+    /// * `timeout` - The bootstrap timeout.
+    ///
+    /// # Returns
+    /// The modified `NodeRouter`.
+    pub fn with_bootstrap_timeout(mut self, timeout: Duration) -> Self {
+        self.bootstrap_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the pool the workers compute their training steps on, the one sized to the
+    /// machine's cores by default.
+    ///
+    /// # Args
+    /// * `compute_pool` - The pool to compute the training steps on.
+    ///
+    /// # Returns
+    /// The modified `NodeRouter`.
+    pub fn with_compute_pool(mut self, compute_pool: ComputePool) -> Self {
+        self.compute_pool = compute_pool;
+        self
+    }
 }
 
-impl<T, F, G> NodeRouter<OwnedReadHalf, OwnedWriteHalf, T, F, G>
+impl<T, F, G> NodeRouter<NetReadHalf, NetWriteHalf, T, F, G>
 where
     T: TransportLayer + 'static,
     F: AsyncFn() -> io::Result<T>,
-    G: Fn(OwnedReadHalf, OwnedWriteHalf) -> T + Clone,
+    G: Fn(NetReadHalf, NetWriteHalf) -> T + Clone,
 {
     /// Waits for incoming connections and runs the specified node instance.
     ///
     /// Blocks indefinitely, accepting one orchestrator connection per iteration.
     /// Session-level errors are logged and the loop continues.
     ///
+    /// If a bootstrap timeout was set and no orchestrator completes the handshake
+    /// in time for the first session, the node stops running.
+    ///
     /// # Returns
-    /// An io error if there's an issue accepting new incoming connections.
+    /// An io error if there's an issue accepting new incoming connections or the
+    /// bootstrap handshake is invalid.
     pub async fn run(mut self) -> io::Result<()> {
-        let src = Entity::Node;
-
         loop {
             info!("awaiting connection");
 
-            let Connection::Orch(orch_handle) = self.acceptor.accept(src).await? else {
+            let conn = match self.accept().await {
+                Ok(Some(conn)) => conn,
+                Ok(None) => {
+                    info!("no orchestrator bootstrapped the node in time, shutting down");
+                    return Ok(());
+                }
+                Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                    warn!("rejected connection: {e}");
+                    continue;
+                }
+                Err(e) => {
+                    let text = format!("invalid bootstrap handshake: {e}");
+                    return Err(io::Error::new(e.kind(), text));
+                }
+            };
+
+            let Connection::Orch(orch_handle) = conn else {
                 warn!("expected an orchestrator connection, got something else");
                 continue;
             };
 
+            // The node was bootstrapped, it's idle in between sessions from now on.
+            self.bootstrap_timeout = None;
             self.handle_orch(orch_handle).await;
         }
     }
 
+    /// Accepts a new incoming connection within the bootstrap timeout, if it's still waiting
+    /// for the first orchestrator.
+    ///
+    /// # Returns
+    /// The new connection, `None` if the bootstrap timeout elapsed or an io error if occurred.
+    async fn accept(&mut self) -> io::Result<Option<Connection<T>>> {
+        let src = Entity::Node;
+
+        let Some(timeout) = self.bootstrap_timeout else {
+            return self.acceptor.accept(src).await.map(Some);
+        };
+
+        match time::timeout(timeout, self.acceptor.accept(src)).await {
+            Ok(conn) => conn.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
     /// Handles an incoming orchestrator connection.
     ///
+    /// Once a session is over the node goes back to idle on the same connection, so the
+    /// orchestrator can bootstrap it again without dialing it.
+    ///
     /// # Args
     /// * `orch_handle` - The newly connected orchestrator's handle.
     async fn handle_orch(&mut self, mut orch_handle: OrchHandle<T>) {
@@ -100,15 +175,30 @@ where
             debug!("Received {event:?} from orchestrator");
 
             match event {
-                OrchEvent::Create { spec } => {
-                    if let Err(e) = self.route(spec, orch_handle).await {
-                        error!("session failed with an error: {e}");
+                OrchEvent::Create { spec } => match self.route(spec, orch_handle).await {
+                    Ok(released) => {
+                        info!("session over, idle on the orchestrator's connection");
+                        orch_handle = released;
+                        continue;
                     }
-                }
+                    Err(e) => error!("session failed with an error: {e}"),
+                },
                 OrchEvent::StatsRequest { reqs } => {
-                    if let Err(e) = self.service_stats(reqs, orch_handle).await {
+                    // The orchestrator keeps the connection to bootstrap the node afterwards.
+                    if let Err(e) = self.service_stats(reqs, &mut orch_handle).await {
                         error!("stat service failed with an error: {e}");
+                        break;
                     }
+
+                    continue;
+                }
+                OrchEvent::Ping => {
+                    if let Err(e) = orch_handle.pong().await {
+                        error!("failed to answer the orchestrator's ping: {e}");
+                        break;
+                    }
+
+                    continue;
                 }
                 OrchEvent::Disconnect => {}
                 _ => {
@@ -132,7 +222,7 @@ where
     async fn service_stats(
         &mut self,
         reqs: Vec<StatRequest>,
-        mut orch_handle: OrchHandle<T>,
+        orch_handle: &mut OrchHandle<T>,
     ) -> io::Result<()> {
         let mut service = StatService::new(&mut self.acceptor, &mut self.connector);
         let stats = service.serve(reqs).await;
@@ -146,8 +236,12 @@ where
     /// * `orch_handle` - The handle for communicating with the orchestrator.
     ///
     /// # Returns
-    /// An io error if occurred.
-    async fn route(&mut self, spec: NodeSpec, orch_handle: OrchHandle<T>) -> io::Result<()> {
+    /// The orchestrator's handle once the session is over or an io error if occurred.
+    async fn route(
+        &mut self,
+        spec: NodeSpec,
+        orch_handle: OrchHandle<T>,
+    ) -> io::Result<OrchHandle<T>> {
         match spec {
             NodeSpec::Server(spec) => self.as_server(spec, orch_handle).await,
             NodeSpec::Worker(spec) => self.as_worker(spec, orch_handle).await,
@@ -161,31 +255,96 @@ where
     /// * `orch_handle` - The handle for communicating with the orchestrator.
     ///
     /// # Returns
-    /// An io error if occurred.
-    async fn as_server(&mut self, spec: ServerSpec, orch_handle: OrchHandle<T>) -> io::Result<()> {
+    /// The orchestrator's handle once the session is over or an io error if occurred.
+    async fn as_server(
+        &mut self,
+        spec: ServerSpec,
+        orch_handle: OrchHandle<T>,
+    ) -> io::Result<OrchHandle<T>> {
+        let late_workers = spec.late_workers();
         let mut server_builder = ServerBuilder::new(&mut self.acceptor);
-        let mut server = server_builder.build(spec, orch_handle).await?;
-        self.run_server(server.as_mut()).await
+        let mut server = server_builder.build(spec.clone(), orch_handle).await?;
+
+        if late_workers == 0 {
+            return self.run_server(server.as_mut()).await;
+        }
+
+        info!("starting parameter server session, accepting up to {late_workers} late workers");
+        let joiner = server.joiner(late_workers);
+
+        {
+            let mut run = server.run();
+
+            tokio::select! {
+                ret = &mut run => ret?,
+                ret = server_builder.accept_joiners(&spec, joiner) => {
+                    if let Err(e) = ret {
+                        warn!("stopped accepting late workers: {e}");
+                    }
+
+                    run.await?;
+                }
+            }
+        }
+
+        info!("parameter server session finished");
+        Self::release(server.as_mut())
     }
 
     /// Builds and runs the node as a worker instance.
     ///
+    /// If the worker fails, the reason is reported to the orchestrator before disconnecting.
+    ///
     /// # Args
     /// * `spec` - The worker's specification.
     /// * `orch_handle` - The handle for communicating with the orchestrator.
     ///
     /// # Returns
-    /// An io error if occurred.
+    /// The orchestrator's handle once the session is over or an io error if occurred.
     async fn as_worker(
         &mut self,
         spec: WorkerSpec,
         mut orch_handle: OrchHandle<T>,
-    ) -> io::Result<()> {
-        let mut worker_builder = WorkerBuilder::new(&mut self.acceptor, self.connector.clone());
-        let mut worker = worker_builder.build(&spec, &mut orch_handle).await?;
+    ) -> io::Result<OrchHandle<T>> {
+        let upgrade = match self.train_as_worker(spec, &mut orch_handle).await {
+            Ok(upgrade) => upgrade,
+            Err(e) => {
+                if let Err(report_err) = orch_handle.report_error(&e.to_string()).await {
+                    warn!("failed to report the worker's error to the orchestrator: {report_err}");
+                }
+
+                return Err(e);
+            }
+        };
+
+        let Some((spec, dataset)) = upgrade else {
+            return Ok(orch_handle);
+        };
+
+        let mut server = self.upgrade(spec, orch_handle, dataset).await?;
+        self.run_server(server.as_mut()).await
+    }
+
+    /// Builds the worker and trains until it's done or is asked to become a server.
+    ///
+    /// # Args
+    /// * `spec` - The worker's specification.
+    /// * `orch_handle` - The handle for communicating with the orchestrator.
+    ///
+    /// # Returns
+    /// The specification of the server to upgrade to along with the worker's dataset,
+    /// `None` if the worker is done, or an io error if occurred.
+    async fn train_as_worker(
+        &mut self,
+        spec: WorkerSpec,
+        orch_handle: &mut OrchHandle<T>,
+    ) -> io::Result<Option<(ServerSpec, Dataset)>> {
+        let mut worker_builder = WorkerBuilder::new(&mut self.acceptor, self.connector.clone())
+            .with_compute_pool(self.compute_pool.clone());
+        let mut worker = worker_builder.build(&spec, orch_handle).await?;
 
         match self.run_worker(worker.as_mut()).await? {
-            Run::Done => Ok(()),
+            Run::Done => Ok(None),
             Run::Switch {
                 server_addrs,
                 server_sizes,
@@ -203,18 +362,16 @@ where
                         server_ordering,
                         dataset,
                         trainer_spec,
-                        &mut orch_handle,
+                        orch_handle,
                     )
                     .await?;
 
                 self.run_worker(Box::new(worker).as_mut()).await?;
-                Ok(())
+                Ok(None)
             }
             Run::Upgrade { spec } => {
                 let trainer = worker.into_trainer();
-                let dataset = trainer.into_dataset();
-                let mut server = self.upgrade(spec, orch_handle, dataset).await?;
-                self.run_server(server.as_mut()).await
+                Ok(Some((spec, trainer.into_dataset())))
             }
         }
     }
@@ -225,12 +382,25 @@ where
     /// * `server` - The server to run.
     ///
     /// # Returns
-    /// An io error if occurred.
-    async fn run_server(&mut self, server: &mut dyn Server<T>) -> io::Result<()> {
+    /// The orchestrator's handle once the session is over or an io error if occurred.
+    async fn run_server(&mut self, server: &mut dyn Server<T>) -> io::Result<OrchHandle<T>> {
         info!("starting parameter server session");
         server.run().await?;
         info!("parameter server session finished");
-        Ok(())
+        Self::release(server)
+    }
+
+    /// Takes back the orchestrator's handle from a server that finished it's session.
+    ///
+    /// # Args
+    /// * `server` - The server that ran.
+    ///
+    /// # Returns
+    /// The orchestrator's handle or an io error if the server didn't release it.
+    fn release(server: &mut dyn Server<T>) -> io::Result<OrchHandle<T>> {
+        server
+            .release()
+            .ok_or_else(|| io::Error::other("the server didn't release the orchestrator"))
     }
 
     /// Runs the node as a worker instance for a training session.
@@ -274,7 +444,8 @@ where
         trainer_spec: TrainerSpec,
         orch_handle: &'a mut OrchHandle<T>,
     ) -> io::Result<ParamServerWorker<'a, T>> {
-        let mut worker_builder = WorkerBuilder::new(&mut self.acceptor, self.connector.clone());
+        let mut worker_builder = WorkerBuilder::new(&mut self.acceptor, self.connector.clone())
+            .with_compute_pool(self.compute_pool.clone());
 
         let worker = worker_builder
             .build_switched(
@@ -318,8 +489,9 @@ where
                 // SAFETY: The amount of partitions is taken from
                 //         the spec, meaning, this closure will be
                 //         called at most `spec.nworkers` times.
-                let (samples_raw, labels_raw) = partitions.next().unwrap();
-                let mut samples_cursor = share_dataset::get_dataset_cursor(samples_raw);
+                let (samples_raw, labels_raw) =
+                    partitions.next().unwrap().map_err(io::Error::other)?;
+                let mut samples_cursor = share_dataset::get_dataset_cursor(&samples_raw);
                 let mut labels_cursor = share_dataset::get_dataset_cursor(labels_raw);
 
                 worker_handle
@@ -339,3 +511,136 @@ where
         Ok(server)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use comms::{
+        NetStream,
+        protocol::{Command, Msg},
+    };
+    use tokio::{
+        io::AsyncWriteExt,
+        net::{TcpListener, TcpStream},
+    };
+    use uuid::Uuid;
+
+    use super::*;
+
+    const BOOTSTRAP_TIMEOUT: Duration = Duration::from_millis(200);
+    const TEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+    async fn run_router(listener: TcpListener) -> io::Result<()> {
+        let port = listener.local_addr()?.port();
+
+        let transport_factory = async || {
+            let (stream, _) = listener.accept().await?;
+            let (rx, tx) = NetStream::Tcp(stream).into_split();
+            Ok(comms::build_simple_transport(rx, tx))
+        };
+
+        let acceptor = Acceptor::new(Uuid::new_v4(), transport_factory).with_port(port);
+        let connector = Connector::new(Uuid::new_v4(), comms::build_simple_transport);
+
+        NodeRouter::new(acceptor, connector)
+            .with_bootstrap_timeout(BOOTSTRAP_TIMEOUT)
+            .run()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_times_out_on_silent_peer() -> io::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let _silent_peer = TcpStream::connect(addr).await?;
+
+        let res = time::timeout(TEST_TIMEOUT, run_router(listener)).await;
+        assert!(matches!(res, Ok(Ok(()))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_rejects_garbled_handshake() -> io::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let (rx, tx) = TcpStream::connect(addr).await?.into_split();
+        let mut transport = comms::build_simple_transport(rx, tx);
+        transport.send(&Msg::Control(Command::Ping)).await?;
+
+        let res = time::timeout(TEST_TIMEOUT, run_router(listener)).await;
+        let Ok(Err(e)) = res else {
+            panic!("expected a bootstrap error, got: {res:?}");
+        };
+
+        assert!(e.to_string().contains("invalid bootstrap handshake"));
+        transport
+            .send(&Msg::Control(Command::Disconnect))
+            .await
+            .ok();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_times_out_on_partial_handshake() -> io::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let mut partial_peer = TcpStream::connect(addr).await?;
+        partial_peer.write_all(&[0xff, 0x00]).await?;
+
+        let res = time::timeout(TEST_TIMEOUT, run_router(listener)).await;
+        assert!(matches!(res, Ok(Ok(()))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_handshake_reports_ephemeral_port() -> io::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let orch = async {
+            let (rx, tx) = TcpStream::connect(addr).await?.into_split();
+            let connector = Connector::new(Uuid::nil(), comms::build_simple_transport);
+            let mut node_handle = connector.connect_node(rx, tx, Entity::Orchestrator).await?;
+            node_handle.disconnect().await?;
+            Ok::<_, io::Error>(node_handle.port())
+        };
+
+        let port = tokio::select! {
+            res = time::timeout(TEST_TIMEOUT, run_router(listener)) => {
+                panic!("the router stopped after being bootstrapped: {res:?}")
+            }
+            port = orch => port?,
+        };
+
+        assert_eq!(port, Some(addr.port()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_timeout_only_applies_to_the_first_orchestrator() -> io::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let connect = async || {
+            let (rx, tx) = TcpStream::connect(addr).await?.into_split();
+            let connector = Connector::new(Uuid::nil(), comms::build_simple_transport);
+            let mut node_handle = connector.connect_node(rx, tx, Entity::Orchestrator).await?;
+            node_handle.disconnect().await
+        };
+
+        let orchs = async {
+            connect().await?;
+            time::sleep(3 * BOOTSTRAP_TIMEOUT).await;
+            connect().await
+        };
+
+        tokio::select! {
+            res = time::timeout(TEST_TIMEOUT, run_router(listener)) => {
+                panic!("the router stopped in between sessions: {res:?}")
+            }
+            res = orchs => res,
+        }
+    }
+}
@@ -1,22 +1,20 @@
 use std::{collections::HashMap, io, time::Duration};
 
 use comms::{
-    Acceptor, Connection, Connector, NodeEvent, NodeHandle, TransportLayer,
+    Acceptor, Connection, Connector, NetReadHalf, NetWriteHalf, NodeEvent, NodeHandle,
+    TransportLayer,
     protocol::Entity,
     specs::node::{Stat, StatRequest, StatResponse},
 };
 use futures::future;
 use log::{debug, warn};
-use tokio::{
-    net::{
-        TcpStream,
-        tcp::{OwnedReadHalf, OwnedWriteHalf},
-    },
-    time::Instant,
-};
+use tokio::time::Instant;
+
+type R = NetReadHalf;
+type W = NetWriteHalf;
 
-type R = OwnedReadHalf;
-type W = OwnedWriteHalf;
+/// The maximum amount of time to wait for each resolved address when connecting to a node.
+const CONNECT_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Resolves the requested statistic calculations by the orchestrator.
 pub struct StatService<'a, T, F, G>
@@ -196,7 +194,7 @@ where
     /// # Returns
     /// A handle to the other node or an io error if occurred.
     async fn connect_node(&self, addr: &str) -> io::Result<NodeHandle<T>> {
-        let stream = TcpStream::connect(addr).await?;
+        let stream = comms::connect(addr, CONNECT_ATTEMPT_TIMEOUT).await?;
         let (rx, tx) = stream.into_split();
         self.connector.connect_node(rx, tx, Entity::Node).await
     }
@@ -1,3 +1,3 @@
-from orchestra._orchestra import BaseSerializer, SparseSerializer
+from orchestra._orchestra import BaseSerializer, SparseSerializer, TopKSerializer
 
-__all__ = ["BaseSerializer", "SparseSerializer"]
\ No newline at end of file
+__all__ = ["BaseSerializer", "SparseSerializer", "TopKSerializer"]
\ No newline at end of file
@@ -1,6 +1,6 @@
-use std::num::NonZeroUsize;
+use std::{num::NonZeroUsize, path::PathBuf};
 
-use comms::floats::Float01;
+use comms::floats::{Float01, FloatPositive};
 use orchestrator::configs::{ActFnConfig, LayerConfig, ModelConfig, ParamGenConfig};
 use pyo3::{
     exceptions::{PyTypeError, PyValueError},
@@ -9,6 +9,7 @@ use pyo3::{
 
 use super::{
     activations::{ReLU, Sigmoid, Softmax, Tanh},
+    convert,
     initialization::{
         Const, Kaiming, Lecun, LecunUniform, Normal, Uniform, UniformInclusive, Xavier,
         XavierUniform,
@@ -100,6 +101,9 @@ pub struct Dense {
     pub output_size: NonZeroUsize,
     pub init: PyInit,
     pub act_fn: Option<PyActFn>,
+    pub tie: Option<usize>,
+    pub frozen: bool,
+    pub init_gain: Option<FloatPositive>,
 }
 
 #[pymethods]
@@ -110,6 +114,12 @@ impl Dense {
     /// * `output_size` - Number of output neurons.
     /// * `init` - Parameter initializer (e.g. `Kaiming()`, `Const(0.0)`).
     /// * `act_fn` - Optional activation function (e.g. `Sigmoid()`). Defaults to `None`.
+    /// * `tie` - Optional index of an earlier dense layer whose transposed weights this one
+    ///   shares, only the biases are initialized with `init`. Defaults to `None`.
+    /// * `frozen` - Whether the layer keeps it's initial parameters through the training.
+    ///   Defaults to `False`.
+    /// * `init_gain` - Optional factor the randomly initialized parameters are scaled by,
+    ///   e.g. `sqrt(2)` for a ReLU. Defaults to `None`.
     ///
     /// # Returns
     /// A dense layer configuration.
@@ -117,13 +127,16 @@ impl Dense {
     /// # Errors
     /// Raises a `TypeError` if `init` is not a supported initializer.
     /// Raises a `TypeError` if `act_fn` is not a supported activation function.
-    /// Raises a `ValueError` if `output_size` is zero.
+    /// Raises a `ValueError` if `output_size` is zero or `init_gain` isn't positive.
     #[new]
-    #[pyo3(signature = (output_size, init, act_fn = None))]
+    #[pyo3(signature = (output_size, init, act_fn = None, tie = None, frozen = false, init_gain = None))]
     pub fn new(
         output_size: usize,
         init: &Bound<'_, PyAny>,
         act_fn: Option<&Bound<'_, PyAny>>,
+        tie: Option<usize>,
+        frozen: bool,
+        init_gain: Option<f32>,
     ) -> PyResult<Self> {
         let output_size = NonZeroUsize::new(output_size)
             .ok_or_else(|| PyValueError::new_err("output_size must be greater than 0"))?;
@@ -132,6 +145,11 @@ impl Dense {
             output_size,
             init: extract_init(init)?,
             act_fn: extract_act_fn(act_fn)?,
+            tie,
+            frozen,
+            init_gain: init_gain
+                .map(|gain| convert::extract_positive(gain, "init_gain"))
+                .transpose()?,
         })
     }
 }
@@ -142,6 +160,9 @@ impl Dense {
             output_size: self.output_size,
             init: py_init_to_config(&self.init),
             act_fn: self.act_fn.as_ref().map(py_act_fn_to_config),
+            tie: self.tie,
+            frozen: self.frozen,
+            init_gain: self.init_gain,
         }
     }
 }
@@ -156,6 +177,8 @@ pub struct Conv2d {
     pub padding: usize,
     pub init: PyInit,
     pub act_fn: Option<PyActFn>,
+    pub frozen: bool,
+    pub init_gain: Option<FloatPositive>,
 }
 
 #[pymethods]
@@ -169,15 +192,19 @@ impl Conv2d {
     /// * `padding` - Zero-padding applied to each spatial side of the input.
     /// * `init` - Parameter initializer (e.g. `Kaiming()`, `Const(0.0)`).
     /// * `act_fn` - Optional activation function (e.g. `Sigmoid()`). Defaults to `None`.
+    /// * `frozen` - Whether the layer keeps it's initial parameters through the training.
+    ///   Defaults to `False`.
+    /// * `init_gain` - Optional factor the randomly initialized parameters are scaled by.
+    ///   Defaults to `None`.
     ///
     /// # Returns
     /// A Conv2d layer configuration.
     ///
     /// # Errors
-    /// Raises a `ValueError` if any dimension or stride is zero.
+    /// Raises a `ValueError` if any dimension or stride is zero or `init_gain` isn't positive.
     /// Raises a `TypeError` if `init` or `act_fn` are not supported types.
     #[new]
-    #[pyo3(signature = (input_dim, kernel_dim, stride, padding, init, act_fn = None))]
+    #[pyo3(signature = (input_dim, kernel_dim, stride, padding, init, act_fn = None, frozen = false, init_gain = None))]
     pub fn new(
         input_dim: (usize, usize, usize),
         kernel_dim: (usize, usize, usize),
@@ -185,6 +212,8 @@ impl Conv2d {
         padding: usize,
         init: &Bound<'_, PyAny>,
         act_fn: Option<&Bound<'_, PyAny>>,
+        frozen: bool,
+        init_gain: Option<f32>,
     ) -> PyResult<Self> {
         let make_nonzero = |v: usize, name: &'static str| {
             NonZeroUsize::new(v).ok_or_else(|| PyValueError::new_err(format!("{name} must be > 0")))
@@ -205,6 +234,10 @@ impl Conv2d {
             padding,
             init: extract_init(init)?,
             act_fn: extract_act_fn(act_fn)?,
+            frozen,
+            init_gain: init_gain
+                .map(|gain| convert::extract_positive(gain, "init_gain"))
+                .transpose()?,
         })
     }
 }
@@ -218,6 +251,8 @@ impl Conv2d {
             padding: self.padding,
             init: py_init_to_config(&self.init),
             act_fn: self.act_fn.as_ref().map(py_act_fn_to_config),
+            frozen: self.frozen,
+            init_gain: self.init_gain,
         }
     }
 }
@@ -301,19 +336,39 @@ impl Sequential {
     ///
     /// # Args
     /// * `layers` - List of `Dense`, `Conv2d` or `MaxPooling` layers. At least one required.
+    /// * `checkpoint_every` - If set, the workers only keep the activations of every that many layers, recomputing the rest during the backward pass. Defaults to `None`.
+    /// * `activation_clamp` - If set, the inputs of every sigmoid and tanh are clamped to `[-activation_clamp, activation_clamp]`. Defaults to `None`.
+    /// * `warm_start` - Optional path to a `.safetensors` file of a previous training, it's layers initialize the ones at the same index and the rest start from scratch. Defaults to `None`.
     ///
     /// # Returns
     /// A sequential model configuration.
     ///
     /// # Errors
-    /// Raises a `ValueError` if `layers` is empty.
+    /// Raises a `ValueError` if `layers` is empty, `checkpoint_every` is zero or `activation_clamp` isn't positive.
     /// Raises a `TypeError` if any element is not a `Dense`, `Conv2d` or `MaxPooling` instance.
     #[new]
-    pub fn new(layers: Vec<Bound<'_, PyAny>>) -> PyResult<Self> {
+    #[pyo3(signature = (layers, checkpoint_every = None, activation_clamp = None, warm_start = None))]
+    pub fn new(
+        layers: Vec<Bound<'_, PyAny>>,
+        checkpoint_every: Option<usize>,
+        activation_clamp: Option<f32>,
+        warm_start: Option<PathBuf>,
+    ) -> PyResult<Self> {
         if layers.is_empty() {
             return Err(PyValueError::new_err("model must have at least one layer"));
         }
 
+        let checkpoint_every = checkpoint_every
+            .map(|every| {
+                NonZeroUsize::new(every)
+                    .ok_or_else(|| PyValueError::new_err("checkpoint_every must be greater than 0"))
+            })
+            .transpose()?;
+
+        let activation_clamp = activation_clamp
+            .map(|clamp| convert::extract_positive(clamp, "activation_clamp"))
+            .transpose()?;
+
         let layer_configs: PyResult<Vec<_>> = layers
             .iter()
             .map(|l| {
@@ -334,6 +389,10 @@ impl Sequential {
         Ok(Self {
             inner: ModelConfig {
                 layers: layer_configs?,
+                heads: Vec::new(),
+                checkpoint_every,
+                activation_clamp,
+                warm_start,
             },
         })
     }
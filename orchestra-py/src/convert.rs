@@ -1,9 +1,11 @@
-use std::num::NonZeroUsize;
+use std::num::{NonZeroU64, NonZeroUsize};
 
 use comms::floats::{Float01, FloatNonNegative, FloatPositive};
 use orchestrator::configs::{
-    DataSrc, DatasetConfig, EarlyStoppingConfig, LossFnConfig, OptimizerConfig, SerializerConfig,
-    StoreConfig, SynchronizerConfig,
+    ClipGroupConfig, DataSrc, DatasetConfig, DivergencePolicyConfig, EarlyStoppingConfig,
+    FinalStepPolicyConfig, LossFnConfig, LrScalingConfig, LrScheduleConfig, MonitorIntervalConfig,
+    OptimizerConfig, QuantizationConfig, ReductionConfig, SamplerConfig, SerializerConfig,
+    StopConditionConfig, StoreConfig, SynchronizerConfig,
 };
 use pyo3::{
     exceptions::{PyTypeError, PyValueError},
@@ -13,8 +15,9 @@ use pyo3::{
 use super::{
     datasets::{InlineDataset, LocalDataset},
     loss_fns::{CrossEntropy, Mse},
+    lr_schedules::WarmRestarts,
     optimizers::{Adam, GradientDescent, GradientDescentWithMomentum},
-    serializer::{BaseSerializer, SparseSerializer},
+    serializer::{BaseSerializer, SparseSerializer, TopKSerializer},
     store::{BlockingStore, WildStore},
     sync::{BarrierSync, NonBlockingSync},
 };
@@ -25,23 +28,127 @@ pub fn parse_nonzero(n: usize, name: &'static str) -> PyResult<NonZeroUsize> {
         .ok_or_else(|| PyValueError::new_err(format!("{name} must be greater than 0")))
 }
 
-/// Converts an optional early-stopping tolerance to an `EarlyStoppingConfig`.
+/// Converts the optional step and wall time limits to a `StopConditionConfig`.
+///
+/// Returns `None` to stop after `max_epochs` if neither is set, or a `ValueError`
+/// if both are set or any of them is zero.
+pub fn extract_stop_condition(
+    max_steps: Option<usize>,
+    max_wall_time_secs: Option<u64>,
+) -> PyResult<Option<StopConditionConfig>> {
+    match (max_steps, max_wall_time_secs) {
+        (None, None) => Ok(None),
+        (Some(steps), None) => {
+            let steps = parse_nonzero(steps, "max_steps")?;
+            Ok(Some(StopConditionConfig::MaxSteps(steps)))
+        }
+        (None, Some(secs)) => {
+            let secs = NonZeroU64::new(secs).ok_or_else(|| {
+                PyValueError::new_err("max_wall_time_secs must be greater than 0")
+            })?;
+
+            Ok(Some(StopConditionConfig::MaxWallTimeSecs(secs)))
+        }
+        (Some(_), Some(_)) => Err(PyValueError::new_err(
+            "only one of max_steps and max_wall_time_secs can be set",
+        )),
+    }
+}
+
+/// Converts an optional early-stopping tolerance and it's epochs floor to an `EarlyStoppingConfig`.
 ///
 /// Returns a `ValueError` if the tolerance is zero or negative.
-pub fn extract_early_stopping(tolerance: Option<f64>) -> PyResult<Option<EarlyStoppingConfig>> {
+pub fn extract_early_stopping(
+    tolerance: Option<f64>,
+    min_epochs: usize,
+) -> PyResult<Option<EarlyStoppingConfig>> {
     match tolerance {
         Some(t) if t.is_sign_negative() || t == 0.0 => Err(PyValueError::new_err(
             "early stopping tolerance must be a non negative number",
         )),
         Some(t) => Ok(Some(EarlyStoppingConfig {
             tolerance: FloatNonNegative::new(t).unwrap(),
+            min_epochs,
         })),
         None => Ok(None),
     }
 }
 
+/// Converts the optional epoch and step monitor intervals to a `MonitorIntervalConfig`.
+///
+/// Returns `None` to check after every epoch if neither is set, or a `ValueError`
+/// if both are set or any of them is zero.
+pub fn extract_monitor_interval(
+    every_epochs: Option<usize>,
+    every_steps: Option<usize>,
+) -> PyResult<Option<MonitorIntervalConfig>> {
+    match (every_epochs, every_steps) {
+        (None, None) => Ok(None),
+        (Some(epochs), None) => {
+            let epochs = parse_nonzero(epochs, "monitor_every_epochs")?;
+            Ok(Some(MonitorIntervalConfig::Epochs(epochs)))
+        }
+        (None, Some(steps)) => {
+            let steps = parse_nonzero(steps, "monitor_every_steps")?;
+            Ok(Some(MonitorIntervalConfig::Steps(steps)))
+        }
+        (Some(_), Some(_)) => Err(PyValueError::new_err(
+            "only one of monitor_every_epochs and monitor_every_steps can be set",
+        )),
+    }
+}
+
+/// Converts the `((start, end), max_norm)` clip groups to a list of `ClipGroupConfig`s.
+///
+/// Returns a `ValueError` if any maximum norm is zero or negative.
+pub fn extract_clip_groups(
+    clip_groups: Vec<((usize, usize), f32)>,
+) -> PyResult<Vec<ClipGroupConfig>> {
+    clip_groups
+        .into_iter()
+        .map(|((start, end), max_norm)| {
+            let max_norm = FloatPositive::new(max_norm).ok_or_else(|| {
+                PyValueError::new_err("clip group max_norm must be greater than 0")
+            })?;
+
+            Ok(ClipGroupConfig {
+                start,
+                end,
+                max_norm,
+            })
+        })
+        .collect()
+}
+
+/// Converts the optional `ema_decay` to a `Float01`.
+///
+/// Returns a `ValueError` if the decay isn't between `0.0` and `1.0`.
+pub fn extract_ema_decay(ema_decay: Option<f32>) -> PyResult<Option<Float01>> {
+    ema_decay
+        .map(|decay| extract_unit(decay, "ema_decay"))
+        .transpose()
+}
+
+/// Converts the optional `server_momentum` to a `Float01`.
+///
+/// Returns a `ValueError` if the decay isn't between `0.0` and `1.0`.
+pub fn extract_server_momentum(server_momentum: Option<f32>) -> PyResult<Option<Float01>> {
+    server_momentum
+        .map(|beta| extract_unit(beta, "server_momentum"))
+        .transpose()
+}
+
+/// Converts the `quantize_params` flag to a `QuantizationConfig`.
+pub fn extract_quantization(quantize_params: bool) -> QuantizationConfig {
+    if quantize_params {
+        QuantizationConfig::Int8
+    } else {
+        QuantizationConfig::None
+    }
+}
+
 /// Validates that a value is strictly positive, returning a `ValueError` otherwise.
-fn extract_positive(value: f32, name: &str) -> PyResult<FloatPositive> {
+pub(crate) fn extract_positive(value: f32, name: &str) -> PyResult<FloatPositive> {
     FloatPositive::new(value)
         .ok_or_else(|| PyValueError::new_err(format!("{name} must be a positive number")))
 }
@@ -107,15 +214,113 @@ pub fn extract_serializer(obj: Option<&Bound<'_, PyAny>>) -> PyResult<Serializer
         Some(s) => {
             if let Ok(sparse) = s.extract::<PyRef<SparseSerializer>>() {
                 Ok(SerializerConfig::SparseCapable { r: sparse.r })
+            } else if let Ok(top_k) = s.extract::<PyRef<TopKSerializer>>() {
+                Ok(SerializerConfig::TopK {
+                    density: top_k.density,
+                })
             } else {
                 Err(PyTypeError::new_err(
-                    "serializer must be BaseSerializer() or SparseSerializer(r=...)",
+                    "serializer must be BaseSerializer(), SparseSerializer(r=...) or TopKSerializer(density=...)",
                 ))
             }
         }
     }
 }
 
+/// Converts the name of a divergence policy to a `DivergencePolicyConfig`.
+///
+/// Returns a `ValueError` if the name is not `"none"`, `"halve_lr"` or `"abort"`.
+pub fn extract_divergence_policy(
+    name: &str,
+    max_reductions: usize,
+) -> PyResult<DivergencePolicyConfig> {
+    match name {
+        "none" => Ok(DivergencePolicyConfig::None),
+        "halve_lr" => Ok(DivergencePolicyConfig::HalveLr { max_reductions }),
+        "abort" => Ok(DivergencePolicyConfig::Abort),
+        _ => Err(PyValueError::new_err(
+            "divergence_policy must be \"none\", \"halve_lr\" or \"abort\"",
+        )),
+    }
+}
+
+/// Converts the name of a final step policy to a `FinalStepPolicyConfig`.
+///
+/// Returns a `ValueError` if the name is not `"apply_partial"` or `"discard_partial"`.
+pub fn extract_final_step(name: &str) -> PyResult<FinalStepPolicyConfig> {
+    match name {
+        "apply_partial" => Ok(FinalStepPolicyConfig::ApplyPartial),
+        "discard_partial" => Ok(FinalStepPolicyConfig::DiscardPartial),
+        _ => Err(PyValueError::new_err(
+            "final_step must be \"apply_partial\" or \"discard_partial\"",
+        )),
+    }
+}
+
+/// Converts the name of a sampler and it's class settings to a `SamplerConfig`.
+///
+/// Returns a `ValueError` if the name is not `"sequential"`, `"shuffle"`, `"weighted_by_class"`
+/// or `"global_order"`, or if `"weighted_by_class"` is missing a positive `num_classes`.
+pub fn extract_sampler(
+    name: &str,
+    label_col: usize,
+    num_classes: Option<usize>,
+) -> PyResult<SamplerConfig> {
+    match name {
+        "sequential" => Ok(SamplerConfig::Sequential),
+        "shuffle" => Ok(SamplerConfig::Shuffle),
+        "weighted_by_class" => {
+            let num_classes = num_classes.ok_or_else(|| {
+                PyValueError::new_err("the weighted_by_class sampler requires num_classes")
+            })?;
+
+            Ok(SamplerConfig::WeightedByClass {
+                label_col,
+                num_classes: parse_nonzero(num_classes, "num_classes")?,
+            })
+        }
+        "global_order" => Ok(SamplerConfig::GlobalOrder),
+        _ => Err(PyValueError::new_err(
+            "sampler must be \"sequential\", \"shuffle\", \"weighted_by_class\" or \"global_order\"",
+        )),
+    }
+}
+
+/// Converts the name of a learning rate scaling rule to a `LrScalingConfig`.
+///
+/// Returns a `ValueError` if the name is not `"none"`, `"linear"` or `"sqrt_linear"`.
+pub fn extract_lr_scaling(name: &str) -> PyResult<LrScalingConfig> {
+    match name {
+        "none" => Ok(LrScalingConfig::None),
+        "linear" => Ok(LrScalingConfig::Linear),
+        "sqrt_linear" => Ok(LrScalingConfig::SqrtLinear),
+        _ => Err(PyValueError::new_err(
+            "lr_scaling must be \"none\", \"linear\" or \"sqrt_linear\"",
+        )),
+    }
+}
+
+/// Converts an optional Python learning rate schedule object to a `LrScheduleConfig`.
+///
+/// Returns `None` for a constant learning rate, a `TypeError` if the object is not a
+/// recognised schedule or a `ValueError` if any of it's cycle lengths is zero or it's
+/// `min_lr` isn't positive.
+pub fn extract_lr_schedule(obj: Option<&Bound<'_, PyAny>>) -> PyResult<Option<LrScheduleConfig>> {
+    let Some(obj) = obj else {
+        return Ok(None);
+    };
+
+    let schedule = obj
+        .extract::<PyRef<WarmRestarts>>()
+        .map_err(|_| PyTypeError::new_err("lr_schedule must be WarmRestarts(t_0=...)"))?;
+
+    Ok(Some(LrScheduleConfig::WarmRestarts {
+        t_0: parse_nonzero(schedule.t_0, "t_0")?,
+        t_mult: Some(parse_nonzero(schedule.t_mult, "t_mult")?),
+        min_lr: extract_positive(schedule.min_lr, "min_lr")?,
+    }))
+}
+
 /// Converts a Python sync object to a `SynchronizerConfig`.
 ///
 /// Returns a `TypeError` if the object is not a recognised synchronizer.
@@ -131,6 +336,15 @@ pub fn extract_synchronizer(obj: &Bound<'_, PyAny>) -> PyResult<SynchronizerConf
     }
 }
 
+/// Extracts the gradients' reduction out of a Python sync object.
+///
+/// Only `BarrierSync()` reduces gradients, any other object maps to the default `Sum`.
+pub fn extract_reduction(obj: &Bound<'_, PyAny>) -> ReductionConfig {
+    obj.extract::<PyRef<BarrierSync>>()
+        .map(|barrier| barrier.reduction)
+        .unwrap_or_default()
+}
+
 /// Converts a Python store object to a `StoreConfig`.
 ///
 /// Returns a `TypeError` if the object is not a recognised store.
@@ -158,6 +372,7 @@ pub fn extract_dataset(obj: &Bound<'_, PyAny>) -> PyResult<DatasetConfig> {
             },
             x_size: d.x_size,
             y_size: d.y_size,
+            test_split: 0.0,
         })
     } else if let Ok(d) = obj.extract::<PyRef<LocalDataset>>() {
         Ok(DatasetConfig {
@@ -167,6 +382,7 @@ pub fn extract_dataset(obj: &Bound<'_, PyAny>) -> PyResult<DatasetConfig> {
             },
             x_size: d.x_size,
             y_size: d.y_size,
+            test_split: 0.0,
         })
     } else {
         Err(PyTypeError::new_err(
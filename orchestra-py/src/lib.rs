@@ -4,6 +4,7 @@ mod convert;
 mod datasets;
 mod initialization;
 mod loss_fns;
+mod lr_schedules;
 mod optimizers;
 mod serializer;
 mod session;
@@ -42,11 +43,14 @@ fn _orchestra(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<optimizers::GradientDescentWithMomentum>()?;
     m.add_class::<optimizers::Adam>()?;
 
+    m.add_class::<lr_schedules::WarmRestarts>()?;
+
     m.add_class::<loss_fns::Mse>()?;
     m.add_class::<loss_fns::CrossEntropy>()?;
 
     m.add_class::<serializer::BaseSerializer>()?;
     m.add_class::<serializer::SparseSerializer>()?;
+    m.add_class::<serializer::TopKSerializer>()?;
 
     m.add_class::<sync::BarrierSync>()?;
     m.add_class::<sync::NonBlockingSync>()?;
@@ -0,0 +1,31 @@
+use pyo3::prelude::*;
+
+/// Cosine annealing with warm restarts (SGDR), followed by the parameter servers.
+///
+/// # Args
+/// * `t_0` - The length in epochs of the first cycle.
+/// * `t_mult` - The factor by which every cycle is longer than the previous one. Defaults to `1`.
+/// * `min_lr` - The learning rate reached at the end of every cycle, must be positive. Defaults to `1e-6`.
+///
+/// # Returns
+/// A warm restarts schedule configuration.
+#[pyclass(skip_from_py_object)]
+#[derive(Clone)]
+pub struct WarmRestarts {
+    pub t_0: usize,
+    pub t_mult: usize,
+    pub min_lr: f32,
+}
+
+#[pymethods]
+impl WarmRestarts {
+    #[new]
+    #[pyo3(signature = (t_0, t_mult = 1, min_lr = 1e-6))]
+    pub fn new(t_0: usize, t_mult: usize, min_lr: f32) -> Self {
+        Self {
+            t_0,
+            t_mult,
+            min_lr,
+        }
+    }
+}
@@ -47,3 +47,31 @@ impl SparseSerializer {
         Ok(Self { r })
     }
 }
+
+/// Top-k serializer — only the largest-magnitude gradient values are sent, the rest are
+/// accumulated locally and sent on later steps.
+///
+/// # Args
+/// * `density` - Fraction of the gradient values to send, between `0.0` and `1.0`.
+///
+/// # Returns
+/// A top-k serializer configuration.
+///
+/// # Errors
+/// Raises a `ValueError` if `density` is outside `[0.0, 1.0]`.
+#[pyclass(skip_from_py_object)]
+#[derive(Clone)]
+pub struct TopKSerializer {
+    pub density: Float01,
+}
+
+#[pymethods]
+impl TopKSerializer {
+    #[new]
+    pub fn new(density: f32) -> PyResult<Self> {
+        let density = Float01::new(density)
+            .ok_or_else(|| PyValueError::new_err("density must be between 0.0 and 1.0"))?;
+
+        Ok(Self { density })
+    }
+}
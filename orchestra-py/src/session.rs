@@ -8,7 +8,7 @@ use std::{
     time::Duration,
 };
 
-use orchestrator::sessions::{CancelHandle, TrainingEvent};
+use orchestrator::sessions::{CancelHandle, LrHandle, TrainingEvent};
 use pyo3::{exceptions::PyRuntimeError, prelude::*};
 use tokio::sync::mpsc::Receiver;
 
@@ -177,7 +177,7 @@ impl ProgressReporter {
 
 #[pyclass]
 pub struct TrainedModel {
-    pub inner: orchestrator::TrainedModel,
+    pub inner: orchestrator::TrainingReport,
 }
 
 #[pymethods]
@@ -190,7 +190,55 @@ impl TrainedModel {
     /// # Returns
     /// The trained parameters in a flat vector.
     pub fn weights(&self) -> Vec<f32> {
-        self.inner.params.to_vec()
+        self.inner.params().to_vec()
+    }
+
+    /// Returns the exponential moving average of the trained parameters.
+    ///
+    /// # Returns
+    /// The averaged parameters in a flat vector or `None` if no `ema_decay` was set.
+    pub fn ema_weights(&self) -> Option<Vec<f32>> {
+        self.inner.ema_params().map(<[f32]>::to_vec)
+    }
+
+    /// Returns the mean of the last loss published by each worker.
+    ///
+    /// # Returns
+    /// The final loss or `None` if no worker published a finite loss.
+    pub fn final_loss(&self) -> Option<f64> {
+        self.inner.final_loss()
+    }
+
+    /// Returns the amount of epochs run by the furthest worker.
+    ///
+    /// # Returns
+    /// The amount of epochs run.
+    pub fn epochs_run(&self) -> usize {
+        self.inner.epochs_run()
+    }
+
+    /// Returns whether the training was stopped by early stopping.
+    ///
+    /// # Returns
+    /// `True` if the loss converged before reaching the max epochs.
+    pub fn stopped_early(&self) -> bool {
+        self.inner.stopped_early()
+    }
+
+    /// Returns the amount of samples processed by all the workers.
+    ///
+    /// # Returns
+    /// The total amount of samples processed.
+    pub fn total_samples(&self) -> u64 {
+        self.inner.total_samples()
+    }
+
+    /// Returns how long the training took.
+    ///
+    /// # Returns
+    /// The wall clock duration of the training in seconds.
+    pub fn wall_time(&self) -> f64 {
+        self.inner.wall_time().as_secs_f64()
     }
 
     /// Saves the trained model in safetensors format.
@@ -202,15 +250,30 @@ impl TrainedModel {
     /// `None`.
     pub fn save_safetensors(&self, path: &str) -> PyResult<()> {
         self.inner
+            .model()
             .save_safetensors(path)
             .map_err(|e| PyRuntimeError::new_err(e.to_string()))
     }
+
+    /// Saves the trained model as a bundle of a `model.json` and a `weights.bin`.
+    ///
+    /// # Args
+    /// * `dir` - Destination directory of the bundle, created if missing.
+    ///
+    /// # Returns
+    /// `None`.
+    pub fn save_bundle(&self, dir: &str) -> PyResult<()> {
+        self.inner
+            .save_bundle(dir)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
 }
 
 #[pyclass]
 pub struct Session {
     pub inner: Option<(orchestrator::Session, Receiver<()>)>,
     pub cancel: CancelHandle,
+    pub lr_handle: LrHandle,
     pub max_epochs: usize,
     pub worker_count: usize,
 }
@@ -224,6 +287,21 @@ impl Session {
         self.cancel.stop();
     }
 
+    /// Continues the training with a new learning rate, taken by the servers at the next round.
+    ///
+    /// Only the servers' optimizers are changed, it has no effect on an all reduce training.
+    ///
+    /// # Args
+    /// * `lr` - The new learning rate.
+    ///
+    /// # Errors
+    /// Raises a `RuntimeError` if the learning rate isn't positive.
+    pub fn set_lr(&self, lr: f32) -> PyResult<()> {
+        self.lr_handle
+            .set_lr(lr)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
     /// Blocks until training completes and returns the trained model.
     ///
     /// # Returns
@@ -251,12 +329,12 @@ impl Session {
 
                     let result = loop {
                         match rx.blocking_recv() {
-                            Some(TrainingEvent::PublishedLosses { worker_id, losses }) => {
+                            Some(TrainingEvent::PublishedLosses {
+                                worker_id, losses, ..
+                            }) => {
                                 reporter.update(worker_id, &losses);
                             }
-                            Some(TrainingEvent::TrainingComplete { model: trained, .. }) => {
-                                break Ok(trained)
-                            }
+                            Some(TrainingEvent::TrainingComplete { report }) => break Ok(report),
                             Some(TrainingEvent::Error(e)) => break Err(e.to_string()),
                             Some(_) => continue,
                             None => break Err("session channel closed unexpectedly".into()),
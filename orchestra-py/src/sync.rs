@@ -1,21 +1,38 @@
-use pyo3::prelude::*;
+use orchestrator::configs::ReductionConfig;
+use pyo3::{exceptions::PyValueError, prelude::*};
 
 /// Barrier synchronization — all workers sync at each epoch.
 ///
 /// # Args
-/// This constructor does not take arguments.
+/// * `reduction` - How the workers' gradients are combined, `"sum"` or `"mean"`. Defaults to `"sum"`.
 ///
 /// # Returns
 /// A barrier synchronization configuration.
+///
+/// # Errors
+/// Raises a `ValueError` if `reduction` is neither `"sum"` nor `"mean"`.
 #[pyclass(skip_from_py_object)]
 #[derive(Clone)]
-pub struct BarrierSync;
+pub struct BarrierSync {
+    pub reduction: ReductionConfig,
+}
 
 #[pymethods]
 impl BarrierSync {
     #[new]
-    pub fn new() -> Self {
-        Self
+    #[pyo3(signature = (reduction = "sum"))]
+    pub fn new(reduction: &str) -> PyResult<Self> {
+        let reduction = match reduction {
+            "sum" => ReductionConfig::Sum,
+            "mean" => ReductionConfig::Mean,
+            _ => {
+                return Err(PyValueError::new_err(
+                    "reduction must be \"sum\" or \"mean\"",
+                ))
+            }
+        };
+
+        Ok(Self { reduction })
     }
 }
 
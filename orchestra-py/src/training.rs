@@ -1,8 +1,12 @@
-use std::thread;
+use std::{path::PathBuf, thread};
 
 use orchestrator::{
-    configs::{AlgorithmConfig, TrainingConfig},
-    train, CancelHandle,
+    CancelHandle,
+    configs::{
+        AlgorithmConfig, EmptyShardConfig, LrScalingConfig, QuantizationConfig, ReductionConfig,
+        StrategyConfig, TrainingConfig, UpdateRuleConfig,
+    },
+    train,
 };
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
@@ -10,8 +14,11 @@ use pyo3::prelude::*;
 use crate::{
     arch::Sequential,
     convert::{
-        extract_dataset, extract_early_stopping, extract_loss_fn, extract_optimizer,
-        extract_serializer, extract_store, extract_synchronizer, parse_nonzero,
+        extract_clip_groups, extract_dataset, extract_divergence_policy, extract_early_stopping,
+        extract_ema_decay, extract_final_step, extract_loss_fn, extract_lr_scaling,
+        extract_lr_schedule, extract_monitor_interval, extract_optimizer, extract_quantization,
+        extract_reduction, extract_sampler, extract_serializer, extract_server_momentum,
+        extract_stop_condition, extract_store, extract_synchronizer, parse_nonzero,
     },
     session::Session,
 };
@@ -41,6 +48,40 @@ pub struct PyTrainingConfig {
 /// * `offline_epochs` - Extra local epochs per sync round. Defaults to `0`.
 /// * `seed` - Optional random seed for reproducibility.
 /// * `early_stopping_tolerance` - If set, training stops when the absolute loss improvement between sync rounds is below this value. Must be > 0. Defaults to `None`.
+/// * `early_stopping_min_epochs` - The amount of epochs run before early stopping starts checking the loss. Defaults to `0`.
+/// * `divergence_policy` - What to do once the loss grows for 3 sync rounds in a row, `"none"`, `"halve_lr"` to halve the servers' learning rate or `"abort"` to stop the training. Defaults to `"none"`.
+/// * `max_lr_reductions` - The maximum amount of times the `"halve_lr"` policy halves the learning rate. Defaults to `3`.
+/// * `quantize_params` - If set, the servers broadcast the parameters quantized to int8 with a per-shard scale. Defaults to `False`.
+/// * `auth_token` - Optional pre-shared token the nodes were started with (`AUTH_TOKEN`). Defaults to `None`.
+/// * `allow_worker_loss` - If set, training continues with the remaining workers when a worker's connection drops. Defaults to `False`.
+/// * `min_workers` - The amount of workers each server waits for before starting the training, the rest may join later. Defaults to all of them.
+/// * `max_workers` - The maximum amount of workers each server accepts, counting the ones joining after the training started. Defaults to all of them.
+/// * `max_frame_size` - Optional maximum size in bytes of the frames exchanged between nodes. Defaults to one fitting the model.
+/// * `chunk_size` - If set, the servers broadcast the parameters split in frames of at most this many bytes. Defaults to `None`.
+/// * `slow_send_ms` - If set, the servers warn about the workers that repeatedly take longer than this many milliseconds to receive the parameters. Defaults to `None`.
+/// * `max_steps` - If set, the workers stop after this many optimization steps, one per mini batch, instead of after `max_epochs`. Defaults to `None`.
+/// * `max_wall_time_secs` - If set, the workers stop after the first epoch ending past this many seconds of training, instead of after `max_epochs`. Defaults to `None`.
+/// * `final_step` - What the workers do with the epoch `max_steps` stops in the middle of, `"apply_partial"` to apply the gradient of it's batches up to the last step or `"discard_partial"` to skip it whole. Defaults to `"apply_partial"`.
+/// * `sampler` - How the workers order the rows of their partition on every epoch, `"sequential"`, `"shuffle"`, `"weighted_by_class"` to draw them weighted by the inverse frequency of their class within the partition or `"global_order"` to deal every batch of a seeded order of the whole dataset between them. Defaults to `"shuffle"`.
+/// * `label_col` - The column of the labels holding the class of every row, for the `"weighted_by_class"` sampler. Defaults to `0`.
+/// * `num_classes` - The amount of classes, required by the `"weighted_by_class"` sampler. Defaults to `None`.
+/// * `lr_schedule` - Optional learning rate schedule followed by the parameter servers, such as `WarmRestarts(t_0=...)`. Defaults to a constant learning rate.
+/// * `lr_scaling` - How the servers' learning rate scales with the amount of workers, `"none"`, `"linear"` or `"sqrt_linear"`. Defaults to `"none"`.
+/// * `require_compression` - If set, training fails when a peer lacks the serializer's compression instead of falling back to dense gradients. Defaults to `False`.
+/// * `monitor_every_epochs` - If set, the early stopping criteria is checked every this many epochs instead of after each one. Defaults to `None`.
+/// * `monitor_every_steps` - If set, the early stopping criteria is checked every this many optimization steps, at the end of the sync round they fall in. Defaults to `None`.
+/// * `clip_groups` - A list of `((start, end), max_norm)` ranges of the model's parameters, aligned to it's layers, whose gradient is clipped to it's own maximum norm. Defaults to none.
+/// * `ema_decay` - If set, the servers keep an exponential moving average of the weights with this decay, available in the training report. Defaults to `None`.
+/// * `server_momentum` - If set, the servers step over an exponential moving average of the gradients with this decay instead of the raw ones. Defaults to `None`.
+/// * `track_staleness` - If set, the servers log how stale every applied gradient is and report a summary once the training ends, requires the `non_blocking` synchronizer. Defaults to `False`.
+/// * `metric_barrier_every` - If set, the workers of the `non_blocking` servers wait for each other every that many epochs, so the losses they report for the next one are computed over the same parameters. Defaults to `None`.
+/// * `accum_f64` - If set, the servers add the gradients up in `f64` before applying them, so many small gradients aren't rounded away. Only the `BlockingStore` accumulates them. Defaults to `False`.
+/// * `accum_f64_layers` - The indices of the layers whose gradients the servers add up in `f64`, the rest are added up in `f32`. Can't be combined with `accum_f64`. Defaults to none.
+/// * `bias_only` - If set, only the biases of the dense and conv layers are trained, the servers keep every other parameter as it was initialized. Defaults to `False`.
+/// * `server_syncs` - A synchronization strategy per server, in order, overriding `sync` so every server can pick it's own. Defaults to none.
+/// * `learning_curve_path` - If set, a CSV with the metrics of every epoch is written to this path once the training stops, the metrics the nodes don't report are left empty. Defaults to `None`.
+/// * `event_feed_path` - If set, the training events are published as newline delimited JSON to a Unix domain socket bound at this path. Defaults to `None`.
+/// * `latency_warmup_rounds` - If set, the workers measure how long every server takes to answer over this many rounds and then send their requests to the slowest ones first. Defaults to `None`.
 ///
 /// # Returns
 /// A `PyTrainingConfig` ready to be passed to `orchestrate(...)`.
@@ -63,6 +104,40 @@ pub struct PyTrainingConfig {
     offline_epochs = 0,
     seed = None,
     early_stopping_tolerance = None,
+    early_stopping_min_epochs = 0,
+    divergence_policy = "none",
+    max_lr_reductions = 3,
+    quantize_params = false,
+    auth_token = None,
+    allow_worker_loss = false,
+    min_workers = None,
+    max_workers = None,
+    max_frame_size = None,
+    chunk_size = None,
+    slow_send_ms = None,
+    max_steps = None,
+    max_wall_time_secs = None,
+    final_step = "apply_partial",
+    sampler = "shuffle",
+    label_col = 0,
+    num_classes = None,
+    lr_schedule = None,
+    lr_scaling = "none",
+    require_compression = false,
+    monitor_every_epochs = None,
+    monitor_every_steps = None,
+    clip_groups = Vec::new(),
+    ema_decay = None,
+    server_momentum = None,
+    track_staleness = false,
+    metric_barrier_every = None,
+    accum_f64 = false,
+    accum_f64_layers = Vec::new(),
+    bias_only = false,
+    server_syncs = Vec::new(),
+    learning_curve_path = None,
+    event_feed_path = None,
+    latency_warmup_rounds = None,
 ))]
 pub fn parameter_server(
     addrs: Vec<String>,
@@ -78,6 +153,40 @@ pub fn parameter_server(
     offline_epochs: usize,
     seed: Option<u64>,
     early_stopping_tolerance: Option<f64>,
+    early_stopping_min_epochs: usize,
+    divergence_policy: &str,
+    max_lr_reductions: usize,
+    quantize_params: bool,
+    auth_token: Option<String>,
+    allow_worker_loss: bool,
+    min_workers: Option<usize>,
+    max_workers: Option<usize>,
+    max_frame_size: Option<usize>,
+    chunk_size: Option<usize>,
+    slow_send_ms: Option<u64>,
+    max_steps: Option<usize>,
+    max_wall_time_secs: Option<u64>,
+    final_step: &str,
+    sampler: &str,
+    label_col: usize,
+    num_classes: Option<usize>,
+    lr_schedule: Option<&Bound<'_, PyAny>>,
+    lr_scaling: &str,
+    require_compression: bool,
+    monitor_every_epochs: Option<usize>,
+    monitor_every_steps: Option<usize>,
+    clip_groups: Vec<((usize, usize), f32)>,
+    ema_decay: Option<f32>,
+    server_momentum: Option<f32>,
+    track_staleness: bool,
+    metric_barrier_every: Option<usize>,
+    accum_f64: bool,
+    accum_f64_layers: Vec<usize>,
+    bias_only: bool,
+    server_syncs: Vec<Bound<'_, PyAny>>,
+    learning_curve_path: Option<PathBuf>,
+    event_feed_path: Option<PathBuf>,
+    latency_warmup_rounds: Option<usize>,
 ) -> PyResult<PyTrainingConfig> {
     let nservers_nz = parse_nonzero(nservers, "nservers")?;
     let max_epochs_nz = parse_nonzero(max_epochs, "max_epochs")?;
@@ -93,6 +202,7 @@ pub fn parameter_server(
                 store: extract_store(store)?,
             },
             serializer: extract_serializer(serializer)?,
+            quantization: extract_quantization(quantize_params),
             dataset: extract_dataset(dataset)?,
             optimizer: extract_optimizer(optimizer)?,
             loss_fn: extract_loss_fn(loss_fn)?,
@@ -100,7 +210,52 @@ pub fn parameter_server(
             max_epochs: max_epochs_nz,
             offline_epochs,
             seed,
-            early_stopping: extract_early_stopping(early_stopping_tolerance)?,
+            early_stopping: extract_early_stopping(
+                early_stopping_tolerance,
+                early_stopping_min_epochs,
+            )?,
+            divergence_policy: extract_divergence_policy(divergence_policy, max_lr_reductions)?,
+            monitor_interval: extract_monitor_interval(monitor_every_epochs, monitor_every_steps)?,
+            auth_token,
+            allow_worker_loss,
+            min_workers,
+            max_workers,
+            max_frame_size,
+            chunk_size: chunk_size
+                .map(|n| parse_nonzero(n, "chunk_size"))
+                .transpose()?,
+            slow_send_ms,
+            reduction: extract_reduction(sync),
+            barrier_timeout: None,
+            update_rule: UpdateRuleConfig::default(),
+            strategy: StrategyConfig::default(),
+            empty_shard: EmptyShardConfig::default(),
+            stop_condition: extract_stop_condition(max_steps, max_wall_time_secs)?,
+            final_step: extract_final_step(final_step)?,
+            sampler: extract_sampler(sampler, label_col, num_classes)?,
+            lr_schedule: extract_lr_schedule(lr_schedule)?,
+            lr_scaling: extract_lr_scaling(lr_scaling)?,
+            ema_decay: extract_ema_decay(ema_decay)?,
+            server_momentum: extract_server_momentum(server_momentum)?,
+            track_staleness,
+            metric_barrier_every: metric_barrier_every
+                .map(|n| parse_nonzero(n, "metric_barrier_every"))
+                .transpose()?,
+            accum_f64,
+            accum_f64_layers,
+            bias_only,
+            server_synchronizers: server_syncs
+                .iter()
+                .map(extract_synchronizer)
+                .collect::<PyResult<_>>()?,
+            require_compression,
+            clip_groups: extract_clip_groups(clip_groups)?,
+            learning_curve_path,
+            snapshot_every: None,
+            event_feed_path,
+            latency_warmup_rounds: latency_warmup_rounds
+                .map(|n| parse_nonzero(n, "latency_warmup_rounds"))
+                .transpose()?,
         },
         max_epochs,
         worker_count,
@@ -120,6 +275,23 @@ pub fn parameter_server(
 /// * `offline_epochs` - Extra local epochs per sync round. Defaults to `0`.
 /// * `seed` - Optional random seed for reproducibility.
 /// * `early_stopping_tolerance` - If set, training stops when the absolute loss improvement between sync rounds is below this value. Must be > 0. Defaults to `None`.
+/// * `early_stopping_min_epochs` - The amount of epochs run before early stopping starts checking the loss. Defaults to `0`.
+/// * `divergence_policy` - What to do once the loss grows for 3 sync rounds in a row, `"none"`, `"halve_lr"` to halve the servers' learning rate or `"abort"` to stop the training. Defaults to `"none"`.
+/// * `max_lr_reductions` - The maximum amount of times the `"halve_lr"` policy halves the learning rate. Defaults to `3`.
+/// * `auth_token` - Optional pre-shared token the nodes were started with (`AUTH_TOKEN`). Defaults to `None`.
+/// * `max_frame_size` - Optional maximum size in bytes of the frames exchanged between nodes. Defaults to one fitting the model.
+/// * `max_steps` - If set, the workers stop after this many optimization steps, one per mini batch, instead of after `max_epochs`. Defaults to `None`.
+/// * `max_wall_time_secs` - If set, the workers stop after the first epoch ending past this many seconds of training, instead of after `max_epochs`. Defaults to `None`.
+/// * `final_step` - What the workers do with the epoch `max_steps` stops in the middle of, `"apply_partial"` to apply the gradient of it's batches up to the last step or `"discard_partial"` to skip it whole. Defaults to `"apply_partial"`.
+/// * `sampler` - How the workers order the rows of their partition on every epoch, `"sequential"`, `"shuffle"`, `"weighted_by_class"` to draw them weighted by the inverse frequency of their class within the partition or `"global_order"` to deal every batch of a seeded order of the whole dataset between them. Defaults to `"shuffle"`.
+/// * `label_col` - The column of the labels holding the class of every row, for the `"weighted_by_class"` sampler. Defaults to `0`.
+/// * `num_classes` - The amount of classes, required by the `"weighted_by_class"` sampler. Defaults to `None`.
+/// * `require_compression` - If set, training fails when a peer lacks the serializer's compression instead of falling back to dense gradients. Defaults to `False`.
+/// * `monitor_every_epochs` - If set, the early stopping criteria is checked every this many epochs instead of after each one. Defaults to `None`.
+/// * `monitor_every_steps` - If set, the early stopping criteria is checked every this many optimization steps, at the end of the sync round they fall in. Defaults to `None`.
+/// * `clip_groups` - A list of `((start, end), max_norm)` ranges of the model's parameters, aligned to it's layers, whose gradient is clipped to it's own maximum norm. Defaults to none.
+/// * `learning_curve_path` - If set, a CSV with the metrics of every epoch is written to this path once the training stops, the metrics the nodes don't report are left empty. Defaults to `None`.
+/// * `event_feed_path` - If set, the training events are published as newline delimited JSON to a Unix domain socket bound at this path. Defaults to `None`.
 ///
 /// # Returns
 /// A `PyTrainingConfig` ready to be passed to `orchestrate(...)`.
@@ -139,6 +311,23 @@ pub fn parameter_server(
     offline_epochs = 0,
     seed = None,
     early_stopping_tolerance = None,
+    early_stopping_min_epochs = 0,
+    divergence_policy = "none",
+    max_lr_reductions = 3,
+    auth_token = None,
+    max_frame_size = None,
+    max_steps = None,
+    max_wall_time_secs = None,
+    final_step = "apply_partial",
+    sampler = "shuffle",
+    label_col = 0,
+    num_classes = None,
+    require_compression = false,
+    monitor_every_epochs = None,
+    monitor_every_steps = None,
+    clip_groups = Vec::new(),
+    learning_curve_path = None,
+    event_feed_path = None,
 ))]
 pub fn all_reduce(
     addrs: Vec<String>,
@@ -151,6 +340,23 @@ pub fn all_reduce(
     offline_epochs: usize,
     seed: Option<u64>,
     early_stopping_tolerance: Option<f64>,
+    early_stopping_min_epochs: usize,
+    divergence_policy: &str,
+    max_lr_reductions: usize,
+    auth_token: Option<String>,
+    max_frame_size: Option<usize>,
+    max_steps: Option<usize>,
+    max_wall_time_secs: Option<u64>,
+    final_step: &str,
+    sampler: &str,
+    label_col: usize,
+    num_classes: Option<usize>,
+    require_compression: bool,
+    monitor_every_epochs: Option<usize>,
+    monitor_every_steps: Option<usize>,
+    clip_groups: Vec<((usize, usize), f32)>,
+    learning_curve_path: Option<PathBuf>,
+    event_feed_path: Option<PathBuf>,
 ) -> PyResult<PyTrainingConfig> {
     let max_epochs_nz = parse_nonzero(max_epochs, "max_epochs")?;
     let batch_size_nz = parse_nonzero(batch_size, "batch_size")?;
@@ -161,6 +367,7 @@ pub fn all_reduce(
             addrs,
             algorithm: AlgorithmConfig::AllReduce,
             serializer: extract_serializer(serializer)?,
+            quantization: QuantizationConfig::None,
             dataset: extract_dataset(dataset)?,
             optimizer: extract_optimizer(optimizer)?,
             loss_fn: extract_loss_fn(loss_fn)?,
@@ -168,7 +375,43 @@ pub fn all_reduce(
             max_epochs: max_epochs_nz,
             offline_epochs,
             seed,
-            early_stopping: extract_early_stopping(early_stopping_tolerance)?,
+            early_stopping: extract_early_stopping(
+                early_stopping_tolerance,
+                early_stopping_min_epochs,
+            )?,
+            divergence_policy: extract_divergence_policy(divergence_policy, max_lr_reductions)?,
+            monitor_interval: extract_monitor_interval(monitor_every_epochs, monitor_every_steps)?,
+            auth_token,
+            allow_worker_loss: false,
+            min_workers: None,
+            max_workers: None,
+            max_frame_size,
+            chunk_size: None,
+            slow_send_ms: None,
+            reduction: ReductionConfig::Sum,
+            barrier_timeout: None,
+            update_rule: UpdateRuleConfig::default(),
+            strategy: StrategyConfig::default(),
+            empty_shard: EmptyShardConfig::default(),
+            stop_condition: extract_stop_condition(max_steps, max_wall_time_secs)?,
+            final_step: extract_final_step(final_step)?,
+            sampler: extract_sampler(sampler, label_col, num_classes)?,
+            lr_schedule: None,
+            lr_scaling: LrScalingConfig::None,
+            ema_decay: None,
+            server_momentum: None,
+            track_staleness: false,
+            metric_barrier_every: None,
+            accum_f64: false,
+            accum_f64_layers: Vec::new(),
+            bias_only: false,
+            server_synchronizers: Vec::new(),
+            require_compression,
+            clip_groups: extract_clip_groups(clip_groups)?,
+            learning_curve_path,
+            snapshot_every: None,
+            event_feed_path,
+            latency_warmup_rounds: None,
         },
         max_epochs,
         worker_count,
@@ -194,6 +437,40 @@ pub fn all_reduce(
 /// * `offline_epochs` - Extra local epochs per sync round. Defaults to `0`.
 /// * `seed` - Optional random seed for reproducibility.
 /// * `early_stopping_tolerance` - If set, training stops when the loss improvement is below this value. Defaults to `None`.
+/// * `early_stopping_min_epochs` - The amount of epochs run before early stopping starts checking the loss. Defaults to `0`.
+/// * `divergence_policy` - What to do once the loss grows for 3 sync rounds in a row, `"none"`, `"halve_lr"` to halve the servers' learning rate or `"abort"` to stop the training. Defaults to `"none"`.
+/// * `max_lr_reductions` - The maximum amount of times the `"halve_lr"` policy halves the learning rate. Defaults to `3`.
+/// * `quantize_params` - If set, the servers broadcast the parameters quantized to int8 with a per-shard scale. Defaults to `False`.
+/// * `auth_token` - Optional pre-shared token the nodes were started with (`AUTH_TOKEN`). Defaults to `None`.
+/// * `allow_worker_loss` - If set, training continues with the remaining workers when a worker's connection drops. Defaults to `False`.
+/// * `min_workers` - The amount of workers each server waits for before starting the training, the rest may join later. Defaults to all of them.
+/// * `max_workers` - The maximum amount of workers each server accepts, counting the ones joining after the training started. Defaults to all of them.
+/// * `max_frame_size` - Optional maximum size in bytes of the frames exchanged between nodes. Defaults to one fitting the model.
+/// * `chunk_size` - If set, the servers broadcast the parameters split in frames of at most this many bytes. Defaults to `None`.
+/// * `slow_send_ms` - If set, the servers warn about the workers that repeatedly take longer than this many milliseconds to receive the parameters. Defaults to `None`.
+/// * `max_steps` - If set, the workers stop after this many optimization steps, one per mini batch, instead of after `max_epochs`. Defaults to `None`.
+/// * `max_wall_time_secs` - If set, the workers stop after the first epoch ending past this many seconds of training, instead of after `max_epochs`. Defaults to `None`.
+/// * `final_step` - What the workers do with the epoch `max_steps` stops in the middle of, `"apply_partial"` to apply the gradient of it's batches up to the last step or `"discard_partial"` to skip it whole. Defaults to `"apply_partial"`.
+/// * `sampler` - How the workers order the rows of their partition on every epoch, `"sequential"`, `"shuffle"`, `"weighted_by_class"` to draw them weighted by the inverse frequency of their class within the partition or `"global_order"` to deal every batch of a seeded order of the whole dataset between them. Defaults to `"shuffle"`.
+/// * `label_col` - The column of the labels holding the class of every row, for the `"weighted_by_class"` sampler. Defaults to `0`.
+/// * `num_classes` - The amount of classes, required by the `"weighted_by_class"` sampler. Defaults to `None`.
+/// * `lr_schedule` - Optional learning rate schedule followed by the parameter servers, such as `WarmRestarts(t_0=...)`. Defaults to a constant learning rate.
+/// * `lr_scaling` - How the servers' learning rate scales with the amount of workers, `"none"`, `"linear"` or `"sqrt_linear"`. Defaults to `"none"`.
+/// * `require_compression` - If set, training fails when a peer lacks the serializer's compression instead of falling back to dense gradients. Defaults to `False`.
+/// * `monitor_every_epochs` - If set, the early stopping criteria is checked every this many epochs instead of after each one. Defaults to `None`.
+/// * `monitor_every_steps` - If set, the early stopping criteria is checked every this many optimization steps, at the end of the sync round they fall in. Defaults to `None`.
+/// * `clip_groups` - A list of `((start, end), max_norm)` ranges of the model's parameters, aligned to it's layers, whose gradient is clipped to it's own maximum norm. Defaults to none.
+/// * `ema_decay` - If set, the servers keep an exponential moving average of the weights with this decay, available in the training report. Defaults to `None`.
+/// * `server_momentum` - If set, the servers step over an exponential moving average of the gradients with this decay instead of the raw ones. Defaults to `None`.
+/// * `track_staleness` - If set, the servers log how stale every applied gradient is and report a summary once the training ends, requires the `non_blocking` synchronizer. Defaults to `False`.
+/// * `metric_barrier_every` - If set, the workers of the `non_blocking` servers wait for each other every that many epochs, so the losses they report for the next one are computed over the same parameters. Defaults to `None`.
+/// * `accum_f64` - If set, the servers add the gradients up in `f64` before applying them, so many small gradients aren't rounded away. Only the `BlockingStore` accumulates them. Defaults to `False`.
+/// * `accum_f64_layers` - The indices of the layers whose gradients the servers add up in `f64`, the rest are added up in `f32`. Can't be combined with `accum_f64`. Defaults to none.
+/// * `bias_only` - If set, only the biases of the dense and conv layers are trained, the servers keep every other parameter as it was initialized. Defaults to `False`.
+/// * `server_syncs` - A synchronization strategy per server, in order, overriding `sync` so every server can pick it's own. Defaults to none.
+/// * `learning_curve_path` - If set, a CSV with the metrics of every epoch is written to this path once the training stops, the metrics the nodes don't report are left empty. Defaults to `None`.
+/// * `event_feed_path` - If set, the training events are published as newline delimited JSON to a Unix domain socket bound at this path. Defaults to `None`.
+/// * `latency_warmup_rounds` - If set, the workers measure how long every server takes to answer over this many rounds and then send their requests to the slowest ones first. Defaults to `None`.
 ///
 /// # Returns
 /// A `PyTrainingConfig` ready to be passed to `orchestrate(...)`.
@@ -216,6 +493,40 @@ pub fn all_reduce(
     offline_epochs = 0,
     seed = None,
     early_stopping_tolerance = None,
+    early_stopping_min_epochs = 0,
+    divergence_policy = "none",
+    max_lr_reductions = 3,
+    quantize_params = false,
+    auth_token = None,
+    allow_worker_loss = false,
+    min_workers = None,
+    max_workers = None,
+    max_frame_size = None,
+    chunk_size = None,
+    slow_send_ms = None,
+    max_steps = None,
+    max_wall_time_secs = None,
+    final_step = "apply_partial",
+    sampler = "shuffle",
+    label_col = 0,
+    num_classes = None,
+    lr_schedule = None,
+    lr_scaling = "none",
+    require_compression = false,
+    monitor_every_epochs = None,
+    monitor_every_steps = None,
+    clip_groups = Vec::new(),
+    ema_decay = None,
+    server_momentum = None,
+    track_staleness = false,
+    metric_barrier_every = None,
+    accum_f64 = false,
+    accum_f64_layers = Vec::new(),
+    bias_only = false,
+    server_syncs = Vec::new(),
+    learning_curve_path = None,
+    event_feed_path = None,
+    latency_warmup_rounds = None,
 ))]
 pub fn strategy_switch(
     addrs: Vec<String>,
@@ -231,6 +542,40 @@ pub fn strategy_switch(
     offline_epochs: usize,
     seed: Option<u64>,
     early_stopping_tolerance: Option<f64>,
+    early_stopping_min_epochs: usize,
+    divergence_policy: &str,
+    max_lr_reductions: usize,
+    quantize_params: bool,
+    auth_token: Option<String>,
+    allow_worker_loss: bool,
+    min_workers: Option<usize>,
+    max_workers: Option<usize>,
+    max_frame_size: Option<usize>,
+    chunk_size: Option<usize>,
+    slow_send_ms: Option<u64>,
+    max_steps: Option<usize>,
+    max_wall_time_secs: Option<u64>,
+    final_step: &str,
+    sampler: &str,
+    label_col: usize,
+    num_classes: Option<usize>,
+    lr_schedule: Option<&Bound<'_, PyAny>>,
+    lr_scaling: &str,
+    require_compression: bool,
+    monitor_every_epochs: Option<usize>,
+    monitor_every_steps: Option<usize>,
+    clip_groups: Vec<((usize, usize), f32)>,
+    ema_decay: Option<f32>,
+    server_momentum: Option<f32>,
+    track_staleness: bool,
+    metric_barrier_every: Option<usize>,
+    accum_f64: bool,
+    accum_f64_layers: Vec<usize>,
+    bias_only: bool,
+    server_syncs: Vec<Bound<'_, PyAny>>,
+    learning_curve_path: Option<PathBuf>,
+    event_feed_path: Option<PathBuf>,
+    latency_warmup_rounds: Option<usize>,
 ) -> PyResult<PyTrainingConfig> {
     let nservers_nz = parse_nonzero(nservers, "nservers")?;
     let max_epochs_nz = parse_nonzero(max_epochs, "max_epochs")?;
@@ -246,6 +591,7 @@ pub fn strategy_switch(
                 store: extract_store(store)?,
             },
             serializer: extract_serializer(serializer)?,
+            quantization: extract_quantization(quantize_params),
             dataset: extract_dataset(dataset)?,
             optimizer: extract_optimizer(optimizer)?,
             loss_fn: extract_loss_fn(loss_fn)?,
@@ -253,7 +599,52 @@ pub fn strategy_switch(
             max_epochs: max_epochs_nz,
             offline_epochs,
             seed,
-            early_stopping: extract_early_stopping(early_stopping_tolerance)?,
+            early_stopping: extract_early_stopping(
+                early_stopping_tolerance,
+                early_stopping_min_epochs,
+            )?,
+            divergence_policy: extract_divergence_policy(divergence_policy, max_lr_reductions)?,
+            monitor_interval: extract_monitor_interval(monitor_every_epochs, monitor_every_steps)?,
+            auth_token,
+            allow_worker_loss,
+            min_workers,
+            max_workers,
+            max_frame_size,
+            chunk_size: chunk_size
+                .map(|n| parse_nonzero(n, "chunk_size"))
+                .transpose()?,
+            slow_send_ms,
+            reduction: extract_reduction(sync),
+            barrier_timeout: None,
+            update_rule: UpdateRuleConfig::default(),
+            strategy: StrategyConfig::default(),
+            empty_shard: EmptyShardConfig::default(),
+            stop_condition: extract_stop_condition(max_steps, max_wall_time_secs)?,
+            final_step: extract_final_step(final_step)?,
+            sampler: extract_sampler(sampler, label_col, num_classes)?,
+            lr_schedule: extract_lr_schedule(lr_schedule)?,
+            lr_scaling: extract_lr_scaling(lr_scaling)?,
+            ema_decay: extract_ema_decay(ema_decay)?,
+            server_momentum: extract_server_momentum(server_momentum)?,
+            track_staleness,
+            metric_barrier_every: metric_barrier_every
+                .map(|n| parse_nonzero(n, "metric_barrier_every"))
+                .transpose()?,
+            accum_f64,
+            accum_f64_layers,
+            bias_only,
+            server_synchronizers: server_syncs
+                .iter()
+                .map(extract_synchronizer)
+                .collect::<PyResult<_>>()?,
+            require_compression,
+            clip_groups: extract_clip_groups(clip_groups)?,
+            learning_curve_path,
+            snapshot_every: None,
+            event_feed_path,
+            latency_warmup_rounds: latency_warmup_rounds
+                .map(|n| parse_nonzero(n, "latency_warmup_rounds"))
+                .transpose()?,
         },
         max_epochs,
         worker_count,
@@ -291,10 +682,12 @@ pub fn orchestrate(
         .map_err(PyRuntimeError::new_err)?;
 
     let (cancel, cancel_rx) = CancelHandle::pair();
+    let lr_handle = session.lr_handle();
 
     Ok(Session {
         inner: Some((session, cancel_rx)),
         cancel,
+        lr_handle,
         max_epochs,
         worker_count,
     })
@@ -0,0 +1,65 @@
+use std::{env, fs, num::NonZeroUsize, path::Path, thread};
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use orchestrator::dataset_format::{DatasetFormat, convert_to_binary_with_threads};
+
+/// The amount of rows of the synthetic dataset.
+const NROWS: usize = 200_000;
+
+/// The amount of columns of the synthetic dataset.
+const NCOLS: usize = 32;
+
+/// Writes a synthetic csv dataset with a header.
+fn write_dataset(path: &Path) {
+    let mut contents = (0..NCOLS)
+        .map(|j| format!("c{j}"))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    for i in 0..NROWS {
+        contents.push('\n');
+
+        let row: Vec<_> = (0..NCOLS)
+            .map(|j| format!("{:.6}", ((i * NCOLS + j) as f32).sin()))
+            .collect();
+
+        contents.push_str(&row.join(","));
+    }
+
+    fs::write(path, contents).unwrap();
+}
+
+fn bench_convert_to_binary(c: &mut Criterion) {
+    let src = env::temp_dir().join("orchestrator_bench_dataset.csv");
+    let out = src.with_extension("bin");
+    write_dataset(&src);
+
+    let parallelism = thread::available_parallelism().unwrap_or(NonZeroUsize::MIN);
+    let mut nthreads = vec![NonZeroUsize::MIN];
+
+    if parallelism > NonZeroUsize::MIN {
+        nthreads.push(parallelism);
+    }
+
+    let mut group = c.benchmark_group("convert_to_binary");
+    group.sample_size(10);
+
+    for nthreads in nthreads {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(nthreads),
+            &nthreads,
+            |b, &nthreads| {
+                b.iter(|| {
+                    convert_to_binary_with_threads(&src, DatasetFormat::Csv, nthreads).unwrap();
+                    fs::remove_file(&out).unwrap();
+                })
+            },
+        );
+    }
+
+    group.finish();
+    fs::remove_file(src).unwrap();
+}
+
+criterion_group!(benches, bench_convert_to_binary);
+criterion_main!(benches);
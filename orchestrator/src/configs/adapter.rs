@@ -4,30 +4,48 @@ use std::{
     fs,
     net::ToSocketAddrs,
     num::NonZeroUsize,
+    ops::Range,
     path::PathBuf,
+    time::Duration,
 };
 
-use comms::specs::{
-    machine_learning::{
-        ActFnSpec, DatasetSpec, DistributionSpec, LayerSpec, LossFnSpec, OptimizerSpec,
-        ParamGenSpec, TrainerSpec,
+use comms::{
+    LocalListener,
+    floats::FloatPositive,
+    specs::{
+        machine_learning::{
+            ActFnSpec, ClipGroupSpec, DatasetSpec, DistributionSpec, FinalStepPolicy, HeadSpec,
+            LayerSpec, LossFnSpec, LrScheduleSpec, OptimizerSpec, ParamGenSpec, SamplerSpec,
+            StopCondition, StrategySpec, SyntheticSpec, TrainerSpec,
+        },
+        node::StatResponse,
+        server::{
+            BarrierTimeout, QuantizationSpec, Reduction, ServerSpec, StoreSpec, SynchronizerSpec,
+            TimeoutPolicy, UpdateRule,
+        },
+        worker::{AlgorithmSpec, SerializerSpec, WorkerSpec},
     },
-    node::StatResponse,
-    server::{ServerSpec, StoreSpec, SynchronizerSpec},
-    worker::{AlgorithmSpec, SerializerSpec, WorkerSpec},
 };
+use log::{info, warn};
+use machine_learning::{arch::layers::build_custom_layer, optimization::WarmRestarts, rng};
 use uuid::Uuid;
 
 use super::{ModelConfig, Partition, SerializerConfig, ServerAdapt, TrainingConfig, WorkerAdapt};
 use crate::{
     calculator::{Calculator, RoleAssignment},
     configs::{
-        ActFnConfig, AlgorithmConfig, DataSrc, DatasetConfig, LayerConfig, LossFnConfig,
-        OptimizerConfig, OrchAdapt, ParamGenConfig, StoreConfig, StrategySwitchTracking,
-        SynchronizerConfig, WorkerPostAction,
+        ActFnConfig, AlgorithmConfig, BarrierTimeoutConfig, ClipGroupConfig, DataSrc,
+        DatasetConfig, DivergencePolicyConfig, EmptyShardConfig, FinalStepPolicyConfig,
+        LayerConfig, LossFnConfig, LrScalingConfig, LrScheduleConfig, MonitorIntervalConfig,
+        OptimizerConfig, OrchAdapt, ParamGenConfig, QuantizationConfig, ReductionConfig,
+        SamplerConfig, StopConditionConfig, StoreConfig, StrategyConfig, StrategySwitchTracking,
+        SynchronizerConfig, TimeoutPolicyConfig, UpdateRuleConfig, WarmStart, WorkerPostAction,
     },
     error::{OrchErr, Result},
-    sessions::{ConvergenceTracker, GreaterThanOneUsize, LossRecorder, SwitchTracker},
+    sessions::{
+        ConvergenceTracker, DivergenceTracker, GreaterThanOneUsize, LossRecorder, MonitorCadence,
+        RestartTracker, SwitchTracker,
+    },
 };
 
 /// Converts user model and training configurations into worker and server specifications.
@@ -43,6 +61,43 @@ impl Adapter {
         Self
     }
 
+    /// Lays the model's parameters out over the servers, every weighted layer lands whole
+    /// on a single server.
+    ///
+    /// # Args
+    /// * `model` - The model's architecture and initialization configuration.
+    /// * `training` - The training's configuration.
+    /// * `nservers` - The amount of servers.
+    ///
+    /// # Returns
+    /// The layers every server holds as `(layer_index, start, end)` tuples, the range of
+    /// the server's parameters taken by the model's layer at that index, in order.
+    ///
+    /// # Errors
+    /// An `OrchErr` if the model's initialization can't be adapted.
+    pub fn layer_layout(
+        &self,
+        model: &ModelConfig,
+        training: &TrainingConfig,
+        nservers: NonZeroUsize,
+    ) -> Result<Vec<Vec<(usize, usize, usize)>>> {
+        let (_, _, _, layer_offsets, _) = self.adapt_param_gens(model, training, nservers.get())?;
+        let mut layout = vec![Vec::new(); nservers.get()];
+
+        // The layers without parameters aren't held by any server.
+        for (layer, (server, start, end)) in layer_offsets.into_iter().enumerate() {
+            if start < end {
+                layout[server].push((layer, start, end));
+            }
+        }
+
+        for layers in &mut layout {
+            layers.sort_unstable_by_key(|&(_, start, _)| start);
+        }
+
+        Ok(layout)
+    }
+
     /// Adapts both `ModelConfig` and `TrainingConfig` into `WorkerSpec`, `ServerSpec` and their network addresses.
     ///
     /// # Args
@@ -64,9 +119,12 @@ impl Adapter {
         stats: BTreeMap<String, Vec<StatResponse>>,
         addr_ids: HashMap<String, Uuid>,
     ) -> Result<(OrchAdapt, Vec<WorkerAdapt<'a>>, Vec<ServerAdapt>)> {
+        self.check_unique_addrs(&training.addrs, &[])?;
+        self.check_clip_groups(&self.layer_sizes(&model, training), &training.clip_groups)?;
+        self.warn_idle_servers(&model, training);
         let calculator = Calculator::new(stats);
 
-        let (orch, workers, servers) = match training.algorithm {
+        let (mut orch, workers, servers) = match training.algorithm {
             AlgorithmConfig::ParameterServer {
                 nservers,
                 synchronizer,
@@ -77,6 +135,8 @@ impl Adapter {
                     worker_addrs,
                 } = calculator.node_role_assignment(nservers.get());
 
+                self.check_unique_addrs(&worker_addrs, &server_addrs)?;
+
                 let orch = self.adapt_non_strategy_switch_orch(
                     &model,
                     training,
@@ -93,8 +153,7 @@ impl Adapter {
                     store,
                 )?;
 
-                let partitions =
-                    self.adapt_dataset_partitions(&training.dataset, worker_addrs.len() as u64)?;
+                let partitions = self.adapt_worker_partitions(training, worker_addrs.len())?;
 
                 let workers = self.adapt_parameter_server_workers(
                     &model,
@@ -112,8 +171,7 @@ impl Adapter {
                 let worker_addrs = calculator.worker_cycle();
                 let orch = self.adapt_non_strategy_switch_orch(&model, training, &[], &addr_ids)?;
 
-                let partitions =
-                    self.adapt_dataset_partitions(&training.dataset, worker_addrs.len() as u64)?;
+                let partitions = self.adapt_worker_partitions(training, worker_addrs.len())?;
 
                 let workers =
                     self.adapt_all_reduce_workers(&model, training, worker_addrs, partitions)?;
@@ -139,8 +197,7 @@ impl Adapter {
                     store,
                 )?;
 
-                let partitions =
-                    self.adapt_dataset_partitions(&training.dataset, training.addrs.len() as u64)?;
+                let partitions = self.adapt_worker_partitions(training, training.addrs.len())?;
 
                 let workers =
                     self.adapt_strategy_switch_workers(&model, training, worker_addrs, partitions)?;
@@ -149,9 +206,123 @@ impl Adapter {
             }
         };
 
+        orch.monitor_cadence = self.adapt_monitor_cadence(training, &workers);
         Ok((orch, workers, servers))
     }
 
+    /// Checks that no address is listed twice, neither within a role nor across them.
+    ///
+    /// A repeated address would get connected to twice, throwing off the amount of
+    /// workers the servers and the barriers wait for.
+    ///
+    /// # Args
+    /// * `worker_addrs` - The addresses of the workers.
+    /// * `server_addrs` - The addresses of the servers.
+    ///
+    /// # Errors
+    /// An `InvalidConfig` error naming the first repeated address.
+    fn check_unique_addrs(&self, worker_addrs: &[String], server_addrs: &[String]) -> Result<()> {
+        let mut roles = HashMap::new();
+        let addrs = server_addrs
+            .iter()
+            .map(|addr| (addr, "server"))
+            .chain(worker_addrs.iter().map(|addr| (addr, "worker")));
+
+        for (addr, role) in addrs {
+            let Some(first) = roles.insert(addr, role) else {
+                continue;
+            };
+
+            let text = if first == role {
+                format!("the address {addr} is listed more than once as a {role}")
+            } else {
+                format!("the address {addr} is listed both as a {first} and a {role}")
+            };
+
+            return Err(OrchErr::InvalidConfig(text));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the clip groups are aligned to the layers and don't overlap.
+    ///
+    /// # Args
+    /// * `layer_sizes` - The amount of parameters of each of the model's layers.
+    /// * `clip_groups` - The clip groups of the training.
+    ///
+    /// # Errors
+    /// An `InvalidConfig` error naming the first offending group.
+    fn check_clip_groups(
+        &self,
+        layer_sizes: &[usize],
+        clip_groups: &[ClipGroupConfig],
+    ) -> Result<()> {
+        let boundaries: HashSet<_> = layer_sizes
+            .iter()
+            .scan(0, |offset, size| {
+                *offset += size;
+                Some(*offset)
+            })
+            .chain([0])
+            .collect();
+
+        let mut ranges: Vec<_> = clip_groups
+            .iter()
+            .map(|group| (group.start, group.end))
+            .collect();
+
+        for &(start, end) in &ranges {
+            if start >= end {
+                let text = format!("the clip group {start}..{end} holds no parameters");
+                return Err(OrchErr::InvalidConfig(text));
+            }
+
+            if !boundaries.contains(&start) || !boundaries.contains(&end) {
+                let text =
+                    format!("the clip group {start}..{end} isn't aligned to the model's layers");
+                return Err(OrchErr::InvalidConfig(text));
+            }
+        }
+
+        ranges.sort_unstable();
+
+        for pair in ranges.windows(2) {
+            let ((start, end), (next_start, next_end)) = (pair[0], pair[1]);
+
+            if next_start < end {
+                let text =
+                    format!("the clip groups {start}..{end} and {next_start}..{next_end} overlap");
+                return Err(OrchErr::InvalidConfig(text));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Warns when there are more servers than layers to distribute between them.
+    ///
+    /// Layers aren't split between servers, so the extra ones are left without parameters.
+    ///
+    /// # Args
+    /// * `model` - The model's configuration.
+    /// * `training` - The training's configuration.
+    fn warn_idle_servers(&self, model: &ModelConfig, training: &TrainingConfig) {
+        let (AlgorithmConfig::ParameterServer { nservers, .. }
+        | AlgorithmConfig::StrategySwitch { nservers, .. }) = training.algorithm
+        else {
+            return;
+        };
+
+        let nlayers = model.all_layers().count();
+
+        if nservers.get() > nlayers {
+            warn!(
+                "{nservers} servers were requested for a model of {nlayers} layers, some will hold no parameters"
+            );
+        }
+    }
+
     /// Adapts both `ModelConfig` and `TrainingConfig` into a `WorkerAdapt`.
     ///
     /// # Args
@@ -171,16 +342,20 @@ impl Adapter {
     ) -> Result<OrchAdapt> {
         let convergence_tracker = training.early_stopping.map(|cfg| {
             let winsize = GreaterThanOneUsize::new(3).unwrap();
-            ConvergenceTracker::new(winsize, cfg.tolerance)
+            ConvergenceTracker::new(winsize, cfg.tolerance, cfg.min_epochs)
         });
 
-        let layer_offsets =
+        let (layer_offsets, restart_tracker, nservers) =
             if let AlgorithmConfig::ParameterServer { nservers, .. } = training.algorithm {
-                self.adapt_param_gens(model, training, nservers.get())?.3
+                let layer_offsets = self.adapt_param_gens(model, training, nservers.get())?.3;
+                let restart_tracker = self.adapt_restart_tracker(training);
+                (layer_offsets, restart_tracker, nservers.get())
             } else {
-                Vec::new()
+                (Vec::new(), None, 0)
             };
 
+        let divergence_tracker = self.adapt_divergence_tracker(training, nservers);
+
         let layer_offsets: Vec<_> = layer_offsets
             .into_iter()
             .map(|(i, start, end)| (addr_ids[&server_addrs[i]], start, end))
@@ -190,10 +365,21 @@ impl Adapter {
             input_size: training.dataset.x_size,
             loss_recorder: LossRecorder::new(),
             convergence_tracker,
+            divergence_tracker,
+            monitor_cadence: MonitorCadence::default(),
+            restart_tracker,
             switch_tracking: None,
             model_config: model.clone(),
             algorithm_config: training.algorithm.clone(),
             layer_param_offsets: layer_offsets,
+            allow_worker_loss: training.allow_worker_loss,
+            ema: training.ema_decay.is_some(),
+            track_staleness: training.track_staleness,
+            learning_curve_path: training.learning_curve_path.clone(),
+            snapshot_every: training.snapshot_every,
+            event_feed_path: training.event_feed_path.clone(),
+            test_set: None,
+            seed: training.seed,
         };
 
         Ok(adapt)
@@ -223,7 +409,7 @@ impl Adapter {
     ) -> Result<OrchAdapt> {
         let convergence_tracker = training.early_stopping.map(|cfg| {
             let winsize = GreaterThanOneUsize::new(3).unwrap();
-            ConvergenceTracker::new(winsize, cfg.tolerance)
+            ConvergenceTracker::new(winsize, cfg.tolerance, cfg.min_epochs)
         });
 
         // SAFETY: The winsize is greater than `1`.
@@ -231,19 +417,23 @@ impl Adapter {
         let tracker = SwitchTracker::new(winsize, 0.01);
 
         let nservers = server_addrs.len();
-        let nworkers = training.addrs.len() - nservers;
 
         let trainer_spec = self.adapt_trainer(model, training);
         let (_, _, _, _, param_ranges) = self.adapt_param_gens(model, training, nservers)?;
         let (servers, server_sizes, server_ordering, layer_offsets) =
             self.adapt_servers(model, training, server_addrs, addr_ids, synchronizer, store)?;
 
-        let mut switches = (0..nworkers).map(|_| WorkerPostAction::Switch {
+        let switch = |worker_id| WorkerPostAction::Switch {
             server_addrs: server_addrs.to_vec(),
             server_sizes: server_sizes.clone(),
             server_ordering: server_ordering.clone(),
-            trainer_spec: trainer_spec.clone(),
-        });
+            trainer_spec: self.adapt_worker_trainer(
+                &trainer_spec,
+                training,
+                worker_id,
+                worker_addrs.len(),
+            ),
+        };
 
         let mut upgrades = servers
             .into_iter()
@@ -253,11 +443,12 @@ impl Adapter {
         let server_addr_set: HashSet<_> = server_addrs.iter().collect();
         let post_actions = worker_addrs
             .iter()
-            .map(|addr| {
+            .enumerate()
+            .map(|(i, addr)| {
                 if server_addr_set.contains(addr) {
                     upgrades.next()
                 } else {
-                    switches.next()
+                    Some(switch(i))
                 }
             })
             .collect::<Option<Vec<_>>>()
@@ -274,10 +465,21 @@ impl Adapter {
             input_size: training.dataset.x_size,
             loss_recorder: LossRecorder::new(),
             convergence_tracker,
+            divergence_tracker: self.adapt_divergence_tracker(training, nservers),
+            monitor_cadence: MonitorCadence::default(),
+            restart_tracker: None,
             switch_tracking: Some(tracking),
             model_config: model.clone(),
             algorithm_config: training.algorithm.clone(),
             layer_param_offsets: layer_offsets,
+            allow_worker_loss: training.allow_worker_loss,
+            ema: training.ema_decay.is_some(),
+            track_staleness: training.track_staleness,
+            learning_curve_path: training.learning_curve_path.clone(),
+            snapshot_every: training.snapshot_every,
+            event_feed_path: training.event_feed_path.clone(),
+            test_set: None,
+            seed: training.seed,
         };
 
         Ok(adapt)
@@ -320,17 +522,27 @@ impl Adapter {
         let workers = worker_addrs
             .iter()
             .zip(partitions)
-            .map(|(addr, partition)| {
-                if let Err(..) | Ok(None) = addr.to_socket_addrs().map(|mut addrs| addrs.next()) {
+            .enumerate()
+            .map(|(i, (addr, partition))| {
+                if !resolves(addr) {
                     let text = format!("failed to resolve worker network address: {addr}");
                     return Err(OrchErr::InvalidConfig(text));
                 }
 
                 let worker_spec = WorkerSpec {
-                    trainer: trainer_spec.clone(),
+                    trainer: self.adapt_worker_trainer(
+                        &trainer_spec,
+                        training,
+                        i,
+                        worker_addrs.len(),
+                    ),
                     algorithm: algorithm_spec.clone(),
                     serializer: serializer_spec,
                     seed: training.seed,
+                    max_frame_size: training.max_frame_size,
+                    require_compression: training.require_compression,
+                    latency_warmup_rounds: training.latency_warmup_rounds,
+                    synthetic: partition.synthetic_spec(),
                 };
 
                 let worker_adapt = WorkerAdapt {
@@ -367,7 +579,7 @@ impl Adapter {
         partitions: Vec<Partition<'a>>,
     ) -> Result<Vec<WorkerAdapt<'a>>> {
         let trainer_spec = self.adapt_trainer(model, training);
-        let (_, param_gen_opts) = self.adapt_layers(model, training.dataset.x_size);
+        let param_gen_opts = self.adapt_inits(model, training.dataset.x_size)?;
 
         let weighted_layers = param_gen_opts.iter().filter(|opt| opt.is_some()).count();
         let param_gen_specs = param_gen_opts.into_iter().flatten().collect();
@@ -387,16 +599,25 @@ impl Adapter {
             .zip(partitions)
             .enumerate()
             .map(|(i, (addr, partition))| {
-                if let Err(..) | Ok(None) = addr.to_socket_addrs().map(|mut addrs| addrs.next()) {
+                if !resolves(addr) {
                     let text = format!("failed to resolve worker network address: {addr}");
                     return Err(OrchErr::InvalidConfig(text));
                 }
 
                 let worker_spec = WorkerSpec {
-                    trainer: trainer_spec.clone(),
+                    trainer: self.adapt_worker_trainer(
+                        &trainer_spec,
+                        training,
+                        i,
+                        worker_addrs.len(),
+                    ),
                     algorithm: algorithm_spec_factory.clone()(i),
                     serializer: serializer_spec,
                     seed: training.seed,
+                    max_frame_size: training.max_frame_size,
+                    require_compression: training.require_compression,
+                    latency_warmup_rounds: training.latency_warmup_rounds,
+                    synthetic: partition.synthetic_spec(),
                 };
 
                 let worker_adapt = WorkerAdapt {
@@ -435,7 +656,7 @@ impl Adapter {
         let serializer_spec = self.adapt_serializer(training);
 
         let trainer_spec = self.adapt_trainer(model, training);
-        let (_, param_gen_opts) = self.adapt_layers(model, training.dataset.x_size);
+        let param_gen_opts = self.adapt_inits(model, training.dataset.x_size)?;
 
         let param_gen_specs = param_gen_opts.into_iter().flatten().collect();
 
@@ -445,7 +666,7 @@ impl Adapter {
             param_gen: ParamGenSpec::Chained {
                 specs: param_gen_specs,
             },
-            amount_of_layers: model.layers.len(),
+            amount_of_layers: model.all_layers().count(),
         };
 
         let workers = worker_addrs
@@ -453,16 +674,25 @@ impl Adapter {
             .zip(partitions)
             .enumerate()
             .map(|(i, (addr, partition))| {
-                if let Err(..) | Ok(None) = addr.to_socket_addrs().map(|mut addrs| addrs.next()) {
+                if !resolves(addr) {
                     let text = format!("failed to resolve worker network address: {addr}");
                     return Err(OrchErr::InvalidConfig(text));
                 }
 
                 let worker_spec = WorkerSpec {
-                    trainer: trainer_spec.clone(),
+                    trainer: self.adapt_worker_trainer(
+                        &trainer_spec,
+                        training,
+                        i,
+                        worker_addrs.len(),
+                    ),
                     algorithm: algorithm_spec_factory.clone()(i),
                     serializer: serializer_spec,
                     seed: training.seed,
+                    max_frame_size: training.max_frame_size,
+                    require_compression: training.require_compression,
+                    latency_warmup_rounds: training.latency_warmup_rounds,
+                    synthetic: partition.synthetic_spec(),
                 };
 
                 let worker_adapt = WorkerAdapt {
@@ -489,6 +719,7 @@ impl Adapter {
         match training.serializer {
             SerializerConfig::Base => SerializerSpec::Base,
             SerializerConfig::SparseCapable { r } => SerializerSpec::SparseCapable { r },
+            SerializerConfig::TopK { density } => SerializerSpec::TopK { density },
         }
     }
 
@@ -498,7 +729,7 @@ impl Adapter {
     /// * `model` - The model's architecture and initialization configuration.
     /// * `training` - The training's configuration.
     /// * `server_addrs` - The server' network addresses.
-    /// * `synchronizer` - The synchronizer's configuration.
+    /// * `synchronizer` - The synchronizer's configuration, unless the server's own is set.
     /// * `store` - The store's configuration.
     ///
     /// # Returns
@@ -526,13 +757,21 @@ impl Adapter {
 
         let nservers = server_addrs.len();
         let nworkers = training.addrs.len() - nservers;
+        let optimizer = self.adapt_server_optimizer(training, nworkers);
+        let mut frozen = self
+            .frozen_ranges(model, &layer_offsets, nservers, training.bias_only)
+            .into_iter();
+        let mut accum_f64_ranges = self
+            .accum_f64_ranges(&layer_offsets, nservers, &training.accum_f64_layers)
+            .into_iter();
 
         let (servers, server_sizes): (Vec<_>, Vec<_>) = server_addrs
             .iter()
             .zip(param_gens)
             .zip(server_sizes)
-            .map(|((addr, param_gen_spec), size)| {
-                if let Err(..) | Ok(None) = addr.to_socket_addrs().map(|mut addrs| addrs.next()) {
+            .enumerate()
+            .map(|(i, ((addr, param_gen_spec), size))| {
+                if !resolves(addr) {
                     let text = format!("failed to resolve server network address: {addr}");
                     return Err(OrchErr::InvalidConfig(text));
                 }
@@ -540,9 +779,35 @@ impl Adapter {
                 let server_spec = ServerSpec {
                     nworkers,
                     param_gen: param_gen_spec,
-                    optimizer: self.adapt_optimizer(training.optimizer),
-                    synchronizer: self.adapt_synchronizer(&synchronizer, nworkers)?,
+                    optimizer,
+                    synchronizer: self.adapt_synchronizer(
+                        training
+                            .server_synchronizers
+                            .get(i)
+                            .unwrap_or(&synchronizer),
+                        training.reduction,
+                        training.barrier_timeout,
+                        nworkers,
+                    )?,
                     store: self.adapt_store(&store),
+                    quantization: self.adapt_quantization(training.quantization),
+                    allow_worker_loss: training.allow_worker_loss,
+                    min_workers: training.min_workers,
+                    max_workers: training.max_workers,
+                    max_frame_size: training.max_frame_size,
+                    chunk_size: training.chunk_size,
+                    slow_send_ms: training.slow_send_ms,
+                    lr_schedule: self.adapt_lr_schedule(training.lr_schedule),
+                    ema_decay: training.ema_decay,
+                    server_momentum: training.server_momentum,
+                    track_staleness: training.track_staleness,
+                    metric_barrier_every: training.metric_barrier_every,
+                    accum_f64: training.accum_f64,
+                    // SAFETY: There's a list of widened ranges per server.
+                    accum_f64_ranges: accum_f64_ranges.next().unwrap(),
+                    // SAFETY: There's a list of frozen ranges per server.
+                    frozen: frozen.next().unwrap(),
+                    update_rule: self.adapt_update_rule(training.update_rule),
                     seed: training.seed,
                 };
 
@@ -565,6 +830,93 @@ impl Adapter {
         Ok((servers, server_sizes, server_ordering, layer_offsets))
     }
 
+    /// Gathers the parameters of the frozen layers held by every server.
+    ///
+    /// With `bias_only` set the weights of every layer are frozen as well, leaving only
+    /// the biases to train, the layers without known biases are frozen whole.
+    ///
+    /// # Args
+    /// * `model` - The model's architecture and initialization configuration.
+    /// * `layer_offsets` - The server and the range of parameters within it of every layer.
+    /// * `nservers` - The amount of servers.
+    /// * `bias_only` - Whether only the biases of the layers are trained.
+    ///
+    /// # Returns
+    /// The ranges of every server's parameters to leave out of the updates, adjacent
+    /// layers are merged into a single range.
+    fn frozen_ranges(
+        &self,
+        model: &ModelConfig,
+        layer_offsets: &[(usize, usize, usize)],
+        nservers: usize,
+        bias_only: bool,
+    ) -> Vec<Vec<Range<usize>>> {
+        let mut frozen: Vec<Vec<Range<usize>>> = vec![Vec::new(); nservers];
+
+        for (layer, &(server, start, end)) in model.all_layers().zip(layer_offsets) {
+            // A tied dense layer only holds it's biases, so none of it is frozen.
+            let frozen_end = match layer.biases() {
+                _ if layer.frozen() => end,
+                Some(biases) if bias_only => end.saturating_sub(biases).max(start),
+                None if bias_only => end,
+                _ => start,
+            };
+
+            if start < frozen_end {
+                frozen[server].push(start..frozen_end);
+            }
+        }
+
+        frozen.iter_mut().for_each(Self::merge_ranges);
+        frozen
+    }
+
+    /// Gathers the parameters of the layers whose gradients are added up in `f64`, held by
+    /// every server.
+    ///
+    /// # Args
+    /// * `layer_offsets` - The server and the range of parameters within it of every layer.
+    /// * `nservers` - The amount of servers.
+    /// * `layers` - The indices of the layers to add up in `f64`.
+    ///
+    /// # Returns
+    /// The ranges of every server's parameters to add up in `f64`, adjacent layers are
+    /// merged into a single range.
+    fn accum_f64_ranges(
+        &self,
+        layer_offsets: &[(usize, usize, usize)],
+        nservers: usize,
+        layers: &[usize],
+    ) -> Vec<Vec<Range<usize>>> {
+        let mut widened: Vec<Vec<Range<usize>>> = vec![Vec::new(); nservers];
+
+        for &(server, start, end) in layers.iter().filter_map(|&i| layer_offsets.get(i)) {
+            if start < end {
+                widened[server].push(start..end);
+            }
+        }
+
+        widened.iter_mut().for_each(Self::merge_ranges);
+        widened
+    }
+
+    /// Sorts the ranges of a server's parameters, merging the adjacent ones.
+    ///
+    /// # Args
+    /// * `ranges` - The ranges to merge.
+    fn merge_ranges(ranges: &mut Vec<Range<usize>>) {
+        ranges.sort_unstable_by_key(|range| range.start);
+        ranges.dedup_by(|next, prev| {
+            let adjacent = prev.end == next.start;
+
+            if adjacent {
+                prev.end = next.end;
+            }
+
+            adjacent
+        });
+    }
+
     /// Adapts the parameter generators and partitions the layers to minimize the
     /// difference in sizes between the servers.
     ///
@@ -588,7 +940,7 @@ impl Adapter {
         Vec<(usize, usize, usize)>,
         Vec<Vec<(usize, usize)>>,
     )> {
-        let (_, param_gen_opts) = self.adapt_layers(model, training.dataset.x_size);
+        let param_gen_opts = self.adapt_inits(model, training.dataset.x_size)?;
 
         let nlayers = param_gen_opts.len();
         let items: Vec<_> = param_gen_opts
@@ -660,6 +1012,8 @@ impl Adapter {
     ///
     /// # Args
     /// * `synchronizer` - A synchronizer's configuration.
+    /// * `reduction` - How a barrier combines the workers' gradients.
+    /// * `timeout` - How long a barrier waits for the workers, if at all.
     /// * `worker_amount` - The total number of workers.
     ///
     /// # Returns
@@ -667,6 +1021,8 @@ impl Adapter {
     fn adapt_synchronizer(
         &self,
         synchronizer: &SynchronizerConfig,
+        reduction: ReductionConfig,
+        timeout: Option<BarrierTimeoutConfig>,
         worker_amount: usize,
     ) -> Result<SynchronizerSpec> {
         let Some(barrier_size) = NonZeroUsize::new(worker_amount) else {
@@ -675,7 +1031,23 @@ impl Adapter {
         };
 
         let spec = match *synchronizer {
-            SynchronizerConfig::Barrier => SynchronizerSpec::Barrier { barrier_size },
+            SynchronizerConfig::Barrier => SynchronizerSpec::Barrier {
+                barrier_size,
+                reduction: match reduction {
+                    ReductionConfig::Mean => Reduction::Mean,
+                    ReductionConfig::Sum => Reduction::Sum,
+                },
+                timeout: timeout.map(|timeout| BarrierTimeout {
+                    after: Duration::from_millis(timeout.after_ms.get()),
+                    policy: match timeout.policy {
+                        TimeoutPolicyConfig::Fail => TimeoutPolicy::Fail,
+                        TimeoutPolicyConfig::RetryOnce { after_ms } => {
+                            TimeoutPolicy::RetryOnce(Duration::from_millis(after_ms.get()))
+                        }
+                        TimeoutPolicyConfig::DropLaggard => TimeoutPolicy::DropLaggard,
+                    },
+                }),
+            },
             SynchronizerConfig::NonBlocking => SynchronizerSpec::NonBlocking,
         };
 
@@ -696,6 +1068,34 @@ impl Adapter {
         }
     }
 
+    /// Adapts a `QuantizationConfig` into a `QuantizationSpec`.
+    ///
+    /// # Args
+    /// * `quantization` - The parameters' quantization configuration.
+    ///
+    /// # Returns
+    /// The quantization's specification.
+    fn adapt_quantization(&self, quantization: QuantizationConfig) -> QuantizationSpec {
+        match quantization {
+            QuantizationConfig::None => QuantizationSpec::None,
+            QuantizationConfig::Int8 => QuantizationSpec::Int8,
+        }
+    }
+
+    /// Adapts an `UpdateRuleConfig` into an `UpdateRule`.
+    ///
+    /// # Args
+    /// * `update_rule` - The servers' update rule configuration.
+    ///
+    /// # Returns
+    /// The update rule.
+    fn adapt_update_rule(&self, update_rule: UpdateRuleConfig) -> UpdateRule {
+        match update_rule {
+            UpdateRuleConfig::UpdateThenSend => UpdateRule::UpdateThenSend,
+            UpdateRuleConfig::SendThenUpdate => UpdateRule::SendThenUpdate,
+        }
+    }
+
     /// Adapts a `ModelConfig` and a `TrainingConfig` into a `TrainerSpec`.
     ///
     /// # Args
@@ -706,6 +1106,7 @@ impl Adapter {
     /// The trainer's specification.
     fn adapt_trainer(&self, model: &ModelConfig, training: &TrainingConfig) -> TrainerSpec {
         let (layers, _) = self.adapt_layers(model, training.dataset.x_size);
+        let heads = self.adapt_heads(model, training.dataset.x_size);
         let loss_fn_spec = self.adapt_loss_fn(training.loss_fn);
         let dataset_spec = self.adapt_dataset(&training.dataset);
         let optimizer_spec =
@@ -721,9 +1122,248 @@ impl Adapter {
             dataset: dataset_spec,
             loss_fn: loss_fn_spec,
             offline_epochs: training.offline_epochs,
-            max_epochs: training.max_epochs,
+            stop_condition: self.adapt_stop_condition(training),
+            final_step: self.adapt_final_step(training.final_step),
             batch_size: training.batch_size,
+            sampler: self.adapt_sampler(training.sampler),
             seed: training.seed,
+            augmentation_seed: None,
+            clip_groups: training
+                .clip_groups
+                .iter()
+                .map(|group| ClipGroupSpec {
+                    start: group.start,
+                    end: group.end,
+                    max_norm: group.max_norm,
+                })
+                .collect(),
+            heads,
+            checkpoint_every: model.checkpoint_every,
+            resume: None,
+            strategy: self.adapt_strategy(training.strategy),
+        }
+    }
+
+    /// Gives a worker it's own copy of the trainer's specification.
+    ///
+    /// # Args
+    /// * `trainer_spec` - The trainer's specification shared by every worker.
+    /// * `training` - The training's configuration.
+    /// * `worker_id` - The position of the worker in the training.
+    /// * `nworkers` - The amount of workers training.
+    ///
+    /// # Returns
+    /// The trainer's specification with the worker's augmentation seed and it's position
+    /// in a global order sampler.
+    fn adapt_worker_trainer(
+        &self,
+        trainer_spec: &TrainerSpec,
+        training: &TrainingConfig,
+        worker_id: usize,
+        nworkers: usize,
+    ) -> TrainerSpec {
+        let sampler = match trainer_spec.sampler {
+            SamplerSpec::GlobalOrder { .. } => SamplerSpec::GlobalOrder {
+                worker: worker_id,
+                nworkers: NonZeroUsize::new(nworkers).unwrap_or(NonZeroUsize::MIN),
+            },
+            sampler => sampler,
+        };
+
+        TrainerSpec {
+            augmentation_seed: training.seed.map(|seed| rng::worker_seed(seed, worker_id)),
+            sampler,
+            ..trainer_spec.clone()
+        }
+    }
+
+    /// Adapts a `StrategyConfig` into a `StrategySpec`.
+    ///
+    /// # Args
+    /// * `strategy` - The strategy's configuration.
+    ///
+    /// # Returns
+    /// The strategy's specification.
+    fn adapt_strategy(&self, strategy: StrategyConfig) -> StrategySpec {
+        match strategy {
+            StrategyConfig::Backprop => StrategySpec::Backprop,
+            StrategyConfig::ClosedFormLinReg => StrategySpec::ClosedFormLinReg,
+        }
+    }
+
+    /// Calculates the amount of parameters of each of the model's layers.
+    ///
+    /// # Args
+    /// * `model` - The model's configuration.
+    /// * `training` - The training's configuration.
+    ///
+    /// # Returns
+    /// The size of every layer, in order.
+    fn layer_sizes(&self, model: &ModelConfig, training: &TrainingConfig) -> Vec<usize> {
+        let (_, param_gens) = self.adapt_layers(model, training.dataset.x_size);
+
+        param_gens
+            .iter()
+            .map(|param_gen| param_gen.as_ref().map_or(0, ParamGenSpec::size))
+            .collect()
+    }
+
+    /// Adapts the training's stop condition into a `StopCondition`.
+    ///
+    /// # Args
+    /// * `training` - A training configuration.
+    ///
+    /// # Returns
+    /// The configured stop condition, stopping after `max_epochs` if there's none.
+    fn adapt_stop_condition(&self, training: &TrainingConfig) -> StopCondition {
+        match training.stop_condition {
+            None => StopCondition::MaxEpochs(training.max_epochs),
+            Some(StopConditionConfig::MaxEpochs(max_epochs)) => {
+                StopCondition::MaxEpochs(max_epochs)
+            }
+            Some(StopConditionConfig::MaxSteps(max_steps)) => StopCondition::MaxSteps(max_steps),
+            Some(StopConditionConfig::MaxWallTimeSecs(secs)) => {
+                StopCondition::MaxWallTime(Duration::from_secs(secs.get()))
+            }
+        }
+    }
+
+    /// Adapts a `FinalStepPolicyConfig` into a `FinalStepPolicy`.
+    ///
+    /// # Args
+    /// * `final_step` - The final step policy's configuration.
+    ///
+    /// # Returns
+    /// The final step policy.
+    fn adapt_final_step(&self, final_step: FinalStepPolicyConfig) -> FinalStepPolicy {
+        match final_step {
+            FinalStepPolicyConfig::ApplyPartial => FinalStepPolicy::ApplyPartial,
+            FinalStepPolicyConfig::DiscardPartial => FinalStepPolicy::DiscardPartial,
+        }
+    }
+
+    /// Adapts a `SamplerConfig` into a `SamplerSpec`.
+    ///
+    /// # Args
+    /// * `sampler` - The sampler's configuration.
+    ///
+    /// # Returns
+    /// The sampler's specification.
+    fn adapt_sampler(&self, sampler: SamplerConfig) -> SamplerSpec {
+        match sampler {
+            SamplerConfig::Sequential => SamplerSpec::Sequential,
+            SamplerConfig::Shuffle => SamplerSpec::Shuffle,
+            SamplerConfig::WeightedByClass {
+                label_col,
+                num_classes,
+            } => SamplerSpec::WeightedByClass {
+                label_col,
+                num_classes,
+            },
+            // The worker's position is filled in for every worker's trainer.
+            SamplerConfig::GlobalOrder => SamplerSpec::GlobalOrder {
+                worker: 0,
+                nworkers: NonZeroUsize::MIN,
+            },
+        }
+    }
+
+    /// Adapts the training's monitor interval into the cadence of the convergence checks.
+    ///
+    /// # Args
+    /// * `training` - The training's configuration.
+    /// * `workers` - The adapted workers.
+    ///
+    /// # Returns
+    /// The monitor cadence, checking after every epoch if there's no interval.
+    fn adapt_monitor_cadence(
+        &self,
+        training: &TrainingConfig,
+        workers: &[WorkerAdapt<'_>],
+    ) -> MonitorCadence {
+        match training.monitor_interval {
+            None => MonitorCadence::default(),
+            Some(MonitorIntervalConfig::Epochs(every)) => MonitorCadence::epochs(every),
+            Some(MonitorIntervalConfig::Steps(every)) => {
+                // The rounds end once the worker with the largest partition is done.
+                let x_size = training.dataset.x_size;
+                let batch_size = training.batch_size.get() as u64;
+                let steps_per_epoch = workers
+                    .iter()
+                    .map(|worker| worker.partition.nsamples(x_size).div_ceil(batch_size))
+                    .max()
+                    .unwrap_or_default();
+
+                MonitorCadence::steps(every, steps_per_epoch as usize)
+            }
+        }
+    }
+
+    /// Adapts a `LrScheduleConfig` into a `LrScheduleSpec`.
+    ///
+    /// # Args
+    /// * `lr_schedule` - The optional learning rate schedule's configuration.
+    ///
+    /// # Returns
+    /// The learning rate schedule's specification, `None` for a constant learning rate.
+    fn adapt_lr_schedule(&self, lr_schedule: Option<LrScheduleConfig>) -> Option<LrScheduleSpec> {
+        match lr_schedule? {
+            LrScheduleConfig::WarmRestarts {
+                t_0,
+                t_mult,
+                min_lr,
+            } => Some(LrScheduleSpec::WarmRestarts {
+                t_0,
+                t_mult: t_mult.unwrap_or(NonZeroUsize::MIN),
+                min_lr,
+            }),
+        }
+    }
+
+    /// Creates a tracker of the epochs the servers' learning rate schedule follows.
+    ///
+    /// # Args
+    /// * `training` - The training's configuration.
+    ///
+    /// # Returns
+    /// The restart tracker or `None` if the learning rate is constant.
+    fn adapt_restart_tracker(&self, training: &TrainingConfig) -> Option<RestartTracker> {
+        let LrScheduleSpec::WarmRestarts {
+            t_0,
+            t_mult,
+            min_lr,
+        } = self.adapt_lr_schedule(training.lr_schedule)?;
+
+        let schedule = WarmRestarts::new(t_0, t_mult, *min_lr);
+        Some(RestartTracker::new(schedule))
+    }
+
+    /// Adapts the divergence policy into the tracker of the loss' trend.
+    ///
+    /// # Args
+    /// * `training` - The training's configuration.
+    /// * `nservers` - The amount of servers.
+    ///
+    /// # Returns
+    /// The divergence tracker or `None` if the training is let diverge.
+    fn adapt_divergence_tracker(
+        &self,
+        training: &TrainingConfig,
+        nservers: usize,
+    ) -> Option<DivergenceTracker> {
+        // SAFETY: The winsize is greater than `1`.
+        let winsize = GreaterThanOneUsize::new(3).unwrap();
+
+        match training.divergence_policy {
+            DivergencePolicyConfig::None => None,
+            DivergencePolicyConfig::HalveLr { max_reductions } => {
+                let nworkers = training.addrs.len().saturating_sub(nservers);
+                let lr = self
+                    .adapt_server_optimizer(training, nworkers)
+                    .learning_rate();
+                Some(DivergenceTracker::halving(winsize, lr, max_reductions))
+            }
+            DivergencePolicyConfig::Abort => Some(DivergenceTracker::aborting(winsize)),
         }
     }
 
@@ -784,31 +1424,113 @@ impl Adapter {
             .collect()
     }
 
-    /// Helper method for `adapt_dataset` — partitions a local dataset file.
+    /// Helper method for `adapt_dataset` — partitions a CSR dataset in contiguous ranges
+    /// of rows, the workers receive them still sparse.
     ///
     /// # Args
-    /// * `samples_path` - Path to the local dataset samples file.
-    /// * `labels_path` - Path to the local dataset labels file.
-    /// * `partition_sizes` - An iterator of partition sample and label sizes in **bytes**.
-    /// * `x_size` - The number of input features per sample.
-    /// * `y_size` - The number of output values per sample.
+    /// * `indptr` - The offsets of the matrix's rows.
+    /// * `indices` - The columns of the matrix's entries.
+    /// * `values` - The values of the matrix's entries.
+    /// * `labels` - The labels of the rows.
+    /// * `y_size` - The size of a label.
+    /// * `partition_rows` - An iterator over the number of rows per partition.
     ///
     /// # Returns
-    /// A list of `Partition::Local`.
-    fn adapt_local_dataset<'a, T>(
+    /// A list of `Partition::Csr`.
+    fn adapt_csr_dataset<'a, I>(
         &self,
-        samples_path: PathBuf,
-        labels_path: PathBuf,
-        partition_sizes: T,
+        indptr: &'a [usize],
+        indices: &'a [usize],
+        values: &'a [f32],
+        mut labels: &'a [f32],
+        y_size: NonZeroUsize,
+        partition_rows: I,
     ) -> Vec<Partition<'a>>
     where
-        T: Iterator<Item = (u64, u64)>,
+        I: Iterator<Item = u64>,
     {
-        let mut samples_offset = 0;
-        let mut labels_offset = 0;
-
-        partition_sizes
-            .map(|(x_size_bytes, y_size_bytes)| {
+        let mut start = 0;
+
+        partition_rows
+            .map(|rows| {
+                let labels_curr;
+                let end = start + rows as usize;
+                let window = &indptr[start..=end];
+                let entries = window[0]..window[window.len() - 1];
+
+                (labels_curr, labels) = labels.split_at(rows as usize * y_size.get());
+                start = end;
+
+                Partition::Csr {
+                    indptr: window,
+                    indices: &indices[entries.clone()],
+                    values: &values[entries],
+                    labels: labels_curr,
+                }
+            })
+            .collect()
+    }
+
+    /// Helper method for `adapt_dataset` — partitions a synthetic dataset in contiguous
+    /// ranges of rows, each worker generates it's own.
+    ///
+    /// # Args
+    /// * `seed` - The seed of the dataset.
+    /// * `noise_std` - The standard deviation of the noise added to the labels.
+    /// * `partition_rows` - An iterator of the amount of rows of each partition.
+    ///
+    /// # Returns
+    /// A list of `Partition::Synthetic`.
+    fn adapt_synthetic_dataset<'a, I>(
+        &self,
+        seed: u64,
+        noise_std: f32,
+        partition_rows: I,
+    ) -> Vec<Partition<'a>>
+    where
+        I: Iterator<Item = u64>,
+    {
+        let mut start = 0;
+
+        partition_rows
+            .map(|rows| {
+                let synthetic = SyntheticSpec {
+                    seed,
+                    rows: start..start + rows,
+                    noise_std,
+                };
+
+                start += rows;
+                Partition::Synthetic(synthetic)
+            })
+            .collect()
+    }
+
+    /// Helper method for `adapt_dataset` — partitions a local dataset file.
+    ///
+    /// # Args
+    /// * `samples_path` - Path to the local dataset samples file.
+    /// * `labels_path` - Path to the local dataset labels file.
+    /// * `partition_sizes` - An iterator of partition sample and label sizes in **bytes**.
+    /// * `x_size` - The number of input features per sample.
+    /// * `y_size` - The number of output values per sample.
+    ///
+    /// # Returns
+    /// A list of `Partition::Local`.
+    fn adapt_local_dataset<'a, T>(
+        &self,
+        samples_path: PathBuf,
+        labels_path: PathBuf,
+        partition_sizes: T,
+    ) -> Vec<Partition<'a>>
+    where
+        T: Iterator<Item = (u64, u64)>,
+    {
+        let mut samples_offset = 0;
+        let mut labels_offset = 0;
+
+        partition_sizes
+            .map(|(x_size_bytes, y_size_bytes)| {
                 let partition = Partition::Local {
                     samples_path: samples_path.clone(),
                     labels_path: labels_path.clone(),
@@ -837,7 +1559,41 @@ impl Adapter {
         DatasetSpec {
             x_size: dataset.x_size,
             y_size: dataset.y_size,
+            sparse: matches!(dataset.src, DataSrc::Csr { .. }),
+        }
+    }
+
+    /// Partitions the training's dataset between the workers.
+    ///
+    /// With a global order sampler every worker trains on it's share of every batch of the
+    /// whole dataset, so each of them gets all of it's rows.
+    ///
+    /// # Args
+    /// * `training` - The training's configuration.
+    /// * `nworkers` - The amount of workers.
+    ///
+    /// # Returns
+    /// The partition of every worker.
+    ///
+    /// # Errors
+    /// See `adapt_dataset_partitions`.
+    fn adapt_worker_partitions<'a>(
+        &self,
+        training: &'a TrainingConfig,
+        nworkers: usize,
+    ) -> Result<Vec<Partition<'a>>> {
+        if training.sampler != SamplerConfig::GlobalOrder {
+            let npartitions = nworkers as u64;
+            return self.adapt_dataset_partitions(
+                &training.dataset,
+                npartitions,
+                training.empty_shard,
+            );
         }
+
+        let partitions =
+            self.adapt_dataset_partitions(&training.dataset, 1, training.empty_shard)?;
+        Ok(partitions.into_iter().cycle().take(nworkers).collect())
     }
 
     /// Converts a `DatasetConfig` into `DatasetSpec`s and `Partition`s.
@@ -850,59 +1606,94 @@ impl Adapter {
     /// # Args
     /// * `dataset` - A dataset's configuration.
     /// * `npartitions` - The amount of partitions.
+    /// * `empty_shard` - What to do if there are more partitions than rows.
     ///
     /// # Returns
     /// A list of resolved dataset specs and a list with partition metadata.
     ///
     /// # Errors
-    /// Returns an `OrchErr` if the dataset cannot be resolved.
+    /// Returns an `OrchErr` if the dataset cannot be resolved or an `OrchErr::EmptyShard`
+    /// for the first empty partition if those aren't skipped.
     fn adapt_dataset_partitions<'a>(
         &self,
         dataset: &'a DatasetConfig,
         npartitions: u64,
+        empty_shard: EmptyShardConfig,
     ) -> Result<Vec<Partition<'a>>> {
         let DatasetConfig {
             src,
             x_size,
             y_size,
+            ..
         } = dataset;
 
-        let size_bytes = match src {
+        let x_size_bytes = (x_size.get() * size_of::<f32>()) as u64;
+        let y_size_bytes = (y_size.get() * size_of::<f32>()) as u64;
+        let row_size_bytes = x_size_bytes + y_size_bytes;
+
+        let nrows = match src {
             DataSrc::Local {
                 samples_path,
                 labels_path,
             } => {
                 let samples_size_bytes = fs::metadata(samples_path)?.len();
                 let labels_size_bytes = fs::metadata(labels_path)?.len();
-                samples_size_bytes + labels_size_bytes
+                (samples_size_bytes + labels_size_bytes) / row_size_bytes
             }
             DataSrc::Inline { samples, labels } => {
                 let data_len = samples.len() + labels.len();
-                (data_len * size_of::<f32>()) as u64
+                (data_len * size_of::<f32>()) as u64 / row_size_bytes
+            }
+            DataSrc::Synthetic { nsamples, .. } => nsamples.get() as u64,
+            DataSrc::Csr { indptr, .. } => indptr.len().saturating_sub(1) as u64,
+            DataSrc::Stdin { .. } => {
+                let text = "stdin datasets must be read before being partitioned".into();
+                return Err(OrchErr::InvalidConfig(text));
             }
         };
 
-        let x_size_bytes = (x_size.get() * size_of::<f32>()) as u64;
-        let y_size_bytes = (y_size.get() * size_of::<f32>()) as u64;
-        let row_size_bytes = x_size_bytes + y_size_bytes;
-        let nrows = size_bytes / row_size_bytes;
         let base_rows = nrows / npartitions;
         let remainder = nrows % npartitions;
 
-        let partition_sizes = (0..npartitions).map(|i| {
-            let rows = if i < remainder {
+        if nrows < npartitions {
+            match empty_shard {
+                EmptyShardConfig::Fail => {
+                    let worker_id = nrows as usize;
+                    return Err(OrchErr::EmptyShard { worker_id });
+                }
+                EmptyShardConfig::Skip => warn!(
+                    "{} of the {npartitions} workers get no rows, they'll send zero gradients",
+                    npartitions - nrows
+                ),
+            }
+        }
+
+        let partition_rows = (0..npartitions).map(|i| {
+            if i < remainder {
                 base_rows + 1
             } else {
                 base_rows
-            };
-
-            (rows * x_size_bytes, rows * y_size_bytes)
+            }
         });
 
+        let partition_sizes = partition_rows
+            .clone()
+            .map(|rows| (rows * x_size_bytes, rows * y_size_bytes));
+
         let partitions = match src {
             DataSrc::Inline { samples, labels } => {
                 self.adapt_inline_dataset(samples, labels, partition_sizes)
             }
+            DataSrc::Synthetic {
+                noise_std, seed, ..
+            } => self.adapt_synthetic_dataset(*seed, *noise_std, partition_rows),
+            DataSrc::Csr {
+                indptr,
+                indices,
+                values,
+                labels,
+            } => self.adapt_csr_dataset(indptr, indices, values, labels, *y_size, partition_rows),
+            DataSrc::Stdin { .. } => unreachable!("stdin datasets are rejected above"),
             DataSrc::Local {
                 samples_path,
                 labels_path,
@@ -943,6 +1734,34 @@ impl Adapter {
         }
     }
 
+    /// Adapts the servers' `OptimizerConfig`, scaling it's learning rate with the workers.
+    ///
+    /// # Args
+    /// * `training` - The training's configuration.
+    /// * `nworkers` - The amount of workers training against the servers.
+    ///
+    /// # Returns
+    /// The servers' optimizer specification.
+    fn adapt_server_optimizer(&self, training: &TrainingConfig, nworkers: usize) -> OptimizerSpec {
+        let optimizer = self.adapt_optimizer(training.optimizer);
+        let base_lr = optimizer.learning_rate();
+
+        let factor = match training.lr_scaling {
+            LrScalingConfig::None => return optimizer,
+            LrScalingConfig::Linear => nworkers.max(1) as f32,
+            LrScalingConfig::SqrtLinear => (nworkers.max(1) as f32).sqrt(),
+        };
+
+        // SAFETY: The factor is at least 1.
+        let lr = FloatPositive::new(*base_lr * factor).unwrap();
+        info!(
+            "scaling the learning rate {} for {nworkers} workers, the effective learning rate is {}",
+            *base_lr, *lr
+        );
+
+        optimizer.with_learning_rate(lr)
+    }
+
     /// Adapts an `OptimizerConfig` into an `OptimizerSpec::GradientDescent`.
     ///
     /// # Args
@@ -971,24 +1790,109 @@ impl Adapter {
     /// * `input_size` - The model's input size.
     ///
     /// # Returns
-    /// The layers' specifications and their parameter generators' specifications.
-    fn adapt_layers(
+    /// The shared layers' specifications and the parameter generators' specifications of
+    /// every layer, the heads' following the shared ones.
+    pub(crate) fn adapt_layers(
         &self,
         model: &ModelConfig,
         input_size: NonZeroUsize,
     ) -> (Vec<LayerSpec>, Vec<Option<ParamGenSpec>>) {
-        let (layer_specs, param_gen_specs) = model
-            .layers
+        let (layer_specs, mut param_gen_specs, output_size) =
+            self.adapt_layer_seq(&model.layers, input_size, model.activation_clamp);
+
+        for head in &model.heads {
+            let (_, head_param_gens, _) =
+                self.adapt_layer_seq(&head.layers, output_size, model.activation_clamp);
+            param_gen_specs.extend(head_param_gens);
+        }
+
+        (layer_specs, param_gen_specs)
+    }
+
+    /// Adapts the heads of a `ModelConfig` into `HeadSpec`s.
+    ///
+    /// # Args
+    /// * `model` - A model's architecture and initialization configuration.
+    /// * `input_size` - The model's input size.
+    ///
+    /// # Returns
+    /// The heads' specifications, in order.
+    fn adapt_heads(&self, model: &ModelConfig, input_size: NonZeroUsize) -> Vec<HeadSpec> {
+        let (_, _, output_size) =
+            self.adapt_layer_seq(&model.layers, input_size, model.activation_clamp);
+
+        model
+            .heads
+            .iter()
+            .map(|head| {
+                let (layers, _, _) =
+                    self.adapt_layer_seq(&head.layers, output_size, model.activation_clamp);
+
+                HeadSpec {
+                    layers,
+                    loss_fn: self.adapt_loss_fn(head.loss_fn),
+                    y_size: head.y_size,
+                    weight: head.weight,
+                }
+            })
+            .collect()
+    }
+
+    /// Adapts a sequence of `LayerConfig`s, each fed the output of the previous one.
+    ///
+    /// # Args
+    /// * `layers` - The layers' configurations.
+    /// * `input_size` - The size of the first layer's input.
+    /// * `clamp` - The magnitude the sigmoid and tanh inputs are clamped to, if any.
+    ///
+    /// # Returns
+    /// The layers' specifications, their parameter generators' specifications and the
+    /// output size of the last layer.
+    fn adapt_layer_seq(
+        &self,
+        layers: &[LayerConfig],
+        input_size: NonZeroUsize,
+        clamp: Option<FloatPositive>,
+    ) -> (Vec<LayerSpec>, Vec<Option<ParamGenSpec>>, NonZeroUsize) {
+        let mut output_size = input_size;
+
+        let (layer_specs, param_gen_specs) = layers
             .iter()
-            .scan(input_size, |input_size, config| {
-                let (layer_spec, param_gen_spec, output_size) =
-                    self.adapt_layer(config, *input_size);
-                *input_size = output_size;
-                Some((layer_spec, param_gen_spec))
+            .map(|config| {
+                let (layer_spec, param_gen_spec, size) =
+                    self.adapt_layer(config, output_size, clamp);
+                output_size = size;
+                (layer_spec, param_gen_spec)
             })
             .unzip();
 
-        (layer_specs, param_gen_specs)
+        (layer_specs, param_gen_specs, output_size)
+    }
+
+    /// Adapts the parameter generators of the model's layers, warm starting the ones
+    /// saved in the model's `warm_start` file.
+    ///
+    /// # Args
+    /// * `model` - The model's configuration.
+    /// * `input_size` - The size of the model's input.
+    ///
+    /// # Returns
+    /// The parameter generator of every layer, `None` for the ones without parameters.
+    ///
+    /// # Errors
+    /// Returns an `OrchErr` if the warm start can't be read or doesn't fit the model.
+    fn adapt_inits(
+        &self,
+        model: &ModelConfig,
+        input_size: NonZeroUsize,
+    ) -> Result<Vec<Option<ParamGenSpec>>> {
+        let (_, mut param_gens) = self.adapt_layers(model, input_size);
+
+        if let Some(ref path) = model.warm_start {
+            WarmStart::load(path)?.apply(model, input_size, &mut param_gens)?;
+        }
+
+        Ok(param_gens)
     }
 
     /// Adapts a `ParamGenConfig` into a `ParamGenSpec`.
@@ -996,6 +1900,7 @@ impl Adapter {
     /// # Args
     /// * `param_gen` - A parameter generator configuration.
     /// * `(fan_in, limit, fan_out)` - It's layer sizing information.
+    /// * `gain` - The factor the random parameters are scaled by, `1` if not set.
     ///
     /// # Returns
     /// The parameter generator's specification.
@@ -1003,41 +1908,26 @@ impl Adapter {
         &self,
         param_gen: ParamGenConfig,
         (fan_in, limit, fan_out): (usize, usize, usize),
+        gain: Option<FloatPositive>,
     ) -> ParamGenSpec {
-        match param_gen {
-            ParamGenConfig::Const { value } => ParamGenSpec::Const { value, limit },
-            ParamGenConfig::Uniform { low, high } => ParamGenSpec::Rand {
-                distribution: DistributionSpec::Uniform { low, high },
-                limit,
-            },
-            ParamGenConfig::UniformInclusive { low, high } => ParamGenSpec::Rand {
-                distribution: DistributionSpec::UniformInclusive { low, high },
-                limit,
-            },
-            ParamGenConfig::XavierUniform => ParamGenSpec::Rand {
-                distribution: DistributionSpec::XavierUniform { fan_in, fan_out },
-                limit,
-            },
-            ParamGenConfig::LecunUniform => ParamGenSpec::Rand {
-                distribution: DistributionSpec::LecunUniform { fan_in },
-                limit,
-            },
-            ParamGenConfig::Normal { mean, std_dev } => ParamGenSpec::Rand {
-                distribution: DistributionSpec::Normal { mean, std_dev },
-                limit,
-            },
-            ParamGenConfig::Kaiming => ParamGenSpec::Rand {
-                distribution: DistributionSpec::Kaiming { fan_in },
-                limit,
-            },
-            ParamGenConfig::Xavier => ParamGenSpec::Rand {
-                distribution: DistributionSpec::Xavier { fan_in, fan_out },
-                limit,
-            },
-            ParamGenConfig::Lecun => ParamGenSpec::Rand {
-                distribution: DistributionSpec::Lecun { fan_in },
-                limit,
-            },
+        let distribution = match param_gen {
+            ParamGenConfig::Const { value } => return ParamGenSpec::Const { value, limit },
+            ParamGenConfig::Uniform { low, high } => DistributionSpec::Uniform { low, high },
+            ParamGenConfig::UniformInclusive { low, high } => {
+                DistributionSpec::UniformInclusive { low, high }
+            }
+            ParamGenConfig::XavierUniform => DistributionSpec::XavierUniform { fan_in, fan_out },
+            ParamGenConfig::LecunUniform => DistributionSpec::LecunUniform { fan_in },
+            ParamGenConfig::Normal { mean, std_dev } => DistributionSpec::Normal { mean, std_dev },
+            ParamGenConfig::Kaiming => DistributionSpec::Kaiming { fan_in },
+            ParamGenConfig::Xavier => DistributionSpec::Xavier { fan_in, fan_out },
+            ParamGenConfig::Lecun => DistributionSpec::Lecun { fan_in },
+        };
+
+        ParamGenSpec::Rand {
+            distribution,
+            limit,
+            gain: gain.map_or(1.0, |gain| *gain),
         }
     }
 
@@ -1046,6 +1936,7 @@ impl Adapter {
     /// # Args
     /// * `layer` - The layer configuration.
     /// * `input_size` - The input size to this layer.
+    /// * `clamp` - The magnitude the sigmoid and tanh inputs are clamped to, if any.
     ///
     /// # Returns
     /// The layer's specification, it's parameter generator specifications and it's output size.
@@ -1053,23 +1944,34 @@ impl Adapter {
         &self,
         layer: &LayerConfig,
         input_size: NonZeroUsize,
+        clamp: Option<FloatPositive>,
     ) -> (LayerSpec, Option<ParamGenSpec>, NonZeroUsize) {
         match *layer {
             LayerConfig::Dense {
                 output_size,
                 init,
                 act_fn,
+                tie,
+                frozen,
+                init_gain,
             } => {
-                let act_fn_spec = act_fn.map(|act_fn| self.adapt_act_fn(act_fn));
-                let layer_size = input_size.saturating_add(1).saturating_mul(output_size);
+                let act_fn_spec = act_fn.map(|act_fn| self.adapt_act_fn(act_fn, clamp));
+
+                // A tied layer only holds it's biases, the weights are it's source's.
+                let layer_size = match tie {
+                    Some(_) => output_size,
+                    None => input_size.saturating_add(1).saturating_mul(output_size),
+                };
                 let sizes = (input_size.get(), layer_size.get(), output_size.get());
 
                 (
                     LayerSpec::Dense {
                         dim: (sizes.0, sizes.2),
                         act_fn: act_fn_spec,
+                        tie,
+                        frozen,
                     },
-                    Some(self.adapt_param_gen(init, sizes)),
+                    Some(self.adapt_param_gen(init, sizes, init_gain)),
                     output_size,
                 )
             }
@@ -1080,8 +1982,10 @@ impl Adapter {
                 padding,
                 init,
                 act_fn,
+                frozen,
+                init_gain,
             } => {
-                let act_fn_spec = act_fn.map(|act_fn| self.adapt_act_fn(act_fn));
+                let act_fn_spec = act_fn.map(|act_fn| self.adapt_act_fn(act_fn, clamp));
 
                 let (filters, channels, kernel_size) =
                     (kernel_dim.0.get(), kernel_dim.1.get(), kernel_dim.2.get());
@@ -1098,8 +2002,9 @@ impl Adapter {
                         stride: stride.get(),
                         padding,
                         act_fn: act_fn_spec,
+                        frozen,
                     },
-                    Some(self.adapt_param_gen(init, sizes)),
+                    Some(self.adapt_param_gen(init, sizes, init_gain)),
                     output_size,
                 )
             }
@@ -1110,7 +2015,7 @@ impl Adapter {
                 padding,
                 act_fn,
             } => {
-                let act_fn_spec = act_fn.map(|act_fn| self.adapt_act_fn(act_fn));
+                let act_fn_spec = act_fn.map(|act_fn| self.adapt_act_fn(act_fn, clamp));
 
                 let input_dim = (input_dim.0.get(), input_dim.1.get(), input_dim.2.get());
                 let output_size = layer.output_size();
@@ -1127,6 +2032,26 @@ impl Adapter {
                     output_size,
                 )
             }
+            LayerConfig::Custom {
+                ref name,
+                output_size,
+                ref params,
+                init,
+            } => {
+                // The validator already made sure there's a layer registered under this name.
+                let layer_size = build_custom_layer(name, params).map_or(0, |layer| layer.size());
+                let sizes = (input_size.get(), layer_size, output_size.get());
+
+                (
+                    LayerSpec::Custom {
+                        name: name.clone(),
+                        params: params.clone(),
+                    },
+                    init.filter(|_| layer_size > 0)
+                        .map(|init| self.adapt_param_gen(init, sizes, None)),
+                    output_size,
+                )
+            }
         }
     }
 
@@ -1134,14 +2059,15 @@ impl Adapter {
     ///
     /// # Args
     /// * `act_fn` - An activation function configuration.
+    /// * `clamp` - The magnitude the sigmoid and tanh inputs are clamped to, if any.
     ///
     /// # Returns
     /// The activation function's specification.
-    fn adapt_act_fn(&self, act_fn: ActFnConfig) -> ActFnSpec {
+    fn adapt_act_fn(&self, act_fn: ActFnConfig, clamp: Option<FloatPositive>) -> ActFnSpec {
         match act_fn {
-            ActFnConfig::Sigmoid { amp } => ActFnSpec::Sigmoid { amp },
+            ActFnConfig::Sigmoid { amp } => ActFnSpec::Sigmoid { amp, clamp },
             ActFnConfig::Softmax => ActFnSpec::Softmax,
-            ActFnConfig::Tanh { amp } => ActFnSpec::Tanh { amp },
+            ActFnConfig::Tanh { amp } => ActFnSpec::Tanh { amp, clamp },
             ActFnConfig::ReLU { slope } => ActFnSpec::ReLU { slope },
         }
     }
@@ -1171,8 +2097,31 @@ fn balanced_partitions<T>(mut items: Vec<(T, usize)>, k: usize) -> Vec<Vec<T>> {
     bins
 }
 
+/// Tells whether an address can be connected to, either a bound local listener's or a
+/// network address resolving to at least one socket address.
+///
+/// # Args
+/// * `addr` - The address of the node.
+///
+/// # Returns
+/// Whether the address resolves.
+fn resolves(addr: &str) -> bool {
+    LocalListener::is_bound(addr)
+        || matches!(
+            addr.to_socket_addrs().map(|mut addrs| addrs.next()),
+            Ok(Some(_))
+        )
+}
+
 #[cfg(test)]
 mod tests {
+    use machine_learning::{
+        datasets::{CsrMatrix, SyntheticDataset},
+        initialization::ParamGenBuilder,
+        rng::RngPurpose,
+    };
+    use rand::Rng;
+
     use super::*;
 
     #[test]
@@ -1189,6 +2138,7 @@ mod tests {
             },
             x_size,
             y_size,
+            test_split: 0.0,
         };
 
         let expected_partitions = [
@@ -1208,7 +2158,7 @@ mod tests {
 
         let adapter = Adapter::new();
         let partitions = adapter
-            .adapt_dataset_partitions(&config, npartitions)
+            .adapt_dataset_partitions(&config, npartitions, EmptyShardConfig::Fail)
             .unwrap();
         assert_eq!(partitions, expected_partitions);
     }
@@ -1227,6 +2177,7 @@ mod tests {
             },
             x_size,
             y_size,
+            test_split: 0.0,
         };
 
         let expected_partitions = [
@@ -1246,11 +2197,156 @@ mod tests {
 
         let adapter = Adapter::new();
         let partitions = adapter
-            .adapt_dataset_partitions(&config, npartitions)
+            .adapt_dataset_partitions(&config, npartitions, EmptyShardConfig::Fail)
             .unwrap();
         assert_eq!(partitions, expected_partitions);
     }
 
+    #[test]
+    fn test_the_synthetic_shards_concatenate_to_the_single_machine_dataset() {
+        let (x_size, y_size) = (NonZeroUsize::new(3).unwrap(), NonZeroUsize::new(2).unwrap());
+        let config = DatasetConfig {
+            src: DataSrc::Synthetic {
+                nsamples: NonZeroUsize::new(10).unwrap(),
+                noise_std: 0.1,
+                seed: 7,
+            },
+            x_size,
+            y_size,
+            test_split: 0.0,
+        };
+
+        let adapter = Adapter::new();
+        let partitions = adapter
+            .adapt_dataset_partitions(&config, 3, EmptyShardConfig::Fail)
+            .unwrap();
+
+        let (mut samples, mut labels) = (Vec::new(), Vec::new());
+
+        for partition in &partitions {
+            let SyntheticSpec {
+                seed,
+                ref rows,
+                noise_std,
+            } = partition.synthetic_spec().unwrap();
+
+            let rows = rows.start as usize..rows.end as usize;
+            let shard =
+                SyntheticDataset::generate_rows(rows, x_size.get(), y_size.get(), noise_std, seed)
+                    .unwrap();
+
+            samples.extend(shard.samples);
+            labels.extend(shard.labels);
+        }
+
+        let whole = SyntheticDataset::generate(10, x_size.get(), y_size.get(), 0.1, 7).unwrap();
+        let nsamples: Vec<_> = partitions.iter().map(|p| p.nsamples(x_size)).collect();
+
+        assert_eq!(nsamples, [4, 3, 3]);
+        assert_eq!((samples, labels), (whole.samples, whole.labels));
+    }
+
+    #[test]
+    fn test_the_csr_partitions_decode_to_contiguous_rows() {
+        let (x_size, y_size) = (NonZeroUsize::new(3).unwrap(), NonZeroUsize::MIN);
+        let dense = [0., 1., 0., 2., 0., 0., 0., 0., 0., 0., 3., 4., 5., 0., 0.];
+        let (indptr, indices, values) = CsrMatrix::from_dense(&dense, 3).unwrap().into_parts();
+        let config = DatasetConfig {
+            src: DataSrc::Csr {
+                indptr,
+                indices,
+                values,
+                labels: vec![1., 2., 3., 4., 5.],
+            },
+            x_size,
+            y_size,
+            test_split: 0.0,
+        };
+
+        let adapter = Adapter::new();
+        let partitions = adapter
+            .adapt_dataset_partitions(&config, 2, EmptyShardConfig::Fail)
+            .unwrap();
+        let nsamples: Vec<_> = partitions.iter().map(|p| p.nsamples(x_size)).collect();
+        assert_eq!(nsamples, [3, 2]);
+
+        let (mut samples, mut labels) = (Vec::new(), Vec::new());
+
+        for partition in partitions {
+            let Partition::Csr {
+                indptr,
+                indices,
+                values,
+                labels: rows_labels,
+            } = partition
+            else {
+                panic!("expected a csr partition, got: {partition:?}");
+            };
+
+            let words = CsrMatrix::encode_rows(indptr, indices, values).unwrap();
+            let rows = CsrMatrix::from_words(3, rows_labels.len(), &words).unwrap();
+            samples.extend(rows.to_dense());
+            labels.extend_from_slice(rows_labels);
+        }
+
+        assert_eq!(samples, dense);
+        assert_eq!(labels, [1., 2., 3., 4., 5.]);
+        assert!(adapter.adapt_dataset(&config).sparse);
+    }
+
+    #[test]
+    fn test_more_workers_than_rows_fail_or_get_empty_partitions() {
+        let samples = [1., 3., 5.];
+        let labels = [2., 4., 6.];
+        let size = NonZeroUsize::new(1).unwrap();
+        let config = DatasetConfig {
+            src: DataSrc::Inline {
+                samples: samples.into(),
+                labels: labels.into(),
+            },
+            x_size: size,
+            y_size: size,
+            test_split: 0.0,
+        };
+
+        let adapter = Adapter::new();
+        let err = adapter
+            .adapt_dataset_partitions(&config, 4, EmptyShardConfig::Fail)
+            .unwrap_err();
+        assert!(matches!(err, OrchErr::EmptyShard { worker_id: 3 }));
+
+        let partitions = adapter
+            .adapt_dataset_partitions(&config, 4, EmptyShardConfig::Skip)
+            .unwrap();
+        let nsamples: Vec<_> = partitions.iter().map(|p| p.nsamples(size)).collect();
+        assert_eq!(nsamples, [1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_an_empty_inline_dataset_leaves_every_partition_empty() {
+        let size = NonZeroUsize::new(1).unwrap();
+        let config = DatasetConfig {
+            src: DataSrc::Inline {
+                samples: Vec::new(),
+                labels: Vec::new(),
+            },
+            x_size: size,
+            y_size: size,
+            test_split: 0.0,
+        };
+
+        let adapter = Adapter::new();
+        let err = adapter
+            .adapt_dataset_partitions(&config, 2, EmptyShardConfig::Fail)
+            .unwrap_err();
+        assert!(matches!(err, OrchErr::EmptyShard { worker_id: 0 }));
+
+        let partitions = adapter
+            .adapt_dataset_partitions(&config, 2, EmptyShardConfig::Skip)
+            .unwrap();
+        assert!(partitions.iter().all(|p| p.nsamples(size) == 0));
+    }
+
     #[test]
     fn test_adapter_adapt_layers_returns_exepcted_layers() {
         let cfg = ModelConfig {
@@ -1270,13 +2366,22 @@ mod tests {
                     padding: 0,
                     init: ParamGenConfig::Kaiming,
                     act_fn: None,
+                    frozen: false,
+                    init_gain: None,
                 },
                 LayerConfig::Dense {
                     output_size: NonZeroUsize::new(4).unwrap(),
                     init: ParamGenConfig::Kaiming,
                     act_fn: Some(ActFnConfig::Sigmoid { amp: 1.0 }),
+                    tie: None,
+                    frozen: false,
+                    init_gain: None,
                 },
             ],
+            heads: Vec::new(),
+            checkpoint_every: None,
+            activation_clamp: None,
+            warm_start: None,
         };
         let input_size = NonZeroUsize::new(9).unwrap();
 
@@ -1287,10 +2392,16 @@ mod tests {
                 stride: 1,
                 padding: 0,
                 act_fn: None,
+                frozen: false,
             },
             LayerSpec::Dense {
                 dim: (4, 4),
-                act_fn: Some(ActFnSpec::Sigmoid { amp: 1.0 }),
+                act_fn: Some(ActFnSpec::Sigmoid {
+                    amp: 1.0,
+                    clamp: None,
+                }),
+                tie: None,
+                frozen: false,
             },
         ];
 
@@ -1299,4 +2410,442 @@ mod tests {
 
         assert_eq!(got_specs, expected_specs);
     }
+
+    #[test]
+    fn test_adapter_keeps_the_activation_amplitude() {
+        let json = r#"{
+            "layers": [
+                { "dense": { "output_size": 2, "init": "kaiming", "act_fn": { "sigmoid": { "amp": 2.5 } } } },
+                { "dense": { "output_size": 1, "init": "kaiming", "act_fn": { "tanh": { "amp": 0.5 } } } }
+            ]
+        }"#;
+        let cfg: ModelConfig = serde_json::from_str(json).unwrap();
+        let input_size = NonZeroUsize::new(3).unwrap();
+
+        let adapter = Adapter::new();
+        let (got_specs, _) = adapter.adapt_layers(&cfg, input_size);
+
+        let act_fns: Vec<_> = got_specs
+            .iter()
+            .map(|spec| match spec {
+                LayerSpec::Dense { act_fn, .. } => *act_fn,
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            act_fns,
+            [
+                Some(ActFnSpec::Sigmoid {
+                    amp: 2.5,
+                    clamp: None
+                }),
+                Some(ActFnSpec::Tanh {
+                    amp: 0.5,
+                    clamp: None
+                })
+            ]
+        );
+    }
+
+    #[test]
+    fn test_adapter_clamps_the_sigmoid_and_tanh_inputs_of_the_model() {
+        let json = r#"{
+            "layers": [
+                { "dense": { "output_size": 2, "init": "kaiming", "act_fn": { "sigmoid": { "amp": 1.0 } } } },
+                { "dense": { "output_size": 2, "init": "kaiming", "act_fn": "softmax" } },
+                { "dense": { "output_size": 1, "init": "kaiming", "act_fn": { "tanh": { "amp": 1.0 } } } }
+            ],
+            "activation_clamp": 20.0
+        }"#;
+        let cfg: ModelConfig = serde_json::from_str(json).unwrap();
+        let input_size = NonZeroUsize::new(3).unwrap();
+
+        let adapter = Adapter::new();
+        let (got_specs, _) = adapter.adapt_layers(&cfg, input_size);
+
+        let clamps: Vec<_> = got_specs
+            .iter()
+            .map(|spec| match spec {
+                LayerSpec::Dense {
+                    act_fn: Some(ActFnSpec::Sigmoid { clamp, .. } | ActFnSpec::Tanh { clamp, .. }),
+                    ..
+                } => clamp.map(|clamp| *clamp),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(clamps, [Some(20.0), None, Some(20.0)]);
+    }
+
+    #[test]
+    fn test_adapter_only_generates_the_biases_of_a_tied_layer() {
+        let json = r#"{
+            "layers": [
+                { "dense": { "output_size": 2, "init": "kaiming" } },
+                { "dense": { "output_size": 3, "init": "kaiming", "tie": 0 } }
+            ]
+        }"#;
+        let cfg: ModelConfig = serde_json::from_str(json).unwrap();
+        let input_size = NonZeroUsize::new(3).unwrap();
+
+        let adapter = Adapter::new();
+        let (got_specs, param_gens) = adapter.adapt_layers(&cfg, input_size);
+
+        let ties: Vec<_> = got_specs
+            .iter()
+            .map(|spec| match spec {
+                LayerSpec::Dense { dim, tie, .. } => (*dim, *tie),
+                _ => unreachable!(),
+            })
+            .collect();
+        let sizes: Vec<_> = param_gens
+            .iter()
+            .flatten()
+            .map(|param_gen| param_gen.size())
+            .collect();
+
+        assert_eq!(ties, [((3, 2), None), ((2, 3), Some(0))]);
+        assert_eq!(sizes, [8, 3]);
+    }
+
+    #[test]
+    fn test_adapter_merges_the_frozen_layers_of_every_server() {
+        let json = r#"{
+            "layers": [
+                { "dense": { "output_size": 2, "init": "kaiming", "frozen": true } },
+                { "dense": { "output_size": 2, "init": "kaiming", "frozen": true } },
+                { "dense": { "output_size": 1, "init": "kaiming" } }
+            ]
+        }"#;
+        let cfg: ModelConfig = serde_json::from_str(json).unwrap();
+
+        // The first two layers land back to back on the first server, the head on the second.
+        let layer_offsets = [(0, 0, 8), (0, 8, 14), (1, 0, 3)];
+
+        let adapter = Adapter::new();
+        let frozen: Vec<Vec<_>> = adapter
+            .frozen_ranges(&cfg, &layer_offsets, 2, false)
+            .into_iter()
+            .map(|ranges| ranges.into_iter().map(|r| (r.start, r.end)).collect())
+            .collect();
+
+        assert_eq!(frozen, [vec![(0, 14)], vec![]]);
+    }
+
+    #[test]
+    fn test_adapter_freezes_every_weight_when_training_the_biases_only() {
+        let json = r#"{
+            "layers": [
+                { "dense": { "output_size": 2, "init": "kaiming" } },
+                { "dense": { "output_size": 3, "init": "kaiming", "tie": 0 } },
+                { "dense": { "output_size": 1, "init": "kaiming", "frozen": true } }
+            ]
+        }"#;
+        let cfg: ModelConfig = serde_json::from_str(json).unwrap();
+
+        let layer_offsets = [(0, 0, 8), (0, 8, 11), (1, 0, 4)];
+
+        let adapter = Adapter::new();
+        let frozen: Vec<Vec<_>> = adapter
+            .frozen_ranges(&cfg, &layer_offsets, 2, true)
+            .into_iter()
+            .map(|ranges| ranges.into_iter().map(|r| (r.start, r.end)).collect())
+            .collect();
+
+        assert_eq!(frozen, [vec![(0, 6)], vec![(0, 4)]]);
+    }
+
+    #[test]
+    fn test_adapter_widens_only_the_listed_layers_of_every_server() {
+        // A body of two layers on the first server and the head split off on the second.
+        let layer_offsets = [(0, 0, 8), (0, 8, 14), (1, 0, 3)];
+
+        let adapter = Adapter::new();
+        let widened: Vec<Vec<_>> = adapter
+            .accum_f64_ranges(&layer_offsets, 2, &[2, 1])
+            .into_iter()
+            .map(|ranges| ranges.into_iter().map(|r| (r.start, r.end)).collect())
+            .collect();
+
+        assert_eq!(widened, [vec![(8, 14)], vec![(0, 3)]]);
+    }
+
+    fn addrs(addrs: &[&str]) -> Vec<String> {
+        addrs.iter().map(|addr| addr.to_string()).collect()
+    }
+
+    #[test]
+    fn test_adapter_rejects_duplicate_worker_addrs() {
+        let adapter = Adapter::new();
+        let workers = addrs(&["node-0:50000", "node-1:50000", "node-0:50000"]);
+
+        let Err(OrchErr::InvalidConfig(text)) = adapter.check_unique_addrs(&workers, &[]) else {
+            panic!("duplicate worker addresses were accepted");
+        };
+
+        assert!(text.contains("node-0:50000") && text.contains("worker"));
+    }
+
+    #[test]
+    fn test_adapter_rejects_duplicate_server_addrs() {
+        let adapter = Adapter::new();
+        let workers = addrs(&["node-0:50000"]);
+        let servers = addrs(&["node-1:50000", "node-1:50000"]);
+
+        let Err(OrchErr::InvalidConfig(text)) = adapter.check_unique_addrs(&workers, &servers)
+        else {
+            panic!("duplicate server addresses were accepted");
+        };
+
+        assert!(text.contains("node-1:50000") && text.contains("server"));
+    }
+
+    #[test]
+    fn test_adapter_rejects_addrs_in_both_roles() {
+        let adapter = Adapter::new();
+        let workers = addrs(&["node-0:50000", "node-1:50000"]);
+        let servers = addrs(&["node-1:50000"]);
+
+        let Err(OrchErr::InvalidConfig(text)) = adapter.check_unique_addrs(&workers, &servers)
+        else {
+            panic!("an address in both roles was accepted");
+        };
+
+        assert!(text.contains("both as a server and a worker"));
+        assert!(
+            adapter
+                .check_unique_addrs(&workers, &addrs(&["node-2:50000"]))
+                .is_ok()
+        );
+    }
+
+    fn clip_group(start: usize, end: usize) -> ClipGroupConfig {
+        ClipGroupConfig {
+            start,
+            end,
+            max_norm: FloatPositive::new(1.0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_adapter_checks_the_clip_groups() {
+        let adapter = Adapter::new();
+        let layer_sizes = [10, 0, 6, 4];
+
+        let groups = [clip_group(0, 10), clip_group(16, 20)];
+        assert!(adapter.check_clip_groups(&layer_sizes, &groups).is_ok());
+
+        let groups = [clip_group(10, 20), clip_group(0, 16)];
+        let Err(OrchErr::InvalidConfig(text)) = adapter.check_clip_groups(&layer_sizes, &groups)
+        else {
+            panic!("overlapping clip groups were accepted");
+        };
+
+        assert!(text.contains("overlap"));
+
+        let groups = [clip_group(0, 12)];
+        assert!(adapter.check_clip_groups(&layer_sizes, &groups).is_err());
+    }
+
+    #[test]
+    fn test_adapter_scales_the_server_learning_rate() {
+        let training = r#"{
+            "addrs": ["node-0", "node-1", "node-2", "node-3", "node-4"],
+            "algorithm": {
+                "parameter_server": { "nservers": 1, "synchronizer": "barrier", "store": "blocking" }
+            },
+            "dataset": {
+                "src": { "inline": { "samples": [1.0, 2.0], "labels": [2.0, 4.0] } },
+                "x_size": 1,
+                "y_size": 1
+            },
+            "optimizer": { "gradient_descent": { "lr": 0.01 } },
+            "loss_fn": "mse",
+            "batch_size": 1,
+            "max_epochs": 1,
+            "offline_epochs": 0
+        }"#;
+
+        let mut training: TrainingConfig = serde_json::from_str(training).unwrap();
+        let adapter = Adapter::new();
+
+        for (lr_scaling, expected) in [
+            (LrScalingConfig::None, 0.01),
+            (LrScalingConfig::Linear, 0.04),
+            (LrScalingConfig::SqrtLinear, 0.02),
+        ] {
+            training.lr_scaling = lr_scaling;
+            let optimizer = adapter.adapt_server_optimizer(&training, 4);
+            assert!((*optimizer.learning_rate() - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_adapter_lays_every_layer_out_whole_on_a_single_server() {
+        let model = r#"{
+            "layers": [
+                { "dense": { "output_size": 4, "init": "kaiming" } },
+                { "dense": { "output_size": 3, "init": "kaiming" } },
+                { "dense": { "output_size": 2, "init": "kaiming" } },
+                { "dense": { "output_size": 1, "init": "kaiming" } }
+            ]
+        }"#;
+        let training = r#"{
+            "addrs": ["node-0", "node-1", "node-2"],
+            "algorithm": {
+                "parameter_server": { "nservers": 2, "synchronizer": "barrier", "store": "blocking" }
+            },
+            "dataset": {
+                "src": { "inline": { "samples": [1.0, 2.0], "labels": [2.0, 4.0] } },
+                "x_size": 2,
+                "y_size": 1
+            },
+            "optimizer": { "gradient_descent": { "lr": 0.01 } },
+            "loss_fn": "mse",
+            "batch_size": 1,
+            "max_epochs": 1,
+            "offline_epochs": 0
+        }"#;
+
+        let model: ModelConfig = serde_json::from_str(model).unwrap();
+        let training: TrainingConfig = serde_json::from_str(training).unwrap();
+        let nservers = NonZeroUsize::new(2).unwrap();
+
+        let adapter = Adapter::new();
+        let layout = adapter.layer_layout(&model, &training, nservers).unwrap();
+        let sizes = adapter.layer_sizes(&model, &training);
+        let (_, server_sizes, ..) = adapter
+            .adapt_param_gens(&model, &training, nservers.get())
+            .unwrap();
+
+        let mut layers: Vec<_> = layout.iter().flatten().map(|&(layer, ..)| layer).collect();
+        layers.sort_unstable();
+        assert_eq!(layers, [0, 1, 2, 3]);
+
+        for (layers, server_size) in layout.iter().zip(server_sizes) {
+            let mut cursor = 0;
+
+            for &(layer, start, end) in layers {
+                assert_eq!(start, cursor, "the layers of a server leave a gap");
+                assert_eq!(end - start, sizes[layer]);
+                cursor = end;
+            }
+
+            assert_eq!(cursor, server_size);
+        }
+    }
+
+    #[test]
+    fn test_workers_share_the_initial_weights_but_not_the_augmentation_streams() {
+        let model = r#"{ "layers": [{ "dense": { "output_size": 1, "init": "kaiming" } }] }"#;
+        let training = r#"{
+            "addrs": ["localhost:50000", "localhost:50001"],
+            "algorithm": "all_reduce",
+            "dataset": {
+                "src": { "inline": { "samples": [1.0, 2.0], "labels": [2.0, 4.0] } },
+                "x_size": 1,
+                "y_size": 1
+            },
+            "optimizer": { "gradient_descent": { "lr": 0.01 } },
+            "loss_fn": "mse",
+            "batch_size": 1,
+            "max_epochs": 1,
+            "offline_epochs": 0,
+            "seed": 42
+        }"#;
+
+        let model: ModelConfig = serde_json::from_str(model).unwrap();
+        let training: TrainingConfig = serde_json::from_str(training).unwrap();
+        let partitions = vec![
+            Partition::Inline {
+                samples: &[1.0],
+                labels: &[2.0],
+            },
+            Partition::Inline {
+                samples: &[2.0],
+                labels: &[4.0],
+            },
+        ];
+
+        let adapter = Adapter::new();
+        let workers = adapter
+            .adapt_all_reduce_workers(&model, &training, training.addrs.clone(), partitions)
+            .unwrap();
+
+        let weights: Vec<_> = workers
+            .iter()
+            .map(|worker| {
+                let AlgorithmSpec::AllReduce { param_gen, .. } = &worker.spec.algorithm else {
+                    panic!("the worker isn't all reduce");
+                };
+
+                let mut param_gen = ParamGenBuilder::new()
+                    .build(param_gen.clone(), worker.spec.seed)
+                    .unwrap();
+                param_gen.sample(2).unwrap()
+            })
+            .collect();
+
+        assert_eq!(weights[0], weights[1]);
+
+        let streams: Vec<Vec<u64>> = workers
+            .iter()
+            .map(|worker| {
+                let seed = worker.spec.trainer.augmentation_seed;
+                let mut rng = rng::rng_for(seed, RngPurpose::Augmentation);
+                (0..4).map(|_| rng.random()).collect()
+            })
+            .collect();
+
+        assert_eq!(workers[1].spec.trainer.augmentation_seed, Some(43));
+        assert_ne!(streams[0], streams[1]);
+    }
+
+    #[test]
+    fn test_a_global_order_gives_every_worker_the_whole_dataset_and_a_position() {
+        let model = r#"{ "layers": [{ "dense": { "output_size": 1, "init": "kaiming" } }] }"#;
+        let training = r#"{
+            "addrs": ["localhost:50000", "localhost:50001", "localhost:50002"],
+            "algorithm": "all_reduce",
+            "dataset": {
+                "src": { "inline": { "samples": [1.0, 2.0], "labels": [2.0, 4.0] } },
+                "x_size": 1,
+                "y_size": 1
+            },
+            "optimizer": { "gradient_descent": { "lr": 0.01 } },
+            "loss_fn": "mse",
+            "batch_size": 2,
+            "max_epochs": 1,
+            "offline_epochs": 0,
+            "seed": 42,
+            "sampler": "global_order"
+        }"#;
+
+        let model: ModelConfig = serde_json::from_str(model).unwrap();
+        let training: TrainingConfig = serde_json::from_str(training).unwrap();
+
+        let adapter = Adapter::new();
+        let partitions = adapter.adapt_worker_partitions(&training, 3).unwrap();
+        let workers = adapter
+            .adapt_all_reduce_workers(&model, &training, training.addrs.clone(), partitions)
+            .unwrap();
+
+        let nworkers = NonZeroUsize::new(3).unwrap();
+        for (i, worker) in workers.iter().enumerate() {
+            let whole = Partition::Inline {
+                samples: &[1.0, 2.0],
+                labels: &[2.0, 4.0],
+            };
+
+            assert_eq!(worker.partition, whole);
+            assert_eq!(
+                worker.spec.trainer.sampler,
+                SamplerSpec::GlobalOrder {
+                    worker: i,
+                    nworkers
+                }
+            );
+        }
+    }
 }
@@ -0,0 +1,205 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::{ModelConfig, TrainingConfig};
+use crate::{OrchErr, Result};
+
+/// A whole run's configuration as read from a file, the model's architecture along with
+/// the training's configuration under the `model` and `training` keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigFile {
+    pub model: ModelConfig,
+    pub training: TrainingConfig,
+}
+
+/// The formats a `ConfigFile` can be written in, told apart by the file's extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Tells the format of a config file by it's extension.
+    ///
+    /// # Args
+    /// * `path` - The path of the file.
+    ///
+    /// # Returns
+    /// The format or `None` if the extension isn't `.json` nor `.toml`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+
+        match ext.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+}
+
+impl ConfigFile {
+    /// Reads a config file, in the format it's extension tells.
+    ///
+    /// The configs are only deserialized, they're validated once they're trained on.
+    ///
+    /// # Args
+    /// * `path` - The path of the `.json` or `.toml` file.
+    ///
+    /// # Returns
+    /// The configuration in the file.
+    ///
+    /// # Errors
+    /// An `OrchErr::InvalidConfig` if the file can't be read, has an unknown extension or
+    /// doesn't hold a valid configuration, naming the offending field.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        let Some(format) = ConfigFormat::from_path(path) else {
+            let text = format!(
+                "the config file {} must have a .json or .toml extension",
+                path.display()
+            );
+            return Err(OrchErr::InvalidConfig(text));
+        };
+
+        let content = fs::read_to_string(path).map_err(|e| {
+            OrchErr::InvalidConfig(format!("cannot read config file {}: {e}", path.display()))
+        })?;
+
+        Self::parse(&content, format).map_err(|e| match e {
+            OrchErr::InvalidConfig(text) => {
+                OrchErr::InvalidConfig(format!("{}: {text}", path.display()))
+            }
+            e => e,
+        })
+    }
+
+    /// Parses a configuration in the given format.
+    ///
+    /// # Args
+    /// * `content` - The serialized configuration.
+    /// * `format` - The format it's serialized in.
+    ///
+    /// # Returns
+    /// The parsed configuration.
+    ///
+    /// # Errors
+    /// An `OrchErr::InvalidConfig` naming the offending field if the content doesn't hold
+    /// a valid configuration.
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self> {
+        match format {
+            ConfigFormat::Json => {
+                let mut de = serde_json::Deserializer::from_str(content);
+                serde_path_to_error::deserialize(&mut de).map_err(|e| {
+                    OrchErr::InvalidConfig(format!("at `{}`: {}", e.path(), e.inner()))
+                })
+            }
+            ConfigFormat::Toml => {
+                let de = toml::Deserializer::new(content);
+                serde_path_to_error::deserialize(de).map_err(|e| {
+                    // The toml errors span a few lines to point at the offending value.
+                    let inner = e.inner().message().to_string();
+                    OrchErr::InvalidConfig(format!("at `{}`: {inner}", e.path()))
+                })
+            }
+        }
+    }
+
+    /// Serializes the configuration in the given format.
+    ///
+    /// # Args
+    /// * `format` - The format to serialize it in.
+    ///
+    /// # Returns
+    /// The serialized configuration.
+    ///
+    /// # Errors
+    /// An `OrchErr::InvalidConfig` if the configuration can't be represented in the format.
+    pub fn to_string(&self, format: ConfigFormat) -> Result<String> {
+        let res = match format {
+            ConfigFormat::Json => serde_json::to_string_pretty(self).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::to_string_pretty(self).map_err(|e| e.to_string()),
+        };
+
+        res.map_err(|e| OrchErr::InvalidConfig(format!("cannot serialize the config: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    const CONFIG: &str = r#"{
+        "model": {
+            "layers": [
+                { "dense": { "output_size": 4, "init": "kaiming", "act_fn": { "sigmoid": { "amp": 1.0 } } } },
+                { "dense": { "output_size": 1, "init": "kaiming" } }
+            ]
+        },
+        "training": {
+            "addrs": ["node-0", "node-1", "node-2"],
+            "algorithm": {
+                "parameter_server": { "nservers": 1, "synchronizer": "non_blocking", "store": "blocking" }
+            },
+            "dataset": {
+                "src": { "inline": { "samples": [1.0, 2.0], "labels": [2.0] } },
+                "x_size": 2,
+                "y_size": 1
+            },
+            "optimizer": { "gradient_descent": { "lr": 0.01 } },
+            "loss_fn": "mse",
+            "batch_size": 1,
+            "max_epochs": 10,
+            "offline_epochs": 0,
+            "metric_barrier_every": 2,
+            "seed": 42
+        }
+    }"#;
+
+    #[test]
+    fn test_a_config_round_trips_through_both_formats() {
+        let config = ConfigFile::parse(CONFIG, ConfigFormat::Json).unwrap();
+        let expected = serde_json::to_value(&config).unwrap();
+
+        for (ext, format) in [("json", ConfigFormat::Json), ("toml", ConfigFormat::Toml)] {
+            let path = env::temp_dir().join(format!("{}.{ext}", Uuid::new_v4()));
+            fs::write(&path, config.to_string(format).unwrap()).unwrap();
+
+            let read = ConfigFile::load(&path);
+            fs::remove_file(&path).unwrap();
+
+            let read = serde_json::to_value(read.unwrap()).unwrap();
+            assert_eq!(read, expected, "the {ext} config changed on the round trip");
+        }
+    }
+
+    #[test]
+    fn test_an_invalid_field_is_named_in_the_error() {
+        let json = CONFIG.replace(r#""max_epochs": 10"#, r#""max_epochs": 0"#);
+        let toml = r#"
+            [model]
+            layers = []
+
+            [training]
+            addrs = ["node-0"]
+            algorithm = "gossip"
+        "#;
+
+        for (content, format, field) in [
+            (json.as_str(), ConfigFormat::Json, "training.max_epochs"),
+            (toml, ConfigFormat::Toml, "training.algorithm"),
+        ] {
+            let Err(OrchErr::InvalidConfig(text)) = ConfigFile::parse(content, format) else {
+                panic!("the {format:?} config should be invalid");
+            };
+
+            assert!(text.contains(&format!("`{field}`")), "{text}");
+        }
+    }
+}
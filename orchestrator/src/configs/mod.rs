@@ -1,26 +1,39 @@
 mod adapter;
+mod config_file;
 mod model;
 mod partition;
 mod stat_requester;
 mod training;
+mod validation_report;
 mod validator;
+mod warm_start;
 
-use std::num::NonZeroUsize;
+use std::{num::NonZeroUsize, path::PathBuf};
 
 use comms::specs::{machine_learning::TrainerSpec, server::ServerSpec, worker::WorkerSpec};
 
 pub use adapter::Adapter;
-pub use model::{ActFnConfig, LayerConfig, ModelConfig, ParamGenConfig};
+pub use config_file::{ConfigFile, ConfigFormat};
+pub use model::{ActFnConfig, HeadConfig, LayerConfig, ModelConfig, ParamGenConfig};
 pub use partition::Partition;
 pub use stat_requester::StatRequester;
 pub use training::{
-    AlgorithmConfig, DataSrc, DatasetConfig, EarlyStoppingConfig, LossFnConfig, OptimizerConfig,
-    SerializerConfig, StoreConfig, SynchronizerConfig, TrainingConfig,
+    AlgorithmConfig, BarrierTimeoutConfig, ClipGroupConfig, DataSrc, DatasetConfig,
+    DivergencePolicyConfig, EarlyStoppingConfig, EmptyShardConfig, FinalStepPolicyConfig,
+    LossFnConfig, LrScalingConfig, LrScheduleConfig, MonitorIntervalConfig, OptimizerConfig,
+    QuantizationConfig, ReductionConfig, SamplerConfig, SerializerConfig, StopConditionConfig,
+    StoreConfig, StrategyConfig, StreamFormatConfig, SynchronizerConfig, TimeoutPolicyConfig,
+    TrainingConfig, UpdateRuleConfig,
 };
 use uuid::Uuid;
+pub use validation_report::ValidationReport;
 pub use validator::Validator;
+pub use warm_start::WarmStart;
 
-use crate::sessions::{ConvergenceTracker, LossRecorder, SwitchTracker};
+use crate::sessions::{
+    ConvergenceTracker, DivergenceTracker, LossRecorder, MonitorCadence, RestartTracker,
+    SwitchTracker, TestSet,
+};
 
 /// An action taken by the orchestrator based on strategy switch for each worker.
 #[derive(Debug, Clone)]
@@ -65,8 +78,19 @@ pub struct OrchAdapt {
     pub input_size: NonZeroUsize,
     pub loss_recorder: LossRecorder,
     pub convergence_tracker: Option<ConvergenceTracker>,
+    pub divergence_tracker: Option<DivergenceTracker>,
+    pub monitor_cadence: MonitorCadence,
+    pub restart_tracker: Option<RestartTracker>,
     pub switch_tracking: Option<StrategySwitchTracking>,
     pub model_config: ModelConfig,
     pub algorithm_config: AlgorithmConfig,
     pub layer_param_offsets: Vec<(Uuid, usize, usize)>,
+    pub allow_worker_loss: bool,
+    pub ema: bool,
+    pub track_staleness: bool,
+    pub learning_curve_path: Option<PathBuf>,
+    pub snapshot_every: Option<NonZeroUsize>,
+    pub event_feed_path: Option<PathBuf>,
+    pub test_set: Option<TestSet>,
+    pub seed: Option<u64>,
 }
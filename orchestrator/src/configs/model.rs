@@ -1,8 +1,10 @@
-use std::num::NonZeroUsize;
+use std::{num::NonZeroUsize, path::PathBuf};
 
-use comms::floats::Float01;
+use comms::floats::{Float01, FloatPositive};
 use serde::{Deserialize, Serialize};
 
+use super::LossFnConfig;
+
 /// The `ParamGen` configuration.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -19,6 +21,8 @@ pub enum ParamGenConfig {
 }
 
 /// The `ActFn` configuration.
+///
+/// The `amp` of the sigmoid and the tanh scales their output, `amp * f(x)`.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ActFnConfig {
@@ -29,14 +33,25 @@ pub enum ActFnConfig {
 }
 
 /// The `Layer` configuration.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LayerConfig {
+    /// A dense layer, with `tie` set it's weights are the transpose of the ones of the
+    /// dense layer at that index, only it's biases are initialized with `init`.
+    ///
+    /// The `init_gain` scales the randomly initialized parameters, e.g. `sqrt(2)` for a
+    /// ReLU, the constant ones are left as they are.
     Dense {
         output_size: NonZeroUsize,
         init: ParamGenConfig,
         #[serde(default)]
         act_fn: Option<ActFnConfig>,
+        #[serde(default)]
+        tie: Option<usize>,
+        #[serde(default)]
+        frozen: bool,
+        #[serde(default)]
+        init_gain: Option<FloatPositive>,
     },
     Conv {
         /// The in channels, height and width of the input.
@@ -48,6 +63,10 @@ pub enum LayerConfig {
         init: ParamGenConfig,
         #[serde(default)]
         act_fn: Option<ActFnConfig>,
+        #[serde(default)]
+        frozen: bool,
+        #[serde(default)]
+        init_gain: Option<FloatPositive>,
     },
     MaxPooling {
         /// The in channels, height and width of the input.
@@ -59,6 +78,17 @@ pub enum LayerConfig {
         #[serde(default)]
         act_fn: Option<ActFnConfig>,
     },
+    /// A layer registered under `name` in the layer registry of the orchestrator and of
+    /// every node, built out of `params`. It's parameters, as many as the layer reports,
+    /// are initialized with `init`.
+    Custom {
+        name: String,
+        output_size: NonZeroUsize,
+        #[serde(default)]
+        params: serde_json::Value,
+        #[serde(default)]
+        init: Option<ParamGenConfig>,
+    },
 }
 
 impl LayerConfig {
@@ -68,7 +98,9 @@ impl LayerConfig {
     /// The layer's output size.
     pub fn output_size(&self) -> NonZeroUsize {
         match *self {
-            LayerConfig::Dense { output_size, .. } => output_size,
+            LayerConfig::Dense { output_size, .. } | LayerConfig::Custom { output_size, .. } => {
+                output_size
+            }
             LayerConfig::Conv {
                 input_dim,
                 kernel_dim,
@@ -110,17 +142,96 @@ impl LayerConfig {
     /// `Some(size)` for layers with a fixed input shape (`Conv`), `None` otherwise.
     pub fn expected_input_size(&self) -> Option<NonZeroUsize> {
         match *self {
-            LayerConfig::Dense { .. } => None,
+            LayerConfig::Dense { .. } | LayerConfig::Custom { .. } => None,
             LayerConfig::Conv { input_dim, .. } | LayerConfig::MaxPooling { input_dim, .. } => {
                 NonZeroUsize::new(input_dim.0.get() * input_dim.1.get() * input_dim.2.get())
             }
         }
     }
+
+    /// The activation function applied after this layer.
+    ///
+    /// # Returns
+    /// The layer's activation function, if any.
+    pub fn act_fn(&self) -> Option<ActFnConfig> {
+        match *self {
+            LayerConfig::Dense { act_fn, .. }
+            | LayerConfig::Conv { act_fn, .. }
+            | LayerConfig::MaxPooling { act_fn, .. } => act_fn,
+            LayerConfig::Custom { .. } => None,
+        }
+    }
+
+    /// Whether the layer's parameters are left untouched by the training.
+    ///
+    /// # Returns
+    /// `true` if the layer is frozen, layers without parameters never are.
+    pub fn frozen(&self) -> bool {
+        match *self {
+            LayerConfig::Dense { frozen, .. } | LayerConfig::Conv { frozen, .. } => frozen,
+            LayerConfig::MaxPooling { .. } | LayerConfig::Custom { .. } => false,
+        }
+    }
+
+    /// The amount of biases of the layer, they're the last of it's parameters.
+    ///
+    /// # Returns
+    /// The layer's amount of biases or `None` if it has no known biases.
+    pub fn biases(&self) -> Option<usize> {
+        match *self {
+            LayerConfig::Dense { output_size, .. } => Some(output_size.get()),
+            LayerConfig::Conv { kernel_dim, .. } => Some(kernel_dim.0.get()),
+            LayerConfig::MaxPooling { .. } | LayerConfig::Custom { .. } => None,
+        }
+    }
 }
 
 /// The `Model` configuration.
+///
+/// With `checkpoint_every` set the workers only keep the activations of every that many
+/// layers through the forward pass, computing the rest again during the backward pass.
+/// With `activation_clamp` set the inputs of every sigmoid and tanh are clamped to
+/// `[-activation_clamp, activation_clamp]`, so large inputs can't overflow into `NaN`s.
+/// With `warm_start` set the layers saved in that `.safetensors` file start from their
+/// trained weights, matched to the model's layers by index.
+/// With `heads` set the model branches into them after it's layers, their parameters
+/// following the shared ones.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ModelConfig {
     pub layers: Vec<LayerConfig>,
+    #[serde(default)]
+    pub heads: Vec<HeadConfig>,
+    #[serde(default)]
+    pub checkpoint_every: Option<NonZeroUsize>,
+    #[serde(default)]
+    pub activation_clamp: Option<FloatPositive>,
+    #[serde(default)]
+    pub warm_start: Option<PathBuf>,
+}
+
+impl ModelConfig {
+    /// Iterates the model's layers, the heads' following the shared ones.
+    ///
+    /// # Returns
+    /// An iterator over every layer of the model.
+    pub fn all_layers(&self) -> impl Iterator<Item = &LayerConfig> {
+        let heads = self.heads.iter().flat_map(|head| &head.layers);
+        self.layers.iter().chain(heads)
+    }
+}
+
+/// The `Head` configuration, a branch of a multi task model taking the output of the
+/// shared layers to predict some of the targets.
+///
+/// The heads predict consecutive columns of the labels in the order they're given, the
+/// model's loss is the sum of their losses scaled by their `weight`, `1` if not set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct HeadConfig {
+    pub layers: Vec<LayerConfig>,
+    pub loss_fn: LossFnConfig,
+    pub y_size: NonZeroUsize,
+    #[serde(default)]
+    pub weight: Option<FloatPositive>,
 }
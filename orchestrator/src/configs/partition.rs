@@ -1,4 +1,6 @@
-use std::path::PathBuf;
+use std::{num::NonZeroUsize, path::PathBuf};
+
+use comms::specs::machine_learning::SyntheticSpec;
 
 /// The metadata of a dataset partition.
 #[derive(PartialEq, Debug, Clone)]
@@ -15,4 +17,44 @@ pub enum Partition<'a> {
         samples_size: u64,
         labels_size: u64,
     },
+    Synthetic(SyntheticSpec),
+    /// Some contiguous rows of a CSR dataset, `indptr` keeps the offsets of the whole
+    /// matrix and `indices` and `values` only hold the rows' entries.
+    Csr {
+        indptr: &'a [usize],
+        indices: &'a [usize],
+        values: &'a [f32],
+        labels: &'a [f32],
+    },
+}
+
+impl Partition<'_> {
+    /// The amount of samples in the partition.
+    ///
+    /// # Args
+    /// * `x_size` - The number of input features per sample.
+    ///
+    /// # Returns
+    /// The amount of samples the partition holds.
+    pub fn nsamples(&self, x_size: NonZeroUsize) -> u64 {
+        let len = match self {
+            Partition::Inline { samples, .. } => samples.len() as u64,
+            Partition::Local { samples_size, .. } => samples_size / size_of::<f32>() as u64,
+            Partition::Synthetic(synthetic) => return synthetic.rows.end - synthetic.rows.start,
+            Partition::Csr { indptr, .. } => return indptr.len().saturating_sub(1) as u64,
+        };
+
+        len / x_size.get() as u64
+    }
+
+    /// The specification of the partition's rows if the worker generates them itself.
+    ///
+    /// # Returns
+    /// The synthetic shard's spec or `None` if the rows are sent to the worker.
+    pub fn synthetic_spec(&self) -> Option<SyntheticSpec> {
+        match self {
+            Partition::Synthetic(synthetic) => Some(synthetic.clone()),
+            _ => None,
+        }
+    }
 }
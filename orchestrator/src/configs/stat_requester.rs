@@ -9,6 +9,7 @@ use comms::{
 const PING_ROUNDS: usize = 10;
 
 /// Obtains the statistics from the nodes in the network.
+#[derive(Default)]
 pub struct StatRequester;
 
 impl StatRequester {
@@ -20,8 +21,10 @@ impl StatRequester {
         Self
     }
 
-    /// Connects to the nodes given their addresses and calculates requests the relevant
-    /// statistics for training.
+    /// Requests the relevant statistics for training to the connected nodes.
+    ///
+    /// The nodes are left connected and idle, so they can be bootstrapped through the
+    /// same handles afterwards.
     ///
     /// # Args
     /// * `handles` - The handles for communicating with the nodes.
@@ -30,15 +33,13 @@ impl StatRequester {
     /// The accumulated statistic responses or an io error if occurred.
     pub async fn obtain_stats<T>(
         &mut self,
-        mut handles: BTreeMap<String, NodeHandle<T>>,
+        handles: &mut BTreeMap<String, NodeHandle<T>>,
     ) -> io::Result<BTreeMap<String, Vec<StatResponse>>>
     where
         T: TransportLayer,
     {
-        self.request_stats(&mut handles).await?;
-        let stats = self.wait_for_responses(&mut handles).await?;
-        self.disconnect_nodes(handles.values_mut()).await;
-        Ok(stats)
+        self.request_stats(handles).await?;
+        self.wait_for_responses(handles).await
     }
 
     /// Requests the nodes to calculate their ping latency between each other.
@@ -93,18 +94,4 @@ impl StatRequester {
 
         Ok(stats)
     }
-
-    /// Disconnects the node handles from the network.
-    ///
-    /// # Args
-    /// * `handles` - The handles for communicating with the nodes.
-    async fn disconnect_nodes<'a, I, T>(&self, handles: I)
-    where
-        T: TransportLayer + 'a,
-        I: Iterator<Item = &'a mut NodeHandle<T>> + 'a,
-    {
-        for node_handle in handles {
-            let _ = node_handle.disconnect().await;
-        }
-    }
 }
@@ -1,6 +1,6 @@
 use std::{
     fmt::{self, Display, Formatter},
-    num::NonZeroUsize,
+    num::{NonZeroU64, NonZeroUsize},
     path::PathBuf,
 };
 
@@ -13,6 +13,10 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct EarlyStoppingConfig {
     pub tolerance: FloatNonNegative,
+    /// The amount of epochs run before the losses start being checked, so a noisy start
+    /// doesn't stop the training.
+    #[serde(default)]
+    pub min_epochs: usize,
 }
 
 impl Display for EarlyStoppingConfig {
@@ -60,6 +64,44 @@ pub enum DataSrc {
         samples: Vec<f32>,
         labels: Vec<f32>,
     },
+    /// Samples given as a sparse matrix in CSR format, the labels stay dense.
+    ///
+    /// The workers receive their rows still sparse and the model's first layer skips
+    /// their zero entries, so it must be a dense one and the model can't keep checkpoints.
+    Csr {
+        indptr: Vec<usize>,
+        indices: Vec<usize>,
+        values: Vec<f32>,
+        labels: Vec<f32>,
+    },
+    /// Rows read from the orchestrator's stdin until it's closed, each holding a sample
+    /// followed by it's labels.
+    ///
+    /// The whole stream is read before the dataset is partitioned, so it can be piped
+    /// from a generator without a temporary file.
+    Stdin {
+        format: StreamFormatConfig,
+    },
+    /// Rows generated out of a random linear map of the samples plus gaussian noise.
+    ///
+    /// The rows are generated before the dataset is partitioned, the same seed always
+    /// gives the same rows and every worker gets a disjoint slice of them.
+    Synthetic {
+        nsamples: NonZeroUsize,
+        #[serde(default)]
+        noise_std: f32,
+        seed: u64,
+    },
+}
+
+/// The encoding of a dataset streamed through stdin.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamFormatConfig {
+    Csv,
+    Tsv,
+    /// Packed little endian `f32` values.
+    Raw,
 }
 
 /// The `Dataset` configuration.
@@ -69,6 +111,14 @@ pub struct DatasetConfig {
     pub src: DataSrc,
     pub x_size: NonZeroUsize,
     pub y_size: NonZeroUsize,
+    /// The fraction of the rows held out of the training, the model is tested on them
+    /// once the training stops.
+    ///
+    /// The held out rows are kept in the orchestrator's memory until then. A local dataset
+    /// is streamed while it's split, the rows to train on are copied to a temporary file
+    /// instead, so holding out it's test split takes as much disk as the dataset itself.
+    #[serde(default)]
+    pub test_split: f32,
 }
 
 /// The `Synchronizer` configuration.
@@ -79,6 +129,74 @@ pub enum SynchronizerConfig {
     NonBlocking,
 }
 
+/// How the barrier synchronizer combines the workers' gradients.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReductionConfig {
+    Mean,
+    #[default]
+    Sum,
+}
+
+/// What the barrier synchronizer does once it's workers take too long to arrive.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutPolicyConfig {
+    #[default]
+    Fail,
+    RetryOnce {
+        after_ms: NonZeroU64,
+    },
+    /// Requires `allow_worker_loss`, since the workers left behind are dropped.
+    DropLaggard,
+}
+
+/// How long the barrier synchronizer waits for it's workers on every step.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BarrierTimeoutConfig {
+    pub after_ms: NonZeroU64,
+    #[serde(default)]
+    pub policy: TimeoutPolicyConfig,
+}
+
+/// Whether the servers send the workers the parameters after or before applying their gradient.
+///
+/// `send_then_update` cuts the workers' wait for the update, but every gradient is then
+/// computed over parameters one update behind.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateRuleConfig {
+    #[default]
+    UpdateThenSend,
+    SendThenUpdate,
+}
+
+/// How the workers fit the model to their dataset.
+///
+/// `closed_form_lin_reg` solves a linear regression exactly instead of descending it's
+/// gradient, the model must be a single dense layer without an activation function
+/// trained with the mean squared error.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyConfig {
+    #[default]
+    Backprop,
+    ClosedFormLinReg,
+}
+
+/// What to do with the workers whose dataset partition ends up empty, when there are more
+/// workers than rows.
+///
+/// `skip` keeps them in the training sending zero gradients so the barrier stays balanced,
+/// they report no losses. `fail` refuses to start the training.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyShardConfig {
+    #[default]
+    Fail,
+    Skip,
+}
+
 /// The `Store` configuration.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -113,6 +231,132 @@ pub enum SerializerConfig {
     SparseCapable {
         r: Float01,
     },
+    TopK {
+        density: Float01,
+    },
+}
+
+/// The parameters' broadcast `Quantization` configuration.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum QuantizationConfig {
+    #[default]
+    None,
+    Int8,
+}
+
+/// When the workers stop training.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopConditionConfig {
+    MaxEpochs(NonZeroUsize),
+    MaxSteps(NonZeroUsize),
+    MaxWallTimeSecs(NonZeroU64),
+}
+
+/// What the workers do with the epoch a `MaxSteps` stop condition falls in the middle of.
+///
+/// `ApplyPartial` applies the gradient of the epoch's batches up to the last step and
+/// `DiscardPartial` skips the whole epoch, leaving the weights as they were after the
+/// previous one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinalStepPolicyConfig {
+    #[default]
+    ApplyPartial,
+    DiscardPartial,
+}
+
+/// How the workers order the rows of their dataset partition on every epoch.
+///
+/// `WeightedByClass` draws the rows with replacement, weighting each by the inverse of the
+/// frequency of it's class so the batches come out roughly class balanced. The class of a
+/// row is the value of it's label's `label_col` column, a whole number below `num_classes`.
+/// Every worker counts the frequencies over it's own partition.
+///
+/// `GlobalOrder` gives every worker the whole dataset and shuffles it in an order decided by
+/// the seed and the epoch alone, dealing every batch of `batch_size` rows between the workers.
+/// The batches the model sees don't change with the amount of workers, it requires a seed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplerConfig {
+    Sequential,
+    #[default]
+    Shuffle,
+    WeightedByClass {
+        #[serde(default)]
+        label_col: usize,
+        num_classes: NonZeroUsize,
+    },
+    GlobalOrder,
+}
+
+/// How often the orchestrator checks the workers' losses against the early stopping criteria.
+///
+/// The checks happen at the end of the training rounds, a check due in the middle of
+/// one waits until the workers synchronized.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorIntervalConfig {
+    Epochs(NonZeroUsize),
+    Steps(NonZeroUsize),
+}
+
+/// How the servers' learning rate scales with the amount of workers.
+///
+/// With a barrier every step is taken over the batches of all the workers, `Linear`
+/// multiplies the learning rate by the amount of workers and `SqrtLinear` by it's square root.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LrScalingConfig {
+    #[default]
+    None,
+    Linear,
+    SqrtLinear,
+}
+
+/// What the orchestrator does once the loss grows for 3 rounds in a row.
+///
+/// `HalveLr` halves the servers' learning rate up to `max_reductions` times, letting the
+/// training go on as is afterwards, and `Abort` stops the training.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DivergencePolicyConfig {
+    #[default]
+    None,
+    HalveLr {
+        max_reductions: usize,
+    },
+    Abort,
+}
+
+/// A range of the model's parameters whose gradient is clipped to it's own maximum norm.
+///
+/// The parameters are numbered following the order of the model's layers and the
+/// range must start and end at the boundaries of a layer.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ClipGroupConfig {
+    pub start: usize,
+    pub end: usize,
+    pub max_norm: FloatPositive,
+}
+
+/// The learning rate schedule followed by the parameter servers.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LrScheduleConfig {
+    WarmRestarts {
+        t_0: NonZeroUsize,
+        #[serde(default)]
+        t_mult: Option<NonZeroUsize>,
+        #[serde(default = "default_min_lr")]
+        min_lr: FloatPositive,
+    },
+}
+
+/// The learning rate the warm restarts anneal down to, low enough for any optimizer's.
+fn default_min_lr() -> FloatPositive {
+    FloatPositive::new(1e-6).unwrap()
 }
 
 /// The `Training` configuration.
@@ -122,6 +366,8 @@ pub struct TrainingConfig {
     pub algorithm: AlgorithmConfig,
     #[serde(default)]
     pub serializer: SerializerConfig,
+    #[serde(default)]
+    pub quantization: QuantizationConfig,
     pub dataset: DatasetConfig,
     pub optimizer: OptimizerConfig,
     pub loss_fn: LossFnConfig,
@@ -132,4 +378,70 @@ pub struct TrainingConfig {
     pub seed: Option<u64>,
     #[serde(default)]
     pub early_stopping: Option<EarlyStoppingConfig>,
+    #[serde(default)]
+    pub divergence_policy: DivergencePolicyConfig,
+    #[serde(default)]
+    pub monitor_interval: Option<MonitorIntervalConfig>,
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    #[serde(default)]
+    pub allow_worker_loss: bool,
+    #[serde(default)]
+    pub min_workers: Option<usize>,
+    #[serde(default)]
+    pub max_workers: Option<usize>,
+    #[serde(default)]
+    pub max_frame_size: Option<usize>,
+    #[serde(default)]
+    pub chunk_size: Option<NonZeroUsize>,
+    #[serde(default)]
+    pub slow_send_ms: Option<u64>,
+    #[serde(default)]
+    pub reduction: ReductionConfig,
+    #[serde(default)]
+    pub barrier_timeout: Option<BarrierTimeoutConfig>,
+    #[serde(default)]
+    pub update_rule: UpdateRuleConfig,
+    #[serde(default)]
+    pub stop_condition: Option<StopConditionConfig>,
+    #[serde(default)]
+    pub final_step: FinalStepPolicyConfig,
+    #[serde(default)]
+    pub sampler: SamplerConfig,
+    #[serde(default)]
+    pub lr_schedule: Option<LrScheduleConfig>,
+    #[serde(default)]
+    pub lr_scaling: LrScalingConfig,
+    #[serde(default)]
+    pub ema_decay: Option<Float01>,
+    #[serde(default)]
+    pub server_momentum: Option<Float01>,
+    #[serde(default)]
+    pub track_staleness: bool,
+    #[serde(default)]
+    pub metric_barrier_every: Option<NonZeroUsize>,
+    #[serde(default)]
+    pub accum_f64: bool,
+    #[serde(default)]
+    pub accum_f64_layers: Vec<usize>,
+    #[serde(default)]
+    pub bias_only: bool,
+    #[serde(default)]
+    pub server_synchronizers: Vec<SynchronizerConfig>,
+    #[serde(default)]
+    pub require_compression: bool,
+    #[serde(default)]
+    pub clip_groups: Vec<ClipGroupConfig>,
+    #[serde(default)]
+    pub learning_curve_path: Option<PathBuf>,
+    #[serde(default)]
+    pub snapshot_every: Option<NonZeroUsize>,
+    #[serde(default)]
+    pub event_feed_path: Option<PathBuf>,
+    #[serde(default)]
+    pub latency_warmup_rounds: Option<NonZeroUsize>,
+    #[serde(default)]
+    pub strategy: StrategyConfig,
+    #[serde(default)]
+    pub empty_shard: EmptyShardConfig,
 }
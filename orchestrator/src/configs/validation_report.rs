@@ -0,0 +1,127 @@
+use std::fmt::{self, Display, Formatter};
+
+use comms::specs::worker::AlgorithmSpec;
+
+use super::{OrchAdapt, ServerAdapt, WorkerAdapt, WorkerPostAction};
+
+/// The outcome of validating a training's configs against the network without training.
+///
+/// Summarizes how the training would be laid out, so the cluster's resources can be
+/// checked before committing them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    nparams: usize,
+    shards: Vec<(String, usize)>,
+    splits: Vec<(String, u64)>,
+}
+
+impl ValidationReport {
+    /// Creates a new `ValidationReport` from the specifications that would be sent to the nodes.
+    ///
+    /// # Args
+    /// * `orch` - The orchestrator's adapted values.
+    /// * `workers` - The workers' adapted values.
+    /// * `servers` - The servers' adapted values.
+    ///
+    /// # Returns
+    /// A new `ValidationReport` instance.
+    pub fn new(orch: &OrchAdapt, workers: &[WorkerAdapt<'_>], servers: &[ServerAdapt]) -> Self {
+        let mut shards: Vec<_> = servers
+            .iter()
+            .map(|server| (server.addr.clone(), server.spec.param_gen.size()))
+            .collect();
+
+        // The strategy switch's servers are workers upgraded after the all-reduce phase.
+        if let Some(tracking) = &orch.switch_tracking {
+            let upgrades =
+                workers
+                    .iter()
+                    .zip(&tracking.post_actions)
+                    .filter_map(|(worker, action)| match action {
+                        WorkerPostAction::Upgrade { spec, .. } => {
+                            Some((worker.addr.clone(), spec.param_gen.size()))
+                        }
+                        WorkerPostAction::Switch { .. } => None,
+                    });
+
+            shards.extend(upgrades);
+        }
+
+        let nparams = match workers.first().map(|worker| &worker.spec.algorithm) {
+            Some(AlgorithmSpec::ParameterServer { server_sizes, .. }) => server_sizes.iter().sum(),
+            Some(
+                AlgorithmSpec::AllReduce { param_gen, .. }
+                | AlgorithmSpec::Evaluate { param_gen, .. },
+            ) => param_gen.size(),
+            None => shards.iter().map(|(_, size)| size).sum(),
+        };
+
+        let splits = workers
+            .iter()
+            .map(|worker| {
+                let x_size = worker.spec.trainer.dataset.x_size;
+                (worker.addr.clone(), worker.partition.nsamples(x_size))
+            })
+            .collect();
+
+        Self {
+            nparams,
+            shards,
+            splits,
+        }
+    }
+
+    /// The amount of parameters of the model.
+    ///
+    /// # Returns
+    /// The model's parameter count.
+    pub fn nparams(&self) -> usize {
+        self.nparams
+    }
+
+    /// The memory the model's parameters take on a node holding all of them.
+    ///
+    /// # Returns
+    /// The size of the parameters in bytes.
+    pub fn param_bytes(&self) -> usize {
+        self.nparams * size_of::<f32>()
+    }
+
+    /// How the parameters are sharded between the servers.
+    ///
+    /// # Returns
+    /// The address of each server along with the amount of parameters it holds, empty
+    /// if every worker holds the whole model.
+    pub fn shards(&self) -> &[(String, usize)] {
+        &self.shards
+    }
+
+    /// How the dataset is split between the workers.
+    ///
+    /// # Returns
+    /// The address of each worker along with the amount of samples it trains on.
+    pub fn splits(&self) -> &[(String, u64)] {
+        &self.splits
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} parameters ({} bytes)",
+            self.nparams,
+            self.param_bytes()
+        )?;
+
+        for (addr, size) in &self.shards {
+            write!(f, "\n  server {addr}: {size} parameters")?;
+        }
+
+        for (addr, nsamples) in &self.splits {
+            write!(f, "\n  worker {addr}: {nsamples} samples")?;
+        }
+
+        Ok(())
+    }
+}
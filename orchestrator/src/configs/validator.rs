@@ -1,6 +1,13 @@
-use std::fs;
+use std::{fs, mem, num::NonZeroUsize};
 
-use super::{AlgorithmConfig, DataSrc, DatasetConfig, LayerConfig, ModelConfig, TrainingConfig};
+use machine_learning::{arch::layers::build_custom_layer, datasets::CsrMatrix};
+
+use super::{
+    ActFnConfig, AlgorithmConfig, BarrierTimeoutConfig, DataSrc, DatasetConfig,
+    DivergencePolicyConfig, FinalStepPolicyConfig, LayerConfig, LossFnConfig, LrScalingConfig,
+    ModelConfig, SamplerConfig, StopConditionConfig, StrategyConfig, SynchronizerConfig,
+    TimeoutPolicyConfig, TrainingConfig, UpdateRuleConfig,
+};
 use crate::error::{OrchErr, Result};
 
 /// Validates orchestrator configs before adaptation, ensuring all invariants
@@ -29,6 +36,128 @@ impl Validator {
         self.validate_model(model)?;
         self.validate_training(training)?;
         self.validate_dimensions(model, training)?;
+        self.validate_strategy(model, training)?;
+        self.validate_bias_only(model, training)?;
+        self.validate_accum_f64_layers(model, training)?;
+        self.validate_sparse_input(model, training)?;
+        Ok(())
+    }
+
+    /// Validates that the model can be fit with the training's strategy.
+    ///
+    /// # Args
+    /// * `model` - The model architecture and initialization configuration.
+    /// * `training` - The training configuration.
+    ///
+    /// # Errors
+    /// An `OrchErr` if the closed form linear regression is asked for a model that isn't a
+    /// single untied dense layer without an activation function nor heads trained with the
+    /// mean squared error.
+    fn validate_strategy(&self, model: &ModelConfig, training: &TrainingConfig) -> Result<()> {
+        if !matches!(training.strategy, StrategyConfig::ClosedFormLinReg) {
+            return Ok(());
+        }
+
+        let linear = matches!(
+            model.layers.as_slice(),
+            [LayerConfig::Dense {
+                act_fn: None,
+                tie: None,
+                frozen: false,
+                ..
+            }]
+        );
+
+        if !linear || !model.heads.is_empty() || !matches!(training.loss_fn, LossFnConfig::Mse) {
+            let text = "the closed form linear regression needs a single untied and unfrozen dense layer without an activation function and the mse loss";
+            return Err(OrchErr::InvalidConfig(text.into()));
+        }
+
+        Ok(())
+    }
+
+    /// Validates that the model can take the sparse samples of a CSR dataset.
+    ///
+    /// # Args
+    /// * `model` - The model architecture and initialization configuration.
+    /// * `training` - The training configuration.
+    ///
+    /// # Errors
+    /// An `OrchErr` if the dataset is a CSR one and the model's first layer isn't a dense
+    /// one or the model keeps checkpoints.
+    fn validate_sparse_input(&self, model: &ModelConfig, training: &TrainingConfig) -> Result<()> {
+        if !matches!(training.dataset.src, DataSrc::Csr { .. }) {
+            return Ok(());
+        }
+
+        if !matches!(model.layers.first(), Some(LayerConfig::Dense { .. })) {
+            let text = "the sparse samples of a csr dataset need a dense first layer";
+            return Err(OrchErr::InvalidConfig(text.into()));
+        }
+
+        if model.checkpoint_every.is_some() {
+            let text = "the checkpoints can't keep the sparse samples of a csr dataset, unset checkpoint_every";
+            return Err(OrchErr::InvalidConfig(text.into()));
+        }
+
+        Ok(())
+    }
+
+    /// Validates that a bias only training has biases to train.
+    ///
+    /// # Args
+    /// * `model` - The model architecture and initialization configuration.
+    /// * `training` - The training configuration.
+    ///
+    /// # Errors
+    /// An `OrchErr` if `bias_only` is set and none of the model's layers have biases.
+    fn validate_bias_only(&self, model: &ModelConfig, training: &TrainingConfig) -> Result<()> {
+        if training.bias_only && model.all_layers().all(|layer| layer.biases().is_none()) {
+            let text =
+                "bias_only trains the biases of the dense and conv layers, the model has none";
+            return Err(OrchErr::InvalidConfig(text.into()));
+        }
+
+        Ok(())
+    }
+
+    /// Validates the layers whose gradients the servers add up in `f64`.
+    ///
+    /// # Args
+    /// * `model` - The model architecture and initialization configuration.
+    /// * `training` - The training configuration.
+    ///
+    /// # Errors
+    /// An `OrchErr` if a layer isn't one of the model's or is listed twice, or if
+    /// `accum_f64` already adds every layer up in `f64`.
+    fn validate_accum_f64_layers(
+        &self,
+        model: &ModelConfig,
+        training: &TrainingConfig,
+    ) -> Result<()> {
+        let layers = &training.accum_f64_layers;
+
+        if training.accum_f64 && !layers.is_empty() {
+            let text =
+                "accum_f64 already adds every layer up in f64, accum_f64_layers is redundant";
+            return Err(OrchErr::InvalidConfig(text.into()));
+        }
+
+        let nlayers = model.all_layers().count();
+        let mut seen = vec![false; nlayers];
+
+        for &layer in layers {
+            if layer >= nlayers {
+                let text = format!("accum_f64_layers lists layer {layer}, the model has {nlayers}");
+                return Err(OrchErr::InvalidConfig(text));
+            }
+
+            if mem::replace(&mut seen[layer], true) {
+                let text = format!("accum_f64_layers lists layer {layer} twice");
+                return Err(OrchErr::InvalidConfig(text));
+            }
+        }
+
         Ok(())
     }
 
@@ -40,26 +169,42 @@ impl Validator {
     ///
     /// # Errors
     /// An `OrchErr` if a layer's expected input does not match the size it receives,
-    /// or if the model's output size does not match the dataset's `y_size`.
+    /// if a dense layer is tied to one that isn't an earlier untied dense layer of the
+    /// transposed dimension or if the model's output size does not match the dataset's `y_size`.
+    /// With heads, every head's output must match it's `y_size` and they must add up to
+    /// the dataset's `y_size`.
     fn validate_dimensions(&self, model: &ModelConfig, training: &TrainingConfig) -> Result<()> {
-        let mut input_size = training.dataset.x_size;
+        let output_size = self.validate_layer_dims(&model.layers, training.dataset.x_size)?;
 
-        for layer in &model.layers {
-            if let Some(expected) = layer.expected_input_size()
-                && expected != input_size
-            {
+        if model.heads.is_empty() {
+            if output_size != training.dataset.y_size {
                 let text = format!(
-                    "layer expects an input of {expected} but the incoming size is {input_size}"
+                    "the model's output size ({output_size}) must match the dataset's y_size ({})",
+                    training.dataset.y_size
                 );
                 return Err(OrchErr::InvalidConfig(text));
             }
 
-            input_size = layer.output_size();
+            return Ok(());
         }
 
-        if input_size != training.dataset.y_size {
+        for (i, head) in model.heads.iter().enumerate() {
+            let head_output_size = self.validate_layer_dims(&head.layers, output_size)?;
+
+            if head_output_size != head.y_size {
+                let text = format!(
+                    "head {i}'s output size ({head_output_size}) must match it's y_size ({})",
+                    head.y_size
+                );
+                return Err(OrchErr::InvalidConfig(text));
+            }
+        }
+
+        let y_size: usize = model.heads.iter().map(|head| head.y_size.get()).sum();
+
+        if y_size != training.dataset.y_size.get() {
             let text = format!(
-                "the model's output size ({input_size}) must match the dataset's y_size ({})",
+                "the heads' y_sizes add up to {y_size} but the dataset's y_size is {}",
                 training.dataset.y_size
             );
             return Err(OrchErr::InvalidConfig(text));
@@ -68,6 +213,69 @@ impl Validator {
         Ok(())
     }
 
+    /// Validates the dimensions of a sequence of layers, each fed the previous one's output.
+    ///
+    /// # Args
+    /// * `layers` - The layers' configurations.
+    /// * `input_size` - The size of the first layer's input.
+    ///
+    /// # Returns
+    /// The output size of the last layer.
+    ///
+    /// # Errors
+    /// An `OrchErr` if a layer's expected input does not match the size it receives or
+    /// if a dense layer is tied to one that isn't an earlier untied dense layer of the
+    /// transposed dimension.
+    fn validate_layer_dims(
+        &self,
+        layers: &[LayerConfig],
+        mut input_size: NonZeroUsize,
+    ) -> Result<NonZeroUsize> {
+        let mut input_sizes = Vec::with_capacity(layers.len());
+
+        for (i, layer) in layers.iter().enumerate() {
+            if let Some(expected) = layer.expected_input_size()
+                && expected != input_size
+            {
+                let text = format!(
+                    "layer expects an input of {expected} but the incoming size is {input_size}"
+                );
+                return Err(OrchErr::InvalidConfig(text));
+            }
+
+            if let LayerConfig::Dense {
+                output_size,
+                tie: Some(source),
+                ..
+            } = *layer
+            {
+                let transposed = match layers.get(source) {
+                    Some(LayerConfig::Dense {
+                        output_size: source_output,
+                        tie: None,
+                        ..
+                    }) if source < i => {
+                        (input_sizes[source], *source_output) == (output_size, input_size)
+                    }
+                    _ => false,
+                };
+
+                if !transposed {
+                    let text = format!(
+                        "dense layer {i} can only be tied to an earlier untied dense layer of the transposed dimension, got layer {source}"
+                    );
+                    return Err(OrchErr::InvalidConfig(text));
+                }
+            }
+
+            input_sizes.push(input_size);
+
+            input_size = layer.output_size();
+        }
+
+        Ok(input_size)
+    }
+
     /// Validates the model's configuration.
     ///
     /// # Args
@@ -82,7 +290,26 @@ impl Validator {
             ));
         }
 
-        for layer in &model.layers {
+        for head in &model.heads {
+            let tied = head
+                .layers
+                .iter()
+                .any(|layer| matches!(layer, LayerConfig::Dense { tie: Some(_), .. }));
+
+            if tied {
+                let text = "the layers of a head can't be tied";
+                return Err(OrchErr::InvalidConfig(text.into()));
+            }
+        }
+
+        for layer in model.all_layers() {
+            if let Some(ActFnConfig::Sigmoid { amp } | ActFnConfig::Tanh { amp }) = layer.act_fn()
+                && (amp == 0.0 || !amp.is_finite())
+            {
+                let text = format!("activation amplitude must be finite and non zero, got {amp}");
+                return Err(OrchErr::InvalidConfig(text));
+            }
+
             match layer {
                 LayerConfig::Dense { .. } => {
                     continue;
@@ -107,6 +334,17 @@ impl Validator {
                         return Err(OrchErr::InvalidConfig(details.into()));
                     }
                 }
+                LayerConfig::Custom {
+                    name, params, init, ..
+                } => {
+                    let custom = build_custom_layer(name, params)
+                        .map_err(|e| OrchErr::InvalidConfig(e.to_string()))?;
+
+                    if custom.size() > 0 && init.is_none() {
+                        let text = format!("the custom layer {name} has parameters but no init");
+                        return Err(OrchErr::InvalidConfig(text));
+                    }
+                }
             }
         }
 
@@ -133,10 +371,59 @@ impl Validator {
             return Err(OrchErr::InvalidConfig(text));
         }
 
+        if let AlgorithmConfig::AllReduce = training.algorithm {
+            self.validate_serverless(training)?;
+        }
+
+        self.validate_synchronizers(training)?;
+
+        if training.final_step == FinalStepPolicyConfig::DiscardPartial
+            && !matches!(
+                training.stop_condition,
+                Some(StopConditionConfig::MaxSteps(_))
+            )
+        {
+            let text = "the discard_partial final_step requires a max_steps stop_condition".into();
+            return Err(OrchErr::InvalidConfig(text));
+        }
+
+        if let SamplerConfig::WeightedByClass { label_col, .. } = training.sampler
+            && label_col >= training.dataset.y_size.get()
+        {
+            let text = format!(
+                "the sampler's label_col ({label_col}) must be a column of the labels, below y_size ({})",
+                training.dataset.y_size
+            );
+            return Err(OrchErr::InvalidConfig(text));
+        }
+
+        if training.sampler == SamplerConfig::GlobalOrder {
+            if training.seed.is_none() {
+                let text = "the global_order sampler requires a seed".into();
+                return Err(OrchErr::InvalidConfig(text));
+            }
+
+            if let AlgorithmConfig::StrategySwitch { .. } = training.algorithm {
+                let text = "the global_order sampler can't be used with strategy_switch".into();
+                return Err(OrchErr::InvalidConfig(text));
+            }
+        }
+
+        if let Some(BarrierTimeoutConfig {
+            policy: TimeoutPolicyConfig::DropLaggard,
+            ..
+        }) = training.barrier_timeout
+            && !training.allow_worker_loss
+        {
+            let text = "the drop_laggard barrier timeout policy requires allow_worker_loss".into();
+            return Err(OrchErr::InvalidConfig(text));
+        }
+
         let DatasetConfig {
             ref src,
             x_size,
             y_size,
+            ..
         } = training.dataset;
 
         let Some(row_size) = x_size.checked_add(y_size.get()) else {
@@ -146,18 +433,65 @@ impl Validator {
 
         let samples = match src {
             DataSrc::Inline { samples, labels } => {
-                let len = samples.len() + labels.len();
+                let nsamples = samples.len() / x_size.get();
 
-                if len % row_size != 0 {
+                if samples.len() % x_size != 0 || labels.len() != nsamples * y_size.get() {
                     let text = format!(
-                        "dataset length ({len}) is not divisible by x_size + y_size ({row_size})"
+                        "dataset has {} samples and {} labels, not whole rows of x_size {x_size} and y_size {y_size}",
+                        samples.len(),
+                        labels.len()
                     );
 
                     return Err(OrchErr::InvalidConfig(text));
                 }
 
-                // SAFETY: row_size is a positive integer.
-                len / row_size.get()
+                nsamples
+            }
+            DataSrc::Csr {
+                indptr,
+                indices,
+                values,
+                labels,
+            } => {
+                let samples = CsrMatrix::new(
+                    x_size.get(),
+                    indptr.clone(),
+                    indices.clone(),
+                    values.clone(),
+                )
+                .map_err(|e| OrchErr::InvalidConfig(format!("invalid csr dataset: {e}")))?;
+
+                let nsamples = samples.nrows();
+                let expected = nsamples * y_size.get();
+
+                if labels.len() != expected {
+                    let text = format!(
+                        "dataset has {} labels, expected {expected} for {nsamples} samples",
+                        labels.len()
+                    );
+
+                    return Err(OrchErr::InvalidConfig(text));
+                }
+
+                nsamples
+            }
+            DataSrc::Stdin { .. } => {
+                let text = "stdin datasets must be read before being validated".into();
+                return Err(OrchErr::InvalidConfig(text));
+            }
+            DataSrc::Synthetic {
+                nsamples,
+                noise_std,
+                ..
+            } => {
+                if !noise_std.is_finite() || *noise_std < 0.0 {
+                    let text = format!(
+                        "the synthetic dataset's noise_std ({noise_std}) must be a finite non negative number"
+                    );
+                    return Err(OrchErr::InvalidConfig(text));
+                }
+
+                nsamples.get()
             }
             DataSrc::Local {
                 samples_path,
@@ -200,4 +534,118 @@ impl Validator {
 
         Ok(())
     }
+
+    /// Validates the synchronizer of every parameter server.
+    ///
+    /// * `training` - The training configuration.
+    ///
+    /// # Errors
+    /// An `OrchErr` if `server_synchronizers` doesn't hold one synchronizer per server or if
+    /// `track_staleness` is set while a server isn't `non_blocking` or `metric_barrier_every` is
+    /// set while none of them is or along with the `send_then_update` update rule.
+    fn validate_synchronizers(&self, training: &TrainingConfig) -> Result<()> {
+        let (AlgorithmConfig::ParameterServer {
+            nservers,
+            synchronizer,
+            ..
+        }
+        | AlgorithmConfig::StrategySwitch {
+            nservers,
+            synchronizer,
+            ..
+        }) = training.algorithm
+        else {
+            return Ok(());
+        };
+
+        let overrides = &training.server_synchronizers;
+        if !overrides.is_empty() && overrides.len() != nservers.get() {
+            let text = format!(
+                "server_synchronizers holds {} synchronizers, it needs one per server ({nservers})",
+                overrides.len()
+            );
+            return Err(OrchErr::InvalidConfig(text));
+        }
+
+        let non_blocking =
+            |sync: &SynchronizerConfig| matches!(sync, SynchronizerConfig::NonBlocking);
+        let (all_non_blocking, any_non_blocking) = match overrides.is_empty() {
+            true => (non_blocking(&synchronizer), non_blocking(&synchronizer)),
+            false => (
+                overrides.iter().all(non_blocking),
+                overrides.iter().any(non_blocking),
+            ),
+        };
+
+        if training.track_staleness && !all_non_blocking {
+            let text =
+                "track_staleness requires every server to use the non_blocking synchronizer".into();
+            return Err(OrchErr::InvalidConfig(text));
+        }
+
+        if training.metric_barrier_every.is_some() && !any_non_blocking {
+            let text = "metric_barrier_every requires a server using the non_blocking synchronizer, the barrier already synchronizes the workers".into();
+            return Err(OrchErr::InvalidConfig(text));
+        }
+
+        if training.metric_barrier_every.is_some()
+            && matches!(training.update_rule, UpdateRuleConfig::SendThenUpdate)
+        {
+            let text = "metric_barrier_every requires the update_then_send update_rule, otherwise the workers get the parameters before the barrier".into();
+            return Err(OrchErr::InvalidConfig(text));
+        }
+
+        Ok(())
+    }
+
+    /// Validates that an all-reduce training doesn't configure the parameter servers.
+    ///
+    /// * `training` - The training configuration.
+    ///
+    /// # Errors
+    /// An `OrchErr` if a server only setting is set, since there are no servers to apply it.
+    fn validate_serverless(&self, training: &TrainingConfig) -> Result<()> {
+        let server_settings = [
+            ("lr_schedule", training.lr_schedule.is_some()),
+            (
+                "lr_scaling",
+                !matches!(training.lr_scaling, LrScalingConfig::None),
+            ),
+            ("min_workers", training.min_workers.is_some()),
+            ("max_workers", training.max_workers.is_some()),
+            ("ema_decay", training.ema_decay.is_some()),
+            (
+                "the halve_lr divergence_policy",
+                matches!(
+                    training.divergence_policy,
+                    DivergencePolicyConfig::HalveLr { .. }
+                ),
+            ),
+            ("server_momentum", training.server_momentum.is_some()),
+            ("track_staleness", training.track_staleness),
+            (
+                "metric_barrier_every",
+                training.metric_barrier_every.is_some(),
+            ),
+            ("accum_f64", training.accum_f64),
+            ("accum_f64_layers", !training.accum_f64_layers.is_empty()),
+            ("bias_only", training.bias_only),
+            (
+                "server_synchronizers",
+                !training.server_synchronizers.is_empty(),
+            ),
+            ("snapshot_every", training.snapshot_every.is_some()),
+            (
+                "latency_warmup_rounds",
+                training.latency_warmup_rounds.is_some(),
+            ),
+        ];
+
+        if let Some((setting, _)) = server_settings.iter().find(|(_, set)| *set) {
+            let text = format!("{setting} configures the servers, all_reduce trains without them");
+            return Err(OrchErr::InvalidConfig(text));
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,266 @@
+use std::{collections::HashMap, fs, num::NonZeroUsize, path::Path};
+
+use comms::specs::machine_learning::ParamGenSpec;
+use log::info;
+use safetensors::{Dtype, SafeTensors};
+
+use super::{LayerConfig, ModelConfig};
+use crate::{OrchErr, Result};
+
+/// The weights of a previously trained model, used to initialize the layers of a
+/// different but compatible one.
+///
+/// The saved layers are matched to the model's by index, following the `layer_N.weight`
+/// and `layer_N.bias` names `TrainedModel::save_safetensors` writes. The layers missing
+/// from the file keep their own initialization.
+#[derive(Debug)]
+pub struct WarmStart {
+    tensors: HashMap<String, (Vec<usize>, Vec<f32>)>,
+}
+
+impl WarmStart {
+    /// Loads the weights of a `.safetensors` file.
+    ///
+    /// # Args
+    /// * `path` - The path to the file.
+    ///
+    /// # Returns
+    /// A new `WarmStart` instance.
+    ///
+    /// # Errors
+    /// Returns an `OrchErr` if the file can't be read or holds tensors other than `f32`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = fs::read(path.as_ref())?;
+        let safetensors = SafeTensors::deserialize(&bytes)?;
+
+        let tensors = safetensors
+            .tensors()
+            .into_iter()
+            .map(|(name, view)| {
+                if view.dtype() != Dtype::F32 {
+                    let text = format!("the warm start tensor {name} isn't f32");
+                    return Err(OrchErr::InvalidConfig(text));
+                }
+
+                // SAFETY: Every f32 is 4 bytes long.
+                let params = view
+                    .data()
+                    .chunks_exact(4)
+                    .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+                    .collect();
+                Ok((name, (view.shape().to_vec(), params)))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { tensors })
+    }
+
+    /// Replaces the parameter generators of the layers found in the file with their weights.
+    ///
+    /// # Args
+    /// * `model` - The model being initialized.
+    /// * `input_size` - The size of the model's input.
+    /// * `param_gens` - The parameter generator of every layer of the model.
+    ///
+    /// # Errors
+    /// Returns an `OrchErr` if a saved layer doesn't have the dimensions of the model's
+    /// layer at the same index.
+    pub fn apply(
+        &self,
+        model: &ModelConfig,
+        input_size: NonZeroUsize,
+        param_gens: &mut [Option<ParamGenSpec>],
+    ) -> Result<()> {
+        let mut input_size = input_size.get();
+        let mut loaded = 0;
+
+        for (i, (layer, param_gen)) in model.layers.iter().zip(param_gens).enumerate() {
+            let shapes = Self::shapes(layer, input_size);
+            input_size = layer.output_size().get();
+
+            let Some((weight_shape, bias_shape)) = shapes else {
+                continue;
+            };
+
+            let weight = self.tensors.get(&format!("layer_{i}.weight"));
+            let bias = self.tensors.get(&format!("layer_{i}.bias"));
+
+            let (weight, bias) = match (weight, bias) {
+                (None, None) => continue,
+                (Some(weight), Some(bias)) => (weight, bias),
+                _ => {
+                    let text = format!("the warm start is missing half of layer {i}");
+                    return Err(OrchErr::InvalidConfig(text));
+                }
+            };
+
+            Self::check_shape(i, "weight", &weight.0, &weight_shape)?;
+            Self::check_shape(i, "bias", &bias.0, &bias_shape)?;
+
+            // A tied layer only holds it's biases, the weights are it's source's.
+            let params = match layer {
+                LayerConfig::Dense { tie: Some(_), .. } => bias.1.clone(),
+                _ => [weight.1.as_slice(), &bias.1].concat(),
+            };
+
+            *param_gen = Some(ParamGenSpec::Inline { params });
+            loaded += 1;
+        }
+
+        info!("warm started {loaded} layers, the rest are initialized from scratch");
+        Ok(())
+    }
+
+    /// The shapes of the weight and bias tensors of a layer.
+    ///
+    /// # Args
+    /// * `layer` - The layer's configuration.
+    /// * `input_size` - The size of the layer's input.
+    ///
+    /// # Returns
+    /// The shapes or `None` if the layer has no parameters.
+    fn shapes(layer: &LayerConfig, input_size: usize) -> Option<(Vec<usize>, Vec<usize>)> {
+        match *layer {
+            LayerConfig::Dense { output_size, .. } => {
+                let out = output_size.get();
+                Some((vec![input_size, out], vec![out]))
+            }
+            LayerConfig::Conv { kernel_dim, .. } => {
+                let (filters, channels, kernel_size) =
+                    (kernel_dim.0.get(), kernel_dim.1.get(), kernel_dim.2.get());
+
+                let weight_shape = vec![filters, channels, kernel_size, kernel_size];
+                Some((weight_shape, vec![filters]))
+            }
+            // A custom layer's parameters have no known layout to match a saved tensor against.
+            LayerConfig::MaxPooling { .. } | LayerConfig::Custom { .. } => None,
+        }
+    }
+
+    /// Checks a saved tensor has the shape the model expects.
+    ///
+    /// # Args
+    /// * `layer` - The index of the layer.
+    /// * `name` - The name of the tensor.
+    /// * `got` - The saved tensor's shape.
+    /// * `expected` - The shape the model expects.
+    ///
+    /// # Errors
+    /// Returns an `OrchErr` if the shapes differ.
+    fn check_shape(layer: usize, name: &str, got: &[usize], expected: &[usize]) -> Result<()> {
+        if got != expected {
+            let text = format!(
+                "the warm start {name} of layer {layer} has shape {got:?}, but the model expects {expected:?}"
+            );
+            return Err(OrchErr::InvalidConfig(text));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::PathBuf};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        configs::{ActFnConfig, ParamGenConfig},
+        sessions::TrainedModel,
+    };
+
+    fn dense(output_size: usize) -> LayerConfig {
+        LayerConfig::Dense {
+            output_size: NonZeroUsize::new(output_size).unwrap(),
+            init: ParamGenConfig::Kaiming,
+            act_fn: Some(ActFnConfig::Tanh { amp: 1.0 }),
+            tie: None,
+            frozen: false,
+            init_gain: None,
+        }
+    }
+
+    fn model(layers: Vec<LayerConfig>) -> ModelConfig {
+        ModelConfig {
+            layers,
+            heads: Vec::new(),
+            checkpoint_every: None,
+            activation_clamp: None,
+            warm_start: None,
+        }
+    }
+
+    /// Saves a trained model of two dense layers, `2 -> 3 -> 2`.
+    ///
+    /// # Returns
+    /// The path to the file and the saved parameters.
+    fn save_two_layers() -> (PathBuf, Vec<f32>) {
+        let params: Vec<_> = (0..(2 + 1) * 3 + (3 + 1) * 2).map(|i| i as f32).collect();
+        let trained = TrainedModel {
+            params: params.clone(),
+            ema_params: None,
+            model: model(vec![dense(3), dense(2)]),
+            input_size: 2,
+        };
+
+        let path = env::temp_dir().join(format!("{}.safetensors", Uuid::new_v4()));
+        trained.save_safetensors(&path).unwrap();
+        (path, params)
+    }
+
+    #[test]
+    fn test_a_two_layer_checkpoint_warm_starts_a_three_layer_model() {
+        let (path, params) = save_two_layers();
+        let warm_start = WarmStart::load(&path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let grown = model(vec![dense(3), dense(2), dense(1)]);
+        let input_size = NonZeroUsize::new(2).unwrap();
+        let mut param_gens = vec![
+            None,
+            None,
+            Some(ParamGenSpec::Const {
+                value: 0.5,
+                limit: 3,
+            }),
+        ];
+        warm_start
+            .apply(&grown, input_size, &mut param_gens)
+            .unwrap();
+
+        let Some(ParamGenSpec::Inline { params: first }) = &param_gens[0] else {
+            panic!("the first layer wasn't warm started");
+        };
+        let Some(ParamGenSpec::Inline { params: second }) = &param_gens[1] else {
+            panic!("the second layer wasn't warm started");
+        };
+
+        assert_eq!(first, &params[..9]);
+        assert_eq!(second, &params[9..]);
+        assert!(matches!(
+            param_gens[2],
+            Some(ParamGenSpec::Const { limit: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_a_layer_with_different_dimensions_is_rejected() {
+        let (path, _) = save_two_layers();
+        let warm_start = WarmStart::load(&path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let widened = model(vec![dense(4), dense(2)]);
+        let input_size = NonZeroUsize::new(2).unwrap();
+        let mut param_gens = vec![None, None];
+        let err = warm_start
+            .apply(&widened, input_size, &mut param_gens)
+            .unwrap_err();
+
+        let OrchErr::InvalidConfig(text) = err else {
+            panic!("expected an invalid config error, got {err:?}");
+        };
+        assert!(text.contains("layer 0"), "{text}");
+    }
+}
@@ -1,11 +1,16 @@
 use std::{
-    fs::File,
-    io::{self, BufRead, BufReader, BufWriter, Write},
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    num::NonZeroUsize,
     path::{Path, PathBuf},
+    thread,
 };
 
 use log::info;
 
+use crate::configs::StreamFormatConfig;
+
 /// Supported dataset formats that can be transparently converted
 /// to raw packed `f32` binary before training begins.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -37,10 +42,16 @@ impl DatasetFormat {
     }
 }
 
+/// The amount of bytes parsed at once on a thread, only as many chunks as there are
+/// threads are held in memory at a time.
+const CHUNK_SIZE: u64 = 1 << 20;
+
 /// Converts a delimited dataset file (CSV/TSV) to a raw packed `f32` binary file.
 ///
-/// Processes the source file line by line. If the first non-empty row cannot be
-/// fully parsed as `f32` values it is treated as a header and skipped automatically.
+/// The file is split in byte ranges aligned to line boundaries that are parsed in
+/// parallel, up to one per available core, and written back in order. If the first
+/// non-empty row cannot be fully parsed as `f32` values it is treated as a header
+/// and skipped automatically.
 ///
 /// The output file is placed next to the source with a `.bin` extension. If it
 /// already exists, conversion is skipped and the existing path is returned.
@@ -56,6 +67,32 @@ impl DatasetFormat {
 /// Returns an `io::Error` if the file cannot be read or written, or if any
 /// field in a data row cannot be parsed as `f32`.
 pub fn convert_to_binary(src: &Path, format: DatasetFormat) -> io::Result<PathBuf> {
+    let nthreads = thread::available_parallelism().unwrap_or(NonZeroUsize::MIN);
+    convert_to_binary_with_threads(src, format, nthreads)
+}
+
+/// Converts a delimited dataset file (CSV/TSV) to a raw packed `f32` binary file
+/// parsing it on up to `nthreads` threads.
+///
+/// The output doesn't depend on the amount of threads, a single one parses the
+/// file sequentially.
+///
+/// # Args
+/// * `src` - Path to the source delimited file.
+/// * `format` - The dataset format to use for parsing.
+/// * `nthreads` - The maximum amount of threads to parse the file on.
+///
+/// # Returns
+/// The path to the converted binary file.
+///
+/// # Errors
+/// Returns an `io::Error` if the file cannot be read or written, or if any
+/// field in a data row cannot be parsed as `f32`.
+pub fn convert_to_binary_with_threads(
+    src: &Path,
+    format: DatasetFormat,
+    nthreads: NonZeroUsize,
+) -> io::Result<PathBuf> {
     let out = src.with_extension("bin");
 
     if out.exists() {
@@ -69,11 +106,14 @@ pub fn convert_to_binary(src: &Path, format: DatasetFormat) -> io::Result<PathBu
     info!("converting {} to binary at {}", src.display(), out.display());
 
     let sep = format.separator();
-    let reader = BufReader::new(File::open(src)?);
-    let mut writer = BufWriter::new(File::create(&out)?);
+    let (data_start, first_line) = skip_header(BufReader::new(File::open(src)?), sep)?;
+    let len = fs::metadata(src)?.len();
 
-    write_all_rows(reader, sep, &mut writer)?;
+    let nchunks = ((len - data_start) / CHUNK_SIZE).max(1);
+    let bounds = chunk_bounds(src, data_start, len, nchunks as usize)?;
 
+    let mut writer = BufWriter::new(File::create(&out)?);
+    write_chunks(src, sep, &bounds, first_line, nthreads, &mut writer)?;
     writer.flush()?;
 
     info!(
@@ -85,50 +125,280 @@ pub fn convert_to_binary(src: &Path, format: DatasetFormat) -> io::Result<PathBu
     Ok(out)
 }
 
-fn write_all_rows(
-    reader: BufReader<File>,
+/// Finds where the data rows begin, past the header if there's one.
+///
+/// # Args
+/// * `reader` - The reader of the delimited rows.
+/// * `sep` - The fields' separator.
+///
+/// # Returns
+/// The byte offset of the data rows and the line number they start at.
+fn skip_header<R: BufRead>(mut reader: R, sep: char) -> io::Result<(u64, usize)> {
+    let mut line = String::new();
+    let (mut offset, mut line_n) = (0, 0);
+
+    loop {
+        let read = reader.read_line(&mut line)?;
+        let trimmed = line.trim();
+
+        if read == 0 {
+            return Ok((0, 0));
+        }
+
+        if !trimmed.is_empty() {
+            if is_data_row(trimmed, sep) {
+                return Ok((0, 0));
+            }
+
+            info!("skipping header row: {trimmed:?}");
+            return Ok((offset + read as u64, line_n + 1));
+        }
+
+        offset += read as u64;
+        line_n += 1;
+        line.clear();
+    }
+}
+
+/// Splits a byte range of the file in chunks that start at the beginning of a line.
+///
+/// # Args
+/// * `src` - Path to the source delimited file.
+/// * `start` - The offset of the range's first byte.
+/// * `end` - The offset past the range's last byte.
+/// * `nchunks` - The amount of chunks to split the range in.
+///
+/// # Returns
+/// The boundaries of the chunks, from `start` to `end`, fewer chunks than requested
+/// are made if the lines are too long to fit them.
+fn chunk_bounds(src: &Path, start: u64, end: u64, nchunks: usize) -> io::Result<Vec<u64>> {
+    let mut reader = BufReader::new(File::open(src)?);
+    let mut bounds = vec![start];
+    let mut buf = Vec::new();
+
+    for i in 1..nchunks as u64 {
+        let target = start + (end - start) * i / nchunks as u64;
+
+        if target <= *bounds.last().unwrap() {
+            continue;
+        }
+
+        // Moves the boundary right past the end of the line the target falls in.
+        reader.seek(SeekFrom::Start(target - 1))?;
+        buf.clear();
+        let bound = target - 1 + reader.read_until(b'\n', &mut buf)? as u64;
+
+        if bound < end && bound > *bounds.last().unwrap() {
+            bounds.push(bound);
+        }
+    }
+
+    bounds.push(end);
+    Ok(bounds)
+}
+
+/// Parses the chunks of the file in parallel and writes their rows in order.
+///
+/// Every chunk is written as soon as it and the ones before it are parsed, and a
+/// new one starts parsing in it's place, so at most `window` chunks are in memory.
+///
+/// # Args
+/// * `src` - Path to the source delimited file.
+/// * `sep` - The fields' separator.
+/// * `bounds` - The boundaries of the chunks.
+/// * `first_line` - The line number the first chunk starts at.
+/// * `window` - The maximum amount of chunks parsed at the same time.
+/// * `writer` - The writer to write the packed `f32` values to.
+///
+/// # Errors
+/// Returns an `io::Error` if the file cannot be read or any field fails to parse,
+/// naming the line it's in.
+fn write_chunks<W: Write>(
+    src: &Path,
     sep: char,
-    writer: &mut BufWriter<File>,
+    bounds: &[u64],
+    first_line: usize,
+    window: NonZeroUsize,
+    writer: &mut W,
 ) -> io::Result<()> {
-    let mut lines = reader.lines().enumerate();
+    thread::scope(|s| {
+        let spawn = |range: &[u64]| {
+            let (start, end) = (range[0], range[1]);
+            s.spawn(move || parse_chunk(src, sep, start, end))
+        };
 
-    if let Some((n, first)) = find_first_data_line(&mut lines, sep)? {
-        write_row(&first, sep, n + 1, writer)?;
-    }
+        let mut ranges = bounds.windows(2);
+        let mut in_flight: VecDeque<_> = ranges.by_ref().take(window.get()).map(spawn).collect();
+        let mut line_n = first_line;
+
+        while let Some(handle) = in_flight.pop_front() {
+            let chunk = handle.join().expect("a dataset parsing thread panicked");
+            let (values, nlines) = chunk?.map_err(|(n, field)| parse_error(line_n + n, &field))?;
+            writer.write_all(bytemuck::cast_slice(&values))?;
+            line_n += nlines;
+
+            in_flight.extend(ranges.next().map(spawn));
+        }
+
+        Ok(())
+    })
+}
+
+/// Parses the rows of a chunk of the file.
+///
+/// # Args
+/// * `src` - Path to the source delimited file.
+/// * `sep` - The fields' separator.
+/// * `start` - The offset of the chunk's first byte.
+/// * `end` - The offset past the chunk's last byte.
+///
+/// # Returns
+/// The values of the rows and the amount of lines in the chunk, or the line within
+/// the chunk and the field that failed to parse.
+///
+/// # Errors
+/// Returns an `io::Error` if the file cannot be read.
+fn parse_chunk(src: &Path, sep: char, start: u64, end: u64) -> io::Result<ParsedRows> {
+    let mut file = File::open(src)?;
+    file.seek(SeekFrom::Start(start))?;
+    parse_rows(BufReader::new(file.take(end - start)), sep)
+}
+
+/// The values of the parsed rows and the amount of lines they span, or the line
+/// and the field that failed to parse.
+type ParsedRows = Result<(Vec<f32>, usize), (usize, String)>;
+
+/// Parses delimited rows, skipping the empty ones.
+///
+/// # Args
+/// * `reader` - The reader of the delimited rows.
+/// * `sep` - The fields' separator.
+///
+/// # Returns
+/// The parsed rows.
+///
+/// # Errors
+/// Returns an `io::Error` if the rows cannot be read.
+fn parse_rows<R: BufRead>(reader: R, sep: char) -> io::Result<ParsedRows> {
+    let mut values = Vec::new();
+    let mut nlines = 0;
 
-    for (line_n, line) in lines {
+    for line in reader.lines() {
         let line = line?;
         let trimmed = line.trim();
 
         if !trimmed.is_empty() {
-            write_row(trimmed, sep, line_n + 1, writer)?;
+            for field in trimmed.split(sep) {
+                let Ok(val) = field.trim().parse() else {
+                    return Ok(Err((nlines, field.to_owned())));
+                };
+
+                values.push(val);
+            }
         }
+
+        nlines += 1;
     }
 
-    Ok(())
+    Ok(Ok((values, nlines)))
 }
 
-fn find_first_data_line<I>(lines: &mut I, sep: char) -> io::Result<Option<(usize, String)>>
-where
-    I: Iterator<Item = (usize, io::Result<String>)>,
-{
-    for (n, line) in lines {
-        let line = line?;
-        let trimmed = line.trim().to_owned();
+/// Builds the error of a field that failed to parse.
+///
+/// # Args
+/// * `line_n` - The zero based line of the field.
+/// * `field` - The field that failed to parse.
+///
+/// # Returns
+/// An `io::Error` of kind `InvalidData` naming the line.
+fn parse_error(line_n: usize, field: &str) -> io::Error {
+    let text = format!("line {}: cannot parse field as f32: {field:?}", line_n + 1);
+    io::Error::new(io::ErrorKind::InvalidData, text)
+}
 
-        if trimmed.is_empty() {
-            continue;
-        }
+/// Reads a whole dataset from a stream of rows, each holding a sample followed by it's labels.
+///
+/// Delimited rows skip the header like `convert_to_binary` does.
+///
+/// # Args
+/// * `reader` - The reader of the stream, read until it's closed.
+/// * `format` - The encoding of the stream.
+/// * `x_size` - The number of input features per sample.
+/// * `y_size` - The number of labels per sample.
+///
+/// # Returns
+/// The samples and the labels of the dataset.
+///
+/// # Errors
+/// Returns an `io::Error` if the stream cannot be read, any field fails to parse
+/// or the values don't make up whole rows.
+pub fn read_dataset<R: Read>(
+    mut reader: R,
+    format: StreamFormatConfig,
+    x_size: NonZeroUsize,
+    y_size: NonZeroUsize,
+) -> io::Result<(Vec<f32>, Vec<f32>)> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
 
-        if !is_data_row(&trimmed, sep) {
-            info!("skipping header row: {trimmed:?}");
-            return Ok(None);
+    let values = match format {
+        StreamFormatConfig::Csv => parse_delimited(&buf, DatasetFormat::Csv.separator())?,
+        StreamFormatConfig::Tsv => parse_delimited(&buf, DatasetFormat::Tsv.separator())?,
+        StreamFormatConfig::Raw => {
+            if buf.len() % size_of::<f32>() != 0 {
+                let text = format!(
+                    "the stream's length ({}) is not a multiple of an f32's size",
+                    buf.len()
+                );
+                return Err(io::Error::new(io::ErrorKind::InvalidData, text));
+            }
+
+            buf.chunks_exact(size_of::<f32>())
+                .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+                .collect()
         }
+    };
+
+    let row_size = x_size.get() + y_size.get();
 
-        return Ok(Some((n, trimmed)));
+    if values.len() % row_size != 0 {
+        let text = format!(
+            "the stream holds {} values, not divisible by x_size + y_size ({row_size})",
+            values.len()
+        );
+        return Err(io::Error::new(io::ErrorKind::InvalidData, text));
     }
 
-    Ok(None)
+    let nrows = values.len() / row_size;
+    let mut samples = Vec::with_capacity(nrows * x_size.get());
+    let mut labels = Vec::with_capacity(nrows * y_size.get());
+
+    for row in values.chunks_exact(row_size) {
+        let (x, y) = row.split_at(x_size.get());
+        samples.extend_from_slice(x);
+        labels.extend_from_slice(y);
+    }
+
+    Ok((samples, labels))
+}
+
+/// Parses a buffer of delimited rows, skipping the header if there's one.
+///
+/// # Args
+/// * `buf` - The delimited rows.
+/// * `sep` - The fields' separator.
+///
+/// # Returns
+/// The values of the rows.
+///
+/// # Errors
+/// Returns an `io::Error` if any field fails to parse.
+fn parse_delimited(buf: &[u8], sep: char) -> io::Result<Vec<f32>> {
+    let (data_start, first_line) = skip_header(buf, sep)?;
+    let (values, _) = parse_rows(&buf[data_start as usize..], sep)?
+        .map_err(|(n, field)| parse_error(first_line + n, &field))?;
+
+    Ok(values)
 }
 
 fn is_data_row(line: &str, sep: char) -> bool {
@@ -136,20 +406,124 @@ fn is_data_row(line: &str, sep: char) -> bool {
         .all(|field| field.trim().parse::<f32>().is_ok())
 }
 
-fn write_row(
-    line: &str,
-    sep: char,
-    line_n: usize,
-    writer: &mut BufWriter<File>,
-) -> io::Result<()> {
-    for field in line.split(sep) {
-        let val: f32 = field.trim().parse().map_err(|_| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("line {line_n}: cannot parse field as f32: {field:?}"),
-            )
-        })?;
-        writer.write_all(bytemuck::bytes_of(&val))?;
+#[cfg(test)]
+mod tests {
+    use std::{env, io::pipe};
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    /// Converts the contents as a csv file parsing it on `nthreads` threads.
+    fn convert(contents: &str, nthreads: usize) -> io::Result<Vec<u8>> {
+        let src = env::temp_dir().join(format!("{}.csv", Uuid::new_v4()));
+        fs::write(&src, contents)?;
+
+        let nthreads = NonZeroUsize::new(nthreads).unwrap();
+        let res =
+            convert_to_binary_with_threads(&src, DatasetFormat::Csv, nthreads).and_then(|out| {
+                let bytes = fs::read(&out);
+                fs::remove_file(out)?;
+                bytes
+            });
+
+        fs::remove_file(src)?;
+        res
+    }
+
+    /// Splits the file in `nchunks` chunks and writes their rows in order, parsing up to
+    /// `window` of them at a time.
+    fn parse(contents: &str, nchunks: usize, window: usize) -> io::Result<Vec<u8>> {
+        let src = env::temp_dir().join(format!("{}.csv", Uuid::new_v4()));
+        fs::write(&src, contents)?;
+
+        let (data_start, first_line) = skip_header(BufReader::new(File::open(&src)?), ',')?;
+        let bounds = chunk_bounds(&src, data_start, contents.len() as u64, nchunks)?;
+        let mut bytes = Vec::new();
+        let window = NonZeroUsize::new(window).unwrap();
+        let res = write_chunks(&src, ',', &bounds, first_line, window, &mut bytes);
+
+        fs::remove_file(src)?;
+        res.map(|_| bytes)
+    }
+
+    #[test]
+    fn test_parallel_parse_matches_the_sequential_one() {
+        let mut contents = String::from("\nx0,x1,y\n");
+
+        for i in 0..500 {
+            let blank = if i % 37 == 0 { "\n  \n" } else { "" };
+            contents.push_str(&format!("{i}.5, {}, -{i}e-3\r\n{blank}", i * 3));
+        }
+
+        let sequential = parse(&contents, 1, 1).unwrap();
+        assert_eq!(sequential.len(), 500 * 3 * size_of::<f32>());
+        assert_eq!(&sequential[..4], bytemuck::bytes_of(&0.5f32));
+
+        for nchunks in [2, 3, 7, 64, 4096] {
+            for window in [1, 3, 8] {
+                assert_eq!(parse(&contents, nchunks, window).unwrap(), sequential);
+            }
+        }
+
+        assert_eq!(convert(&contents, 8).unwrap(), sequential);
+    }
+
+    #[test]
+    fn test_parallel_parse_reports_the_failing_line() {
+        let mut contents = String::from("a,b\n");
+
+        for i in 0..100 {
+            let field = if i == 80 { "nan?" } else { "1.0" };
+            contents.push_str(&format!("{field},2.0\n"));
+        }
+
+        for nchunks in [1, 5] {
+            let e = parse(&contents, nchunks, 2).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+            assert!(e.to_string().starts_with("line 82:"), "{e}");
+        }
     }
-    Ok(())
-}
\ No newline at end of file
+
+    /// Reads a dataset written to a pipe from another thread.
+    fn read_piped(bytes: Vec<u8>, format: StreamFormatConfig) -> io::Result<(Vec<f32>, Vec<f32>)> {
+        let (reader, mut writer) = pipe()?;
+        let producer = thread::spawn(move || writer.write_all(&bytes));
+
+        let x_size = NonZeroUsize::new(2).unwrap();
+        let res = read_dataset(reader, format, x_size, NonZeroUsize::MIN);
+
+        producer.join().unwrap()?;
+        res
+    }
+
+    #[test]
+    fn test_read_dataset_from_a_pipe() {
+        let csv = "x0,x1,y\n1,2,3\n\n4,5,6\n".as_bytes().to_vec();
+        let (samples, labels) = read_piped(csv, StreamFormatConfig::Csv).unwrap();
+
+        assert_eq!(samples, [1., 2., 4., 5.]);
+        assert_eq!(labels, [3., 6.]);
+
+        let raw = [1f32, 2., 3., 4., 5., 6.]
+            .iter()
+            .flat_map(|val| val.to_le_bytes())
+            .collect();
+
+        let (samples, labels) = read_piped(raw, StreamFormatConfig::Raw).unwrap();
+
+        assert_eq!(samples, [1., 2., 4., 5.]);
+        assert_eq!(labels, [3., 6.]);
+    }
+
+    #[test]
+    fn test_read_dataset_rejects_partial_rows() {
+        let tsv = "1\t2\t3\n4\t5\n".as_bytes().to_vec();
+        let e = read_piped(tsv, StreamFormatConfig::Tsv).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+
+        let raw = vec![0; 3 * size_of::<f32>() + 1];
+        let e = read_piped(raw, StreamFormatConfig::Raw).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    }
+}
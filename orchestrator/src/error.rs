@@ -11,12 +11,30 @@ use crate::sessions::WorkerRequest;
 pub enum OrchErr {
     InvalidConfig(String),
     Unsupported(String),
-    ConnectionFailed { addr: String, source: io::Error },
-    WorkerError { id: usize, details: String },
+    ConnectionFailed {
+        addr: String,
+        source: io::Error,
+    },
+    WorkerError {
+        id: usize,
+        details: String,
+    },
+    WorkerFailed {
+        id: usize,
+        cause: String,
+    },
+    EmptyShard {
+        worker_id: usize,
+    },
     ServerError(String),
     SafeTensors(safetensors::SafeTensorError),
     InvalidRequest(WorkerRequest),
     Adapting(String),
+    EpochHook(String),
+    FailedWithPartial {
+        cause: Box<OrchErr>,
+        params: Vec<f32>,
+    },
     Io(io::Error),
 }
 
@@ -34,10 +52,21 @@ impl Display for OrchErr {
             Self::WorkerError { id, details: msg } => {
                 format!("worker {id} error: {msg}")
             }
+            Self::WorkerFailed { id, cause } => format!("worker {id} failed: {cause}"),
+            Self::EmptyShard { worker_id } => {
+                format!(
+                    "worker {worker_id} got no rows of the dataset, there are more workers than rows"
+                )
+            }
             Self::Adapting(msg) => format!("an error occurred while adapting configs: {msg}"),
+            Self::EpochHook(msg) => format!("the epoch callback panicked: {msg}"),
             Self::InvalidRequest(req) => format!("invalid worker request: {req:?}"),
             Self::SafeTensors(e) => format!("safetensors error: {e}"),
             Self::ServerError(msg) => format!("server error: {msg}"),
+            Self::FailedWithPartial { cause, params } => {
+                let nparams = params.len();
+                format!("{cause}, the {nparams} parameters of the last snapshot were kept")
+            }
             Self::Io(e) => format!("io error: {e}"),
         };
 
@@ -50,6 +79,7 @@ impl Error for OrchErr {
         match self {
             Self::ConnectionFailed { source, .. } => Some(source),
             Self::Io(e) => Some(e),
+            Self::FailedWithPartial { cause, .. } => Some(cause.as_ref()),
             _ => None,
         }
     }
@@ -2,38 +2,70 @@ mod calculator;
 pub mod configs;
 pub mod dataset_format;
 mod error;
+#[cfg(feature = "local")]
+mod local_nodes;
+mod pool;
 pub mod sessions;
 
 use std::{
-    collections::{BTreeMap, HashMap},
-    fs, io,
-    path::PathBuf,
-    time::Duration,
+    collections::HashMap,
+    env, fs,
+    io::{self, IsTerminal},
+    mem,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::Instant,
 };
 
-use comms::{Connector, NodeHandle, TransportLayer, protocol::Entity};
-
-use configs::{Adapter, DataSrc, ModelConfig, TrainingConfig, Validator};
-use dataset_format::{DatasetFormat, convert_to_binary};
+use comms::specs::{machine_learning::ParamGenSpec, worker::AlgorithmSpec};
+pub use configs::ValidationReport;
+use configs::{
+    Adapter, AlgorithmConfig, ConfigFile, DataSrc, DatasetConfig, ModelConfig, TrainingConfig,
+    Validator,
+};
+use dataset_format::{DatasetFormat, convert_to_binary, read_dataset};
 pub use error::{OrchErr, Result};
-use log::debug;
-pub use sessions::{CancelHandle, Session, StopReason, TrainedModel, TrainingEvent};
-use tokio::{
-    net::{
-        TcpStream,
-        tcp::{OwnedReadHalf, OwnedWriteHalf},
-    },
-    runtime::Runtime,
+#[cfg(feature = "local")]
+use local_nodes::LocalNodes;
+use log::{debug, info};
+use machine_learning::datasets::{CsrMatrix, SyntheticDataset};
+pub use pool::OrchestratorPool;
+pub use sessions::{
+    CancelHandle, EpochSummary, EvaluationReport, LrHandle, ParamsPreview, SeedReport, Session,
+    StopReason, TrainedModel, TrainingEvent, TrainingReport,
 };
+use sessions::{Rows, TestSet};
 use uuid::Uuid;
+pub use worker::metrics::{ConfusionMatrix, RegressionMetrics};
 
 use crate::configs::StatRequester;
 
-type R = OwnedReadHalf;
-type W = OwnedWriteHalf;
+/// Reads the model and training configs from a `.json` or `.toml` file.
+///
+/// The file holds the model's config under the `model` key and the training's under the
+/// `training` key, laid out as their serialized form. The configs are only deserialized,
+/// they're validated once they're trained on.
+///
+/// # Args
+/// * `path` - The path of the config file.
+///
+/// # Returns
+/// The model and training configs.
+///
+/// # Errors
+/// An `OrchErr::InvalidConfig` if the file can't be read, has an unknown extension or
+/// doesn't hold valid configs, naming the offending field.
+pub fn config_from_file(path: impl AsRef<Path>) -> Result<(ModelConfig, TrainingConfig)> {
+    let ConfigFile { model, training } = ConfigFile::load(path)?;
+    Ok((model, training))
+}
 
 /// Starts the distributed training process and returns an active session.
 ///
+/// A dataset streamed through stdin is read whole into an inline one before anything else,
+/// a sparse CSR one is sent to the workers as it is. If the dataset has a test split, it's
+/// rows are held out of the training and the model is tested on them once it stops.
+///
 /// If the dataset source is a local file with a known delimited format
 /// (`.csv`, `.tsv`), it is transparently converted to a raw packed `f32`
 /// binary file before validation and adaptation. The converted file is
@@ -50,33 +82,82 @@ type W = OwnedWriteHalf;
 /// # Errors
 /// Returns an `OrchErr` if dataset conversion fails, config validation fails,
 /// or connecting to any worker or server fails.
-pub fn train(model: ModelConfig, mut training: TrainingConfig) -> Result<Session> {
+pub fn train(model: ModelConfig, training: TrainingConfig) -> Result<Session> {
+    let mut pool = OrchestratorPool::new()?;
+
+    if let Some(token) = &training.auth_token {
+        pool = pool.with_auth_token(token.as_bytes());
+    }
+
+    train_with_pool(model, training, &mut pool)
+}
+
+/// Starts the distributed training process borrowing the node connections from `pool`.
+///
+/// Connections already established in the pool are reused and only the ones that are
+/// missing or died since the last session are dialed again. Meant for launching many
+/// short sessions back to back, such as hyperparameter sweeps.
+///
+/// The nodes are authenticated with the pool's token, `training.auth_token` is ignored.
+///
+/// # Args
+/// * `model` - The model architecture configuration.
+/// * `training` - The training configuration, including worker and server addresses.
+/// * `pool` - The pool of node connections to borrow from.
+///
+/// # Returns
+/// A new ongoing session.
+///
+/// # Errors
+/// Returns an `OrchErr` if dataset conversion fails, config validation fails,
+/// or connecting to any worker or server fails.
+pub fn train_with_pool(
+    model: ModelConfig,
+    mut training: TrainingConfig,
+    pool: &mut OrchestratorPool,
+) -> Result<Session> {
+    read_stdin_dataset(&mut training.dataset)?;
     let dataset_bin = generate_binary_dataset(&mut training.dataset.src);
 
+    let res = hold_out_test_set(&mut training.dataset, training.seed).and_then(
+        |(held_out, train_bin)| {
+            let res = start_session(model, training, held_out, pool);
+            remove_binaries(train_bin);
+            res
+        },
+    );
+
+    remove_binaries(dataset_bin);
+    res
+}
+
+/// Validates the configs, connects to the nodes and bootstraps them into a new session.
+///
+/// # Args
+/// * `model` - The model architecture configuration.
+/// * `training` - The training configuration, it's dataset already read and split.
+/// * `held_out` - The rows held out of the training to test the model on.
+/// * `pool` - The pool of node connections to borrow from.
+///
+/// # Returns
+/// A new ongoing session.
+///
+/// # Errors
+/// Returns an `OrchErr` if config validation fails or connecting to any worker or server fails.
+fn start_session(
+    model: ModelConfig,
+    training: TrainingConfig,
+    held_out: Option<Rows>,
+    pool: &mut OrchestratorPool,
+) -> Result<Session> {
     debug!("Validating configs");
     let validator = Validator::new();
     validator.validate(&model, &training)?;
 
-    // TODO: De momento lo dejaría acá, no creo que sea muy importante poder
-    //       configurar esto, si tenemos tiempo y vemos que viene bien lo
-    //       podemos mover y que sea parte de un `CommsConfig`.
-    let transport_factory = |rx, tx| {
-        comms::build_reliable_transport(
-            rx,
-            tx,
-            Duration::from_secs(5),
-            Duration::from_secs(2),
-            2,
-            4,
-        )
-    };
-
-    let id = Uuid::nil();
-    let mut connector = Connector::new(id, transport_factory);
-    let runtime = Runtime::new()?;
+    let runtime = pool.runtime();
 
     debug!("Connecting to nodes");
-    let handles = runtime.block_on(connect_to_nodes(&mut connector, &training.addrs))?;
+    let mut handles = runtime.block_on(pool.checkout_all(&training.addrs))?;
 
     let addr_ids: HashMap<_, _> = handles
         .iter()
@@ -85,49 +166,580 @@ pub fn train(model: ModelConfig, mut training: TrainingConfig) -> Result<Session
 
     debug!("Requesting stats");
     let mut stat_requester = StatRequester::new();
-    let stats = runtime.block_on(stat_requester.obtain_stats(handles))?;
+    let stats = runtime.block_on(stat_requester.obtain_stats(&mut handles))?;
 
     debug!("Adapting configs");
     let adapter = Adapter::new();
-    let (orch, workers, servers) =
-        adapter.adapt_configs(model.clone(), &training, stats, addr_ids)?;
+    let (mut orch, workers, servers) =
+        match adapter.adapt_configs(model.clone(), &training, stats, addr_ids) {
+            Ok(adapted) => adapted,
+            Err(e) => {
+                pool.checkin_all(handles);
+                return Err(e);
+            }
+        };
 
-    let session = Session::new(orch, workers, servers, connector)?;
-    if let Some((samples_bin, labels_bin)) = dataset_bin {
-        remove_binary(&samples_bin);
-        remove_binary(&labels_bin);
+    orch.test_set = held_out
+        .zip(workers.first())
+        .map(|((samples, labels), worker)| {
+            TestSet::new(samples, labels, worker.spec.trainer.clone())
+        });
+
+    Session::new(orch, workers, servers, handles, pool)
+}
+
+/// Validates the configs against the network without training.
+///
+/// Runs every config check, connects to the nodes and requests their stats as
+/// `train` would, then adapts the configs and disconnects from the nodes instead
+/// of bootstrapping them, so no epoch is ever started.
+///
+/// # Args
+/// * `model` - The model architecture configuration.
+/// * `training` - The training configuration, including worker and server addresses.
+///
+/// # Returns
+/// A report of the parameter count, the servers' shards and the workers' dataset splits.
+///
+/// # Errors
+/// Returns an `OrchErr` if dataset conversion fails, config validation fails,
+/// connecting to any of the nodes fails or the configs fail to be adapted.
+pub fn validate(model: ModelConfig, training: TrainingConfig) -> Result<ValidationReport> {
+    let mut pool = OrchestratorPool::new()?;
+
+    if let Some(token) = &training.auth_token {
+        pool = pool.with_auth_token(token.as_bytes());
     }
 
-    Ok(session)
+    validate_with_pool(model, training, &mut pool)
 }
 
-/// Will connect to the nodes.
+/// Validates the configs against the network borrowing the node connections from `pool`.
+///
+/// The connections are checked back into the pool once the nodes' stats are in, so a
+/// session started from the same pool afterwards bootstraps the nodes through them.
+///
+/// The nodes are authenticated with the pool's token, `training.auth_token` is ignored.
 ///
 /// # Args
-/// * `connector` - The connector to connect to the nodes.
-/// * `addrs` - The network addresses to connect to.
+/// * `model` - The model architecture configuration.
+/// * `training` - The training configuration, including worker and server addresses.
+/// * `pool` - The pool of node connections to borrow from.
 ///
 /// # Returns
-/// A sorted list of node handles or an io error if occurred.
-async fn connect_to_nodes<T, F>(
-    connector: &mut Connector<R, W, T, F>,
-    addrs: &[String],
-) -> io::Result<BTreeMap<String, NodeHandle<T>>>
-where
-    T: TransportLayer,
-    F: Fn(R, W) -> T,
-{
-    let mut handles = BTreeMap::new();
-    let src = Entity::Orchestrator;
-
-    for addr in addrs {
-        let stream = TcpStream::connect(addr).await?;
-        let (rx, tx) = stream.into_split();
-        let node_handle = connector.connect_node(rx, tx, src).await?;
-        handles.insert(addr.clone(), node_handle);
+/// A report of the parameter count, the servers' shards and the workers' dataset splits.
+///
+/// # Errors
+/// Returns an `OrchErr` if dataset conversion fails, config validation fails,
+/// connecting to any of the nodes fails or the configs fail to be adapted.
+pub fn validate_with_pool(
+    model: ModelConfig,
+    mut training: TrainingConfig,
+    pool: &mut OrchestratorPool,
+) -> Result<ValidationReport> {
+    read_stdin_dataset(&mut training.dataset)?;
+    let dataset_bin = generate_binary_dataset(&mut training.dataset.src);
+
+    debug!("Validating configs");
+    let validator = Validator::new();
+    let res = hold_out_test_set(&mut training.dataset, training.seed).and_then(|(_, train_bin)| {
+        remove_binaries(train_bin);
+        validator.validate(&model, &training)?;
+
+        let runtime = pool.runtime();
+
+        debug!("Connecting to nodes");
+        let mut handles = runtime.block_on(pool.checkout_all(&training.addrs))?;
+
+        let addr_ids: HashMap<_, _> = handles
+            .iter()
+            .map(|(addr, node_handle)| (addr.clone(), node_handle.id()))
+            .collect();
+
+        debug!("Requesting stats");
+        let mut stat_requester = StatRequester::new();
+        let stats = runtime.block_on(stat_requester.obtain_stats(&mut handles))?;
+        pool.checkin_all(handles);
+
+        debug!("Adapting configs");
+        let adapter = Adapter::new();
+        let (orch, workers, servers) = adapter.adapt_configs(model, &training, stats, addr_ids)?;
+
+        Ok(ValidationReport::new(&orch, &workers, &servers))
+    });
+
+    remove_binaries(dataset_bin);
+
+    let report = res?;
+    info!("Validated configs: {report}");
+    Ok(report)
+}
+
+/// Evaluates a model over a dataset without training it.
+///
+/// Every node in `training.addrs` is bootstrapped as a worker that only runs it's partition
+/// of the dataset forward through the model with the given weights, so no gradient is ever
+/// computed nor sent and no server is started. The loss of each partition is weighted by
+/// it's amount of samples into the loss over the whole dataset.
+///
+/// The dataset is read as `train` would but every row of it is evaluated, it's test split
+/// is ignored. Only the nodes, the dataset, the loss function and the batch size of
+/// `training` are used, though the configs are still validated as a whole.
+///
+/// # Args
+/// * `model` - The model architecture configuration.
+/// * `weights` - The parameters of the model to evaluate, laid out as the trained ones.
+/// * `training` - The training configuration, including the workers' addresses.
+///
+/// # Returns
+/// A report of the loss over the whole dataset and over each worker's partition.
+///
+/// # Errors
+/// Returns an `OrchErr` if dataset conversion fails, config validation fails, the weights
+/// don't match the model's parameters, connecting to any of the nodes fails or any of the
+/// workers fails while evaluating.
+pub fn evaluate(
+    model: ModelConfig,
+    weights: Vec<f32>,
+    training: TrainingConfig,
+) -> Result<EvaluationReport> {
+    let mut pool = OrchestratorPool::new()?;
+
+    if let Some(token) = &training.auth_token {
+        pool = pool.with_auth_token(token.as_bytes());
     }
 
-    Ok(handles)
+    evaluate_dataset(model, weights, training, &mut pool)
+}
+
+/// Reads the dataset and evaluates the model over it borrowing the node connections
+/// from `pool`.
+///
+/// # Args
+/// * `model` - The model architecture configuration.
+/// * `weights` - The parameters of the model to evaluate.
+/// * `training` - The training configuration, including the workers' addresses.
+/// * `pool` - The pool of node connections to borrow from.
+///
+/// # Returns
+/// A report of the loss over the whole dataset and over each worker's partition.
+///
+/// # Errors
+/// Returns an `OrchErr` if dataset conversion fails or the evaluation fails.
+fn evaluate_dataset(
+    model: ModelConfig,
+    weights: Vec<f32>,
+    mut training: TrainingConfig,
+    pool: &mut OrchestratorPool,
+) -> Result<EvaluationReport> {
+    read_stdin_dataset(&mut training.dataset)?;
+    let dataset_bin = generate_binary_dataset(&mut training.dataset.src);
+    training.dataset.test_split = 0.0;
+
+    let res = evaluate_with_pool(model, weights, training, pool);
+    remove_binaries(dataset_bin);
+
+    let report = res?;
+    info!(
+        "Evaluated {} samples in {:.2?}, loss: {:?}",
+        report.total_samples(),
+        report.wall_time(),
+        report.loss()
+    );
+
+    Ok(report)
+}
+
+/// Runs an evaluation session over a dataset that's already been read.
+///
+/// # Args
+/// * `model` - The model architecture configuration.
+/// * `weights` - The parameters of the model to evaluate.
+/// * `training` - The training configuration, including the workers' addresses.
+/// * `pool` - The pool of node connections to borrow from.
+///
+/// # Returns
+/// The evaluation's report.
+///
+/// # Errors
+/// Returns an `OrchErr` if config validation fails, the weights don't match the model's
+/// parameters, connecting to any of the nodes fails or any of the workers fails.
+fn evaluate_with_pool(
+    model: ModelConfig,
+    weights: Vec<f32>,
+    mut training: TrainingConfig,
+    pool: &mut OrchestratorPool,
+) -> Result<EvaluationReport> {
+    debug!("Validating configs");
+    let validator = Validator::new();
+    validator.validate(&model, &training)?;
+
+    let adapter = Adapter::new();
+    let (_, param_gens) = adapter.adapt_layers(&model, training.dataset.x_size);
+    let nparams: usize = param_gens.iter().flatten().map(ParamGenSpec::size).sum();
+
+    if weights.len() != nparams {
+        let text = format!(
+            "got {} weights to evaluate but the model has {nparams} parameters",
+            weights.len()
+        );
+        return Err(OrchErr::InvalidConfig(text));
+    }
+
+    // Every node is a worker holding the whole model, there's no server to shard it among.
+    training.algorithm = AlgorithmConfig::AllReduce;
+
+    let runtime = pool.runtime();
+
+    debug!("Connecting to nodes");
+    let mut handles = runtime.block_on(pool.checkout_all(&training.addrs))?;
+
+    let addr_ids: HashMap<_, _> = handles
+        .iter()
+        .map(|(addr, node_handle)| (addr.clone(), node_handle.id()))
+        .collect();
+
+    debug!("Requesting stats");
+    let mut stat_requester = StatRequester::new();
+    let stats = runtime.block_on(stat_requester.obtain_stats(&mut handles))?;
+
+    debug!("Adapting configs");
+    let (orch, mut workers, servers) =
+        match adapter.adapt_configs(model, &training, stats, addr_ids) {
+            Ok(adapted) => adapted,
+            Err(e) => {
+                pool.checkin_all(handles);
+                return Err(e);
+            }
+        };
+
+    for worker in &mut workers {
+        if let AlgorithmSpec::AllReduce {
+            amount_of_layers, ..
+        } = worker.spec.algorithm
+        {
+            let params = weights.clone();
+            let param_gen = ParamGenSpec::Inline { params };
+
+            worker.spec.algorithm = AlgorithmSpec::Evaluate {
+                param_gen,
+                amount_of_layers,
+            };
+        }
+    }
+
+    let worker_samples: Vec<_> = workers
+        .iter()
+        .map(|worker| {
+            worker
+                .partition
+                .nsamples(worker.spec.trainer.dataset.x_size)
+        })
+        .collect();
+
+    let start = Instant::now();
+    let session = Session::new(orch, workers, servers, handles, pool)?;
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut events = session.event_listener(cancel_rx);
+    let mut worker_losses = vec![None; worker_samples.len()];
+
+    loop {
+        match events.blocking_recv() {
+            Some(TrainingEvent::PublishedLosses {
+                worker_id, losses, ..
+            }) => worker_losses[worker_id] = losses.last().copied(),
+            Some(TrainingEvent::TrainingComplete { .. }) => break,
+            Some(TrainingEvent::Error(e)) => return Err(e),
+            Some(_) => {}
+            None => {
+                let text = "the session ended before every worker reported it's loss";
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, text).into());
+            }
+        }
+    }
+
+    Ok(EvaluationReport::new(
+        worker_losses,
+        worker_samples,
+        start.elapsed(),
+    ))
+}
+
+/// Starts the training with every node running inside this process.
+///
+/// Spawns one node per address in `training.addrs`, so the addresses themselves are
+/// ignored and only their amount matters. Useful for quick experiments and for
+/// debugging the whole system in a single process.
+///
+/// The nodes run on the session's runtime and talk to each other through in-memory
+/// streams instead of the network. They're stopped and joined once the session is over,
+/// or once it's dropped if it's never listened to.
+///
+/// The local nodes don't authenticate their peers, `training.auth_token` is ignored.
+///
+/// # Args
+/// * `model` - The model architecture configuration.
+/// * `training` - The training configuration.
+///
+/// # Returns
+/// A new ongoing session.
+///
+/// # Errors
+/// Returns an `OrchErr` if spawning the local nodes, dataset conversion, config
+/// validation or connecting to any of the nodes fails.
+#[cfg(feature = "local")]
+pub fn train_local(model: ModelConfig, mut training: TrainingConfig) -> Result<Session> {
+    let mut pool = OrchestratorPool::new()?;
+    let local_nodes = LocalNodes::spawn(training.addrs.len(), pool.runtime())?;
+
+    training.addrs = local_nodes.addrs().to_vec();
+    training.auth_token = None;
+
+    let session = train_with_pool(model, training, &mut pool)?;
+    Ok(session.with_local_nodes(local_nodes))
+}
+
+/// Evaluates a model with every node running inside this process.
+///
+/// Spawns the nodes as `train_local` does, one per address in `training.addrs`, and
+/// stops them once the evaluation is over.
+///
+/// # Args
+/// * `model` - The model architecture configuration.
+/// * `weights` - The parameters of the model to evaluate.
+/// * `training` - The training configuration.
+///
+/// # Returns
+/// A report of the loss over the whole dataset and over each worker's partition.
+///
+/// # Errors
+/// Returns an `OrchErr` if spawning the local nodes or the evaluation fails.
+#[cfg(feature = "local")]
+pub fn evaluate_local(
+    model: ModelConfig,
+    weights: Vec<f32>,
+    mut training: TrainingConfig,
+) -> Result<EvaluationReport> {
+    let mut pool = OrchestratorPool::new()?;
+    let local_nodes = LocalNodes::spawn(training.addrs.len(), pool.runtime())?;
+
+    training.addrs = local_nodes.addrs().to_vec();
+    training.auth_token = None;
+
+    let res = evaluate_dataset(model, weights, training, &mut pool);
+
+    // The idle nodes are disconnected along with the pool before stopping them.
+    drop(pool);
+    drop(local_nodes);
+    res
+}
+
+/// Reads a dataset streamed through stdin into an inline one.
+///
+/// # Args
+/// * `dataset` - The dataset's configuration.
+///
+/// # Errors
+/// An `OrchErr::InvalidConfig` if stdin is a terminal instead of a pipe or it doesn't
+/// hold a valid dataset.
+fn read_stdin_dataset(dataset: &mut DatasetConfig) -> Result<()> {
+    let DataSrc::Stdin { format } = dataset.src else {
+        return Ok(());
+    };
+
+    let stdin = io::stdin();
+
+    if stdin.is_terminal() {
+        let text = "the dataset is read from stdin but it's a terminal, pipe the dataset into it";
+        return Err(OrchErr::InvalidConfig(text.into()));
+    }
+
+    debug!("Reading the dataset from stdin");
+    let (samples, labels) = read_dataset(stdin.lock(), format, dataset.x_size, dataset.y_size)
+        .map_err(|e| OrchErr::InvalidConfig(format!("invalid stdin dataset: {e}")))?;
+
+    dataset.src = DataSrc::Inline { samples, labels };
+    Ok(())
+}
+
+/// Holds the rows of the dataset's test split out of the training.
+///
+/// An inline dataset is left with the rest of the rows inline and a CSR one with the rest
+/// of them sparse, only the held out ones are expanded. A local one is streamed
+/// instead of read whole, only the held out rows are kept in memory and the rest of them
+/// are copied to a pair of binaries in the temp dir the dataset then points to.
+///
+/// # Args
+/// * `dataset` - The dataset's configuration.
+/// * `seed` - The seed of the run, the same seed always holds out the same rows.
+///
+/// # Returns
+/// The held out samples and labels or `None` if the dataset has no test split, along with
+/// the paths of the binaries the rows to train on were copied to, if any.
+///
+/// # Errors
+/// An `OrchErr::InvalidConfig` if the split isn't in `[0, 1)` or the dataset's files
+/// can't be read as whole rows.
+fn hold_out_test_set(
+    dataset: &mut DatasetConfig,
+    seed: Option<u64>,
+) -> Result<(Option<Rows>, Option<(PathBuf, PathBuf)>)> {
+    let test_split = dataset.test_split;
+
+    if !(0.0..1.0).contains(&test_split) {
+        let text = format!("the test split ({test_split}) must be in [0, 1)");
+        return Err(OrchErr::InvalidConfig(text));
+    }
+
+    if test_split == 0.0 {
+        return Ok((None, None));
+    }
+
+    let (x_size, y_size) = (dataset.x_size.get(), dataset.y_size.get());
+
+    let (samples, labels) = match &mut dataset.src {
+        DataSrc::Inline { samples, labels } => (mem::take(samples), mem::take(labels)),
+        DataSrc::Local {
+            samples_path,
+            labels_path,
+        } => {
+            let train_bin = (
+                env::temp_dir().join(format!("{}.bin", Uuid::new_v4())),
+                env::temp_dir().join(format!("{}.bin", Uuid::new_v4())),
+            );
+
+            let test = sessions::hold_out_files(
+                samples_path,
+                labels_path,
+                x_size,
+                y_size,
+                test_split,
+                seed,
+                (&train_bin.0, &train_bin.1),
+            )
+            .map_err(|e| {
+                let _ = fs::remove_file(&train_bin.0);
+                let _ = fs::remove_file(&train_bin.1);
+
+                let text = format!(
+                    "cannot hold out the test split of dataset file {}: {e}",
+                    samples_path.display()
+                );
+                OrchErr::InvalidConfig(text)
+            })?;
+
+            info!(
+                "held out {} rows to test the model on",
+                test.0.len() / x_size
+            );
+
+            dataset.src = DataSrc::Local {
+                samples_path: train_bin.0.clone(),
+                labels_path: train_bin.1.clone(),
+            };
+
+            return Ok((Some(test), Some(train_bin)));
+        }
+        DataSrc::Synthetic {
+            nsamples,
+            noise_std,
+            seed,
+        } => {
+            // The rows are drawn independently of each other, holding out the last ones
+            // is as good as any and the workers still generate a contiguous range.
+            let nrows = nsamples.get();
+            let ntest = sessions::held_out_count(nrows, test_split);
+            let rows = nrows - ntest..nrows;
+
+            let Some(test) =
+                SyntheticDataset::generate_rows(rows, x_size, y_size, *noise_std, *seed)
+            else {
+                let text = format!(
+                    "the synthetic dataset's noise_std ({noise_std}) must be a finite non negative number"
+                );
+                return Err(OrchErr::InvalidConfig(text));
+            };
+
+            info!("held out {ntest} of the {nrows} rows to test the model on");
+
+            // SAFETY: At least a row is always left to train on.
+            *nsamples = NonZeroUsize::new(nrows - ntest).unwrap();
+            return Ok((Some((test.samples, test.labels)), None));
+        }
+        DataSrc::Csr {
+            indptr,
+            indices,
+            values,
+            labels,
+        } => {
+            let samples = CsrMatrix::new(
+                x_size,
+                mem::take(indptr),
+                mem::take(indices),
+                mem::take(values),
+            )
+            .map_err(|e| OrchErr::InvalidConfig(format!("invalid csr dataset: {e}")))?;
+
+            let nrows = samples.nrows();
+
+            if labels.len() != nrows * y_size {
+                let text = format!(
+                    "dataset has {} labels, expected {} for {nrows} samples",
+                    labels.len(),
+                    nrows * y_size
+                );
+                return Err(OrchErr::InvalidConfig(text));
+            }
+
+            let ((train, train_labels), test) =
+                sessions::hold_out_csr(&samples, labels, y_size, test_split, seed);
+
+            info!(
+                "held out {} of the {nrows} rows to test the model on",
+                test.0.len() / x_size
+            );
+
+            let (indptr, indices, values) = train.into_parts();
+            dataset.src = DataSrc::Csr {
+                indptr,
+                indices,
+                values,
+                labels: train_labels,
+            };
+
+            return Ok((Some(test), None));
+        }
+        DataSrc::Stdin { .. } => {
+            let text = "stdin datasets must be made inline before holding out a test split";
+            return Err(OrchErr::InvalidConfig(text.into()));
+        }
+    };
+
+    let nrows = samples.len() / x_size;
+
+    if samples.len() % x_size != 0 || labels.len() != nrows * y_size {
+        let text = format!(
+            "the dataset's {} samples and {} labels aren't whole rows",
+            samples.len(),
+            labels.len()
+        );
+        return Err(OrchErr::InvalidConfig(text));
+    }
+
+    let ((train_samples, train_labels), test) =
+        sessions::hold_out(&samples, &labels, x_size, y_size, test_split, seed);
+
+    info!(
+        "held out {} of the {nrows} rows to test the model on",
+        test.0.len() / x_size
+    );
+
+    dataset.src = DataSrc::Inline {
+        samples: train_samples,
+        labels: train_labels,
+    };
+
+    Ok((Some(test), None))
 }
 
 /// Converts delimited dataset samples and labels so the validator always operates on raw packed
@@ -164,6 +776,17 @@ fn generate_binary_dataset(src: &mut DataSrc) -> Option<(PathBuf, PathBuf)> {
     Some((samples_bin_path, labels_bin_path))
 }
 
+/// Removes a pair of binary dataset files, if any.
+///
+/// # Args
+/// * `binaries` - The paths of the samples and labels files to remove.
+fn remove_binaries(binaries: Option<(PathBuf, PathBuf)>) {
+    if let Some((samples_bin, labels_bin)) = binaries {
+        remove_binary(&samples_bin);
+        remove_binary(&labels_bin);
+    }
+}
+
 /// Removes a binary dataset file.
 ///
 /// # Args
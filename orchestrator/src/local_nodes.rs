@@ -0,0 +1,130 @@
+use std::{
+    io, mem,
+    sync::Arc,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use comms::LocalListener;
+use log::{debug, warn};
+use tokio::{runtime::Runtime, sync::watch};
+
+/// The maximum amount of time to wait for the local nodes to stop once they're dropped.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The interval in between checks of whether the local nodes stopped.
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Nodes running inside this process, on the orchestrator's runtime.
+///
+/// The nodes listen on local listeners, so every connection to and between them is an
+/// in-memory stream instead of a tcp one. Each node is driven by it's own thread, as the
+/// nodes block on their training steps, and dropping them stops and joins every thread.
+pub(crate) struct LocalNodes {
+    addrs: Vec<String>,
+    threads: Vec<JoinHandle<()>>,
+    stop_tx: Option<watch::Sender<()>>,
+}
+
+impl LocalNodes {
+    /// Spawns the nodes on the given runtime.
+    ///
+    /// # Args
+    /// * `amount` - The amount of nodes to spawn.
+    /// * `runtime` - The runtime to run the nodes on.
+    ///
+    /// # Returns
+    /// A new `LocalNodes` instance or an io error if a thread couldn't be spawned.
+    pub(crate) fn spawn(amount: usize, runtime: Arc<Runtime>) -> io::Result<Self> {
+        let (stop_tx, stop_rx) = watch::channel(());
+
+        let mut local_nodes = Self {
+            addrs: Vec::with_capacity(amount),
+            threads: Vec::with_capacity(amount),
+            stop_tx: Some(stop_tx),
+        };
+
+        for i in 0..amount {
+            let listener = LocalListener::bind();
+            local_nodes.addrs.push(listener.local_addr());
+
+            let (runtime, mut stop_rx) = (runtime.clone(), stop_rx.clone());
+
+            let thread = thread::Builder::new()
+                .name(format!("local-node-{i}"))
+                .spawn(move || {
+                    runtime.block_on(async move {
+                        tokio::select! {
+                            res = node::serve(listener, None, None, None, None) => {
+                                if let Err(e) = res {
+                                    debug!("local node stopped: {e}");
+                                }
+                            }
+                            _ = stop_rx.changed() => {}
+                        }
+                    })
+                })?;
+
+            local_nodes.threads.push(thread);
+        }
+
+        debug!("Spawned {amount} local nodes");
+        Ok(local_nodes)
+    }
+
+    /// The addresses the nodes listen on.
+    ///
+    /// # Returns
+    /// The nodes' addresses.
+    pub(crate) fn addrs(&self) -> &[String] {
+        &self.addrs
+    }
+}
+
+impl Drop for LocalNodes {
+    fn drop(&mut self) {
+        for addr in &self.addrs {
+            LocalListener::unbind(addr);
+        }
+
+        // The nodes still in a session drop it the next time they're polled.
+        drop(self.stop_tx.take());
+
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        while self.threads.iter().any(|thread| !thread.is_finished()) {
+            if Instant::now() >= deadline {
+                warn!("the local nodes didn't stop in {SHUTDOWN_TIMEOUT:?}, detaching them");
+                return;
+            }
+
+            thread::sleep(SHUTDOWN_POLL_INTERVAL);
+        }
+
+        for thread in mem::take(&mut self.threads) {
+            if thread.join().is_err() {
+                warn!("a local node panicked");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dropping_the_local_nodes_stops_and_joins_them() {
+        let runtime = Arc::new(Runtime::new().unwrap());
+        let local_nodes = LocalNodes::spawn(3, runtime.clone()).unwrap();
+        let addrs = local_nodes.addrs().to_vec();
+
+        let start = Instant::now();
+        drop(local_nodes);
+
+        assert!(start.elapsed() < SHUTDOWN_TIMEOUT);
+        assert!(addrs.iter().all(|addr| !LocalListener::is_bound(addr)));
+
+        // Every node's thread released it's handle to the runtime once joined.
+        assert_eq!(Arc::strong_count(&runtime), 1);
+    }
+}
@@ -8,11 +8,17 @@ use std::{
 
 use comms::floats::{Float01, FloatNonNegative, FloatPositive};
 use log::info;
-use orchestrator::{CancelHandle, TrainingEvent, configs::*, train};
+use orchestrator::{CancelHandle, TrainingEvent, configs::*, sessions::ProgressBar, train};
 
 const MODEL_OUTPUT_PATH: &str = "model.safetensors";
+
+// The amount of trained params printed, the whole model is saved to `MODEL_OUTPUT_PATH`.
+const PARAMS_PREVIEW: usize = 10;
 const NODE_BASE_PORT: usize = 40_000;
 
+// Set to any value to hide the training's progress on stderr.
+const NO_PROGRESS_VAR: &str = "ONO_NO_PROGRESS";
+
 // The file path for the compose up script file.
 const COMPOSE_FILE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../docker/compose_up.py");
 
@@ -68,6 +74,8 @@ fn make_nielsen_mnist_model() -> ModelConfig {
             padding: 0,
             init: Kaiming,
             act_fn: None,
+            frozen: false,
+            init_gain: None,
         },
         // MaxPooling {
         //     input_dim: (nonzero(10), nonzero(24), nonzero(24)),
@@ -82,15 +90,27 @@ fn make_nielsen_mnist_model() -> ModelConfig {
             act_fn: Some(ReLU {
                 slope: Float01::new(0.0).unwrap(),
             }),
+            tie: None,
+            frozen: false,
+            init_gain: None,
         },
         Dense {
             output_size: nonzero(10),
             init: Kaiming,
             act_fn: Some(Softmax),
+            tie: None,
+            frozen: false,
+            init_gain: None,
         },
     ];
 
-    ModelConfig { layers }
+    ModelConfig {
+        layers,
+        heads: Vec::new(),
+        checkpoint_every: None,
+        activation_clamp: None,
+        warm_start: None,
+    }
 }
 
 fn make_mnist_dataset() -> DatasetConfig {
@@ -106,6 +126,7 @@ fn make_mnist_dataset() -> DatasetConfig {
         src,
         x_size,
         y_size,
+        test_split: 0.0,
     }
 }
 
@@ -148,6 +169,7 @@ fn main() -> io::Result<()> {
         serializer: SerializerConfig::SparseCapable {
             r: Float01::new(0.9).unwrap(),
         },
+        quantization: QuantizationConfig::None,
         dataset: make_mnist_dataset(),
         optimizer: OptimizerConfig::GradientDescentWithMomentum {
             lr: FloatPositive::new(0.1).unwrap(),
@@ -161,27 +183,81 @@ fn main() -> io::Result<()> {
         seed: Some(42),
         early_stopping: Some(EarlyStoppingConfig {
             tolerance: FloatNonNegative::new(0.02).unwrap(),
+            min_epochs: 0,
         }),
+        divergence_policy: DivergencePolicyConfig::None,
+        monitor_interval: None,
+        auth_token: None,
+        allow_worker_loss: false,
+        min_workers: None,
+        max_workers: None,
+        max_frame_size: None,
+        chunk_size: None,
+        slow_send_ms: None,
+        reduction: ReductionConfig::Sum,
+        barrier_timeout: None,
+        update_rule: UpdateRuleConfig::default(),
+        strategy: StrategyConfig::default(),
+        empty_shard: EmptyShardConfig::default(),
+        stop_condition: None,
+        final_step: FinalStepPolicyConfig::ApplyPartial,
+        sampler: SamplerConfig::Shuffle,
+        lr_schedule: None,
+        lr_scaling: LrScalingConfig::None,
+        ema_decay: None,
+        server_momentum: None,
+        track_staleness: false,
+        metric_barrier_every: None,
+        accum_f64: false,
+        accum_f64_layers: Vec::new(),
+        bias_only: false,
+        server_synchronizers: Vec::new(),
+        require_compression: false,
+        clip_groups: Vec::new(),
+        learning_curve_path: None,
+        snapshot_every: None,
+        event_feed_path: None,
+        latency_warmup_rounds: None,
     };
 
+    // The workers' ids are below the amount of nodes, the servers never publish losses.
+    let nworkers = training_config.addrs.len();
+    let max_epochs = training_config.max_epochs;
+
     let start = Instant::now();
     let session = train(model_config, training_config).unwrap();
+    let mut progress = env::var_os(NO_PROGRESS_VAR)
+        .is_none()
+        .then(|| ProgressBar::stderr(nworkers, max_epochs));
     let (_cancel, cancel_rx) = CancelHandle::pair();
     let mut rx = session.event_listener(cancel_rx);
 
     loop {
-        match rx.blocking_recv() {
-            Some(TrainingEvent::PublishedLosses { losses, worker_id }) => {
+        let event = rx.blocking_recv();
+
+        if let (Some(progress), Some(event)) = (&mut progress, &event) {
+            progress.observe(event)?;
+        }
+
+        match event {
+            Some(TrainingEvent::PublishedLosses {
+                losses, worker_id, ..
+            }) => {
                 info!("losses: {worker_id}: {losses:?}");
             }
-            Some(TrainingEvent::TrainingComplete {
-                model: trained,
-                stop_reason: reason,
-            }) => {
-                info!("params: {:?}", trained.params);
-                info!("stop reason: {reason:?}");
+            Some(TrainingEvent::TrainingComplete { report }) => {
+                info!("params: {}", report.params_preview(PARAMS_PREVIEW));
+                info!("stop reason: {:?}", report.stop_reason());
+                info!(
+                    "ran {} epochs over {} samples in {:?}, final loss: {:?}",
+                    report.epochs_run(),
+                    report.total_samples(),
+                    report.wall_time(),
+                    report.final_loss(),
+                );
 
-                trained
+                report
+                    .model()
                     .save_safetensors(MODEL_OUTPUT_PATH)
                     .expect("failed to save model");
 
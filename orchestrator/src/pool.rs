@@ -0,0 +1,442 @@
+use std::{
+    collections::BTreeMap,
+    mem,
+    sync::{Arc, Mutex, MutexGuard, Weak},
+    time::Duration,
+};
+
+use comms::{
+    Connector, NetReadHalf, NetRtp, NetWriteHalf, NodeEvent, NodeHandle, protocol::Entity,
+};
+use futures::future;
+use log::{debug, warn};
+use tokio::{
+    runtime::{Builder, Runtime},
+    time,
+};
+use uuid::Uuid;
+
+use crate::{OrchErr, Result};
+
+type R = NetReadHalf;
+type W = NetWriteHalf;
+type TransportFactory = fn(R, W) -> NetRtp;
+type IdleHandles = BTreeMap<String, NodeHandle<NetRtp>>;
+
+/// The maximum amount of time to wait for each resolved address when connecting to a node.
+const CONNECT_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximum amount of time to wait for a pooled connection to answer a ping.
+const LIVENESS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A pool of established node connections that can be reused across training sessions.
+///
+/// Every connection in the pool belongs to an idle node waiting to be bootstrapped. Sessions
+/// borrow connections from the pool instead of dialing the nodes from scratch, a pooled
+/// connection that died in the meantime is transparently replaced by a new one. Once a
+/// session is over, the connections of the nodes it bootstrapped are checked back in.
+///
+/// The pool also owns the runtime where the connections are driven, since the sockets
+/// are bound to it, every session started from this pool runs on it. Dropping the pool
+/// disconnects the idle nodes.
+pub struct OrchestratorPool {
+    runtime: Arc<Runtime>,
+    connector: Connector<R, W, NetRtp, TransportFactory>,
+    handles: Arc<Mutex<IdleHandles>>,
+    dials: usize,
+}
+
+/// The way back into the pool for the connections of the nodes a session bootstrapped.
+///
+/// It doesn't keep the pool alive, the connections returned once the pool was dropped
+/// are disconnected instead.
+#[derive(Clone)]
+pub(crate) struct PoolReturn {
+    handles: Weak<Mutex<IdleHandles>>,
+}
+
+impl OrchestratorPool {
+    /// Creates a new empty `OrchestratorPool`.
+    ///
+    /// # Returns
+    /// A new `OrchestratorPool` instance or an io error if the runtime couldn't be built.
+    pub fn new() -> Result<Self> {
+        let runtime = Builder::new_multi_thread().enable_all().build()?;
+
+        let pool = Self {
+            runtime: Arc::new(runtime),
+            connector: Self::connector(),
+            handles: Arc::new(Mutex::new(BTreeMap::new())),
+            dials: 0,
+        };
+
+        Ok(pool)
+    }
+
+    /// Sets the pre-shared token used to authenticate against the nodes.
+    ///
+    /// # Args
+    /// * `token` - The pre-shared token the nodes were started with.
+    ///
+    /// # Returns
+    /// The modified `OrchestratorPool`.
+    pub fn with_auth_token(mut self, token: impl Into<Vec<u8>>) -> Self {
+        let connector = mem::replace(&mut self.connector, Self::connector());
+        self.connector = connector.with_auth_token(token);
+        self
+    }
+
+    /// Creates the connector to dial the nodes with.
+    ///
+    /// # Returns
+    /// A new connector without an auth token.
+    fn connector() -> Connector<R, W, NetRtp, TransportFactory> {
+        // TODO: De momento lo dejaría acá, no creo que sea muy importante poder
+        //       configurar esto, si tenemos tiempo y vemos que viene bien lo
+        //       podemos mover y que sea parte de un `CommsConfig`.
+        let transport_factory: TransportFactory = |rx, tx| {
+            comms::build_reliable_transport(
+                rx,
+                tx,
+                Duration::from_secs(5),
+                Duration::from_secs(30),
+                Duration::from_secs(2),
+                2,
+                4,
+            )
+        };
+
+        Connector::new(Uuid::nil(), transport_factory)
+    }
+
+    /// The runtime driving the pooled connections.
+    ///
+    /// # Returns
+    /// A shared reference to the pool's runtime.
+    pub fn runtime(&self) -> Arc<Runtime> {
+        self.runtime.clone()
+    }
+
+    /// The amount of idle connections currently in the pool.
+    ///
+    /// # Returns
+    /// The number of pooled connections.
+    pub fn len(&self) -> usize {
+        self.idle().len()
+    }
+
+    /// Whether the pool has no idle connections.
+    ///
+    /// # Returns
+    /// `true` if there are no pooled connections.
+    pub fn is_empty(&self) -> bool {
+        self.idle().is_empty()
+    }
+
+    /// The amount of connections the pool dialed, the reused ones aren't counted again.
+    ///
+    /// # Returns
+    /// The number of connections established since the pool was created.
+    pub fn dials(&self) -> usize {
+        self.dials
+    }
+
+    /// Establishes a connection to every given node that isn't already pooled.
+    ///
+    /// Useful in between sessions so the next one finds its connections ready.
+    ///
+    /// # Args
+    /// * `addrs` - The network addresses of the nodes.
+    ///
+    /// # Errors
+    /// Returns an `OrchErr` if connecting to any of the nodes fails.
+    pub fn warm_up(&mut self, addrs: &[String]) -> Result<()> {
+        let runtime = self.runtime.clone();
+        let handles = runtime.block_on(self.checkout_all(addrs))?;
+        self.idle().extend(handles);
+        Ok(())
+    }
+
+    /// Returns an idle node connection to the pool so a later session can borrow it.
+    ///
+    /// # Args
+    /// * `addr` - The network address of the node.
+    /// * `node_handle` - The handle for communicating with the node.
+    pub fn checkin(&mut self, addr: String, node_handle: NodeHandle<NetRtp>) {
+        self.idle().insert(addr, node_handle);
+    }
+
+    /// Returns many idle node connections to the pool at once.
+    ///
+    /// # Args
+    /// * `handles` - The node handles keyed by their address.
+    pub(crate) fn checkin_all<I>(&mut self, handles: I)
+    where
+        I: IntoIterator<Item = (String, NodeHandle<NetRtp>)>,
+    {
+        self.idle().extend(handles);
+    }
+
+    /// Creates the way back into the pool for the connections of a session's nodes.
+    ///
+    /// # Returns
+    /// A new `PoolReturn` for this pool.
+    pub(crate) fn pool_return(&self) -> PoolReturn {
+        PoolReturn {
+            handles: Arc::downgrade(&self.handles),
+        }
+    }
+
+    /// Locks the idle connections of the pool.
+    ///
+    /// # Returns
+    /// The guard of the pooled connections.
+    fn idle(&self) -> MutexGuard<'_, IdleHandles> {
+        // SAFETY: The lock is never held while something may panic.
+        self.handles.lock().unwrap()
+    }
+
+    /// Borrows a connection for each of the given nodes.
+    ///
+    /// Pooled connections are checked to be alive before being handed out, the ones that
+    /// aren't and the ones missing from the pool are (re)established concurrently.
+    ///
+    /// # Args
+    /// * `addrs` - The network addresses of the nodes.
+    ///
+    /// # Returns
+    /// The node handles keyed by their address.
+    ///
+    /// # Errors
+    /// Returns an `OrchErr` if connecting to any of the nodes fails.
+    pub(crate) async fn checkout_all(
+        &mut self,
+        addrs: &[String],
+    ) -> Result<BTreeMap<String, NodeHandle<NetRtp>>> {
+        let mut handles = BTreeMap::new();
+        let mut missing = Vec::new();
+
+        let pooled: Vec<_> = {
+            let mut idle = self.idle();
+            addrs.iter().map(|addr| (addr, idle.remove(addr))).collect()
+        };
+
+        for (addr, node_handle) in pooled {
+            let Some(mut node_handle) = node_handle else {
+                missing.push(addr);
+                continue;
+            };
+
+            if Self::is_alive(&mut node_handle).await {
+                debug!("reusing pooled connection to {addr}");
+                handles.insert(addr.clone(), node_handle);
+            } else {
+                warn!("pooled connection to {addr} died, reconnecting");
+                missing.push(addr);
+            }
+        }
+
+        self.dials += missing.len();
+
+        let connector = &self.connector;
+        let futs = missing.into_iter().map(|addr| async move {
+            let node_handle = Self::connect(connector, addr).await?;
+            Ok::<_, OrchErr>((addr.clone(), node_handle))
+        });
+
+        handles.extend(future::try_join_all(futs).await?);
+        Ok(handles)
+    }
+
+    /// Checks whether a pooled connection is still alive by pinging the node.
+    ///
+    /// # Args
+    /// * `node_handle` - The handle for communicating with the node.
+    ///
+    /// # Returns
+    /// `true` if the node answered the ping in time.
+    async fn is_alive(node_handle: &mut NodeHandle<NetRtp>) -> bool {
+        if node_handle.ping().await.is_err() {
+            return false;
+        }
+
+        matches!(
+            time::timeout(LIVENESS_TIMEOUT, node_handle.recv_event()).await,
+            Ok(Ok(NodeEvent::Pong))
+        )
+    }
+
+    /// Establishes a new connection to a node.
+    ///
+    /// # Args
+    /// * `connector` - The connector to create the network connection.
+    /// * `addr` - The network address of the node.
+    ///
+    /// # Returns
+    /// The node's handle or an orch error if occurred.
+    async fn connect(
+        connector: &Connector<R, W, NetRtp, TransportFactory>,
+        addr: &str,
+    ) -> Result<NodeHandle<NetRtp>> {
+        debug!("connecting to node at {addr}");
+
+        let conn_err = |e| OrchErr::ConnectionFailed {
+            addr: addr.to_string(),
+            source: e,
+        };
+
+        let stream = comms::connect(addr, CONNECT_ATTEMPT_TIMEOUT)
+            .await
+            .map_err(conn_err)?;
+        let peer_addr = stream.peer_addr().map_err(conn_err)?;
+        let (rx, tx) = stream.into_split();
+
+        let node_handle = connector
+            .connect_node(rx, tx, Entity::Orchestrator)
+            .await
+            .map_err(conn_err)?;
+
+        match (node_handle.port(), peer_addr) {
+            (Some(port), Some(peer_addr)) if port != peer_addr.port() => {
+                warn!("node at {addr} reports listening on port {port}, peers may not reach it")
+            }
+            _ => {}
+        }
+
+        Ok(node_handle)
+    }
+}
+
+impl PoolReturn {
+    /// Returns the connections of the nodes that went back to idle once their session
+    /// was over, they're disconnected if the pool was already dropped.
+    ///
+    /// # Args
+    /// * `handles` - The node handles keyed by their address.
+    pub(crate) async fn checkin_all<I>(&self, handles: I)
+    where
+        I: IntoIterator<Item = (String, NodeHandle<NetRtp>)>,
+    {
+        let Some(pooled) = self.handles.upgrade() else {
+            disconnect_all(handles.into_iter().map(|(_, node_handle)| node_handle)).await;
+            return;
+        };
+
+        // SAFETY: The lock is never held while something may panic.
+        pooled.lock().unwrap().extend(handles);
+    }
+}
+
+impl Drop for OrchestratorPool {
+    fn drop(&mut self) {
+        let handles = mem::take(&mut *self.idle());
+        self.runtime.block_on(disconnect_all(handles.into_values()));
+    }
+}
+
+/// Disconnects the given idle nodes.
+///
+/// The idle nodes would otherwise wait for their connection to time out before accepting
+/// a new orchestrator.
+///
+/// # Args
+/// * `node_handles` - The handles of the idle nodes.
+async fn disconnect_all<I>(node_handles: I)
+where
+    I: IntoIterator<Item = NodeHandle<NetRtp>>,
+{
+    let futs = node_handles.into_iter().map(|mut node_handle| async move {
+        let _ = node_handle.disconnect().await;
+    });
+
+    future::join_all(futs).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            mpsc,
+        },
+        thread,
+    };
+
+    use comms::{Acceptor, Connection, OrchEvent};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Spawns a mock node that answers pings and counts the accepted connections, the
+    /// first `ndrop` connections are dropped right after being accepted.
+    fn spawn_mock_node(ndrop: usize) -> (String, Arc<AtomicUsize>) {
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        let (addr_tx, addr_rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+
+            runtime.block_on(async move {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                addr_tx.send(listener.local_addr().unwrap()).unwrap();
+
+                let transport_factory = async || {
+                    let (stream, _) = listener.accept().await?;
+                    let (rx, tx) = stream.into_split();
+                    Ok(comms::build_simple_transport(rx, tx))
+                };
+
+                let mut acceptor = Acceptor::new(Uuid::new_v4(), transport_factory);
+
+                while let Ok(Connection::Orch(mut orch_handle)) =
+                    acceptor.accept(Entity::Node).await
+                {
+                    if counter.fetch_add(1, Ordering::SeqCst) < ndrop {
+                        continue;
+                    }
+
+                    while let Ok(OrchEvent::Ping) = orch_handle.recv_event().await {
+                        orch_handle.pong().await.unwrap();
+                    }
+                }
+            });
+        });
+
+        (addr_rx.recv().unwrap().to_string(), accepted)
+    }
+
+    #[test]
+    fn test_checkout_reuses_pooled_connection() {
+        let mut pool = OrchestratorPool::new().unwrap();
+        let (addr, accepted) = spawn_mock_node(0);
+        let addrs = [addr.clone()];
+
+        pool.warm_up(&addrs).unwrap();
+        assert_eq!(pool.len(), 1);
+
+        let runtime = pool.runtime();
+        let handles = runtime.block_on(pool.checkout_all(&addrs)).unwrap();
+
+        assert!(handles.contains_key(&addr));
+        assert!(pool.is_empty());
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.dials(), 1);
+    }
+
+    #[test]
+    fn test_checkout_reconnects_dead_connection() {
+        let mut pool = OrchestratorPool::new().unwrap();
+        let (addr, accepted) = spawn_mock_node(1);
+        let addrs = [addr.clone()];
+
+        pool.warm_up(&addrs).unwrap();
+
+        let runtime = pool.runtime();
+        let mut handles = runtime.block_on(pool.checkout_all(&addrs)).unwrap();
+        let node_handle = handles.get_mut(&addr).unwrap();
+
+        assert!(runtime.block_on(OrchestratorPool::is_alive(node_handle)));
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    }
+}
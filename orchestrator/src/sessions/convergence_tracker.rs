@@ -7,6 +7,8 @@ use super::GreaterThanOneUsize;
 pub struct ConvergenceTracker {
     winsize: GreaterThanOneUsize,
     tolerance: FloatNonNegative,
+    min_epochs: usize,
+    epochs: usize,
     last: Option<f64>,
     count: usize,
 }
@@ -17,23 +19,42 @@ impl ConvergenceTracker {
     /// # Args
     /// * `winsize` - The amount of losses to check.
     /// * `tolerance` - The tolerance of the delta between subsequent losses.
+    /// * `min_epochs` - The amount of epochs before the losses start being recorded.
     ///
     /// # Returns
     /// A new `ConvergenceTracker` instance.
-    pub fn new(winsize: GreaterThanOneUsize, tolerance: FloatNonNegative) -> Self {
+    pub fn new(
+        winsize: GreaterThanOneUsize,
+        tolerance: FloatNonNegative,
+        min_epochs: usize,
+    ) -> Self {
         Self {
             winsize,
             tolerance,
+            min_epochs,
+            epochs: 0,
             last: None,
             count: 0,
         }
     }
 
-    /// Records a new loss.
+    /// Counts the epochs of a finished training round.
+    ///
+    /// # Args
+    /// * `epochs` - The amount of epochs the round took.
+    pub fn elapse(&mut self, epochs: usize) {
+        self.epochs += epochs;
+    }
+
+    /// Records a new loss, ignored until `min_epochs` epochs elapsed.
     ///
     /// # Args
     /// * `loss` - The latest loss.
     pub fn record(&mut self, loss: f64) {
+        if self.epochs < self.min_epochs {
+            return;
+        }
+
         if let Some(last) = self.last {
             let delta = (last - loss).abs();
 
@@ -54,3 +75,29 @@ impl ConvergenceTracker {
         self.count == *self.winsize
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stopped_at(min_epochs: usize, losses: &[f64]) -> Option<usize> {
+        let winsize = GreaterThanOneUsize::new(3).unwrap();
+        let tolerance = FloatNonNegative::new(0.01).unwrap();
+        let mut tracker = ConvergenceTracker::new(winsize, tolerance, min_epochs);
+
+        losses.iter().position(|&loss| {
+            tracker.elapse(1);
+            tracker.record(loss);
+            tracker.converged()
+        })
+    }
+
+    #[test]
+    fn test_min_epochs_holds_the_stop_back_through_a_noisy_start() {
+        // The loss stalls over the first epochs before it starts to improve.
+        let losses = [2.0, 2.0, 2.0, 1.5, 1.0, 0.5, 0.2, 0.1, 0.1, 0.1];
+
+        assert_eq!(stopped_at(0, &losses), Some(2));
+        assert_eq!(stopped_at(4, &losses), Some(9));
+    }
+}
@@ -0,0 +1,136 @@
+use comms::floats::FloatPositive;
+use log::warn;
+
+use super::GreaterThanOneUsize;
+
+/// What to do about a diverging training.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Divergence {
+    /// Set the servers' learning rate to this one.
+    HalveLr(FloatPositive),
+    /// Stop the training.
+    Abort,
+}
+
+/// Tracks wheather the training is diverging, the loss growing for many rounds in a row.
+#[derive(Debug)]
+pub struct DivergenceTracker {
+    winsize: GreaterThanOneUsize,
+    lr: Option<FloatPositive>,
+    reductions_left: usize,
+    last: Option<f64>,
+    count: usize,
+}
+
+impl DivergenceTracker {
+    /// Creates a new `DivergenceTracker` which halves the learning rate once the training diverges.
+    ///
+    /// # Args
+    /// * `winsize` - The amount of rounds the loss must grow for in a row.
+    /// * `lr` - The servers' current learning rate.
+    /// * `max_reductions` - The maximum amount of times the learning rate is halved.
+    ///
+    /// # Returns
+    /// A new `DivergenceTracker` instance.
+    pub fn halving(winsize: GreaterThanOneUsize, lr: FloatPositive, max_reductions: usize) -> Self {
+        Self {
+            winsize,
+            lr: Some(lr),
+            reductions_left: max_reductions,
+            last: None,
+            count: 0,
+        }
+    }
+
+    /// Creates a new `DivergenceTracker` which stops the training once it diverges.
+    ///
+    /// # Args
+    /// * `winsize` - The amount of rounds the loss must grow for in a row.
+    ///
+    /// # Returns
+    /// A new `DivergenceTracker` instance.
+    pub fn aborting(winsize: GreaterThanOneUsize) -> Self {
+        Self {
+            winsize,
+            lr: None,
+            reductions_left: 0,
+            last: None,
+            count: 0,
+        }
+    }
+
+    /// Takes note of a learning rate set by the user, the following halvings start from it.
+    ///
+    /// # Args
+    /// * `lr` - The servers' new learning rate.
+    pub fn set_lr(&mut self, lr: FloatPositive) {
+        if self.lr.is_some() {
+            self.lr = Some(lr);
+        }
+    }
+
+    /// Records a new loss.
+    ///
+    /// A loss that isn't finite always counts as a growing one.
+    ///
+    /// # Args
+    /// * `loss` - The latest loss.
+    ///
+    /// # Returns
+    /// What to do about the training if it diverged.
+    pub fn record(&mut self, loss: f64) -> Option<Divergence> {
+        let grew = !loss.is_finite() || self.last.is_some_and(|last| loss > last);
+        self.count = if grew { self.count + 1 } else { 0 };
+        self.last = Some(loss);
+
+        if self.count < *self.winsize {
+            return None;
+        }
+
+        self.count = 0;
+
+        let Some(lr) = self.lr else {
+            return Some(Divergence::Abort);
+        };
+
+        if self.reductions_left == 0 {
+            warn!("the training diverges but the learning rate was already halved the most times");
+            return None;
+        }
+
+        // SAFETY: Half of a positive number is still positive.
+        let halved = FloatPositive::new(*lr / 2.0).unwrap();
+        self.reductions_left -= 1;
+        self.lr = Some(halved);
+        Some(Divergence::HalveLr(halved))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_the_learning_rate_is_halved_at_most_max_reductions_times() {
+        let winsize = GreaterThanOneUsize::new(2).unwrap();
+        let lr = FloatPositive::new(1.0).unwrap();
+        let mut tracker = DivergenceTracker::halving(winsize, lr, 2);
+
+        let halvings: Vec<_> = (0..10).filter_map(|i| tracker.record(i as f64)).collect();
+
+        let expected = [0.5, 0.25].map(|lr| Divergence::HalveLr(FloatPositive::new(lr).unwrap()));
+        assert_eq!(halvings, expected);
+    }
+
+    #[test]
+    fn test_a_falling_loss_resets_the_count() {
+        let winsize = GreaterThanOneUsize::new(3).unwrap();
+        let mut tracker = DivergenceTracker::aborting(winsize);
+
+        for loss in [1.0, 2.0, 3.0, 1.0, 2.0, 3.0] {
+            assert_eq!(tracker.record(loss), None);
+        }
+
+        assert_eq!(tracker.record(f64::NAN), Some(Divergence::Abort));
+    }
+}
@@ -0,0 +1,64 @@
+use std::{ops::ControlFlow, sync::Arc};
+
+use serde::Serialize;
+
+use crate::{OrchErr, Result};
+
+/// The metrics of the training gathered once every worker finished an epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EpochSummary {
+    /// The amount of epochs run by the furthest worker.
+    pub epoch: usize,
+    /// The loss of the round, the worst one among the workers.
+    pub loss: f64,
+}
+
+/// A user callback invoked after every epoch of a training session.
+#[derive(Clone)]
+pub struct EpochHook(Arc<dyn Fn(&EpochSummary) -> ControlFlow<()> + Send + Sync>);
+
+impl EpochHook {
+    /// Creates a new `EpochHook`.
+    ///
+    /// # Args
+    /// * `callback` - The callback to invoke, returning `ControlFlow::Break` stops the training.
+    ///
+    /// # Returns
+    /// A new `EpochHook` instance.
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&EpochSummary) -> ControlFlow<()> + Send + Sync + 'static,
+    {
+        Self(Arc::new(callback))
+    }
+
+    /// Invokes the callback in a blocking thread, so a slow callback doesn't hold back
+    /// the runtime's tasks.
+    ///
+    /// # Args
+    /// * `summary` - The metrics of the epoch that just finished.
+    ///
+    /// # Returns
+    /// Whether the training should continue or stop.
+    ///
+    /// # Errors
+    /// An `OrchErr::EpochHook` if the callback panicked.
+    pub async fn call(&self, summary: EpochSummary) -> Result<ControlFlow<()>> {
+        let callback = Arc::clone(&self.0);
+
+        tokio::task::spawn_blocking(move || callback(&summary))
+            .await
+            .map_err(|e| {
+                let text = match e.try_into_panic() {
+                    Ok(payload) => payload
+                        .downcast_ref::<&str>()
+                        .map(|msg| msg.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "unknown panic".into()),
+                    Err(e) => e.to_string(),
+                };
+
+                OrchErr::EpochHook(text)
+            })
+    }
+}
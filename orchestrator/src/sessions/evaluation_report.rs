@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+/// The summary of an evaluation of a model over a dataset.
+#[derive(Debug)]
+pub struct EvaluationReport {
+    loss: Option<f64>,
+    worker_losses: Vec<Option<f64>>,
+    worker_samples: Vec<u64>,
+    wall_time: Duration,
+}
+
+impl EvaluationReport {
+    /// Creates a new `EvaluationReport`.
+    ///
+    /// # Args
+    /// * `worker_losses` - The mean loss over the partition of each worker, `None` for the empty ones.
+    /// * `worker_samples` - The amount of samples in the partition of each worker.
+    /// * `wall_time` - The time from the start of the evaluation until every worker reported.
+    ///
+    /// # Returns
+    /// A new `EvaluationReport` instance.
+    pub fn new(
+        worker_losses: Vec<Option<f64>>,
+        worker_samples: Vec<u64>,
+        wall_time: Duration,
+    ) -> Self {
+        let (loss_sum, nsamples) = worker_losses
+            .iter()
+            .zip(&worker_samples)
+            .filter_map(|(loss, &samples)| loss.map(|loss| (loss, samples)))
+            .fold((0.0, 0), |(loss_sum, nsamples), (loss, samples)| {
+                (loss_sum + loss * samples as f64, nsamples + samples)
+            });
+
+        // Every worker's loss is weighted by it's samples, as if a single one went over all of them.
+        let loss = (nsamples > 0).then(|| loss_sum / nsamples as f64);
+
+        Self {
+            loss,
+            worker_losses,
+            worker_samples,
+            wall_time,
+        }
+    }
+
+    /// The mean loss over every sample of the dataset.
+    ///
+    /// # Returns
+    /// The loss or `None` if the dataset was empty.
+    pub fn loss(&self) -> Option<f64> {
+        self.loss
+    }
+
+    /// The mean loss over the partition of each worker.
+    ///
+    /// # Returns
+    /// The loss of every worker, `None` for the ones with an empty partition.
+    pub fn worker_losses(&self) -> &[Option<f64>] {
+        &self.worker_losses
+    }
+
+    /// The amount of samples evaluated by all the workers.
+    ///
+    /// # Returns
+    /// The total amount of samples evaluated.
+    pub fn total_samples(&self) -> u64 {
+        self.worker_samples.iter().sum()
+    }
+
+    /// The time from the start of the evaluation until every worker reported.
+    ///
+    /// # Returns
+    /// The wall clock duration of the evaluation.
+    pub fn wall_time(&self) -> Duration {
+        self.wall_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_the_loss_weighs_every_worker_by_their_samples() {
+        let report = EvaluationReport::new(
+            vec![Some(1.0), Some(4.0), None],
+            vec![3, 1, 0],
+            Duration::ZERO,
+        );
+
+        assert_eq!(report.loss(), Some(7.0 / 4.0));
+        assert_eq!(report.total_samples(), 4);
+
+        let report = EvaluationReport::new(vec![None], vec![0], Duration::ZERO);
+        assert_eq!(report.loss(), None);
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    fs,
+    io::{self, ErrorKind, Write},
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+    sync::{
+        Arc,
+        mpsc::{self, SyncSender, TrySendError},
+    },
+    thread,
+};
+
+use comms::specs::{
+    server::StalenessStats,
+    worker::{MetricsReport, TimeBreakdown},
+};
+use log::{debug, info, warn};
+use serde::Serialize;
+
+use crate::{StopReason, TrainingEvent};
+
+/// The amount of lines a slow consumer can fall behind before it starts missing events.
+const BACKLOG: usize = 1024;
+
+/// The JSON shape of a `TrainingEvent`, tagged by it's `event` field.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum FeedEvent<'a> {
+    PublishedLosses {
+        worker_id: usize,
+        losses: &'a [f64],
+        timing: TimeBreakdown,
+    },
+    Metrics {
+        worker_id: usize,
+        #[serde(flatten)]
+        report: &'a MetricsReport,
+    },
+    RoundTiming {
+        timing: TimeBreakdown,
+    },
+    WorkerDone {
+        worker_id: usize,
+    },
+    Staleness {
+        server: usize,
+        stats: StalenessStats,
+    },
+    LrRestart {
+        epoch: usize,
+        cycle: usize,
+    },
+    RegressionMetrics {
+        values: usize,
+        mse: f64,
+        msle: f64,
+        r_squared: f64,
+    },
+    TrainingComplete {
+        stop_reason: StopReason,
+        final_loss: Option<f64>,
+        test_loss: Option<f64>,
+        epochs_run: usize,
+        total_samples: u64,
+    },
+    Disconnect {
+        worker_id: usize,
+    },
+    Upgrading {
+        worker_id: usize,
+    },
+    Upgraded {
+        worker_id: usize,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl<'a> FeedEvent<'a> {
+    /// Mirrors a `TrainingEvent` into it's JSON shape.
+    ///
+    /// # Args
+    /// * `event` - The event to mirror.
+    ///
+    /// # Returns
+    /// The event's JSON shape, `None` for the trained parameters.
+    fn from_event(event: &'a TrainingEvent) -> Option<Self> {
+        let feed_event = match event {
+            TrainingEvent::PublishedLosses {
+                worker_id,
+                losses,
+                timing,
+            } => Self::PublishedLosses {
+                worker_id: *worker_id,
+                losses,
+                timing: *timing,
+            },
+            TrainingEvent::Metrics { worker_id, report } => Self::Metrics {
+                worker_id: *worker_id,
+                report,
+            },
+            TrainingEvent::RoundTiming { timing } => Self::RoundTiming { timing: *timing },
+            TrainingEvent::WorkerDone(worker_id) => Self::WorkerDone {
+                worker_id: *worker_id,
+            },
+            TrainingEvent::Staleness { server, stats } => Self::Staleness {
+                server: *server,
+                stats: *stats,
+            },
+            TrainingEvent::LrRestart { epoch, cycle } => Self::LrRestart {
+                epoch: *epoch,
+                cycle: *cycle,
+            },
+            TrainingEvent::RegressionMetrics { metrics } => Self::RegressionMetrics {
+                values: metrics.len(),
+                mse: metrics.mse(),
+                msle: metrics.msle(),
+                r_squared: metrics.r_squared(),
+            },
+            TrainingEvent::TrainingComplete { report } => Self::TrainingComplete {
+                stop_reason: report.stop_reason(),
+                final_loss: report.final_loss(),
+                test_loss: report.test_loss(),
+                epochs_run: report.epochs_run(),
+                total_samples: report.total_samples(),
+            },
+            TrainingEvent::Disconnect { worker_id } => Self::Disconnect {
+                worker_id: *worker_id,
+            },
+            TrainingEvent::Upgrading { worker_id } => Self::Upgrading {
+                worker_id: *worker_id,
+            },
+            TrainingEvent::Upgraded { worker_id, .. } => Self::Upgraded {
+                worker_id: *worker_id,
+            },
+            TrainingEvent::Error(e) => Self::Error {
+                message: e.to_string(),
+            },
+            TrainingEvent::Params(..) => return None,
+        };
+
+        Some(feed_event)
+    }
+}
+
+/// Publishes the training events as newline delimited JSON to the processes connected
+/// to a Unix domain socket.
+///
+/// Every consumer gets it's own writer thread and a bounded backlog of lines, a consumer
+/// that falls behind misses the events that don't fit and one that disconnects is
+/// forgotten, neither of them ever blocks the training.
+pub struct EventFeed {
+    path: PathBuf,
+    listener: UnixListener,
+    consumers: Vec<SyncSender<Arc<str>>>,
+}
+
+impl EventFeed {
+    /// Binds a new `EventFeed` to the given path, replacing any stale socket left there.
+    ///
+    /// Anything at the path other than a socket is left untouched.
+    ///
+    /// # Args
+    /// * `path` - Where to bind the socket.
+    ///
+    /// # Returns
+    /// A new `EventFeed` instance.
+    ///
+    /// # Errors
+    /// Returns an `io::Error` if something other than a socket is at the path or the
+    /// socket can't be bound.
+    pub fn bind(path: PathBuf) -> io::Result<Self> {
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(&path)?,
+            Ok(_) => {
+                let text = format!(
+                    "{} already exists and isn't a socket, refusing to replace it",
+                    path.display()
+                );
+                return Err(io::Error::new(ErrorKind::AlreadyExists, text));
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+        info!("publishing the training events to {}", path.display());
+
+        let feed = Self {
+            path,
+            listener,
+            consumers: Vec::new(),
+        };
+
+        Ok(feed)
+    }
+
+    /// Publishes an event to every connected consumer.
+    ///
+    /// # Args
+    /// * `event` - The event to publish.
+    pub fn publish(&mut self, event: &TrainingEvent) {
+        self.accept_pending();
+
+        if self.consumers.is_empty() {
+            return;
+        }
+
+        let Some(feed_event) = FeedEvent::from_event(event) else {
+            return;
+        };
+
+        let mut line = match serde_json::to_string(&feed_event) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("failed to serialize a training event: {e}");
+                return;
+            }
+        };
+
+        line.push('\n');
+        let line: Arc<str> = line.into();
+
+        self.consumers
+            .retain(|tx| match tx.try_send(Arc::clone(&line)) {
+                Ok(()) | Err(TrySendError::Full(..)) => true,
+                Err(TrySendError::Disconnected(..)) => false,
+            });
+    }
+
+    /// Accepts every consumer waiting to connect.
+    fn accept_pending(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if let Err(e) = self.spawn_writer(stream) {
+                        warn!("failed to set up an event feed consumer: {e}");
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    warn!("failed to accept an event feed consumer: {e}");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Spawns the thread writing the published lines to a consumer.
+    ///
+    /// # Args
+    /// * `stream` - The consumer's stream.
+    ///
+    /// # Errors
+    /// Returns an `io::Error` if the stream can't be made blocking.
+    fn spawn_writer(&mut self, mut stream: UnixStream) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        let (tx, rx) = mpsc::sync_channel::<Arc<str>>(BACKLOG);
+
+        thread::spawn(move || {
+            for line in rx {
+                if let Err(e) = stream.write_all(line.as_bytes()) {
+                    debug!("event feed consumer disconnected: {e}");
+                    break;
+                }
+            }
+        });
+
+        self.consumers.push(tx);
+        Ok(())
+    }
+}
+
+impl Drop for EventFeed {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        io::{BufRead, BufReader},
+        process,
+    };
+
+    use super::*;
+
+    fn losses(worker_id: usize, loss: f64) -> TrainingEvent {
+        TrainingEvent::PublishedLosses {
+            worker_id,
+            losses: vec![loss],
+            timing: TimeBreakdown::default(),
+        }
+    }
+
+    #[test]
+    fn test_a_disconnected_consumer_is_dropped_without_failing() {
+        let path = env::temp_dir().join(format!("event-feed-{}.sock", process::id()));
+        let mut feed = EventFeed::bind(path.clone()).unwrap();
+
+        let reader = UnixStream::connect(&path).unwrap();
+        feed.publish(&losses(0, 1.0));
+        assert_eq!(feed.consumers.len(), 1);
+        drop(reader);
+
+        // The writer only notices the consumer is gone once a write fails.
+        while !feed.consumers.is_empty() {
+            feed.publish(&losses(0, 0.5));
+            thread::yield_now();
+        }
+
+        let mut reader = BufReader::new(UnixStream::connect(&path).unwrap());
+        feed.publish(&TrainingEvent::WorkerDone(0));
+        drop(feed);
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"event\":\"worker_done\",\"worker_id\":0}\n");
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_binding_over_a_regular_file_leaves_it_in_place() {
+        let path = env::temp_dir().join(format!("event-feed-{}.txt", process::id()));
+        fs::write(&path, "not a socket").unwrap();
+
+        let res = EventFeed::bind(path.clone());
+        let content = fs::read_to_string(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(res.err().map(|e| e.kind()), Some(ErrorKind::AlreadyExists));
+        assert_eq!(content.unwrap(), "not a socket");
+    }
+}
@@ -1,18 +1,23 @@
-use std::{mem, num::NonZeroUsize};
+use std::{collections::HashMap, mem, num::NonZeroUsize, ops::ControlFlow};
 
-use comms::{NetRtp, ParamServerHandle};
-use log::info;
+use comms::{NetRtp, ParamServerHandle, floats::FloatPositive, specs::worker::TimeBreakdown};
+use log::{debug, error, info, warn};
 use tokio::sync::mpsc::{Receiver, Sender};
 
 use crate::{
-    StopReason, TrainingEvent,
+    OrchErr, StopReason, TrainingEvent,
     configs::{StrategySwitchTracking, WorkerPostAction},
-    sessions::{ConvergenceTracker, LossRecorder, WorkerRequest},
+    sessions::{
+        ConvergenceTracker, Divergence, DivergenceTracker, EpochHook, EpochSummary, LearningCurve,
+        LossRecorder, MonitorCadence, ProgressRecorder, RestartTracker, SnapshotKeeper,
+        TimeRecorder, ValidationRecorder, WorkerRequest,
+    },
 };
 
 /// The main loop over the training events in the system.
 pub struct EventListener<'a> {
     cancel_rx: Receiver<()>,
+    lr_rx: Receiver<FloatPositive>,
     server_handles: &'a mut Vec<ParamServerHandle<NetRtp>>,
     req_txs: &'a mut [Sender<WorkerRequest>],
     event_rx: &'a mut Receiver<TrainingEvent>,
@@ -21,9 +26,19 @@ pub struct EventListener<'a> {
     // Training state
     workers_left: usize,
     loss_recorder: LossRecorder,
+    progress_recorder: ProgressRecorder,
+    validation_recorder: ValidationRecorder,
+    time_recorder: TimeRecorder,
+    learning_curve: Option<LearningCurve>,
     switch_tracking: Option<StrategySwitchTracking>,
     convergence_tracker: Option<ConvergenceTracker>,
+    divergence_tracker: Option<DivergenceTracker>,
+    monitor_cadence: MonitorCadence,
+    restart_tracker: Option<RestartTracker>,
+    epoch_hook: Option<EpochHook>,
+    snapshot_keeper: Option<SnapshotKeeper>,
     stop_reason: Option<StopReason>,
+    pending_lr: Option<FloatPositive>,
 }
 
 impl<'a> EventListener<'a> {
@@ -31,39 +46,64 @@ impl<'a> EventListener<'a> {
     ///
     /// # Args
     /// * `cancel_rx` - The training cancellation request receiver.
+    /// * `lr_rx` - The learning rate change request receiver.
     /// * `req_txs` - The request senders for the worker listeners.
     /// * `server_handles` - The server handles session vec.
     /// * `loss_recorder` - The workers' loss recorder.
     /// * `convergence_tracker` - A tracker device to track model convergence.
+    /// * `divergence_tracker` - A tracker of the loss' trend to act on a diverging model.
+    /// * `monitor_cadence` - The rounds after which convergence is checked.
+    /// * `restart_tracker` - A tracker of the learning rate schedule's restarts.
     /// * `event_rx` - An event producer.
     /// * `event_tx` - An event consumer.
     /// * `switch_tracking` - The strategy switch tracking metadata.
+    /// * `learning_curve` - The optional recorder of the per epoch metrics.
+    /// * `epoch_hook` - The optional user callback to invoke after every epoch.
+    /// * `snapshot_keeper` - The optional keeper of the servers' parameters during the training.
     ///
     /// # Returns
     /// A new `EventListener` instance.
     pub fn new(
         cancel_rx: Receiver<()>,
+        lr_rx: Receiver<FloatPositive>,
         req_txs: &'a mut [Sender<WorkerRequest>],
         server_handles: &'a mut Vec<ParamServerHandle<NetRtp>>,
         loss_recorder: LossRecorder,
         convergence_tracker: Option<ConvergenceTracker>,
+        divergence_tracker: Option<DivergenceTracker>,
+        monitor_cadence: MonitorCadence,
+        restart_tracker: Option<RestartTracker>,
         event_rx: &'a mut Receiver<TrainingEvent>,
         event_tx: Sender<TrainingEvent>,
         switch_tracking: Option<StrategySwitchTracking>,
+        learning_curve: Option<LearningCurve>,
+        epoch_hook: Option<EpochHook>,
+        snapshot_keeper: Option<SnapshotKeeper>,
     ) -> Self {
         let nworkers = req_txs.len();
 
         Self {
             cancel_rx,
+            lr_rx,
             server_handles,
             req_txs,
             loss_recorder,
+            progress_recorder: ProgressRecorder::new(nworkers),
+            validation_recorder: ValidationRecorder::default(),
+            time_recorder: TimeRecorder::default(),
+            learning_curve,
             convergence_tracker,
+            divergence_tracker,
+            monitor_cadence,
+            restart_tracker,
+            epoch_hook,
+            snapshot_keeper,
             event_rx,
             event_tx,
             switch_tracking,
             workers_left: nworkers,
             stop_reason: None,
+            pending_lr: None,
         }
     }
 
@@ -83,6 +123,14 @@ impl<'a> EventListener<'a> {
                     self.stop_reason = Some(StopReason::ManualStop);
                     self.broadcast_request(WorkerRequest::Stop).await;
                 }
+                Some(lr) = self.lr_rx.recv() => {
+                    info!("learning rate change to {} requested by the user", *lr);
+                    self.pending_lr = Some(lr);
+
+                    if let Some(ref mut tracker) = self.divergence_tracker {
+                        tracker.set_lr(lr);
+                    }
+                }
                 event = self.event_rx.recv() => {
                     let Some(event) = event else {
                         break;
@@ -96,6 +144,39 @@ impl<'a> EventListener<'a> {
         Some(self.stop_reason.unwrap_or_default())
     }
 
+    /// Takes the progress the workers made so far.
+    ///
+    /// # Returns
+    /// The recorded progress of the workers.
+    pub fn take_progress(&mut self) -> ProgressRecorder {
+        mem::take(&mut self.progress_recorder)
+    }
+
+    /// Takes the metrics of the final model the workers reported.
+    ///
+    /// # Returns
+    /// The merged validation metrics of the workers.
+    pub fn take_validation(&mut self) -> ValidationRecorder {
+        mem::take(&mut self.validation_recorder)
+    }
+
+    /// Takes the keeper of the servers' parameters, for the finalization to fail with it's
+    /// last snapshot.
+    ///
+    /// # Returns
+    /// The snapshot keeper, if one was set.
+    pub fn take_snapshot_keeper(&mut self) -> Option<SnapshotKeeper> {
+        self.snapshot_keeper.take()
+    }
+
+    /// Takes the learning curve recorded so far.
+    ///
+    /// # Returns
+    /// The learning curve or `None` if it isn't recorded.
+    pub fn take_learning_curve(&mut self) -> Option<LearningCurve> {
+        self.learning_curve.take()
+    }
+
     /// Handles an incoming event from a worker.
     ///
     /// # Args
@@ -110,9 +191,38 @@ impl<'a> EventListener<'a> {
                 self.workers_left = self.workers_left.saturating_sub(1);
                 Some(self.workers_left > 0)
             }
-            TrainingEvent::PublishedLosses { worker_id, losses } => {
+            TrainingEvent::PublishedLosses {
+                worker_id,
+                losses,
+                timing,
+            } => {
+                self.progress_recorder.record(worker_id, &losses);
+
+                if let Some(ref mut learning_curve) = self.learning_curve {
+                    learning_curve.record(worker_id, &losses, timing);
+                }
+
                 self.handle_losses(worker_id, &losses).await;
-                let event = TrainingEvent::PublishedLosses { worker_id, losses };
+                let event = TrainingEvent::PublishedLosses {
+                    worker_id,
+                    losses,
+                    timing,
+                };
+                let _ = self.event_tx.send(event).await;
+                self.handle_timing(worker_id, timing).await;
+
+                self.advance_lr_schedule().await;
+                Some(true)
+            }
+            TrainingEvent::Metrics { worker_id, report } => {
+                self.progress_recorder.record_samples(report.samples);
+                self.progress_recorder.record_bytes(
+                    report.epoch,
+                    report.bytes_sent,
+                    report.bytes_received,
+                );
+                self.validation_recorder.record(worker_id, &report);
+                let event = TrainingEvent::Metrics { worker_id, report };
                 let _ = self.event_tx.send(event).await;
                 Some(true)
             }
@@ -125,10 +235,142 @@ impl<'a> EventListener<'a> {
                 self.workers_left = self.workers_left.saturating_sub(1);
                 Some(self.workers_left > 0)
             }
+            TrainingEvent::Error(cause) => {
+                let err = self.with_partial(cause);
+                let _ = self.event_tx.send(TrainingEvent::Error(err)).await;
+                None
+            }
             other => {
-                let is_err = matches!(other, TrainingEvent::Error(..));
                 let _ = self.event_tx.send(other).await;
-                (!is_err).then_some(true)
+                Some(true)
+            }
+        }
+    }
+
+    /// Handles the time split of a worker's latest round, reporting the mean of the
+    /// workers once all of them finished it.
+    ///
+    /// # Args
+    /// * `worker_id` - The id of the worker that finished the round.
+    /// * `timing` - How it spent the round.
+    async fn handle_timing(&mut self, worker_id: usize, timing: TimeBreakdown) {
+        self.time_recorder.record(worker_id, timing);
+
+        let Some(workers) = NonZeroUsize::new(self.workers_left) else {
+            return;
+        };
+
+        let Some(timing) = self.time_recorder.mean(workers) else {
+            return;
+        };
+
+        self.time_recorder.clear();
+        let _ = self
+            .event_tx
+            .send(TrainingEvent::RoundTiming { timing })
+            .await;
+    }
+
+    /// Attaches the parameters of the last snapshot to the error the training failed with.
+    ///
+    /// # Args
+    /// * `cause` - The error the training failed with.
+    ///
+    /// # Returns
+    /// An `OrchErr::FailedWithPartial` or the cause itself if no snapshot was kept.
+    fn with_partial(&mut self, cause: OrchErr) -> OrchErr {
+        match self.snapshot_keeper {
+            Some(ref mut keeper) => keeper.attach(cause),
+            None => cause,
+        }
+    }
+
+    /// Pulls the parameters of every server once a snapshot is due.
+    async fn take_snapshot(&mut self) {
+        let Some(ref mut keeper) = self.snapshot_keeper else {
+            return;
+        };
+
+        if self.server_handles.is_empty() || !keeper.due(self.progress_recorder.epochs_run()) {
+            return;
+        }
+
+        let mut server_params = HashMap::with_capacity(self.server_handles.len());
+
+        for server_handle in self.server_handles.iter_mut() {
+            let id = server_handle.id();
+
+            let params = match server_handle.req_snapshot().await {
+                Ok(()) => server_handle
+                    .pull_params()
+                    .await
+                    .map(|params| params.to_vec()),
+                Err(e) => Err(e),
+            };
+
+            match params {
+                Ok(params) => server_params.insert(id, params),
+                Err(e) => {
+                    warn!("failed to snapshot the parameters of server {id}: {e}");
+                    return;
+                }
+            };
+        }
+
+        debug!(
+            "snapshotted the parameters of {} servers",
+            server_params.len()
+        );
+        keeper.keep(&server_params);
+    }
+
+    /// Sends the servers the epoch the workers reached, for them to follow their learning
+    /// rate schedule, and reports the schedule's restarts.
+    async fn advance_lr_schedule(&mut self) {
+        let epoch = self.progress_recorder.epochs_run();
+
+        let Some(restarts) = self
+            .restart_tracker
+            .as_mut()
+            .and_then(|tracker| tracker.advance(epoch))
+        else {
+            return;
+        };
+
+        for server_handle in self.server_handles.iter_mut() {
+            if let Err(e) = server_handle.set_epoch(epoch).await {
+                let id = server_handle.id();
+                warn!("failed to send epoch {epoch} to server {id}: {e}");
+            }
+        }
+
+        for (epoch, cycle) in restarts {
+            info!("learning rate restarted at epoch {epoch}, starting cycle {cycle}");
+            let event = TrainingEvent::LrRestart { epoch, cycle };
+            let _ = self.event_tx.send(event).await;
+        }
+    }
+
+    /// Sends the learning rate the user asked for to the servers, in between two rounds.
+    async fn set_pending_lr(&mut self) {
+        let Some(lr) = self.pending_lr.take() else {
+            return;
+        };
+
+        if self.server_handles.is_empty() {
+            warn!(
+                "the learning rate can't be set to {}, there are no servers",
+                *lr
+            );
+            return;
+        }
+
+        info!("setting the servers' learning rate to {}", *lr);
+
+        for server_handle in self.server_handles.iter_mut() {
+            if let Err(e) = server_handle.set_learning_rate(lr).await {
+                let id = server_handle.id();
+                warn!("failed to set the learning rate of server {id}: {e}");
             }
         }
     }
@@ -157,14 +399,64 @@ impl<'a> EventListener<'a> {
 
         self.loss_recorder.clear();
 
+        if let Some(ref mut tracker) = self.divergence_tracker {
+            match tracker.record(loss) {
+                Some(Divergence::HalveLr(lr)) => {
+                    warn!("the loss diverges, halving the learning rate to {}", *lr);
+                    self.pending_lr = Some(lr);
+                }
+                Some(Divergence::Abort) => {
+                    warn!("the loss diverges, aborting the training");
+                    self.stop_reason = Some(StopReason::Diverged);
+                    self.broadcast_request(WorkerRequest::Stop).await;
+                    return;
+                }
+                None => {}
+            }
+        }
+
+        self.set_pending_lr().await;
+        self.take_snapshot().await;
+
+        if let Some(ref hook) = self.epoch_hook {
+            let summary = EpochSummary {
+                epoch: self.progress_recorder.epochs_run(),
+                loss,
+            };
+
+            match hook.call(summary).await {
+                Ok(ControlFlow::Continue(())) => {}
+                Ok(ControlFlow::Break(())) => {
+                    info!("the epoch callback stopped the training");
+                    self.stop_reason = Some(StopReason::Callback);
+                    self.broadcast_request(WorkerRequest::Stop).await;
+                    return;
+                }
+                Err(e) => {
+                    error!("{e}");
+                    self.epoch_hook = None;
+                    self.stop_reason = Some(StopReason::Callback);
+                    self.broadcast_request(WorkerRequest::Stop).await;
+                    let _ = self.event_tx.send(TrainingEvent::Error(e)).await;
+                    return;
+                }
+            }
+        }
+
+        let due = self.monitor_cadence.record(losses.len());
+
         if let Some(ref mut tracker) = self.convergence_tracker {
-            tracker.record(loss);
+            tracker.elapse(losses.len());
 
-            if tracker.converged() {
-                info!("early stopping triggered");
-                self.stop_reason = Some(StopReason::EarlyStopping);
-                self.broadcast_request(WorkerRequest::Stop).await;
-                return;
+            if due {
+                tracker.record(loss);
+
+                if tracker.converged() {
+                    info!("early stopping triggered");
+                    self.stop_reason = Some(StopReason::EarlyStopping);
+                    self.broadcast_request(WorkerRequest::Stop).await;
+                    return;
+                }
             }
         }
 
@@ -0,0 +1,159 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use comms::specs::worker::TimeBreakdown;
+use log::{info, warn};
+
+/// The header of the learning curve's CSV.
+const HEADER: &str = "epoch,train_loss,val_loss,grad_norm,lr,throughput";
+
+/// The metrics of a single epoch, accumulated over the workers that ran it.
+#[derive(Debug, Default)]
+struct Epoch {
+    loss_sum: f64,
+    nlosses: usize,
+    throughput: f64,
+}
+
+/// Accumulates the metrics of every epoch of the training, written out as a CSV once it ends.
+///
+/// Each epoch's training loss is the mean of the workers' and it's throughput the sum of
+/// the samples per second they processed. The workers don't publish validation losses,
+/// gradient norms or learning rates, so those cells are left empty.
+#[derive(Debug)]
+pub struct LearningCurve {
+    path: PathBuf,
+    worker_samples: Vec<u64>,
+    worker_epochs: Vec<usize>,
+    epochs: Vec<Epoch>,
+}
+
+impl LearningCurve {
+    /// Creates a new `LearningCurve`.
+    ///
+    /// # Args
+    /// * `path` - Where to write the CSV.
+    /// * `worker_samples` - The amount of samples in each worker's partition.
+    ///
+    /// # Returns
+    /// A new `LearningCurve` instance.
+    pub fn new(path: PathBuf, worker_samples: Vec<u64>) -> Self {
+        Self {
+            path,
+            worker_epochs: vec![0; worker_samples.len()],
+            worker_samples,
+            epochs: Vec::new(),
+        }
+    }
+
+    /// Records the losses a worker published, one per epoch it ran.
+    ///
+    /// # Args
+    /// * `worker_id` - The id of the worker that published the losses.
+    /// * `losses` - The losses it published.
+    /// * `timing` - How long the worker took to run those epochs.
+    pub fn record(&mut self, worker_id: usize, losses: &[f64], timing: TimeBreakdown) {
+        let Some(worker_epochs) = self.worker_epochs.get_mut(worker_id) else {
+            return;
+        };
+
+        let first = *worker_epochs;
+        *worker_epochs += losses.len();
+
+        let secs = timing.total().as_secs_f64() / losses.len().max(1) as f64;
+        let throughput = if secs > 0.0 {
+            self.worker_samples[worker_id] as f64 / secs
+        } else {
+            0.0
+        };
+
+        if self.epochs.len() < first + losses.len() {
+            self.epochs
+                .resize_with(first + losses.len(), Epoch::default);
+        }
+
+        for (epoch, &loss) in self.epochs[first..].iter_mut().zip(losses) {
+            if loss.is_finite() {
+                epoch.loss_sum += loss;
+                epoch.nlosses += 1;
+            }
+
+            epoch.throughput += throughput;
+        }
+    }
+
+    /// Writes the learning curve to it's path, only logging if it fails since the
+    /// training itself is over by then.
+    pub fn flush(&self) {
+        let res = File::create(&self.path).and_then(|file| self.write(BufWriter::new(file)));
+
+        match res {
+            Ok(()) => info!("wrote the learning curve to {}", self.path.display()),
+            Err(e) => warn!(
+                "failed to write the learning curve to {}: {e}",
+                self.path.display()
+            ),
+        }
+    }
+
+    /// Writes the learning curve as a CSV, one row per epoch.
+    ///
+    /// # Args
+    /// * `writer` - Where to write the CSV.
+    ///
+    /// # Errors
+    /// Returns an `io::Error` if the writer fails.
+    fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "{HEADER}")?;
+
+        for (i, epoch) in self.epochs.iter().enumerate() {
+            let train_loss = match epoch.nlosses {
+                0 => String::new(),
+                n => (epoch.loss_sum / n as f64).to_string(),
+            };
+
+            let throughput = if epoch.throughput > 0.0 {
+                epoch.throughput.to_string()
+            } else {
+                String::new()
+            };
+
+            writeln!(writer, "{},{train_loss},,,,{throughput}", i + 1)?;
+        }
+
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn timing(secs: u64) -> TimeBreakdown {
+        TimeBreakdown {
+            compute: Duration::from_secs(secs),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_missing_metrics_are_left_empty() {
+        let mut curve = LearningCurve::new(PathBuf::new(), vec![10, 20]);
+        curve.record(0, &[3.0, 1.0], timing(2));
+        curve.record(1, &[5.0], timing(2));
+        curve.record(1, &[f64::NAN], TimeBreakdown::default());
+
+        let mut csv = Vec::new();
+        curve.write(&mut csv).unwrap();
+
+        // The second epoch's throughput only counts the first worker, since the second one
+        // reported no timing, and it's loss leaves the NaN out.
+        let expected = format!("{HEADER}\n1,4,,,,20\n2,1,,,,10\n");
+        assert_eq!(String::from_utf8(csv).unwrap(), expected);
+    }
+}
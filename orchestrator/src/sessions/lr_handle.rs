@@ -0,0 +1,30 @@
+use comms::floats::FloatPositive;
+use tokio::sync::mpsc::Sender;
+
+use crate::{OrchErr, Result};
+
+/// A handle that lets any caller change the learning rate of an ongoing training session.
+#[derive(Clone)]
+pub struct LrHandle(pub(super) Sender<FloatPositive>);
+
+impl LrHandle {
+    /// Asks the session's servers to continue the training with a new learning rate.
+    ///
+    /// The servers take it once the workers finish their current round, their optimizers
+    /// keep the rest of their state and a learning rate schedule continues from it.
+    ///
+    /// # Args
+    /// * `lr` - The new learning rate.
+    ///
+    /// # Errors
+    /// An `OrchErr::InvalidConfig` if the learning rate isn't positive.
+    pub fn set_lr(&self, lr: f32) -> Result<()> {
+        let Some(lr) = FloatPositive::new(lr) else {
+            let text = format!("the learning rate ({lr}) must be positive");
+            return Err(OrchErr::InvalidConfig(text));
+        };
+
+        let _ = self.0.try_send(lr);
+        Ok(())
+    }
+}
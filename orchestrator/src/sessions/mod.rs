@@ -1,28 +1,75 @@
 mod cancel_handle;
 mod convergence_tracker;
+mod divergence_tracker;
+mod epoch_hook;
+mod evaluation_report;
+#[cfg(unix)]
+mod event_feed;
 mod event_listener;
 mod greater_than_one_usize;
+mod learning_curve;
 mod loss_recorder;
+mod lr_handle;
+mod monitor_cadence;
+#[cfg(not(unix))]
+mod no_event_feed;
+mod params_preview;
+mod progress_bar;
+mod progress_recorder;
+mod restart_tracker;
+mod seed_report;
 mod session;
+mod snapshot_keeper;
 mod switch_tracker;
+mod test_set;
+mod time_recorder;
 mod trained_model;
+mod training_report;
+mod validation_recorder;
 mod worker_listener;
 
 use comms::{
     NetRtp, ParamServerHandle,
-    specs::{machine_learning::TrainerSpec, server::ServerSpec},
+    specs::{
+        machine_learning::TrainerSpec,
+        server::{ServerSpec, StalenessStats},
+        worker::{MetricsReport, TimeBreakdown},
+    },
 };
 
 pub use cancel_handle::CancelHandle;
 pub use convergence_tracker::ConvergenceTracker;
+pub use divergence_tracker::{Divergence, DivergenceTracker};
+pub use epoch_hook::{EpochHook, EpochSummary};
+pub use evaluation_report::EvaluationReport;
+#[cfg(unix)]
+pub use event_feed::EventFeed;
 pub use event_listener::EventListener;
 pub use greater_than_one_usize::GreaterThanOneUsize;
+pub use learning_curve::LearningCurve;
 pub use loss_recorder::LossRecorder;
+pub use lr_handle::LrHandle;
+pub use monitor_cadence::MonitorCadence;
+#[cfg(not(unix))]
+pub use no_event_feed::EventFeed;
+pub use params_preview::ParamsPreview;
+pub use progress_bar::ProgressBar;
+pub use progress_recorder::ProgressRecorder;
+pub use restart_tracker::RestartTracker;
+pub use seed_report::SeedReport;
 pub use session::Session;
+pub use snapshot_keeper::SnapshotKeeper;
 pub use switch_tracker::SwitchTracker;
+pub use test_set::{Rows, TestSet, held_out_count, hold_out, hold_out_csr, hold_out_files};
+pub use time_recorder::TimeRecorder;
 pub use trained_model::TrainedModel;
+pub use training_report::TrainingReport;
+pub use validation_recorder::ValidationRecorder;
 pub use worker_listener::WorkerListener;
 
+use serde::Serialize;
+use worker::metrics::RegressionMetrics;
+
 use crate::OrchErr;
 
 /// Requests that the orchestrator can make to a worker handler task.
@@ -49,11 +96,38 @@ pub enum TrainingEvent {
     PublishedLosses {
         worker_id: usize,
         losses: Vec<f64>,
+        timing: TimeBreakdown,
+    },
+    /// The metrics a worker reported for it's latest training round.
+    Metrics {
+        worker_id: usize,
+        report: MetricsReport,
+    },
+    /// How the workers spent their last training round on average.
+    RoundTiming {
+        timing: TimeBreakdown,
     },
     WorkerDone(usize),
+    /// How stale were the gradients applied by a server in non blocking mode.
+    Staleness {
+        server: usize,
+        stats: StalenessStats,
+    },
+    LrRestart {
+        epoch: usize,
+        cycle: usize,
+    },
+    /// The regression metrics of the trained model over the training rows, merged across
+    /// the workers once the training stopped. Sent for models trained with the mean squared
+    /// error, right before the training completes.
+    ///
+    /// The R² is `NaN` when the targets have no variance, as it's undefined, and so is the
+    /// MSLE when a prediction or target isn't greater than -1.
+    RegressionMetrics {
+        metrics: RegressionMetrics,
+    },
     TrainingComplete {
-        model: TrainedModel,
-        stop_reason: StopReason,
+        report: TrainingReport,
     },
     Params(Vec<f32>),
     Disconnect {
@@ -70,10 +144,13 @@ pub enum TrainingEvent {
 }
 
 /// Why a training session ended.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum StopReason {
     #[default]
     MaxEpochsReached,
     EarlyStopping,
     ManualStop,
+    Diverged,
+    Callback,
 }
@@ -0,0 +1,103 @@
+use std::num::NonZeroUsize;
+
+/// Decides after which training rounds the stop condition is evaluated.
+///
+/// The workers only publish their losses at the end of a round, once their gradients
+/// were synchronized, so a check asked for in the middle of a round is held until it's
+/// end, the first point where every worker can be stopped safely.
+#[derive(Debug, Clone)]
+pub struct MonitorCadence {
+    every: NonZeroUsize,
+    steps_per_epoch: usize,
+    progress: usize,
+    checks: usize,
+}
+
+impl MonitorCadence {
+    /// Creates a new `MonitorCadence` checking every some amount of epochs.
+    ///
+    /// # Args
+    /// * `every` - The amount of epochs between checks.
+    ///
+    /// # Returns
+    /// A new `MonitorCadence` instance.
+    pub fn epochs(every: NonZeroUsize) -> Self {
+        Self::steps(every, 1)
+    }
+
+    /// Creates a new `MonitorCadence` checking every some amount of optimization steps.
+    ///
+    /// # Args
+    /// * `every` - The amount of steps between checks.
+    /// * `steps_per_epoch` - The amount of steps the workers take every epoch.
+    ///
+    /// # Returns
+    /// A new `MonitorCadence` instance.
+    pub fn steps(every: NonZeroUsize, steps_per_epoch: usize) -> Self {
+        Self {
+            every,
+            steps_per_epoch: steps_per_epoch.max(1),
+            progress: 0,
+            checks: 0,
+        }
+    }
+
+    /// Records the end of a training round.
+    ///
+    /// # Args
+    /// * `epochs` - The amount of epochs the round took.
+    ///
+    /// # Returns
+    /// Whether the stop condition should be evaluated at the end of this round.
+    pub fn record(&mut self, epochs: usize) -> bool {
+        self.progress += epochs * self.steps_per_epoch;
+
+        let checks = self.progress / self.every;
+        let due = checks > self.checks;
+        self.checks = checks;
+        due
+    }
+}
+
+impl Default for MonitorCadence {
+    /// Checks after every epoch.
+    fn default() -> Self {
+        Self::epochs(NonZeroUsize::MIN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checked_rounds(mut cadence: MonitorCadence, epochs: usize, rounds: usize) -> Vec<usize> {
+        (1..=rounds).filter(|_| cadence.record(epochs)).collect()
+    }
+
+    #[test]
+    fn test_the_default_cadence_checks_every_epoch() {
+        let rounds = checked_rounds(MonitorCadence::default(), 1, 4);
+        assert_eq!(rounds, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_every_n_epochs_skips_the_rounds_in_between() {
+        let every = NonZeroUsize::new(3).unwrap();
+        let rounds = checked_rounds(MonitorCadence::epochs(every), 1, 9);
+        assert_eq!(rounds, [3, 6, 9]);
+
+        // Rounds of two epochs check once they went past every multiple of three.
+        let rounds = checked_rounds(MonitorCadence::epochs(every), 2, 6);
+        assert_eq!(rounds, [2, 3, 5, 6]);
+    }
+
+    #[test]
+    fn test_sub_epoch_steps_are_held_until_the_end_of_the_round() {
+        let every = NonZeroUsize::new(4).unwrap();
+        let rounds = checked_rounds(MonitorCadence::steps(every, 10), 1, 3);
+        assert_eq!(rounds, [1, 2, 3]);
+
+        let rounds = checked_rounds(MonitorCadence::steps(every, 3), 1, 4);
+        assert_eq!(rounds, [2, 3, 4]);
+    }
+}
@@ -0,0 +1,29 @@
+use std::{io, path::PathBuf};
+
+use crate::TrainingEvent;
+
+/// Stands in for the event feed where there are no Unix sockets to publish it on.
+pub struct EventFeed;
+
+impl EventFeed {
+    /// Fails, the event feed is published on a Unix socket.
+    ///
+    /// # Args
+    /// * `path` - Where the socket would be bound.
+    ///
+    /// # Errors
+    /// Always returns an `io::Error` of kind `Unsupported`.
+    pub fn bind(path: PathBuf) -> io::Result<Self> {
+        let text = format!(
+            "the event feed at {} needs Unix sockets, which this platform lacks",
+            path.display()
+        );
+        Err(io::Error::new(io::ErrorKind::Unsupported, text))
+    }
+
+    /// Does nothing, no feed is ever bound.
+    ///
+    /// # Args
+    /// * `event` - The event to publish.
+    pub fn publish(&mut self, _event: &TrainingEvent) {}
+}
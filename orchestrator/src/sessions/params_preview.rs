@@ -0,0 +1,71 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Displays the first few of a model's parameters and how many there are in total.
+///
+/// Printing every parameter of a large model floods the terminal, the whole of them
+/// are better saved as a bundle through `TrainingReport::save_bundle`.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamsPreview<'a> {
+    params: &'a [f32],
+    len: usize,
+}
+
+impl<'a> ParamsPreview<'a> {
+    /// Creates a new `ParamsPreview`.
+    ///
+    /// # Args
+    /// * `params` - The model's parameters.
+    /// * `len` - The amount of parameters to display.
+    ///
+    /// # Returns
+    /// A new `ParamsPreview` instance.
+    pub fn new(params: &'a [f32], len: usize) -> Self {
+        Self { params, len }
+    }
+}
+
+impl Display for ParamsPreview<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let shown = &self.params[..self.len.min(self.params.len())];
+
+        write!(f, "[")?;
+
+        for (i, param) in shown.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{param}")?;
+        }
+
+        if shown.len() < self.params.len() {
+            let sep = if shown.is_empty() { "" } else { ", " };
+            write!(f, "{sep}...")?;
+        }
+
+        write!(f, "] ({} params)", self.params.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_the_first_params_are_displayed() {
+        let params = [0.5, 1.0, -2.0, 3.0];
+
+        assert_eq!(
+            ParamsPreview::new(&params, 2).to_string(),
+            "[0.5, 1, ...] (4 params)"
+        );
+        assert_eq!(
+            ParamsPreview::new(&params, 10).to_string(),
+            "[0.5, 1, -2, 3] (4 params)"
+        );
+        assert_eq!(
+            ParamsPreview::new(&params, 0).to_string(),
+            "[...] (4 params)"
+        );
+    }
+}
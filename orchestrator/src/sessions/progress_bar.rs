@@ -0,0 +1,260 @@
+use std::{
+    io::{self, IsTerminal, Stderr, Write},
+    num::NonZeroUsize,
+    time::{Duration, Instant},
+};
+
+use super::{ProgressRecorder, TrainingEvent};
+
+/// The width of the bar, in characters.
+const BAR_WIDTH: usize = 30;
+
+/// The least time in between two lines when the output can't be redrawn.
+const LINE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Shows how far the training got, driven by the session's events.
+///
+/// On a terminal the bar is redrawn in place every time an epoch finishes, otherwise a
+/// line is printed at most every `LINE_INTERVAL` so the output stays readable in a log.
+pub struct ProgressBar<W: Write> {
+    out: W,
+    redraw: bool,
+    total_epochs: NonZeroUsize,
+    recorder: ProgressRecorder,
+    start: Instant,
+    shown_epoch: usize,
+    last_line: Option<Instant>,
+}
+
+impl ProgressBar<Stderr> {
+    /// Creates a new `ProgressBar` on stderr, redrawn in place only if it's a terminal.
+    ///
+    /// # Args
+    /// * `nworkers` - The amount of workers training.
+    /// * `total_epochs` - The maximum amount of epochs the training runs.
+    ///
+    /// # Returns
+    /// A new `ProgressBar` instance.
+    pub fn stderr(nworkers: usize, total_epochs: NonZeroUsize) -> Self {
+        let out = io::stderr();
+        let redraw = out.is_terminal();
+        Self::new(out, redraw, nworkers, total_epochs)
+    }
+}
+
+impl<W: Write> ProgressBar<W> {
+    /// Creates a new `ProgressBar`.
+    ///
+    /// # Args
+    /// * `out` - Where to write the progress to.
+    /// * `redraw` - Whether to redraw the bar in place instead of printing lines.
+    /// * `nworkers` - The amount of workers training.
+    /// * `total_epochs` - The maximum amount of epochs the training runs.
+    ///
+    /// # Returns
+    /// A new `ProgressBar` instance.
+    pub fn new(out: W, redraw: bool, nworkers: usize, total_epochs: NonZeroUsize) -> Self {
+        Self {
+            out,
+            redraw,
+            total_epochs,
+            recorder: ProgressRecorder::new(nworkers),
+            start: Instant::now(),
+            shown_epoch: 0,
+            last_line: None,
+        }
+    }
+
+    /// Updates the progress with an event of the session.
+    ///
+    /// # Args
+    /// * `event` - The event received from the session.
+    ///
+    /// # Errors
+    /// An io error if writing the progress fails.
+    pub fn observe(&mut self, event: &TrainingEvent) -> io::Result<()> {
+        match event {
+            TrainingEvent::PublishedLosses {
+                worker_id, losses, ..
+            } => {
+                self.recorder.record(*worker_id, losses);
+
+                let epoch = self.recorder.epochs_run();
+                if epoch == self.shown_epoch {
+                    return Ok(());
+                }
+
+                self.shown_epoch = epoch;
+                self.show(false)
+            }
+            TrainingEvent::TrainingComplete { .. } | TrainingEvent::Error(..) => self.finish(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Shows the last progress, ending the bar's line.
+    ///
+    /// # Errors
+    /// An io error if writing the progress fails.
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.show(true)?;
+
+        if self.redraw {
+            writeln!(self.out)?;
+        }
+
+        self.out.flush()
+    }
+
+    /// Writes the current progress, as a redrawn bar or a new line.
+    ///
+    /// # Args
+    /// * `last` - Whether it's the last time the progress is shown.
+    ///
+    /// # Errors
+    /// An io error if writing the progress fails.
+    fn show(&mut self, last: bool) -> io::Result<()> {
+        let now = Instant::now();
+        let status = self.status(now - self.start);
+
+        if self.redraw {
+            write!(self.out, "\r{} {status}\x1b[K", self.bar())?;
+            return self.out.flush();
+        }
+
+        let due = self
+            .last_line
+            .is_none_or(|last_line| now - last_line >= LINE_INTERVAL);
+
+        if last || due {
+            self.last_line = Some(now);
+            writeln!(self.out, "{status}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws the bar filled up to the current epoch.
+    ///
+    /// # Returns
+    /// The drawn bar.
+    fn bar(&self) -> String {
+        let epoch = self.shown_epoch.min(self.total_epochs.get());
+        let filled = epoch * BAR_WIDTH / self.total_epochs.get();
+        format!("[{}{}]", "=".repeat(filled), " ".repeat(BAR_WIDTH - filled))
+    }
+
+    /// Describes the current epoch, loss and the estimated time left.
+    ///
+    /// # Args
+    /// * `elapsed` - The time since the training started.
+    ///
+    /// # Returns
+    /// The progress' description.
+    fn status(&self, elapsed: Duration) -> String {
+        let loss = self
+            .recorder
+            .final_loss()
+            .map_or("-".to_string(), |loss| format!("{loss:.6}"));
+
+        let eta = eta(elapsed, self.shown_epoch, self.total_epochs.get())
+            .map_or("-".to_string(), format_duration);
+
+        format!(
+            "epoch {}/{}, loss {loss}, eta {eta}",
+            self.shown_epoch, self.total_epochs
+        )
+    }
+}
+
+/// Estimates the time left for the training out of the mean time of it's epochs so far.
+///
+/// # Args
+/// * `elapsed` - The time since the training started.
+/// * `epoch` - The amount of epochs run so far.
+/// * `total` - The maximum amount of epochs.
+///
+/// # Returns
+/// The time left or `None` if no epoch finished yet.
+fn eta(elapsed: Duration, epoch: usize, total: usize) -> Option<Duration> {
+    if epoch == 0 {
+        return None;
+    }
+
+    let left = total.saturating_sub(epoch) as u32;
+    Some(elapsed / epoch as u32 * left)
+}
+
+/// Formats a duration as `mm:ss`, or `hh:mm:ss` once it's an hour or longer.
+///
+/// # Args
+/// * `duration` - The duration to format.
+///
+/// # Returns
+/// The formatted duration.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, mins, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+
+    match hours {
+        0 => format!("{mins:02}:{secs:02}"),
+        _ => format!("{hours:02}:{mins:02}:{secs:02}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn losses(worker_id: usize, loss: f64) -> TrainingEvent {
+        TrainingEvent::PublishedLosses {
+            worker_id,
+            losses: vec![loss],
+            timing: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_the_eta_is_the_mean_epoch_time_times_the_epochs_left() {
+        let elapsed = Duration::from_secs(30);
+        assert_eq!(eta(elapsed, 0, 10), None);
+        assert_eq!(eta(elapsed, 3, 10), Some(Duration::from_secs(70)));
+        assert_eq!(format_duration(Duration::from_secs(70)), "01:10");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "01:02:05");
+    }
+
+    #[test]
+    fn test_without_a_terminal_only_periodic_lines_are_printed() {
+        let total_epochs = NonZeroUsize::new(4).unwrap();
+        let mut bar = ProgressBar::new(Vec::new(), false, 2, total_epochs);
+
+        for epoch in 1..=3 {
+            bar.observe(&losses(0, epoch as f64)).unwrap();
+            bar.observe(&losses(1, epoch as f64 + 1.0)).unwrap();
+        }
+
+        bar.finish().unwrap();
+
+        let out = String::from_utf8(bar.out).unwrap();
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 2, "{out}");
+        assert!(lines[0].starts_with("epoch 1/4, loss 1.000000, eta "));
+        assert!(lines[1].starts_with("epoch 3/4, loss 3.500000, eta "));
+        assert!(!out.contains('\r'));
+    }
+
+    #[test]
+    fn test_on_a_terminal_the_bar_is_redrawn_in_place() {
+        let total_epochs = NonZeroUsize::new(2).unwrap();
+        let mut bar = ProgressBar::new(Vec::new(), true, 1, total_epochs);
+
+        bar.observe(&losses(0, 0.5)).unwrap();
+        bar.observe(&losses(0, 0.25)).unwrap();
+        bar.finish().unwrap();
+
+        let out = String::from_utf8(bar.out).unwrap();
+        assert_eq!(out.matches('\r').count(), 3);
+        assert_eq!(out.lines().count(), 1);
+        assert!(out.contains(&format!("[{}]", "=".repeat(BAR_WIDTH))));
+    }
+}
@@ -0,0 +1,137 @@
+/// Records how far each worker got through the training.
+#[derive(Debug, Default)]
+pub struct ProgressRecorder {
+    epochs: Vec<usize>,
+    last_losses: Vec<Option<f64>>,
+    samples: u64,
+    bytes_per_epoch: Vec<(u64, u64)>,
+}
+
+impl ProgressRecorder {
+    /// Creates a new `ProgressRecorder`.
+    ///
+    /// # Args
+    /// * `nworkers` - The amount of workers training.
+    ///
+    /// # Returns
+    /// A new `ProgressRecorder` instance.
+    pub fn new(nworkers: usize) -> Self {
+        Self {
+            epochs: vec![0; nworkers],
+            last_losses: vec![None; nworkers],
+            samples: 0,
+            bytes_per_epoch: Vec::new(),
+        }
+    }
+
+    /// Records the losses a worker published, one per epoch it ran.
+    ///
+    /// # Args
+    /// * `worker_id` - The id of the worker that published the losses.
+    /// * `losses` - The losses it published.
+    pub fn record(&mut self, worker_id: usize, losses: &[f64]) {
+        let Some(epochs) = self.epochs.get_mut(worker_id) else {
+            return;
+        };
+
+        *epochs += losses.len();
+
+        if let Some(&last) = losses.last() {
+            self.last_losses[worker_id] = Some(last);
+        }
+    }
+
+    /// Records the samples a worker reported training on during a round.
+    ///
+    /// # Args
+    /// * `samples` - The amount of samples it trained on.
+    pub fn record_samples(&mut self, samples: u64) {
+        self.samples += samples;
+    }
+
+    /// Records the bytes a worker reported exchanging with it's peers during a round.
+    ///
+    /// # Args
+    /// * `epoch` - The amount of epochs the worker finished by the end of the round.
+    /// * `bytes_sent` - The amount of bytes it sent.
+    /// * `bytes_received` - The amount of bytes it received.
+    pub fn record_bytes(&mut self, epoch: usize, bytes_sent: u64, bytes_received: u64) {
+        if bytes_sent == 0 && bytes_received == 0 {
+            return;
+        }
+
+        let i = epoch.saturating_sub(1);
+        if self.bytes_per_epoch.len() <= i {
+            self.bytes_per_epoch.resize(i + 1, (0, 0));
+        }
+
+        let (sent, received) = &mut self.bytes_per_epoch[i];
+        *sent += bytes_sent;
+        *received += bytes_received;
+    }
+
+    /// The amount of epochs run by the furthest worker.
+    ///
+    /// # Returns
+    /// The maximum amount of epochs run by a worker.
+    pub fn epochs_run(&self) -> usize {
+        self.epochs.iter().copied().max().unwrap_or(0)
+    }
+
+    /// The mean of the last loss published by each worker.
+    ///
+    /// # Returns
+    /// The final loss or `None` if no worker published a finite loss.
+    pub fn final_loss(&self) -> Option<f64> {
+        let finite: Vec<_> = self
+            .last_losses
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|loss| loss.is_finite())
+            .collect();
+
+        (!finite.is_empty()).then(|| finite.iter().sum::<f64>() / finite.len() as f64)
+    }
+
+    /// The amount of samples processed by all the workers, as they reported them.
+    ///
+    /// # Returns
+    /// The total amount of samples processed.
+    pub fn total_samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// The bytes all the workers exchanged with their peers, summed per epoch.
+    ///
+    /// # Returns
+    /// The bytes sent and received during each epoch, the first one at index 0.
+    pub fn bytes_per_epoch(&self) -> &[(u64, u64)] {
+        &self.bytes_per_epoch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_is_summarized_across_workers() {
+        let mut recorder = ProgressRecorder::new(2);
+        recorder.record(0, &[3.0, 2.0]);
+        recorder.record(1, &[4.0]);
+        recorder.record(0, &[1.0]);
+        recorder.record(1, &[f64::NAN]);
+        recorder.record_samples(25);
+        recorder.record_samples(40);
+        recorder.record_bytes(1, 100, 200);
+        recorder.record_bytes(1, 10, 20);
+        recorder.record_bytes(3, 5, 0);
+        recorder.record_bytes(3, 0, 0);
+
+        assert_eq!(recorder.epochs_run(), 3);
+        assert_eq!(recorder.total_samples(), 65);
+        assert_eq!(recorder.final_loss(), Some(1.0));
+        assert_eq!(recorder.bytes_per_epoch(), [(110, 220), (0, 0), (5, 0)]);
+    }
+}
@@ -0,0 +1,78 @@
+use machine_learning::optimization::{LrSchedule, WarmRestarts};
+
+/// Follows the epochs the workers ran to move the servers along their learning rate
+/// schedule and spot it's restarts.
+///
+/// The epochs are counted from the losses the workers publish, one per epoch they ran,
+/// so the offline epochs are counted as well.
+#[derive(Debug)]
+pub struct RestartTracker {
+    schedule: WarmRestarts,
+    epoch: usize,
+}
+
+impl RestartTracker {
+    /// Creates a new `RestartTracker`.
+    ///
+    /// # Args
+    /// * `schedule` - The servers' learning rate schedule.
+    ///
+    /// # Returns
+    /// A new `RestartTracker` instance.
+    pub fn new(schedule: WarmRestarts) -> Self {
+        Self { schedule, epoch: 0 }
+    }
+
+    /// Moves the tracker to the epoch the workers reached.
+    ///
+    /// # Args
+    /// * `epoch` - The amount of epochs run so far.
+    ///
+    /// # Returns
+    /// `None` if the epoch didn't advance, otherwise the epochs and the indices of the
+    /// cycles that start with them for every restart in between.
+    pub fn advance(&mut self, epoch: usize) -> Option<Vec<(usize, usize)>> {
+        if epoch <= self.epoch {
+            return None;
+        }
+
+        let restarts = (self.epoch + 1..=epoch)
+            .filter(|&epoch| self.schedule.is_restart(epoch))
+            .map(|epoch| (epoch, self.schedule.cycle(epoch).0))
+            .collect();
+
+        self.epoch = epoch;
+        Some(restarts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+
+    #[test]
+    fn test_restarts_are_reported_once_the_epochs_reach_them() {
+        let t_0 = NonZeroUsize::new(2).unwrap();
+        let t_mult = NonZeroUsize::new(2).unwrap();
+        let mut tracker = RestartTracker::new(WarmRestarts::new(t_0, t_mult, 1e-3));
+
+        let restarts: Vec<_> = (1..=14)
+            .filter_map(|epoch| tracker.advance(epoch))
+            .flatten()
+            .collect();
+        assert_eq!(restarts, [(2, 1), (6, 2), (14, 3)]);
+    }
+
+    #[test]
+    fn test_jumping_over_several_epochs_reports_every_restart() {
+        let t_0 = NonZeroUsize::new(2).unwrap();
+        let mut tracker = RestartTracker::new(WarmRestarts::new(t_0, NonZeroUsize::MIN, 1e-3));
+
+        assert_eq!(tracker.advance(5), Some(vec![(2, 1), (4, 2)]));
+        assert_eq!(tracker.advance(5), None);
+        assert_eq!(tracker.advance(3), None);
+        assert_eq!(tracker.advance(6), Some(vec![(6, 3)]));
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+use comms::specs::worker::{AlgorithmSpec, SerializerSpec, WorkerSpec};
+use machine_learning::rng::{self, RngPurpose};
+
+use crate::configs::{ServerAdapt, WorkerAdapt};
+
+/// The purpose of the seed the sparse serializers draw their masks with.
+const SPARSE_COMPRESSION: &str = "sparse_compression";
+
+/// The seeds each node of a session derives for it's sources of randomness.
+///
+/// Lets a non reproducible run be debugged by spotting the nodes that share, or
+/// unexpectedly don't share, a seed for the same purpose.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedReport {
+    seed: Option<u64>,
+    nodes: Vec<(String, BTreeMap<&'static str, u64>)>,
+}
+
+impl SeedReport {
+    /// Creates a new `SeedReport` from the specifications sent to the nodes.
+    ///
+    /// # Args
+    /// * `seed` - The seed of the training.
+    /// * `workers` - The workers' adapted values.
+    /// * `servers` - The servers' adapted values.
+    ///
+    /// # Returns
+    /// A new `SeedReport` instance.
+    pub fn new(seed: Option<u64>, workers: &[WorkerAdapt<'_>], servers: &[ServerAdapt]) -> Self {
+        let workers = workers.iter().enumerate().map(|(i, worker)| {
+            let node = format!("worker {i} ({})", worker.addr);
+            (node, Self::worker_seeds(&worker.spec))
+        });
+
+        let servers = servers.iter().enumerate().map(|(i, server)| {
+            let node = format!("server {i} ({})", server.addr);
+            let seeds = Self::derive(server.spec.seed, [RngPurpose::WeightInit]);
+            (node, seeds.collect())
+        });
+
+        Self {
+            seed,
+            nodes: workers.chain(servers).collect(),
+        }
+    }
+
+    /// The seed of the training.
+    ///
+    /// # Returns
+    /// The seed or `None` if the nodes seed their generators from the os.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// The seeds derived by a node.
+    ///
+    /// # Args
+    /// * `node` - The position of the node, workers first and then servers.
+    ///
+    /// # Returns
+    /// The node's name and it's seed for each purpose, if the node exists.
+    pub fn node(&self, node: usize) -> Option<(&str, &BTreeMap<&'static str, u64>)> {
+        self.nodes
+            .get(node)
+            .map(|(name, seeds)| (name.as_str(), seeds))
+    }
+
+    /// The seeds that are used by more than one node or purpose.
+    ///
+    /// # Returns
+    /// Each shared seed along with the nodes and purposes it's used for.
+    pub fn shared(&self) -> BTreeMap<u64, Vec<(&str, &'static str)>> {
+        let mut users: BTreeMap<_, Vec<_>> = BTreeMap::new();
+
+        for (name, seeds) in &self.nodes {
+            for (&purpose, &seed) in seeds {
+                users
+                    .entry(seed)
+                    .or_default()
+                    .push((name.as_str(), purpose));
+            }
+        }
+
+        users.retain(|_, users| users.len() > 1);
+        users
+    }
+
+    /// The seeds a worker derives from it's specification.
+    ///
+    /// # Args
+    /// * `spec` - The worker's specification.
+    ///
+    /// # Returns
+    /// The worker's seed for each purpose.
+    fn worker_seeds(spec: &WorkerSpec) -> BTreeMap<&'static str, u64> {
+        let mut seeds: BTreeMap<_, _> =
+            Self::derive(spec.trainer.seed, [RngPurpose::Shuffle]).collect();
+        seeds.extend(Self::derive(
+            spec.trainer.augmentation_seed,
+            [RngPurpose::Augmentation],
+        ));
+
+        if let AlgorithmSpec::AllReduce { .. } = spec.algorithm {
+            seeds.extend(Self::derive(spec.seed, [RngPurpose::WeightInit]));
+        }
+
+        // The sparse serializers seed their generators with the seed as is.
+        if let (SerializerSpec::SparseCapable { .. }, Some(seed)) = (spec.serializer, spec.seed) {
+            seeds.insert(SPARSE_COMPRESSION, seed);
+        }
+
+        seeds
+    }
+
+    /// Derives the seeds of the given purposes.
+    ///
+    /// # Args
+    /// * `seed` - The seed the node received.
+    /// * `purposes` - The purposes to derive a seed for.
+    ///
+    /// # Returns
+    /// An iterator over the name and seed of each purpose, empty if there's no seed.
+    fn derive<const N: usize>(
+        seed: Option<u64>,
+        purposes: [RngPurpose; N],
+    ) -> impl Iterator<Item = (&'static str, u64)> {
+        seed.into_iter().flat_map(move |seed| {
+            purposes
+                .into_iter()
+                .map(move |purpose| (purpose.name(), rng::derive_seed(seed, purpose)))
+        })
+    }
+}
+
+impl Display for SeedReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Some(seed) = self.seed else {
+            return write!(
+                f,
+                "no seed set, every node seeds it's generators from the os"
+            );
+        };
+
+        write!(f, "seed {seed}")?;
+
+        for (name, seeds) in &self.nodes {
+            write!(f, "\n  {name}:")?;
+
+            for (purpose, seed) in seeds {
+                write!(f, " {purpose}={seed:#018x}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use comms::{
+        floats::{Float01, FloatPositive},
+        specs::machine_learning::{
+            DatasetSpec, FinalStepPolicy, LossFnSpec, OptimizerSpec, SamplerSpec, StopCondition,
+            StrategySpec, TrainerSpec,
+        },
+    };
+
+    use super::*;
+    use crate::configs::Partition;
+
+    fn worker(addr: &str, seed: Option<u64>) -> WorkerAdapt<'static> {
+        let one = NonZeroUsize::new(1).unwrap();
+
+        let trainer = TrainerSpec {
+            layers: Vec::new(),
+            optimizer: OptimizerSpec::GradientDescent {
+                learning_rate: FloatPositive::new(0.1).unwrap(),
+            },
+            dataset: DatasetSpec {
+                x_size: one,
+                y_size: one,
+                sparse: false,
+            },
+            loss_fn: LossFnSpec::Mse,
+            offline_epochs: 0,
+            stop_condition: StopCondition::MaxEpochs(one),
+            final_step: FinalStepPolicy::ApplyPartial,
+            batch_size: one,
+            sampler: SamplerSpec::Shuffle,
+            seed,
+            augmentation_seed: None,
+            clip_groups: Vec::new(),
+            heads: Vec::new(),
+            checkpoint_every: None,
+            resume: None,
+            strategy: StrategySpec::Backprop,
+        };
+
+        let spec = WorkerSpec {
+            trainer,
+            algorithm: AlgorithmSpec::ParameterServer {
+                server_addrs: Vec::new(),
+                server_sizes: Vec::new(),
+                server_ordering: Vec::new(),
+            },
+            serializer: SerializerSpec::SparseCapable {
+                r: Float01::new(0.5).unwrap(),
+            },
+            seed,
+            max_frame_size: None,
+            require_compression: false,
+            latency_warmup_rounds: None,
+            synthetic: None,
+        };
+
+        WorkerAdapt {
+            addr: addr.into(),
+            spec,
+            partition: Partition::Inline {
+                samples: &[],
+                labels: &[],
+            },
+        }
+    }
+
+    #[test]
+    fn test_seed_report_exposes_the_derived_seeds() {
+        let workers = [worker("node-0", Some(42)), worker("node-1", Some(42))];
+        let report = SeedReport::new(Some(42), &workers, &[]);
+
+        let (name, seeds) = report.node(1).unwrap();
+        let shuffle = rng::derive_seed(42, RngPurpose::Shuffle);
+
+        assert_eq!(name, "worker 1 (node-1)");
+        assert_eq!(seeds["shuffle"], shuffle);
+        assert_eq!(seeds[SPARSE_COMPRESSION], 42);
+        assert!(report.node(2).is_none());
+
+        let shared = report.shared();
+        assert_eq!(shared.len(), 2);
+        assert_eq!(
+            shared[&shuffle],
+            [
+                ("worker 0 (node-0)", "shuffle"),
+                ("worker 1 (node-1)", "shuffle")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_seed_report_is_empty_without_a_seed() {
+        let report = SeedReport::new(None, &[worker("node-0", None)], &[]);
+
+        assert!(report.node(0).unwrap().1.is_empty());
+        assert!(report.shared().is_empty());
+    }
+}
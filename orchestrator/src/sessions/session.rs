@@ -1,100 +1,244 @@
 use std::{
-    collections::{HashMap, HashSet},
-    io::SeekFrom,
+    collections::{BTreeMap, HashMap, HashSet},
+    io::{self, SeekFrom},
+    mem,
+    ops::ControlFlow,
     path::Path,
+    sync::Arc,
     thread,
+    time::{Duration, Instant},
 };
 
 use comms::{
-    Connector, NetRtp, ParamServerHandle, TransportLayer, WorkerHandle, protocol::Entity,
+    NetRtp, NodeHandle, ParamServerHandle, TransportLayer, WorkerHandle, floats::FloatPositive,
     share_dataset,
 };
 use futures::future;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use machine_learning::datasets::CsrMatrix;
 use tokio::{
     fs::File,
     io::{AsyncReadExt, AsyncSeekExt},
-    net::{
-        TcpStream,
-        tcp::{OwnedReadHalf, OwnedWriteHalf},
-    },
-    runtime::{Builder, Runtime},
+    runtime::Runtime,
     sync::mpsc::{self, Receiver, Sender},
+    task::JoinHandle,
+    time,
 };
 use uuid::Uuid;
 
-use super::{EventListener, TrainedModel, WorkerListener, WorkerRequest};
+use super::{
+    EpochHook, EpochSummary, EventFeed, EventListener, LrHandle, SnapshotKeeper, TrainedModel,
+    TrainingReport, WorkerListener, WorkerRequest,
+};
+#[cfg(feature = "local")]
+use crate::local_nodes::LocalNodes;
 use crate::{
-    OrchErr, Result, StopReason, TrainingEvent,
+    OrchErr, OrchestratorPool, Result, StopReason, TrainingEvent,
     configs::{
         AlgorithmConfig, OrchAdapt, Partition, ServerAdapt, StrategySwitchTracking, WorkerAdapt,
     },
-    sessions::{ConvergenceTracker, LossRecorder},
+    pool::PoolReturn,
+    sessions::{
+        ConvergenceTracker, DivergenceTracker, LearningCurve, LossRecorder, MonitorCadence,
+        ProgressRecorder, RestartTracker, SeedReport, ValidationRecorder,
+    },
 };
 
+/// The maximum amount of time to wait for a worker's listener to hand back it's connection
+/// once the session is over.
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The task of a worker's listener, yielding the worker's handle if it's session ended in
+/// an orderly way.
+type WorkerListenerTask = JoinHandle<Option<WorkerHandle<NetRtp>>>;
+
 /// An ongoing training session.
 pub struct Session {
-    runtime: Runtime,
+    runtime: Arc<Runtime>,
     orch_adapt: OrchAdapt,
     worker_handles: Vec<WorkerHandle<NetRtp>>,
     server_handles: Vec<ParamServerHandle<NetRtp>>,
+    worker_samples: Vec<u64>,
+    seed_report: SeedReport,
+    event_feed: Option<EventFeed>,
+    lr_tx: Sender<FloatPositive>,
+    lr_rx: Receiver<FloatPositive>,
+    epoch_hook: Option<EpochHook>,
+    node_addrs: HashMap<Uuid, String>,
+    pool_return: PoolReturn,
+    #[cfg(feature = "local")]
+    local_nodes: Option<LocalNodes>,
 }
 
 impl Session {
-    /// Creates a new session by connecting to all workers and servers.
+    /// Creates a new session by bootstrapping all workers and servers.
+    ///
+    /// The nodes are bootstrapped through the given handles, the ones missing are borrowed
+    /// from the pool. The given handles of the nodes the session doesn't bootstrap are
+    /// checked back into the pool, idle, for a later session. So are the connections of
+    /// the bootstrapped nodes once the session is over and they went back to idle.
     ///
     /// # Args
     /// * `orch` - The orchestrator's values for orchestrating the session.
     /// * `workers` - The workers' network addresses, specifications and dataset partitions.
     /// * `servers` - The servers' network addresses and specifications.
-    /// * `connector` - The network connector.
+    /// * `node_handles` - The already connected node handles keyed by their address.
+    /// * `pool` - The pool to borrow the missing node connections from.
     ///
     /// # Returns
     /// A ready session with all connections established.
     ///
     /// # Errors
     /// Returns an `OrchErr` if any connection or bootstrap message fails.
-    pub fn new<F>(
+    pub fn new(
         orch: OrchAdapt,
         workers: Vec<WorkerAdapt<'_>>,
         servers: Vec<ServerAdapt>,
-        connector: Connector<OwnedReadHalf, OwnedWriteHalf, NetRtp, F>,
-    ) -> Result<Self>
-    where
-        F: Fn(OwnedReadHalf, OwnedWriteHalf) -> NetRtp,
-    {
-        let runtime = Builder::new_multi_thread().enable_all().build()?;
+        mut node_handles: BTreeMap<String, NodeHandle<NetRtp>>,
+        pool: &mut OrchestratorPool,
+    ) -> Result<Self> {
+        let runtime = pool.runtime();
         let (nworkers, nservers) = (workers.len(), servers.len());
 
+        let seed_report = SeedReport::new(orch.seed, &workers, &servers);
+        info!("{seed_report}");
+
+        let event_feed = orch
+            .event_feed_path
+            .clone()
+            .map(EventFeed::bind)
+            .transpose()?;
+
+        let server_addrs: Vec<_> = match orch.algorithm_config {
+            AlgorithmConfig::ParameterServer { .. } => {
+                servers.iter().map(|server| server.addr.clone()).collect()
+            }
+            _ => Vec::new(),
+        };
+
+        let worker_addrs: Vec<_> = workers.iter().map(|worker| worker.addr.clone()).collect();
+
+        info!("connecting to {nservers} servers and {nworkers} workers");
+        let server_node_handles = runtime.block_on(Self::borrow_node_handles(
+            &mut node_handles,
+            &server_addrs,
+            pool,
+        ))?;
+        let worker_node_handles = runtime.block_on(Self::borrow_node_handles(
+            &mut node_handles,
+            &worker_addrs,
+            pool,
+        ))?;
+
+        // The nodes left out of the session are still idle.
+        pool.checkin_all(node_handles);
+
+        let node_addrs = server_node_handles
+            .iter()
+            .chain(&worker_node_handles)
+            .map(|(addr, node_handle)| (node_handle.id(), addr.clone()))
+            .collect();
+
         let server_handles = match orch.algorithm_config {
             AlgorithmConfig::ParameterServer { .. } => {
-                info!("connecting to {nservers} servers");
-                let server_handles = runtime.block_on(Self::create_servers(servers, &connector))?;
+                let server_handles =
+                    runtime.block_on(Self::create_servers(servers, server_node_handles))?;
                 info!("successfully created servers");
                 server_handles
             }
             _ => Vec::new(),
         };
 
-        info!("connecting to {nworkers} workers");
-        let worker_handles = runtime.block_on(Self::create_workers(workers, &connector))?;
+        let worker_samples = workers
+            .iter()
+            .map(|worker| {
+                let x_size = worker.spec.trainer.dataset.x_size;
+                worker.partition.nsamples(x_size)
+            })
+            .collect();
+
+        let worker_handles =
+            runtime.block_on(Self::create_workers(workers, worker_node_handles))?;
         info!("successfully created workers");
 
+        let (lr_tx, lr_rx) = mpsc::channel(8);
+
         let session = Self {
             runtime,
             orch_adapt: orch,
             worker_handles,
             server_handles,
+            worker_samples,
+            seed_report,
+            event_feed,
+            lr_tx,
+            lr_rx,
+            epoch_hook: None,
+            node_addrs,
+            pool_return: pool.pool_return(),
+            #[cfg(feature = "local")]
+            local_nodes: None,
         };
 
         Ok(session)
     }
 
+    /// The seeds every node derives for it's sources of randomness.
+    ///
+    /// # Returns
+    /// The session's seed report.
+    pub fn seed_report(&self) -> &SeedReport {
+        &self.seed_report
+    }
+
+    /// Creates a handle to change the learning rate of the servers while the session runs.
+    ///
+    /// Only the servers' optimizers are changed, so it has no effect on an all reduce
+    /// training. The handle must be created before the session is consumed by
+    /// `Session::event_listener`.
+    ///
+    /// # Returns
+    /// A new `LrHandle` for this session.
+    pub fn lr_handle(&self) -> LrHandle {
+        LrHandle(self.lr_tx.clone())
+    }
+
+    /// Registers a callback to invoke after every epoch of the training.
+    ///
+    /// The callback runs in a blocking thread once every worker finished the epoch,
+    /// returning `ControlFlow::Break` stops the training. If it panics, the panic is
+    /// surfaced as a `TrainingEvent::Error` and the training stops. It must be
+    /// registered before the session is consumed by `Session::event_listener`.
+    ///
+    /// # Args
+    /// * `callback` - The callback to invoke with the metrics of each epoch.
+    pub fn on_epoch<F>(&mut self, callback: F)
+    where
+        F: Fn(&EpochSummary) -> ControlFlow<()> + Send + Sync + 'static,
+    {
+        self.epoch_hook = Some(EpochHook::new(callback));
+    }
+
+    /// Ties the nodes running inside this process to the session, they're stopped and
+    /// joined once the session is over or it's dropped before being listened to.
+    ///
+    /// # Args
+    /// * `local_nodes` - The nodes the session runs on.
+    ///
+    /// # Returns
+    /// The modified `Session`.
+    #[cfg(feature = "local")]
+    pub(crate) fn with_local_nodes(mut self, local_nodes: LocalNodes) -> Self {
+        self.local_nodes = Some(local_nodes);
+        self
+    }
+
     /// Consumes `self` and creates an event listener for this training session.
     ///
     /// Spawns a background task that drives the session. The `cancel_rx` must come
     /// from a `CancelHandle::pair()` call; the caller retains the `CancelHandle` to
-    /// request an orderly stop at any time.
+    /// request an orderly stop at any time. If there's an event feed, every event is
+    /// published to it before reaching the returned receiver.
     ///
     /// # Args
     /// * `cancel_rx` - A training cancel event receiver.
@@ -108,64 +252,209 @@ impl Session {
             runtime,
             worker_handles,
             mut server_handles,
+            worker_samples,
+            seed_report: _,
+            event_feed,
+            lr_tx: _,
+            lr_rx,
+            epoch_hook,
+            node_addrs,
+            pool_return,
+            #[cfg(feature = "local")]
+            local_nodes,
             orch_adapt:
                 OrchAdapt {
                     input_size,
                     loss_recorder,
                     convergence_tracker,
+                    divergence_tracker,
+                    monitor_cadence,
+                    restart_tracker,
                     model_config,
                     algorithm_config,
                     switch_tracking,
                     layer_param_offsets,
+                    allow_worker_loss,
+                    ema,
+                    track_staleness,
+                    learning_curve_path,
+                    snapshot_every,
+                    event_feed_path: _,
+                    test_set,
+                    seed: _,
                 },
         } = self;
 
-        let run_loop_fut = async move {
+        let learning_curve =
+            learning_curve_path.map(|path| LearningCurve::new(path, worker_samples.clone()));
+
+        let snapshot_keeper =
+            snapshot_every.map(|every| SnapshotKeeper::new(every, layer_param_offsets.clone()));
+
+        let run_loop = async move |user_event_tx: Sender<TrainingEvent>| {
+            let start = Instant::now();
             let (event_tx, mut event_rx) = mpsc::channel(256);
 
-            let (Some(stop_reason), mut req_txs) = Self::start_training(
+            let (
+                Some(stop_reason),
+                progress,
+                mut validation,
+                snapshot_keeper,
+                mut req_txs,
+                listeners,
+            ) = Self::start_training(
                 worker_handles,
                 &mut event_rx,
                 &event_tx,
                 cancel_rx,
+                lr_rx,
                 loss_recorder,
                 convergence_tracker,
+                divergence_tracker,
+                monitor_cadence,
+                restart_tracker,
                 &user_event_tx,
                 switch_tracking,
                 &mut server_handles,
+                allow_worker_loss,
+                learning_curve,
+                epoch_hook,
+                snapshot_keeper,
             )
             .await
             else {
                 return;
             };
 
-            let params = Self::finalize_training(
+            let finalized = Self::finalize_training(
                 algorithm_config,
-                server_handles,
+                &mut server_handles,
                 &user_event_tx,
                 &mut req_txs,
                 &mut event_rx,
                 &layer_param_offsets,
+                ema,
+                track_staleness,
             )
             .await;
 
+            let (params, ema_params) = match finalized {
+                Ok(finalized) => finalized,
+                Err(cause) => {
+                    let err = match snapshot_keeper {
+                        Some(mut keeper) => keeper.attach(cause),
+                        None => cause,
+                    };
+
+                    let _ = user_event_tx.send(TrainingEvent::Error(err)).await;
+                    return;
+                }
+            };
+
+            let node_handles = Self::release_nodes(listeners, server_handles, &node_addrs).await;
+            pool_return.checkin_all(node_handles).await;
+
             let nparams = params.len();
             info!("received {nparams} total parameters");
 
+            let test_loss = test_set.and_then(|test_set| {
+                let nrows = test_set.len();
+
+                match test_set.loss(&params) {
+                    Ok(loss) => {
+                        info!("test loss over the {nrows} held out rows: {loss}");
+                        Some(loss)
+                    }
+                    Err(e) => {
+                        warn!("failed to test the model on the held out rows: {e}");
+                        None
+                    }
+                }
+            });
+
             let model = TrainedModel {
                 params,
+                ema_params,
                 model: model_config,
                 input_size: input_size.get(),
             };
 
-            let event = TrainingEvent::TrainingComplete { model, stop_reason };
+            let mut report = TrainingReport::new(
+                model,
+                stop_reason,
+                progress.final_loss(),
+                test_loss,
+                progress.epochs_run(),
+                progress.total_samples(),
+                start.elapsed(),
+            )
+            .with_bytes_per_epoch(progress.bytes_per_epoch().to_vec());
+
+            if let Some(metrics) = validation.take_regression_metrics() {
+                info!(
+                    "regression metrics over the {} training values, mse: {}, msle: {}, r²: {}",
+                    metrics.len(),
+                    metrics.mse(),
+                    metrics.msle(),
+                    metrics.r_squared()
+                );
+
+                let event = TrainingEvent::RegressionMetrics { metrics };
+                let _ = user_event_tx.send(event).await;
+            }
+
+            if let Some(confusion_matrix) = validation.take_confusion_matrix() {
+                report = report.with_confusion_matrix(confusion_matrix);
+            }
+
+            let event = TrainingEvent::TrainingComplete { report };
             let _ = user_event_tx.send(event).await;
         };
 
-        thread::spawn(move || runtime.block_on(run_loop_fut));
+        let Some(event_feed) = event_feed else {
+            thread::spawn(move || {
+                runtime.block_on(run_loop(user_event_tx));
+
+                // The local nodes are done once the session is over.
+                #[cfg(feature = "local")]
+                drop(local_nodes);
+            });
+
+            return user_event_rx;
+        };
+
+        let (feed_event_tx, feed_event_rx) = mpsc::channel(256);
+        let relay_fut = Self::relay_events(event_feed, feed_event_rx, user_event_tx);
+
+        thread::spawn(move || {
+            runtime.block_on(async move {
+                tokio::join!(run_loop(feed_event_tx), relay_fut);
+            });
+
+            #[cfg(feature = "local")]
+            drop(local_nodes);
+        });
+
         user_event_rx
     }
 
+    /// Publishes every event of the session to the event feed and forwards it to the user.
+    ///
+    /// # Args
+    /// * `event_feed` - The feed to publish the events to.
+    /// * `event_rx` - The session's event receiver.
+    /// * `user_event_tx` - The user event producer.
+    async fn relay_events(
+        mut event_feed: EventFeed,
+        mut event_rx: Receiver<TrainingEvent>,
+        user_event_tx: Sender<TrainingEvent>,
+    ) {
+        while let Some(event) = event_rx.recv().await {
+            event_feed.publish(&event);
+            let _ = user_event_tx.send(event).await;
+        }
+    }
+
     /// Starts the training stage for the orchestrator. Spawns the worker
     /// listeners and orchestrates the model's training.
     ///
@@ -174,76 +463,151 @@ impl Session {
     /// * `event_rx` - The worker listener event receiver.
     /// * `event_tx` - The event producer for the worker listeners.
     /// * `cancel_rx` - The user's halt event receiver.
+    /// * `lr_rx` - The user's learning rate change receiver.
     /// * `loss_recorder` - The workers' loss recorder.
     /// * `convergence_tracker` - A tracker device to track model convergence.
+    /// * `divergence_tracker` - A tracker of the loss' trend to act on a diverging model.
+    /// * `monitor_cadence` - The rounds after which convergence is checked.
+    /// * `restart_tracker` - A tracker of the learning rate schedule's restarts.
     /// * `user_event_tx` - The user event producer.
     /// * `switch_tracking` - The strategy switch tracking metadata.
     /// * `server_handles` - The server handles session vec.
+    /// * `allow_worker_loss` - Whether a lost worker connection is tolerated.
+    /// * `learning_curve` - The optional recorder of the per epoch metrics, written once
+    ///   the training stops for whatever reason.
+    /// * `epoch_hook` - The optional user callback to invoke after every epoch.
+    /// * `snapshot_keeper` - The optional keeper of the servers' parameters during the training.
     ///
     /// # Returns
-    /// The worker listener requesters and the stopping reason for the training.
+    /// The stopping reason for the training, the workers' progress, the metrics of the final
+    /// model they reported, the keeper of the last snapshot, the worker listener requesters
+    /// and the listeners themselves.
     async fn start_training(
         worker_handles: Vec<WorkerHandle<NetRtp>>,
         event_rx: &mut Receiver<TrainingEvent>,
         event_tx: &Sender<TrainingEvent>,
         cancel_rx: Receiver<()>,
+        lr_rx: Receiver<FloatPositive>,
         loss_recorder: LossRecorder,
         convergence_tracker: Option<ConvergenceTracker>,
+        divergence_tracker: Option<DivergenceTracker>,
+        monitor_cadence: MonitorCadence,
+        restart_tracker: Option<RestartTracker>,
         user_event_tx: &Sender<TrainingEvent>,
         switch_tracking: Option<StrategySwitchTracking>,
         server_handles: &mut Vec<ParamServerHandle<NetRtp>>,
-    ) -> (Option<StopReason>, Vec<Sender<WorkerRequest>>) {
-        let mut req_txs = Self::spawn_worker_listeners(worker_handles, event_tx);
+        allow_worker_loss: bool,
+        learning_curve: Option<LearningCurve>,
+        epoch_hook: Option<EpochHook>,
+        snapshot_keeper: Option<SnapshotKeeper>,
+    ) -> (
+        Option<StopReason>,
+        ProgressRecorder,
+        ValidationRecorder,
+        Option<SnapshotKeeper>,
+        Vec<Sender<WorkerRequest>>,
+        Vec<WorkerListenerTask>,
+    ) {
+        let (mut req_txs, listeners) =
+            Self::spawn_worker_listeners(worker_handles, event_tx, allow_worker_loss);
 
         let mut event_listener = EventListener::new(
             cancel_rx,
+            lr_rx,
             &mut req_txs,
             server_handles,
             loss_recorder,
             convergence_tracker,
+            divergence_tracker,
+            monitor_cadence,
+            restart_tracker,
             event_rx,
             user_event_tx.clone(),
             switch_tracking,
+            learning_curve,
+            epoch_hook,
+            snapshot_keeper,
         );
 
-        (event_listener.listen().await, req_txs)
+        let stop_reason = event_listener.listen().await;
+
+        if let Some(learning_curve) = event_listener.take_learning_curve() {
+            learning_curve.flush();
+        }
+
+        let progress = event_listener.take_progress();
+        let validation = event_listener.take_validation();
+        let snapshot_keeper = event_listener.take_snapshot_keeper();
+        (
+            stop_reason,
+            progress,
+            validation,
+            snapshot_keeper,
+            req_txs,
+            listeners,
+        )
     }
 
     /// Retrieves the parameters from the desired entity.
     ///
     /// # Args
     /// * `algorithm` - The current training algorithm.
-    /// * `server_handles` - The handles for communicating with the servers.
+    /// * `server_handles` - The handles for communicating with the servers, only the ones
+    ///   of the servers disconnected cleanly are left.
     /// * `user_event_tx` - The user event notifier.
     /// * `req_txs` - The request senders for the worker listeners.
     /// * `event_rx` - The worker listener event consumer.
     /// * `layer_offsets` - Per-layer parameter locations: (server_id, start, end) within each server's buffer.
+    /// * `ema` - Whether the servers keep a moving average of the parameters.
+    /// * `staleness` - Whether the servers track the staleness of their gradients.
     ///
     /// # Returns
-    /// The trained parameters of the model.
+    /// The trained parameters of the model along with their moving average, if there's one.
+    ///
+    /// # Errors
+    /// An `OrchErr::ServerError` if a server failed to send it's parameters.
+    #[allow(clippy::too_many_arguments)]
     async fn finalize_training<T>(
         algorithm: AlgorithmConfig,
-        server_handles: Vec<ParamServerHandle<T>>,
+        server_handles: &mut Vec<ParamServerHandle<T>>,
         user_event_tx: &Sender<TrainingEvent>,
         req_txs: &mut [Sender<WorkerRequest>],
         event_rx: &mut Receiver<TrainingEvent>,
         layer_offsets: &[(Uuid, usize, usize)],
-    ) -> Vec<f32>
+        ema: bool,
+        staleness: bool,
+    ) -> Result<(Vec<f32>, Option<Vec<f32>>)>
     where
         T: TransportLayer + 'static,
     {
         match algorithm {
             AlgorithmConfig::ParameterServer { .. } => {
-                Self::finalize_parameter_server(server_handles, layer_offsets, user_event_tx).await
+                Self::finalize_parameter_server(
+                    server_handles,
+                    layer_offsets,
+                    user_event_tx,
+                    ema,
+                    staleness,
+                )
+                .await
             }
             AlgorithmConfig::AllReduce => {
-                Self::finalize_all_reduce(req_txs, event_rx, user_event_tx).await
+                let params = Self::finalize_all_reduce(req_txs, event_rx, user_event_tx).await;
+                Ok((params, None))
             }
             AlgorithmConfig::StrategySwitch { .. } if server_handles.is_empty() => {
-                Self::finalize_all_reduce(req_txs, event_rx, user_event_tx).await
+                let params = Self::finalize_all_reduce(req_txs, event_rx, user_event_tx).await;
+                Ok((params, None))
             }
             AlgorithmConfig::StrategySwitch { .. } => {
-                Self::finalize_parameter_server(server_handles, layer_offsets, user_event_tx).await
+                Self::finalize_parameter_server(
+                    server_handles,
+                    layer_offsets,
+                    user_event_tx,
+                    ema,
+                    staleness,
+                )
+                .await
             }
         }
     }
@@ -253,24 +617,69 @@ impl Session {
     /// # Args
     /// * `worker_handles` - The handles for communicating with the workers.
     /// * `event_tx` - The listener's response sender.
+    /// * `allow_worker_loss` - Whether a lost worker connection is tolerated.
     ///
     /// # Returns
-    /// A list of senders to make requests to the listeners.
+    /// A list of senders to make requests to the listeners along with their tasks.
     fn spawn_worker_listeners(
         worker_handles: Vec<WorkerHandle<NetRtp>>,
         event_tx: &Sender<TrainingEvent>,
-    ) -> Vec<Sender<WorkerRequest>> {
+        allow_worker_loss: bool,
+    ) -> (Vec<Sender<WorkerRequest>>, Vec<WorkerListenerTask>) {
         let mut req_txs = Vec::with_capacity(worker_handles.len());
+        let mut listeners = Vec::with_capacity(worker_handles.len());
 
         for (i, worker_handle) in worker_handles.into_iter().enumerate() {
             let (req_tx, req_rx) = mpsc::channel(256);
             req_txs.push(req_tx);
 
-            let worker_listener = WorkerListener::new(i, worker_handle);
-            tokio::spawn(worker_listener.listen(req_rx, event_tx.clone()));
+            let worker_listener =
+                WorkerListener::new(i, worker_handle).with_worker_loss(allow_worker_loss);
+            listeners.push(tokio::spawn(
+                worker_listener.listen(req_rx, event_tx.clone()),
+            ));
         }
 
-        req_txs
+        (req_txs, listeners)
+    }
+
+    /// Collects the connections of the nodes that went back to idle once the session is over.
+    ///
+    /// # Args
+    /// * `listeners` - The tasks of the worker listeners.
+    /// * `server_handles` - The handles of the servers that were disconnected cleanly.
+    /// * `node_addrs` - The network address of each of the session's nodes keyed by their id.
+    ///
+    /// # Returns
+    /// The handles of the idle nodes keyed by their address.
+    async fn release_nodes(
+        listeners: Vec<WorkerListenerTask>,
+        server_handles: Vec<ParamServerHandle<NetRtp>>,
+        node_addrs: &HashMap<Uuid, String>,
+    ) -> Vec<(String, NodeHandle<NetRtp>)> {
+        let mut node_handles: Vec<_> = server_handles
+            .into_iter()
+            .map(ParamServerHandle::into_node_handle)
+            .collect();
+
+        for mut listener in listeners {
+            match time::timeout(RELEASE_TIMEOUT, &mut listener).await {
+                Ok(Ok(Some(worker_handle))) => node_handles.push(worker_handle.into_node_handle()),
+                Ok(_) => {}
+                Err(_) => {
+                    warn!("a worker's listener didn't finish in time, dropping it's connection");
+                    listener.abort();
+                }
+            }
+        }
+
+        node_handles
+            .into_iter()
+            .filter_map(|node_handle| {
+                let addr = node_addrs.get(&node_handle.id())?;
+                Some((addr.clone(), node_handle))
+            })
+            .collect()
     }
 
     /// Finalizes a training using parameter server. Retrieves the parameters from
@@ -283,32 +692,41 @@ impl Session {
     /// correct order.
     ///
     /// # Args
-    /// * `server_handles` - The handles for communicating with the servers.
+    /// * `server_handles` - The handles for communicating with the servers, only the ones
+    ///   of the servers disconnected cleanly are left.
     /// * `layer_offsets` - Per-layer locations: `(server_id, start, end)` within
     ///   each server's parameter buffer, indexed by layer index.
     /// * `user_event_tx` - The user sender for communicating if an error occurred.
+    /// * `ema` - Whether to also pull the moving average of the parameters, a server failing
+    ///   to send it is reported and left without one.
+    /// * `staleness` - Whether to also pull and notify the staleness of the applied gradients.
     ///
     /// # Returns
-    /// The trained parameters of the model in layer order.
+    /// The trained parameters of the model and their moving average in layer order, the latter
+    /// only if it was pulled from every server.
+    ///
+    /// # Errors
+    /// An `OrchErr::ServerError` if a server failed to send it's parameters.
     async fn finalize_parameter_server<T>(
-        server_handles: Vec<ParamServerHandle<T>>,
+        server_handles: &mut Vec<ParamServerHandle<T>>,
         layer_offsets: &[(Uuid, usize, usize)],
         user_event_tx: &Sender<TrainingEvent>,
-    ) -> Vec<f32>
+        ema: bool,
+        staleness: bool,
+    ) -> Result<(Vec<f32>, Option<Vec<f32>>)>
     where
         T: TransportLayer,
     {
         debug!("all workers done, reading final params from all servers");
         let mut server_params = HashMap::with_capacity(server_handles.len());
+        let mut server_emas = HashMap::new();
 
-        let req_err = async |i, e| {
+        let server_err = |i, e| {
             let details = format!("unexpected error from server {i}: {e}");
-            let err = OrchErr::ServerError(details);
-            let event = TrainingEvent::Error(err);
-            let _ = user_event_tx.send(event).await;
+            OrchErr::ServerError(details)
         };
 
-        for (i, mut server_handle) in server_handles.into_iter().enumerate() {
+        for (i, mut server_handle) in mem::take(server_handles).into_iter().enumerate() {
             // TODO: Acá eventualmente hay que ver como manejamos las caídas de
             //       los servidores. Capaz conviene tener a mano al Acceptor y
             //       en caso de que un servidor no responda que se vuelva a
@@ -320,28 +738,99 @@ impl Session {
             //       es quién.
             let server_id = server_handle.id();
 
-            loop {
-                if let Err(e) = server_handle.req_params().await {
-                    req_err(i, e).await;
-                    continue;
-                }
-
-                match server_handle.pull_params().await {
-                    Ok(params) => {
-                        debug!("server {i}: pulled {} params", params.len());
-                        server_params.insert(server_id, params.to_vec());
+            let params = Self::pull_server_params(&mut server_handle, false)
+                .await
+                .map_err(|e| server_err(i, e))?;
 
-                        if let Err(e) = server_handle.disconnect().await {
-                            error!("Failed to disconnect server {i}: {e}");
-                        }
+            debug!("server {i}: pulled {} params", params.len());
+            server_params.insert(server_id, params);
 
-                        break;
+            if ema {
+                match Self::pull_server_params(&mut server_handle, true).await {
+                    Ok(ema_params) => {
+                        server_emas.insert(server_id, ema_params);
+                    }
+                    Err(e) => {
+                        let event = TrainingEvent::Error(server_err(i, e));
+                        let _ = user_event_tx.send(event).await;
                     }
-                    Err(e) => req_err(i, e).await,
                 }
             }
+
+            if staleness {
+                let stats = match server_handle.req_staleness().await {
+                    Ok(()) => server_handle.pull_staleness().await,
+                    Err(e) => Err(e),
+                };
+
+                let event = match stats {
+                    Ok(stats) => TrainingEvent::Staleness { server: i, stats },
+                    Err(e) => TrainingEvent::Error(server_err(i, e)),
+                };
+
+                let _ = user_event_tx.send(event).await;
+            }
+
+            match server_handle.disconnect().await {
+                Ok(()) => server_handles.push(server_handle),
+                Err(e) => error!("Failed to disconnect server {i}: {e}"),
+            }
+        }
+
+        let model_params = Self::assemble_params(layer_offsets, &server_params);
+        let pulled = layer_offsets
+            .iter()
+            .all(|(server_id, ..)| server_emas.contains_key(server_id));
+
+        if ema && !pulled {
+            warn!("the moving average of some servers' params is missing, leaving it out");
         }
 
+        let model_emas =
+            (ema && pulled).then(|| Self::assemble_params(layer_offsets, &server_emas));
+        Ok((model_params, model_emas))
+    }
+
+    /// Pulls a server's parameters once, the transport already retries a dropped connection.
+    ///
+    /// # Args
+    /// * `server_handle` - The handle for communicating with the server.
+    /// * `ema` - Whether to pull the moving average of the parameters instead.
+    ///
+    /// # Returns
+    /// The server's parameters or an io error if occurred.
+    async fn pull_server_params<T>(
+        server_handle: &mut ParamServerHandle<T>,
+        ema: bool,
+    ) -> io::Result<Vec<f32>>
+    where
+        T: TransportLayer,
+    {
+        if ema {
+            server_handle.req_ema_params().await?;
+        } else {
+            server_handle.req_params().await?;
+        }
+
+        server_handle
+            .pull_params()
+            .await
+            .map(|params| params.to_vec())
+    }
+
+    /// Concatenates the layers held by the servers following the model's order.
+    ///
+    /// # Args
+    /// * `layer_offsets` - Per-layer locations: `(server_id, start, end)` within
+    ///   each server's parameter buffer, indexed by layer index.
+    /// * `server_params` - The parameter buffer of each server.
+    ///
+    /// # Returns
+    /// The parameters of the model in layer order.
+    fn assemble_params(
+        layer_offsets: &[(Uuid, usize, usize)],
+        server_params: &HashMap<Uuid, Vec<f32>>,
+    ) -> Vec<f32> {
         let total: usize = layer_offsets
             .iter()
             .map(|&(_, start, end)| end - start)
@@ -411,41 +900,59 @@ impl Session {
         params
     }
 
-    /// Connects to the server nodes and sends their specification.
+    /// Takes the handles of the given nodes, borrowing the ones that aren't given from the pool.
+    ///
+    /// # Args
+    /// * `node_handles` - The already connected node handles keyed by their address.
+    /// * `addrs` - The network addresses of the nodes.
+    /// * `pool` - The pool to borrow the missing node connections from.
+    ///
+    /// # Returns
+    /// The handles of the given nodes keyed by their address.
+    ///
+    /// # Errors
+    /// Returns an `OrchErr` if connecting to any of the missing nodes fails.
+    async fn borrow_node_handles(
+        node_handles: &mut BTreeMap<String, NodeHandle<NetRtp>>,
+        addrs: &[String],
+        pool: &mut OrchestratorPool,
+    ) -> Result<BTreeMap<String, NodeHandle<NetRtp>>> {
+        let mut borrowed = BTreeMap::new();
+        let mut missing = Vec::new();
+
+        for addr in addrs {
+            match node_handles.remove(addr) {
+                Some(node_handle) => {
+                    borrowed.insert(addr.clone(), node_handle);
+                }
+                None => missing.push(addr.clone()),
+            }
+        }
+
+        borrowed.extend(pool.checkout_all(&missing).await?);
+        Ok(borrowed)
+    }
+
+    /// Sends the specification to each of the server nodes.
     ///
     /// # Args
     /// * `servers` - The servers' network addresses and specifications.
-    /// * `connector` - The connector to create the network connections.
+    /// * `node_handles` - The connected node handles keyed by their address.
     ///
     /// # Returns
-    /// The worker handles or an orch error if occurred.
-    async fn create_servers<I, F>(
+    /// The server handles or an orch error if occurred.
+    async fn create_servers<I>(
         servers: I,
-        connector: &Connector<OwnedReadHalf, OwnedWriteHalf, NetRtp, F>,
+        mut node_handles: BTreeMap<String, NodeHandle<NetRtp>>,
     ) -> Result<Vec<ParamServerHandle<NetRtp>>>
     where
         I: IntoIterator<Item = ServerAdapt>,
-        F: Fn(OwnedReadHalf, OwnedWriteHalf) -> NetRtp,
     {
         let mut handles = Vec::new();
 
         for ServerAdapt { addr, spec } in servers {
-            debug!("connecting to server at {addr}");
-
-            let stream =
-                TcpStream::connect(&addr)
-                    .await
-                    .map_err(|e| OrchErr::ConnectionFailed {
-                        addr: addr.clone(),
-                        source: e,
-                    })?;
-
-            let (rx, tx) = stream.into_split();
-            let node_handle = connector
-                .connect_node(rx, tx, Entity::Orchestrator)
-                .await
-                .map_err(|e| OrchErr::ConnectionFailed { addr, source: e })?;
-
+            debug!("creating server at {addr}");
+            let node_handle = Self::take_node_handle(&mut node_handles, &addr)?;
             let server_handle = node_handle.create_server(spec).await?;
             handles.push(server_handle);
         }
@@ -453,56 +960,65 @@ impl Session {
         Ok(handles)
     }
 
-    /// Connects to the worker nodes and sends their specification and dataset partitions.
+    /// Sends the specification and dataset partition to each of the worker nodes.
     ///
     /// # Args
     /// * `workers` - The workers' network addresses, specifications and dataset partitions.
-    /// * `partitions` - The workers' dataset partitions.
-    /// * `connector` - The connector to create the network connections.
+    /// * `node_handles` - The connected node handles keyed by their address.
     ///
     /// # Returns
     /// The worker handles or an orch error if occurred.
-    async fn create_workers<'a, F, I>(
+    async fn create_workers<'a, I>(
         workers: I,
-        connector: &Connector<OwnedReadHalf, OwnedWriteHalf, NetRtp, F>,
+        mut node_handles: BTreeMap<String, NodeHandle<NetRtp>>,
     ) -> Result<Vec<WorkerHandle<NetRtp>>>
     where
-        F: Fn(OwnedReadHalf, OwnedWriteHalf) -> NetRtp,
         I: IntoIterator<Item = WorkerAdapt<'a>>,
     {
         const CHUNK_SIZE: usize = 8192;
 
-        let futs = workers.into_iter().map(|adapt| async move {
-            let WorkerAdapt {
-                addr,
-                spec,
-                partition,
-            } = adapt;
-
-            debug!("connecting to worker at {addr}");
-
-            let stream =
-                TcpStream::connect(&addr)
-                    .await
-                    .map_err(|e| OrchErr::ConnectionFailed {
-                        addr: addr.clone(),
-                        source: e,
-                    })?;
-
-            let (rx, tx) = stream.into_split();
-            let node_handle = connector
-                .connect_node(rx, tx, Entity::Orchestrator)
-                .await
-                .map_err(|e| OrchErr::ConnectionFailed { addr, source: e })?;
+        let mut futs = Vec::new();
 
-            let mut worker_handle = node_handle.create_worker(spec).await?;
-            Self::send_partition(&mut worker_handle, partition, CHUNK_SIZE).await?;
-            Ok::<_, OrchErr>(worker_handle)
-        });
+        for adapt in workers {
+            let node_handle = Self::take_node_handle(&mut node_handles, &adapt.addr)?;
+
+            futs.push(async move {
+                let WorkerAdapt {
+                    addr,
+                    spec,
+                    partition,
+                } = adapt;
+
+                debug!("creating worker at {addr}");
+                let mut worker_handle = node_handle.create_worker(spec).await?;
+                Self::send_partition(&mut worker_handle, partition, CHUNK_SIZE).await?;
+                Ok::<_, OrchErr>(worker_handle)
+            });
+        }
 
         future::try_join_all(futs).await
     }
 
+    /// Takes the node handle for the given address out of the connected handles.
+    ///
+    /// # Args
+    /// * `node_handles` - The connected node handles keyed by their address.
+    /// * `addr` - The network address of the node.
+    ///
+    /// # Returns
+    /// The node's handle or an orch error if there's no connection to it.
+    fn take_node_handle(
+        node_handles: &mut BTreeMap<String, NodeHandle<NetRtp>>,
+        addr: &str,
+    ) -> Result<NodeHandle<NetRtp>> {
+        node_handles
+            .remove(addr)
+            .ok_or_else(|| OrchErr::ConnectionFailed {
+                addr: addr.to_string(),
+                source: io::Error::from(io::ErrorKind::NotConnected),
+            })
+    }
+
     /// Sends a dataset partition to a worker.
     ///
     /// # Args
@@ -541,6 +1057,19 @@ impl Session {
             Partition::Inline { samples, labels } => {
                 Self::send_inline_partition(worker_handle, samples, labels, chunk_size).await
             }
+            // The worker generates the rows itself out of it's spec.
+            Partition::Synthetic(_) => Ok(()),
+            Partition::Csr {
+                indptr,
+                indices,
+                values,
+                labels,
+            } => {
+                let samples = CsrMatrix::encode_rows(indptr, indices, values)
+                    .map_err(|e| OrchErr::InvalidConfig(format!("invalid csr dataset: {e}")))?;
+
+                Self::send_inline_partition(worker_handle, &samples, labels, chunk_size).await
+            }
         }
     }
 
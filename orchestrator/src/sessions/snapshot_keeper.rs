@@ -0,0 +1,127 @@
+use std::{collections::HashMap, num::NonZeroUsize};
+
+use log::warn;
+use uuid::Uuid;
+
+use crate::OrchErr;
+
+/// Keeps the parameters of the last snapshot pulled from the servers during the training,
+/// so a session failing late can still hand out a mostly trained model.
+#[derive(Debug)]
+pub struct SnapshotKeeper {
+    every: NonZeroUsize,
+    next_epoch: usize,
+    layer_offsets: Vec<(Uuid, usize, usize)>,
+    params: Option<Vec<f32>>,
+}
+
+impl SnapshotKeeper {
+    /// Creates a new `SnapshotKeeper`.
+    ///
+    /// # Args
+    /// * `every` - The amount of epochs in between two snapshots.
+    /// * `layer_offsets` - Per-layer locations: `(server_id, start, end)` within
+    ///   each server's parameter buffer, indexed by layer index.
+    ///
+    /// # Returns
+    /// A new `SnapshotKeeper` instance.
+    pub fn new(every: NonZeroUsize, layer_offsets: Vec<(Uuid, usize, usize)>) -> Self {
+        Self {
+            every,
+            next_epoch: every.get(),
+            layer_offsets,
+            params: None,
+        }
+    }
+
+    /// Checks whether a snapshot is due, scheduling the next one if it is.
+    ///
+    /// # Args
+    /// * `epochs_run` - The amount of epochs run so far.
+    ///
+    /// # Returns
+    /// Whether the servers should be snapshotted.
+    pub fn due(&mut self, epochs_run: usize) -> bool {
+        if epochs_run < self.next_epoch {
+            return false;
+        }
+
+        self.next_epoch = epochs_run + self.every.get();
+        true
+    }
+
+    /// Keeps the parameters pulled from the servers in layer order, replacing the last ones.
+    ///
+    /// The snapshot is dropped if a layer's server wasn't pulled, such as the ones a
+    /// strategy switch didn't upgrade yet.
+    ///
+    /// # Args
+    /// * `server_params` - The parameter buffer of each server.
+    pub fn keep(&mut self, server_params: &HashMap<Uuid, Vec<f32>>) {
+        let mut params = Vec::new();
+
+        for &(server_id, start, end) in &self.layer_offsets {
+            let Some(server_params) = server_params.get(&server_id) else {
+                return;
+            };
+
+            params.extend_from_slice(&server_params[start..end]);
+        }
+
+        self.params = Some(params);
+    }
+
+    /// Takes the parameters of the last snapshot.
+    ///
+    /// # Returns
+    /// The parameters or `None` if no snapshot was kept.
+    pub fn take(&mut self) -> Option<Vec<f32>> {
+        self.params.take()
+    }
+
+    /// Attaches the parameters of the last snapshot to the error the training failed with.
+    ///
+    /// # Args
+    /// * `cause` - The error the training failed with.
+    ///
+    /// # Returns
+    /// An `OrchErr::FailedWithPartial` or the cause itself if no snapshot was kept.
+    pub fn attach(&mut self, cause: OrchErr) -> OrchErr {
+        let Some(params) = self.take() else {
+            return cause;
+        };
+
+        warn!("the training failed, keeping the parameters of the last snapshot");
+        OrchErr::FailedWithPartial {
+            cause: Box::new(cause),
+            params,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_a_snapshot_is_due_every_that_many_epochs() {
+        let every = NonZeroUsize::new(3).unwrap();
+        let mut keeper = SnapshotKeeper::new(every, Vec::new());
+
+        let due: Vec<_> = (1..=10).filter(|&epoch| keeper.due(epoch)).collect();
+        assert_eq!(due, [3, 6, 9]);
+    }
+
+    #[test]
+    fn test_the_layers_are_kept_in_the_model_order() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let layer_offsets = vec![(b, 0, 2), (a, 0, 1), (b, 2, 3)];
+        let mut keeper = SnapshotKeeper::new(NonZeroUsize::MIN, layer_offsets);
+
+        keeper.keep(&HashMap::from([(a, vec![0.0])]));
+        assert_eq!(keeper.take(), None);
+
+        keeper.keep(&HashMap::from([(a, vec![0.0]), (b, vec![1.0, 2.0, 3.0])]));
+        assert_eq!(keeper.take(), Some(vec![1.0, 2.0, 0.0, 3.0]));
+    }
+}
@@ -0,0 +1,458 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use comms::specs::machine_learning::{LayerSpec, TrainerSpec};
+use machine_learning::{
+    Result,
+    datasets::CsrMatrix,
+    param_manager::{ParamManager, ParamsMetadata},
+    rng::{self, RngPurpose},
+    training::TrainerBuilder,
+};
+use ndarray::ArrayView2;
+use rand::seq::SliceRandom;
+
+/// The samples and labels of a set of rows of the dataset.
+pub type Rows = (Vec<f32>, Vec<f32>);
+
+/// Splits the rows of a dataset between the training and the test set.
+///
+/// The rows are drawn from the `test_split` rng substream of the seed, so the same seed
+/// always holds out the same rows. Both sets keep the rows in their original order.
+///
+/// # Args
+/// * `samples` - The dataset's samples.
+/// * `labels` - The dataset's labels.
+/// * `x_size` - The size of a sample.
+/// * `y_size` - The size of a label.
+/// * `test_split` - The fraction of the rows to hold out.
+/// * `seed` - The seed of the run.
+///
+/// # Returns
+/// The rows to train on and the held out ones.
+pub fn hold_out(
+    samples: &[f32],
+    labels: &[f32],
+    x_size: usize,
+    y_size: usize,
+    test_split: f32,
+    seed: Option<u64>,
+) -> (Rows, Rows) {
+    let nrows = samples.len() / x_size;
+    let mut train = (Vec::new(), Vec::new());
+    let mut test = (Vec::new(), Vec::new());
+
+    for (row, held_out) in held_out_rows(nrows, test_split, seed)
+        .into_iter()
+        .enumerate()
+    {
+        let (samples_dst, labels_dst) = if held_out { &mut test } else { &mut train };
+        samples_dst.extend_from_slice(&samples[row * x_size..(row + 1) * x_size]);
+        labels_dst.extend_from_slice(&labels[row * y_size..(row + 1) * y_size]);
+    }
+
+    (train, test)
+}
+
+/// Splits the rows of a dataset whose samples are the rows of a sparse matrix.
+///
+/// Holds out the same rows `hold_out` would for the same seed, the held out samples are
+/// expanded to test the model on them while the rest of them stay sparse.
+///
+/// # Args
+/// * `samples` - The dataset's samples, one per row of the matrix.
+/// * `labels` - The dataset's labels.
+/// * `y_size` - The size of a label.
+/// * `test_split` - The fraction of the rows to hold out.
+/// * `seed` - The seed of the run.
+///
+/// # Returns
+/// The sparse samples to train on along with their labels and the held out rows.
+pub fn hold_out_csr(
+    samples: &CsrMatrix,
+    labels: &[f32],
+    y_size: usize,
+    test_split: f32,
+    seed: Option<u64>,
+) -> ((CsrMatrix, Vec<f32>), Rows) {
+    let (mut train, mut test) = (Vec::new(), Vec::new());
+
+    for (row, held_out) in held_out_rows(samples.nrows(), test_split, seed)
+        .into_iter()
+        .enumerate()
+    {
+        let rows = if held_out { &mut test } else { &mut train };
+        rows.push(row);
+    }
+
+    let labels_of = |rows: &[usize]| -> Vec<f32> {
+        rows.iter()
+            .flat_map(|&row| &labels[row * y_size..(row + 1) * y_size])
+            .copied()
+            .collect()
+    };
+
+    let (test_samples, _) = samples.select(&test).to_dense().into_raw_vec_and_offset();
+    let train = (samples.select(&train), labels_of(&train));
+
+    (train, (test_samples, labels_of(&test)))
+}
+
+/// Splits the rows of a dataset stored in files of packed little endian `f32` values,
+/// streaming them.
+///
+/// Holds out the same rows `hold_out` would for the same seed, but only the held out ones
+/// are kept in memory. The rows to train on are copied to the given files as they're read.
+///
+/// # Args
+/// * `samples_path` - The path of the dataset's samples.
+/// * `labels_path` - The path of the dataset's labels.
+/// * `x_size` - The size of a sample.
+/// * `y_size` - The size of a label.
+/// * `test_split` - The fraction of the rows to hold out.
+/// * `seed` - The seed of the run.
+/// * `train_paths` - The paths to write the samples and labels to train on to.
+///
+/// # Returns
+/// The held out rows.
+///
+/// # Errors
+/// Returns an `io::Error` if any of the files can't be read or written or the files
+/// don't hold the same amount of whole rows.
+pub fn hold_out_files(
+    samples_path: &Path,
+    labels_path: &Path,
+    x_size: usize,
+    y_size: usize,
+    test_split: f32,
+    seed: Option<u64>,
+    train_paths: (&Path, &Path),
+) -> io::Result<Rows> {
+    let (x_bytes, y_bytes) = (x_size * size_of::<f32>(), y_size * size_of::<f32>());
+    let samples_len = fs::metadata(samples_path)?.len() as usize;
+    let labels_len = fs::metadata(labels_path)?.len() as usize;
+    let nrows = samples_len / x_bytes;
+
+    if !samples_len.is_multiple_of(x_bytes) || labels_len != nrows * y_bytes {
+        let text = format!(
+            "the dataset's {samples_len} bytes of samples and {labels_len} bytes of labels aren't whole rows"
+        );
+        return Err(io::Error::new(io::ErrorKind::InvalidData, text));
+    }
+
+    let mut samples = BufReader::new(File::open(samples_path)?);
+    let mut labels = BufReader::new(File::open(labels_path)?);
+    let mut train_samples = BufWriter::new(File::create(train_paths.0)?);
+    let mut train_labels = BufWriter::new(File::create(train_paths.1)?);
+
+    let (mut x, mut y) = (vec![0; x_bytes], vec![0; y_bytes]);
+    let mut test = (Vec::new(), Vec::new());
+
+    for held_out in held_out_rows(nrows, test_split, seed) {
+        samples.read_exact(&mut x)?;
+        labels.read_exact(&mut y)?;
+
+        if held_out {
+            test.0.extend(floats(&x));
+            test.1.extend(floats(&y));
+        } else {
+            train_samples.write_all(&x)?;
+            train_labels.write_all(&y)?;
+        }
+    }
+
+    train_samples.flush()?;
+    train_labels.flush()?;
+    Ok(test)
+}
+
+/// Reads packed little endian `f32` values.
+///
+/// # Args
+/// * `bytes` - The packed values.
+///
+/// # Returns
+/// An iterator over the values.
+fn floats(bytes: &[u8]) -> impl Iterator<Item = f32> {
+    bytes
+        .chunks_exact(size_of::<f32>())
+        .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// The amount of rows of a dataset to hold out.
+///
+/// # Args
+/// * `nrows` - The amount of rows in the dataset.
+/// * `test_split` - The fraction of the rows to hold out.
+///
+/// # Returns
+/// The amount of held out rows, at least one row is always left to train on.
+pub fn held_out_count(nrows: usize, test_split: f32) -> usize {
+    ((nrows as f32 * test_split).round() as usize).min(nrows.saturating_sub(1))
+}
+
+/// Draws the rows of a dataset to hold out from the `test_split` rng substream of the seed.
+///
+/// # Args
+/// * `nrows` - The amount of rows in the dataset.
+/// * `test_split` - The fraction of the rows to hold out.
+/// * `seed` - The seed of the run.
+///
+/// # Returns
+/// Whether each of the rows is held out, at least one row is always left to train on.
+fn held_out_rows(nrows: usize, test_split: f32, seed: Option<u64>) -> Vec<bool> {
+    let ntest = held_out_count(nrows, test_split);
+
+    let mut rows: Vec<_> = (0..nrows).collect();
+    rows.shuffle(&mut rng::rng_for(seed, RngPurpose::TestSplit));
+
+    let mut held_out = vec![false; nrows];
+    for &row in &rows[..ntest] {
+        held_out[row] = true;
+    }
+
+    held_out
+}
+
+/// The rows of the dataset held out of the training, the model is tested on them
+/// once the training stops.
+#[derive(Debug)]
+pub struct TestSet {
+    samples: Vec<f32>,
+    labels: Vec<f32>,
+    trainer: TrainerSpec,
+}
+
+impl TestSet {
+    /// Creates a new `TestSet`.
+    ///
+    /// # Args
+    /// * `samples` - The held out samples.
+    /// * `labels` - The held out labels.
+    /// * `trainer` - The workers' trainer, only it's model and loss are used.
+    ///
+    /// # Returns
+    /// A new `TestSet` instance.
+    pub fn new(samples: Vec<f32>, labels: Vec<f32>, trainer: TrainerSpec) -> Self {
+        Self {
+            samples,
+            labels,
+            trainer,
+        }
+    }
+
+    /// The amount of held out rows.
+    ///
+    /// # Returns
+    /// The amount of rows in the set.
+    pub fn len(&self) -> usize {
+        self.samples.len() / self.trainer.dataset.x_size.get()
+    }
+
+    /// Whether no row was held out.
+    ///
+    /// # Returns
+    /// `true` if the set has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Computes the loss of the trained model over the held out rows, forward only.
+    ///
+    /// Consumes the set, so the test loss is computed once and only once the training stops.
+    ///
+    /// # Args
+    /// * `params` - The trained parameters, in the model's order.
+    ///
+    /// # Returns
+    /// The mean loss over every held out row.
+    ///
+    /// # Errors
+    /// Returns an `MlErr` if the trainer can't be built or the forward pass fails.
+    pub fn loss(self, params: &[f32]) -> Result<f64> {
+        let Self {
+            samples,
+            labels,
+            trainer,
+        } = self;
+
+        let x_size = trainer.dataset.x_size.get();
+        let y_size = trainer.dataset.y_size.get();
+        let batch_size = trainer.batch_size.get();
+
+        let heads = trainer.heads.iter().flat_map(|head| &head.layers);
+        let weighted_layers = trainer
+            .layers
+            .iter()
+            .chain(heads)
+            .filter(|layer| !matches!(layer, LayerSpec::MaxPooling { .. }))
+            .count();
+
+        let ordering = vec![0; weighted_layers];
+        let mut trainer = TrainerBuilder::new().build(trainer, &[params.len()])?;
+
+        let mut params = params.to_vec();
+        let mut grad = vec![0.0; params.len()];
+        let mut residual = vec![0.0; params.len()];
+        let servers = vec![ParamsMetadata::new(&mut params, &mut grad, &mut residual)];
+        let mut param_manager = ParamManager::for_parameter_server(servers, &ordering);
+
+        let mut loss_sum = 0.0;
+        let batches = samples
+            .chunks(batch_size * x_size)
+            .zip(labels.chunks(batch_size * y_size));
+
+        for (x, y) in batches {
+            let nrows = x.len() / x_size;
+
+            // SAFETY: Every chunk holds whole rows.
+            let x = ArrayView2::from_shape((nrows, x_size), x).unwrap();
+            let y = ArrayView2::from_shape((nrows, y_size), y).unwrap();
+            loss_sum += trainer.evaluate(&mut param_manager, x.into(), y)? * nrows as f64;
+        }
+
+        Ok(loss_sum / (samples.len() / x_size) as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, env, num::NonZeroUsize, path::PathBuf};
+
+    use comms::{
+        floats::FloatPositive,
+        specs::machine_learning::{
+            DatasetSpec, FinalStepPolicy, LossFnSpec, OptimizerSpec, SamplerSpec, StopCondition,
+            StrategySpec,
+        },
+    };
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn rows(samples: &[f32]) -> HashSet<u32> {
+        samples.iter().map(|&sample| sample as u32).collect()
+    }
+
+    #[test]
+    fn test_the_held_out_rows_are_disjoint_and_deterministic() {
+        let samples: Vec<_> = (0..20).map(|i| i as f32).collect();
+        let labels: Vec<_> = samples.iter().flat_map(|&x| [x, -x]).collect();
+
+        let (train, test) = hold_out(&samples, &labels, 1, 2, 0.25, Some(7));
+        let (train_rows, test_rows) = (rows(&train.0), rows(&test.0));
+
+        assert_eq!(test_rows.len(), 5);
+        assert!(train_rows.is_disjoint(&test_rows));
+        assert_eq!(train_rows.len() + test_rows.len(), samples.len());
+
+        // The labels follow their samples to the set they were split into.
+        let labels_of = |rows: &Rows| rows.0.iter().flat_map(|&x| [x, -x]).collect::<Vec<_>>();
+        assert_eq!(train.1, labels_of(&train));
+        assert_eq!(test.1, labels_of(&test));
+
+        assert_eq!(
+            hold_out(&samples, &labels, 1, 2, 0.25, Some(7)),
+            (train, test)
+        );
+    }
+
+    #[test]
+    fn test_holding_out_files_matches_holding_out_in_memory() {
+        let samples: Vec<_> = (0..30).map(|i| i as f32).collect();
+        let labels: Vec<_> = (0..15).map(|i| -i as f32).collect();
+
+        let write = |values: &[f32]| {
+            let path = env::temp_dir().join(format!("{}.bin", Uuid::new_v4()));
+            fs::write(&path, bytemuck::cast_slice::<f32, u8>(values)).unwrap();
+            path
+        };
+
+        let read = |path: &PathBuf| {
+            let bytes = fs::read(path).unwrap();
+            fs::remove_file(path).unwrap();
+            floats(&bytes).collect::<Vec<_>>()
+        };
+
+        let (samples_path, labels_path) = (write(&samples), write(&labels));
+        let train_samples_path = env::temp_dir().join(format!("{}.bin", Uuid::new_v4()));
+        let train_labels_path = env::temp_dir().join(format!("{}.bin", Uuid::new_v4()));
+
+        let test = hold_out_files(
+            &samples_path,
+            &labels_path,
+            2,
+            1,
+            0.4,
+            Some(3),
+            (&train_samples_path, &train_labels_path),
+        );
+
+        let (_, _) = (read(&samples_path), read(&labels_path));
+        let train = (read(&train_samples_path), read(&train_labels_path));
+
+        let expected = hold_out(&samples, &labels, 2, 1, 0.4, Some(3));
+        assert_eq!((train, test.unwrap()), expected);
+    }
+
+    #[test]
+    fn test_holding_out_csr_rows_matches_holding_out_dense_ones() {
+        let samples: Vec<_> = (0..30)
+            .map(|i| (i % 3 == 0) as u8 as f32 * i as f32)
+            .collect();
+        let labels: Vec<_> = (0..10).map(|i| -i as f32).collect();
+        let csr = CsrMatrix::from_dense(&samples, 3).unwrap();
+
+        let ((train, train_labels), test) = hold_out_csr(&csr, &labels, 1, 0.3, Some(5));
+        let (train_samples, _) = train.to_dense().into_raw_vec_and_offset();
+
+        let expected = hold_out(&samples, &labels, 3, 1, 0.3, Some(5));
+        assert_eq!(((train_samples, train_labels), test), expected);
+    }
+
+    #[test]
+    fn test_the_test_loss_is_the_mean_over_every_held_out_row() {
+        let size = NonZeroUsize::MIN;
+        let trainer = TrainerSpec {
+            layers: vec![LayerSpec::Dense {
+                dim: (1, 1),
+                act_fn: None,
+                tie: None,
+                frozen: false,
+            }],
+            optimizer: OptimizerSpec::GradientDescent {
+                learning_rate: FloatPositive::new(0.1).unwrap(),
+            },
+            dataset: DatasetSpec {
+                x_size: size,
+                y_size: size,
+                sparse: false,
+            },
+            loss_fn: LossFnSpec::Mse,
+            offline_epochs: 0,
+            stop_condition: StopCondition::MaxEpochs(size),
+            final_step: FinalStepPolicy::ApplyPartial,
+            batch_size: NonZeroUsize::new(2).unwrap(),
+            sampler: SamplerSpec::Shuffle,
+            seed: Some(42),
+            augmentation_seed: None,
+            clip_groups: Vec::new(),
+            heads: Vec::new(),
+            checkpoint_every: None,
+            resume: None,
+            strategy: StrategySpec::Backprop,
+        };
+
+        // The model predicts `2x + 1`, off by 1, 0 and 2 on the three rows, so the last
+        // batch is shorter than the others and still weighs in by it's rows.
+        let test_set = TestSet::new(vec![0.0, 1.0, 2.0], vec![0.0, 3.0, 7.0], trainer);
+        assert_eq!(test_set.len(), 3);
+
+        let loss = test_set.loss(&[2.0, 1.0]).unwrap();
+        assert!((loss - 5.0 / 3.0).abs() < 1e-6, "{loss}");
+    }
+}
@@ -0,0 +1,79 @@
+use std::{collections::HashMap, num::NonZeroUsize, time::Duration};
+
+use comms::specs::worker::TimeBreakdown;
+
+/// Records how the workers spent their training rounds.
+#[derive(Debug, Default)]
+pub struct TimeRecorder {
+    breakdowns: HashMap<usize, TimeBreakdown>,
+}
+
+impl TimeRecorder {
+    /// Records the breakdown of a worker's latest round.
+    ///
+    /// # Args
+    /// * `worker_id` - The id of the worker whose round is being recorded.
+    /// * `breakdown` - The time split of the round.
+    pub fn record(&mut self, worker_id: usize, breakdown: TimeBreakdown) {
+        self.breakdowns.insert(worker_id, breakdown);
+    }
+
+    /// Gets the mean breakdown of all the workers.
+    ///
+    /// # Args
+    /// * `workers` - The amount of workers currently expected to report.
+    ///
+    /// # Returns
+    /// Either `Some(breakdown)` or `None` if not all workers have recorded their round.
+    pub fn mean(&self, workers: NonZeroUsize) -> Option<TimeBreakdown> {
+        if self.breakdowns.len() != workers.get() {
+            return None;
+        }
+
+        let n = workers.get() as u32;
+        let mean = |section: fn(&TimeBreakdown) -> Duration| {
+            self.breakdowns.values().map(section).sum::<Duration>() / n
+        };
+
+        Some(TimeBreakdown {
+            compute: mean(|b| b.compute),
+            comm: mean(|b| b.comm),
+            wait: mean(|b| b.wait),
+        })
+    }
+
+    /// Clears the inner state for the following recording.
+    pub fn clear(&mut self) {
+        self.breakdowns.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breakdown(compute: u64, comm: u64, wait: u64) -> TimeBreakdown {
+        TimeBreakdown {
+            compute: Duration::from_millis(compute),
+            comm: Duration::from_millis(comm),
+            wait: Duration::from_millis(wait),
+        }
+    }
+
+    #[test]
+    fn test_the_mean_waits_for_every_worker() {
+        let workers = NonZeroUsize::new(2).unwrap();
+        let mut recorder = TimeRecorder::default();
+
+        recorder.record(0, breakdown(10, 4, 0));
+        assert_eq!(recorder.mean(workers), None);
+
+        // A worker reporting twice only keeps it's latest round.
+        recorder.record(0, breakdown(30, 2, 6));
+        recorder.record(1, breakdown(10, 4, 2));
+        assert_eq!(recorder.mean(workers), Some(breakdown(20, 3, 4)));
+
+        recorder.clear();
+        assert_eq!(recorder.mean(workers), None);
+    }
+}
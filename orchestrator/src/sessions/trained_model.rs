@@ -1,13 +1,25 @@
-use std::path::Path;
+use std::{fs, io, num::NonZeroUsize, path::Path};
 
+use comms::specs::machine_learning::LayerSpec;
 use log::info;
+use machine_learning::arch::{BUNDLE_MODEL_FILE, BUNDLE_WEIGHTS_FILE, layers::build_custom_layer};
 use safetensors::tensor;
+use serde::Serialize;
 
 use crate::{
-    Result,
-    configs::{LayerConfig, ModelConfig},
+    OrchErr, Result,
+    configs::{Adapter, LayerConfig, ModelConfig},
 };
 
+/// The contents of a bundle's `model.json`.
+#[derive(Serialize)]
+struct BundleManifest<'a> {
+    input_size: usize,
+    /// The layers as the workers build them, for `Model::load_bundle` to read back.
+    layers: Vec<LayerSpec>,
+    model: &'a ModelConfig,
+}
+
 /// The result of a completed training session.
 ///
 /// Contains the trained model parameters alongside the model architecture,
@@ -15,6 +27,8 @@ use crate::{
 #[derive(Debug)]
 pub struct TrainedModel {
     pub params: Vec<f32>,
+    /// The exponential moving average of the parameters, if the servers kept one.
+    pub ema_params: Option<Vec<f32>>,
     pub model: ModelConfig,
     pub input_size: usize,
 }
@@ -31,11 +45,13 @@ impl TrainedModel {
     /// * `path` - The output file path (e.g. `"model.safetensors"`).
     ///
     /// # Errors
-    /// Returns an `OrchErr` if the file cannot be written or the parameter
-    /// buffer does not match the model architecture.
+    /// Returns an `OrchErr` if the file cannot be written, the parameter
+    /// buffer does not match the model architecture or the model has heads.
     pub fn save_safetensors(&self, path: impl AsRef<Path>) -> Result<()> {
         use safetensors::{Dtype, tensor::TensorView};
 
+        self.check_no_heads()?;
+
         let params_bytes: &[u8] = bytemuck::cast_slice(&self.params);
         let mut tensors: Vec<(String, TensorView)> = Vec::new();
         let mut offset = 0;
@@ -100,6 +116,16 @@ impl TrainedModel {
 
                     (w_count, b_count, vec![0], vec![0], out)
                 }
+                LayerConfig::Custom {
+                    name,
+                    output_size,
+                    params,
+                    ..
+                } => {
+                    // A custom layer's parameters are saved flat, as only it knows their layout.
+                    let w_count = build_custom_layer(name, params).map_or(0, |layer| layer.size());
+                    (w_count, 0, vec![w_count], vec![0], output_size.get())
+                }
             };
 
             let w_bytes = &params_bytes[offset * 4..(offset + w_count) * 4];
@@ -119,4 +145,139 @@ impl TrainedModel {
         info!("model saved to {}", path.as_ref().display());
         Ok(())
     }
+
+    /// Saves the trained model as a bundle, a directory holding a `model.json` with it's
+    /// architecture and a `weights.bin` with it's parameters as raw little endian `f32`s.
+    ///
+    /// The `model.json` holds the model's configuration under `"model"` and the layers the
+    /// workers build out of it under `"layers"`, which `Model::load_bundle` reads back.
+    ///
+    /// # Args
+    /// * `dir` - The bundle's directory, created if missing.
+    ///
+    /// # Errors
+    /// Returns an `OrchErr` if the files cannot be written, the model has no input or
+    /// it has heads.
+    pub fn save_bundle(&self, dir: impl AsRef<Path>) -> Result<()> {
+        self.check_no_heads()?;
+
+        let dir = dir.as_ref();
+        let input_size = NonZeroUsize::new(self.input_size)
+            .ok_or_else(|| OrchErr::InvalidConfig("the model has no input".into()))?;
+
+        let (layers, _) = Adapter::new().adapt_layers(&self.model, input_size);
+        let manifest = BundleManifest {
+            input_size: self.input_size,
+            layers,
+            model: &self.model,
+        };
+
+        let json = serde_json::to_vec_pretty(&manifest).map_err(io::Error::from)?;
+        let weights: Vec<u8> = self.params.iter().flat_map(|p| p.to_le_bytes()).collect();
+
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(BUNDLE_MODEL_FILE), json)?;
+        fs::write(dir.join(BUNDLE_WEIGHTS_FILE), weights)?;
+
+        info!("model bundle saved to {}", dir.display());
+        Ok(())
+    }
+
+    /// Checks the model doesn't branch into heads, their layers aren't saved yet.
+    ///
+    /// # Errors
+    /// Returns an `OrchErr` if the model has heads.
+    fn check_no_heads(&self) -> Result<()> {
+        if !self.model.heads.is_empty() {
+            let text = "the models with heads can't be saved yet";
+            return Err(OrchErr::InvalidConfig(text.into()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, num::NonZeroUsize, path::PathBuf};
+
+    use machine_learning::{MlErr, arch::Model};
+    use ndarray::ArrayD;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::configs::ParamGenConfig;
+
+    fn dense(output_size: usize) -> LayerConfig {
+        LayerConfig::Dense {
+            output_size: NonZeroUsize::new(output_size).unwrap(),
+            init: ParamGenConfig::Kaiming,
+            act_fn: None,
+            tie: None,
+            frozen: false,
+            init_gain: None,
+        }
+    }
+
+    /// Saves a trained model of two dense layers, `2 -> 3 -> 1`, as a bundle.
+    ///
+    /// # Returns
+    /// The bundle's directory and the saved parameters.
+    fn save_two_layers() -> (PathBuf, Vec<f32>) {
+        let params: Vec<_> = (0..(2 + 1) * 3 + (3 + 1)).map(|i| i as f32 / 10.).collect();
+        let trained = TrainedModel {
+            params: params.clone(),
+            ema_params: None,
+            model: ModelConfig {
+                layers: vec![dense(3), dense(1)],
+                heads: Vec::new(),
+                checkpoint_every: None,
+                activation_clamp: None,
+                warm_start: None,
+            },
+            input_size: 2,
+        };
+
+        let dir = env::temp_dir().join(format!("bundle-{}", Uuid::new_v4()));
+        trained.save_bundle(&dir).unwrap();
+        (dir, params)
+    }
+
+    #[test]
+    fn test_a_saved_bundle_loads_back_for_inference() {
+        let (dir, params) = save_two_layers();
+        let mut model = Model::load_bundle(&dir).unwrap();
+
+        let json = fs::read_to_string(dir.join(BUNDLE_MODEL_FILE)).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+
+        let manifest: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(manifest["input_size"], 2);
+        assert_eq!(manifest["model"]["layers"].as_array().unwrap().len(), 2);
+        assert_eq!(model.params(), params.as_slice());
+
+        let x = ArrayD::zeros(vec![1, 2]);
+        let y = model.predict(x.view()).unwrap();
+        assert_eq!(y.shape(), &[1, 1]);
+    }
+
+    #[test]
+    fn test_a_bundle_with_missing_weights_is_rejected() {
+        let (dir, params) = save_two_layers();
+
+        let truncated: Vec<u8> = params[1..].iter().flat_map(|p| p.to_le_bytes()).collect();
+        fs::write(dir.join(BUNDLE_WEIGHTS_FILE), truncated).unwrap();
+
+        let err = Model::load_bundle(&dir).err().unwrap();
+        fs::remove_dir_all(dir).unwrap();
+
+        assert!(matches!(
+            err,
+            MlErr::SizeMismatch {
+                got: 12,
+                expected: 13,
+                ..
+            }
+        ));
+    }
 }
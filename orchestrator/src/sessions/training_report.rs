@@ -0,0 +1,208 @@
+use std::{path::Path, time::Duration};
+
+use crate::{ConfusionMatrix, ParamsPreview, Result, StopReason, TrainedModel};
+
+/// The summary of a finished training session.
+#[derive(Debug)]
+pub struct TrainingReport {
+    model: TrainedModel,
+    stop_reason: StopReason,
+    final_loss: Option<f64>,
+    test_loss: Option<f64>,
+    epochs_run: usize,
+    total_samples: u64,
+    wall_time: Duration,
+    confusion_matrix: Option<ConfusionMatrix>,
+    bytes_per_epoch: Vec<(u64, u64)>,
+}
+
+impl TrainingReport {
+    /// Creates a new `TrainingReport`.
+    ///
+    /// # Args
+    /// * `model` - The trained model.
+    /// * `stop_reason` - Why the training ended.
+    /// * `final_loss` - The mean of the last loss of each worker, if any.
+    /// * `test_loss` - The loss over the held out test rows, if any.
+    /// * `epochs_run` - The amount of epochs run by the furthest worker.
+    /// * `total_samples` - The amount of samples processed by all the workers.
+    /// * `wall_time` - The time from the start of the training until the parameters were received.
+    ///
+    /// # Returns
+    /// A new `TrainingReport` instance.
+    pub fn new(
+        model: TrainedModel,
+        stop_reason: StopReason,
+        final_loss: Option<f64>,
+        test_loss: Option<f64>,
+        epochs_run: usize,
+        total_samples: u64,
+        wall_time: Duration,
+    ) -> Self {
+        Self {
+            model,
+            stop_reason,
+            final_loss,
+            test_loss,
+            epochs_run,
+            total_samples,
+            wall_time,
+            confusion_matrix: None,
+            bytes_per_epoch: Vec::new(),
+        }
+    }
+
+    /// Sets the classifications of the trained model over the training rows.
+    ///
+    /// # Args
+    /// * `confusion_matrix` - The confusion matrix merged across the workers.
+    ///
+    /// # Returns
+    /// The modified `TrainingReport`.
+    pub fn with_confusion_matrix(mut self, confusion_matrix: ConfusionMatrix) -> Self {
+        self.confusion_matrix = Some(confusion_matrix);
+        self
+    }
+
+    /// Sets the bytes the workers exchanged with their peers during each epoch.
+    ///
+    /// # Args
+    /// * `bytes_per_epoch` - The bytes sent and received, summed across the workers.
+    ///
+    /// # Returns
+    /// The modified `TrainingReport`.
+    pub fn with_bytes_per_epoch(mut self, bytes_per_epoch: Vec<(u64, u64)>) -> Self {
+        self.bytes_per_epoch = bytes_per_epoch;
+        self
+    }
+
+    /// The trained parameters of the model.
+    ///
+    /// # Returns
+    /// The parameters as a flat slice.
+    pub fn params(&self) -> &[f32] {
+        &self.model.params
+    }
+
+    /// A preview of the trained parameters, to display them without dumping the whole model.
+    ///
+    /// # Args
+    /// * `len` - The amount of parameters to display.
+    ///
+    /// # Returns
+    /// The first `len` parameters along with the amount of them.
+    pub fn params_preview(&self, len: usize) -> ParamsPreview<'_> {
+        ParamsPreview::new(&self.model.params, len)
+    }
+
+    /// The exponential moving average of the trained parameters.
+    ///
+    /// # Returns
+    /// The averaged parameters as a flat slice, or `None` if no `ema_decay` was set or a
+    /// server failed to send it's share of them.
+    pub fn ema_params(&self) -> Option<&[f32]> {
+        self.model.ema_params.as_deref()
+    }
+
+    /// The trained model.
+    ///
+    /// # Returns
+    /// A reference to the trained model.
+    pub fn model(&self) -> &TrainedModel {
+        &self.model
+    }
+
+    /// Saves the trained model as a bundle, see `TrainedModel::save_bundle`.
+    ///
+    /// # Args
+    /// * `dir` - The bundle's directory, created if missing.
+    ///
+    /// # Errors
+    /// Returns an `OrchErr` if the files cannot be written.
+    pub fn save_bundle(&self, dir: impl AsRef<Path>) -> Result<()> {
+        self.model.save_bundle(dir)
+    }
+
+    /// Consumes the report, keeping only the trained model.
+    ///
+    /// # Returns
+    /// The trained model.
+    pub fn into_model(self) -> TrainedModel {
+        self.model
+    }
+
+    /// Why the training ended.
+    ///
+    /// # Returns
+    /// The training's stop reason.
+    pub fn stop_reason(&self) -> StopReason {
+        self.stop_reason
+    }
+
+    /// Whether the training was stopped by early stopping.
+    ///
+    /// # Returns
+    /// `true` if the loss converged before reaching the max epochs.
+    pub fn stopped_early(&self) -> bool {
+        matches!(self.stop_reason, StopReason::EarlyStopping)
+    }
+
+    /// The mean of the last loss published by each worker.
+    ///
+    /// # Returns
+    /// The final loss or `None` if no worker published a finite loss.
+    pub fn final_loss(&self) -> Option<f64> {
+        self.final_loss
+    }
+
+    /// The loss of the trained model over the rows held out by the dataset's test split,
+    /// computed once the training stopped.
+    ///
+    /// # Returns
+    /// The test loss or `None` if no rows were held out.
+    pub fn test_loss(&self) -> Option<f64> {
+        self.test_loss
+    }
+
+    /// The amount of epochs run by the furthest worker.
+    ///
+    /// # Returns
+    /// The amount of epochs run.
+    pub fn epochs_run(&self) -> usize {
+        self.epochs_run
+    }
+
+    /// The amount of samples processed by all the workers.
+    ///
+    /// # Returns
+    /// The total amount of samples processed.
+    pub fn total_samples(&self) -> u64 {
+        self.total_samples
+    }
+
+    /// The time from the start of the training until the parameters were received.
+    ///
+    /// # Returns
+    /// The wall clock duration of the training.
+    pub fn wall_time(&self) -> Duration {
+        self.wall_time
+    }
+
+    /// The classifications of the trained model over the training rows, each worker running
+    /// it over it's partition once the training stopped.
+    ///
+    /// # Returns
+    /// The confusion matrix merged across the workers, `None` unless the model was trained
+    /// with cross entropy.
+    pub fn confusion_matrix(&self) -> Option<&ConfusionMatrix> {
+        self.confusion_matrix.as_ref()
+    }
+
+    /// The bytes the workers exchanged with the servers or their ring peers during each epoch.
+    ///
+    /// # Returns
+    /// The bytes sent and received summed across the workers, the first epoch at index 0.
+    pub fn bytes_per_epoch(&self) -> &[(u64, u64)] {
+        &self.bytes_per_epoch
+    }
+}
@@ -0,0 +1,117 @@
+use comms::specs::worker::MetricsReport;
+use log::warn;
+use worker::metrics::{ConfusionMatrix, RegressionMetrics};
+
+/// Merges the metrics of the final model the workers report over their partitions.
+#[derive(Debug, Default)]
+pub struct ValidationRecorder {
+    confusion_matrix: Option<ConfusionMatrix>,
+    regression_metrics: Option<RegressionMetrics>,
+}
+
+impl ValidationRecorder {
+    /// Records the validation metrics a report carries, if any.
+    ///
+    /// # Args
+    /// * `worker_id` - The id of the worker that sent the report.
+    /// * `report` - The report to record.
+    pub fn record(&mut self, worker_id: usize, report: &MetricsReport) {
+        if let Some(sums) = report.regression {
+            let regression_metrics = RegressionMetrics::from(sums);
+            self.regression_metrics
+                .get_or_insert_default()
+                .merge(&regression_metrics);
+        }
+
+        let Some(ref counts) = report.confusion else {
+            return;
+        };
+
+        let confusion_matrix = match ConfusionMatrix::try_from(counts.clone()) {
+            Ok(confusion_matrix) => confusion_matrix,
+            Err(e) => {
+                warn!("worker {worker_id} sent an invalid confusion matrix: {e}");
+                return;
+            }
+        };
+
+        let Some(ref mut merged) = self.confusion_matrix else {
+            self.confusion_matrix = Some(confusion_matrix);
+            return;
+        };
+
+        if let Err(e) = merged.merge(&confusion_matrix) {
+            warn!("failed to merge the confusion matrix of worker {worker_id}: {e}");
+        }
+    }
+
+    /// Takes the confusion matrix merged across the workers.
+    ///
+    /// # Returns
+    /// The merged confusion matrix, `None` if no worker reported one.
+    pub fn take_confusion_matrix(&mut self) -> Option<ConfusionMatrix> {
+        self.confusion_matrix.take()
+    }
+
+    /// Takes the regression metrics merged across the workers.
+    ///
+    /// # Returns
+    /// The merged regression metrics, `None` if no worker reported them.
+    pub fn take_regression_metrics(&mut self) -> Option<RegressionMetrics> {
+        self.regression_metrics.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use comms::specs::worker::TimeBreakdown;
+
+    use super::*;
+
+    fn report(predictions: &[f32], targets: &[f32]) -> MetricsReport {
+        let mut matrix = ConfusionMatrix::new(NonZeroUsize::new(2).unwrap());
+        matrix.update(predictions, targets).unwrap();
+
+        MetricsReport::new(3, Vec::new(), 0.0, TimeBreakdown::default())
+            .with_confusion(matrix.into())
+    }
+
+    #[test]
+    fn test_confusion_matrices_are_merged_across_workers() {
+        let mut recorder = ValidationRecorder::default();
+        recorder.record(0, &report(&[0.9, 0.1, 0.2, 0.8], &[1., 0., 1., 0.]));
+        recorder.record(1, &report(&[0.3, 0.7], &[0., 1.]));
+        recorder.record(2, &MetricsReport::default());
+
+        let merged = recorder.take_confusion_matrix().unwrap();
+        assert_eq!(merged.as_slice(), [1, 1, 0, 1]);
+        assert!(recorder.take_confusion_matrix().is_none());
+        assert!(recorder.take_regression_metrics().is_none());
+    }
+
+    #[test]
+    fn test_regression_metrics_are_merged_across_workers() {
+        let targets = [1.0, 2.0, 3.0, 4.0];
+        let predictions = [1.5, 2.0, 2.5, 4.0];
+        let mut recorder = ValidationRecorder::default();
+
+        for (predictions, targets) in predictions.chunks(2).zip(targets.chunks(2)) {
+            let mut metrics = RegressionMetrics::default();
+            metrics.update(predictions, targets);
+
+            let report = MetricsReport::new(3, Vec::new(), 0.0, TimeBreakdown::default())
+                .with_regression(metrics.into());
+            recorder.record(0, &report);
+        }
+
+        let mut whole = RegressionMetrics::default();
+        whole.update(&predictions, &targets);
+
+        let merged = recorder.take_regression_metrics().unwrap();
+        assert_eq!(merged.len(), 4);
+        assert!((merged.r_squared() - whole.r_squared()).abs() < 1e-12);
+        assert!(recorder.take_confusion_matrix().is_none());
+    }
+}
@@ -1,3 +1,5 @@
+use std::io;
+
 use comms::{NetRtp, WorkerEvent, WorkerHandle};
 use log::{debug, error, info, warn};
 use tokio::sync::mpsc::{Receiver, Sender};
@@ -13,7 +15,7 @@ enum ReqResolution {
 
 /// The return type of the `handle_event` method.
 enum EventResolution {
-    NotifyOrch(TrainingEvent),
+    NotifyOrch(Box<TrainingEvent>),
     Upgraded,
     Exit,
 }
@@ -23,6 +25,7 @@ pub struct WorkerListener {
     id: usize,
     worker_handle: WorkerHandle<NetRtp>,
     stopping: bool,
+    allow_loss: bool,
 }
 
 impl WorkerListener {
@@ -39,9 +42,23 @@ impl WorkerListener {
             id,
             worker_handle,
             stopping: false,
+            allow_loss: false,
         }
     }
 
+    /// Sets whether losing the connection with the worker should be treated as the
+    /// worker being done instead of failing the training.
+    ///
+    /// # Args
+    /// * `allow` - Whether to tolerate the worker's loss.
+    ///
+    /// # Returns
+    /// The modified `WorkerListener`.
+    pub fn with_worker_loss(mut self, allow: bool) -> Self {
+        self.allow_loss = allow;
+        self
+    }
+
     /// Starts the worker listener.
     ///
     /// # Args
@@ -49,12 +66,13 @@ impl WorkerListener {
     /// * `event_tx` - The training events sender.
     ///
     /// # Returns
-    /// An orch error if occurred.
+    /// The worker's handle if it's session ended in an orderly way, the node it ran on
+    /// is then idle on the same connection.
     pub async fn listen(
         mut self,
         mut req_rx: Receiver<WorkerRequest>,
         event_tx: Sender<TrainingEvent>,
-    ) {
+    ) -> Option<WorkerHandle<NetRtp>> {
         let id = self.id;
 
         loop {
@@ -66,7 +84,7 @@ impl WorkerListener {
 
                     match self.handle_request(req, &event_tx).await {
                         Ok(ReqResolution::Continue) => continue,
-                        Ok(ReqResolution::Halt) => {}
+                        Ok(ReqResolution::Halt) => return Some(self.worker_handle),
                         Err(e) => {
                             let _ = event_tx.send(TrainingEvent::Error(e)).await;
                         }
@@ -76,9 +94,9 @@ impl WorkerListener {
                 },
                 event = self.worker_handle.recv_event() => match event {
                     Ok(event) => match Self::handle_event(id, event) {
-                        Ok(EventResolution::Exit) => break,
+                        Ok(EventResolution::Exit) => return Some(self.worker_handle),
                         Ok(EventResolution::NotifyOrch(event)) => {
-                            let _ = event_tx.send(event).await;
+                            let _ = event_tx.send(*event).await;
                         }
                         Ok(EventResolution::Upgraded) => {
                             info!("upgraded worker {id}");
@@ -96,6 +114,11 @@ impl WorkerListener {
                             break;
                         }
                     }
+                    Err(e) if self.allow_loss && is_connection_loss(&e) => {
+                        warn!("lost connection with worker {id}, continuing without it: {e}");
+                        let _ = event_tx.send(TrainingEvent::WorkerDone(id)).await;
+                        break;
+                    }
                     Err(e) => {
                         error!("worker {id} error: {e}");
                         let err = OrchErr::WorkerError { id, details: e.to_string() };
@@ -105,6 +128,8 @@ impl WorkerListener {
                 }
             }
         }
+
+        None
     }
 
     /// Handles the orchestrator's requests for a worker.
@@ -210,20 +235,31 @@ impl WorkerListener {
     /// An `EventResolution` or an orch error if the received event is invalid.
     fn handle_event(id: usize, event: WorkerEvent<'_>) -> Result<EventResolution> {
         match event {
-            WorkerEvent::Loss(losses) => {
+            WorkerEvent::Loss(losses, timing) => {
                 debug!("worker {id} reported {} losses", losses.len());
 
                 let training_event = TrainingEvent::PublishedLosses {
                     worker_id: id,
                     losses,
+                    timing,
+                };
+
+                Ok(EventResolution::NotifyOrch(Box::new(training_event)))
+            }
+            WorkerEvent::Metrics(report) => {
+                debug!("worker {id} reported the metrics of epoch {}", report.epoch);
+
+                let training_event = TrainingEvent::Metrics {
+                    worker_id: id,
+                    report,
                 };
 
-                Ok(EventResolution::NotifyOrch(training_event))
+                Ok(EventResolution::NotifyOrch(Box::new(training_event)))
             }
             WorkerEvent::Done => {
                 info!("worker {id} done");
                 let training_event = TrainingEvent::WorkerDone(id);
-                Ok(EventResolution::NotifyOrch(training_event))
+                Ok(EventResolution::NotifyOrch(Box::new(training_event)))
             }
             WorkerEvent::Disconnect => {
                 info!("worker {id} disconnected");
@@ -233,6 +269,10 @@ impl WorkerListener {
                 info!("worker {id} upgraded");
                 Ok(EventResolution::Upgraded)
             }
+            WorkerEvent::Error(cause) => {
+                error!("worker {id} failed: {cause}");
+                Err(OrchErr::WorkerFailed { id, cause })
+            }
             _ => {
                 warn!("worker {id}: unexpected event {event:?}");
                 let details = format!("invalid event from worker {id}: {event:?}");
@@ -241,3 +281,65 @@ impl WorkerListener {
         }
     }
 }
+
+/// Whether the given error means the worker's connection was dropped.
+///
+/// # Args
+/// * `e` - The io error.
+///
+/// # Returns
+/// `true` if the connection was lost.
+fn is_connection_loss(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use comms::{NetStream, OrchHandle};
+    use tokio::{io, sync::mpsc};
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn transport(stream: NetStream) -> NetRtp {
+        let (rx, tx) = stream.into_split();
+        let timeout = Duration::from_secs(5);
+        comms::build_reliable_transport(rx, tx, timeout, timeout, Duration::from_millis(10), 2, 1)
+    }
+
+    #[tokio::test]
+    async fn test_a_reported_error_names_the_failed_worker_and_cause() {
+        let (worker_stream, orch_stream) = io::duplex(1 << 16);
+
+        let worker_handle =
+            WorkerHandle::new(Uuid::new_v4(), transport(NetStream::Local(orch_stream)));
+        let mut orch_handle =
+            OrchHandle::new(Uuid::new_v4(), transport(NetStream::Local(worker_stream)));
+
+        let (_req_tx, req_rx) = mpsc::channel(1);
+        let (event_tx, mut event_rx) = mpsc::channel(1);
+        let listening =
+            tokio::spawn(WorkerListener::new(3, worker_handle).listen(req_rx, event_tx));
+
+        let cause = "size mismatch for params in layer 1 during the forward pass";
+        orch_handle.report_error(cause).await.unwrap();
+
+        match event_rx.recv().await {
+            Some(TrainingEvent::Error(OrchErr::WorkerFailed { id, cause: got })) => {
+                assert_eq!(id, 3);
+                assert_eq!(got, cause);
+            }
+            other => panic!("expected the worker's failure, got {other:?}"),
+        }
+
+        listening.await.unwrap();
+    }
+}
@@ -0,0 +1,56 @@
+use orchestrator::{OrchErr, configs::*};
+
+const MODEL: &str = r#"{
+    "layers": [
+        { "dense": { "output_size": 1, "init": "kaiming" } }
+    ]
+}"#;
+
+const TRAINING: &str = r#"{
+    "addrs": ["node-0", "node-1"],
+    "algorithm": {
+        "parameter_server": {
+            "nservers": 1,
+            "synchronizer": "barrier",
+            "store": "blocking"
+        }
+    },
+    "dataset": {
+        "src": {
+            "inline": {
+                "samples": [1.0, 2.0, 3.0, 4.0],
+                "labels": [3.0, 5.0, 8.0, 11.0]
+            }
+        },
+        "x_size": 1,
+        "y_size": 1
+    },
+    "optimizer": { "gradient_descent": { "lr": 0.01 } },
+    "loss_fn": "mse",
+    "batch_size": 1,
+    "max_epochs": 5,
+    "offline_epochs": 0,
+    "seed": 42
+}"#;
+
+#[test]
+fn test_evaluate_local_computes_the_loss_over_every_shard() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+
+    // y = 2x + 1 predicts 3, 5, 7 and 9, off by 0, 0, 1 and 2 from the labels.
+    let report = orchestrator::evaluate_local(model, vec![2.0, 1.0], training).unwrap();
+
+    assert_eq!(report.total_samples(), 4);
+    assert_eq!(report.worker_losses().len(), 2);
+    assert!((report.loss().unwrap() - 5.0 / 4.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_evaluate_local_rejects_weights_that_dont_fit_the_model() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+
+    let err = orchestrator::evaluate_local(model, vec![2.0], training).unwrap_err();
+    assert!(matches!(err, OrchErr::InvalidConfig(_)), "{err}");
+}
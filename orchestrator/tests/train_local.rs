@@ -0,0 +1,848 @@
+use std::{
+    io::{BufRead, BufReader},
+    net,
+    num::NonZeroUsize,
+    ops::ControlFlow,
+    os::unix::net::UnixStream,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use comms::floats::FloatPositive;
+use machine_learning::datasets::SyntheticDataset;
+use orchestrator::{CancelHandle, OrchErr, StopReason, TrainingEvent, TrainingReport, configs::*};
+use serde_json::Value;
+use tokio::{
+    net::TcpListener,
+    runtime::Runtime,
+    sync::mpsc::{self, UnboundedSender},
+};
+
+const MODEL: &str = r#"{
+    "layers": [
+        { "dense": { "output_size": 4, "init": "kaiming", "act_fn": { "sigmoid": { "amp": 1.0 } } } },
+        { "dense": { "output_size": 1, "init": "kaiming" } }
+    ]
+}"#;
+
+const TRAINING: &str = r#"{
+    "addrs": ["node-0", "node-1", "node-2"],
+    "algorithm": {
+        "parameter_server": {
+            "nservers": 1,
+            "synchronizer": "barrier",
+            "store": "blocking"
+        }
+    },
+    "dataset": {
+        "src": {
+            "inline": {
+                "samples": [1.0, 2.0, 3.0, 4.0],
+                "labels": [2.0, 4.0, 6.0, 8.0]
+            }
+        },
+        "x_size": 1,
+        "y_size": 1
+    },
+    "optimizer": { "gradient_descent": { "lr": 0.01 } },
+    "loss_fn": "mse",
+    "batch_size": 2,
+    "max_epochs": 5,
+    "offline_epochs": 0,
+    "seed": 42
+}"#;
+
+const TWO_HEAD_MODEL: &str = r#"{
+    "layers": [{ "dense": { "output_size": 3, "init": "xavier" } }],
+    "heads": [
+        {
+            "layers": [{ "dense": { "output_size": 1, "init": "xavier" } }],
+            "loss_fn": "mse",
+            "y_size": 1
+        },
+        {
+            "layers": [{ "dense": { "output_size": 1, "init": "xavier" } }],
+            "loss_fn": "mse",
+            "y_size": 1,
+            "weight": 2.0
+        }
+    ]
+}"#;
+
+/// Spawns a node on the loopback interface, on it's own thread and runtime, that shuts
+/// down as soon as the returned sender is signaled.
+fn spawn_killable_node() -> (String, UnboundedSender<()>) {
+    let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    let (kill_tx, mut kill_rx) = mpsc::unbounded_channel();
+    let runtime = Runtime::new().unwrap();
+
+    thread::spawn(move || {
+        runtime.block_on(async move {
+            let listener = TcpListener::from_std(listener).unwrap();
+
+            tokio::select! {
+                _ = node::serve(listener, None, None, None, None) => {}
+                _ = kill_rx.recv() => {}
+            }
+        });
+
+        // Drops the server and worker tasks along with their connections.
+        runtime.shutdown_background();
+    });
+
+    (addr, kill_tx)
+}
+
+/// Runs a training with every node in process until it completes.
+fn train_local(training: TrainingConfig) -> TrainingReport {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    train_local_model(model, training)
+}
+
+/// Runs a training of the given model with every node in process until it completes.
+fn train_local_model(model: ModelConfig, training: TrainingConfig) -> TrainingReport {
+    let session = orchestrator::train_local(model, training).unwrap();
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+
+    loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::TrainingComplete { report }) => break report,
+            Some(TrainingEvent::Error(e)) => panic!("training failed: {e}"),
+            Some(_) => continue,
+            None => panic!("the session ended without completing"),
+        }
+    }
+}
+
+#[test]
+fn test_train_local_runs_every_node_in_process() {
+    let training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    let report = train_local(training);
+
+    assert_eq!(report.params().len(), 4 + 4 + 4 + 1);
+    assert_eq!(report.epochs_run(), 5);
+    assert_eq!(report.total_samples(), 5 * 4);
+    assert!(report.confusion_matrix().is_none());
+}
+
+#[test]
+fn test_a_classifier_reports_the_confusion_matrix_of_every_training_row() {
+    let model: ModelConfig = serde_json::from_str(
+        r#"{ "layers": [{ "dense": { "output_size": 2, "init": "xavier", "act_fn": "softmax" } }] }"#,
+    )
+    .unwrap();
+
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.loss_fn = LossFnConfig::CrossEntropy;
+    training.dataset.y_size = NonZeroUsize::new(2).unwrap();
+    training.dataset.src = DataSrc::Inline {
+        samples: vec![-2.0, -1.0, 1.0, 2.0, -3.0, 3.0],
+        labels: vec![1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0],
+    };
+
+    let mut all_reduce = training.clone();
+    all_reduce.algorithm = AlgorithmConfig::AllReduce;
+    all_reduce.addrs.truncate(2);
+
+    for training in [training, all_reduce] {
+        let report = train_local_model(model.clone(), training);
+        let confusion_matrix = report.confusion_matrix().unwrap();
+
+        assert_eq!(confusion_matrix.nclasses(), 2);
+        assert_eq!(confusion_matrix.len(), 6);
+    }
+}
+
+#[test]
+fn test_total_samples_counts_the_steps_of_a_partial_epoch() {
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.batch_size = NonZeroUsize::MIN;
+    training.stop_condition = Some(StopConditionConfig::MaxSteps(NonZeroUsize::new(3).unwrap()));
+
+    let report = train_local(training);
+
+    // Every worker takes 2 steps of a single sample per epoch, the last one stops half way.
+    assert_eq!(report.epochs_run(), 2);
+    assert_eq!(report.total_samples(), 2 * 3);
+}
+
+#[test]
+fn test_train_local_runs_all_reduce_without_servers() {
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.algorithm = AlgorithmConfig::AllReduce;
+    training.addrs.truncate(2);
+
+    let report = train_local(training);
+
+    assert_eq!(report.params().len(), 4 + 4 + 4 + 1);
+    assert!(report.params().iter().all(|p| p.is_finite()));
+    assert_eq!(report.epochs_run(), 5);
+    assert_eq!(report.total_samples(), 5 * 4);
+}
+
+#[test]
+fn test_every_worker_reports_the_metrics_of_each_round() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+
+    let session = orchestrator::train_local(model, training).unwrap();
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+    let mut reports = vec![Vec::new(); 2];
+
+    while let Some(event) = rx.blocking_recv() {
+        match event {
+            TrainingEvent::Metrics { worker_id, report } => reports[worker_id].push(report),
+            TrainingEvent::Error(e) => panic!("training failed: {e}"),
+            _ => {}
+        }
+    }
+
+    for mut reports in reports {
+        // The last report is the one of the validation pass over the final parameters.
+        let validation = reports.pop().unwrap();
+        assert_eq!(validation.epoch, 5);
+        assert!(validation.losses.is_empty());
+        assert_eq!(validation.samples, 0);
+        assert_eq!(validation.regression.unwrap().n, 2);
+
+        let epochs: Vec<_> = reports.iter().map(|report| report.epoch).collect();
+        assert_eq!(epochs, [1, 2, 3, 4, 5]);
+        assert!(reports.iter().all(|report| report.losses.len() == 1));
+        assert!(reports.iter().all(|report| report.throughput >= 0.0));
+        assert!(reports.iter().all(|report| report.regression.is_none()));
+    }
+}
+
+#[test]
+fn test_a_regression_reports_the_metrics_of_every_training_row() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.algorithm = AlgorithmConfig::AllReduce;
+    training.addrs.truncate(2);
+
+    let session = orchestrator::train_local(model, training).unwrap();
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+    let mut regression_metrics = Vec::new();
+
+    loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::RegressionMetrics { metrics }) => regression_metrics.push(metrics),
+            Some(TrainingEvent::TrainingComplete { .. }) => break,
+            Some(TrainingEvent::Error(e)) => panic!("training failed: {e}"),
+            Some(_) => continue,
+            None => panic!("the session ended without completing"),
+        }
+    }
+
+    let [metrics] = regression_metrics[..] else {
+        panic!("expected a single regression metrics event, got {regression_metrics:?}");
+    };
+
+    assert_eq!(metrics.len(), 4);
+    assert!(metrics.mse().is_finite());
+    assert!(metrics.r_squared().is_finite());
+}
+
+#[test]
+fn test_constant_targets_report_an_undefined_r_squared() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.dataset.src = DataSrc::Inline {
+        samples: vec![1.0, 2.0, 3.0, 4.0],
+        labels: vec![3.0; 4],
+    };
+
+    let session = orchestrator::train_local(model, training).unwrap();
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+
+    let metrics = loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::RegressionMetrics { metrics }) => break metrics,
+            Some(TrainingEvent::Error(e)) => panic!("training failed: {e}"),
+            Some(_) => continue,
+            None => panic!("the session ended without reporting the regression metrics"),
+        }
+    };
+
+    assert_eq!(metrics.len(), 4);
+    assert!(metrics.mse().is_finite());
+    assert!(metrics.r_squared().is_nan());
+}
+
+#[test]
+fn test_the_learning_rate_set_mid_run_reaches_the_servers() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    let baseline = train_local(training.clone());
+
+    let session = orchestrator::train_local(model, training).unwrap();
+    let lr_handle = session.lr_handle();
+    assert!(lr_handle.set_lr(0.0).is_err());
+
+    // Nearly freezes the model after the first round.
+    lr_handle.set_lr(1e-9).unwrap();
+
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+
+    let report = loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::TrainingComplete { report }) => break report,
+            Some(TrainingEvent::Error(e)) => panic!("training failed: {e}"),
+            Some(_) => continue,
+            None => panic!("the session ended without completing"),
+        }
+    };
+
+    assert_eq!(report.epochs_run(), 5);
+    assert_ne!(report.params(), baseline.params());
+}
+
+#[test]
+fn test_a_server_holding_only_frozen_layers_leaves_them_untouched() {
+    let model: ModelConfig = serde_json::from_str(
+        r#"{
+            "layers": [
+                { "dense": { "output_size": 4, "init": { "const": { "value": 0.5 } }, "frozen": true } },
+                { "dense": { "output_size": 1, "init": { "const": { "value": 0.0 } } } }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    // The layers are balanced between the servers, so the frozen one fills a server of it's own.
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    let AlgorithmConfig::ParameterServer { nservers, .. } = &mut training.algorithm else {
+        panic!("the training isn't on parameter servers");
+    };
+    *nservers = NonZeroUsize::new(2).unwrap();
+
+    let session = orchestrator::train_local(model, training).unwrap();
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+
+    let report = loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::TrainingComplete { report }) => break report,
+            Some(TrainingEvent::Error(e)) => panic!("training failed: {e}"),
+            Some(_) => continue,
+            None => panic!("the session ended without completing"),
+        }
+    };
+
+    let (frozen, trained) = report.params().split_at(8);
+    assert_eq!(frozen, [0.5; 8]);
+    assert!(trained.iter().any(|&param| param != 0.0));
+}
+
+#[test]
+fn test_a_non_blocking_server_trains_alongside_a_barrier_one() {
+    let model: ModelConfig = serde_json::from_str(
+        r#"{
+            "layers": [
+                { "dense": { "output_size": 4, "init": { "const": { "value": 0.5 } }, "act_fn": { "sigmoid": { "amp": 1.0 } } } },
+                { "dense": { "output_size": 4, "init": { "const": { "value": 0.5 } } } },
+                { "dense": { "output_size": 1, "init": { "const": { "value": 0.5 } } } }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    // The first server's layers change on every gradient, the rest once both workers sent theirs.
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.addrs.push("node-3".into());
+    let AlgorithmConfig::ParameterServer { nservers, .. } = &mut training.algorithm else {
+        panic!("the training isn't on parameter servers");
+    };
+    *nservers = NonZeroUsize::new(2).unwrap();
+    training.server_synchronizers =
+        vec![SynchronizerConfig::NonBlocking, SynchronizerConfig::Barrier];
+
+    let report = train_local_model(model, training);
+    assert_eq!(report.epochs_run(), 5);
+
+    let params = report.params();
+    for (start, end) in [(0, 8), (8, 28), (28, 33)] {
+        assert!(params[start..end].iter().any(|&param| param != 0.5));
+    }
+}
+
+#[test]
+fn test_the_accum_f64_layers_must_be_layers_of_the_model() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let validator = Validator::new();
+
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.accum_f64_layers = vec![1];
+    assert!(validator.validate(&model, &training).is_ok());
+
+    for layers in [vec![2], vec![1, 1]] {
+        training.accum_f64_layers = layers;
+        assert!(validator.validate(&model, &training).is_err());
+    }
+
+    training.accum_f64_layers = vec![1];
+    training.accum_f64 = true;
+    assert!(validator.validate(&model, &training).is_err());
+}
+
+#[test]
+fn test_the_server_synchronizers_need_one_per_server() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.server_synchronizers =
+        vec![SynchronizerConfig::NonBlocking, SynchronizerConfig::Barrier];
+
+    let validator = Validator::new();
+    assert!(validator.validate(&model, &training).is_err());
+
+    training.server_synchronizers.pop();
+    assert!(validator.validate(&model, &training).is_ok());
+}
+
+#[test]
+fn test_a_bias_only_training_leaves_every_weight_untouched() {
+    let model: ModelConfig = serde_json::from_str(
+        r#"{
+            "layers": [
+                { "dense": { "output_size": 4, "init": { "const": { "value": 0.5 } }, "act_fn": { "sigmoid": { "amp": 1.0 } } } },
+                { "dense": { "output_size": 1, "init": { "const": { "value": 0.5 } } } }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.bias_only = true;
+
+    let session = orchestrator::train_local(model, training).unwrap();
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+
+    let report = loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::TrainingComplete { report }) => break report,
+            Some(TrainingEvent::Error(e)) => panic!("training failed: {e}"),
+            Some(_) => continue,
+            None => panic!("the session ended without completing"),
+        }
+    };
+
+    // Every dense layer holds it's weights first and it's biases last.
+    let params = report.params();
+    let (weights, biases): (Vec<_>, Vec<_>) = [(0..4, 4..8), (8..12, 12..13)]
+        .into_iter()
+        .map(|(weights, biases)| (&params[weights], &params[biases]))
+        .unzip();
+
+    assert!(
+        weights
+            .iter()
+            .all(|weights| weights.iter().all(|&w| w == 0.5))
+    );
+    assert!(biases.iter().all(|biases| biases.iter().all(|&b| b != 0.5)));
+}
+
+#[test]
+fn test_a_bias_only_training_needs_a_layer_with_biases() {
+    let model: ModelConfig = serde_json::from_str(
+        r#"{
+            "layers": [
+                { "max_pooling": { "input_dim": [1, 2, 2], "filter_size": 1, "stride": 1, "padding": 0 } }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    // The four samples and labels make up a single row of the pooling's size.
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.dataset.x_size = NonZeroUsize::new(4).unwrap();
+    training.dataset.y_size = NonZeroUsize::new(4).unwrap();
+    training.batch_size = NonZeroUsize::MIN;
+
+    let validator = Validator::new();
+    assert!(validator.validate(&model, &training).is_ok());
+
+    training.bias_only = true;
+    assert!(validator.validate(&model, &training).is_err());
+}
+
+#[test]
+fn test_halving_the_learning_rate_recovers_a_diverging_run() {
+    let model: ModelConfig = serde_json::from_str(
+        r#"{ "layers": [{ "dense": { "output_size": 1, "init": { "const": { "value": 0.0 } } } }] }"#,
+    )
+    .unwrap();
+
+    // Too high a learning rate for the dataset, every step overshoots further.
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.optimizer = OptimizerConfig::GradientDescent {
+        lr: FloatPositive::new(0.12).unwrap(),
+    };
+    training.max_epochs = NonZeroUsize::new(100).unwrap();
+    training.addrs.truncate(2);
+    training.batch_size = NonZeroUsize::new(4).unwrap();
+
+    let run = |training: TrainingConfig| {
+        let session = orchestrator::train_local(model.clone(), training).unwrap();
+        let (_cancel, cancel_rx) = CancelHandle::pair();
+        let mut rx = session.event_listener(cancel_rx);
+
+        loop {
+            match rx.blocking_recv() {
+                Some(TrainingEvent::TrainingComplete { report }) => break Ok(report),
+                Some(TrainingEvent::Error(e)) => break Err(e),
+                Some(_) => continue,
+                None => panic!("the session ended without completing"),
+            }
+        }
+    };
+
+    let diverged = run(training.clone());
+
+    training.divergence_policy = DivergencePolicyConfig::HalveLr { max_reductions: 4 };
+    let recovered = run(training);
+
+    // The model starts at zero, with a loss of 30 over the whole dataset.
+    let diverged = diverged.unwrap().final_loss().unwrap();
+    let recovered = recovered.unwrap().final_loss().unwrap();
+    assert!(diverged > 30.0, "the run didn't diverge, loss: {diverged}");
+    assert!(recovered < 1.0, "the run didn't recover, loss: {recovered}");
+}
+
+#[test]
+fn test_the_epoch_callback_sees_every_epoch_and_stops_the_training() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.max_epochs = NonZeroUsize::new(200).unwrap();
+
+    let epochs = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&epochs);
+
+    let mut session = orchestrator::train_local(model, training).unwrap();
+    session.on_epoch(move |summary| {
+        assert!(summary.loss.is_finite());
+        let mut seen = seen.lock().unwrap();
+        seen.push(summary.epoch);
+
+        if seen.len() < 3 {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
+        }
+    });
+
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+
+    let report = loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::TrainingComplete { report }) => break report,
+            Some(TrainingEvent::Error(e)) => panic!("training failed: {e}"),
+            Some(_) => continue,
+            None => panic!("the session ended without completing"),
+        }
+    };
+
+    // The workers aren't in lockstep, the callback runs once all of them finished an epoch.
+    let epochs = epochs.lock().unwrap();
+    assert_eq!(epochs.len(), 3);
+    assert!(epochs.is_sorted_by(|a, b| a < b));
+    assert!(matches!(report.stop_reason(), StopReason::Callback));
+    assert!(report.epochs_run() < 200);
+}
+
+#[test]
+fn test_a_panicking_epoch_callback_is_surfaced_as_an_error() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+
+    let mut session = orchestrator::train_local(model, training).unwrap();
+    session.on_epoch(|_| panic!("callback failure"));
+
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+
+    let err = loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::TrainingComplete { .. }) => panic!("the panic wasn't surfaced"),
+            Some(TrainingEvent::Error(e)) => break e,
+            Some(_) => continue,
+            None => panic!("the session ended without completing"),
+        }
+    };
+
+    assert!(matches!(err, OrchErr::EpochHook(msg) if msg == "callback failure"));
+}
+
+#[test]
+fn test_a_linear_model_recovers_the_map_of_a_synthetic_dataset() {
+    let model: ModelConfig = serde_json::from_str(
+        r#"{ "layers": [{ "dense": { "output_size": 1, "init": { "const": { "value": 0.0 } } } }] }"#,
+    )
+    .unwrap();
+
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.dataset.x_size = NonZeroUsize::new(2).unwrap();
+    training.dataset.src = DataSrc::Synthetic {
+        nsamples: NonZeroUsize::new(64).unwrap(),
+        noise_std: 0.0,
+        seed: 7,
+    };
+    training.optimizer = OptimizerConfig::GradientDescent {
+        lr: FloatPositive::new(0.05).unwrap(),
+    };
+    training.batch_size = NonZeroUsize::new(8).unwrap();
+    training.max_epochs = NonZeroUsize::new(100).unwrap();
+
+    let report = train_local_model(model, training);
+    let truth = SyntheticDataset::generate(64, 2, 1, 0.0, 7).unwrap();
+    let expected: Vec<_> = truth.weights.iter().chain(&truth.biases).collect();
+
+    for (param, expected) in report.params().iter().zip(expected) {
+        assert!(
+            (param - expected).abs() < 0.05,
+            "{param} isn't close to {expected}"
+        );
+    }
+}
+
+#[test]
+fn test_both_heads_of_a_two_target_regression_converge() {
+    let model: ModelConfig = serde_json::from_str(TWO_HEAD_MODEL).unwrap();
+
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.dataset.x_size = NonZeroUsize::new(2).unwrap();
+    training.dataset.y_size = NonZeroUsize::new(2).unwrap();
+    training.dataset.test_split = 0.25;
+    training.dataset.src = DataSrc::Synthetic {
+        nsamples: NonZeroUsize::new(64).unwrap(),
+        noise_std: 0.0,
+        seed: 7,
+    };
+    training.optimizer = OptimizerConfig::GradientDescent {
+        lr: FloatPositive::new(0.05).unwrap(),
+    };
+    training.batch_size = NonZeroUsize::new(8).unwrap();
+    training.max_epochs = NonZeroUsize::new(100).unwrap();
+
+    let report = train_local_model(model, training);
+
+    // The shared layer's parameters, followed by each head's.
+    assert_eq!(report.params().len(), 3 * 3 + 4 + 4);
+
+    let (final_loss, test_loss) = (report.final_loss().unwrap(), report.test_loss().unwrap());
+    assert!(
+        final_loss < 1e-3,
+        "the training loss {final_loss} didn't converge"
+    );
+    assert!(
+        test_loss < 1e-3,
+        "the test loss {test_loss} didn't converge"
+    );
+}
+
+#[test]
+fn test_the_heads_must_predict_every_target() {
+    let mut model: ModelConfig = serde_json::from_str(TWO_HEAD_MODEL).unwrap();
+    let validator = Validator::new();
+
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.dataset.x_size = NonZeroUsize::new(2).unwrap();
+    training.dataset.y_size = NonZeroUsize::new(2).unwrap();
+    training.dataset.src = DataSrc::Synthetic {
+        nsamples: NonZeroUsize::new(8).unwrap(),
+        noise_std: 0.0,
+        seed: 7,
+    };
+    assert!(validator.validate(&model, &training).is_ok());
+
+    training.dataset.y_size = NonZeroUsize::MIN;
+    assert!(validator.validate(&model, &training).is_err());
+
+    training.dataset.y_size = NonZeroUsize::new(2).unwrap();
+    model.heads[1].y_size = NonZeroUsize::new(2).unwrap();
+    assert!(validator.validate(&model, &training).is_err());
+}
+
+#[test]
+fn test_all_reduce_rejects_server_settings() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.algorithm = AlgorithmConfig::AllReduce;
+
+    let validator = Validator::new();
+    assert!(validator.validate(&model, &training).is_ok());
+
+    training.lr_scaling = LrScalingConfig::Linear;
+    assert!(validator.validate(&model, &training).is_err());
+}
+
+#[test]
+fn test_inline_samples_and_labels_must_hold_the_same_rows() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.dataset.y_size = NonZeroUsize::new(2).unwrap();
+
+    // Four samples and two labels add up to two rows of size three, but hold four and one rows.
+    let validator = Validator::new();
+    let DataSrc::Inline { labels, .. } = &mut training.dataset.src else {
+        panic!("the dataset isn't inline");
+    };
+    labels.truncate(2);
+    assert!(validator.validate(&model, &training).is_err());
+
+    training.dataset.y_size = NonZeroUsize::MIN;
+    let DataSrc::Inline { labels, .. } = &mut training.dataset.src else {
+        panic!("the dataset isn't inline");
+    };
+    labels.extend([6.0, 8.0]);
+    assert!(validator.validate(&model, &training).is_ok());
+}
+
+#[test]
+fn test_the_event_feed_streams_every_epoch_to_a_connected_reader() {
+    let path = std::env::temp_dir().join(format!("train-local-{}.sock", std::process::id()));
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.event_feed_path = Some(path.clone());
+
+    let session = orchestrator::train_local(model, training).unwrap();
+    let reader = BufReader::new(UnixStream::connect(&path).unwrap());
+
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+    while rx.blocking_recv().is_some() {}
+
+    let events: Vec<Value> = reader
+        .lines()
+        .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+        .collect();
+
+    for worker_id in [0, 1] {
+        let epochs: usize = events
+            .iter()
+            .filter(|e| e["event"] == "published_losses" && e["worker_id"] == worker_id)
+            .map(|e| e["losses"].as_array().unwrap().len())
+            .sum();
+
+        assert_eq!(epochs, 5);
+    }
+
+    let last = events.last().unwrap();
+    assert_eq!(last["event"], "training_complete");
+    assert_eq!(last["epochs_run"], 5);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_a_server_lost_mid_run_surfaces_the_params_of_the_last_snapshot() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let nodes: Vec<_> = (0..3).map(|_| spawn_killable_node()).collect();
+
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.addrs = nodes.iter().map(|(addr, _)| addr.clone()).collect();
+    training.max_epochs = NonZeroUsize::new(100_000).unwrap();
+    training.snapshot_every = NonZeroUsize::new(2);
+
+    let mut session = orchestrator::train(model, training).unwrap();
+
+    // The nodes are listed workers first, the last one is the server.
+    let (server, _) = session.seed_report().node(2).unwrap();
+    let (_, kill_tx) = nodes
+        .iter()
+        .find(|(addr, _)| server.contains(addr.as_str()))
+        .unwrap();
+    let kill_tx = kill_tx.clone();
+
+    session.on_epoch(move |summary| {
+        if summary.epoch >= 4 {
+            let _ = kill_tx.send(());
+        }
+
+        ControlFlow::Continue(())
+    });
+
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+
+    let err = loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::TrainingComplete { .. }) => panic!("the server wasn't lost"),
+            Some(TrainingEvent::Error(e)) => break e,
+            Some(_) => continue,
+            None => panic!("the session ended without failing"),
+        }
+    };
+
+    let OrchErr::FailedWithPartial { params, .. } = err else {
+        panic!("the failure didn't keep the last snapshot: {err}");
+    };
+
+    assert_eq!(params.len(), 4 + 4 + 4 + 1);
+    assert!(params.iter().all(|p| p.is_finite()));
+}
+
+#[test]
+fn test_a_server_lost_after_the_last_epoch_surfaces_the_params_of_the_last_snapshot() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let nodes: Vec<_> = (0..2).map(|_| spawn_killable_node()).collect();
+
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.addrs = nodes.iter().map(|(addr, _)| addr.clone()).collect();
+    training.max_epochs = NonZeroUsize::new(4).unwrap();
+    training.snapshot_every = NonZeroUsize::new(2);
+
+    let mut session = orchestrator::train(model, training).unwrap();
+
+    // The nodes are listed workers first, the last one is the server.
+    let (server, _) = session.seed_report().node(1).unwrap();
+    let (addr, kill_tx) = nodes
+        .iter()
+        .find(|(addr, _)| server.contains(addr.as_str()))
+        .unwrap();
+    let (addr, kill_tx) = (addr.clone(), kill_tx.clone());
+
+    // With a single worker the callback runs on every epoch, the training is held until
+    // the server is gone so it's the session asking for the trained parameters that fails.
+    session.on_epoch(move |summary| {
+        if summary.epoch == 4 {
+            let _ = kill_tx.send(());
+
+            while net::TcpStream::connect(&addr).is_ok() {
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            thread::sleep(Duration::from_millis(200));
+        }
+
+        ControlFlow::Continue(())
+    });
+
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+
+    let err = loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::TrainingComplete { .. }) => panic!("the server wasn't lost"),
+            Some(TrainingEvent::Error(e)) => break e,
+            Some(_) => continue,
+            None => panic!("the session ended without failing"),
+        }
+    };
+
+    let OrchErr::FailedWithPartial { params, .. } = err else {
+        panic!("the failure didn't keep the last snapshot: {err}");
+    };
+
+    assert_eq!(params.len(), 4 + 4 + 4 + 1);
+    assert!(params.iter().all(|p| p.is_finite()));
+}
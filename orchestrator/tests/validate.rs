@@ -0,0 +1,164 @@
+use std::{net, thread};
+
+use orchestrator::{CancelHandle, OrchestratorPool, Session, TrainingEvent, configs::*};
+use tokio::{net::TcpListener, runtime::Runtime};
+
+const MODEL: &str = r#"{
+    "layers": [
+        { "dense": { "output_size": 4, "init": "kaiming", "act_fn": { "sigmoid": { "amp": 1.0 } } } },
+        { "dense": { "output_size": 1, "init": "kaiming" } }
+    ]
+}"#;
+
+const TRAINING: &str = r#"{
+    "addrs": [],
+    "algorithm": {
+        "parameter_server": {
+            "nservers": 1,
+            "synchronizer": "barrier",
+            "store": "blocking"
+        }
+    },
+    "dataset": {
+        "src": {
+            "inline": {
+                "samples": [1.0, 2.0, 3.0, 4.0],
+                "labels": [2.0, 4.0, 6.0, 8.0]
+            }
+        },
+        "x_size": 1,
+        "y_size": 1
+    },
+    "optimizer": { "gradient_descent": { "lr": 0.01 } },
+    "loss_fn": "mse",
+    "batch_size": 2,
+    "max_epochs": 2,
+    "offline_epochs": 0,
+    "seed": 42
+}"#;
+
+/// Spawns nodes on the loopback interface that live until the process exits.
+fn spawn_nodes(n: usize) -> Vec<String> {
+    (0..n)
+        .map(|_| {
+            let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.set_nonblocking(true).unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+
+            let runtime = Runtime::new().unwrap();
+            thread::spawn(move || {
+                runtime.block_on(async {
+                    let listener = TcpListener::from_std(listener).unwrap();
+                    node::serve(listener, None, None, None, None).await
+                })
+            });
+
+            addr
+        })
+        .collect()
+}
+
+/// Listens to the session until the training completes.
+fn complete(session: Session) {
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+
+    loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::TrainingComplete { .. }) => break,
+            Some(TrainingEvent::Error(e)) => panic!("training failed: {e}"),
+            Some(_) => continue,
+            None => panic!("the session ended without completing"),
+        }
+    }
+}
+
+#[test]
+fn test_validate_reports_the_layout_without_training() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.addrs = spawn_nodes(3);
+
+    let report = orchestrator::validate(model.clone(), training.clone()).unwrap();
+
+    assert_eq!(report.nparams(), 4 + 4 + 4 + 1);
+    assert_eq!(report.param_bytes(), report.nparams() * size_of::<f32>());
+    assert_eq!(report.shards().len(), 1);
+    assert_eq!(report.shards()[0].1, report.nparams());
+    assert_eq!(report.splits().len(), 2);
+    assert_eq!(report.splits().iter().map(|(_, n)| n).sum::<u64>(), 4);
+
+    // The nodes were left idle, so they can still be trained on.
+    let session = orchestrator::train(model, training).unwrap();
+    let (_cancel, cancel_rx) = CancelHandle::pair();
+    let mut rx = session.event_listener(cancel_rx);
+
+    loop {
+        match rx.blocking_recv() {
+            Some(TrainingEvent::TrainingComplete { report }) => {
+                assert_eq!(report.epochs_run(), 2);
+                break;
+            }
+            Some(TrainingEvent::Error(e)) => panic!("training failed: {e}"),
+            Some(_) => continue,
+            None => panic!("the session ended without completing"),
+        }
+    }
+}
+
+#[test]
+fn test_validate_rejects_unreachable_nodes() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+
+    let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let closed = listener.local_addr().unwrap().to_string();
+    drop(listener);
+
+    training.addrs = spawn_nodes(2);
+    training.addrs.push(closed);
+
+    assert!(orchestrator::validate(model, training).is_err());
+}
+
+#[test]
+fn test_a_session_after_a_validation_dials_no_new_connection() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.addrs = spawn_nodes(3);
+
+    let mut pool = OrchestratorPool::new().unwrap();
+    orchestrator::validate_with_pool(model.clone(), training.clone(), &mut pool).unwrap();
+    assert_eq!(pool.len(), 3);
+    assert_eq!(pool.dials(), 3);
+
+    // Both the stats and the bootstrap go through the validation's connections.
+    let session = orchestrator::train_with_pool(model, training, &mut pool).unwrap();
+    assert!(pool.is_empty());
+    assert_eq!(pool.dials(), 3);
+
+    // The nodes go back to idle on the same connections once the session is over.
+    complete(session);
+    assert_eq!(pool.len(), 3);
+    assert_eq!(pool.dials(), 3);
+}
+
+#[test]
+fn test_back_to_back_sessions_dial_no_new_connection() {
+    let model: ModelConfig = serde_json::from_str(MODEL).unwrap();
+    let mut training: TrainingConfig = serde_json::from_str(TRAINING).unwrap();
+    training.addrs = spawn_nodes(3);
+
+    let mut pool = OrchestratorPool::new().unwrap();
+    let session =
+        orchestrator::train_with_pool(model.clone(), training.clone(), &mut pool).unwrap();
+    complete(session);
+    assert_eq!(pool.dials(), 3);
+
+    // The server of the first session is bootstrapped as a worker this time.
+    training.algorithm = serde_json::from_str(r#""all_reduce""#).unwrap();
+    let session = orchestrator::train_with_pool(model, training, &mut pool).unwrap();
+    complete(session);
+    assert_eq!(pool.len(), 3);
+    assert_eq!(pool.dials(), 3);
+}
@@ -1,9 +1,9 @@
 use ratatui::{
+    Frame,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::Modifier,
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
-    Frame,
 };
 
 use orchestrator::StopReason;
@@ -30,6 +30,7 @@ pub fn draw_header(f: &mut Frame, area: Rect, state: &TrainingState) {
             Some(StopReason::ManualStop) => {
                 Span::styled("FINISHED · stopped", Theme::accent_magenta())
             }
+            Some(StopReason::Diverged) => Span::styled("FINISHED · diverged", Theme::error()),
             _ => Span::styled("FINISHED", Theme::accent_magenta()),
         },
         Phase::Error => Span::styled("ERROR", Theme::error()),
@@ -2,15 +2,15 @@ use std::time::{Duration, Instant};
 
 use crossterm::event::KeyCode;
 use orchestrator::{
+    CancelHandle, Session, StopReason, TrainedModel, TrainingEvent,
     configs::{AlgorithmConfig, DataSrc, ModelConfig, TrainingConfig},
     dataset_format::DatasetFormat,
-    CancelHandle, Session, StopReason, TrainedModel, TrainingEvent,
 };
 use ratatui::{
+    Frame,
     layout::{Constraint, Direction, Layout},
     style::Color,
     widgets::Block,
-    Frame,
 };
 
 use super::{Action, Screen};
@@ -35,6 +35,9 @@ pub const WORKER_COLORS: &[Color] = &[
     Color::Rgb(255, 130, 0),
 ];
 
+/// The amount of trained parameters logged once the training completes.
+const PARAMS_PREVIEW: usize = 5;
+
 /// Which training algorithm the session is using.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AlgorithmKind {
@@ -319,7 +322,9 @@ impl TrainingState {
     /// Applies a single training event to the state.
     fn apply(&mut self, event: TrainingEvent) {
         match event {
-            TrainingEvent::PublishedLosses { worker_id, losses } => {
+            TrainingEvent::PublishedLosses {
+                worker_id, losses, ..
+            } => {
                 self.phase = Phase::Training;
 
                 if worker_id < self.workers.len() {
@@ -350,14 +355,40 @@ impl TrainingState {
                 }
                 self.push_log(LogLevel::Info, format!("worker {worker_id} disconnected"));
             }
+            TrainingEvent::LrRestart { epoch, cycle } => {
+                self.push_log(
+                    LogLevel::Info,
+                    format!("learning rate restarted at epoch {epoch}  (cycle {cycle})"),
+                );
+            }
+            TrainingEvent::RoundTiming { timing } => {
+                self.push_log(
+                    LogLevel::Info,
+                    format!(
+                        "round took {:.2}s  (compute {:.2}s, comm {:.2}s, wait {:.2}s)",
+                        timing.total().as_secs_f64(),
+                        timing.compute.as_secs_f64(),
+                        timing.comm.as_secs_f64(),
+                        timing.wait.as_secs_f64()
+                    ),
+                );
+            }
+            TrainingEvent::Staleness { server, stats } => {
+                self.push_log(
+                    LogLevel::Info,
+                    format!(
+                        "server {server} applied {} gradients  (staleness mean {:.2}, max {})",
+                        stats.updates, stats.mean, stats.max
+                    ),
+                );
+            }
             TrainingEvent::Upgrading { worker_id } => {
                 if worker_id < self.workers.len() {
                     self.workers[worker_id].became_server = true;
                     self.workers[worker_id].done = true;
                 }
                 self.last_upgrade = Some((worker_id, Instant::now()));
-                self.ps_server_count =
-                    self.workers.iter().filter(|w| w.became_server).count();
+                self.ps_server_count = self.workers.iter().filter(|w| w.became_server).count();
                 // If the chart was focused on the worker that just upgraded,
                 // move focus to an active worker so no server shows in graphs.
                 if self.selected_worker == worker_id {
@@ -372,25 +403,35 @@ impl TrainingState {
                     ),
                 );
             }
-            TrainingEvent::TrainingComplete {
-                model: trained,
-                stop_reason: reason,
-            } => {
+            TrainingEvent::RegressionMetrics { metrics } => {
+                self.push_log(
+                    LogLevel::Info,
+                    format!(
+                        "regression metrics — mse: {:.6}, r²: {:.4}",
+                        metrics.mse(),
+                        metrics.r_squared()
+                    ),
+                );
+            }
+            TrainingEvent::TrainingComplete { report } => {
+                let reason = report.stop_reason();
                 self.phase = Phase::Finished;
                 self.finish_reason = Some(reason);
                 let reason_str = match reason {
                     StopReason::MaxEpochsReached => "max epochs reached",
                     StopReason::EarlyStopping => "early stopping — loss converged",
                     StopReason::ManualStop => "stopped manually",
+                    StopReason::Diverged => "aborted — loss diverged",
+                    StopReason::Callback => "stopped by the epoch callback",
                 };
                 self.push_log(
                     LogLevel::Info,
                     format!(
-                        "training complete ({reason_str}) — {} parameters received",
-                        trained.params.len()
+                        "training complete ({reason_str}) — params: {}",
+                        report.params_preview(PARAMS_PREVIEW)
                     ),
                 );
-                self.final_trained = Some(trained);
+                self.final_trained = Some(report.into_model());
             }
             TrainingEvent::Error(e) => {
                 self.phase = Phase::Error;
@@ -398,7 +439,8 @@ impl TrainingState {
                 self.error = Some(msg.clone());
                 self.push_log(LogLevel::Error, msg);
             }
-            _ => unreachable!(),
+            // The per round metrics and the rest are already covered by the events above.
+            _ => {}
         }
     }
 
@@ -473,7 +515,7 @@ impl TrainingState {
 
     /// Returns `true` if the worker at `id` has upgraded into a parameter server.
     fn is_server_worker(&self, id: usize) -> bool {
-        self.workers.get(id).map_or(false, |w| w.became_server)
+        self.workers.get(id).is_some_and(|w| w.became_server)
     }
 
     /// Advances the selected worker to the next one, skipping any that have
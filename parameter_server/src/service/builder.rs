@@ -4,16 +4,21 @@ use comms::{
     Acceptor, Connection, OrchHandle, TransportLayer, WorkerHandle,
     protocol::Entity,
     specs::{
-        machine_learning::OptimizerSpec,
-        server::{ServerSpec, StoreSpec, SynchronizerSpec},
+        machine_learning::{LrScheduleSpec, OptimizerSpec},
+        server::{QuantizationSpec, ServerSpec, StoreSpec, SynchronizerSpec, UpdateRule},
     },
 };
+use log::warn;
 use machine_learning::{
     initialization::{ParamGenBuilder, Result},
-    optimization::{Adam, GradientDescent, GradientDescentWithMomentum, Optimizer},
+    optimization::{Adam, GradientDescent, GradientDescentWithMomentum, Optimizer, WarmRestarts},
+};
+use tokio::{
+    runtime::{Handle, RuntimeFlavor},
+    sync::mpsc::Sender,
 };
 
-use super::{ParameterServer, Server};
+use super::{LrScheduler, ParameterServer, Server};
 use crate::{
     storage::{BlockingStore, Store, WildStore},
     synchronization::{BarrierSync, NoBlockingSync, Synchronizer},
@@ -86,25 +91,100 @@ where
     where
         G: AsyncFnMut(&mut WorkerHandle<T>) -> io::Result<()>,
     {
-        let nworkers = spec.nworkers;
-        let src = Entity::ParamServer;
+        // The synchronizers block their thread, which would stall the whole runtime.
+        if Handle::current().runtime_flavor() == RuntimeFlavor::CurrentThread {
+            let text = "parameter servers need a multi thread runtime";
+            return Err(io::Error::new(io::ErrorKind::Unsupported, text));
+        }
+
+        let nworkers = spec.initial_workers();
+        let quantization = spec.quantization;
+        let shard_size = Self::shard_size(spec.param_gen.size());
+        let frame_size = spec.frame_size();
+        let chunk_size = spec.chunk_size;
+        let slow_send_threshold = spec.slow_send_threshold();
 
         let mut server = self
             .resolve_optimizer(spec, orch_handle)
             .map_err(io::Error::other)?;
 
         for _ in 0..nworkers {
-            let Connection::Worker(mut worker_handle) = self.acceptor.accept(src).await? else {
-                return Err(io::Error::other("Unexpected non worker connection"));
-            };
-
+            let mut worker_handle = self.accept_worker(quantization, shard_size).await?;
             connection_hook(&mut worker_handle).await?;
+            worker_handle.set_max_frame_size(Some(frame_size));
+            worker_handle.set_chunk_size(chunk_size);
+            worker_handle.set_slow_send_threshold(slow_send_threshold);
             server.spawn(worker_handle);
         }
 
         Ok(server)
     }
 
+    /// Accepts the workers that join after the training started and hands them to the server.
+    ///
+    /// # Args
+    /// * `spec` - The specification of the parameter server.
+    /// * `joiner` - The sender for the late joining worker handles.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    pub async fn accept_joiners(
+        &mut self,
+        spec: &ServerSpec,
+        joiner: Sender<WorkerHandle<T>>,
+    ) -> io::Result<()> {
+        let shard_size = Self::shard_size(spec.param_gen.size());
+        let frame_size = spec.frame_size();
+
+        for _ in 0..spec.late_workers() {
+            let mut worker_handle = self.accept_worker(spec.quantization, shard_size).await?;
+            worker_handle.set_max_frame_size(Some(frame_size));
+            worker_handle.set_chunk_size(spec.chunk_size);
+            worker_handle.set_slow_send_threshold(spec.slow_send_threshold());
+
+            if joiner.send(worker_handle).await.is_err() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Accepts a new worker connection, skipping the ones that fail to authenticate.
+    ///
+    /// # Args
+    /// * `quantization` - The encoding of the parameters broadcasted to the worker.
+    /// * `shard_size` - The amount of parameters per shard of the store.
+    ///
+    /// # Returns
+    /// The handle for the new worker or an io error if occurred.
+    async fn accept_worker(
+        &mut self,
+        quantization: QuantizationSpec,
+        shard_size: NonZeroUsize,
+    ) -> io::Result<WorkerHandle<T>> {
+        loop {
+            let conn = match self.acceptor.accept(Entity::ParamServer).await {
+                Ok(conn) => conn,
+                Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                    warn!("rejected worker connection: {e}");
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            let Connection::Worker(mut worker_handle) = conn else {
+                return Err(io::Error::other("Unexpected non worker connection"));
+            };
+
+            if let QuantizationSpec::Int8 = quantization {
+                worker_handle.enable_params_quantization(shard_size);
+            }
+
+            return Ok(worker_handle);
+        }
+    }
+
     /// Resolves the `Optimizer` for this server.
     ///
     /// # Args
@@ -163,13 +243,7 @@ where
     {
         let param_gen_builder = ParamGenBuilder::new();
         let mut param_gen = param_gen_builder.build(spec.param_gen.clone(), spec.seed)?;
-
-        // SAFETY: The argument is at least 1.
-        let nparams = unsafe { NonZeroUsize::new_unchecked(param_gen.size().max(1)) };
-        let cores = thread::available_parallelism().unwrap_or(DEFAULT_CORE_COUNT);
-        let max_shard_amount = cores.saturating_mul(SHARD_AMOUNT_FACTOR);
-        let shard_amount = nparams.min(max_shard_amount);
-        let shard_size = NonZeroUsize::new(nparams.get().div_ceil(shard_amount.get())).unwrap();
+        let shard_size = Self::shard_size(param_gen.size());
 
         match spec.store {
             StoreSpec::Blocking => {
@@ -183,6 +257,22 @@ where
         }
     }
 
+    /// Calculates the size of each shard of the store given the amount of parameters.
+    ///
+    /// # Args
+    /// * `nparams` - The amount of parameters in the server.
+    ///
+    /// # Returns
+    /// The amount of parameters per shard.
+    fn shard_size(nparams: usize) -> NonZeroUsize {
+        // SAFETY: The argument is at least 1.
+        let nparams = unsafe { NonZeroUsize::new_unchecked(nparams.max(1)) };
+        let cores = thread::available_parallelism().unwrap_or(DEFAULT_CORE_COUNT);
+        let max_shard_amount = cores.saturating_mul(SHARD_AMOUNT_FACTOR);
+        let shard_amount = nparams.min(max_shard_amount);
+        NonZeroUsize::new(nparams.get().div_ceil(shard_amount.get())).unwrap()
+    }
+
     /// Resolves the `Synchronizer` for this server.
     ///
     /// # Args
@@ -201,24 +291,125 @@ where
     where
         PS: Store + Send + Sync + 'static,
     {
+        let allow_worker_loss = spec.allow_worker_loss;
+        let initial_workers = spec.initial_workers();
+        let lr_scheduler = Self::resolve_lr_scheduler(&spec);
+        let track_staleness = spec.track_staleness;
+        let update_rule = spec.update_rule;
+        let metric_barrier_every = spec.metric_barrier_every;
+
+        if let Some(decay) = spec.ema_decay {
+            store.enable_ema(decay);
+        }
+
+        if let Some(beta) = spec.server_momentum {
+            store.enable_momentum(beta);
+        }
+
+        if spec.accum_f64 {
+            store.enable_f64_accumulation();
+        } else if !spec.accum_f64_ranges.is_empty() {
+            store.enable_f64_accumulation_for(&spec.accum_f64_ranges);
+        }
+
+        if !spec.frozen.is_empty() {
+            store.freeze(&spec.frozen);
+        }
+
         match spec.synchronizer {
-            SynchronizerSpec::Barrier { barrier_size } => {
-                let synchronizer = BarrierSync::new(barrier_size);
-                self.terminate_build(orch_handle, store, synchronizer)
+            SynchronizerSpec::Barrier {
+                barrier_size,
+                reduction,
+                timeout,
+            } => {
+                // SAFETY: The amount of initial workers is at least 1.
+                let barrier_size = barrier_size.min(NonZeroUsize::new(initial_workers).unwrap());
+                let mut synchronizer = BarrierSync::new(barrier_size).with_reduction(reduction);
+
+                if let Some(timeout) = timeout {
+                    synchronizer = synchronizer.with_timeout(timeout);
+                }
+
+                if track_staleness {
+                    warn!("the barrier applies no stale gradients, their staleness isn't tracked");
+                }
+
+                if metric_barrier_every.is_some() {
+                    warn!(
+                        "the barrier already hands every worker the same parameters, the metric barrier is ignored"
+                    );
+                }
+
+                self.terminate_build(
+                    orch_handle,
+                    store,
+                    synchronizer,
+                    allow_worker_loss,
+                    lr_scheduler,
+                    false,
+                    update_rule,
+                )
             }
             SynchronizerSpec::NonBlocking => {
-                let synchronizer = NoBlockingSync::new();
-                self.terminate_build(orch_handle, store, synchronizer)
+                let mut synchronizer = NoBlockingSync::new();
+
+                match (metric_barrier_every, update_rule) {
+                    (Some(_), UpdateRule::SendThenUpdate) => {
+                        warn!(
+                            "the workers get the parameters before their gradient is applied, the metric barrier is ignored"
+                        );
+                    }
+                    (Some(every), UpdateRule::UpdateThenSend) => {
+                        // SAFETY: The amount of initial workers is at least 1.
+                        let size = NonZeroUsize::new(initial_workers).unwrap();
+                        synchronizer = synchronizer.with_metric_barrier(every, size);
+                    }
+                    (None, _) => {}
+                }
+
+                self.terminate_build(
+                    orch_handle,
+                    store,
+                    synchronizer,
+                    allow_worker_loss,
+                    lr_scheduler,
+                    track_staleness,
+                    update_rule,
+                )
             }
         }
     }
 
+    /// Resolves the `LrScheduler` for this server.
+    ///
+    /// # Args
+    /// * `spec` - The specification for the parameter server.
+    ///
+    /// # Returns
+    /// The learning rate scheduler or `None` if the learning rate is constant.
+    fn resolve_lr_scheduler(spec: &ServerSpec) -> Option<LrScheduler> {
+        let schedule = match spec.lr_schedule? {
+            LrScheduleSpec::WarmRestarts {
+                t_0,
+                t_mult,
+                min_lr,
+            } => Box::new(WarmRestarts::new(t_0, t_mult, *min_lr)),
+        };
+
+        let base_lr = spec.optimizer.learning_rate();
+        Some(LrScheduler::new(schedule, base_lr))
+    }
+
     /// Terminates the entire build for this session and finally instanciates all the entities.
     ///
     /// # Args
     /// * `orch_handle` - The handle for communicating with the orchestrator.
     /// * `store` - A resolved store.
     /// * `synchronizer` - A resolved synchronizer.
+    /// * `allow_worker_loss` - Whether to keep training when a worker's connection drops.
+    /// * `lr_scheduler` - The optional learning rate scheduler.
+    /// * `track_staleness` - Whether to track the staleness of the applied gradients.
+    /// * `update_rule` - Whether the workers get the parameters before or after the update.
     ///
     /// # Returns
     /// A new server.
@@ -227,12 +418,89 @@ where
         orch_handle: OrchHandle<T>,
         store: PS,
         synchronizer: Sy,
+        allow_worker_loss: bool,
+        lr_scheduler: Option<LrScheduler>,
+        track_staleness: bool,
+        update_rule: UpdateRule,
     ) -> Box<dyn Server<T>>
     where
         PS: Store + Send + Sync + 'static,
         Sy: Synchronizer + Send + Sync + 'static,
     {
-        let pserver = ParameterServer::new(store, synchronizer, orch_handle);
+        let mut pserver = ParameterServer::new(store, synchronizer, orch_handle)
+            .with_worker_loss(allow_worker_loss)
+            .with_update_rule(update_rule);
+
+        if let Some(lr_scheduler) = lr_scheduler {
+            pserver = pserver.with_lr_scheduler(lr_scheduler);
+        }
+
+        if track_staleness {
+            pserver = pserver.with_staleness_tracking();
+        }
+
         Box::new(pserver)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use comms::{Stp, floats::FloatPositive, specs::machine_learning::ParamGenSpec};
+    use tokio::io::{self, DuplexStream, ReadHalf, WriteHalf};
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_build_rejects_the_current_thread_runtime() {
+        let (stream, _peer) = io::duplex(4096);
+        let (rx, tx) = io::split(stream);
+        let orch_handle = OrchHandle::new(Uuid::new_v4(), comms::build_simple_transport(rx, tx));
+
+        let mut acceptor = Acceptor::new(Uuid::new_v4(), async || {
+            Err::<Stp<ReadHalf<DuplexStream>, WriteHalf<DuplexStream>>, _>(io::Error::other(
+                "no worker connects",
+            ))
+        });
+
+        let spec = ServerSpec {
+            nworkers: 1,
+            param_gen: ParamGenSpec::Const {
+                value: 0.0,
+                limit: 1,
+            },
+            optimizer: OptimizerSpec::GradientDescent {
+                learning_rate: FloatPositive::new(0.1).unwrap(),
+            },
+            synchronizer: SynchronizerSpec::NonBlocking,
+            store: StoreSpec::Blocking,
+            quantization: QuantizationSpec::default(),
+            allow_worker_loss: false,
+            min_workers: None,
+            max_workers: None,
+            max_frame_size: None,
+            chunk_size: None,
+            slow_send_ms: None,
+            lr_schedule: None,
+            seed: None,
+            ema_decay: None,
+            server_momentum: None,
+            track_staleness: false,
+            metric_barrier_every: None,
+            accum_f64: false,
+            accum_f64_ranges: Vec::new(),
+            frozen: Vec::new(),
+            update_rule: UpdateRule::default(),
+        };
+
+        let res = ServerBuilder::new(&mut acceptor)
+            .build(spec, orch_handle)
+            .await;
+
+        let Err(e) = res else {
+            panic!("a server was built on a current thread runtime");
+        };
+
+        assert_eq!(e.kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,123 @@
+use comms::floats::FloatPositive;
+use log::{info, warn};
+use machine_learning::optimization::LrSchedule;
+
+use crate::storage::Store;
+
+/// Moves the learning rate of the store along a schedule as the epochs go by.
+///
+/// The server doesn't see the workers' epochs, so it follows the ones the orchestrator
+/// counts from the losses the workers publish, offline epochs included.
+pub struct LrScheduler {
+    schedule: Box<dyn LrSchedule + Send + Sync>,
+    base_lr: FloatPositive,
+    epoch: usize,
+}
+
+impl LrScheduler {
+    /// Creates a new `LrScheduler`.
+    ///
+    /// # Args
+    /// * `schedule` - The learning rate schedule to follow.
+    /// * `base_lr` - The learning rate the optimizers were configured with.
+    ///
+    /// # Returns
+    /// A new `LrScheduler` instance.
+    pub fn new(schedule: Box<dyn LrSchedule + Send + Sync>, base_lr: FloatPositive) -> Self {
+        Self {
+            schedule,
+            base_lr,
+            epoch: 0,
+        }
+    }
+
+    /// Moves the schedule to a new epoch and updates the store's learning rate.
+    ///
+    /// Epochs older than the latest one are ignored.
+    ///
+    /// # Args
+    /// * `store` - The store whose optimizers follow the schedule.
+    /// * `epoch` - The amount of epochs the workers ran so far.
+    pub fn step<PS: Store>(&mut self, store: &PS, epoch: usize) {
+        if epoch <= self.epoch {
+            return;
+        }
+
+        self.epoch = epoch;
+
+        if self.schedule.is_restart(epoch) {
+            info!("restarting the learning rate schedule at epoch {epoch}");
+        }
+
+        let lr = self.schedule.learning_rate(*self.base_lr, epoch);
+
+        let Some(lr) = FloatPositive::new(lr) else {
+            warn!(
+                "the schedule's learning rate for epoch {epoch} isn't positive ({lr}), keeping the current one"
+            );
+            return;
+        };
+
+        store.set_learning_rate(lr);
+    }
+
+    /// Replaces the learning rate the schedule starts from, the next epochs follow it
+    /// from the new one.
+    ///
+    /// # Args
+    /// * `base_lr` - The new learning rate to schedule from.
+    pub fn set_base_lr(&mut self, base_lr: FloatPositive) {
+        self.base_lr = base_lr;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use machine_learning::{
+        initialization::ConstParamGen,
+        optimization::{GradientDescent, WarmRestarts},
+    };
+
+    use super::*;
+    use crate::storage::BlockingStore;
+
+    const BASE_LR: f32 = 0.1;
+    const MIN_LR: f32 = 0.02;
+
+    /// Takes a step with a unit gradient and returns how much the parameter moved.
+    fn step_size<PS: Store>(store: &PS) -> f32 {
+        let (mut before, mut after) = ([0.0], [0.0]);
+        store.pull_params(&mut before).unwrap();
+        store.accumulate(&[1.0]).unwrap();
+        store.update_params();
+        store.pull_params(&mut after).unwrap();
+        before[0] - after[0]
+    }
+
+    #[test]
+    fn test_the_learning_rate_follows_the_reported_epochs() {
+        let shard_size = NonZeroUsize::MIN;
+        let mut param_gen = ConstParamGen::new(0.0, 1);
+        let base_lr = FloatPositive::new(BASE_LR).unwrap();
+        let store = BlockingStore::new(shard_size, &mut param_gen, |_| {
+            GradientDescent::new(base_lr)
+        });
+
+        let t_0 = NonZeroUsize::new(2).unwrap();
+        let schedule = WarmRestarts::new(t_0, NonZeroUsize::MIN, MIN_LR);
+        let mut lr_scheduler = LrScheduler::new(Box::new(schedule), base_lr);
+
+        lr_scheduler.step(&store, 1);
+        let half_way = (BASE_LR + MIN_LR) / 2.0;
+        assert!((step_size(&store) - half_way).abs() < 1e-6);
+
+        // Older epochs, as the ones published late by a slower worker, are ignored.
+        lr_scheduler.step(&store, 0);
+        assert!((step_size(&store) - half_way).abs() < 1e-6);
+
+        lr_scheduler.step(&store, 2);
+        assert!((step_size(&store) - BASE_LR).abs() < 1e-6);
+    }
+}
@@ -1,7 +1,11 @@
 mod builder;
+mod lr_scheduler;
 mod pserver;
 mod server;
+mod staleness_tracker;
 
 pub use builder::ServerBuilder;
+pub use lr_scheduler::LrScheduler;
 pub use pserver::ParameterServer;
 pub use server::Server;
+pub use staleness_tracker::StalenessTracker;
@@ -1,11 +1,35 @@
-use std::io;
+use std::{future, io, sync::Arc};
 
-use comms::{OrchEvent, OrchHandle, TransportLayer, WorkerEvent, WorkerHandle};
+use comms::{
+    OrchEvent, OrchHandle, TransportLayer, WorkerEvent, WorkerHandle,
+    specs::server::{StalenessStats, UpdateRule},
+};
 use log::{debug, error, info, warn};
-use tokio::task::JoinSet;
+use tokio::{
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        oneshot,
+    },
+    task::JoinSet,
+};
 
-use super::Server;
-use crate::{storage::Store, synchronization::Synchronizer};
+use super::{LrScheduler, Server, StalenessTracker};
+use crate::{
+    storage::{ParamServerErr, Store},
+    synchronization::Synchronizer,
+};
+
+/// The amount of consecutive slow sends after which a worker is reported as slow.
+const SLOW_STREAK: usize = 3;
+
+/// An event of the orchestrator along with the sender of the server's reply to it.
+type OrchRequest = (OrchEvent, oneshot::Sender<OrchReply>);
+
+/// The server's reply to an orchestrator's request.
+enum OrchReply {
+    Params(Vec<f32>),
+    Staleness(StalenessStats),
+}
 
 /// The central server structure, it handles task management and io between workers.
 pub struct ParameterServer<PS, Sy, T>
@@ -17,7 +41,14 @@ where
     tasks: JoinSet<io::Result<()>>,
     store: PS,
     synchronizer: Sy,
-    orch_handle: OrchHandle<T>,
+    orch_handle: Option<OrchHandle<T>>,
+    released_orch_handle: Option<OrchHandle<T>>,
+    allow_worker_loss: bool,
+    next_id: usize,
+    joiner_rx: Option<Receiver<WorkerHandle<T>>>,
+    lr_scheduler: Option<LrScheduler>,
+    staleness_tracker: Option<Arc<StalenessTracker>>,
+    update_rule: UpdateRule,
 }
 
 impl<PS, Sy, T> ParameterServer<PS, Sy, T>
@@ -39,23 +70,147 @@ where
             tasks: JoinSet::new(),
             store,
             synchronizer,
-            orch_handle,
+            orch_handle: Some(orch_handle),
+            released_orch_handle: None,
+            allow_worker_loss: false,
+            next_id: 1,
+            joiner_rx: None,
+            lr_scheduler: None,
+            staleness_tracker: None,
+            update_rule: UpdateRule::default(),
         }
     }
+
+    /// Sets whether the server keeps training with the remaining workers when a worker's
+    /// connection drops, instead of failing the whole run.
+    ///
+    /// The lost worker leaves the synchronizer, so subsequent steps stop waiting for it.
+    ///
+    /// # Args
+    /// * `allow` - Whether to tolerate worker losses.
+    ///
+    /// # Returns
+    /// The modified `ParameterServer`.
+    pub fn with_worker_loss(mut self, allow: bool) -> Self {
+        self.allow_worker_loss = allow;
+        self
+    }
+
+    /// Sets the schedule the learning rate of the store follows during the training.
+    ///
+    /// # Args
+    /// * `lr_scheduler` - The learning rate scheduler.
+    ///
+    /// # Returns
+    /// The modified `ParameterServer`.
+    pub fn with_lr_scheduler(mut self, lr_scheduler: LrScheduler) -> Self {
+        self.lr_scheduler = Some(lr_scheduler);
+        self
+    }
+
+    /// Tracks how stale the gradients applied by the server are, logging every one of them.
+    ///
+    /// # Returns
+    /// The modified `ParameterServer`.
+    pub fn with_staleness_tracking(mut self) -> Self {
+        self.staleness_tracker = Some(Arc::new(StalenessTracker::new()));
+        self
+    }
+
+    /// Sets whether a worker gets the parameters before or after it's gradient is applied.
+    ///
+    /// Sending them first copies every gradient, since it's received into the same
+    /// buffer the parameters are sent from.
+    ///
+    /// # Args
+    /// * `update_rule` - The order of the update and the send.
+    ///
+    /// # Returns
+    /// The modified `ParameterServer`.
+    pub fn with_update_rule(mut self, update_rule: UpdateRule) -> Self {
+        self.update_rule = update_rule;
+        self
+    }
+
+    /// Opens the server to workers joining after the training started.
+    ///
+    /// Every worker handle sent through the returned sender while the server is running
+    /// gets bootstrapped with the current parameters and takes part in the training from
+    /// the next synchronized step onwards.
+    ///
+    /// # Args
+    /// * `capacity` - The maximum amount of workers that can be queued to join.
+    ///
+    /// # Returns
+    /// The sender for the late joining worker handles.
+    pub fn joiner(&mut self, capacity: usize) -> Sender<WorkerHandle<T>> {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        self.joiner_rx = Some(rx);
+        tx
+    }
+
+    /// Takes back the orchestrator's handle once the orchestrator disconnected the server,
+    /// so the node can wait on the same connection for it's next session.
+    ///
+    /// # Returns
+    /// The orchestrator's handle or `None` if the server didn't run until being disconnected.
+    pub fn release(&mut self) -> Option<OrchHandle<T>> {
+        self.released_orch_handle.take()
+    }
 }
 
 impl<PS, Sy, T> ParameterServer<PS, Sy, T>
 where
-    PS: Store,
-    Sy: Synchronizer,
-    T: TransportLayer,
+    PS: Store + Send + Sync + 'static,
+    Sy: Synchronizer + 'static,
+    T: TransportLayer + Send + 'static,
 {
     /// Starts the training process with the spawned workers.
     ///
+    /// The orchestrator's events are read by a task of their own and forwarded to the
+    /// server, so none of them is dropped halfway through when a worker's task ends first.
+    ///
     /// # Returns
     /// The trained parameters of the model.
     pub async fn run(&mut self) -> io::Result<()> {
-        while let Some(ret) = self.tasks.join_next().await {
+        let Some(orch_handle) = self.orch_handle.take() else {
+            return Err(io::Error::other("the server already ran"));
+        };
+
+        let (events_tx, mut events_rx) = mpsc::channel(1);
+        let mut orch_task = JoinSet::new();
+        orch_task.spawn(serve_orch(orch_handle, events_tx));
+        let mut deferred = Vec::new();
+
+        loop {
+            let ret = tokio::select! {
+                biased;
+                Some(worker_handle) = recv_joiner(&mut self.joiner_rx) => {
+                    self.spawn_task(worker_handle, true);
+                    continue;
+                }
+                request = events_rx.recv() => {
+                    match request {
+                        Some(Ok((OrchEvent::RequestSnapshot, reply))) => self.reply_snapshot(reply),
+                        Some(Ok((event, reply))) => {
+                            let event = self.handle_training_event(event);
+                            deferred.extend(event.map(|event| (event, reply)));
+                        }
+                        // The orchestrator has nothing to say while the workers train.
+                        Some(Err(e)) if e.kind() == io::ErrorKind::TimedOut => {}
+                        Some(Err(e)) => return Err(e),
+                        None => return Err(orch_task_err(&mut orch_task).await),
+                    }
+
+                    continue;
+                }
+                ret = self.tasks.join_next() => ret,
+            };
+
+            let Some(ret) = ret else {
+                break;
+            };
+
             match ret {
                 Ok(Err(e)) => {
                     error!("worker task failed with error: {e}");
@@ -76,14 +231,47 @@ where
         //         the amount of parameters in the storage.
         self.store.pull_params(&mut params).unwrap();
 
+        let mut deferred = deferred.into_iter();
+
         loop {
-            let event = self.orch_handle.recv_event().await?;
+            let (event, reply) = match deferred.next() {
+                Some(request) => request,
+                None => match events_rx.recv().await {
+                    Some(request) => request?,
+                    None => return Err(orch_task_err(&mut orch_task).await),
+                },
+            };
 
-            match event {
+            // The orchestrator's task only fails to get a reply once it stopped.
+            let _ = match event {
                 OrchEvent::Disconnect => break,
-                OrchEvent::RequestParams => self.orch_handle.push_params(&mut params).await?,
-                event => warn!("Unexpected OrchEvent: {event:?}"),
-            }
+                // The last epochs can be reported after the workers left.
+                OrchEvent::SetEpoch { .. } => continue,
+                OrchEvent::RequestParams | OrchEvent::RequestSnapshot => {
+                    reply.send(OrchReply::Params(params.clone()))
+                }
+                OrchEvent::RequestEmaParams => {
+                    let mut ema = vec![0.0; nparams];
+                    self.store.pull_ema(&mut ema).map_err(io::Error::other)?;
+                    reply.send(OrchReply::Params(ema))
+                }
+                OrchEvent::RequestStaleness => {
+                    let Some(ref staleness_tracker) = self.staleness_tracker else {
+                        let text = "The server doesn't track the staleness of it's gradients";
+                        return Err(io::Error::other(text));
+                    };
+
+                    reply.send(OrchReply::Staleness(staleness_tracker.stats()))
+                }
+                event => {
+                    warn!("Unexpected OrchEvent: {event:?}");
+                    continue;
+                }
+            };
+        }
+
+        if let Some(ret) = orch_task.join_next().await {
+            self.released_orch_handle = ret.map_err(io::Error::other)??;
         }
 
         Ok(())
@@ -96,27 +284,108 @@ where
     Sy: Synchronizer + 'static,
     T: TransportLayer,
 {
+    /// Handles an event sent by the orchestrator while the workers are training.
+    ///
+    /// # Args
+    /// * `event` - The orchestrator's event.
+    ///
+    /// # Returns
+    /// The event if it's meant for once the training ended, such as a request for the
+    /// parameters sent as the last workers were leaving.
+    fn handle_training_event(&mut self, event: OrchEvent) -> Option<OrchEvent> {
+        match event {
+            OrchEvent::SetLearningRate { lr } => {
+                info!("setting the learning rate to {}", *lr);
+                self.store.set_learning_rate(lr);
+
+                if let Some(ref mut lr_scheduler) = self.lr_scheduler {
+                    lr_scheduler.set_base_lr(lr);
+                }
+
+                None
+            }
+            OrchEvent::SetEpoch { epoch } => {
+                match self.lr_scheduler {
+                    Some(ref mut lr_scheduler) => lr_scheduler.step(&self.store, epoch),
+                    None => debug!("ignoring epoch {epoch}, the learning rate is constant"),
+                }
+
+                None
+            }
+            event => Some(event),
+        }
+    }
+
+    /// Replies to the orchestrator with the parameters the store holds at this moment of
+    /// the training.
+    ///
+    /// # Args
+    /// * `reply` - The sender of the reply to the orchestrator's request.
+    fn reply_snapshot(&self, reply: oneshot::Sender<OrchReply>) {
+        let mut params = vec![0.0; self.store.len()];
+
+        // SAFETY: The parameter vector is the same size as
+        //         the amount of parameters in the storage.
+        self.store.pull_params(&mut params).unwrap();
+
+        // The orchestrator's task only fails to get the reply once it stopped.
+        let _ = reply.send(OrchReply::Params(params));
+    }
+
     /// Binds a new worker to this server and spawns it's own training task.
     ///
     /// # Args
     /// * `worker_handle` - The handle for a worker connection.
-    pub fn spawn(&mut self, mut worker_handle: WorkerHandle<T>)
+    pub fn spawn(&mut self, worker_handle: WorkerHandle<T>)
     where
         T: TransportLayer + Send + 'static,
     {
-        let id = self.tasks.len() + 1;
+        self.spawn_task(worker_handle, false);
+    }
+
+    /// Spawns the training task for a worker.
+    ///
+    /// # Args
+    /// * `worker_handle` - The handle for a worker connection.
+    /// * `late` - Whether the worker joined after the training started.
+    fn spawn_task(&mut self, mut worker_handle: WorkerHandle<T>, late: bool)
+    where
+        T: TransportLayer + Send + 'static,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+
         let store = self.store.clone();
-        let synchronizer = self.synchronizer.clone();
+        let mut synchronizer = self.synchronizer.clone();
+        let allow_worker_loss = self.allow_worker_loss;
+        let staleness_tracker = self.staleness_tracker.clone();
+        let send_first = self.update_rule == UpdateRule::SendThenUpdate;
+
+        if let Some(samples) = worker_handle.samples() {
+            synchronizer.set_samples(samples);
+        }
+
+        let serve = async move {
+            if late {
+                let step = synchronizer.join().await;
+                info!(worker_id = id; "worker joined the training at step {step}");
+            }
 
-        let task = async move {
             let nparams = store.len();
             let mut params = vec![0.0; nparams];
 
             // SAFETY: This buffer is the same size as the
             //         amount of parameters in the storage.
             store.pull_params(&mut params).unwrap();
+            let mut seen = staleness_tracker.as_ref().map_or(0, |t| t.version());
             worker_handle.push_params(&mut params).await?;
 
+            let mut pending = if send_first {
+                vec![0.0; nparams]
+            } else {
+                Vec::new()
+            };
+
             loop {
                 debug!(worker_id = id; "waiting to receive a message");
 
@@ -124,18 +393,46 @@ where
                     WorkerEvent::RequestParams => {
                         debug!(worker_id = id; "sending parameters");
                         worker_handle.push_params(&mut params).await?;
+                        warn_if_slow(id, &worker_handle);
                     }
                     WorkerEvent::Grad(grad) if nparams == grad.len() => {
                         debug!(worker_id = id; "received gradient, applying step");
 
+                        let mut sent = None;
+                        let grad = if send_first {
+                            pending.copy_from_slice(grad);
+
+                            // SAFETY: This buffer is the same size as the
+                            //         amount of parameters in the storage.
+                            store.pull_params(&mut params).unwrap();
+                            sent = staleness_tracker.as_ref().map(|t| t.version());
+                            worker_handle.push_params(&mut params).await?;
+                            warn_if_slow(id, &worker_handle);
+                            &pending
+                        } else {
+                            grad
+                        };
+
+                        if let Some(ref staleness_tracker) = staleness_tracker {
+                            let (staleness, version) = staleness_tracker.record(seen);
+                            info!(worker_id = id; "applying a gradient {staleness} updates stale");
+                            seen = sent.unwrap_or(version);
+                        }
+
                         // SAFETY: We checked that the gradient is the same
                         //         size as the buffer and the storage.
-                        synchronizer
-                            .step(&store, grad, &mut params)
-                            .await
-                            .map_err(io::Error::other)?;
+                        match synchronizer.step(&store, grad, &mut params).await {
+                            Err(ParamServerErr::DroppedLaggard) => {
+                                warn!(worker_id = id; "the worker lagged behind the barrier and was dropped from the training");
+                                break;
+                            }
+                            ret => ret.map_err(io::Error::other)?,
+                        }
 
-                        worker_handle.push_params(&mut params).await?;
+                        if !send_first {
+                            worker_handle.push_params(&mut params).await?;
+                            warn_if_slow(id, &worker_handle);
+                        }
                     }
                     WorkerEvent::Disconnect => {
                         info!(worker_id = id; "gracefully disconnecting worker");
@@ -158,10 +455,145 @@ where
             Ok(())
         };
 
+        let task = async move {
+            match serve.await {
+                Err(e) if allow_worker_loss && is_connection_loss(&e) => {
+                    warn!(worker_id = id; "lost connection with the worker, continuing with the remaining ones: {e}");
+                    Ok(())
+                }
+                ret => ret,
+            }
+        };
+
         self.tasks.spawn(task);
     }
 }
 
+/// Receives the next late joining worker, if the server accepts them.
+///
+/// # Args
+/// * `joiner_rx` - The optional receiver of late joining worker handles.
+///
+/// # Returns
+/// The worker handle or `None` if the joiners' sender was dropped.
+async fn recv_joiner<T>(
+    joiner_rx: &mut Option<Receiver<WorkerHandle<T>>>,
+) -> Option<WorkerHandle<T>>
+where
+    T: TransportLayer,
+{
+    match joiner_rx {
+        Some(rx) => rx.recv().await,
+        None => future::pending().await,
+    }
+}
+
+/// Reads the orchestrator's events and forwards them to the server, pushing back the
+/// server's replies to the ones that expect one.
+///
+/// Being the only reader of the orchestrator's handle, none of it's reads is ever cancelled
+/// halfway through a message.
+///
+/// # Args
+/// * `orch_handle` - The handle for communicating with the orchestrator.
+/// * `events_tx` - The sender of the orchestrator's events to the server.
+///
+/// # Returns
+/// The orchestrator's handle once it disconnected the server, `None` if the server stopped
+/// listening first, or an io error if one occurred while pushing a reply.
+async fn serve_orch<T>(
+    mut orch_handle: OrchHandle<T>,
+    events_tx: Sender<io::Result<OrchRequest>>,
+) -> io::Result<Option<OrchHandle<T>>>
+where
+    T: TransportLayer,
+{
+    loop {
+        let event = match orch_handle.recv_event().await {
+            Ok(event) => event,
+            Err(e) => {
+                let timed_out = e.kind() == io::ErrorKind::TimedOut;
+
+                if events_tx.send(Err(e)).await.is_err() || !timed_out {
+                    return Ok(None);
+                }
+
+                continue;
+            }
+        };
+
+        let disconnect = matches!(event, OrchEvent::Disconnect);
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        if events_tx.send(Ok((event, reply_tx))).await.is_err() {
+            return Ok(None);
+        }
+
+        // The server drops the reply's sender of the events it doesn't answer.
+        match reply_rx.await {
+            Ok(OrchReply::Params(mut params)) => orch_handle.push_params(&mut params).await?,
+            Ok(OrchReply::Staleness(stats)) => orch_handle.push_staleness(stats).await?,
+            Err(_) => {}
+        }
+
+        if disconnect {
+            return Ok(Some(orch_handle));
+        }
+    }
+}
+
+/// Waits for the orchestrator's task once it stopped forwarding events.
+///
+/// # Args
+/// * `orch_task` - The set holding the orchestrator's task.
+///
+/// # Returns
+/// The error the task failed with or a connection loss if it stopped on it's own.
+async fn orch_task_err<R: 'static>(orch_task: &mut JoinSet<io::Result<R>>) -> io::Error {
+    match orch_task.join_next().await {
+        Some(Ok(Err(e))) => e,
+        Some(Err(e)) => io::Error::other(e),
+        _ => io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "the orchestrator stopped before the server did",
+        ),
+    }
+}
+
+/// Warns when the worker has been slow to drain the last `SLOW_STREAK` sends.
+///
+/// # Args
+/// * `id` - The id of the worker's task.
+/// * `worker_handle` - The handle of the worker.
+fn warn_if_slow<T: TransportLayer>(id: usize, worker_handle: &WorkerHandle<T>) {
+    let stats = worker_handle.flush_stats();
+
+    if stats.slow_streak == SLOW_STREAK {
+        warn!(
+            worker_id = id;
+            "the worker is slow to drain the parameters, the last send took {:?} (mean {:?}, max {:?})",
+            stats.last, stats.mean(), stats.max
+        );
+    }
+}
+
+/// Whether the given error means the peer's connection was dropped.
+///
+/// # Args
+/// * `e` - The io error.
+///
+/// # Returns
+/// `true` if the connection was lost.
+fn is_connection_loss(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+    )
+}
+
 #[async_trait::async_trait]
 impl<T, PS, Sy> Server<T> for ParameterServer<PS, Sy, T>
 where
@@ -176,4 +608,12 @@ where
     fn spawn(&mut self, worker_handle: WorkerHandle<T>) {
         self.spawn(worker_handle)
     }
+
+    fn joiner(&mut self, capacity: usize) -> Sender<WorkerHandle<T>> {
+        self.joiner(capacity)
+    }
+
+    fn release(&mut self) -> Option<OrchHandle<T>> {
+        self.release()
+    }
 }
@@ -1,6 +1,7 @@
 use std::io;
 
-use comms::{TransportLayer, WorkerHandle};
+use comms::{OrchHandle, TransportLayer, WorkerHandle};
+use tokio::sync::mpsc::Sender;
 
 /// This trait acts as an indirection layer, allowing the `ServerBuilder` to return
 /// and manage different `ParameterServer` configurations from it's unique build method.
@@ -17,4 +18,19 @@ where
     /// # Args
     /// * `worker_handle` - The handle to enable communication with the worker.
     fn spawn(&mut self, worker_handle: WorkerHandle<T>);
+
+    /// Indirection method for `ParameterServer::joiner`
+    ///
+    /// # Args
+    /// * `capacity` - The maximum amount of workers that can be queued to join.
+    ///
+    /// # Returns
+    /// The sender for the late joining worker handles.
+    fn joiner(&mut self, capacity: usize) -> Sender<WorkerHandle<T>>;
+
+    /// Indirection method for `ParameterServer::release`
+    ///
+    /// # Returns
+    /// The orchestrator's handle or `None` if the server didn't run until being disconnected.
+    fn release(&mut self) -> Option<OrchHandle<T>>;
 }
@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use comms::specs::server::StalenessStats;
+
+/// Tracks how stale the gradients applied by the server are.
+///
+/// The parameters are versioned by the amount of gradients applied to them, every worker
+/// task remembers the version it last sent to it's worker and the difference with the
+/// current one when the worker's gradient arrives is that gradient's staleness.
+#[derive(Debug, Default)]
+pub struct StalenessTracker {
+    version: AtomicUsize,
+    total: AtomicUsize,
+    max: AtomicUsize,
+}
+
+impl StalenessTracker {
+    /// Creates a new `StalenessTracker`.
+    ///
+    /// # Returns
+    /// A new `StalenessTracker` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current version of the parameters.
+    ///
+    /// # Returns
+    /// The amount of gradients applied so far.
+    pub fn version(&self) -> usize {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Records a gradient that's about to be applied.
+    ///
+    /// # Args
+    /// * `seen` - The version of the parameters the gradient was computed over.
+    ///
+    /// # Returns
+    /// The staleness of the gradient along with the version of the parameters once it's applied.
+    pub fn record(&self, seen: usize) -> (usize, usize) {
+        let version = self.version.fetch_add(1, Ordering::AcqRel);
+        let staleness = version.saturating_sub(seen);
+
+        self.total.fetch_add(staleness, Ordering::Relaxed);
+        self.max.fetch_max(staleness, Ordering::Relaxed);
+        (staleness, version + 1)
+    }
+
+    /// Summarizes the staleness of the gradients recorded so far.
+    ///
+    /// # Returns
+    /// The staleness statistics.
+    pub fn stats(&self) -> StalenessStats {
+        let updates = self.version();
+        let total = self.total.load(Ordering::Relaxed);
+
+        StalenessStats {
+            updates,
+            mean: if updates == 0 {
+                0.0
+            } else {
+                total as f64 / updates as f64
+            },
+            max: self.max.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_a_delayed_worker_grows_staler() {
+        let tracker = StalenessTracker::new();
+        let slow = tracker.version();
+        let mut fast = tracker.version();
+
+        for _ in 0..3 {
+            let (staleness, version) = tracker.record(fast);
+            assert_eq!(staleness, 0);
+            fast = version;
+        }
+
+        let (first, slow) = tracker.record(slow);
+
+        for _ in 0..5 {
+            fast = tracker.record(fast).1;
+        }
+
+        let (second, _) = tracker.record(slow);
+        assert_eq!((first, second), (3, 5));
+
+        // The fast worker's first gradient after the slow one's was one update behind.
+        let stats = tracker.stats();
+        assert_eq!(stats.updates, 10);
+        assert_eq!(stats.max, 5);
+        assert_eq!(stats.mean, (3 + 1 + 5) as f64 / 10.0);
+    }
+}
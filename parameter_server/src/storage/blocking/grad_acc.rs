@@ -0,0 +1,152 @@
+use std::ops::Range;
+
+/// A buffer the gradients of a shard are added up in until they're applied.
+///
+/// Adding many small gradients to a large `f32` sum rounds them away, so the wide
+/// accumulator adds them up in `f64` and only casts the sum back to `f32` for the update.
+/// The mixed one does so only for some ranges of the shard, keeping `f32` sums elsewhere.
+#[derive(Debug)]
+pub enum GradAcc {
+    Narrow(Box<[f32]>),
+    Wide {
+        sum: Box<[f64]>,
+        grad: Box<[f32]>,
+    },
+    Mixed {
+        sum: Box<[f32]>,
+        wide: Vec<(Range<usize>, Box<[f64]>)>,
+    },
+}
+
+impl GradAcc {
+    /// Creates a new `f32` `GradAcc`.
+    ///
+    /// # Args
+    /// * `nparams` - The amount of parameters of the shard.
+    ///
+    /// # Returns
+    /// A new zeroed `GradAcc` instance.
+    pub fn narrow(nparams: usize) -> Self {
+        Self::Narrow(vec![0.; nparams].into_boxed_slice())
+    }
+
+    /// Creates a new `f64` `GradAcc`.
+    ///
+    /// # Args
+    /// * `nparams` - The amount of parameters of the shard.
+    ///
+    /// # Returns
+    /// A new zeroed `GradAcc` instance.
+    pub fn wide(nparams: usize) -> Self {
+        Self::Wide {
+            sum: vec![0.; nparams].into_boxed_slice(),
+            grad: vec![0.; nparams].into_boxed_slice(),
+        }
+    }
+
+    /// Creates a new `GradAcc` adding up only the given ranges in `f64`.
+    ///
+    /// # Args
+    /// * `nparams` - The amount of parameters of the shard.
+    /// * `ranges` - The ranges of the shard's parameters to add up in `f64`.
+    ///
+    /// # Returns
+    /// A new zeroed `GradAcc` instance, a narrow or wide one if the ranges cover none
+    /// or all of the shard.
+    pub fn mixed(nparams: usize, ranges: &[Range<usize>]) -> Self {
+        let widened: usize = ranges.iter().map(|range| range.len()).sum();
+
+        if widened == 0 {
+            return Self::narrow(nparams);
+        }
+
+        if widened >= nparams {
+            return Self::wide(nparams);
+        }
+
+        Self::Mixed {
+            sum: vec![0.; nparams].into_boxed_slice(),
+            wide: ranges
+                .iter()
+                .filter(|range| !range.is_empty())
+                .map(|range| (range.clone(), vec![0.; range.len()].into_boxed_slice()))
+                .collect(),
+        }
+    }
+
+    /// Adds a gradient to the sum.
+    ///
+    /// # Args
+    /// * `grad` - A gradient of the same size as the accumulator.
+    pub fn add(&mut self, grad: &[f32]) {
+        match self {
+            Self::Narrow(sum) => sum.iter_mut().zip(grad).for_each(|(acc, g)| *acc += g),
+            Self::Wide { sum, .. } => sum
+                .iter_mut()
+                .zip(grad)
+                .for_each(|(acc, &g)| *acc += f64::from(g)),
+            Self::Mixed { sum, wide } => {
+                sum.iter_mut().zip(grad).for_each(|(acc, g)| *acc += g);
+
+                for (range, wide_sum) in wide {
+                    wide_sum
+                        .iter_mut()
+                        .zip(&grad[range.clone()])
+                        .for_each(|(acc, &g)| *acc += f64::from(g));
+                }
+            }
+        }
+    }
+
+    /// The accumulated gradient, cast back to `f32` if it was added up in `f64`.
+    ///
+    /// # Returns
+    /// The sum of the added gradients.
+    pub fn grad(&mut self) -> &[f32] {
+        match self {
+            Self::Narrow(sum) => sum,
+            Self::Wide { sum, grad } => {
+                for (g, &acc) in grad.iter_mut().zip(sum.iter()) {
+                    *g = acc as f32;
+                }
+
+                grad
+            }
+            Self::Mixed { sum, wide } => {
+                // The `f32` sum of the wide ranges is overwritten with their `f64` one.
+                for (range, wide_sum) in wide.iter() {
+                    for (g, &acc) in sum[range.clone()].iter_mut().zip(wide_sum.iter()) {
+                        *g = acc as f32;
+                    }
+                }
+
+                sum
+            }
+        }
+    }
+
+    /// Zeroes the sum.
+    pub fn clear(&mut self) {
+        match self {
+            Self::Narrow(sum) => sum.fill(0.),
+            Self::Wide { sum, .. } => sum.fill(0.),
+            Self::Mixed { sum, wide } => {
+                sum.fill(0.);
+                wide.iter_mut().for_each(|(_, wide_sum)| wide_sum.fill(0.));
+            }
+        }
+    }
+
+    /// Whether the sum is zero.
+    ///
+    /// # Returns
+    /// `true` if nothing was added since it was last cleared, or it all added up to zero.
+    #[cfg(test)]
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Self::Narrow(sum) => sum.iter().all(|&g| g == 0.),
+            Self::Wide { sum, .. } => sum.iter().all(|&g| g == 0.),
+            Self::Mixed { sum, .. } => sum.iter().all(|&g| g == 0.),
+        }
+    }
+}
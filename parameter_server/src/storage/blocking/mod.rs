@@ -1,3 +1,4 @@
+mod grad_acc;
 mod shard;
 mod store;
 
@@ -1,7 +1,11 @@
+use std::ops::Range;
+
+use comms::floats::{Float01, FloatPositive};
 use machine_learning::optimization::Optimizer;
 use parking_lot::{Mutex, RwLock};
 
-use crate::storage::{Result, error::ParamServerErr};
+use super::grad_acc::GradAcc;
+use crate::storage::{Ema, Frozen, Momentum, Result, ShardCheckpoint, error::ParamServerErr};
 
 /// A buffer for accumulating gradients and parameters across multiple threads using locks.
 ///
@@ -11,9 +15,12 @@ use crate::storage::{Result, error::ParamServerErr};
 #[derive(Debug)]
 pub struct BlockingShard<O: Optimizer> {
     nparams: usize,
-    grads: [Mutex<Box<[f32]>>; 2],
+    grads: [Mutex<GradAcc>; 2],
     params: RwLock<Box<[f32]>>,
     optimizer: Mutex<O>,
+    ema: Mutex<Option<Ema>>,
+    momentum: Mutex<Option<Momentum>>,
+    frozen: Mutex<Frozen>,
 }
 
 impl<O: Optimizer> BlockingShard<O> {
@@ -31,11 +38,14 @@ impl<O: Optimizer> BlockingShard<O> {
         Self {
             nparams,
             grads: [
-                Mutex::new(vec![0.; nparams].into_boxed_slice()),
-                Mutex::new(vec![0.; nparams].into_boxed_slice()),
+                Mutex::new(GradAcc::narrow(nparams)),
+                Mutex::new(GradAcc::narrow(nparams)),
             ],
             params: RwLock::new(params.into_boxed_slice()),
             optimizer: Mutex::new(optimizer),
+            ema: Mutex::new(None),
+            momentum: Mutex::new(None),
+            frozen: Mutex::new(Frozen::default()),
         }
     }
 
@@ -52,12 +62,7 @@ impl<O: Optimizer> BlockingShard<O> {
             return Err(ParamServerErr::SizeMismatch);
         }
 
-        self.grads[active_idx]
-            .lock()
-            .iter_mut()
-            .zip(grad)
-            .for_each(|(acc, g)| *acc += g);
-
+        self.grads[active_idx].lock().add(grad);
         Ok(())
     }
 
@@ -67,15 +72,46 @@ impl<O: Optimizer> BlockingShard<O> {
     /// * `frozen_idx` - The index of the frozen gradient, must be `0` or `1`.
     pub fn update_params(&self, frozen_idx: usize) {
         let mut params = self.params.write();
-        let mut grad = self.grads[frozen_idx].lock();
+        let mut acc = self.grads[frozen_idx].lock();
+        let mut momentum = self.momentum.lock();
+        let grad = match momentum.as_mut() {
+            Some(momentum) => momentum.smooth(acc.grad()),
+            None => acc.grad(),
+        };
+
+        self.frozen.lock().update(&mut params, |params| {
+            // SAFETY: Both grad and params have the same length.
+            self.optimizer.lock().update_params(grad, params).unwrap();
+        });
 
-        // SAFETY: Both grad and params have the same length.
-        self.optimizer
-            .lock()
-            .update_params(&grad, &mut params)
-            .unwrap();
+        if let Some(ema) = self.ema.lock().as_mut() {
+            ema.update(&params);
+        }
 
-        grad.fill(0.);
+        acc.clear();
+    }
+
+    /// Adds the following gradients up in `f64` instead of `f32`, dropping the ones
+    /// accumulated so far.
+    pub fn enable_f64_accumulation(&self) {
+        for grad in &self.grads {
+            *grad.lock() = GradAcc::wide(self.nparams);
+        }
+    }
+
+    /// Adds the following gradients of the given ranges up in `f64` instead of `f32`,
+    /// dropping the ones accumulated so far.
+    ///
+    /// # Args
+    /// * `ranges` - The ranges of the shard's parameters to widen.
+    pub fn enable_f64_accumulation_for(&self, ranges: &[Range<usize>]) {
+        if ranges.is_empty() {
+            return;
+        }
+
+        for grad in &self.grads {
+            *grad.lock() = GradAcc::mixed(self.nparams, ranges);
+        }
     }
 
     /// Copies the shard's inner parameters into the provided destination buffer.
@@ -94,6 +130,131 @@ impl<O: Optimizer> BlockingShard<O> {
         out.copy_from_slice(&params);
         Ok(())
     }
+
+    /// Starts keeping a moving average of the shard's parameters from their current state.
+    ///
+    /// # Args
+    /// * `decay` - How much of the average is kept on every update.
+    pub fn enable_ema(&self, decay: Float01) {
+        let params = self.params.read();
+        *self.ema.lock() = Some(Ema::new(decay, &params));
+    }
+
+    /// Starts applying a moving average of the shard's gradients instead of the raw ones.
+    ///
+    /// # Args
+    /// * `beta` - How much of the average is kept on every update.
+    pub fn enable_momentum(&self, beta: Float01) {
+        *self.momentum.lock() = Some(Momentum::new(beta, self.nparams));
+    }
+
+    /// Leaves some of the shard's parameters out of every following update.
+    ///
+    /// # Args
+    /// * `frozen` - The frozen ranges of the shard's parameters.
+    pub fn freeze(&self, frozen: Frozen) {
+        *self.frozen.lock() = frozen;
+    }
+
+    /// Copies the moving average of the shard's parameters into the provided destination buffer.
+    ///
+    /// # Args
+    /// * `out` - A mutable slice where the averaged parameters will be copied.
+    ///
+    /// # Returns
+    /// An `EmaDisabled` error if the average isn't being kept or a `SizeMismatchErr`
+    /// if `out` isn't the same size as this shard.
+    pub fn pull_ema(&self, out: &mut [f32]) -> Result<()> {
+        if self.nparams != out.len() {
+            return Err(ParamServerErr::SizeMismatch);
+        }
+
+        let ema = self.ema.lock();
+        let ema = ema.as_ref().ok_or(ParamServerErr::EmaDisabled)?;
+        out.copy_from_slice(ema.params());
+        Ok(())
+    }
+
+    /// Replaces the learning rate of the shard's optimizer.
+    ///
+    /// # Args
+    /// * `learning_rate` - The new learning rate.
+    pub fn set_learning_rate(&self, learning_rate: FloatPositive) {
+        self.optimizer.lock().set_learning_rate(learning_rate);
+    }
+
+    /// Takes a snapshot of the shard's parameters and optimizer state.
+    ///
+    /// # Returns
+    /// The shard's checkpoint.
+    pub fn checkpoint(&self) -> ShardCheckpoint {
+        let params = self.params.read();
+        let optimizer = self.optimizer.lock();
+
+        ShardCheckpoint {
+            params: params.to_vec(),
+            optimizer: optimizer.name().to_string(),
+            optimizer_state: optimizer.state(),
+        }
+    }
+
+    /// Checks that a checkpoint can be restored into this shard without touching it.
+    ///
+    /// # Args
+    /// * `checkpoint` - The shard's checkpoint.
+    /// * `reset_optimizer_state` - Whether the optimizer's state would be zeroed instead of restored.
+    ///
+    /// # Returns
+    /// An `OptimizerMismatch` error if the checkpoint was taken with another optimizer or
+    /// a `SizeMismatch` error if it's size or it's optimizer's state size doesn't match this shard's.
+    pub fn check_restore(
+        &self,
+        checkpoint: &ShardCheckpoint,
+        reset_optimizer_state: bool,
+    ) -> Result<()> {
+        let optimizer = self.optimizer.lock();
+
+        if checkpoint.optimizer != optimizer.name() {
+            return Err(ParamServerErr::OptimizerMismatch {
+                expected: optimizer.name(),
+                got: checkpoint.optimizer.clone(),
+            });
+        }
+
+        if self.nparams != checkpoint.params.len() {
+            return Err(ParamServerErr::SizeMismatch);
+        }
+
+        if !reset_optimizer_state && optimizer.state().len() != checkpoint.optimizer_state.len() {
+            return Err(ParamServerErr::SizeMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Restores the shard's parameters and optimizer state.
+    ///
+    /// # Args
+    /// * `checkpoint` - The shard's checkpoint.
+    /// * `reset_optimizer_state` - Whether to zero the optimizer's state instead of restoring it.
+    ///
+    /// # Returns
+    /// The same errors as `check_restore`, in which case the shard is left untouched.
+    pub fn restore(&self, checkpoint: &ShardCheckpoint, reset_optimizer_state: bool) -> Result<()> {
+        self.check_restore(checkpoint, reset_optimizer_state)?;
+
+        let mut params = self.params.write();
+        let mut optimizer = self.optimizer.lock();
+
+        if reset_optimizer_state {
+            optimizer.reset_state();
+        } else {
+            optimizer.load_state(&checkpoint.optimizer_state)?;
+        }
+
+        params.copy_from_slice(&checkpoint.params);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -114,6 +275,14 @@ mod tests {
         fn learning_rate(&self) -> FloatPositive {
             panic!()
         }
+
+        fn set_learning_rate(&mut self, _: FloatPositive) {
+            panic!()
+        }
+
+        fn name(&self) -> &'static str {
+            "add"
+        }
     }
 
     #[test]
@@ -123,14 +292,8 @@ mod tests {
         shard.accumulate(0, &[1.0, 2.0, 3.0]).unwrap();
         shard.accumulate(0, &[1.0, 1.0, 1.0]).unwrap();
 
-        {
-            let grad0 = shard.grads[0].lock();
-            assert_ne!(**grad0, [0., 0., 0.]);
-        }
-        {
-            let grad1 = shard.grads[1].lock();
-            assert_eq!(**grad1, [0., 0., 0.]);
-        }
+        assert!(!shard.grads[0].lock().is_zero());
+        assert!(shard.grads[1].lock().is_zero());
 
         shard.update_params(0);
 
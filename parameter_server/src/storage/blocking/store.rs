@@ -1,16 +1,21 @@
 use std::{
     num::NonZeroUsize,
+    ops::Range,
     sync::{
         Arc,
         atomic::{AtomicBool, AtomicU8, Ordering},
     },
 };
 
+use comms::floats::{Float01, FloatPositive};
+use log::warn;
 use machine_learning::{initialization::ParamGen, optimization::Optimizer};
 use rayon::prelude::*;
 
 use super::BlockingShard;
-use crate::storage::{ParamServerErr, Result, Store};
+use crate::storage::{
+    Checkpoint, Frozen, ParamServerErr, Result, ResumeConfig, Store, split_ranges,
+};
 
 /// Partitions the model's parameters in shards and leverages
 /// parallelization to read and write data as fast as possible.
@@ -75,6 +80,16 @@ impl<O: Optimizer + Send> Store for BlockingStore<O> {
         self.nparams
     }
 
+    fn shard_ranges(&self) -> Vec<(usize, usize, usize)> {
+        let shard_size = self.shard_size.get();
+
+        (0..self.nparams)
+            .step_by(shard_size)
+            .enumerate()
+            .map(|(i, start)| (i, start, (start + shard_size).min(self.nparams)))
+            .collect()
+    }
+
     fn accumulate(&self, grad: &[f32]) -> Result<()> {
         let active_idx = self.active_idx.load(Ordering::Acquire) as usize;
 
@@ -116,14 +131,91 @@ impl<O: Optimizer + Send> Store for BlockingStore<O> {
 
         Ok(())
     }
+
+    fn set_learning_rate(&self, learning_rate: FloatPositive) {
+        self.shards
+            .iter()
+            .for_each(|shard| shard.set_learning_rate(learning_rate));
+    }
+
+    fn enable_ema(&self, decay: Float01) {
+        self.shards.iter().for_each(|shard| shard.enable_ema(decay));
+    }
+
+    fn enable_momentum(&self, beta: Float01) {
+        self.shards
+            .iter()
+            .for_each(|shard| shard.enable_momentum(beta));
+    }
+
+    fn enable_f64_accumulation(&self) {
+        self.shards
+            .iter()
+            .for_each(|shard| shard.enable_f64_accumulation());
+    }
+
+    fn enable_f64_accumulation_for(&self, ranges: &[Range<usize>]) {
+        let widened = split_ranges(ranges, self.shard_size.get(), self.nparams);
+
+        self.shards
+            .iter()
+            .zip(widened)
+            .for_each(|(shard, ranges)| shard.enable_f64_accumulation_for(&ranges));
+    }
+
+    fn freeze(&self, ranges: &[Range<usize>]) {
+        let frozen = Frozen::split(ranges, self.shard_size.get(), self.nparams);
+
+        self.shards
+            .iter()
+            .zip(frozen)
+            .for_each(|(shard, frozen)| shard.freeze(frozen));
+    }
+
+    fn pull_ema(&self, out: &mut [f32]) -> Result<()> {
+        if self.nparams != out.len() {
+            return Err(ParamServerErr::SizeMismatch);
+        }
+
+        self.shards
+            .par_iter()
+            .zip(out.par_chunks_mut(self.shard_size.get()))
+            .try_for_each(|(shard, out_slice)| shard.pull_ema(out_slice))
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        let shards = self.shards.iter().map(|shard| shard.checkpoint()).collect();
+        Checkpoint::new(shards)
+    }
+
+    fn restore(&self, checkpoint: &Checkpoint, config: ResumeConfig) -> Result<()> {
+        if self.shards.len() != checkpoint.shards().len() {
+            return Err(ParamServerErr::SizeMismatch);
+        }
+
+        let reset = config.reset_optimizer_state;
+
+        // Every shard is checked before any is restored, so a mismatch leaves the store untouched.
+        let mut shards = self.shards.iter().zip(checkpoint.shards());
+        shards.clone().try_for_each(|(shard, shard_checkpoint)| {
+            shard.check_restore(shard_checkpoint, reset)
+        })?;
+
+        if reset {
+            warn!("resuming with the optimizers' state reset, the first updates may be unstable");
+        }
+
+        shards.try_for_each(|(shard, shard_checkpoint)| shard.restore(shard_checkpoint, reset))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::num::NonZeroUsize;
+    use std::{num::NonZeroUsize, slice};
 
     use comms::floats::FloatPositive;
-    use machine_learning::{Result, initialization::ConstParamGen};
+    use machine_learning::{Result, initialization::ConstParamGen, optimization::GradientDescent};
+    use rand::{Rng, SeedableRng, rngs::StdRng};
 
     use super::*;
 
@@ -138,6 +230,14 @@ mod tests {
         fn learning_rate(&self) -> FloatPositive {
             panic!()
         }
+
+        fn set_learning_rate(&mut self, _: FloatPositive) {
+            panic!()
+        }
+
+        fn name(&self) -> &'static str {
+            "add"
+        }
     }
 
     fn create_test_store(params: usize, shard_size: usize) -> BlockingStore<AddOptimizer> {
@@ -162,6 +262,15 @@ mod tests {
         assert_eq!(out, [1.0; PARAMS]);
     }
 
+    #[test]
+    fn test_shard_ranges_cover_every_parameter() {
+        let store = create_test_store(15, 4);
+        assert_eq!(
+            store.shard_ranges(),
+            [(0, 0, 4), (1, 4, 8), (2, 8, 12), (3, 12, 15)]
+        );
+    }
+
     #[test]
     fn test_handle_buffer_swap() {
         const PARAMS: usize = 10;
@@ -183,6 +292,158 @@ mod tests {
         assert_eq!(params, [6.0; PARAMS]);
     }
 
+    #[test]
+    fn test_frozen_range_spanning_shards_is_left_untouched() {
+        const PARAMS: usize = 10;
+        const SHARD_SIZE: usize = 4;
+
+        let store = create_test_store(PARAMS, SHARD_SIZE);
+        store.freeze(&[1..2, 3..7]);
+        store.accumulate(&[1.0; PARAMS]).unwrap();
+        store.update_params();
+
+        let mut params = [0.0; PARAMS];
+        store.pull_params(&mut params).unwrap();
+        assert_eq!(params, [1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_f64_accumulation_keeps_many_small_gradients() {
+        const PARAMS: usize = 4;
+        const SHARD_SIZE: usize = 2;
+        const STEPS: usize = 20_000;
+        const SMALL: f32 = 5e-8;
+
+        let run = |wide: bool| {
+            let store = create_test_store(PARAMS, SHARD_SIZE);
+            if wide {
+                store.enable_f64_accumulation();
+            }
+
+            store.accumulate(&[1.0; PARAMS]).unwrap();
+            for _ in 0..STEPS {
+                store.accumulate(&[SMALL; PARAMS]).unwrap();
+            }
+
+            store.update_params();
+
+            let mut params = [0.0; PARAMS];
+            store.pull_params(&mut params).unwrap();
+            params[0]
+        };
+
+        let expected = 1.0 + STEPS as f64 * SMALL as f64;
+        let narrow_err = (run(false) as f64 - expected).abs();
+        let wide_err = (run(true) as f64 - expected).abs();
+
+        // Every small gradient is rounded away when added to a sum of 1 in f32.
+        assert!(narrow_err > 9e-4, "f32 error {narrow_err}");
+        assert!(wide_err < 1e-6, "f64 error {wide_err}");
+    }
+
+    #[test]
+    fn test_f64_accumulation_for_a_range_only_widens_it() {
+        const PARAMS: usize = 4;
+        const SHARD_SIZE: usize = 2;
+        const STEPS: usize = 20_000;
+        const SMALL: f32 = 5e-8;
+
+        // The range spans both shards, only half of each is widened.
+        let store = create_test_store(PARAMS, SHARD_SIZE);
+        let widened = 1..3;
+        store.enable_f64_accumulation_for(slice::from_ref(&widened));
+
+        store.accumulate(&[1.0; PARAMS]).unwrap();
+        for _ in 0..STEPS {
+            store.accumulate(&[SMALL; PARAMS]).unwrap();
+        }
+
+        store.update_params();
+
+        let mut params = [0.0; PARAMS];
+        store.pull_params(&mut params).unwrap();
+
+        let expected = 1.0 + STEPS as f64 * SMALL as f64;
+        let errs = params.map(|param| (param as f64 - expected).abs());
+
+        assert!(errs[0] > 9e-4 && errs[3] > 9e-4, "f32 errors {errs:?}");
+        assert!(errs[1] < 1e-6 && errs[2] < 1e-6, "f64 errors {errs:?}");
+    }
+
+    #[test]
+    fn test_setting_the_learning_rate_mid_run_scales_the_updates() {
+        const PARAMS: usize = 6;
+
+        let lr = FloatPositive::new(1.0).unwrap();
+        let mut param_gen = ConstParamGen::new(0., PARAMS);
+        let shard_size = NonZeroUsize::new(4).unwrap();
+        let store = BlockingStore::new(shard_size, &mut param_gen, |_| GradientDescent::new(lr));
+
+        let step = || {
+            let mut before = [0.0; PARAMS];
+            store.pull_params(&mut before).unwrap();
+
+            store.accumulate(&[1.0; PARAMS]).unwrap();
+            store.update_params();
+
+            let mut after = [0.0; PARAMS];
+            store.pull_params(&mut after).unwrap();
+            (before[0] - after[0]).abs()
+        };
+
+        let full = step();
+        store.set_learning_rate(FloatPositive::new(0.1).unwrap());
+        let reduced = step();
+
+        assert!((full - 1.0).abs() < 1e-6, "update at lr 1: {full}");
+        assert!((reduced - 0.1).abs() < 1e-6, "update at lr 0.1: {reduced}");
+    }
+
+    #[test]
+    fn test_server_momentum_reduces_the_variance_of_noisy_updates() {
+        const PARAMS: usize = 4;
+        const STEPS: usize = 500;
+
+        let variance = |momentum: Option<Float01>| {
+            let store = create_test_store(PARAMS, 2);
+            if let Some(beta) = momentum {
+                store.enable_momentum(beta);
+            }
+
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut before = [0.0; PARAMS];
+            let mut after = [0.0; PARAMS];
+            let mut updates = Vec::with_capacity(STEPS * PARAMS);
+
+            for _ in 0..STEPS {
+                let grad: Vec<f32> = (0..PARAMS)
+                    .map(|_| 1.0 + rng.random_range(-1.0..1.0))
+                    .collect();
+                store.pull_params(&mut before).unwrap();
+                store.accumulate(&grad).unwrap();
+                store.update_params();
+                store.pull_params(&mut after).unwrap();
+
+                updates.extend(after.iter().zip(&before).map(|(a, b)| a - b));
+            }
+
+            let mean = updates.iter().sum::<f32>() / updates.len() as f32;
+            let variance =
+                updates.iter().map(|u| (u - mean).powi(2)).sum::<f32>() / updates.len() as f32;
+            (mean, variance)
+        };
+
+        let (raw_mean, raw_variance) = variance(None);
+        let (smooth_mean, smooth_variance) = variance(Some(Float01::new(0.9).unwrap()));
+
+        // Both follow the true gradient of 1, the smoothed updates with a fraction of the noise.
+        assert!((raw_mean - 1.0).abs() < 0.1 && (smooth_mean - 1.0).abs() < 0.1);
+        assert!(
+            smooth_variance < raw_variance / 5.0,
+            "variance {smooth_variance} with momentum against {raw_variance} without"
+        );
+    }
+
     #[test]
     fn test_update_locking_mechanism() {
         const PARAMS: usize = 10;
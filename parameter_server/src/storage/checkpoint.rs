@@ -0,0 +1,375 @@
+use std::io::{self, Read, Write};
+
+/// Identifies the checkpoint files.
+const MAGIC: &[u8; 4] = b"ONOC";
+
+/// The version of the checkpoint format.
+const VERSION: u32 = 1;
+
+/// The longest optimizer name a checkpoint may hold.
+const MAX_OPTIMIZER_NAME_LEN: usize = 64;
+
+/// The most floats a shard's parameters or optimizer's state may hold.
+const MAX_FLOATS: usize = u32::MAX as usize;
+
+/// The saved state of a single shard of a store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardCheckpoint {
+    pub params: Vec<f32>,
+    pub optimizer: String,
+    pub optimizer_state: Vec<f32>,
+}
+
+/// How a store is resumed from a checkpoint.
+///
+/// Like the checkpoints themselves it's only part of the storage API, no training setting
+/// resumes the servers from a checkpoint yet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResumeConfig {
+    /// Zeroes the optimizers' moments and velocities instead of restoring them, a cold
+    /// restart of the optimizers' state that warns since the first updates may be unstable.
+    pub reset_optimizer_state: bool,
+}
+
+/// A snapshot of a store's parameters and optimizers' state, shard by shard.
+///
+/// Resuming from the weights alone would reset the optimizers' moments and velocities,
+/// so every shard keeps it's optimizer's state tagged with the optimizer's name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    shards: Vec<ShardCheckpoint>,
+}
+
+impl Checkpoint {
+    /// Creates a new `Checkpoint`.
+    ///
+    /// # Args
+    /// * `shards` - The saved state of each shard, in order.
+    ///
+    /// # Returns
+    /// A new `Checkpoint` instance.
+    pub fn new(shards: Vec<ShardCheckpoint>) -> Self {
+        Self { shards }
+    }
+
+    /// The saved state of each shard.
+    ///
+    /// # Returns
+    /// The shards' checkpoints, in order.
+    pub fn shards(&self) -> &[ShardCheckpoint] {
+        &self.shards
+    }
+
+    /// Writes the checkpoint.
+    ///
+    /// # Args
+    /// * `writer` - Where to write the checkpoint to.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        write_len(&mut writer, self.shards.len())?;
+
+        for shard in &self.shards {
+            write_len(&mut writer, shard.optimizer.len())?;
+            writer.write_all(shard.optimizer.as_bytes())?;
+            write_floats(&mut writer, &shard.params)?;
+            write_floats(&mut writer, &shard.optimizer_state)?;
+        }
+
+        writer.flush()
+    }
+
+    /// Reads a checkpoint written by `Checkpoint::write_to`.
+    ///
+    /// # Args
+    /// * `reader` - Where to read the checkpoint from.
+    ///
+    /// # Returns
+    /// The read checkpoint or an io error if occurred, `InvalidData` if it isn't a valid checkpoint.
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != MAGIC {
+            return Err(invalid_data("Not a checkpoint".into()));
+        }
+
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+
+        if version != VERSION {
+            let text = format!("Unsupported checkpoint version {version}, expected {VERSION}");
+            return Err(invalid_data(text));
+        }
+
+        let nshards = read_len(&mut reader)?;
+        let mut shards = Vec::new();
+
+        for _ in 0..nshards {
+            let len = read_len(&mut reader)?;
+            if len > MAX_OPTIMIZER_NAME_LEN {
+                let text =
+                    format!("Optimizer name of {len} bytes, at most {MAX_OPTIMIZER_NAME_LEN}");
+                return Err(invalid_data(text));
+            }
+
+            let mut optimizer = vec![0; len];
+            reader.read_exact(&mut optimizer)?;
+            let optimizer = String::from_utf8(optimizer)
+                .map_err(|e| invalid_data(format!("Invalid optimizer name: {e}")))?;
+
+            shards.push(ShardCheckpoint {
+                params: read_floats(&mut reader)?,
+                optimizer,
+                optimizer_state: read_floats(&mut reader)?,
+            });
+        }
+
+        Ok(Self { shards })
+    }
+}
+
+/// Creates a new `InvalidData` io error.
+///
+/// # Args
+/// * `text` - The error's description.
+///
+/// # Returns
+/// The io error.
+fn invalid_data(text: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, text)
+}
+
+/// Writes a length as a little endian `u64`.
+///
+/// # Args
+/// * `writer` - Where to write the length to.
+/// * `len` - The length to write.
+///
+/// # Returns
+/// An io error if occurred.
+fn write_len<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
+    writer.write_all(&(len as u64).to_le_bytes())
+}
+
+/// Reads a length written by `write_len`.
+///
+/// # Args
+/// * `reader` - Where to read the length from.
+///
+/// # Returns
+/// The length or an io error if occurred.
+fn read_len<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let mut len = [0; 8];
+    reader.read_exact(&mut len)?;
+    usize::try_from(u64::from_le_bytes(len)).map_err(|e| invalid_data(e.to_string()))
+}
+
+/// Writes a length prefixed slice of little endian floats.
+///
+/// # Args
+/// * `writer` - Where to write the floats to.
+/// * `floats` - The floats to write.
+///
+/// # Returns
+/// An io error if occurred.
+fn write_floats<W: Write>(writer: &mut W, floats: &[f32]) -> io::Result<()> {
+    write_len(writer, floats.len())?;
+
+    for x in floats {
+        writer.write_all(&x.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Reads the floats written by `write_floats`.
+///
+/// The buffer grows as the floats are read, so a corrupt length fails on the end of the
+/// data instead of allocating it upfront.
+///
+/// # Args
+/// * `reader` - Where to read the floats from.
+///
+/// # Returns
+/// The floats or an io error if occurred, `InvalidData` if there are more than `MAX_FLOATS`.
+fn read_floats<R: Read>(reader: &mut R) -> io::Result<Vec<f32>> {
+    let len = read_len(reader)?;
+    if len > MAX_FLOATS {
+        return Err(invalid_data(format!("{len} floats, at most {MAX_FLOATS}")));
+    }
+
+    let mut floats = Vec::new();
+    let mut buf = [0; 4];
+
+    for _ in 0..len {
+        reader.read_exact(&mut buf)?;
+        floats.push(f32::from_le_bytes(buf));
+    }
+
+    Ok(floats)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use comms::floats::{Float01, FloatPositive};
+    use machine_learning::{
+        initialization::ConstParamGen,
+        optimization::{Adam, GradientDescent},
+    };
+
+    use super::*;
+    use crate::storage::{BlockingStore, ParamServerErr, Store};
+
+    const NPARAMS: usize = 10;
+    const NSTEPS: usize = 20;
+    const CHECKPOINT_AT: usize = 7;
+
+    fn adam_store(init: f32) -> BlockingStore<Adam> {
+        let shard_size = NonZeroUsize::new(4).unwrap();
+        let mut param_gen = ConstParamGen::new(init, NPARAMS);
+        let lr = FloatPositive::new(0.05).unwrap();
+        let (b1, b2) = (Float01::new(0.9).unwrap(), Float01::new(0.999).unwrap());
+        let eps = FloatPositive::new(1e-8).unwrap();
+        BlockingStore::new(shard_size, &mut param_gen, |len| {
+            Adam::new(len, lr, b1, b2, eps)
+        })
+    }
+
+    /// Takes the steps of minimizing `(p - i)²` for every parameter `p` at position `i`.
+    fn train<S: Store>(store: &S, steps: usize) {
+        let mut params = vec![0.; NPARAMS];
+
+        for _ in 0..steps {
+            store.pull_params(&mut params).unwrap();
+            let grad: Vec<_> = params
+                .iter()
+                .enumerate()
+                .map(|(i, p)| 2. * (p - i as f32))
+                .collect();
+
+            store.accumulate(&grad).unwrap();
+            store.update_params();
+        }
+    }
+
+    #[test]
+    fn test_resumed_run_follows_the_uninterrupted_trajectory() {
+        let uninterrupted = adam_store(0.5);
+        train(&uninterrupted, NSTEPS);
+
+        let interrupted = adam_store(0.5);
+        train(&interrupted, CHECKPOINT_AT);
+
+        let mut bytes = Vec::new();
+        interrupted.checkpoint().write_to(&mut bytes).unwrap();
+        let checkpoint = Checkpoint::read_from(bytes.as_slice()).unwrap();
+
+        let resumed = adam_store(-3.0);
+        resumed
+            .restore(&checkpoint, ResumeConfig::default())
+            .unwrap();
+        train(&resumed, NSTEPS - CHECKPOINT_AT);
+
+        let (mut expected, mut got) = (vec![0.; NPARAMS], vec![0.; NPARAMS]);
+        uninterrupted.pull_params(&mut expected).unwrap();
+        resumed.pull_params(&mut got).unwrap();
+        assert_eq!(expected, got);
+    }
+
+    #[test]
+    fn test_a_warm_resume_keeps_the_moments_and_a_cold_one_zeroes_them() {
+        let store = adam_store(0.5);
+        train(&store, CHECKPOINT_AT);
+        let checkpoint = store.checkpoint();
+
+        let optimizer_states = |checkpoint: &Checkpoint| -> Vec<_> {
+            let shards = checkpoint.shards().iter();
+            shards.map(|shard| shard.optimizer_state.clone()).collect()
+        };
+
+        let expected = optimizer_states(&checkpoint);
+
+        let warm = adam_store(-3.0);
+        warm.restore(&checkpoint, ResumeConfig::default()).unwrap();
+        assert_eq!(optimizer_states(&warm.checkpoint()), expected);
+
+        let cold = adam_store(-3.0);
+        let config = ResumeConfig {
+            reset_optimizer_state: true,
+        };
+        cold.restore(&checkpoint, config).unwrap();
+
+        let fresh = optimizer_states(&adam_store(0.5).checkpoint());
+        assert_eq!(optimizer_states(&cold.checkpoint()), fresh);
+        assert_ne!(fresh, expected);
+
+        // The parameters are restored either way.
+        let (mut warm_params, mut cold_params) = (vec![0.; NPARAMS], vec![0.; NPARAMS]);
+        warm.pull_params(&mut warm_params).unwrap();
+        cold.pull_params(&mut cold_params).unwrap();
+        assert_eq!(warm_params, cold_params);
+    }
+
+    #[test]
+    fn test_restoring_a_different_optimizer_fails() {
+        let store = adam_store(0.5);
+        train(&store, 2);
+
+        let shard_size = NonZeroUsize::new(4).unwrap();
+        let mut param_gen = ConstParamGen::new(0.5, NPARAMS);
+        let lr = FloatPositive::new(0.05).unwrap();
+        let other = BlockingStore::new(shard_size, &mut param_gen, |_| GradientDescent::new(lr));
+
+        let err = other
+            .restore(&store.checkpoint(), ResumeConfig::default())
+            .unwrap_err();
+        assert!(matches!(err, ParamServerErr::OptimizerMismatch { .. }));
+        assert!(err.to_string().contains("adam"));
+    }
+
+    #[test]
+    fn test_a_failed_restore_leaves_the_store_untouched() {
+        let store = adam_store(0.5);
+        train(&store, CHECKPOINT_AT);
+
+        let mut shards = store.checkpoint().shards().to_vec();
+        shards.last_mut().unwrap().params.pop();
+        let checkpoint = Checkpoint::new(shards);
+
+        let other = adam_store(-3.0);
+        let expected = other.checkpoint();
+
+        let err = other
+            .restore(&checkpoint, ResumeConfig::default())
+            .unwrap_err();
+        assert!(matches!(err, ParamServerErr::SizeMismatch));
+        assert_eq!(other.checkpoint(), expected);
+    }
+
+    #[test]
+    fn test_corrupt_lengths_are_rejected_before_allocating() {
+        let mut bytes = Vec::new();
+        adam_store(0.5).checkpoint().write_to(&mut bytes).unwrap();
+
+        // The first shard's optimizer name length follows the magic, version and shard count.
+        let mut name = bytes.clone();
+        name[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        // And it's parameters' length follows the name.
+        let name_len = "adam".len();
+        let mut params = bytes.clone();
+        params[24 + name_len..32 + name_len].copy_from_slice(&(1u64 << 40).to_le_bytes());
+
+        for bytes in [name, params] {
+            let err = Checkpoint::read_from(bytes.as_slice()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use comms::floats::Float01;
+
+/// An exponential moving average of a shard's parameters.
+///
+/// Follows the parameters after every optimizer step, `ema = decay * ema + (1 - decay) * w`,
+/// smoothing the noise of the last steps out of the trained weights.
+#[derive(Debug)]
+pub struct Ema {
+    decay: f32,
+    params: Box<[f32]>,
+}
+
+impl Ema {
+    /// Creates a new `Ema` starting at the given parameters.
+    ///
+    /// # Args
+    /// * `decay` - How much of the average is kept on every update.
+    /// * `params` - The initial state of the parameters.
+    ///
+    /// # Returns
+    /// A new `Ema` instance.
+    pub fn new(decay: Float01, params: &[f32]) -> Self {
+        Self {
+            decay: *decay,
+            params: params.into(),
+        }
+    }
+
+    /// Moves the average towards the latest parameters.
+    ///
+    /// # Args
+    /// * `params` - The parameters after an optimizer step.
+    pub fn update(&mut self, params: &[f32]) {
+        let decay = self.decay;
+
+        for (avg, &w) in self.params.iter_mut().zip(params) {
+            *avg = decay * *avg + (1.0 - decay) * w;
+        }
+    }
+
+    /// The averaged parameters.
+    ///
+    /// # Returns
+    /// The parameters as a flat slice.
+    pub fn params(&self) -> &[f32] {
+        &self.params
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ema_tracks_but_lags_a_monotonic_trajectory() {
+        let decay = Float01::new(0.9).unwrap();
+        let mut ema = Ema::new(decay, &[0.0, 0.0]);
+        let mut last = 0.0;
+
+        for step in 1..=50 {
+            let w = step as f32;
+            ema.update(&[w, -w]);
+
+            let [avg, neg] = ema.params() else {
+                panic!("the average changed it's size");
+            };
+
+            assert!(*avg > last && *avg < w);
+            assert_eq!(*avg, -*neg);
+            last = *avg;
+        }
+
+        // The lag of an average over a linear trajectory settles at `decay / (1 - decay)`.
+        assert!((50.0 - last - 9.0).abs() < 0.1);
+    }
+}
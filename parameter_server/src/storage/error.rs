@@ -13,17 +13,34 @@ pub type Result<T> = std::result::Result<T, ParamServerErr>;
 #[derive(Debug)]
 pub enum ParamServerErr {
     SizeMismatch,
+    OptimizerMismatch { expected: &'static str, got: String },
+    EmaDisabled,
+    BarrierTimeout,
+    DroppedLaggard,
     Other,
 }
 
 impl Display for ParamServerErr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = match self {
-            Self::SizeMismatch => "The provided buffer length doesn't match the shard size",
-            Self::Other => "Unknown parameter server error",
-        };
-
-        f.write_str(s)
+        match self {
+            Self::SizeMismatch => {
+                f.write_str("The provided buffer length doesn't match the shard size")
+            }
+            Self::OptimizerMismatch { expected, got } => write!(
+                f,
+                "The checkpoint holds the state of a {got} optimizer, but {expected} is configured"
+            ),
+            Self::EmaDisabled => {
+                f.write_str("The store doesn't keep a moving average of it's parameters")
+            }
+            Self::BarrierTimeout => {
+                f.write_str("The workers took longer than the barrier's timeout to synchronize")
+            }
+            Self::DroppedLaggard => {
+                f.write_str("The worker was dropped from the barrier for lagging behind")
+            }
+            Self::Other => f.write_str("Unknown parameter server error"),
+        }
     }
 }
 
@@ -0,0 +1,123 @@
+use std::ops::Range;
+
+/// The ranges of a shard's parameters left out of the optimizer's updates.
+///
+/// The optimizers keep their state over the whole shard, so a partially frozen shard
+/// still takes the step and the frozen parameters are put back right after it.
+#[derive(Debug, Default)]
+pub struct Frozen {
+    ranges: Vec<Range<usize>>,
+    nparams: usize,
+}
+
+impl Frozen {
+    /// Splits the frozen ranges of a store between it's shards.
+    ///
+    /// # Args
+    /// * `ranges` - The frozen ranges of the store's parameters.
+    /// * `shard_size` - The maximum amount of parameters per shard.
+    /// * `nparams` - The amount of parameters in the store.
+    ///
+    /// # Returns
+    /// The frozen ranges of every shard, relative to it's own parameters.
+    pub fn split(ranges: &[Range<usize>], shard_size: usize, nparams: usize) -> Vec<Self> {
+        split_ranges(ranges, shard_size, nparams)
+            .into_iter()
+            .zip((0..nparams).step_by(shard_size))
+            .map(|(ranges, start)| Self {
+                ranges,
+                nparams: (start + shard_size).min(nparams) - start,
+            })
+            .collect()
+    }
+
+    /// Applies an update to the parameters, keeping the frozen ones as they were.
+    ///
+    /// # Args
+    /// * `params` - The shard's parameters.
+    /// * `update` - The optimizer's step, skipped if the whole shard is frozen.
+    pub fn update<F>(&self, params: &mut [f32], update: F)
+    where
+        F: FnOnce(&mut [f32]),
+    {
+        if self.ranges.is_empty() {
+            return update(params);
+        }
+
+        let frozen: usize = self.ranges.iter().map(|range| range.len()).sum();
+        if frozen >= self.nparams {
+            return;
+        }
+
+        let kept: Vec<_> = self
+            .ranges
+            .iter()
+            .flat_map(|range| params[range.clone()].to_vec())
+            .collect();
+
+        update(params);
+
+        let mut kept = kept.as_slice();
+        for range in &self.ranges {
+            let (head, tail) = kept.split_at(range.len());
+            params[range.clone()].copy_from_slice(head);
+            kept = tail;
+        }
+    }
+}
+
+/// Splits ranges of a store's parameters between it's shards.
+///
+/// # Args
+/// * `ranges` - The ranges of the store's parameters.
+/// * `shard_size` - The maximum amount of parameters per shard.
+/// * `nparams` - The amount of parameters in the store.
+///
+/// # Returns
+/// The ranges falling in every shard, relative to it's own parameters.
+pub fn split_ranges(
+    ranges: &[Range<usize>],
+    shard_size: usize,
+    nparams: usize,
+) -> Vec<Vec<Range<usize>>> {
+    (0..nparams)
+        .step_by(shard_size)
+        .map(|start| {
+            let end = (start + shard_size).min(nparams);
+
+            ranges
+                .iter()
+                .map(|range| range.start.max(start)..range.end.min(end))
+                .filter(|range| !range.is_empty())
+                .map(|range| range.start - start..range.end - start)
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_a_range_spanning_shards_is_split_between_them() {
+        let shards = Frozen::split(&[2..7, 8..10], 4, 10);
+        let ranges: Vec<Vec<_>> = shards
+            .iter()
+            .map(|shard| shard.ranges.iter().map(|r| (r.start, r.end)).collect())
+            .collect();
+
+        assert_eq!(ranges, [vec![(2, 4)], vec![(0, 3)], vec![(0, 2)]]);
+
+        // The middle shard keeps it's first three parameters.
+        let mut params = [0.0; 4];
+        shards[1].update(&mut params, |params| params.fill(1.0));
+        assert_eq!(params, [0.0, 0.0, 0.0, 1.0]);
+
+        // The last shard is frozen as a whole, so it's update is skipped.
+        let mut params = [0.0; 2];
+        let mut stepped = false;
+        shards[2].update(&mut params, |_| stepped = true);
+        assert!(!stepped);
+    }
+}
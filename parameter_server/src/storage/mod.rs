@@ -1,9 +1,17 @@
 mod blocking;
+mod checkpoint;
+mod ema;
 mod error;
+mod frozen;
+mod momentum;
 mod store;
 mod wild;
 
 pub use blocking::BlockingStore;
+pub use checkpoint::{Checkpoint, ResumeConfig, ShardCheckpoint};
+pub use ema::Ema;
 pub use error::{ParamServerErr, Result};
+pub use frozen::{Frozen, split_ranges};
+pub use momentum::Momentum;
 pub use store::Store;
 pub use wild::WildStore;
@@ -0,0 +1,55 @@
+use comms::floats::Float01;
+
+/// An exponential moving average of a shard's incoming gradients, the server side momentum.
+///
+/// The optimizer steps over `m = beta * m + (1 - beta) * g` instead of the raw gradient,
+/// smoothing the noise of the workers' batches out of the updates whatever the optimizer.
+#[derive(Debug)]
+pub struct Momentum {
+    beta: f32,
+    avg: Box<[f32]>,
+    started: bool,
+}
+
+impl Momentum {
+    /// Creates a new `Momentum`.
+    ///
+    /// # Args
+    /// * `beta` - How much of the average is kept on every step.
+    /// * `nparams` - The amount of parameters of the shard.
+    ///
+    /// # Returns
+    /// A new `Momentum` instance.
+    pub fn new(beta: Float01, nparams: usize) -> Self {
+        Self {
+            beta: *beta,
+            avg: vec![0.0; nparams].into_boxed_slice(),
+            started: false,
+        }
+    }
+
+    /// Moves the average towards the latest gradient.
+    ///
+    /// The average starts at the first gradient, so the first steps aren't shrunk towards zero.
+    ///
+    /// # Args
+    /// * `grad` - The gradient about to be applied.
+    ///
+    /// # Returns
+    /// The smoothed gradient to apply instead.
+    pub fn smooth(&mut self, grad: &[f32]) -> &[f32] {
+        if !self.started {
+            self.avg.copy_from_slice(grad);
+            self.started = true;
+            return &self.avg;
+        }
+
+        let beta = self.beta;
+
+        for (avg, &g) in self.avg.iter_mut().zip(grad) {
+            *avg = beta * *avg + (1.0 - beta) * g;
+        }
+
+        &self.avg
+    }
+}
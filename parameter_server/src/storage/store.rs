@@ -1,4 +1,8 @@
-use super::Result;
+use std::ops::Range;
+
+use comms::floats::{Float01, FloatPositive};
+
+use super::{Checkpoint, Result, ResumeConfig};
 
 /// Defines the strategy to handle the model's parameters, either block when reading and
 /// writing or embrace race conditions to benefit performance over training stability.
@@ -12,6 +16,13 @@ pub trait Store: Clone {
     /// The amount of parameters in the storage.
     fn len(&self) -> usize;
 
+    /// The boundaries of the storage's shards.
+    ///
+    /// # Returns
+    /// The shards as `(shard_index, start, end)` tuples, the range of the storage's
+    /// parameters each of them holds, in order.
+    fn shard_ranges(&self) -> Vec<(usize, usize, usize)>;
+
     /// Accumulates a new gradient into the storage.
     ///
     /// # Args
@@ -32,4 +43,76 @@ pub trait Store: Clone {
     /// # Returns
     /// A `SizeMismatchErr` if the length of `out` and the size of the storage mismatch.
     fn pull_params(&self, out: &mut [f32]) -> Result<()>;
+
+    /// Replaces the learning rate of every shard's optimizer.
+    ///
+    /// # Args
+    /// * `learning_rate` - The new learning rate.
+    fn set_learning_rate(&self, learning_rate: FloatPositive);
+
+    /// Starts keeping an exponential moving average of the parameters, updated after
+    /// every optimizer step.
+    ///
+    /// # Args
+    /// * `decay` - How much of the average is kept on every step.
+    fn enable_ema(&self, decay: Float01);
+
+    /// Starts applying an exponential moving average of the incoming gradients instead of
+    /// the raw ones, the server side momentum.
+    ///
+    /// # Args
+    /// * `beta` - How much of the average is kept on every step.
+    fn enable_momentum(&self, beta: Float01);
+
+    /// Adds the gradients up in `f64` until they're applied, casting the sum back to `f32`
+    /// for the update, so many small gradients aren't rounded away.
+    ///
+    /// Takes one `f64` buffer per gradient buffer of every shard.
+    fn enable_f64_accumulation(&self);
+
+    /// Adds the gradients of only some of the parameters up in `f64`, as
+    /// `enable_f64_accumulation` does for all of them, keeping `f32` sums elsewhere.
+    ///
+    /// # Args
+    /// * `ranges` - The ranges of parameters to widen, which can span several shards.
+    fn enable_f64_accumulation_for(&self, ranges: &[Range<usize>]);
+
+    /// Leaves some of the parameters out of every following update, they're still pulled.
+    ///
+    /// # Args
+    /// * `ranges` - The ranges of parameters to freeze, which can span several shards.
+    fn freeze(&self, ranges: &[Range<usize>]);
+
+    /// Writes the moving average of the parameters into the given output buffer.
+    ///
+    /// # Args
+    /// * `out` - A mutable slice where the averaged parameters will be copied.
+    ///
+    /// # Returns
+    /// An `EmaDisabled` error if the average isn't being kept or a `SizeMismatchErr`
+    /// if the length of `out` and the size of the storage mismatch.
+    fn pull_ema(&self, out: &mut [f32]) -> Result<()>;
+
+    /// Takes a snapshot of the parameters and the optimizers' state of every shard.
+    ///
+    /// Gradients accumulated but not yet applied aren't part of the snapshot, so it
+    /// should be taken between updates. The server never checkpoints it's store on it's
+    /// own, this is only a storage API for now.
+    ///
+    /// # Returns
+    /// The store's checkpoint.
+    fn checkpoint(&self) -> Checkpoint;
+
+    /// Restores the parameters and the optimizers' state from a checkpoint.
+    ///
+    /// The optimizers' state is never zeroed unless `config` explicitly asks for it.
+    ///
+    /// # Args
+    /// * `checkpoint` - A checkpoint taken from a store with the same layout.
+    /// * `config` - How to resume from the checkpoint.
+    ///
+    /// # Returns
+    /// An `OptimizerMismatch` error if the checkpoint was taken with another optimizer or a
+    /// `SizeMismatch` error if the shards of the checkpoint and the storage mismatch.
+    fn restore(&self, checkpoint: &Checkpoint, config: ResumeConfig) -> Result<()>;
 }
@@ -1,8 +1,9 @@
 use std::cell::UnsafeCell;
 
+use comms::floats::{Float01, FloatPositive};
 use machine_learning::optimization::Optimizer;
 
-use crate::storage::{ParamServerErr, Result};
+use crate::storage::{Ema, Frozen, Momentum, ParamServerErr, Result, ShardCheckpoint};
 
 /// A buffer for accumulating parameters across multiple threads without using locks.
 ///
@@ -11,6 +12,9 @@ pub struct WildShard<O: Optimizer> {
     nparams: usize,
     params: UnsafeCell<Box<[f32]>>,
     optimizer: UnsafeCell<O>,
+    ema: UnsafeCell<Option<Ema>>,
+    momentum: UnsafeCell<Option<Momentum>>,
+    frozen: UnsafeCell<Frozen>,
 }
 
 unsafe impl<O: Optimizer> Send for WildShard<O> {}
@@ -30,6 +34,9 @@ impl<O: Optimizer> WildShard<O> {
             nparams: params.len(),
             params: UnsafeCell::new(params.into_boxed_slice()),
             optimizer: UnsafeCell::new(optimizer),
+            ema: UnsafeCell::new(None),
+            momentum: UnsafeCell::new(None),
+            frozen: UnsafeCell::new(Frozen::default()),
         }
     }
 
@@ -51,9 +58,24 @@ impl<O: Optimizer> WildShard<O> {
         //        For this particular shard implementation we're embracing race conditions.
         let params = unsafe { &mut *self.params.get() };
         let optimizer = unsafe { &mut *self.optimizer.get() };
+        let ema = unsafe { &mut *self.ema.get() };
+        let momentum = unsafe { &mut *self.momentum.get() };
+        let frozen = unsafe { &*self.frozen.get() };
+
+        let grad = match momentum {
+            Some(momentum) => momentum.smooth(grad),
+            None => grad,
+        };
+
+        frozen.update(params, |params| {
+            // SAFETY: Both grad and params have the same length.
+            optimizer.update_params(grad, params).unwrap();
+        });
+
+        if let Some(ema) = ema {
+            ema.update(params);
+        }
 
-        // SAFETY: Both grad and params have the same length.
-        optimizer.update_params(grad, params).unwrap();
         Ok(())
     }
 
@@ -77,4 +99,157 @@ impl<O: Optimizer> WildShard<O> {
         out.copy_from_slice(params);
         Ok(())
     }
+
+    /// Starts keeping a moving average of the shard's parameters from their current state.
+    ///
+    /// # Args
+    /// * `decay` - How much of the average is kept on every update.
+    pub fn enable_ema(&self, decay: Float01) {
+        // SAFETY: Both params and the average are pinned to memory during the `Shard`'s life. It
+        //         will be maintaind valid and initialized during this method's execution.
+        //
+        //        For this particular shard implementation we're embracing race conditions.
+        let params = unsafe { &*self.params.get() };
+        let ema = unsafe { &mut *self.ema.get() };
+
+        *ema = Some(Ema::new(decay, params));
+    }
+
+    /// Starts applying a moving average of the shard's gradients instead of the raw ones.
+    ///
+    /// # Args
+    /// * `beta` - How much of the average is kept on every update.
+    pub fn enable_momentum(&self, beta: Float01) {
+        // SAFETY: The average is pinned to memory during the `Shard`'s life. It is
+        //         set before the training starts updating the parameters.
+        let momentum = unsafe { &mut *self.momentum.get() };
+        *momentum = Some(Momentum::new(beta, self.nparams));
+    }
+
+    /// Leaves some of the shard's parameters out of every following update.
+    ///
+    /// # Args
+    /// * `frozen` - The frozen ranges of the shard's parameters.
+    pub fn freeze(&self, frozen: Frozen) {
+        // SAFETY: The frozen ranges are pinned to memory during the `Shard`'s life. They
+        //         are set before the training starts updating the parameters.
+        let shard_frozen = unsafe { &mut *self.frozen.get() };
+        *shard_frozen = frozen;
+    }
+
+    /// Copies the moving average of the shard's parameters into the provided destination buffer.
+    ///
+    /// # Args
+    /// * `out` - A mutable slice where the averaged parameters will be copied.
+    ///
+    /// # Returns
+    /// An `EmaDisabled` error if the average isn't being kept or a `SizeMismatchErr`
+    /// if `out` isn't the same size as this shard.
+    pub fn pull_ema(&self, out: &mut [f32]) -> Result<()> {
+        if self.nparams != out.len() {
+            return Err(ParamServerErr::SizeMismatch);
+        }
+
+        // SAFETY: The average is pinned to memory during the `Shard`'s life. It will
+        //         be maintaind valid and initialized during this method's execution.
+        let ema = unsafe { &*self.ema.get() };
+        let ema = ema.as_ref().ok_or(ParamServerErr::EmaDisabled)?;
+
+        out.copy_from_slice(ema.params());
+        Ok(())
+    }
+
+    /// Replaces the learning rate of the shard's optimizer.
+    ///
+    /// # Args
+    /// * `learning_rate` - The new learning rate.
+    pub fn set_learning_rate(&self, learning_rate: FloatPositive) {
+        // SAFETY: The optimizer is pinned to memory during the `Shard`'s life. It will
+        //         be maintaind valid and initialized during this method's execution.
+        //
+        //        For this particular shard implementation we're embracing race conditions.
+        let optimizer = unsafe { &mut *self.optimizer.get() };
+        optimizer.set_learning_rate(learning_rate);
+    }
+
+    /// Takes a snapshot of the shard's parameters and optimizer state.
+    ///
+    /// # Returns
+    /// The shard's checkpoint.
+    pub fn checkpoint(&self) -> ShardCheckpoint {
+        // SAFETY: Both params and optimizer are pinned to memory during the `Shard`'s life. It will
+        //         be maintaind valid and initialized during this method's execution.
+        let params = unsafe { &*self.params.get() };
+        let optimizer = unsafe { &*self.optimizer.get() };
+
+        ShardCheckpoint {
+            params: params.to_vec(),
+            optimizer: optimizer.name().to_string(),
+            optimizer_state: optimizer.state(),
+        }
+    }
+
+    /// Checks that a checkpoint can be restored into this shard without touching it.
+    ///
+    /// # Args
+    /// * `checkpoint` - The shard's checkpoint.
+    /// * `reset_optimizer_state` - Whether the optimizer's state would be zeroed instead of restored.
+    ///
+    /// # Returns
+    /// An `OptimizerMismatch` error if the checkpoint was taken with another optimizer or
+    /// a `SizeMismatch` error if it's size or it's optimizer's state size doesn't match this shard's.
+    pub fn check_restore(
+        &self,
+        checkpoint: &ShardCheckpoint,
+        reset_optimizer_state: bool,
+    ) -> Result<()> {
+        // SAFETY: Both params and optimizer are pinned to memory during the `Shard`'s life. It will
+        //         be maintaind valid and initialized during this method's execution.
+        let optimizer = unsafe { &*self.optimizer.get() };
+
+        if checkpoint.optimizer != optimizer.name() {
+            return Err(ParamServerErr::OptimizerMismatch {
+                expected: optimizer.name(),
+                got: checkpoint.optimizer.clone(),
+            });
+        }
+
+        if self.nparams != checkpoint.params.len() {
+            return Err(ParamServerErr::SizeMismatch);
+        }
+
+        if !reset_optimizer_state && optimizer.state().len() != checkpoint.optimizer_state.len() {
+            return Err(ParamServerErr::SizeMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Restores the shard's parameters and optimizer state.
+    ///
+    /// # Args
+    /// * `checkpoint` - The shard's checkpoint.
+    /// * `reset_optimizer_state` - Whether to zero the optimizer's state instead of restoring it.
+    ///
+    /// # Returns
+    /// The same errors as `check_restore`, in which case the shard is left untouched.
+    pub fn restore(&self, checkpoint: &ShardCheckpoint, reset_optimizer_state: bool) -> Result<()> {
+        self.check_restore(checkpoint, reset_optimizer_state)?;
+
+        // SAFETY: Both params and optimizer are pinned to memory during the `Shard`'s life. It will
+        //         be maintaind valid and initialized during this method's execution.
+        //
+        //        For this particular shard implementation we're embracing race conditions.
+        let params = unsafe { &mut *self.params.get() };
+        let optimizer = unsafe { &mut *self.optimizer.get() };
+
+        if reset_optimizer_state {
+            optimizer.reset_state();
+        } else {
+            optimizer.load_state(&checkpoint.optimizer_state)?;
+        }
+
+        params.copy_from_slice(&checkpoint.params);
+        Ok(())
+    }
 }
@@ -1,12 +1,19 @@
-use std::{num::NonZeroUsize, sync::Arc};
+use std::{num::NonZeroUsize, ops::Range, sync::Arc};
 
+use comms::floats::{Float01, FloatPositive};
+use log::warn;
 use machine_learning::{initialization::ParamGen, optimization::Optimizer};
 use rayon::prelude::*;
 
 use super::WildShard;
-use crate::storage::{ParamServerErr, Result, Store};
+use crate::storage::{Checkpoint, Frozen, ParamServerErr, Result, ResumeConfig, Store};
 
 /// A parameter storage with no synchronization, it embraces concurrent reads and writes.
+///
+/// The shards hold plain `f32`s written in place by the optimizer, there are no atomics
+/// behind the updates, so there's no atomic backing to pick per float width. The
+/// optimizers and the wire format are `f32` only as well, a wider store would need them
+/// to be generic over the float type first.
 pub struct WildStore<O: Optimizer> {
     nparams: usize,
     shards: Arc<[WildShard<O>]>,
@@ -66,6 +73,16 @@ impl<O: Optimizer> Store for WildStore<O> {
         self.nparams
     }
 
+    fn shard_ranges(&self) -> Vec<(usize, usize, usize)> {
+        let shard_size = self.shard_size.get();
+
+        (0..self.nparams)
+            .step_by(shard_size)
+            .enumerate()
+            .map(|(i, start)| (i, start, (start + shard_size).min(self.nparams)))
+            .collect()
+    }
+
     /// This method diverges from the trait's definition. It accumulates the grad
     /// directly into the parameters of the model in the same call.
     ///
@@ -108,4 +125,75 @@ impl<O: Optimizer> Store for WildStore<O> {
 
         Ok(())
     }
+
+    fn set_learning_rate(&self, learning_rate: FloatPositive) {
+        self.shards
+            .iter()
+            .for_each(|shard| shard.set_learning_rate(learning_rate));
+    }
+
+    fn enable_ema(&self, decay: Float01) {
+        self.shards.iter().for_each(|shard| shard.enable_ema(decay));
+    }
+
+    fn enable_momentum(&self, beta: Float01) {
+        self.shards
+            .iter()
+            .for_each(|shard| shard.enable_momentum(beta));
+    }
+
+    /// A no-op, the gradients are applied as they arrive instead of being added up.
+    fn enable_f64_accumulation(&self) {
+        warn!("the wild store applies every gradient as it arrives, there's no sum to widen");
+    }
+
+    /// A no-op, the gradients are applied as they arrive instead of being added up.
+    fn enable_f64_accumulation_for(&self, _ranges: &[Range<usize>]) {
+        warn!("the wild store applies every gradient as it arrives, there's no sum to widen");
+    }
+
+    fn freeze(&self, ranges: &[Range<usize>]) {
+        let frozen = Frozen::split(ranges, self.shard_size.get(), self.nparams);
+
+        self.shards
+            .iter()
+            .zip(frozen)
+            .for_each(|(shard, frozen)| shard.freeze(frozen));
+    }
+
+    fn pull_ema(&self, out: &mut [f32]) -> Result<()> {
+        if self.nparams != out.len() {
+            return Err(ParamServerErr::SizeMismatch);
+        }
+
+        self.shards
+            .par_iter()
+            .zip(out.par_chunks_mut(self.shard_size.get()))
+            .try_for_each(|(shard, out_slice)| shard.pull_ema(out_slice))
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        let shards = self.shards.iter().map(|shard| shard.checkpoint()).collect();
+        Checkpoint::new(shards)
+    }
+
+    fn restore(&self, checkpoint: &Checkpoint, config: ResumeConfig) -> Result<()> {
+        if self.shards.len() != checkpoint.shards().len() {
+            return Err(ParamServerErr::SizeMismatch);
+        }
+
+        let reset = config.reset_optimizer_state;
+
+        // Every shard is checked before any is restored, so a mismatch leaves the store untouched.
+        let mut shards = self.shards.iter().zip(checkpoint.shards());
+        shards.clone().try_for_each(|(shard, shard_checkpoint)| {
+            shard.check_restore(shard_checkpoint, reset)
+        })?;
+
+        if reset {
+            warn!("resuming with the optimizers' state reset, the first updates may be unstable");
+        }
+
+        shards.try_for_each(|(shard, shard_checkpoint)| shard.restore(shard_checkpoint, reset))
+    }
 }
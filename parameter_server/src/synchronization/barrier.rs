@@ -1,15 +1,92 @@
-use std::{num::NonZeroUsize, sync::Arc};
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
+use comms::specs::server::{BarrierTimeout, Reduction, TimeoutPolicy};
+use log::warn;
+use parking_lot::Mutex;
 use tokio::task;
 
-use super::{DynBarrier, Synchronizer};
-use crate::storage::{Result, Store};
+use super::{
+    DynBarrier, Synchronizer,
+    dyn_barrier::{Expiry, Wait},
+};
+use crate::storage::{ParamServerErr, Result, Store};
 
 /// Synchronizes parameter updates across multiple workers by waiting for every worker
 /// to collaborate on the current gradient aggregation using a barrier.
-#[derive(Clone)]
+///
+/// Every clone is a place in the barrier and remembers the generation it last saw
+/// it at, so a worker dropped for lagging behind finds out once it arrives.
+///
+/// Every worker is served by it's own task, so the gradients of the workers still
+/// computing keep arriving while the ones already there wait on the barrier. A worker
+/// can't send the gradient of the next step before it gets the updated parameters, so
+/// no gradient lands on the update of a step it wasn't computed for.
 pub struct BarrierSync {
     barrier: Arc<DynBarrier>,
+    reduction: Reduction,
+    timeout: Option<BarrierTimeout>,
+    seen: AtomicUsize,
+    samples: u64,
+    weighted: Arc<Mutex<WeightedGrad>>,
+}
+
+/// The gradients of the current step added up weighted by the samples they were
+/// computed over, for the `Mean` reduction.
+#[derive(Default)]
+struct WeightedGrad {
+    sum: Vec<f32>,
+    samples: u64,
+}
+
+impl WeightedGrad {
+    /// Adds a worker's gradient weighted by it's samples.
+    ///
+    /// # Args
+    /// * `grad` - The worker's gradient.
+    /// * `samples` - The amount of samples the gradient was computed over.
+    ///
+    /// # Returns
+    /// A `SizeMismatch` error if the gradient's size differs from the previous ones.
+    fn add(&mut self, grad: &[f32], samples: u64) -> Result<()> {
+        if self.sum.is_empty() {
+            self.sum.resize(grad.len(), 0.0);
+        }
+
+        if self.sum.len() != grad.len() {
+            return Err(ParamServerErr::SizeMismatch);
+        }
+
+        let weight = samples as f32;
+        for (acc, g) in self.sum.iter_mut().zip(grad) {
+            *acc += g * weight;
+        }
+
+        self.samples += samples;
+        Ok(())
+    }
+
+    /// Accumulates the mean of the added gradients into the store and starts over.
+    ///
+    /// # Args
+    /// * `store` - The store to accumulate the mean into.
+    fn flush<PS: Store>(&mut self, store: &PS) {
+        if self.samples > 0 {
+            let scale = 1.0 / self.samples as f32;
+            self.sum.iter_mut().for_each(|g| *g *= scale);
+
+            // SAFETY: The server only steps with gradients the same size as the store.
+            store.accumulate(&self.sum).unwrap();
+        }
+
+        self.sum.fill(0.0);
+        self.samples = 0;
+    }
 }
 
 impl BarrierSync {
@@ -23,6 +100,97 @@ impl BarrierSync {
     pub fn new(size: NonZeroUsize) -> Self {
         Self {
             barrier: Arc::new(DynBarrier::new(size)),
+            reduction: Reduction::default(),
+            timeout: None,
+            seen: AtomicUsize::new(0),
+            samples: 1,
+            weighted: Arc::default(),
+        }
+    }
+
+    /// Sets how the gradients of the workers are combined, `Sum` by default.
+    ///
+    /// With `Mean` each gradient is weighted by the samples it's worker trains on and the
+    /// step's update is divided by the samples of the workers that took part in it, so
+    /// workers with uneven shards don't bias it and it stays the mean even when workers
+    /// leave or join. A worker that didn't report it's samples weighs as a single one,
+    /// so the update is the plain mean of the gradients when none of them did.
+    ///
+    /// # Args
+    /// * `reduction` - The reduction to apply to the gradients.
+    ///
+    /// # Returns
+    /// The modified `BarrierSync` instance.
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    /// Sets how long a step waits for the rest of the workers and what it does once
+    /// it waited that long, by default it waits for as long as it takes.
+    ///
+    /// # Args
+    /// * `timeout` - The barrier's timeout and policy.
+    ///
+    /// # Returns
+    /// The modified `BarrierSync` instance.
+    pub fn with_timeout(mut self, timeout: BarrierTimeout) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Waits for the rest of the workers following the timeout's policy.
+    ///
+    /// # Args
+    /// * `timeout` - The barrier's timeout and policy.
+    /// * `leader_fn` - The function the leader runs before the barrier triggers.
+    ///
+    /// # Returns
+    /// An error if the workers took too long or this one was dropped for it.
+    fn wait_timeout_with<F>(&self, timeout: BarrierTimeout, mut leader_fn: F) -> Result<()>
+    where
+        F: FnMut(),
+    {
+        let seen = self.seen.load(Ordering::Acquire);
+        let BarrierTimeout { after, policy } = timeout;
+
+        let expiry = match policy {
+            TimeoutPolicy::DropLaggard => Expiry::DropLaggards,
+            TimeoutPolicy::Fail | TimeoutPolicy::RetryOnce(_) => Expiry::Withdraw,
+        };
+
+        let mut wait = self
+            .barrier
+            .wait_timeout_with(seen, after, expiry, &mut leader_fn);
+
+        if let (Wait::TimedOut, TimeoutPolicy::RetryOnce(retry)) = (wait, policy) {
+            warn!("the workers took longer than {after:?} to synchronize, waiting {retry:?} more");
+            wait = self
+                .barrier
+                .wait_timeout_with(seen, retry, expiry, &mut leader_fn);
+        }
+
+        match wait {
+            Wait::Triggered => Ok(()),
+            Wait::DroppedLaggards(n) => {
+                warn!("{n} workers took longer than {after:?} to synchronize, dropping them");
+                Ok(())
+            }
+            Wait::TimedOut => Err(ParamServerErr::BarrierTimeout),
+            Wait::Dropped => Err(ParamServerErr::DroppedLaggard),
+        }
+    }
+}
+
+impl Clone for BarrierSync {
+    fn clone(&self) -> Self {
+        Self {
+            barrier: self.barrier.clone(),
+            reduction: self.reduction,
+            timeout: self.timeout,
+            seen: AtomicUsize::new(self.barrier.generation()),
+            samples: self.samples,
+            weighted: self.weighted.clone(),
         }
     }
 }
@@ -31,8 +199,8 @@ impl Drop for BarrierSync {
     fn drop(&mut self) {
         let barrier = &mut self.barrier;
 
-        if Arc::strong_count(&barrier) > 1 {
-            barrier.acquire();
+        if Arc::strong_count(barrier) > 1 {
+            barrier.acquire(*self.seen.get_mut());
         }
     }
 }
@@ -43,9 +211,327 @@ impl Synchronizer for BarrierSync {
         PS: Store + Send + Sync,
     {
         task::block_in_place(|| {
-            store.accumulate(grad)?;
-            self.barrier.wait_with(|| store.update_params());
+            // A dropped worker's gradient would land on a step it no longer takes part in.
+            if self.barrier.generation() != self.seen.load(Ordering::Acquire) {
+                return Err(ParamServerErr::DroppedLaggard);
+            }
+
+            match self.reduction {
+                Reduction::Sum => store.accumulate(grad)?,
+                Reduction::Mean => self.weighted.lock().add(grad, self.samples)?,
+            }
+
+            let update = || {
+                if let Reduction::Mean = self.reduction {
+                    self.weighted.lock().flush(store);
+                }
+
+                store.update_params();
+            };
+
+            match self.timeout {
+                Some(timeout) => self.wait_timeout_with(timeout, update)?,
+                None => self.barrier.wait_with(update),
+            }
+
+            self.seen.fetch_add(1, Ordering::AcqRel);
             store.pull_params(params)
         })
     }
+
+    async fn join(&self) -> usize {
+        task::block_in_place(|| {
+            self.barrier.join();
+
+            let generation = self.barrier.generation();
+            self.seen.store(generation, Ordering::Release);
+            generation
+        })
+    }
+
+    fn set_samples(&mut self, samples: u64) {
+        self.samples = samples;
+    }
+
+    fn barrier_size(&self) -> usize {
+        self.barrier.size()
+    }
+
+    fn resize(&self, new_size: NonZeroUsize) {
+        self.barrier.resize(new_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ops::Range, time::Duration};
+
+    use comms::floats::FloatPositive;
+    use machine_learning::{initialization::ConstParamGen, optimization::GradientDescent};
+
+    use super::*;
+    use crate::storage::BlockingStore;
+
+    const NWORKERS: usize = 4;
+    const NPARAMS: usize = 3;
+
+    fn store() -> BlockingStore<GradientDescent> {
+        let shard_size = NonZeroUsize::new(NPARAMS).unwrap();
+        let mut param_gen = ConstParamGen::new(0.0, NPARAMS);
+        let optimizer_factory = |_| GradientDescent::new(FloatPositive::new(1.0).unwrap());
+        BlockingStore::new(shard_size, &mut param_gen, optimizer_factory)
+    }
+
+    /// Runs a single barrier step with every worker pushing a gradient of ones.
+    async fn update_after_one_step(reduction: Reduction) -> Vec<f32> {
+        let store = store();
+        let sync = BarrierSync::new(NonZeroUsize::new(NWORKERS).unwrap()).with_reduction(reduction);
+
+        let steps = (0..NWORKERS).map(|_| {
+            let (store, sync) = (store.clone(), sync.clone());
+
+            tokio::spawn(async move {
+                let mut params = [0.0; NPARAMS];
+                sync.step(&store, &[1.0; NPARAMS], &mut params).await
+            })
+        });
+
+        for step in steps.collect::<Vec<_>>() {
+            step.await.unwrap().unwrap();
+        }
+
+        let mut params = vec![0.0; NPARAMS];
+        store.pull_params(&mut params).unwrap();
+        params
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_sum_is_worker_count_times_mean() {
+        let sum = update_after_one_step(Reduction::Sum).await;
+        let mean = update_after_one_step(Reduction::Mean).await;
+
+        for (s, m) in sum.iter().zip(&mean) {
+            assert!((m.abs() - 1.0).abs() < 1e-6);
+            assert!((s / m - NWORKERS as f32).abs() < 1e-6);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_mean_weighs_uneven_workers_by_their_samples() {
+        let per_sample = |k: usize| [k as f32, (k * k) as f32, 1.0];
+        let mean_over = |samples: Range<usize>| {
+            let n = samples.len() as f32;
+            samples
+                .map(per_sample)
+                .fold([0.0; NPARAMS], |mut acc, grad| {
+                    acc.iter_mut().zip(grad).for_each(|(a, g)| *a += g / n);
+                    acc
+                })
+        };
+
+        let store = store();
+        let sync = BarrierSync::new(NonZeroUsize::new(2).unwrap()).with_reduction(Reduction::Mean);
+
+        let steps = [0..2, 2..12].map(|samples| {
+            let (store, mut sync) = (store.clone(), sync.clone());
+            sync.set_samples(samples.len() as u64);
+            let grad = mean_over(samples);
+
+            tokio::spawn(async move {
+                let mut params = [0.0; NPARAMS];
+                sync.step(&store, &grad, &mut params).await
+            })
+        });
+
+        for step in steps {
+            step.await.unwrap().unwrap();
+        }
+
+        let mut params = [0.0; NPARAMS];
+        store.pull_params(&mut params).unwrap();
+
+        for (p, g) in params.iter().zip(mean_over(0..12)) {
+            assert!((p + g).abs() < 1e-4, "{p} != -{g}");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_a_step_only_applies_the_gradients_of_the_same_round() {
+        const ROUNDS: usize = 5;
+
+        let store = store();
+        let sync = BarrierSync::new(NonZeroUsize::new(NWORKERS).unwrap());
+
+        // Every worker races into the next round as soon as it gets the parameters, the
+        // gradients of round `r` are all `r + 1` so any of them leaking into an earlier
+        // update shows in the parameters.
+        let workers: Vec<_> = (0..NWORKERS)
+            .map(|_| {
+                let (store, sync) = (store.clone(), sync.clone());
+
+                tokio::spawn(async move {
+                    let mut rounds = Vec::with_capacity(ROUNDS);
+
+                    for r in 0..ROUNDS {
+                        let grad = [(r + 1) as f32; NPARAMS];
+                        let mut params = [0.0; NPARAMS];
+                        sync.step(&store, &grad, &mut params).await.unwrap();
+                        rounds.push(params);
+                    }
+
+                    rounds
+                })
+            })
+            .collect();
+
+        let expected: Vec<_> = (1..=ROUNDS)
+            .scan(0.0, |param, r| {
+                *param -= (NWORKERS * r) as f32;
+                Some([*param; NPARAMS])
+            })
+            .collect();
+
+        for worker in workers {
+            assert_eq!(worker.await.unwrap(), expected);
+        }
+    }
+
+    /// Spawns a step of every given worker pushing a gradient of ones.
+    fn spawn_steps(
+        store: &BlockingStore<GradientDescent>,
+        sync: &BarrierSync,
+        nworkers: usize,
+    ) -> Vec<task::JoinHandle<Result<()>>> {
+        (0..nworkers)
+            .map(|_| {
+                let (store, sync) = (store.clone(), sync.clone());
+
+                tokio::spawn(async move {
+                    let mut params = [0.0; NPARAMS];
+                    sync.step(&store, &[1.0; NPARAMS], &mut params).await
+                })
+            })
+            .collect()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_a_grown_barrier_waits_for_the_new_worker() {
+        let store = store();
+        let sync = BarrierSync::new(NonZeroUsize::new(NWORKERS - 1).unwrap());
+        sync.resize(NonZeroUsize::new(NWORKERS).unwrap());
+        assert_eq!(sync.barrier_size(), NWORKERS);
+
+        let steps = spawn_steps(&store, &sync, NWORKERS - 1);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(steps.iter().all(|step| !step.is_finished()));
+
+        let mut params = [0.0; NPARAMS];
+        sync.step(&store, &[1.0; NPARAMS], &mut params)
+            .await
+            .unwrap();
+
+        for step in steps {
+            step.await.unwrap().unwrap();
+        }
+
+        assert_eq!(params, [-(NWORKERS as f32); NPARAMS]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_a_shrunk_barrier_unblocks_the_waiting_workers() {
+        let store = store();
+        let sync = BarrierSync::new(NonZeroUsize::new(NWORKERS).unwrap());
+
+        let steps = spawn_steps(&store, &sync, NWORKERS - 1);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(steps.iter().all(|step| !step.is_finished()));
+
+        sync.resize(NonZeroUsize::new(NWORKERS - 1).unwrap());
+
+        for step in steps {
+            step.await.unwrap().unwrap();
+        }
+
+        let mut params = [0.0; NPARAMS];
+        store.pull_params(&mut params).unwrap();
+        assert_eq!(params, [-(NWORKERS as f32 - 1.0); NPARAMS]);
+
+        // The worker that never arrived lost it's place in the step.
+        let res = sync.step(&store, &[1.0; NPARAMS], &mut params).await;
+        assert!(matches!(res, Err(ParamServerErr::DroppedLaggard)));
+    }
+
+    /// Spawns a step of every worker but the stalled one, which is handed back without stepping.
+    fn step_without_laggard(
+        store: &BlockingStore<GradientDescent>,
+        policy: TimeoutPolicy,
+    ) -> (Vec<task::JoinHandle<Result<()>>>, BarrierSync) {
+        let timeout = BarrierTimeout {
+            after: Duration::from_millis(50),
+            policy,
+        };
+
+        let laggard = BarrierSync::new(NonZeroUsize::new(NWORKERS).unwrap()).with_timeout(timeout);
+
+        let steps = (1..NWORKERS)
+            .map(|_| {
+                let (store, sync) = (store.clone(), laggard.clone());
+
+                tokio::spawn(async move {
+                    let mut params = [0.0; NPARAMS];
+                    sync.step(&store, &[1.0; NPARAMS], &mut params).await
+                })
+            })
+            .collect();
+
+        (steps, laggard)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_fail_policy_errors_once_the_timeout_runs_out() {
+        let store = store();
+        let (steps, _laggard) = step_without_laggard(&store, TimeoutPolicy::Fail);
+
+        for step in steps {
+            let res = step.await.unwrap();
+            assert!(matches!(res, Err(ParamServerErr::BarrierTimeout)));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_retry_once_policy_waits_for_a_laggard_arriving_during_the_retry() {
+        let store = store();
+        let retry = TimeoutPolicy::RetryOnce(Duration::from_secs(5));
+        let (steps, laggard) = step_without_laggard(&store, retry);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let mut params = [0.0; NPARAMS];
+        laggard
+            .step(&store, &[1.0; NPARAMS], &mut params)
+            .await
+            .unwrap();
+
+        for step in steps {
+            step.await.unwrap().unwrap();
+        }
+
+        assert_eq!(params, [-(NWORKERS as f32); NPARAMS]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_drop_laggard_policy_steps_without_the_laggard() {
+        let store = store();
+        let (steps, laggard) = step_without_laggard(&store, TimeoutPolicy::DropLaggard);
+
+        for step in steps {
+            step.await.unwrap().unwrap();
+        }
+
+        let mut params = [0.0; NPARAMS];
+        store.pull_params(&mut params).unwrap();
+        assert_eq!(params, [-(NWORKERS as f32 - 1.0); NPARAMS]);
+
+        let res = laggard.step(&store, &[1.0; NPARAMS], &mut params).await;
+        assert!(matches!(res, Err(ParamServerErr::DroppedLaggard)));
+    }
 }
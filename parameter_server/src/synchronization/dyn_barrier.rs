@@ -1,13 +1,36 @@
-use std::num::NonZeroUsize;
+use std::{mem, num::NonZeroUsize, time::Duration};
 
 use parking_lot::{Condvar, Mutex, MutexGuard};
 
+/// What a thread does once it waited on a `DynBarrier` for longer than it's timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    /// Takes back it's arrival and stops waiting.
+    Withdraw,
+    /// Triggers the barrier without the threads yet to arrive, dropping their places.
+    DropLaggards,
+}
+
+/// How a wait on a `DynBarrier` with a timeout ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wait {
+    /// Every thread arrived.
+    Triggered,
+    /// The timeout ran out and the thread took back it's arrival.
+    TimedOut,
+    /// The timeout ran out and the barrier triggered without this many threads.
+    DroppedLaggards(usize),
+    /// The thread arrived after it's place was dropped for lagging behind.
+    Dropped,
+}
+
 /// Tracks the uses of a `DynBarrier` instance.
 struct BarrierSharedState {
     generation: usize,
     leader_gen: usize,
     remaining: usize,
     size: usize,
+    pending: usize,
 }
 
 /// A barrier implementation with dynamic amount of waiting threads to trigger.
@@ -30,6 +53,7 @@ impl DynBarrier {
             leader_gen: 0,
             remaining: size.get(),
             size: size.get(),
+            pending: 0,
         };
 
         Self {
@@ -68,10 +92,86 @@ impl DynBarrier {
         }
     }
 
+    /// Like `wait_with`, but gives up waiting for the rest of the threads after a timeout.
+    ///
+    /// # Args
+    /// * `seen` - The generation the thread last saw the barrier at.
+    /// * `timeout` - How long to wait for the rest of the threads.
+    /// * `expiry` - What to do once the timeout runs out.
+    /// * `leader_fn` - The function the leader runs before the barrier triggers.
+    ///
+    /// # Returns
+    /// How the wait ended.
+    pub fn wait_timeout_with<F>(
+        &self,
+        seen: usize,
+        timeout: Duration,
+        expiry: Expiry,
+        mut leader_fn: F,
+    ) -> Wait
+    where
+        F: FnMut(),
+    {
+        let mut state = self.state.lock();
+
+        // The barrier only moves on without a thread if it dropped it's place.
+        if state.generation != seen {
+            return Wait::Dropped;
+        }
+
+        state.remaining -= 1;
+        let mut wait = Wait::Triggered;
+
+        let is_leader = if state.remaining > 0 {
+            let local_gen = state.generation;
+            self.cvar
+                .wait_while_for(&mut state, |state| state.generation == local_gen, timeout);
+
+            if state.generation != local_gen {
+                state.leader_gen < state.generation
+            } else {
+                match expiry {
+                    Expiry::Withdraw => {
+                        state.remaining += 1;
+                        return Wait::TimedOut;
+                    }
+                    Expiry::DropLaggards => {
+                        wait = Wait::DroppedLaggards(state.remaining);
+                        state.size -= state.remaining;
+                        state.remaining = 0;
+                        true
+                    }
+                }
+            }
+        } else {
+            true
+        };
+
+        if is_leader {
+            leader_fn();
+            state.leader_gen += 1;
+
+            if state.leader_gen > state.generation {
+                self.advance(&mut state);
+            }
+        }
+
+        wait
+    }
+
     /// Acquires a waiting thread's place by decreasing the amount of needed threads to trigger the barrier.
-    pub fn acquire(&self) {
+    ///
+    /// A thread whose place was already dropped for lagging behind has none to give up.
+    ///
+    /// # Args
+    /// * `seen` - The generation the thread last saw the barrier at.
+    pub fn acquire(&self, seen: usize) {
         let mut state = self.state.lock();
 
+        if state.generation != seen {
+            return;
+        }
+
         state.remaining -= 1;
         state.size -= 1;
 
@@ -80,18 +180,92 @@ impl DynBarrier {
         }
     }
 
+    /// Registers a new waiting thread, increasing the amount of needed threads to trigger the barrier.
+    ///
+    /// If a generation is in progress the new place only takes effect once it completes, in which
+    /// case the current thread is blocked until the barrier advances to the next generation.
+    pub fn join(&self) {
+        let mut state = self.state.lock();
+
+        if state.remaining == state.size {
+            state.remaining += 1;
+            state.size += 1;
+            return;
+        }
+
+        state.pending += 1;
+        self.wait_until_new_gen(&mut state);
+    }
+
+    /// Resizes the barrier to a new amount of threads.
+    ///
+    /// Growing takes effect at once if no thread arrived at the current generation yet,
+    /// otherwise once it completes, so it doesn't wait for threads it wasn't started with.
+    /// Shrinking takes effect at once, triggering the current generation if the threads
+    /// already waiting on it are enough.
+    ///
+    /// # Args
+    /// * `new_size` - The new amount of threads needed to trigger the barrier.
+    pub fn resize(&self, new_size: NonZeroUsize) {
+        let mut state = self.state.lock();
+        let new_size = new_size.get();
+
+        if new_size > state.size {
+            let grown = new_size - state.size;
+
+            if state.remaining == state.size {
+                state.remaining += grown;
+                state.size += grown;
+            } else {
+                state.pending += grown;
+            }
+
+            return;
+        }
+
+        let shrunk = state.size - new_size;
+        state.size = new_size;
+
+        if shrunk < state.remaining {
+            state.remaining -= shrunk;
+        } else {
+            // The threads waiting run the leader's function once they wake up.
+            state.remaining = 0;
+            self.advance(&mut state);
+        }
+    }
+
+    /// The amount of threads needed to trigger the current generation.
+    ///
+    /// # Returns
+    /// The barrier's size.
+    pub fn size(&self) -> usize {
+        self.state.lock().size
+    }
+
+    /// The current generation of the barrier.
+    ///
+    /// # Returns
+    /// The amount of times the barrier has been triggered.
+    pub fn generation(&self) -> usize {
+        self.state.lock().generation
+    }
+
     /// Advances the barrier to the next generation, updates tracking counters,
     /// and wakes up all threads registered to the condition variable.
     ///
     /// # Args
     /// * `guard` - The barrier's mutable state guard.
     fn advance(&self, guard: &mut MutexGuard<BarrierSharedState>) {
+        let joined = mem::take(&mut guard.pending);
+
         guard.generation += 1;
+        guard.size += joined;
         guard.remaining = guard.size;
 
-        if guard.remaining > 1 {
-            self.cvar.notify_all();
-        }
+        // Even a barrier of size 1 can have a thread waiting on the previous generation
+        // if the others acquired their places, or joining threads waiting to be admitted.
+        self.cvar.notify_all();
     }
 
     /// Blocks the current thread until the current generation changes.
@@ -1,11 +1,37 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use parking_lot::Mutex;
 use tokio::task;
 
-use super::Synchronizer;
+use super::{DynBarrier, Synchronizer};
 use crate::storage::{Result, Store};
 
 /// Skips synchronization between workers for it's operations, will process incoming gradients immediately.
-#[derive(Clone)]
-pub struct NoBlockingSync;
+///
+/// Optionally waits for every worker once every some steps, so they all get the same
+/// parameters for the following one and the losses they report for it are computed over a
+/// single version of the weights. The gradients are still applied as soon as they arrive.
+pub struct NoBlockingSync {
+    metric_barrier: Option<MetricBarrier>,
+}
+
+/// The barrier the workers meet at every some steps to get the same parameters.
+///
+/// The parameters are pulled once by the barrier's leader, otherwise a worker leaving
+/// the barrier first could apply it's next gradient before the rest pull them.
+struct MetricBarrier {
+    barrier: Arc<DynBarrier>,
+    snapshot: Arc<Mutex<Vec<f32>>>,
+    every: NonZeroUsize,
+    steps: AtomicUsize,
+    seen: AtomicUsize,
+}
 
 impl NoBlockingSync {
     /// Creates a new `NonBlockingSync` synchronizer.
@@ -13,7 +39,55 @@ impl NoBlockingSync {
     /// # Returns
     /// A new `NonBlockingSync` instance.
     pub fn new() -> Self {
-        Self {}
+        Self {
+            metric_barrier: None,
+        }
+    }
+
+    /// Makes every worker wait for the rest every that many of it's steps once it's gradient
+    /// is applied, so they all pull the same parameters.
+    ///
+    /// # Args
+    /// * `every` - The amount of steps of a worker in between two waits.
+    /// * `size` - The amount of workers to wait for.
+    ///
+    /// # Returns
+    /// The modified `NonBlockingSync` instance.
+    pub fn with_metric_barrier(mut self, every: NonZeroUsize, size: NonZeroUsize) -> Self {
+        self.metric_barrier = Some(MetricBarrier {
+            barrier: Arc::new(DynBarrier::new(size)),
+            snapshot: Arc::default(),
+            every,
+            steps: AtomicUsize::new(0),
+            seen: AtomicUsize::new(0),
+        });
+
+        self
+    }
+}
+
+impl Clone for NoBlockingSync {
+    fn clone(&self) -> Self {
+        let metric_barrier = self.metric_barrier.as_ref().map(|metric| MetricBarrier {
+            barrier: metric.barrier.clone(),
+            snapshot: metric.snapshot.clone(),
+            every: metric.every,
+            steps: AtomicUsize::new(0),
+            seen: AtomicUsize::new(metric.barrier.generation()),
+        });
+
+        Self { metric_barrier }
+    }
+}
+
+impl Drop for MetricBarrier {
+    fn drop(&mut self) {
+        let barrier = &mut self.barrier;
+
+        // A worker leaving the training stops the rest from waiting for it.
+        if Arc::strong_count(barrier) > 1 {
+            barrier.acquire(*self.seen.get_mut());
+        }
     }
 }
 
@@ -25,8 +99,115 @@ impl Synchronizer for NoBlockingSync {
         task::block_in_place(|| {
             handle.accumulate(grad)?;
             handle.update_params();
-            handle.pull_params(params)?;
+
+            let Some(ref metric) = self.metric_barrier else {
+                return handle.pull_params(params);
+            };
+
+            let steps = metric.steps.fetch_add(1, Ordering::AcqRel) + 1;
+            if !steps.is_multiple_of(metric.every.get()) {
+                return handle.pull_params(params);
+            }
+
+            metric.barrier.wait_with(|| {
+                let mut snapshot = metric.snapshot.lock();
+                snapshot.resize(params.len(), 0.0);
+
+                // SAFETY: The server only steps with buffers the same size as the store.
+                handle.pull_params(&mut snapshot).unwrap();
+            });
+
+            metric.seen.fetch_add(1, Ordering::AcqRel);
+            params.copy_from_slice(&metric.snapshot.lock());
             Ok(())
         })
     }
+
+    async fn join(&self) -> usize {
+        let Some(ref metric) = self.metric_barrier else {
+            return 0;
+        };
+
+        task::block_in_place(|| {
+            metric.barrier.join();
+
+            let generation = metric.barrier.generation();
+            metric.seen.store(generation, Ordering::Release);
+            0
+        })
+    }
+
+    fn barrier_size(&self) -> usize {
+        self.metric_barrier
+            .as_ref()
+            .map_or(0, |metric| metric.barrier.size())
+    }
+
+    fn resize(&self, new_size: NonZeroUsize) {
+        if let Some(ref metric) = self.metric_barrier {
+            metric.barrier.resize(new_size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use comms::floats::FloatPositive;
+    use machine_learning::{initialization::ConstParamGen, optimization::GradientDescent};
+
+    use super::*;
+    use crate::storage::BlockingStore;
+
+    const NWORKERS: usize = 3;
+    const NPARAMS: usize = 2;
+    const EVERY: usize = 4;
+    const ROUNDS: usize = 3 * EVERY;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_the_metric_barrier_hands_every_worker_the_same_params() {
+        let shard_size = NonZeroUsize::new(NPARAMS).unwrap();
+        let mut param_gen = ConstParamGen::new(0.0, NPARAMS);
+        let optimizer_factory = |_| GradientDescent::new(FloatPositive::new(1.0).unwrap());
+        let store = BlockingStore::new(shard_size, &mut param_gen, optimizer_factory);
+
+        let every = NonZeroUsize::new(EVERY).unwrap();
+        let size = NonZeroUsize::new(NWORKERS).unwrap();
+        let sync = NoBlockingSync::new().with_metric_barrier(every, size);
+
+        // Every worker steps at it's own pace, so without the barrier they'd pull the
+        // parameters at different versions.
+        let workers: Vec<_> = (0..NWORKERS)
+            .map(|w| {
+                let (store, sync) = (store.clone(), sync.clone());
+
+                tokio::spawn(async move {
+                    let mut rounds = Vec::with_capacity(ROUNDS);
+
+                    for _ in 0..ROUNDS {
+                        tokio::time::sleep(Duration::from_millis(5 * w as u64)).await;
+                        let mut params = [0.0; NPARAMS];
+                        sync.step(&store, &[1.0; NPARAMS], &mut params)
+                            .await
+                            .unwrap();
+                        rounds.push(params);
+                    }
+
+                    rounds
+                })
+            })
+            .collect();
+
+        let mut rounds = Vec::with_capacity(NWORKERS);
+        for worker in workers {
+            rounds.push(worker.await.unwrap());
+        }
+
+        // The whole gradient of the first `r` steps of every worker is applied by then.
+        for r in (EVERY..=ROUNDS).step_by(EVERY) {
+            let expected = [-((NWORKERS * r) as f32); NPARAMS];
+            assert!(rounds.iter().all(|worker| worker[r - 1] == expected));
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use std::num::NonZeroUsize;
+
 use crate::storage::{Result, Store};
 
 /// Executes a single parameter update step.
@@ -18,4 +20,40 @@ pub trait SynchronizerTemplate: Clone {
     async fn step<PS>(&self, store: &PS, grad: &[f32], params: &mut [f32]) -> Result<()>
     where
         PS: Store + Send + Sync;
+
+    /// Registers a worker that joined after the training started.
+    ///
+    /// Waits until the worker can safely take part in the next step.
+    ///
+    /// # Returns
+    /// The amount of synchronized steps taken before the worker joined, `0` if
+    /// the synchronizer doesn't keep track of them.
+    async fn join(&self) -> usize;
+
+    /// Sets the amount of samples the worker stepping through this synchronizer trains on.
+    ///
+    /// # Args
+    /// * `samples` - The worker's amount of samples.
+    fn set_samples(&mut self, samples: u64) {}
+
+    /// The amount of workers a step waits for.
+    ///
+    /// # Returns
+    /// The size of the barrier, `0` if the synchronizer doesn't wait on one.
+    fn barrier_size(&self) -> usize {
+        0
+    }
+
+    /// Changes the amount of workers a step waits for.
+    ///
+    /// It's only safe to call between the steps of the workers that stay, a grown barrier
+    /// takes effect at once if none of the workers arrived at the current step yet and
+    /// once it completes otherwise, so the step in progress doesn't wait for the new
+    /// ones. A shrunk barrier takes effect at once, completing the step in progress if the
+    /// workers already waiting on it are enough, in which case the ones yet to arrive are
+    /// dropped like laggards. It does nothing if the synchronizer doesn't wait on a barrier.
+    ///
+    /// # Args
+    /// * `new_size` - The new amount of workers.
+    fn resize(&self, new_size: NonZeroUsize) {}
 }
@@ -1,13 +1,27 @@
 #![cfg(test)]
 
-use std::{env, num::NonZeroUsize};
+use std::{env, num::NonZeroUsize, time::Duration};
 
-use comms::{OrchHandle, ParamServerHandle, WorkerEvent, WorkerHandle, floats::FloatPositive};
+use comms::{
+    OrchHandle, ParamServerHandle, WorkerEvent, WorkerHandle, floats::FloatPositive,
+    specs::server::UpdateRule,
+};
 use machine_learning::{initialization::ConstParamGen, optimization::GradientDescent};
-use tokio::io::{self, AsyncRead, AsyncWrite, DuplexStream, ReadHalf, WriteHalf};
+use tokio::{
+    io::{
+        self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream, ReadHalf, WriteHalf,
+    },
+    sync::{oneshot, watch},
+    task::JoinSet,
+    time,
+};
 use uuid::Uuid;
 
-use crate::{service::ParameterServer, storage::BlockingStore, synchronization::BarrierSync};
+use crate::{
+    service::ParameterServer,
+    storage::BlockingStore,
+    synchronization::{BarrierSync, NoBlockingSync},
+};
 
 fn channel_pair() -> (
     (ReadHalf<DuplexStream>, WriteHalf<DuplexStream>),
@@ -49,6 +63,7 @@ where
         server_handle.push_grad(&grad).await?;
     }
 
+    server_handle.discard_one().await?;
     server_handle.disconnect().await?;
     orch_handle.done().await?;
     orch_handle.disconnect().await?;
@@ -76,6 +91,7 @@ where
     while !matches!(worker_handle.recv_event().await?, WorkerEvent::Done) {}
     while !matches!(worker_handle.recv_event().await?, WorkerEvent::Disconnect) {}
 
+    server_handle.req_params().await?;
     let params = server_handle.pull_params().await?.to_vec();
     server_handle.disconnect().await?;
     Ok(params)
@@ -119,3 +135,405 @@ async fn test_lineal_convergence() -> io::Result<()> {
     println!("params: {params:?}");
     Ok(())
 }
+
+/// Reports the epochs a mock worker started and keeps it connected until released.
+struct Progress {
+    epoch_tx: watch::Sender<usize>,
+    release_rx: Option<oneshot::Receiver<()>>,
+}
+
+async fn mock_lossy_worker<R, W>(
+    rx: R,
+    tx: W,
+    max_epochs: usize,
+    drop_at: Option<usize>,
+    nparams: usize,
+    server_id: Uuid,
+    progress: Option<Progress>,
+) -> io::Result<Vec<f32>>
+where
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    let mut grad = vec![0.0; nparams];
+    let mut first_params = Vec::new();
+    let transport = comms::build_simple_transport(rx, tx);
+    let mut server_handle = ParamServerHandle::new(server_id, transport);
+
+    for epoch in 0..max_epochs {
+        let params = server_handle.pull_params().await?;
+
+        if epoch == 0 {
+            first_params = params.to_vec();
+        }
+
+        if let Some(progress) = &progress {
+            let _ = progress.epoch_tx.send(epoch + 1);
+        }
+
+        if drop_at == Some(epoch) {
+            // The connection is dropped without disconnecting from the server.
+            return Ok(first_params);
+        }
+
+        for (g, p) in grad.iter_mut().zip(params) {
+            *g = *p - 1.0;
+        }
+
+        server_handle.push_grad(&grad).await?;
+    }
+
+    server_handle.discard_one().await?;
+
+    if let Some(release_rx) = progress.and_then(|progress| progress.release_rx) {
+        let _ = release_rx.await;
+    }
+
+    server_handle.disconnect().await?;
+    Ok(first_params)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_barrier_survives_worker_loss() -> io::Result<()> {
+    const NWORKERS: usize = 3;
+    const MAX_EPOCHS: usize = 20;
+    const DROP_AT: usize = 5;
+    const NPARAMS: usize = 2;
+
+    let server_id = Uuid::new_v4();
+    let orch_id = Uuid::nil();
+
+    let shard_size = NonZeroUsize::new(1).unwrap();
+    let mut param_gen = ConstParamGen::new(0.5, NPARAMS);
+    let optimizer_factory = |_| GradientDescent::new(FloatPositive::new(0.1).unwrap());
+    let store = BlockingStore::new(shard_size, &mut param_gen, optimizer_factory);
+    let synchronizer = BarrierSync::new(NonZeroUsize::new(NWORKERS).unwrap());
+
+    let ((sv_orch_rx, sv_orch_tx), (orch_sv_rx, orch_sv_tx)) = channel_pair();
+    let transport = comms::build_simple_transport(sv_orch_rx, sv_orch_tx);
+    let orch_handle = OrchHandle::new(orch_id, transport);
+    let mut server = ParameterServer::new(store, synchronizer, orch_handle).with_worker_loss(true);
+
+    let mut workers = JoinSet::new();
+
+    for i in 0..NWORKERS {
+        let ((wk_rx, wk_tx), (sv_rx, sv_tx)) = channel_pair();
+        let transport = comms::build_simple_transport(sv_rx, sv_tx);
+        server.spawn(WorkerHandle::new(Uuid::new_v4(), transport));
+
+        let drop_at = (i == 0).then_some(DROP_AT);
+        workers.spawn(mock_lossy_worker(
+            wk_rx, wk_tx, MAX_EPOCHS, drop_at, NPARAMS, server_id, None,
+        ));
+    }
+
+    let orchestrator_fut = async {
+        for ret in workers.join_all().await {
+            ret?;
+        }
+
+        let transport = comms::build_simple_transport(orch_sv_rx, orch_sv_tx);
+        let mut server_handle = ParamServerHandle::new(server_id, transport);
+        server_handle.req_params().await?;
+        let params = server_handle.pull_params().await?.to_vec();
+        server_handle.disconnect().await?;
+        Ok(params)
+    };
+
+    let (_, params) = tokio::try_join!(server.run(), orchestrator_fut)?;
+    assert_eq!(params.len(), NPARAMS);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_worker_joins_mid_training() -> io::Result<()> {
+    const MAX_EPOCHS: usize = 10;
+    const JOIN_AT: usize = 2;
+    const NPARAMS: usize = 2;
+    const INIT: f32 = 0.5;
+
+    let server_id = Uuid::new_v4();
+    let orch_id = Uuid::nil();
+
+    let shard_size = NonZeroUsize::new(1).unwrap();
+    let mut param_gen = ConstParamGen::new(INIT, NPARAMS);
+    let optimizer_factory = |_| GradientDescent::new(FloatPositive::new(0.1).unwrap());
+    let store = BlockingStore::new(shard_size, &mut param_gen, optimizer_factory);
+    let synchronizer = BarrierSync::new(NonZeroUsize::new(2).unwrap());
+
+    let ((sv_orch_rx, sv_orch_tx), (orch_sv_rx, orch_sv_tx)) = channel_pair();
+    let transport = comms::build_simple_transport(sv_orch_rx, sv_orch_tx);
+    let orch_handle = OrchHandle::new(orch_id, transport);
+    let mut server = ParameterServer::new(store, synchronizer, orch_handle);
+    let joiner = server.joiner(1);
+
+    let (epoch_tx, mut epoch_rx) = watch::channel(0);
+    let (release_tx, release_rx) = oneshot::channel();
+    let mut progress = Some(Progress {
+        epoch_tx,
+        release_rx: Some(release_rx),
+    });
+    let mut workers = JoinSet::new();
+
+    for _ in 0..2 {
+        let ((wk_rx, wk_tx), (sv_rx, sv_tx)) = channel_pair();
+        let transport = comms::build_simple_transport(sv_rx, sv_tx);
+        server.spawn(WorkerHandle::new(Uuid::new_v4(), transport));
+
+        workers.spawn(mock_lossy_worker(
+            wk_rx,
+            wk_tx,
+            MAX_EPOCHS,
+            None,
+            NPARAMS,
+            server_id,
+            progress.take(),
+        ));
+    }
+
+    let orchestrator_fut = async {
+        epoch_rx
+            .wait_for(|&epoch| epoch > JOIN_AT)
+            .await
+            .map_err(io::Error::other)?;
+
+        let ((wk_rx, wk_tx), (sv_rx, sv_tx)) = channel_pair();
+        let transport = comms::build_simple_transport(sv_rx, sv_tx);
+        let worker_handle = WorkerHandle::new(Uuid::new_v4(), transport);
+        joiner.send(worker_handle).await.map_err(io::Error::other)?;
+
+        // The first worker is held connected until the late one is bootstrapped,
+        // otherwise the server could finish before the join takes place.
+        let (late_epoch_tx, mut late_epoch_rx) = watch::channel(0);
+        let late_progress = Progress {
+            epoch_tx: late_epoch_tx,
+            release_rx: None,
+        };
+
+        let late_fut = mock_lossy_worker(
+            wk_rx,
+            wk_tx,
+            MAX_EPOCHS,
+            None,
+            NPARAMS,
+            server_id,
+            Some(late_progress),
+        );
+
+        let release_fut = async {
+            late_epoch_rx
+                .wait_for(|&epoch| epoch > 0)
+                .await
+                .map_err(io::Error::other)?;
+
+            let _ = release_tx.send(());
+            Ok(())
+        };
+
+        let (late_params, _) = tokio::try_join!(late_fut, release_fut)?;
+
+        for ret in workers.join_all().await {
+            assert_eq!(ret?, [INIT; NPARAMS]);
+        }
+
+        let transport = comms::build_simple_transport(orch_sv_rx, orch_sv_tx);
+        let mut server_handle = ParamServerHandle::new(server_id, transport);
+        server_handle.req_params().await?;
+        let params = server_handle.pull_params().await?.to_vec();
+        server_handle.disconnect().await?;
+        Ok((late_params, params))
+    };
+
+    let (_, (late_params, params)) = tokio::try_join!(server.run(), orchestrator_fut)?;
+
+    assert_ne!(late_params, [INIT; NPARAMS]);
+    assert_eq!(params.len(), NPARAMS);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_non_blocking_server_reports_the_staleness_of_a_slow_worker() -> io::Result<()> {
+    const NPARAMS: usize = 2;
+
+    let server_id = Uuid::new_v4();
+    let orch_id = Uuid::nil();
+
+    let shard_size = NonZeroUsize::new(1).unwrap();
+    let mut param_gen = ConstParamGen::new(0.5, NPARAMS);
+    let optimizer_factory = |_| GradientDescent::new(FloatPositive::new(0.1).unwrap());
+    let store = BlockingStore::new(shard_size, &mut param_gen, optimizer_factory);
+
+    let ((sv_orch_rx, sv_orch_tx), (orch_sv_rx, orch_sv_tx)) = channel_pair();
+    let transport = comms::build_simple_transport(sv_orch_rx, sv_orch_tx);
+    let orch_handle = OrchHandle::new(orch_id, transport);
+    let mut server =
+        ParameterServer::new(store, NoBlockingSync::new(), orch_handle).with_staleness_tracking();
+
+    let mut worker_handles = Vec::new();
+
+    for _ in 0..2 {
+        let ((wk_rx, wk_tx), (sv_rx, sv_tx)) = channel_pair();
+        let transport = comms::build_simple_transport(sv_rx, sv_tx);
+        server.spawn(WorkerHandle::new(Uuid::new_v4(), transport));
+
+        let transport = comms::build_simple_transport(wk_rx, wk_tx);
+        worker_handles.push(ParamServerHandle::new(server_id, transport));
+    }
+
+    let orchestrator_fut = async {
+        const FAST: usize = 0;
+        const SLOW: usize = 1;
+
+        for handle in &mut worker_handles {
+            handle.pull_params().await?;
+        }
+
+        // Every step waits for the server's reply, so the updates are applied in this order.
+        for (worker, steps) in [(FAST, 2), (SLOW, 1), (FAST, 5), (SLOW, 1)] {
+            for _ in 0..steps {
+                worker_handles[worker].push_grad(&[0.1; NPARAMS]).await?;
+                worker_handles[worker].pull_params().await?;
+            }
+        }
+
+        for handle in &mut worker_handles {
+            handle.disconnect().await?;
+        }
+
+        let transport = comms::build_simple_transport(orch_sv_rx, orch_sv_tx);
+        let mut server_handle = ParamServerHandle::new(server_id, transport);
+        server_handle.req_staleness().await?;
+        let stats = server_handle.pull_staleness().await?;
+        server_handle.req_params().await?;
+        server_handle.pull_params().await?;
+        server_handle.disconnect().await?;
+        Ok(stats)
+    };
+
+    let (_, stats) = tokio::try_join!(server.run(), orchestrator_fut)?;
+
+    // The slow worker's gradients were 2 and 5 updates behind, the fast worker's first one
+    // after the slow worker's was 1.
+    assert_eq!(stats.updates, 9);
+    assert_eq!(stats.max, 5);
+    assert_eq!(stats.mean, 8.0 / 9.0);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_an_orchestrator_request_split_around_a_worker_leaving_is_read_whole() -> io::Result<()>
+{
+    const NPARAMS: usize = 2;
+
+    let server_id = Uuid::new_v4();
+    let orch_id = Uuid::nil();
+
+    let shard_size = NonZeroUsize::new(1).unwrap();
+    let mut param_gen = ConstParamGen::new(0.5, NPARAMS);
+    let optimizer_factory = |_| GradientDescent::new(FloatPositive::new(0.1).unwrap());
+    let store = BlockingStore::new(shard_size, &mut param_gen, optimizer_factory);
+
+    let ((sv_orch_rx, sv_orch_tx), (orch_sv_rx, mut orch_sv_tx)) = channel_pair();
+    let transport = comms::build_simple_transport(sv_orch_rx, sv_orch_tx);
+    let orch_handle = OrchHandle::new(orch_id, transport);
+    let mut server = ParameterServer::new(store, NoBlockingSync::new(), orch_handle);
+
+    let ((wk_rx, wk_tx), (sv_rx, sv_tx)) = channel_pair();
+    let transport = comms::build_simple_transport(sv_rx, sv_tx);
+    server.spawn(WorkerHandle::new(Uuid::new_v4(), transport));
+
+    let orchestrator_fut = async {
+        // The request is encoded through a transport of it's own to write it in two halves.
+        let ((enc_rx, enc_tx), (mut raw_rx, _)) = channel_pair();
+        let transport = comms::build_simple_transport(enc_rx, enc_tx);
+        let mut encoder = ParamServerHandle::new(server_id, transport);
+        encoder.req_snapshot().await?;
+        drop(encoder);
+
+        let mut request = Vec::new();
+        raw_rx.read_to_end(&mut request).await?;
+        let (head, tail) = request.split_at(request.len() / 2);
+
+        let transport = comms::build_simple_transport(wk_rx, wk_tx);
+        let mut worker_handle = ParamServerHandle::new(server_id, transport);
+        worker_handle.pull_params().await?;
+
+        // The worker leaves while the server is halfway through reading the request.
+        orch_sv_tx.write_all(head).await?;
+        worker_handle.disconnect().await?;
+        time::sleep(Duration::from_millis(50)).await;
+        orch_sv_tx.write_all(tail).await?;
+
+        let transport = comms::build_simple_transport(orch_sv_rx, orch_sv_tx);
+        let mut server_handle = ParamServerHandle::new(server_id, transport);
+        let params = server_handle.pull_params().await?.to_vec();
+        server_handle.disconnect().await?;
+        Ok(params)
+    };
+
+    let (_, params) = tokio::try_join!(server.run(), orchestrator_fut)?;
+    assert_eq!(params, [0.5; NPARAMS]);
+    Ok(())
+}
+
+/// Sends a single gradient to a server following the given update rule.
+///
+/// # Args
+/// * `update_rule` - The order of the server's update and send.
+///
+/// # Returns
+/// The parameters the worker got back for it's gradient and the server's final ones.
+async fn step_once(update_rule: UpdateRule) -> io::Result<(Vec<f32>, Vec<f32>)> {
+    const NPARAMS: usize = 2;
+
+    let server_id = Uuid::new_v4();
+    let orch_id = Uuid::nil();
+
+    let shard_size = NonZeroUsize::new(1).unwrap();
+    let mut param_gen = ConstParamGen::new(0.5, NPARAMS);
+    let optimizer_factory = |_| GradientDescent::new(FloatPositive::new(0.1).unwrap());
+    let store = BlockingStore::new(shard_size, &mut param_gen, optimizer_factory);
+
+    let ((sv_orch_rx, sv_orch_tx), (orch_sv_rx, orch_sv_tx)) = channel_pair();
+    let transport = comms::build_simple_transport(sv_orch_rx, sv_orch_tx);
+    let orch_handle = OrchHandle::new(orch_id, transport);
+    let mut server = ParameterServer::new(store, NoBlockingSync::new(), orch_handle)
+        .with_update_rule(update_rule);
+
+    let ((wk_rx, wk_tx), (sv_rx, sv_tx)) = channel_pair();
+    let transport = comms::build_simple_transport(sv_rx, sv_tx);
+    server.spawn(WorkerHandle::new(Uuid::new_v4(), transport));
+
+    let orchestrator_fut = async {
+        let transport = comms::build_simple_transport(wk_rx, wk_tx);
+        let mut worker_handle = ParamServerHandle::new(server_id, transport);
+        worker_handle.pull_params().await?;
+        worker_handle.push_grad(&[1.0; NPARAMS]).await?;
+        let received = worker_handle.pull_params().await?.to_vec();
+        worker_handle.disconnect().await?;
+
+        let transport = comms::build_simple_transport(orch_sv_rx, orch_sv_tx);
+        let mut server_handle = ParamServerHandle::new(server_id, transport);
+        server_handle.req_params().await?;
+        let params = server_handle.pull_params().await?.to_vec();
+        server_handle.disconnect().await?;
+        Ok((received, params))
+    };
+
+    let (_, ret) = tokio::try_join!(server.run(), orchestrator_fut)?;
+    Ok(ret)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_the_worker_receives_the_parameters_its_update_rule_says() -> io::Result<()> {
+    let (received, params) = step_once(UpdateRule::UpdateThenSend).await?;
+    assert_eq!(received, [0.4; 2]);
+    assert_eq!(params, [0.4; 2]);
+
+    // The reply leaves before the step, so it still holds the parameters the gradient was
+    // computed over, while the server's are updated all the same.
+    let (received, params) = step_once(UpdateRule::SendThenUpdate).await?;
+    assert_eq!(received, [0.5; 2]);
+    assert_eq!(params, [0.4; 2]);
+    Ok(())
+}
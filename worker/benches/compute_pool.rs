@@ -0,0 +1,53 @@
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use futures::future;
+use tokio::{runtime::Runtime, task};
+use worker::compute::ComputePool;
+
+/// The amount of operations of each compute job.
+const JOB_SIZE: usize = 50_000;
+
+/// A compute bound job standing for a training step.
+fn job() -> f32 {
+    (0..JOB_SIZE).map(|i| (i as f32).sqrt().sin()).sum()
+}
+
+/// Runs `njobs` concurrent jobs on tokio's blocking pool.
+async fn run_blocking(njobs: usize) {
+    let jobs = (0..njobs).map(|_| task::spawn_blocking(|| black_box(job())));
+    future::try_join_all(jobs).await.unwrap();
+}
+
+/// Runs `njobs` concurrent jobs on the compute pool.
+async fn run_compute_pool(compute_pool: &ComputePool, njobs: usize) {
+    let jobs = (0..njobs).map(|_| {
+        let compute_pool = compute_pool.clone();
+        tokio::spawn(async move { compute_pool.run(|| black_box(job())) })
+    });
+
+    future::try_join_all(jobs).await.unwrap();
+}
+
+fn bench_compute_pool(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let compute_pool = ComputePool::default();
+
+    let mut group = c.benchmark_group("compute_steps");
+    group.sample_size(20);
+
+    for njobs in [16, 256, 1024] {
+        group.bench_with_input(BenchmarkId::new("blocking_pool", njobs), &njobs, |b, &n| {
+            b.iter(|| runtime.block_on(run_blocking(n)))
+        });
+
+        group.bench_with_input(BenchmarkId::new("compute_pool", njobs), &njobs, |b, &n| {
+            b.iter(|| runtime.block_on(run_compute_pool(&compute_pool, n)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_compute_pool);
+criterion_main!(benches);
@@ -1,31 +1,33 @@
-use std::io;
+use std::{io, time::Duration};
 
 use comms::{
-    Acceptor, Connection, Connector, DatasetSrc, OrchHandle, ParamServerHandle, TransportLayer,
-    protocol::Entity,
+    Acceptor, Connection, Connector, DatasetSrc, NetReadHalf, NetWriteHalf, OrchHandle,
+    ParamServerHandle, TransportLayer,
+    protocol::{Capabilities, Entity},
     specs::{
-        machine_learning::TrainerSpec,
+        machine_learning::{DatasetSpec, SamplerSpec, SyntheticSpec, TrainerSpec},
         worker::{AlgorithmSpec, SerializerSpec, WorkerSpec},
     },
 };
+use log::warn;
 use machine_learning::{
-    datasets::{DataSrc, Dataset},
+    datasets::{CsrMatrix, DataSrc, Dataset, SyntheticDataset},
     initialization::ParamGenBuilder,
-    training::TrainerBuilder,
-};
-use tokio::{
-    io::{AsyncRead, AsyncWrite},
-    net::{
-        TcpStream,
-        tcp::{OwnedReadHalf, OwnedWriteHalf},
-    },
+    training::{Trainer, TrainerBuilder},
 };
+use tokio::io::{AsyncRead, AsyncWrite};
 
 use crate::{
+    compute::ComputePool,
+    data::GlobalShard,
+    metrics::ValidationMetrics,
     middlewares::{ServerClusterManager, WorkerRingManager},
-    workers::{AllReduceWorker, Worker, parameter_server::ParamServerWorker},
+    workers::{AllReduceWorker, EvaluateWorker, Worker, parameter_server::ParamServerWorker},
 };
 
+/// The maximum amount of time to wait for each resolved address when connecting to a peer.
+const CONNECT_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// The worker builder, given a spec, will build a new worker ready to use.
 pub struct WorkerBuilder<'a, R, W, T, F, G>
 where
@@ -37,6 +39,7 @@ where
 {
     acceptor: &'a mut Acceptor<T, F>,
     connector: Connector<R, W, T, G>,
+    compute_pool: ComputePool,
 }
 
 impl<'a, R, W, T, F, G> WorkerBuilder<'a, R, W, T, F, G>
@@ -59,15 +62,29 @@ where
         Self {
             acceptor,
             connector,
+            compute_pool: ComputePool::default(),
         }
     }
+
+    /// Sets the pool the workers compute their training steps on, the one sized to the
+    /// machine's cores by default.
+    ///
+    /// # Args
+    /// * `compute_pool` - The pool to compute the training steps on.
+    ///
+    /// # Returns
+    /// The modified `WorkerBuilder`.
+    pub fn with_compute_pool(mut self, compute_pool: ComputePool) -> Self {
+        self.compute_pool = compute_pool;
+        self
+    }
 }
 
-impl<T, F, G> WorkerBuilder<'_, OwnedReadHalf, OwnedWriteHalf, T, F, G>
+impl<T, F, G> WorkerBuilder<'_, NetReadHalf, NetWriteHalf, T, F, G>
 where
     T: TransportLayer + 'static,
     F: AsyncFn() -> io::Result<T>,
-    G: Fn(OwnedReadHalf, OwnedWriteHalf) -> T,
+    G: Fn(NetReadHalf, NetWriteHalf) -> T,
 {
     /// Builds a `Worker` from a `WorkerSpec`.
     ///
@@ -82,14 +99,29 @@ where
         spec: &WorkerSpec,
         orch_handle: &'a mut OrchHandle<T>,
     ) -> io::Result<Box<dyn Worker + 'a>> {
-        let data_src = self.download_dataset(orch_handle).await?;
+        let data_src = match spec.synthetic {
+            Some(ref synthetic) => self.generate_dataset(synthetic, spec.trainer.dataset)?,
+            None => {
+                self.download_dataset(orch_handle, spec.trainer.dataset)
+                    .await?
+            }
+        };
+
         let trainer_builder = TrainerBuilder::new();
+        let validation = Some(ValidationMetrics::for_loss(
+            spec.trainer.loss_fn,
+            spec.trainer.dataset.y_size,
+        ));
 
         let WorkerSpec {
             ref trainer,
             ref algorithm,
             serializer,
             seed,
+            max_frame_size,
+            require_compression,
+            latency_warmup_rounds,
+            synthetic: _,
         } = *spec;
 
         match *algorithm {
@@ -98,21 +130,36 @@ where
                 ref server_sizes,
                 ref server_ordering,
             } => {
-                let cluster_manager = self
+                let DatasetSpec { x_size, y_size, .. } = trainer.dataset;
+                let samples = data_src.rows(x_size.get(), y_size.get());
+                self.connector.set_samples(Some(samples as u64));
+
+                let mut cluster_manager = self
                     .connect_to_servers(
                         server_addrs,
                         server_sizes,
                         server_ordering.clone(),
                         serializer,
+                        require_compression,
                         seed,
+                        max_frame_size,
                         async |_| Ok(()),
                     )
                     .await?;
 
-                let mut trainer = trainer_builder.build(trainer.clone(), server_sizes);
+                if let Some(rounds) = latency_warmup_rounds {
+                    cluster_manager = cluster_manager.with_latency_warmup(rounds);
+                }
+
+                let mut trainer = trainer_builder
+                    .build(trainer.clone(), server_sizes)
+                    .map_err(io::Error::other)?;
                 trainer.load_dataset(data_src);
+                set_global_order(trainer.as_mut(), &spec.trainer, seed, samples)?;
 
-                let worker = ParamServerWorker::new(trainer, cluster_manager, orch_handle);
+                let worker = ParamServerWorker::new(trainer, cluster_manager, orch_handle)
+                    .with_compute_pool(self.compute_pool.clone())
+                    .with_validation(validation);
                 Ok(Box::new(worker) as Box<dyn Worker>)
             }
             AlgorithmSpec::AllReduce {
@@ -133,17 +180,59 @@ where
                         worker_addrs.clone(),
                         model_size,
                         serializer,
+                        require_compression,
                         seed,
+                        max_frame_size,
                         amount_of_layers,
                     )
                     .await?;
 
+                let DatasetSpec { x_size, y_size, .. } = trainer.dataset;
+                let samples = data_src.rows(x_size.get(), y_size.get());
+
                 // SAFETY: The parameter generator was just created.
                 let params = param_gen.sample_remaining().unwrap();
-                let mut trainer = trainer_builder.build(trainer.clone(), &[model_size]);
+                let mut trainer = trainer_builder
+                    .build(trainer.clone(), &[model_size])
+                    .map_err(io::Error::other)?;
                 trainer.load_dataset(data_src);
+                set_global_order(trainer.as_mut(), &spec.trainer, seed, samples)?;
+
+                let worker = AllReduceWorker::new(trainer, ring_manager, orch_handle, params)
+                    .with_compute_pool(self.compute_pool.clone())
+                    .with_validation(validation);
+                Ok(Box::new(worker) as Box<dyn Worker>)
+            }
+            AlgorithmSpec::Evaluate {
+                ref param_gen,
+                amount_of_layers,
+            } => {
+                let param_gen_builder = ParamGenBuilder::new();
+                let mut param_gen = param_gen_builder
+                    .build(param_gen.clone(), spec.seed)
+                    .map_err(io::Error::other)?;
 
-                let worker = AllReduceWorker::new(trainer, ring_manager, orch_handle, params);
+                let model_size = param_gen.size();
+
+                // SAFETY: The parameter generator was just created.
+                let params = param_gen.sample_remaining().unwrap();
+                let evaluator = trainer_builder
+                    .build(trainer.clone(), &[model_size])
+                    .map_err(io::Error::other)?;
+
+                let DatasetSpec { x_size, y_size, .. } = trainer.dataset;
+                let dataset = Dataset::loaded(data_src, x_size, y_size);
+
+                let worker = EvaluateWorker::new(
+                    evaluator,
+                    dataset,
+                    trainer.batch_size,
+                    orch_handle,
+                    params,
+                    amount_of_layers,
+                )
+                .with_compute_pool(self.compute_pool.clone())
+                .with_validation(validation);
                 Ok(Box::new(worker) as Box<dyn Worker>)
             }
         }
@@ -173,29 +262,52 @@ where
         orch_handle: &'a mut OrchHandle<T>,
     ) -> io::Result<ParamServerWorker<'a, T>> {
         let WorkerSpec {
-            serializer, seed, ..
+            serializer,
+            seed,
+            max_frame_size,
+            require_compression,
+            latency_warmup_rounds,
+            ..
         } = spec;
 
+        let samples = dataset.rows() as u64;
+        self.connector.set_samples(Some(samples));
+
+        let dataset_spec = trainer_spec.dataset;
+        let validation = Some(ValidationMetrics::for_loss(
+            trainer_spec.loss_fn,
+            dataset_spec.y_size,
+        ));
         let trainer_builder = TrainerBuilder::new();
-        let mut trainer = trainer_builder.build(trainer_spec, &server_sizes);
+        let mut trainer = trainer_builder
+            .build(trainer_spec, &server_sizes)
+            .map_err(io::Error::other)?;
         trainer.load_dataset(dataset.into_src());
 
-        let cluster_manager = self
+        let mut cluster_manager = self
             .connect_to_servers(
                 &server_addrs,
                 &server_sizes,
                 server_ordering,
                 serializer,
+                require_compression,
                 seed,
+                max_frame_size,
                 async |param_handle| {
-                    let data_src = self.download_dataset(param_handle).await?;
+                    let data_src = self.download_dataset(param_handle, dataset_spec).await?;
                     trainer.load_dataset(data_src);
                     Ok(())
                 },
             )
             .await?;
 
-        let worker = ParamServerWorker::new(trainer, cluster_manager, orch_handle);
+        if let Some(rounds) = latency_warmup_rounds {
+            cluster_manager = cluster_manager.with_latency_warmup(rounds);
+        }
+
+        let worker = ParamServerWorker::new(trainer, cluster_manager, orch_handle)
+            .with_compute_pool(self.compute_pool.clone())
+            .with_validation(validation);
         Ok(worker)
     }
 
@@ -203,16 +315,71 @@ where
     ///
     /// # Args
     /// * `dataset_src` - The handle to communicate with the dataset producer.
+    /// * `dataset` - The specification of the dataset's rows.
     ///
     /// # Returns
     /// A new `Dataset` instance or an io error if occurred.
-    async fn download_dataset<S>(&self, dataset_src: &mut S) -> io::Result<DataSrc>
+    async fn download_dataset<S>(
+        &self,
+        dataset_src: &mut S,
+        dataset: DatasetSpec,
+    ) -> io::Result<DataSrc>
     where
         S: DatasetSrc,
     {
         let (mut xs, mut ys) = (Vec::new(), Vec::new());
         dataset_src.pull_dataset(&mut xs, &mut ys).await?;
-        Ok(DataSrc::inmem(xs, ys))
+
+        if ys.is_empty() {
+            warn!("received an empty dataset partition, sending zero gradients without training");
+        }
+
+        if !dataset.sparse {
+            return Ok(DataSrc::inmem(xs, ys));
+        }
+
+        let nrows = ys.len() / dataset.y_size.get();
+        let samples = CsrMatrix::from_words(dataset.x_size.get(), nrows, &xs)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        Ok(DataSrc::csr(samples, ys))
+    }
+
+    /// Generates the worker's shard of a synthetic dataset.
+    ///
+    /// # Args
+    /// * `synthetic` - The specification of the shard.
+    /// * `dataset` - The specification of the dataset's rows.
+    ///
+    /// # Returns
+    /// The shard's rows or an io error if the spec is invalid.
+    fn generate_dataset(
+        &self,
+        synthetic: &SyntheticSpec,
+        dataset: DatasetSpec,
+    ) -> io::Result<DataSrc> {
+        let SyntheticSpec {
+            seed,
+            ref rows,
+            noise_std,
+        } = *synthetic;
+
+        let rows = rows.start as usize..rows.end as usize;
+        let (x_size, y_size) = (dataset.x_size.get(), dataset.y_size.get());
+
+        let Some(shard) = SyntheticDataset::generate_rows(rows, x_size, y_size, noise_std, seed)
+        else {
+            let text = format!(
+                "the synthetic dataset's noise_std ({noise_std}) must be a finite non negative number"
+            );
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, text));
+        };
+
+        if shard.is_empty() {
+            warn!("generated an empty dataset partition, sending zero gradients without training");
+        }
+
+        Ok(DataSrc::inmem(shard.samples, shard.labels))
     }
 
     /// Connects this worker to all the servers in the network.
@@ -222,7 +389,9 @@ where
     /// * `server_sizes` - The sizes of the servers in amount of parameters they hold.
     /// * `server_ordering` - The ordering of the servers for the layers of the model.
     /// * `serializer_spec` - The spec of the serialization protocol.
+    /// * `require_compression` - Whether to fail if a server lacks the serializer's compression.
     /// * `seed` - An optional seed for the serializer's random number generator.
+    /// * `max_frame_size` - An optional override for the maximum size of the exchanged frames.
     ///
     /// # Returns
     /// A new `ServerClusterManager` instance or an io error if occurred.
@@ -232,7 +401,9 @@ where
         server_sizes: &[usize],
         server_ordering: Vec<usize>,
         serializer_spec: SerializerSpec,
+        require_compression: bool,
         seed: Option<u64>,
+        max_frame_size: Option<usize>,
         mut connection_hook: H,
     ) -> io::Result<ServerClusterManager<T>>
     where
@@ -242,15 +413,25 @@ where
         let src = Entity::Worker;
 
         for (addr, &size) in server_addrs.iter().cloned().zip(server_sizes) {
-            let stream = TcpStream::connect(addr).await?;
+            let stream = comms::connect(&addr, CONNECT_ATTEMPT_TIMEOUT).await?;
             let (rx, tx) = stream.into_split();
 
             let mut server_handle = self.connector.connect_parameter_server(rx, tx, src).await?;
-            if let SerializerSpec::SparseCapable { r } = serializer_spec {
-                server_handle.enable_sparse_capability(r, seed);
+            let capabilities = server_handle.capabilities();
+
+            match negotiate_serializer(serializer_spec, capabilities, require_compression)? {
+                SerializerSpec::Base => {}
+                SerializerSpec::SparseCapable { r } => {
+                    server_handle.enable_sparse_capability(r, seed)
+                }
+                SerializerSpec::TopK { density } => server_handle.enable_top_k_capability(density),
             }
 
             connection_hook(&mut server_handle).await?;
+
+            let frame_size = max_frame_size.unwrap_or_else(|| comms::max_frame_size(size));
+            server_handle.set_max_frame_size(Some(frame_size));
+            server_handle.set_expected_params(Some(size));
             cluster_manager.spawn(server_handle, size);
         }
 
@@ -264,7 +445,9 @@ where
     /// * `worker_addrs` - The addresses of all the workers in the network.
     /// * `model_size` - The amount of parameters of the model.
     /// * `serializer_spec` - The spec of the serialization protocol.
+    /// * `require_compression` - Whether to fail if the next worker lacks the serializer's compression.
     /// * `seed` - An optional seed for the serializer's random number generator.
+    /// * `max_frame_size` - An optional override for the maximum size of the exchanged frames.
     /// * `amount_of_layers` - The amount of layers in the model.
     ///
     /// # Returns
@@ -275,15 +458,25 @@ where
         addrs: Vec<String>,
         model_size: usize,
         serializer_spec: SerializerSpec,
+        require_compression: bool,
         seed: Option<u64>,
+        max_frame_size: Option<usize>,
         amount_of_layers: usize,
     ) -> io::Result<WorkerRingManager<T>> {
         let src = Entity::Worker;
+        let frame_size = max_frame_size.unwrap_or_else(|| comms::max_frame_size(model_size));
 
         let prev_conn_fut = async {
             loop {
-                if let Connection::Worker(worker_handle) = self.acceptor.accept(src).await? {
-                    return Ok::<_, io::Error>(worker_handle);
+                match self.acceptor.accept(src).await {
+                    Ok(Connection::Worker(worker_handle)) => {
+                        return Ok::<_, io::Error>(worker_handle);
+                    }
+                    Ok(_) => {}
+                    Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                        warn!("rejected worker connection: {e}");
+                    }
+                    Err(e) => return Err(e),
                 }
             }
         };
@@ -292,21 +485,88 @@ where
 
         let next_conn_fut = async {
             let addr = &addrs[(pos + 1) % n];
-            let stream = TcpStream::connect(addr).await?;
+            let stream = comms::connect(addr, CONNECT_ATTEMPT_TIMEOUT).await?;
             let (rx, tx) = stream.into_split();
 
             let mut worker_handle = self.connector.connect_worker(rx, tx, src).await?;
-            if let SerializerSpec::SparseCapable { r } = serializer_spec {
-                worker_handle.enable_sparse_capability(r, seed);
+            let capabilities = worker_handle.capabilities();
+
+            match negotiate_serializer(serializer_spec, capabilities, require_compression)? {
+                SerializerSpec::Base => {}
+                SerializerSpec::SparseCapable { r } => {
+                    worker_handle.enable_sparse_capability(r, seed)
+                }
+                SerializerSpec::TopK { density } => worker_handle.enable_top_k_capability(density),
             }
 
             Ok::<_, io::Error>(worker_handle)
         };
 
-        let (prev, next) = tokio::try_join!(prev_conn_fut, next_conn_fut)?;
+        let (mut prev, mut next) = tokio::try_join!(prev_conn_fut, next_conn_fut)?;
+        prev.set_max_frame_size(Some(frame_size));
+        next.set_max_frame_size(Some(frame_size));
+
         let ring_manager =
             WorkerRingManager::new(pos, addrs, prev, next, model_size, amount_of_layers);
 
         Ok(ring_manager)
     }
 }
+
+/// Has the trainer train on it's share of the batches of a `GlobalOrder` if it's sampler
+/// asks for it, the worker must hold the whole dataset.
+///
+/// # Args
+/// * `trainer` - The worker's trainer.
+/// * `spec` - The specification of the trainer.
+/// * `seed` - The seed of the training.
+/// * `nsamples` - The amount of samples in the whole dataset.
+///
+/// # Returns
+/// An io error of kind `InvalidInput` if the sampler asks for a global order without a seed.
+fn set_global_order(
+    trainer: &mut dyn Trainer,
+    spec: &TrainerSpec,
+    seed: Option<u64>,
+    nsamples: usize,
+) -> io::Result<()> {
+    let SamplerSpec::GlobalOrder { worker, nworkers } = spec.sampler else {
+        return Ok(());
+    };
+
+    let Some(seed) = seed else {
+        let text = "the global order sampler requires a seed";
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, text));
+    };
+
+    let shard = GlobalShard::new(nsamples, seed, worker, nworkers, spec.batch_size);
+    trainer.set_batch_order(Box::new(shard));
+    Ok(())
+}
+
+/// Picks the serializer to use with a peer given the compression modes it supports.
+///
+/// # Args
+/// * `serializer_spec` - The configured serializer.
+/// * `capabilities` - The compression modes both ends support.
+/// * `required` - Whether to fail instead of falling back to dense gradients.
+///
+/// # Returns
+/// The configured serializer or the base one if the peer lacks it's compression.
+///
+/// # Errors
+/// An io error of kind `Unsupported` if the peer lacks the compression and it's required.
+fn negotiate_serializer(
+    serializer_spec: SerializerSpec,
+    capabilities: Capabilities,
+    required: bool,
+) -> io::Result<SerializerSpec> {
+    let requested = serializer_spec.compression();
+
+    if capabilities.negotiate(requested, required)? == requested {
+        return Ok(serializer_spec);
+    }
+
+    warn!("the peer lacks {requested} gradient compression, falling back to dense gradients");
+    Ok(SerializerSpec::Base)
+}
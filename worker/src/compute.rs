@@ -0,0 +1,244 @@
+use std::{
+    io,
+    num::NonZeroUsize,
+    sync::{Arc, OnceLock},
+};
+
+use log::warn;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use tokio::{
+    runtime::{Handle, RuntimeFlavor},
+    task,
+};
+
+/// The pool shared by every `ComputePool` built with the default size.
+static DEFAULT_POOL: OnceLock<Arc<ThreadPool>> = OnceLock::new();
+
+/// Whether this build can pin threads to a set of cpus.
+const AFFINITY_SUPPORTED: bool = cfg!(all(feature = "affinity", target_os = "linux"));
+
+/// A bounded pool of threads for the compute bound steps of the training.
+///
+/// Sized to the machine's cores, unlike tokio's blocking pool which grows to hundreds
+/// of threads that would thrash fighting for them.
+#[derive(Clone)]
+pub struct ComputePool {
+    pool: Arc<ThreadPool>,
+}
+
+impl ComputePool {
+    /// Creates a new `ComputePool` with it's own threads.
+    ///
+    /// # Args
+    /// * `nthreads` - The amount of threads of the pool.
+    ///
+    /// # Returns
+    /// A new `ComputePool` instance or an io error if the threads couldn't be spawned.
+    pub fn new(nthreads: NonZeroUsize) -> io::Result<Self> {
+        let pool = Self::builder()
+            .num_threads(nthreads.get())
+            .build()
+            .map_err(io::Error::other)?;
+
+        Ok(Self {
+            pool: Arc::new(pool),
+        })
+    }
+
+    /// Creates a new `ComputePool` with it's own threads pinned to a set of cpus.
+    ///
+    /// Keeping the compute on the cpus of a single socket spares the memory traffic
+    /// across sockets. Pinning needs the `affinity` feature on Linux, elsewhere the
+    /// threads are left unpinned with a warning.
+    ///
+    /// # Args
+    /// * `nthreads` - The amount of threads of the pool, one per cpu if `None`.
+    /// * `cpus` - The cpus every thread of the pool may run on.
+    ///
+    /// # Returns
+    /// A new `ComputePool` instance or an io error if the set of cpus is empty or the
+    /// threads couldn't be spawned.
+    pub fn pinned(nthreads: Option<NonZeroUsize>, cpus: Vec<usize>) -> io::Result<Self> {
+        if cpus.is_empty() {
+            let text = "the compute threads must be pinned to at least a cpu";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, text));
+        }
+
+        let nthreads = nthreads.map_or(cpus.len(), NonZeroUsize::get);
+        let mut builder = Self::builder().num_threads(nthreads);
+
+        if AFFINITY_SUPPORTED {
+            builder = builder.start_handler(move |i| {
+                if let Err(e) = pin_current_thread(&cpus) {
+                    warn!("failed to pin the compute thread {i} to the cpus {cpus:?}: {e}");
+                }
+            });
+        } else {
+            warn!("pinning threads needs the affinity feature on linux, ignoring the cpu affinity");
+        }
+
+        let pool = builder.build().map_err(io::Error::other)?;
+
+        Ok(Self {
+            pool: Arc::new(pool),
+        })
+    }
+
+    /// The amount of threads of the pool.
+    ///
+    /// # Returns
+    /// The pool's thread count.
+    pub fn nthreads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Runs a compute bound closure on the pool, along with the parallel work it spawns.
+    ///
+    /// On a multi thread runtime the calling thread hands it's other tasks over to the
+    /// rest of the runtime while it waits, so the network keeps being served.
+    ///
+    /// # Args
+    /// * `f` - The closure to run.
+    ///
+    /// # Returns
+    /// The closure's result.
+    pub fn run<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R + Send,
+        R: Send,
+    {
+        let multi_thread = Handle::try_current()
+            .is_ok_and(|handle| handle.runtime_flavor() == RuntimeFlavor::MultiThread);
+
+        if multi_thread {
+            task::block_in_place(|| self.pool.install(f))
+        } else {
+            self.pool.install(f)
+        }
+    }
+
+    /// The builder of the pools' threads.
+    ///
+    /// # Returns
+    /// A new `ThreadPoolBuilder` naming it's threads.
+    fn builder() -> ThreadPoolBuilder {
+        ThreadPoolBuilder::new().thread_name(|i| format!("compute-{i}"))
+    }
+}
+
+/// Pins the calling thread to a set of cpus.
+///
+/// # Args
+/// * `cpus` - The cpus the thread may run on.
+///
+/// # Returns
+/// An io error if a cpu is out of range or the kernel rejected the set.
+#[cfg(all(feature = "affinity", target_os = "linux"))]
+fn pin_current_thread(cpus: &[usize]) -> io::Result<()> {
+    let max = libc::CPU_SETSIZE as usize;
+
+    if let Some(cpu) = cpus.iter().find(|&&cpu| cpu >= max) {
+        let text = format!("cpu {cpu} is out of range, the highest is {}", max - 1);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, text));
+    }
+
+    // SAFETY: An all zeroes `cpu_set_t` is the empty set and every cpu added to it was
+    //         checked to be within it's size. A pid of 0 refers to the calling thread.
+    let ret = unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        cpus.iter().for_each(|&cpu| libc::CPU_SET(cpu, &mut set));
+        libc::sched_setaffinity(0, size_of::<libc::cpu_set_t>(), &set)
+    };
+
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Pins the calling thread to a set of cpus, unsupported by this build.
+///
+/// # Args
+/// * `cpus` - The cpus the thread may run on.
+///
+/// # Returns
+/// Always an `Unsupported` io error.
+#[cfg(not(all(feature = "affinity", target_os = "linux")))]
+fn pin_current_thread(_cpus: &[usize]) -> io::Result<()> {
+    let text = "pinning threads needs the affinity feature on linux";
+    Err(io::Error::new(io::ErrorKind::Unsupported, text))
+}
+
+impl Default for ComputePool {
+    /// The pool sized to the machine's cores, shared by the whole process.
+    fn default() -> Self {
+        let pool = DEFAULT_POOL.get_or_init(|| {
+            let pool = Self::builder()
+                .build()
+                .expect("failed to spawn the default compute pool");
+
+            Arc::new(pool)
+        });
+
+        Self {
+            pool: Arc::clone(pool),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    /// Runs a closure on the pool reporting the name of the thread it ran on.
+    fn thread_name(compute_pool: &ComputePool) -> String {
+        compute_pool.run(|| thread::current().name().unwrap_or_default().to_string())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_computes_on_the_pool() {
+        let compute_pool = ComputePool::new(NonZeroUsize::new(2).unwrap()).unwrap();
+
+        assert_eq!(compute_pool.nthreads(), 2);
+        assert!(thread_name(&compute_pool).starts_with("compute-"));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_run_works_on_a_current_thread_runtime() {
+        let compute_pool = ComputePool::default();
+        assert!(thread_name(&compute_pool).starts_with("compute-"));
+    }
+
+    /// The cpus the calling thread may run on.
+    #[cfg(all(feature = "affinity", target_os = "linux"))]
+    fn current_cpus() -> Vec<usize> {
+        // SAFETY: The set is as large as the size given and a pid of 0 refers to the
+        //         calling thread.
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            assert_eq!(
+                libc::sched_getaffinity(0, size_of::<libc::cpu_set_t>(), &mut set),
+                0
+            );
+
+            (0..libc::CPU_SETSIZE as usize)
+                .filter(|&cpu| libc::CPU_ISSET(cpu, &set))
+                .collect()
+        }
+    }
+
+    #[cfg(all(feature = "affinity", target_os = "linux"))]
+    #[test]
+    fn test_the_affinity_mask_is_applied_to_the_compute_threads() {
+        // The sandbox may not let the process run on every cpu, so the pool is pinned
+        // to the first one it's allowed on.
+        let cpu = current_cpus()[0];
+        let compute_pool = ComputePool::pinned(NonZeroUsize::new(2), vec![cpu]).unwrap();
+
+        let masks = compute_pool.pool.broadcast(|_| current_cpus());
+        assert_eq!(masks, [vec![cpu], vec![cpu]]);
+    }
+}
@@ -0,0 +1,217 @@
+use std::num::NonZeroUsize;
+
+pub use machine_learning::datasets::{CSR_CROSSOVER_DENSITY, CsrMatrix};
+use machine_learning::{
+    datasets::BatchOrder,
+    rng::{self, RngPurpose},
+};
+use rand::seq::SliceRandom;
+
+/// Mixes the epoch into the seed so every epoch gets it's own ordering.
+const EPOCH_MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// The order in which the samples of the whole dataset are seen during an epoch.
+///
+/// The order is a function of the seed and the epoch alone, the amount of workers
+/// only decides how each global batch is split between them. This way the sequence
+/// of samples the model sees globally doesn't change with the parallelism.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalOrder {
+    order: Vec<usize>,
+}
+
+impl GlobalOrder {
+    /// Creates a new `GlobalOrder`.
+    ///
+    /// # Args
+    /// * `nsamples` - The amount of samples in the whole dataset.
+    /// * `seed` - The seed of the training.
+    /// * `epoch` - The epoch the order is for.
+    ///
+    /// # Returns
+    /// A new `GlobalOrder` instance.
+    pub fn new(nsamples: usize, seed: u64, epoch: usize) -> Self {
+        let epoch_seed = seed ^ (epoch as u64).wrapping_mul(EPOCH_MIX);
+        let mut rng = rng::rng_for(Some(epoch_seed), RngPurpose::Shuffle);
+        let mut order: Vec<_> = (0..nsamples).collect();
+        order.shuffle(&mut rng);
+        Self { order }
+    }
+
+    /// The indices of the samples in the order they are seen.
+    ///
+    /// # Returns
+    /// The global ordering of the samples.
+    pub fn as_slice(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Partitions every global batch between the workers.
+    ///
+    /// Each global batch of `batch_size` samples is dealt round robin, so the samples
+    /// of a worker's `i`-th batch always belong to the `i`-th global batch.
+    ///
+    /// # Args
+    /// * `worker` - The position of the worker, must be less than `nworkers`.
+    /// * `nworkers` - The amount of workers training.
+    /// * `batch_size` - The size of the global batches.
+    ///
+    /// # Returns
+    /// An iterator over the sample indices of each of the worker's batches.
+    pub fn shard(
+        &self,
+        worker: usize,
+        nworkers: NonZeroUsize,
+        batch_size: NonZeroUsize,
+    ) -> impl Iterator<Item = Vec<usize>> + '_ {
+        self.order.chunks(batch_size.get()).map(move |batch| {
+            batch
+                .iter()
+                .skip(worker)
+                .step_by(nworkers.get())
+                .copied()
+                .collect()
+        })
+    }
+}
+
+/// A worker's share of every batch of the `GlobalOrder` of each epoch.
+///
+/// Set as the trainer's `BatchOrder` the worker holds the whole dataset and trains only
+/// on the samples dealt to it.
+#[derive(Debug, Clone, Copy)]
+pub struct GlobalShard {
+    nsamples: usize,
+    seed: u64,
+    worker: usize,
+    nworkers: NonZeroUsize,
+    batch_size: NonZeroUsize,
+}
+
+impl GlobalShard {
+    /// Creates a new `GlobalShard`.
+    ///
+    /// # Args
+    /// * `nsamples` - The amount of samples in the whole dataset.
+    /// * `seed` - The seed of the training.
+    /// * `worker` - The position of the worker, must be less than `nworkers`.
+    /// * `nworkers` - The amount of workers training.
+    /// * `batch_size` - The size of the global batches.
+    ///
+    /// # Returns
+    /// A new `GlobalShard` instance.
+    pub fn new(
+        nsamples: usize,
+        seed: u64,
+        worker: usize,
+        nworkers: NonZeroUsize,
+        batch_size: NonZeroUsize,
+    ) -> Self {
+        Self {
+            nsamples,
+            seed,
+            worker,
+            nworkers,
+            batch_size,
+        }
+    }
+}
+
+impl BatchOrder for GlobalShard {
+    fn batches(&self, epoch: usize) -> Vec<Vec<usize>> {
+        GlobalOrder::new(self.nsamples, self.seed, epoch)
+            .shard(self.worker, self.nworkers, self.batch_size)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use machine_learning::datasets::{DataSrc, Dataset, OrderedBatches};
+
+    use super::*;
+
+    const NSAMPLES: usize = 103;
+    const SEED: u64 = 42;
+
+    /// Every `(sample, batch)` assignment of the epoch across all the workers.
+    fn assignments(nworkers: usize, batch_size: usize, epoch: usize) -> Vec<(usize, usize)> {
+        let order = GlobalOrder::new(NSAMPLES, SEED, epoch);
+        let nworkers = NonZeroUsize::new(nworkers).unwrap();
+        let batch_size = NonZeroUsize::new(batch_size).unwrap();
+
+        let mut assignments: Vec<_> = (0..nworkers.get())
+            .flat_map(|worker| {
+                order
+                    .shard(worker, nworkers, batch_size)
+                    .enumerate()
+                    .flat_map(|(batch, samples)| samples.into_iter().map(move |s| (s, batch)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        assignments.sort_unstable();
+        assignments
+    }
+
+    #[test]
+    fn test_batches_dont_depend_on_the_amount_of_workers() {
+        for epoch in 0..3 {
+            let two = assignments(2, 16, epoch);
+            let four = assignments(4, 16, epoch);
+
+            assert_eq!(two.len(), NSAMPLES);
+            assert_eq!(two, four);
+        }
+    }
+
+    /// Every `(sample, batch)` the workers' trainers load over the epoch, out of a dataset
+    /// whose samples are their own row index.
+    fn loaded(nworkers: usize, batch_size: usize, epoch: usize) -> Vec<(usize, usize)> {
+        let samples = (0..NSAMPLES).map(|row| row as f32).collect();
+        let src = DataSrc::inmem(samples, vec![0.0; NSAMPLES]);
+        let dataset = Dataset::loaded(src, NonZeroUsize::MIN, NonZeroUsize::MIN);
+        let nworkers = NonZeroUsize::new(nworkers).unwrap();
+        let batch_size = NonZeroUsize::new(batch_size).unwrap();
+
+        let mut loaded: Vec<_> = (0..nworkers.get())
+            .flat_map(|worker| {
+                let shard = GlobalShard::new(NSAMPLES, SEED, worker, nworkers, batch_size);
+                let batches = OrderedBatches::new(&dataset, &shard.batches(epoch));
+
+                batches
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(batch, (x, _))| {
+                        let x = x.to_dense();
+                        x.iter().map(|&s| (s as usize, batch)).collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        loaded.sort_unstable();
+        loaded
+    }
+
+    #[test]
+    fn test_loaded_batches_dont_depend_on_the_amount_of_workers() {
+        for epoch in 0..3 {
+            let two = loaded(2, 16, epoch);
+            let four = loaded(4, 16, epoch);
+
+            assert_eq!(two.len(), NSAMPLES);
+            assert_eq!(two, four);
+            assert_eq!(two, assignments(1, 16, epoch));
+        }
+    }
+
+    #[test]
+    fn test_order_depends_on_seed_and_epoch() {
+        let order = GlobalOrder::new(NSAMPLES, SEED, 0);
+
+        assert_eq!(order, GlobalOrder::new(NSAMPLES, SEED, 0));
+        assert_ne!(order, GlobalOrder::new(NSAMPLES, SEED, 1));
+        assert_ne!(order, GlobalOrder::new(NSAMPLES, SEED + 1, 0));
+    }
+}
@@ -1,3 +1,8 @@
 pub mod builder;
+pub mod compute;
+pub mod data;
+pub mod metrics;
 pub mod middlewares;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod workers;
@@ -0,0 +1,534 @@
+use std::num::NonZeroUsize;
+
+use comms::specs::{
+    machine_learning::LossFnSpec,
+    worker::{ConfusionCounts, MetricsReport, RegressionSums},
+};
+use machine_learning::{MlErr, Result};
+
+/// The metrics of the final model over a worker's partition, the ones that apply to the
+/// loss function it was trained with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationMetrics {
+    /// The classifications of a model trained with cross entropy.
+    Classification(ConfusionMatrix),
+    /// The regression metrics of a model trained with the mean squared error.
+    Regression(RegressionMetrics),
+}
+
+impl ValidationMetrics {
+    /// Creates the empty metrics that apply to a loss function.
+    ///
+    /// # Args
+    /// * `loss_fn` - The loss function the model is trained with.
+    /// * `y_size` - The size of the model's output.
+    ///
+    /// # Returns
+    /// The metrics to accumulate.
+    pub fn for_loss(loss_fn: LossFnSpec, y_size: NonZeroUsize) -> Self {
+        match loss_fn {
+            LossFnSpec::CrossEntropy => Self::Classification(ConfusionMatrix::new(y_size)),
+            LossFnSpec::Mse => Self::Regression(RegressionMetrics::default()),
+        }
+    }
+
+    /// Accumulates a batch of predictions against their targets.
+    ///
+    /// # Args
+    /// * `predictions` - The outputs of the model.
+    /// * `targets` - The expected outputs, of the same length as `predictions`.
+    ///
+    /// # Returns
+    /// A size mismatch error if the batch doesn't fit the metrics.
+    pub fn update(&mut self, predictions: &[f32], targets: &[f32]) -> Result<()> {
+        match self {
+            Self::Classification(confusion) => confusion.update(predictions, targets),
+            Self::Regression(regression) => {
+                if predictions.len() != targets.len() {
+                    return Err(MlErr::size_mismatch(
+                        "predictions and targets",
+                        predictions.len(),
+                        targets.len(),
+                    ));
+                }
+
+                regression.update(predictions, targets);
+                Ok(())
+            }
+        }
+    }
+
+    /// Attaches the metrics to a report for the orchestrator.
+    ///
+    /// # Args
+    /// * `report` - The report to carry the metrics.
+    ///
+    /// # Returns
+    /// The report with the metrics set.
+    pub fn attach(self, report: MetricsReport) -> MetricsReport {
+        match self {
+            Self::Classification(confusion) => report.with_confusion(confusion.into()),
+            Self::Regression(regression) => report.with_regression(regression.into()),
+        }
+    }
+}
+
+/// Accumulates the regression metrics of a model's predictions over a dataset split.
+///
+/// The target variance is tracked with Welford's algorithm, so partial metrics
+/// computed by different workers can be merged without losing precision.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RegressionMetrics {
+    n: usize,
+    target_mean: f64,
+    target_m2: f64,
+    rss: f64,
+    squared_log_error: f64,
+}
+
+impl RegressionMetrics {
+    /// Accumulates a batch of predictions against their targets.
+    ///
+    /// # Args
+    /// * `predictions` - The outputs of the model.
+    /// * `targets` - The expected outputs, of the same length as `predictions`.
+    pub fn update(&mut self, predictions: &[f32], targets: &[f32]) {
+        for (&p, &y) in predictions.iter().zip(targets) {
+            let (p, y) = (p as f64, y as f64);
+
+            self.n += 1;
+            let delta = y - self.target_mean;
+            self.target_mean += delta / self.n as f64;
+            self.target_m2 += delta * (y - self.target_mean);
+
+            self.rss += (p - y).powi(2);
+            self.squared_log_error += (p.ln_1p() - y.ln_1p()).powi(2);
+        }
+    }
+
+    /// Merges the metrics accumulated over another part of the split.
+    ///
+    /// # Args
+    /// * `other` - The partial metrics to merge into these.
+    pub fn merge(&mut self, other: &Self) {
+        if other.n == 0 {
+            return;
+        }
+
+        let n = self.n + other.n;
+        let delta = other.target_mean - self.target_mean;
+        let (a, b) = (self.n as f64, other.n as f64);
+
+        self.target_mean += delta * b / n as f64;
+        self.target_m2 += other.target_m2 + delta * delta * a * b / n as f64;
+        self.rss += other.rss;
+        self.squared_log_error += other.squared_log_error;
+        self.n = n;
+    }
+
+    /// The amount of accumulated values.
+    ///
+    /// # Returns
+    /// The amount of predictions seen.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Whether no predictions were accumulated.
+    ///
+    /// # Returns
+    /// `true` if the metrics are empty.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// The mean squared error.
+    ///
+    /// # Returns
+    /// The mean of the squared residuals, `NaN` if empty.
+    pub fn mse(&self) -> f64 {
+        self.rss / self.n as f64
+    }
+
+    /// The mean squared logarithmic error.
+    ///
+    /// Only defined for predictions and targets greater than -1, otherwise it's `NaN`.
+    ///
+    /// # Returns
+    /// The mean of the squared differences of `ln(1 + x)`, `NaN` if empty.
+    pub fn msle(&self) -> f64 {
+        self.squared_log_error / self.n as f64
+    }
+
+    /// The coefficient of determination, `1 - RSS / TSS`.
+    ///
+    /// R² is undefined when the targets have no variance, in which case
+    /// `NaN` is returned instead of dividing by zero.
+    ///
+    /// # Returns
+    /// The R² of the predictions, `NaN` if empty or the targets are constant.
+    pub fn r_squared(&self) -> f64 {
+        if self.target_m2 == 0.0 {
+            return f64::NAN;
+        }
+
+        1.0 - self.rss / self.target_m2
+    }
+}
+
+/// Counts the classifications of a model over a dataset split.
+///
+/// Row `i` and column `j` hold the amount of samples of class `i` that were
+/// predicted as class `j`, the predicted class being the argmax of the outputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfusionMatrix {
+    nclasses: NonZeroUsize,
+    counts: Vec<u64>,
+}
+
+impl ConfusionMatrix {
+    /// Creates a new empty `ConfusionMatrix`.
+    ///
+    /// # Args
+    /// * `nclasses` - The amount of classes, the size of the model's output.
+    ///
+    /// # Returns
+    /// A new `ConfusionMatrix` instance with every count at zero.
+    pub fn new(nclasses: NonZeroUsize) -> Self {
+        Self {
+            nclasses,
+            counts: vec![0; nclasses.get() * nclasses.get()],
+        }
+    }
+
+    /// Accumulates a batch of predictions against their one-hot targets.
+    ///
+    /// # Args
+    /// * `predictions` - The outputs of the model, `nclasses` per sample.
+    /// * `targets` - The one-hot expected outputs, of the same length as `predictions`.
+    ///
+    /// # Returns
+    /// A size mismatch error if the lengths don't match or aren't a multiple of `nclasses`.
+    pub fn update(&mut self, predictions: &[f32], targets: &[f32]) -> Result<()> {
+        let nclasses = self.nclasses.get();
+
+        if predictions.len() != targets.len() {
+            return Err(MlErr::size_mismatch(
+                "predictions and targets",
+                predictions.len(),
+                targets.len(),
+            ));
+        }
+
+        if !predictions.len().is_multiple_of(nclasses) {
+            let expected = predictions.len().next_multiple_of(nclasses);
+            return Err(MlErr::size_mismatch(
+                "predictions",
+                predictions.len(),
+                expected,
+            ));
+        }
+
+        for (prediction, target) in predictions.chunks(nclasses).zip(targets.chunks(nclasses)) {
+            let actual = argmax(target);
+            let predicted = argmax(prediction);
+            self.counts[actual * nclasses + predicted] += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Merges the counts accumulated over another part of the split.
+    ///
+    /// # Args
+    /// * `other` - The partial matrix to merge into this one.
+    ///
+    /// # Returns
+    /// A size mismatch error if both matrices have a different amount of classes.
+    pub fn merge(&mut self, other: &Self) -> Result<()> {
+        if self.nclasses != other.nclasses {
+            return Err(MlErr::size_mismatch(
+                "confusion matrix classes",
+                other.nclasses.get(),
+                self.nclasses.get(),
+            ));
+        }
+
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+
+        Ok(())
+    }
+
+    /// The amount of classes.
+    ///
+    /// # Returns
+    /// The amount of rows and columns of the matrix.
+    pub fn nclasses(&self) -> usize {
+        self.nclasses.get()
+    }
+
+    /// The amount of samples of a class predicted as another.
+    ///
+    /// # Args
+    /// * `actual` - The class of the samples.
+    /// * `predicted` - The class they were predicted as.
+    ///
+    /// # Returns
+    /// The count, zero if any of the classes is out of range.
+    pub fn count(&self, actual: usize, predicted: usize) -> u64 {
+        let nclasses = self.nclasses.get();
+
+        if actual >= nclasses || predicted >= nclasses {
+            return 0;
+        }
+
+        self.counts[actual * nclasses + predicted]
+    }
+
+    /// The counts of the matrix.
+    ///
+    /// # Returns
+    /// The counts in row major order, a row per actual class.
+    pub fn as_slice(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// The amount of accumulated samples.
+    ///
+    /// # Returns
+    /// The sum of every count.
+    pub fn len(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Whether no samples were accumulated.
+    ///
+    /// # Returns
+    /// `true` if every count is zero.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The fraction of the samples that were classified correctly.
+    ///
+    /// # Returns
+    /// The sum of the diagonal over the total, `NaN` if empty.
+    pub fn accuracy(&self) -> f64 {
+        let nclasses = self.nclasses.get();
+        let correct: u64 = (0..nclasses).map(|i| self.counts[i * nclasses + i]).sum();
+        correct as f64 / self.len() as f64
+    }
+}
+
+impl From<RegressionMetrics> for RegressionSums {
+    fn from(value: RegressionMetrics) -> Self {
+        Self {
+            n: value.n as u64,
+            target_mean: value.target_mean,
+            target_m2: value.target_m2,
+            rss: value.rss,
+            squared_log_error: value.squared_log_error,
+        }
+    }
+}
+
+impl From<RegressionSums> for RegressionMetrics {
+    fn from(value: RegressionSums) -> Self {
+        Self {
+            n: value.n as usize,
+            target_mean: value.target_mean,
+            target_m2: value.target_m2,
+            rss: value.rss,
+            squared_log_error: value.squared_log_error,
+        }
+    }
+}
+
+impl From<ConfusionMatrix> for ConfusionCounts {
+    fn from(value: ConfusionMatrix) -> Self {
+        Self {
+            nclasses: value.nclasses.get(),
+            counts: value.counts,
+        }
+    }
+}
+
+impl TryFrom<ConfusionCounts> for ConfusionMatrix {
+    type Error = MlErr;
+
+    fn try_from(value: ConfusionCounts) -> Result<Self> {
+        let ConfusionCounts { nclasses, counts } = value;
+        let nclasses = NonZeroUsize::new(nclasses).ok_or(MlErr::size_mismatch(
+            "confusion matrix classes",
+            0,
+            1,
+        ))?;
+
+        let expected = nclasses.get() * nclasses.get();
+        if counts.len() != expected {
+            return Err(MlErr::size_mismatch(
+                "confusion matrix counts",
+                counts.len(),
+                expected,
+            ));
+        }
+
+        Ok(Self { nclasses, counts })
+    }
+}
+
+/// The index of the greatest value, the first one on ties.
+///
+/// # Args
+/// * `values` - A non empty slice of values.
+///
+/// # Returns
+/// The position of the maximum.
+fn argmax(values: &[f32]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .fold((0, f32::NEG_INFINITY), |(best, max), (i, &v)| {
+            if v > max { (i, v) } else { (best, max) }
+        })
+        .0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGETS: [f32; 6] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+
+    #[test]
+    fn test_perfect_predictions_have_r_squared_one() {
+        let mut metrics = RegressionMetrics::default();
+        metrics.update(&TARGETS, &TARGETS);
+
+        assert_eq!(metrics.r_squared(), 1.0);
+        assert_eq!(metrics.mse(), 0.0);
+        assert_eq!(metrics.msle(), 0.0);
+    }
+
+    #[test]
+    fn test_mean_predictions_have_r_squared_zero() {
+        let mut metrics = RegressionMetrics::default();
+        metrics.update(&[3.5; 6], &TARGETS);
+
+        assert!(metrics.r_squared().abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_constant_targets_have_undefined_r_squared() {
+        let mut metrics = RegressionMetrics::default();
+        metrics.update(&[1.0, 2.0, 3.0], &[2.0; 3]);
+
+        assert!(metrics.r_squared().is_nan());
+        assert!(RegressionMetrics::default().r_squared().is_nan());
+    }
+
+    #[test]
+    fn test_merged_metrics_match_a_single_pass() {
+        let predictions = [1.5, 1.0, 3.5, 4.0, 6.0, 5.5];
+
+        let mut whole = RegressionMetrics::default();
+        whole.update(&predictions, &TARGETS);
+
+        let mut left = RegressionMetrics::default();
+        left.update(&predictions[..2], &TARGETS[..2]);
+        let mut right = RegressionMetrics::default();
+        right.update(&predictions[2..], &TARGETS[2..]);
+        left.merge(&right);
+
+        assert_eq!(left.len(), whole.len());
+        assert!((left.r_squared() - whole.r_squared()).abs() < 1e-12);
+        assert!((left.msle() - whole.msle()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_regression_sums_round_trip_through_the_wire() {
+        let mut metrics = RegressionMetrics::default();
+        metrics.update(&[1.5, 1.0, 3.5], &TARGETS[..3]);
+
+        let sums = RegressionSums::from(metrics);
+        assert_eq!(sums.n, 3);
+        assert_eq!(RegressionMetrics::from(sums), metrics);
+    }
+
+    #[test]
+    fn test_confusion_matrix_counts_argmax_against_targets() {
+        let nclasses = NonZeroUsize::new(3).unwrap();
+        let mut matrix = ConfusionMatrix::new(nclasses);
+
+        let targets = [1., 0., 0., 0., 1., 0., 0., 0., 1., 0., 0., 1.];
+        let predictions = [0.8, 0.1, 0.1, 0.2, 0.7, 0.1, 0.5, 0.3, 0.2, 0.1, 0.1, 0.8];
+        matrix.update(&predictions, &targets).unwrap();
+
+        assert_eq!(matrix.as_slice(), [1, 0, 0, 0, 1, 0, 1, 0, 1]);
+        assert_eq!(matrix.count(2, 0), 1);
+        assert_eq!(matrix.accuracy(), 0.75);
+    }
+
+    #[test]
+    fn test_confusion_matrix_rejects_mismatched_dimensions() {
+        let nclasses = NonZeroUsize::new(3).unwrap();
+        let mut matrix = ConfusionMatrix::new(nclasses);
+
+        assert!(matrix.update(&[0.; 6], &[0.; 3]).is_err());
+        assert!(matrix.update(&[0.; 4], &[0.; 4]).is_err());
+
+        let other = ConfusionMatrix::new(NonZeroUsize::new(2).unwrap());
+        assert!(matrix.merge(&other).is_err());
+        assert!(matrix.is_empty());
+    }
+
+    #[test]
+    fn test_merged_confusion_matrices_match_a_single_pass() {
+        let nclasses = NonZeroUsize::new(2).unwrap();
+        let targets = [1., 0., 0., 1., 0., 1., 1., 0.];
+        let predictions = [0.9, 0.1, 0.6, 0.4, 0.2, 0.8, 0.3, 0.7];
+
+        let mut whole = ConfusionMatrix::new(nclasses);
+        whole.update(&predictions, &targets).unwrap();
+
+        let mut left = ConfusionMatrix::new(nclasses);
+        left.update(&predictions[..2], &targets[..2]).unwrap();
+        let mut right = ConfusionMatrix::new(nclasses);
+        right.update(&predictions[2..], &targets[2..]).unwrap();
+        left.merge(&right).unwrap();
+
+        assert_eq!(left, whole);
+        assert_eq!(whole.len(), 4);
+    }
+
+    #[test]
+    fn test_empty_split_has_a_zero_confusion_matrix() {
+        let nclasses = NonZeroUsize::new(4).unwrap();
+        let mut matrix = ConfusionMatrix::new(nclasses);
+        matrix.update(&[], &[]).unwrap();
+
+        assert_eq!(matrix.as_slice(), [0; 16]);
+        assert!(matrix.accuracy().is_nan());
+    }
+
+    #[test]
+    fn test_confusion_counts_round_trip_through_the_wire() {
+        let nclasses = NonZeroUsize::new(2).unwrap();
+        let mut matrix = ConfusionMatrix::new(nclasses);
+        matrix
+            .update(&[0.9, 0.1, 0.2, 0.8], &[1., 0., 1., 0.])
+            .unwrap();
+
+        let counts = ConfusionCounts::from(matrix.clone());
+        assert_eq!(ConfusionMatrix::try_from(counts).unwrap(), matrix);
+
+        let truncated = ConfusionCounts {
+            nclasses: 2,
+            counts: vec![1, 0, 1],
+        };
+        assert!(ConfusionMatrix::try_from(truncated).is_err());
+        assert!(ConfusionMatrix::try_from(ConfusionCounts::default()).is_err());
+    }
+}
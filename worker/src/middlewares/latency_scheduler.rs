@@ -0,0 +1,82 @@
+use std::{cmp::Reverse, num::NonZeroUsize, time::Duration};
+
+/// Picks the order the requests to the servers are issued in from their round trip times.
+///
+/// Over the first rounds it adds up how long every server takes to answer, then orders
+/// them from the slowest to the fastest so the slow ones start working the earliest and
+/// their latency hides behind the rest. The requests are joined all the same, so the
+/// order never changes the result, only how long the round takes.
+#[derive(Debug)]
+pub struct LatencyScheduler {
+    warmup: NonZeroUsize,
+    rounds: usize,
+    totals: Vec<Duration>,
+}
+
+impl LatencyScheduler {
+    /// Creates a new `LatencyScheduler`.
+    ///
+    /// # Args
+    /// * `warmup` - The amount of measured rounds before ordering the servers.
+    ///
+    /// # Returns
+    /// A new `LatencyScheduler` instance.
+    pub fn new(warmup: NonZeroUsize) -> Self {
+        Self {
+            warmup,
+            rounds: 0,
+            totals: Vec::new(),
+        }
+    }
+
+    /// Records the round trip times of a round.
+    ///
+    /// # Args
+    /// * `rtts` - The round trip time of every server, `None` if it wasn't measured.
+    ///
+    /// # Returns
+    /// The indices of the servers from the slowest to the fastest once the warmup is
+    /// over, `None` before and after it.
+    pub fn record(&mut self, rtts: &[Option<Duration>]) -> Option<Vec<usize>> {
+        if self.rounds >= self.warmup.get() || rtts.iter().all(Option::is_none) {
+            return None;
+        }
+
+        self.totals.resize(rtts.len(), Duration::ZERO);
+
+        for (total, rtt) in self.totals.iter_mut().zip(rtts) {
+            *total += rtt.unwrap_or_default();
+        }
+
+        self.rounds += 1;
+        if self.rounds < self.warmup.get() {
+            return None;
+        }
+
+        let mut order: Vec<_> = (0..self.totals.len()).collect();
+        order.sort_by_key(|&i| Reverse(self.totals[i]));
+        Some(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(rtts: &[u64]) -> Vec<Option<Duration>> {
+        rtts.iter()
+            .map(|&ms| Some(Duration::from_millis(ms)))
+            .collect()
+    }
+
+    #[test]
+    fn test_servers_are_ordered_slowest_first_once_the_warmup_ends() {
+        let mut scheduler = LatencyScheduler::new(NonZeroUsize::new(2).unwrap());
+
+        // The first round has nothing to measure, the parameters weren't pushed yet.
+        assert_eq!(scheduler.record(&[None, None, None]), None);
+        assert_eq!(scheduler.record(&ms(&[5, 30, 10])), None);
+        assert_eq!(scheduler.record(&ms(&[5, 2, 40])), Some(vec![2, 1, 0]));
+        assert_eq!(scheduler.record(&ms(&[90, 1, 1])), None);
+    }
+}
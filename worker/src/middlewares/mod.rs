@@ -1,8 +1,10 @@
+mod latency_scheduler;
 mod server_cluster;
 mod worker_ring;
 
 use std::{mem, num::NonZeroUsize};
 
+pub use latency_scheduler::LatencyScheduler;
 pub use server_cluster::ServerClusterManager;
 pub use worker_ring::WorkerRingManager;
 
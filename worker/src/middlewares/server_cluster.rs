@@ -1,8 +1,11 @@
-use std::io;
+use std::{io, num::NonZeroUsize, time::Duration};
 
 use comms::{ParamServerCluster, ParamServerHandle, TransportLayer};
+use log::info;
 use machine_learning::param_manager::{ParamManager, ParamsMetadata};
 
+use super::LatencyScheduler;
+
 // The communication manager between the worker process and the many servers.
 pub struct ServerClusterManager<T>
 where
@@ -12,6 +15,10 @@ where
     server_ordering: Vec<usize>,
     residuals: Vec<Vec<f32>>,
     grads: Vec<Vec<f32>>,
+    latency_scheduler: Option<LatencyScheduler>,
+    /// Whether the servers sent parameters that weren't pulled yet, they send them as soon
+    /// as a worker connects and after every gradient.
+    unpulled: bool,
 }
 
 impl<T> ServerClusterManager<T>
@@ -31,9 +38,24 @@ where
             server_ordering,
             residuals: Vec::new(),
             grads: Vec::new(),
+            latency_scheduler: None,
+            unpulled: true,
         }
     }
 
+    /// Measures the round trip time of every server over the first rounds and then
+    /// issues the requests to the slowest ones first, to hide their latency.
+    ///
+    /// # Args
+    /// * `rounds` - The amount of rounds to measure before ordering the servers.
+    ///
+    /// # Returns
+    /// The modified `ServerClusterManager`.
+    pub fn with_latency_warmup(mut self, rounds: NonZeroUsize) -> Self {
+        self.latency_scheduler = Some(LatencyScheduler::new(rounds));
+        self
+    }
+
     /// Adds a new server communicator to the middleware.
     ///
     /// # Args
@@ -51,6 +73,7 @@ where
     /// A new `ParamManager` instance with all the parameters.
     pub async fn pull_params(&mut self) -> io::Result<ParamManager<'_>> {
         let cluster_params = self.cluster.pull_params().await;
+        self.unpulled = false;
         let mut metadatas = Vec::with_capacity(cluster_params.len());
 
         for ((res, grad), residual) in cluster_params
@@ -80,7 +103,19 @@ where
     /// # Returns
     /// An io error if occurred.
     pub async fn push_grads(&mut self) -> io::Result<()> {
+        // The round trip times of the previous round are known once it's parameters were pulled.
+        if let Some(ref mut scheduler) = self.latency_scheduler
+            && let Some(order) = scheduler.record(&self.cluster.rtts())
+        {
+            info!(
+                "issuing the requests to the servers in the order {order:?}, slowest first, rtts: {:?}",
+                self.cluster.rtts()
+            );
+            self.cluster.set_issue_order(&order);
+        }
+
         let thresholds = self.cluster.push_grads(&self.residuals).await;
+        self.unpulled = true;
 
         for (residual, threshold) in self.residuals.iter_mut().zip(thresholds) {
             match threshold {
@@ -100,12 +135,32 @@ where
         Ok(())
     }
 
-    /// Disconnects this worker from all the servers.
+    /// The round trip time of the latest exchange with every server.
+    ///
+    /// # Returns
+    /// The round trip time of every server, `None` if it wasn't measured yet.
+    pub fn rtts(&self) -> Vec<Option<Duration>> {
+        self.cluster.rtts()
+    }
+
+    /// The amount of bytes exchanged with all the servers.
+    ///
+    /// # Returns
+    /// The total amount of sent and received bytes.
+    pub fn bytes_transferred(&self) -> (u64, u64) {
+        self.cluster.bytes_transferred()
+    }
+
+    /// Disconnects this worker from all the servers, discarding the parameters they sent
+    /// that weren't pulled.
     ///
     /// # Returns
     /// An io error if occurred.
     pub async fn disconnect(&mut self) -> io::Result<()> {
-        self.cluster.discard_one().await?;
+        if self.unpulled {
+            self.cluster.discard_one().await?;
+        }
+
         self.cluster.disconnect().await
     }
 }
@@ -1,7 +1,11 @@
-use std::{io, num::NonZeroUsize};
+use std::{
+    io, mem,
+    num::NonZeroUsize,
+    time::{Duration, Instant},
+};
 
 use comms::{TransportLayer, WorkerEvent, WorkerHandle};
-use machine_learning::param_manager::ParamManager;
+use machine_learning::{MlErr, Phase, param_manager::ParamManager};
 
 use super::SplitIntoChunksMut;
 
@@ -18,6 +22,7 @@ where
     grad: Vec<f32>,
     residual: Vec<f32>,
     amount_of_layers: usize,
+    waited: Duration,
 }
 
 impl<T> WorkerRingManager<T>
@@ -52,9 +57,28 @@ where
             grad: vec![0.0; size],
             residual: vec![0.0; size],
             amount_of_layers,
+            waited: Duration::ZERO,
         }
     }
 
+    /// The amount of bytes exchanged with the previous and next workers.
+    ///
+    /// # Returns
+    /// The total amount of sent and received bytes.
+    pub fn bytes_transferred(&self) -> (u64, u64) {
+        let sent = self.prev.bytes_sent() + self.next.bytes_sent();
+        let received = self.prev.bytes_received() + self.next.bytes_received();
+        (sent, received)
+    }
+
+    /// Takes the time spent blocked on the previous worker since the last call.
+    ///
+    /// # Returns
+    /// The time waited receiving the gradient chunks.
+    pub fn take_waited(&mut self) -> Duration {
+        mem::take(&mut self.waited)
+    }
+
     /// Creates a new `ParameterManager` binded to this ring manager's buffers.
     ///
     /// # Args
@@ -112,10 +136,16 @@ where
             }
 
             i = (i + n.get() - 1) % n.get();
-            let WorkerEvent::Grad(agg_grad) = self.prev.recv_event().await? else {
+            let start = Instant::now();
+            let event = self.prev.recv_event().await?;
+            self.waited += start.elapsed();
+
+            let WorkerEvent::Grad(agg_grad) = event else {
                 return Err(io::Error::other("Received an invalid worker event"));
             };
 
+            check_chunk_size(agg_grad, chunks[i])?;
+
             for (acc, g) in chunks[i].iter_mut().zip(agg_grad) {
                 *acc += *g;
             }
@@ -168,13 +198,37 @@ where
             }
 
             i = (i + n.get() - 1) % n.get();
-            let WorkerEvent::Grad(acc_grad) = self.prev.recv_event().await? else {
+            let start = Instant::now();
+            let event = self.prev.recv_event().await?;
+            self.waited += start.elapsed();
+
+            let WorkerEvent::Grad(acc_grad) = event else {
                 return Err(io::Error::other("Received an invalid worker event"));
             };
 
+            check_chunk_size(acc_grad, chunks[i])?;
             chunks[i].copy_from_slice(acc_grad);
         }
 
         Ok(())
     }
 }
+
+/// Checks that a gradient chunk received from the previous worker matches the local one.
+///
+/// # Args
+/// * `received` - The chunk received from the ring.
+/// * `local` - This worker's chunk at the same position.
+///
+/// # Returns
+/// An io error wrapping a size mismatch if their lengths differ.
+fn check_chunk_size(received: &[f32], local: &[f32]) -> io::Result<()> {
+    if received.len() != local.len() {
+        let e = MlErr::size_mismatch("gradient chunk", received.len(), local.len())
+            .during(Phase::GradientPush);
+
+        return Err(io::Error::other(e));
+    }
+
+    Ok(())
+}
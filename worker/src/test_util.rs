@@ -0,0 +1,193 @@
+use std::{collections::VecDeque, io};
+
+use comms::{ParamServerHandle, Stp, TransportLayer, WorkerEvent, WorkerHandle};
+use tokio::io::{DuplexStream, ReadHalf, WriteHalf};
+use uuid::Uuid;
+
+/// The transport of both ends of an in memory duplex channel.
+pub type DuplexStp = Stp<ReadHalf<DuplexStream>, WriteHalf<DuplexStream>>;
+
+/// A message a `MockParameterServer` received from the worker.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Received {
+    Grad(Vec<f32>),
+    RequestParams,
+    Disconnect,
+    Other(String),
+}
+
+/// A parameter server answering a worker with a scripted sequence of weights.
+///
+/// It pushes the first weights as soon as it runs and the next ones after every gradient,
+/// repeating the last ones once the script runs out. Everything the worker sends is recorded
+/// to assert on after the run, without standing up a real server.
+pub struct MockParameterServer<T>
+where
+    T: TransportLayer,
+{
+    worker_handle: WorkerHandle<T>,
+    script: VecDeque<Vec<f32>>,
+    params: Vec<f32>,
+    received: Vec<Received>,
+}
+
+impl<T> MockParameterServer<T>
+where
+    T: TransportLayer,
+{
+    /// Creates a new `MockParameterServer`.
+    ///
+    /// # Args
+    /// * `worker_handle` - The handle for communicating with the worker.
+    /// * `script` - The weights pushed to the worker, in order.
+    ///
+    /// # Returns
+    /// A new `MockParameterServer` instance.
+    pub fn new(worker_handle: WorkerHandle<T>, script: Vec<Vec<f32>>) -> Self {
+        Self {
+            worker_handle,
+            script: script.into(),
+            params: Vec::new(),
+            received: Vec::new(),
+        }
+    }
+
+    /// Serves the worker until it disconnects.
+    ///
+    /// # Returns
+    /// Everything the worker sent, in order, or an io error if occurred.
+    pub async fn run(mut self) -> io::Result<Vec<Received>> {
+        self.push_next().await?;
+
+        loop {
+            let received = match self.worker_handle.recv_event().await? {
+                WorkerEvent::Grad(grad) => Received::Grad(grad.to_vec()),
+                WorkerEvent::RequestParams => Received::RequestParams,
+                WorkerEvent::Disconnect => Received::Disconnect,
+                other => Received::Other(format!("{other:?}")),
+            };
+
+            match received {
+                Received::Grad(_) => self.push_next().await?,
+                Received::RequestParams => self.worker_handle.push_params(&mut self.params).await?,
+                _ => {}
+            }
+
+            let disconnected = received == Received::Disconnect;
+            self.received.push(received);
+
+            if disconnected {
+                break;
+            }
+        }
+
+        Ok(self.received)
+    }
+
+    /// Pushes the next weights of the script to the worker, or the last ones again if it ran out.
+    ///
+    /// # Errors
+    /// An io error if the weights couldn't be sent.
+    async fn push_next(&mut self) -> io::Result<()> {
+        if let Some(params) = self.script.pop_front() {
+            self.params = params;
+        }
+
+        self.worker_handle.push_params(&mut self.params).await
+    }
+}
+
+impl MockParameterServer<DuplexStp> {
+    /// Creates a new `MockParameterServer` at one end of an in memory duplex channel.
+    ///
+    /// # Args
+    /// * `script` - The weights pushed to the worker, in order.
+    ///
+    /// # Returns
+    /// The mock server and the handle the worker talks to it through.
+    pub fn duplex(script: Vec<Vec<f32>>) -> (Self, ParamServerHandle<DuplexStp>) {
+        let (server_end, worker_end) = tokio::io::duplex(4096);
+        let (rx, tx) = tokio::io::split(server_end);
+        let worker_handle = WorkerHandle::new(Uuid::new_v4(), Stp::new(rx, tx));
+
+        let (rx, tx) = tokio::io::split(worker_end);
+        let server_handle = ParamServerHandle::new(Uuid::new_v4(), Stp::new(rx, tx));
+
+        (Self::new(worker_handle, script), server_handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use comms::{OrchHandle, floats::FloatPositive, specs::machine_learning::StopCondition};
+    use machine_learning::{
+        arch::{Sequential, layers::Layer, loss::Mse},
+        datasets::{DataSrc, Dataset},
+        optimization::GradientDescent,
+        training::BackpropTrainer,
+    };
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        middlewares::ServerClusterManager,
+        workers::{ParamServerWorker, Worker},
+    };
+
+    #[tokio::test]
+    async fn test_the_worker_pushes_the_mse_gradient_of_the_batch() {
+        let x = vec![0., 1., 2., 3.];
+        let y = vec![1., 2., 3., 4.];
+        let size = NonZeroUsize::new(1).unwrap();
+
+        let trainer = BackpropTrainer::new(
+            Sequential::new(vec![Layer::dense((1, 1))]),
+            vec![GradientDescent::new(FloatPositive::new(0.1).unwrap())],
+            Dataset::loaded(DataSrc::inmem(x.clone(), y.clone()), size, size),
+            Mse::new(),
+            0,
+            StopCondition::MaxEpochs(NonZeroUsize::MIN),
+            NonZeroUsize::new(4).unwrap(),
+            StdRng::seed_from_u64(42),
+        );
+
+        let (w, b) = (0.5, 0.5);
+        let (server, server_handle) = MockParameterServer::duplex(vec![vec![w, b]]);
+        let mut cluster_manager = ServerClusterManager::new(vec![0]);
+        cluster_manager.spawn(server_handle, 2);
+
+        let (worker_end, orch_end) = tokio::io::duplex(4096);
+        let (rx, tx) = tokio::io::split(worker_end);
+        let mut orch_handle = OrchHandle::new(Uuid::nil(), Stp::new(rx, tx));
+        let (rx, tx) = tokio::io::split(orch_end);
+        let mut orch_worker_handle = WorkerHandle::new(Uuid::new_v4(), Stp::new(rx, tx));
+
+        let orch = async {
+            while !matches!(
+                orch_worker_handle.recv_event().await?,
+                WorkerEvent::Disconnect
+            ) {}
+            io::Result::Ok(())
+        };
+
+        let mut worker =
+            ParamServerWorker::new(Box::new(trainer), cluster_manager, &mut orch_handle);
+        let (run, received, orch) = tokio::join!(worker.run(), server.run(), orch);
+        run.unwrap();
+        orch.unwrap();
+
+        // The gradient of the mean of (w * x + b - y)² over the batch.
+        let n = x.len() as f32;
+        let residuals: Vec<f32> = x.iter().zip(&y).map(|(x, y)| w * x + b - y).collect();
+        let dw = 2. / n * residuals.iter().zip(&x).map(|(r, x)| r * x).sum::<f32>();
+        let db = 2. / n * residuals.iter().sum::<f32>();
+
+        let received = received.unwrap();
+        assert_eq!(
+            received,
+            [Received::Grad(vec![dw, db]), Received::Disconnect]
+        );
+    }
+}
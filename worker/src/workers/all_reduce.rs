@@ -4,8 +4,8 @@ use comms::{OrchEvent, OrchHandle, TransportLayer, specs::machine_learning::Para
 use log::{debug, info, warn};
 use machine_learning::training::{TrainResult, Trainer};
 
-use super::{Run, Worker};
-use crate::middlewares::WorkerRingManager;
+use super::{RoundTimer, Run, StepCounter, StepStats, Worker, metrics};
+use crate::{compute::ComputePool, metrics::ValidationMetrics, middlewares::WorkerRingManager};
 
 /// The middleman between the workers and the model trainer.
 pub struct AllReduceWorker<'node, T>
@@ -17,6 +17,10 @@ where
     orch_handle: &'node mut OrchHandle<T>,
     optimization_params: Vec<f32>,
     params: Vec<f32>,
+    step_counter: StepCounter,
+    round_timer: RoundTimer,
+    compute_pool: ComputePool,
+    validation: Option<ValidationMetrics>,
 }
 
 impl<'node, T> AllReduceWorker<'node, T>
@@ -45,8 +49,62 @@ where
             orch_handle,
             optimization_params: params.clone(),
             params,
+            step_counter: StepCounter::default(),
+            round_timer: RoundTimer::default(),
+            compute_pool: ComputePool::default(),
+            validation: None,
         }
     }
+
+    /// Sets the pool the training steps are computed on, the one sized to the machine's cores by default.
+    ///
+    /// # Args
+    /// * `compute_pool` - The pool to compute the training steps on.
+    ///
+    /// # Returns
+    /// The modified `AllReduceWorker`.
+    pub fn with_compute_pool(mut self, compute_pool: ComputePool) -> Self {
+        self.compute_pool = compute_pool;
+        self
+    }
+
+    /// Sets the metrics computed over the partition with the final parameters, once the
+    /// training is over.
+    ///
+    /// # Args
+    /// * `validation` - The empty metrics to accumulate, `None` to skip the validation pass.
+    ///
+    /// # Returns
+    /// The modified `AllReduceWorker`.
+    pub fn with_validation(mut self, validation: Option<ValidationMetrics>) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Runs the validation pass over the final parameters and reports it's metrics to the
+    /// orchestrator.
+    ///
+    /// # Args
+    /// * `validation` - The empty metrics to accumulate.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    async fn validate(&mut self, validation: ValidationMetrics) -> io::Result<()> {
+        let mut param_manager = self.ring_manager.build_param_manager(&mut self.params);
+        let trainer = &mut *self.trainer;
+        let validation = self
+            .compute_pool
+            .run(|| metrics::validate(trainer, &mut param_manager, validation))
+            .map_err(io::Error::other)?;
+
+        if let Some(validation) = validation {
+            let epoch = self.trainer.progress().epoch;
+            let report = metrics::validation_metrics(epoch, validation);
+            self.orch_handle.push_metrics(report).await?;
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -62,71 +120,115 @@ where
                 .ring_manager
                 .build_param_manager(&mut self.optimization_params);
 
-            let TrainResult { losses, was_last } = self
-                .trainer
-                .train(&mut param_manager)
+            let trainer = &mut self.trainer;
+            let compute_pool = &self.compute_pool;
+            let TrainResult {
+                losses,
+                was_last,
+                samples,
+            } = self
+                .round_timer
+                .compute(|| compute_pool.run(|| trainer.train(&mut param_manager)))
                 .map_err(io::Error::other)?;
 
-            self.orch_handle.push_losses(losses).await?;
+            let timing = self.round_timer.finish_round();
+            debug!(
+                "round took {:?} computing, {:?} communicating and {:?} waiting",
+                timing.compute, timing.comm, timing.wait
+            );
+
+            let push = self.orch_handle.push_losses(losses, timing);
+            self.round_timer.comm(push).await?;
             should_continue = !was_last;
 
+            // The round's step only finishes once the gradients are exchanged, so it's report is
+            // pushed by whichever branch runs.
+            let losses = losses.to_vec();
+            let epoch = self.trainer.progress().epoch;
+            let report =
+                move |stats| metrics::round_metrics(epoch, losses, samples as u64, timing, stats);
+
             tokio::select! {
                 biased;
-                event = self.orch_handle.recv_event() => match event? {
-                    OrchEvent::Stop => {
-                        info!("received a stop command from orchestrator");
-                        break;
-                    }
-                    OrchEvent::Disconnect => {
-                        info!("received a disconnect command from the orchestrator");
-                        break;
-                    }
-                    OrchEvent::Upgrade { mut spec, ranges } => {
-                        info!("upgrading to parameter server");
-
-                        let params: Vec<_> = ranges
-                            .into_iter()
-                            .flat_map(|(a, b)| &self.params[a..b])
-                            .cloned()
-                            .collect();
-
-                        spec.param_gen = ParamGenSpec::Inline { params };
-                        return Ok(Run::Upgrade { spec })
-                    }
-                    OrchEvent::Switch {
-                        server_addrs,
-                        server_sizes,
-                        server_ordering,
-                        trainer_spec,
-                    } => {
-                        info!("switching algorithm to parameter server as a worker");
-                        return Ok(Run::Switch {
+                event = self.orch_handle.recv_event() => {
+                    let event = event?;
+                    let push = self.orch_handle.push_metrics(report(StepStats::default()));
+                    self.round_timer.comm(push).await?;
+
+                    match event {
+                        OrchEvent::Stop => {
+                            info!("received a stop command from orchestrator");
+                            break;
+                        }
+                        OrchEvent::Disconnect => {
+                            info!("received a disconnect command from the orchestrator");
+                            self.validation = None;
+                            break;
+                        }
+                        OrchEvent::Upgrade { mut spec, ranges } => {
+                            info!("upgrading to parameter server");
+
+                            let params: Vec<_> = ranges
+                                .into_iter()
+                                .flat_map(|(a, b)| &self.params[a..b])
+                                .cloned()
+                                .collect();
+
+                            spec.param_gen = ParamGenSpec::Inline { params };
+                            return Ok(Run::Upgrade { spec })
+                        }
+                        OrchEvent::Switch {
                             server_addrs,
                             server_sizes,
                             server_ordering,
                             trainer_spec,
-                        });
+                        } => {
+                            info!("switching algorithm to parameter server as a worker");
+                            return Ok(Run::Switch {
+                                server_addrs,
+                                server_sizes,
+                                server_ordering,
+                                trainer_spec,
+                            });
+                        }
+                        other => {
+                            warn!("unexpected message from orchestrator, got: {other:?}");
+                        }
                     }
-                    other => {
-                        warn!("unexpected message from orchestrator, got: {other:?}");
-                    }
-                },
-                response = self.ring_manager.pull_grads(&mut self.params) => {
+                }
+                response = self.round_timer.comm(self.ring_manager.pull_grads(&mut self.params)) => {
                     debug!("received gradients from all workers, training...");
 
                     let Ok(mut param_manager) = response else {
+                        let push = self.orch_handle.push_metrics(report(StepStats::default()));
+                        self.round_timer.comm(push).await?;
                         continue;
                     };
 
-                    // SAFETY: The parameter and gradient buffer have the same size.
-                    self.trainer.optimize(&mut param_manager).unwrap();
+                    let trainer = &mut self.trainer;
+                    let compute_pool = &self.compute_pool;
+                    self.round_timer
+                        .compute(|| compute_pool.run(|| trainer.optimize(&mut param_manager)))
+                        .map_err(io::Error::other)?;
                     param_manager.zero_grad();
 
                     self.optimization_params.copy_from_slice(&self.params);
+                    self.round_timer.reclassify_as_wait(self.ring_manager.take_waited());
+
+                    let (sent, received) = self.ring_manager.bytes_transferred();
+                    let stats = self.step_counter.finish_step(sent, received);
+                    debug!("step {} sent {} bytes and received {} bytes", stats.step(), stats.bytes_sent(), stats.bytes_received());
+
+                    let push = self.orch_handle.push_metrics(report(stats));
+                    self.round_timer.comm(push).await?;
                 }
             }
         }
 
+        if let Some(validation) = self.validation.take() {
+            self.validate(validation).await?;
+        }
+
         self.orch_handle.done().await?;
         debug!("sent done to orchestrator");
 
@@ -0,0 +1,179 @@
+use std::{io, num::NonZeroUsize};
+
+use comms::{OrchEvent, OrchHandle, TransportLayer};
+use log::{debug, info, warn};
+use machine_learning::{Result, datasets::Dataset, param_manager::ParamManager, training::Trainer};
+
+use super::{RoundTimer, Run, StepStats, Worker, metrics};
+use crate::{compute::ComputePool, metrics::ValidationMetrics};
+
+/// A worker that only computes the loss of the given parameters over it's partition.
+///
+/// It runs a single forward only round and never trains, so it never exchanges
+/// gradients with anyone, it only reports the loss and hands the parameters back
+/// unchanged if they're requested.
+pub struct EvaluateWorker<'node, T>
+where
+    T: TransportLayer,
+{
+    trainer: Box<dyn Trainer>,
+    dataset: Dataset,
+    batch_size: NonZeroUsize,
+    orch_handle: &'node mut OrchHandle<T>,
+    params: Vec<f32>,
+    amount_of_layers: usize,
+    compute_pool: ComputePool,
+    validation: Option<ValidationMetrics>,
+}
+
+impl<'node, T> EvaluateWorker<'node, T>
+where
+    T: TransportLayer,
+{
+    /// Creates a new `EvaluateWorker`.
+    ///
+    /// # Args
+    /// * `trainer` - The trainer whose model and loss function the parameters are evaluated with.
+    /// * `dataset` - The worker's partition of the dataset.
+    /// * `batch_size` - The maximum amount of rows run through the model at once.
+    /// * `orch_handle` - The handle for communicating with the orchestrator.
+    /// * `params` - The parameters of the model to evaluate.
+    /// * `amount_of_layers` - The amount of layers in the model.
+    ///
+    /// # Returns
+    /// A new `EvaluateWorker` instance.
+    pub fn new(
+        trainer: Box<dyn Trainer>,
+        dataset: Dataset,
+        batch_size: NonZeroUsize,
+        orch_handle: &'node mut OrchHandle<T>,
+        params: Vec<f32>,
+        amount_of_layers: usize,
+    ) -> Self {
+        Self {
+            trainer,
+            dataset,
+            batch_size,
+            orch_handle,
+            params,
+            amount_of_layers,
+            compute_pool: ComputePool::default(),
+            validation: None,
+        }
+    }
+
+    /// Sets the pool the forward passes are computed on, the one sized to the machine's cores by default.
+    ///
+    /// # Args
+    /// * `compute_pool` - The pool to compute the forward passes on.
+    ///
+    /// # Returns
+    /// The modified `EvaluateWorker`.
+    pub fn with_compute_pool(mut self, compute_pool: ComputePool) -> Self {
+        self.compute_pool = compute_pool;
+        self
+    }
+
+    /// Sets the metrics computed over the partition along with the loss.
+    ///
+    /// # Args
+    /// * `validation` - The empty metrics to accumulate, `None` to only compute the loss.
+    ///
+    /// # Returns
+    /// The modified `EvaluateWorker`.
+    pub fn with_validation(mut self, validation: Option<ValidationMetrics>) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Computes the mean loss of the parameters over every row of the partition, along with
+    /// the validation metrics if any were set.
+    ///
+    /// # Returns
+    /// The mean loss, `None` if the partition is empty, and the accumulated metrics, `None`
+    /// if unset or the model can't be run as a whole, or an error if the forward pass failed.
+    fn evaluate(&mut self) -> Result<(Option<f64>, Option<ValidationMetrics>)> {
+        let rows = self.dataset.rows();
+        if rows == 0 {
+            return Ok((None, self.validation.take()));
+        }
+
+        let mut grad = vec![0.0; self.params.len()];
+        let mut residual = vec![0.0; self.params.len()];
+        let mut param_manager = ParamManager::for_all_reduce(
+            &mut self.params,
+            &mut grad,
+            &mut residual,
+            self.amount_of_layers,
+        );
+
+        let mut loss_sum = 0.0;
+        for (x, y) in self.dataset.batches(self.batch_size) {
+            let nrows = x.nrows();
+            loss_sum += self.trainer.evaluate(&mut param_manager, x, y)? * nrows as f64;
+        }
+
+        let mut validation = self.validation.take();
+        if let Some(ref mut metrics) = validation {
+            let mut batches = self.dataset.batches(self.batch_size);
+            let ran =
+                self.trainer
+                    .predict(&mut param_manager, &mut batches, &mut |y_pred, y| {
+                        metrics.update(y_pred, y)
+                    })?;
+
+            validation = validation.filter(|_| ran);
+        }
+
+        Ok((Some(loss_sum / rows as f64), validation))
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Worker for EvaluateWorker<'_, T>
+where
+    T: TransportLayer,
+{
+    async fn run(&mut self) -> io::Result<Run> {
+        let mut round_timer = RoundTimer::default();
+        let compute_pool = self.compute_pool.clone();
+        let (loss, validation) = round_timer
+            .compute(|| compute_pool.run(|| self.evaluate()))
+            .map_err(io::Error::other)?;
+
+        let losses: Vec<_> = loss.into_iter().collect();
+        let timing = round_timer.finish_round();
+        info!("evaluated {} rows, loss: {losses:?}", self.dataset.rows());
+
+        self.orch_handle.push_losses(&losses, timing).await?;
+
+        let samples = self.dataset.rows() as u64;
+        let mut report = metrics::round_metrics(0, losses, samples, timing, StepStats::default());
+        if let Some(validation) = validation {
+            report = validation.attach(report);
+        }
+
+        self.orch_handle.push_metrics(report).await?;
+
+        self.orch_handle.done().await?;
+        debug!("sent done to orchestrator");
+
+        loop {
+            let event = self.orch_handle.recv_event().await?;
+            debug!("received {event:?} from orchestrator");
+
+            match event {
+                OrchEvent::Disconnect => break,
+                OrchEvent::RequestParams => self.orch_handle.push_params(&mut self.params).await?,
+                OrchEvent::Stop => {}
+                other => warn!("unexpected message from orchestrator, got: {other:?}"),
+            }
+        }
+
+        Ok(Run::Done)
+    }
+
+    fn into_trainer(self: Box<Self>) -> Box<dyn Trainer> {
+        self.trainer
+    }
+}
@@ -0,0 +1,111 @@
+use comms::specs::worker::{MetricsReport, TimeBreakdown};
+use machine_learning::{Result, param_manager::ParamManager, training::Trainer};
+
+use super::StepStats;
+use crate::metrics::ValidationMetrics;
+
+/// Builds the metrics of a finished training round.
+///
+/// # Args
+/// * `epoch` - The amount of epochs the worker finished so far.
+/// * `losses` - The losses of the epochs run in the round, one per epoch.
+/// * `samples` - The amount of samples the worker trained on during the round.
+/// * `timing` - How the worker spent the round.
+/// * `stats` - The stats of the round's step, a default one if the step wasn't finished.
+///
+/// # Returns
+/// The round's metrics, with a throughput of zero if the round took no measurable time.
+pub fn round_metrics(
+    epoch: usize,
+    losses: Vec<f64>,
+    samples: u64,
+    timing: TimeBreakdown,
+    stats: StepStats,
+) -> MetricsReport {
+    let secs = timing.total().as_secs_f64();
+    let throughput = if secs > 0.0 {
+        samples as f64 / secs
+    } else {
+        0.0
+    };
+
+    MetricsReport::new(epoch, losses, throughput, timing)
+        .with_samples(samples)
+        .with_bytes(stats.bytes_sent(), stats.bytes_received())
+}
+
+/// Runs the final model over the trainer's partition, accumulating it's validation metrics.
+///
+/// # Args
+/// * `trainer` - The trainer whose dataset and model are run.
+/// * `param_manager` - The manager of the final parameters.
+/// * `validation` - The empty metrics to accumulate.
+///
+/// # Returns
+/// The accumulated metrics, `None` if the model can't be run as a whole, or an error
+/// if the forward pass failed.
+pub fn validate(
+    trainer: &mut dyn Trainer,
+    param_manager: &mut ParamManager<'_>,
+    mut validation: ValidationMetrics,
+) -> Result<Option<ValidationMetrics>> {
+    let ran =
+        trainer.predict_dataset(param_manager, &mut |y_pred, y| validation.update(y_pred, y))?;
+
+    Ok(ran.then_some(validation))
+}
+
+/// Builds the report of the validation pass that follows the last round.
+///
+/// It carries no losses nor samples, so it doesn't count as a training round.
+///
+/// # Args
+/// * `epoch` - The amount of epochs the worker finished.
+/// * `validation` - The accumulated validation metrics.
+///
+/// # Returns
+/// The validation's report.
+pub fn validation_metrics(epoch: usize, validation: ValidationMetrics) -> MetricsReport {
+    validation.attach(MetricsReport::new(
+        epoch,
+        Vec::new(),
+        0.0,
+        TimeBreakdown::default(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_throughput_counts_every_sample_of_the_round() {
+        let timing = TimeBreakdown {
+            compute: Duration::from_millis(1500),
+            comm: Duration::from_millis(300),
+            wait: Duration::from_millis(200),
+        };
+
+        let stats = StepStats::new(3)
+            .with_bytes_sent(512)
+            .with_bytes_received(768);
+
+        let report = round_metrics(4, vec![0.5, 0.25], 200, timing, stats);
+        assert_eq!(report.epoch, 4);
+        assert_eq!(report.throughput, 100.0);
+        assert_eq!(report.samples, 200);
+        assert_eq!(report.timing, timing);
+        assert_eq!((report.bytes_sent, report.bytes_received), (512, 768));
+
+        let report = round_metrics(
+            1,
+            vec![0.5],
+            100,
+            TimeBreakdown::default(),
+            StepStats::default(),
+        );
+        assert_eq!(report.throughput, 0.0);
+    }
+}
@@ -1,7 +1,14 @@
 pub mod all_reduce;
+pub mod evaluate;
+mod metrics;
 pub mod parameter_server;
+mod round_timer;
+mod step_stats;
 mod worker;
 
 pub use all_reduce::AllReduceWorker;
+pub use evaluate::EvaluateWorker;
 pub use parameter_server::ParamServerWorker;
+pub use round_timer::RoundTimer;
+pub use step_stats::{StepCounter, StepStats};
 pub use worker::{Run, Worker};
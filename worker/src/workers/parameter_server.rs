@@ -4,8 +4,8 @@ use comms::{OrchEvent, OrchHandle, TransportLayer};
 use log::{debug, info, warn};
 use machine_learning::training::{TrainResult, Trainer};
 
-use super::{Run, Worker};
-use crate::middlewares::ServerClusterManager;
+use super::{RoundTimer, Run, StepCounter, Worker, metrics};
+use crate::{compute::ComputePool, metrics::ValidationMetrics, middlewares::ServerClusterManager};
 
 /// The middleman between the parameter server and the model trainer.
 pub struct ParamServerWorker<'node, T>
@@ -15,6 +15,10 @@ where
     trainer: Box<dyn Trainer>,
     cluster_manager: ServerClusterManager<T>,
     orch_handle: &'node mut OrchHandle<T>,
+    step_counter: StepCounter,
+    round_timer: RoundTimer,
+    compute_pool: ComputePool,
+    validation: Option<ValidationMetrics>,
 }
 
 impl<'node, T> ParamServerWorker<'node, T>
@@ -39,8 +43,62 @@ where
             trainer,
             cluster_manager,
             orch_handle,
+            step_counter: StepCounter::default(),
+            round_timer: RoundTimer::default(),
+            compute_pool: ComputePool::default(),
+            validation: None,
         }
     }
+
+    /// Sets the pool the training steps are computed on, the one sized to the machine's cores by default.
+    ///
+    /// # Args
+    /// * `compute_pool` - The pool to compute the training steps on.
+    ///
+    /// # Returns
+    /// The modified `ParamServerWorker`.
+    pub fn with_compute_pool(mut self, compute_pool: ComputePool) -> Self {
+        self.compute_pool = compute_pool;
+        self
+    }
+
+    /// Sets the metrics computed over the partition with the final parameters, once the
+    /// training is over.
+    ///
+    /// # Args
+    /// * `validation` - The empty metrics to accumulate, `None` to skip the validation pass.
+    ///
+    /// # Returns
+    /// The modified `ParamServerWorker`.
+    pub fn with_validation(mut self, validation: Option<ValidationMetrics>) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Runs the validation pass over the parameters the servers sent for the last gradient
+    /// and reports it's metrics to the orchestrator.
+    ///
+    /// # Args
+    /// * `validation` - The empty metrics to accumulate.
+    ///
+    /// # Returns
+    /// An io error if occurred.
+    async fn validate(&mut self, validation: ValidationMetrics) -> io::Result<()> {
+        let mut param_manager = self.cluster_manager.pull_params().await?;
+        let trainer = &mut *self.trainer;
+        let validation = self
+            .compute_pool
+            .run(|| metrics::validate(trainer, &mut param_manager, validation))
+            .map_err(io::Error::other)?;
+
+        if let Some(validation) = validation {
+            let epoch = self.trainer.progress().epoch;
+            let report = metrics::validation_metrics(epoch, validation);
+            self.orch_handle.push_metrics(report).await?;
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -66,25 +124,53 @@ where
                     }
                     OrchEvent::Disconnect => {
                         info!("received a disconnect command from the orchestrator");
+                        self.validation = None;
                         break;
                     }
                     other => {
                         warn!("unexpected message from orchestrator, got: {other:?}");
                     }
                 },
-                response = self.cluster_manager.pull_params() => {
+                response = self.round_timer.wait(self.cluster_manager.pull_params()) => {
                     debug!("received parameters from all servers, training...");
 
                     let mut param_manager = response?;
-                    let TrainResult { losses, was_last } = self.trainer.train(&mut param_manager).unwrap();
-                    self.cluster_manager.push_grads().await?;
+                    let trainer = &mut self.trainer;
+                    let compute_pool = &self.compute_pool;
+                    let TrainResult {
+                        losses,
+                        was_last,
+                        samples,
+                    } = self
+                        .round_timer
+                        .compute(|| compute_pool.run(|| trainer.train(&mut param_manager)))
+                        .map_err(io::Error::other)?;
+                    self.round_timer.comm(self.cluster_manager.push_grads()).await?;
+
+                    let (sent, received) = self.cluster_manager.bytes_transferred();
+                    let stats = self.step_counter.finish_step(sent, received);
+                    debug!("step {} sent {} bytes and received {} bytes", stats.step(), stats.bytes_sent(), stats.bytes_received());
+                    debug!("step {} server rtts: {:?}", stats.step(), self.cluster_manager.rtts());
+
+                    let timing = self.round_timer.finish_round();
+                    debug!("round took {:?} computing, {:?} communicating and {:?} waiting", timing.compute, timing.comm, timing.wait);
 
-                    self.orch_handle.push_losses(losses).await?;
+                    let push = self.orch_handle.push_losses(losses, timing);
+                    self.round_timer.comm(push).await?;
                     should_continue = !was_last;
+
+                    let losses = losses.to_vec();
+                    let epoch = self.trainer.progress().epoch;
+                    let report = metrics::round_metrics(epoch, losses, samples as u64, timing, stats);
+                    self.round_timer.comm(self.orch_handle.push_metrics(report)).await?;
                 }
             }
         }
 
+        if let Some(validation) = self.validation.take() {
+            self.validate(validation).await?;
+        }
+
         self.orch_handle.done().await?;
         self.cluster_manager.disconnect().await?;
         self.orch_handle.disconnect().await?;
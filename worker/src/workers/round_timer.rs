@@ -0,0 +1,116 @@
+use std::{
+    mem,
+    time::{Duration, Instant},
+};
+
+use comms::specs::worker::TimeBreakdown;
+
+/// Splits the wall time of a worker's training rounds into compute, communication and wait.
+///
+/// Every section is timed as a whole and the worker runs them one after the other, so
+/// no stretch of time is counted twice. A receive can't tell the transfer apart from the
+/// wait for the sender to be ready, so the time blocked receiving is counted as wait and
+/// only the sends as communication.
+#[derive(Debug, Default)]
+pub struct RoundTimer {
+    breakdown: TimeBreakdown,
+}
+
+impl RoundTimer {
+    /// Times a compute bound section.
+    ///
+    /// # Args
+    /// * `f` - The computation.
+    ///
+    /// # Returns
+    /// The computation's result.
+    pub fn compute<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let start = Instant::now();
+        let res = f();
+        self.breakdown.compute += start.elapsed();
+        res
+    }
+
+    /// Times a communication section.
+    ///
+    /// # Args
+    /// * `fut` - The communication to await.
+    ///
+    /// # Returns
+    /// The communication's result.
+    pub async fn comm<F>(&mut self, fut: F) -> F::Output
+    where
+        F: Future,
+    {
+        let start = Instant::now();
+        let res = fut.await;
+        self.breakdown.comm += start.elapsed();
+        res
+    }
+
+    /// Times a section blocked on the peers, nothing is recorded if the future is dropped.
+    ///
+    /// # Args
+    /// * `fut` - The future to await.
+    ///
+    /// # Returns
+    /// The future's result.
+    pub async fn wait<F>(&mut self, fut: F) -> F::Output
+    where
+        F: Future,
+    {
+        let start = Instant::now();
+        let res = fut.await;
+        self.breakdown.wait += start.elapsed();
+        res
+    }
+
+    /// Moves time already counted as communication over to the wait.
+    ///
+    /// # Args
+    /// * `waited` - The time the communication was blocked on the peers.
+    pub fn reclassify_as_wait(&mut self, waited: Duration) {
+        let waited = waited.min(self.breakdown.comm);
+        self.breakdown.comm -= waited;
+        self.breakdown.wait += waited;
+    }
+
+    /// Closes the current round.
+    ///
+    /// # Returns
+    /// The breakdown of the time since the previous round was closed.
+    pub fn finish_round(&mut self) -> TimeBreakdown {
+        mem::take(&mut self.breakdown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sections_are_counted_once_and_reset_every_round() {
+        let mut timer = RoundTimer::default();
+        let nap = Duration::from_millis(20);
+
+        let start = Instant::now();
+        timer.compute(|| thread::sleep(nap));
+        timer.comm(tokio::time::sleep(2 * nap)).await;
+        timer.reclassify_as_wait(nap);
+        timer.wait(tokio::time::sleep(nap)).await;
+        let elapsed = start.elapsed();
+
+        let breakdown = timer.finish_round();
+        assert!(breakdown.compute >= nap);
+        assert!(breakdown.comm >= nap);
+        assert!(breakdown.wait >= 2 * nap);
+        assert!(breakdown.total() <= elapsed);
+
+        assert_eq!(timer.finish_round(), TimeBreakdown::default());
+    }
+}
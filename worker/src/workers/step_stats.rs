@@ -0,0 +1,100 @@
+/// The statistics of a single training step of a worker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StepStats {
+    step: usize,
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+impl StepStats {
+    /// Creates a new `StepStats`.
+    ///
+    /// # Args
+    /// * `step` - The number of the step, starting from 0.
+    ///
+    /// # Returns
+    /// A new `StepStats` instance without any communication volume.
+    pub fn new(step: usize) -> Self {
+        Self {
+            step,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the amount of bytes sent during the step.
+    ///
+    /// # Args
+    /// * `bytes_sent` - The amount of bytes sent to the peers.
+    ///
+    /// # Returns
+    /// The updated `StepStats`.
+    pub fn with_bytes_sent(mut self, bytes_sent: u64) -> Self {
+        self.bytes_sent = bytes_sent;
+        self
+    }
+
+    /// Sets the amount of bytes received during the step.
+    ///
+    /// # Args
+    /// * `bytes_received` - The amount of bytes received from the peers.
+    ///
+    /// # Returns
+    /// The updated `StepStats`.
+    pub fn with_bytes_received(mut self, bytes_received: u64) -> Self {
+        self.bytes_received = bytes_received;
+        self
+    }
+
+    /// The number of the step.
+    ///
+    /// # Returns
+    /// The number of the step, starting from 0.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// The amount of bytes sent during the step.
+    ///
+    /// # Returns
+    /// The amount of bytes sent to the peers.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// The amount of bytes received during the step.
+    ///
+    /// # Returns
+    /// The amount of bytes received from the peers.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+}
+
+/// Rolls the ever increasing byte counters of the transport up into per step stats.
+#[derive(Debug, Default)]
+pub struct StepCounter {
+    step: usize,
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+impl StepCounter {
+    /// Closes the current step.
+    ///
+    /// # Args
+    /// * `bytes_sent` - The total amount of bytes sent so far.
+    /// * `bytes_received` - The total amount of bytes received so far.
+    ///
+    /// # Returns
+    /// The stats of the closed step.
+    pub fn finish_step(&mut self, bytes_sent: u64, bytes_received: u64) -> StepStats {
+        let stats = StepStats::new(self.step)
+            .with_bytes_sent(bytes_sent.saturating_sub(self.bytes_sent))
+            .with_bytes_received(bytes_received.saturating_sub(self.bytes_received));
+
+        self.step += 1;
+        self.bytes_sent = bytes_sent;
+        self.bytes_received = bytes_received;
+        stats
+    }
+}
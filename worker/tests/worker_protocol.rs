@@ -11,6 +11,7 @@ use tokio::io::{self, AsyncRead, AsyncWrite, DuplexStream, ReadHalf, WriteHalf};
 
 use comms::{
     OrchEvent, OrchHandle, ParamServerHandle, Stp, WorkerEvent, WorkerHandle, floats::FloatPositive,
+    specs::machine_learning::StopCondition,
 };
 use uuid::Uuid;
 use worker::{
@@ -39,11 +40,12 @@ where
     loop {
         match worker_handle.recv_event().await? {
             WorkerEvent::Disconnect => break,
-            WorkerEvent::Loss(losses) => println!("loss: {losses:?}"),
+            WorkerEvent::Loss(losses, _) => println!("loss: {losses:?}"),
             _ => {}
         }
     }
 
+    server_handle.req_params().await?;
     let params = server_handle.pull_params().await?.to_vec();
     server_handle.disconnect().await?;
     Ok(params)
@@ -61,12 +63,14 @@ where
 {
     let mut optimizer = GradientDescent::new(learning_rate);
     let mut params = vec![0.5; nparams];
+    worker_handle.push_params(&mut params).await?;
 
     loop {
         match worker_handle.recv_event().await? {
             WorkerEvent::Disconnect => break,
             WorkerEvent::Grad(grad) => {
                 optimizer.update_params(grad, &mut params).unwrap();
+                worker_handle.push_params(&mut params).await?;
             }
             WorkerEvent::RequestParams => {
                 worker_handle.push_params(&mut params).await?;
@@ -88,10 +92,7 @@ where
     Ok(())
 }
 
-#[tokio::test(flavor = "multi_thread")]
-async fn test_local_lineal_model_convergence() -> io::Result<()> {
-    unsafe { env::set_var("RUST_BACKTRACE", "full") };
-
+async fn run_local_lineal_model(quantize_params: bool) -> io::Result<Vec<f32>> {
     let ((sv_rx, sv_tx), (wk_rx, wk_tx)) = channel_pair();
     let ((wk_orch_rx, wk_orch_tx), (orch_wk_rx, orch_wk_tx)) = channel_pair();
     let ((sv_orch_rx, sv_orch_tx), (orch_sv_rx, orch_sv_tx)) = channel_pair();
@@ -111,7 +112,7 @@ async fn test_local_lineal_model_convergence() -> io::Result<()> {
         ),
         Mse::new(),
         0,
-        NonZeroUsize::new(MAX_EPOCHS).unwrap(),
+        StopCondition::MaxEpochs(NonZeroUsize::new(MAX_EPOCHS).unwrap()),
         NonZeroUsize::new(4).unwrap(),
         StdRng::from_os_rng(),
     );
@@ -132,7 +133,11 @@ async fn test_local_lineal_model_convergence() -> io::Result<()> {
 
     // Server node.
     let transport = Stp::new(wk_rx, wk_tx);
-    let worker_sv_handle = WorkerHandle::new(worker_id, transport);
+    let mut worker_sv_handle = WorkerHandle::new(worker_id, transport);
+    if quantize_params {
+        worker_sv_handle.enable_params_quantization(NonZeroUsize::new(1).unwrap());
+    }
+
     let transport = Stp::new(sv_orch_rx, sv_orch_tx);
     let orch_sv_handle = OrchHandle::new(orch_id, transport);
 
@@ -157,8 +162,27 @@ async fn test_local_lineal_model_convergence() -> io::Result<()> {
     println!("server: {sv:?}");
     println!("orchestrator: {orch:?}");
 
-    let params = orch.unwrap();
+    orch
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_local_lineal_model_convergence() -> io::Result<()> {
+    unsafe { env::set_var("RUST_BACKTRACE", "full") };
+
+    let params = run_local_lineal_model(false).await?;
+    println!("params: {params:?}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_local_lineal_model_convergence_with_quantized_params() -> io::Result<()> {
+    let params = run_local_lineal_model(true).await?;
     println!("params: {params:?}");
 
+    for p in params {
+        assert!((p - 1.0).abs() < 0.1, "{p} didn't converge to 1.0");
+    }
+
     Ok(())
 }